@@ -0,0 +1,443 @@
+//! Accounts money is kept in, and transfers between them.
+//!
+//! Expenses and income entries can name the account they were paid from or
+//! into. Moving money between two accounts, paying off a credit card from
+//! checking say, is a transfer: it changes both balances but is no expense,
+//! so it never shows up as spending. An account's balance is its opening
+//! balance plus everything dated from its opening date on; it is cached on
+//! the row and refreshed by every write that touches the account, with
+//! `recompute` to catch up on anything that arrived another way.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::db::{new_id, now};
+use crate::error::{Error, Result};
+use crate::expenses::{self, parse_date};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountType {
+    Cash,
+    Checking,
+    Savings,
+    CreditCard,
+}
+
+impl AccountType {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Cash => "cash",
+            Self::Checking => "checking",
+            Self::Savings => "savings",
+            Self::CreditCard => "credit_card",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct Account {
+    pub id: String,
+    pub user_id: Option<String>,
+    pub name: String,
+    /// cash, checking, savings or credit_card.
+    pub kind: String,
+    pub opening_balance: f64,
+    /// "YYYY-MM-DD"; what happened before is in the opening balance.
+    pub opening_date: String,
+    /// Negative for a credit card with money owed on it.
+    pub balance: f64,
+    pub balance_updated_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub deleted_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct NewAccount {
+    pub name: String,
+    pub kind: AccountType,
+    /// Defaults to 0.
+    pub opening_balance: Option<f64>,
+    /// Defaults to today.
+    pub opening_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct Transfer {
+    pub id: String,
+    pub user_id: Option<String>,
+    pub from_account_id: String,
+    pub to_account_id: String,
+    pub amount: f64,
+    /// "YYYY-MM-DD".
+    pub date: String,
+    pub note: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub deleted_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct NewTransfer {
+    pub from_account_id: String,
+    pub to_account_id: String,
+    pub amount: f64,
+    /// Defaults to today.
+    pub date: Option<NaiveDate>,
+    pub note: Option<String>,
+}
+
+/// One line of an account's statement.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct AccountEntry {
+    /// expense, income, transfer_in or transfer_out.
+    pub kind: String,
+    /// Id of the expense, income entry or transfer.
+    pub id: String,
+    /// "YYYY-MM-DD".
+    pub date: String,
+    /// Positive when money came in.
+    pub amount: f64,
+    pub note: Option<String>,
+}
+
+fn date_str(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+/// Fail unless `id` is an account that hasn't been deleted.
+pub(crate) async fn check_account(conn: &mut SqliteConnection, id: &str) -> Result<()> {
+    let found: Option<(String,)> =
+        sqlx::query_as("SELECT id FROM accounts WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&mut *conn)
+            .await?;
+    if found.is_none() {
+        return Err(Error::Validation("Unknown account".to_string()));
+    }
+    Ok(())
+}
+
+pub async fn get(pool: &SqlitePool, id: &str) -> Result<Account> {
+    sqlx::query_as::<_, Account>("SELECT * FROM accounts WHERE id = $1 AND deleted_at IS NULL")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| Error::Validation("Unknown account".to_string()))
+}
+
+pub async fn list(pool: &SqlitePool) -> Result<Vec<Account>> {
+    Ok(sqlx::query_as::<_, Account>(
+        "SELECT * FROM accounts WHERE deleted_at IS NULL ORDER BY name COLLATE NOCASE",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+fn check_input(input: &NewAccount) -> Result<&str> {
+    let name = input.name.trim();
+    if name.is_empty() {
+        return Err(Error::Validation("Name is required".to_string()));
+    }
+    if !input.opening_balance.unwrap_or(0.0).is_finite() {
+        return Err(Error::Validation(
+            "Opening balance must be a number".to_string(),
+        ));
+    }
+    Ok(name)
+}
+
+pub async fn create(
+    pool: &SqlitePool,
+    user_id: Option<&str>,
+    input: NewAccount,
+    today: NaiveDate,
+) -> Result<Account> {
+    let name = check_input(&input)?;
+    let id = new_id();
+    let now = now();
+    sqlx::query(
+        "INSERT INTO accounts
+            (id, user_id, name, kind, opening_balance, opening_date, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $7)",
+    )
+    .bind(&id)
+    .bind(user_id)
+    .bind(name)
+    .bind(input.kind.as_str())
+    .bind(input.opening_balance.unwrap_or(0.0))
+    .bind(date_str(input.opening_date.unwrap_or(today)))
+    .bind(&now)
+    .execute(pool)
+    .await?;
+    refresh(pool, &[Some(id.clone())]).await?;
+    get(pool, &id).await
+}
+
+/// Rename an account, change its kind or move its opening balance; an
+/// opening balance or date not given stays as it was. The balance follows.
+pub async fn update(pool: &SqlitePool, id: &str, input: NewAccount) -> Result<Account> {
+    let name = check_input(&input)?;
+    let current = get(pool, id).await?;
+    let opening_date = input.opening_date.map_or(current.opening_date, date_str);
+    sqlx::query(
+        "UPDATE accounts
+         SET name = $1, kind = $2, opening_balance = $3, opening_date = $4, updated_at = $5
+         WHERE id = $6",
+    )
+    .bind(name)
+    .bind(input.kind.as_str())
+    .bind(input.opening_balance.unwrap_or(current.opening_balance))
+    .bind(opening_date)
+    .bind(now())
+    .bind(id)
+    .execute(pool)
+    .await?;
+    refresh(pool, &[Some(id.to_string())]).await?;
+    get(pool, id).await
+}
+
+/// Delete an account. Expenses and transfers keep pointing at it, so their
+/// history reads the same, but nothing new can be booked to it.
+pub async fn delete(pool: &SqlitePool, id: &str) -> Result<()> {
+    let now = now();
+    let deleted = sqlx::query(
+        "UPDATE accounts SET deleted_at = $1, updated_at = $1 WHERE id = $2 AND deleted_at IS NULL",
+    )
+    .bind(&now)
+    .bind(id)
+    .execute(pool)
+    .await?
+    .rows_affected();
+    if deleted == 0 {
+        return Err(Error::Validation("Unknown account".to_string()));
+    }
+    Ok(())
+}
+
+/// Make the balance read `actual` today by moving the opening balance,
+/// for when the bank says otherwise and the difference can't be found.
+pub async fn reconcile(pool: &SqlitePool, id: &str, actual: f64) -> Result<Account> {
+    if !actual.is_finite() {
+        return Err(Error::Validation("Balance must be a number".to_string()));
+    }
+    refresh(pool, &[Some(id.to_string())]).await?;
+    let account = get(pool, id).await?;
+    sqlx::query("UPDATE accounts SET opening_balance = $1, updated_at = $2 WHERE id = $3")
+        .bind(account.opening_balance + actual - account.balance)
+        .bind(now())
+        .bind(id)
+        .execute(pool)
+        .await?;
+    refresh(pool, &[Some(id.to_string())]).await?;
+    get(pool, id).await
+}
+
+const RECOMPUTE: &str = "UPDATE accounts SET
+       balance = opening_balance
+         - COALESCE((SELECT SUM(amount) FROM expenses e
+                     WHERE e.account_id = accounts.id AND e.deleted_at IS NULL
+                       AND substr(e.date, 1, 10) >= accounts.opening_date), 0.0)
+         + COALESCE((SELECT SUM(amount) FROM income_entries i
+                     WHERE i.account_id = accounts.id AND i.deleted_at IS NULL
+                       AND i.date >= accounts.opening_date), 0.0)
+         + COALESCE((SELECT SUM(amount) FROM account_transfers t
+                     WHERE t.to_account_id = accounts.id AND t.deleted_at IS NULL
+                       AND t.date >= accounts.opening_date), 0.0)
+         - COALESCE((SELECT SUM(amount) FROM account_transfers t
+                     WHERE t.from_account_id = accounts.id AND t.deleted_at IS NULL
+                       AND t.date >= accounts.opening_date), 0.0),
+       balance_updated_at = $1
+     WHERE deleted_at IS NULL AND ($2 IS NULL OR id = $2)";
+
+/// Recompute the balances of the accounts among `ids`.
+pub(crate) async fn refresh(pool: &SqlitePool, ids: &[Option<String>]) -> Result<()> {
+    let now = now();
+    let mut done: Vec<&str> = Vec::new();
+    for id in ids.iter().flatten() {
+        if done.contains(&id.as_str()) {
+            continue;
+        }
+        sqlx::query(RECOMPUTE)
+            .bind(&now)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        done.push(id);
+    }
+    Ok(())
+}
+
+/// Recompute every balance from scratch.
+pub async fn recompute(pool: &SqlitePool) -> Result<Vec<Account>> {
+    sqlx::query(RECOMPUTE)
+        .bind(now())
+        .bind(None::<String>)
+        .execute(pool)
+        .await?;
+    list(pool).await
+}
+
+/// Transfers dated `start..=end`, newest first; only those in or out of
+/// `account_id` if given.
+pub async fn transfers(
+    pool: &SqlitePool,
+    account_id: Option<&str>,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<Transfer>> {
+    Ok(sqlx::query_as::<_, Transfer>(
+        "SELECT * FROM account_transfers
+         WHERE deleted_at IS NULL AND date BETWEEN $1 AND $2
+           AND ($3 IS NULL OR from_account_id = $3 OR to_account_id = $3)
+         ORDER BY date DESC, created_at DESC",
+    )
+    .bind(date_str(start))
+    .bind(date_str(end))
+    .bind(account_id)
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn create_transfer(
+    pool: &SqlitePool,
+    user_id: Option<&str>,
+    input: NewTransfer,
+    today: NaiveDate,
+) -> Result<Transfer> {
+    if !input.amount.is_finite() || input.amount <= 0.0 {
+        return Err(Error::Validation(
+            "Amount must be a positive number".to_string(),
+        ));
+    }
+    if input.from_account_id == input.to_account_id {
+        return Err(Error::Validation(
+            "A transfer needs two different accounts".to_string(),
+        ));
+    }
+    let mut conn = pool.acquire().await?;
+    check_account(&mut conn, &input.from_account_id).await?;
+    check_account(&mut conn, &input.to_account_id).await?;
+
+    let id = new_id();
+    let now = now();
+    let note = input
+        .note
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty());
+    let transfer = sqlx::query_as::<_, Transfer>(
+        "INSERT INTO account_transfers
+            (id, user_id, from_account_id, to_account_id, amount, date, note, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $8)
+         RETURNING *",
+    )
+    .bind(&id)
+    .bind(user_id)
+    .bind(&input.from_account_id)
+    .bind(&input.to_account_id)
+    .bind(input.amount)
+    .bind(date_str(input.date.unwrap_or(today)))
+    .bind(note)
+    .bind(&now)
+    .fetch_one(&mut *conn)
+    .await?;
+    drop(conn);
+
+    refresh(
+        pool,
+        &[Some(input.from_account_id), Some(input.to_account_id)],
+    )
+    .await?;
+    Ok(transfer)
+}
+
+pub async fn delete_transfer(pool: &SqlitePool, id: &str) -> Result<()> {
+    let now = now();
+    let accounts: Option<(String, String)> = sqlx::query_as(
+        "UPDATE account_transfers SET deleted_at = $1, updated_at = $1
+         WHERE id = $2 AND deleted_at IS NULL
+         RETURNING from_account_id, to_account_id",
+    )
+    .bind(&now)
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+    let Some((from, to)) = accounts else {
+        return Err(Error::Validation("Transfer not found".to_string()));
+    };
+    refresh(pool, &[Some(from), Some(to)]).await
+}
+
+/// Turn an expense that was really money moved between accounts, the
+/// credit card payment on a bank statement say, into a transfer from the
+/// expense's account to `to_account_id`. The expense is deleted.
+pub async fn transfer_from_expense(
+    pool: &SqlitePool,
+    user_id: Option<&str>,
+    expense_id: &str,
+    to_account_id: &str,
+) -> Result<Transfer> {
+    let row: Option<(f64, Option<String>, String, Option<String>)> = sqlx::query_as(
+        "SELECT amount, note, date, account_id FROM expenses WHERE id = $1 AND deleted_at IS NULL",
+    )
+    .bind(expense_id)
+    .fetch_optional(pool)
+    .await?;
+    let Some((amount, note, date, account_id)) = row else {
+        return Err(Error::Validation("Expense not found".to_string()));
+    };
+    let from_account_id = account_id.ok_or_else(|| {
+        Error::Validation("Pick the account the expense was paid from first".to_string())
+    })?;
+    let date = parse_date(&date)?;
+    let transfer = create_transfer(
+        pool,
+        user_id,
+        NewTransfer {
+            from_account_id,
+            to_account_id: to_account_id.to_string(),
+            amount,
+            date: Some(date),
+            note,
+        },
+        date,
+    )
+    .await?;
+    expenses::delete(pool, expense_id).await?;
+    Ok(transfer)
+}
+
+/// Everything booked to an account between two days (inclusive), newest
+/// first.
+pub async fn activity(
+    pool: &SqlitePool,
+    id: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<AccountEntry>> {
+    Ok(sqlx::query_as::<_, AccountEntry>(
+        "SELECT * FROM (
+           SELECT 'expense' AS kind, id, substr(date, 1, 10) AS date, -amount AS amount, note,
+                  created_at
+           FROM expenses WHERE account_id = $1 AND deleted_at IS NULL
+           UNION ALL
+           SELECT 'income', id, date, amount, note, created_at
+           FROM income_entries WHERE account_id = $1 AND deleted_at IS NULL
+           UNION ALL
+           SELECT 'transfer_in', id, date, amount, note, created_at
+           FROM account_transfers WHERE to_account_id = $1 AND deleted_at IS NULL
+           UNION ALL
+           SELECT 'transfer_out', id, date, -amount, note, created_at
+           FROM account_transfers WHERE from_account_id = $1 AND deleted_at IS NULL
+         )
+         WHERE date BETWEEN $2 AND $3
+         ORDER BY date DESC, created_at DESC",
+    )
+    .bind(id)
+    .bind(date_str(start))
+    .bind(date_str(end))
+    .fetch_all(pool)
+    .await?)
+}
@@ -0,0 +1,197 @@
+//! Two months side by side.
+//!
+//! When one of the months is still running, both are cut off at today's
+//! day of the month, so "this month so far" is compared with the same days
+//! of the other month rather than all of it. Budget adherence is prorated
+//! the same way.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Datelike, Days, Local, Months, NaiveDate};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use super::spending::NET_AMOUNT;
+use crate::budgets;
+use crate::error::{Error, Result};
+use crate::merchants;
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct MonthComparison {
+    pub month_a: String,
+    pub month_b: String,
+    /// Day of the month both were cut off at; `None` when both are over.
+    pub through_day: Option<u32>,
+    pub total_a: f64,
+    pub total_b: f64,
+    /// Largest change first.
+    pub categories: Vec<CategoryDelta>,
+    /// Merchants in `b` that weren't in `a`.
+    pub new_merchants: Vec<String>,
+    /// Merchants in `a` that are gone in `b`.
+    pub disappeared_merchants: Vec<String>,
+    pub budget: BudgetAdherence,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct CategoryDelta {
+    pub category_id: Option<String>,
+    pub category_name: String,
+    pub amount_a: f64,
+    pub amount_b: f64,
+    pub delta: f64,
+    /// Change relative to `a`; `None` when nothing was spent in `a`.
+    pub percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct BudgetAdherence {
+    pub limit_a: Option<f64>,
+    pub limit_b: Option<f64>,
+    /// Share of the (prorated) limit spent, 1.0 being exactly on budget.
+    pub used_a: Option<f64>,
+    pub used_b: Option<f64>,
+    /// `used_b - used_a`; negative means `b` stuck to its budget better.
+    pub difference: Option<f64>,
+}
+
+fn parse_month(month: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d")
+        .map_err(|_| Error::Validation(format!("\"{month}\" is not a YYYY-MM month")))
+}
+
+fn days_in_month(first: NaiveDate) -> u32 {
+    (first + Months::new(1) - Days::new(1)).day()
+}
+
+/// First and last day of `first`'s month, cut off at `through_day`.
+fn range(first: NaiveDate, through_day: Option<u32>) -> (String, String) {
+    let last_day = through_day.map_or(days_in_month(first), |day| day.min(days_in_month(first)));
+    let end = first.with_day(last_day).unwrap_or(first);
+    (
+        first.format("%Y-%m-%d").to_string(),
+        end.format("%Y-%m-%d").to_string(),
+    )
+}
+
+async fn category_totals(
+    pool: &SqlitePool,
+    (start, end): &(String, String),
+) -> Result<Vec<(Option<String>, Option<String>, f64)>> {
+    Ok(sqlx::query_as(&format!(
+        "SELECT e.category_id, c.name, COALESCE(SUM({NET_AMOUNT}), 0.0)
+         FROM expenses e
+         LEFT JOIN categories c ON c.id = e.category_id
+         WHERE e.deleted_at IS NULL AND e.date >= $1 AND e.date <= $2
+         GROUP BY e.category_id"
+    ))
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await?)
+}
+
+async fn merchant_names(
+    pool: &SqlitePool,
+    (start, end): &(String, String),
+) -> Result<HashSet<String>> {
+    Ok(merchants::merchant_stats(pool, start, end)
+        .await?
+        .into_iter()
+        .map(|stats| stats.merchant)
+        .collect())
+}
+
+/// The month's budget limit, prorated to `through_day`.
+async fn prorated_limit(
+    pool: &SqlitePool,
+    first: NaiveDate,
+    through_day: Option<u32>,
+) -> Result<Option<f64>> {
+    let mut conn = pool.acquire().await?;
+    let budget = budgets::for_period(&mut conn, &first.format("%Y-%m").to_string()).await?;
+    let days = days_in_month(first);
+    let share = through_day.map_or(1.0, |day| f64::from(day.min(days)) / f64::from(days));
+    Ok(budget.map(|b| b.limit() * share))
+}
+
+/// Compare month `a` with month `b` (both "YYYY-MM").
+pub async fn compare_months(pool: &SqlitePool, a: &str, b: &str) -> Result<MonthComparison> {
+    let first_a = parse_month(a)?;
+    let first_b = parse_month(b)?;
+    let today = Local::now().date_naive();
+    let running = |first: NaiveDate| first.year() == today.year() && first.month() == today.month();
+    let through_day = (running(first_a) || running(first_b)).then(|| today.day());
+
+    let range_a = range(first_a, through_day);
+    let range_b = range(first_b, through_day);
+
+    let mut by_category: HashMap<Option<String>, (String, f64, f64)> = HashMap::new();
+    for (in_b, rows) in [
+        (false, category_totals(pool, &range_a).await?),
+        (true, category_totals(pool, &range_b).await?),
+    ] {
+        for (category_id, name, total) in rows {
+            let entry = by_category.entry(category_id).or_insert_with(|| {
+                (
+                    name.unwrap_or_else(|| "Uncategorized".to_string()),
+                    0.0,
+                    0.0,
+                )
+            });
+            if in_b {
+                entry.2 = total;
+            } else {
+                entry.1 = total;
+            }
+        }
+    }
+    let mut categories: Vec<CategoryDelta> = by_category
+        .into_iter()
+        .map(
+            |(category_id, (category_name, amount_a, amount_b))| CategoryDelta {
+                category_id,
+                category_name,
+                amount_a,
+                amount_b,
+                delta: amount_b - amount_a,
+                percent: (amount_a > 0.0).then(|| (amount_b - amount_a) / amount_a * 100.0),
+            },
+        )
+        .collect();
+    categories.sort_by(|x, y| y.delta.abs().total_cmp(&x.delta.abs()));
+
+    let merchants_a = merchant_names(pool, &range_a).await?;
+    let merchants_b = merchant_names(pool, &range_b).await?;
+    let mut new_merchants: Vec<String> = merchants_b.difference(&merchants_a).cloned().collect();
+    let mut disappeared_merchants: Vec<String> =
+        merchants_a.difference(&merchants_b).cloned().collect();
+    new_merchants.sort();
+    disappeared_merchants.sort();
+
+    let total_a: f64 = categories.iter().map(|c| c.amount_a).sum();
+    let total_b: f64 = categories.iter().map(|c| c.amount_b).sum();
+    let limit_a = prorated_limit(pool, first_a, through_day).await?;
+    let limit_b = prorated_limit(pool, first_b, through_day).await?;
+    let used = |total: f64, limit: Option<f64>| limit.filter(|l| *l > 0.0).map(|l| total / l);
+    let used_a = used(total_a, limit_a);
+    let used_b = used(total_b, limit_b);
+
+    Ok(MonthComparison {
+        month_a: a.to_string(),
+        month_b: b.to_string(),
+        through_day,
+        total_a,
+        total_b,
+        categories,
+        new_merchants,
+        disappeared_merchants,
+        budget: BudgetAdherence {
+            limit_a,
+            limit_b,
+            used_a,
+            used_b,
+            difference: used_a.zip(used_b).map(|(a, b)| b - a),
+        },
+    })
+}
@@ -0,0 +1,115 @@
+//! How much can realistically go towards savings goals?
+//!
+//! Looks at what was left over in past months (income minus net spending)
+//! and reads the plans against that distribution rather than a single
+//! average: the median is what a typical month leaves, and a contribution
+//! above the 75th percentile only works out in unusually good months.
+//!
+//! Income is the logged income for the month where there is any, otherwise
+//! the month's budget total. Months with neither are left out.
+
+use chrono::{Datelike, Months, NaiveDate};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::budgets;
+use crate::error::Result;
+use crate::goals;
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct FundingForecast {
+    /// Past months with enough data to count.
+    pub months_considered: usize,
+    pub median_surplus: f64,
+    pub p25_surplus: f64,
+    pub p75_surplus: f64,
+    /// Sum of all goals' planned monthly contributions.
+    pub planned_contributions: f64,
+    pub goals: Vec<GoalFunding>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct GoalFunding {
+    pub goal_id: String,
+    pub name: String,
+    pub monthly_contribution: f64,
+    /// The contribution is more than three months in four have left over.
+    pub at_risk: bool,
+}
+
+/// Linear interpolation between the closest ranks of `sorted`.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    match sorted.len() {
+        0 => 0.0,
+        1 => sorted[0],
+        n => {
+            let rank = p * (n - 1) as f64;
+            let lower = rank.floor() as usize;
+            let upper = rank.ceil() as usize;
+            sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+        }
+    }
+}
+
+async fn month_surplus(pool: &SqlitePool, month: &str) -> Result<Option<f64>> {
+    let mut conn = pool.acquire().await?;
+    let (logged,): (f64,) = sqlx::query_as(
+        "SELECT COALESCE(SUM(amount), 0.0) FROM income_entries
+         WHERE substr(date, 1, 7) = $1 AND deleted_at IS NULL",
+    )
+    .bind(month)
+    .fetch_one(&mut *conn)
+    .await?;
+    let income = if logged > 0.0 {
+        logged
+    } else {
+        let budget: Option<(f64,)> = sqlx::query_as(
+            "SELECT total_amount FROM budgets WHERE month = $1 AND deleted_at IS NULL",
+        )
+        .bind(month)
+        .fetch_optional(&mut *conn)
+        .await?;
+        match budget {
+            Some((total,)) => total,
+            None => return Ok(None),
+        }
+    };
+    let spent = budgets::month_spending(&mut conn, month).await?;
+    Ok(Some(income - spent))
+}
+
+/// Forecast from the `months` full months before `today`.
+pub async fn forecast(pool: &SqlitePool, months: u32, today: NaiveDate) -> Result<FundingForecast> {
+    let first_of_month = today.with_day(1).unwrap_or(today);
+    let mut surpluses = Vec::new();
+    for back in 1..=months {
+        let Some(month) = first_of_month.checked_sub_months(Months::new(back)) else {
+            break;
+        };
+        if let Some(surplus) = month_surplus(pool, &month.format("%Y-%m").to_string()).await? {
+            surpluses.push(surplus);
+        }
+    }
+    surpluses.sort_by(f64::total_cmp);
+
+    let p75_surplus = percentile(&surpluses, 0.75);
+    let goals: Vec<GoalFunding> = goals::list(pool)
+        .await?
+        .into_iter()
+        .map(|goal| GoalFunding {
+            at_risk: !surpluses.is_empty() && goal.monthly_contribution > p75_surplus,
+            goal_id: goal.id,
+            name: goal.name,
+            monthly_contribution: goal.monthly_contribution,
+        })
+        .collect();
+
+    Ok(FundingForecast {
+        months_considered: surpluses.len(),
+        median_surplus: percentile(&surpluses, 0.5),
+        p25_surplus: percentile(&surpluses, 0.25),
+        p75_surplus,
+        planned_contributions: goals.iter().map(|g| g.monthly_contribution).sum(),
+        goals,
+    })
+}
@@ -0,0 +1,190 @@
+//! Notable facts about a month's spending, per category.
+//!
+//! Each insight is a structured comparison (this month against the three
+//! before it, or against a run of earlier months) plus a ready-made
+//! sentence for the insights feed and the weekly digest. They come ranked
+//! by how much money is behind them, so the first few are worth showing.
+//!
+//! A month still in progress is scaled up to a full month at the pace so
+//! far; otherwise every category would look down on its average.
+
+use std::collections::HashMap;
+
+use chrono::{Datelike, Local, Months, NaiveDate};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use super::spending::NET_AMOUNT;
+use crate::error::{Error, Result};
+
+/// Months before the one looked at that are loaded, for streaks.
+const HISTORY_MONTHS: usize = 6;
+
+/// Months the average is taken over.
+const AVERAGE_MONTHS: usize = 3;
+
+/// Smallest change against the average worth mentioning, in percent...
+const MIN_CHANGE_PERCENT: f64 = 20.0;
+
+/// ...and in money.
+const MIN_CHANGE_AMOUNT: f64 = 10.0;
+
+/// Months of growth in a row before it counts as a trend.
+const MIN_STREAK: usize = 3;
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Fact {
+    AboveAverage {
+        percent: f64,
+        average: f64,
+    },
+    BelowAverage {
+        percent: f64,
+        average: f64,
+    },
+    /// Nothing was spent here in the months before.
+    NewSpending,
+    GrowthStreak {
+        months: usize,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct CategoryInsight {
+    pub category_id: Option<String>,
+    pub category_name: String,
+    /// Net spend this month, scaled up if the month isn't over.
+    pub amount: f64,
+    #[serde(flatten)]
+    pub fact: Fact,
+    pub message: String,
+    /// Money behind the fact; insights are sorted by it.
+    pub score: f64,
+}
+
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (1, 11) | (2, 12) | (3, 13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{n}{suffix}")
+}
+
+/// Facts about one category, given its totals with this month first.
+fn facts(name: &str, totals: &[f64]) -> Vec<(Fact, String, f64)> {
+    let current = totals[0];
+    let mut found = Vec::new();
+
+    let previous = &totals[1..=AVERAGE_MONTHS];
+    let average = previous.iter().sum::<f64>() / AVERAGE_MONTHS as f64;
+    if average > 0.0 {
+        let diff = current - average;
+        let percent = diff / average * 100.0;
+        if percent.abs() >= MIN_CHANGE_PERCENT && diff.abs() >= MIN_CHANGE_AMOUNT {
+            let (fact, direction) = if diff > 0.0 {
+                (Fact::AboveAverage { percent, average }, "above")
+            } else {
+                (Fact::BelowAverage { percent, average }, "below")
+            };
+            found.push((
+                fact,
+                format!(
+                    "{name} is {:.0}% {direction} your {AVERAGE_MONTHS}-month average",
+                    percent.abs()
+                ),
+                diff.abs(),
+            ));
+        }
+    } else if current >= MIN_CHANGE_AMOUNT {
+        found.push((
+            Fact::NewSpending,
+            format!("{name} is new this month"),
+            current,
+        ));
+    }
+
+    let streak = totals
+        .windows(2)
+        .take_while(|pair| pair[1] > 0.0 && pair[0] > pair[1])
+        .count();
+    if streak >= MIN_STREAK {
+        found.push((
+            Fact::GrowthStreak { months: streak },
+            format!("{name} grew for the {} straight month", ordinal(streak)),
+            current - totals[streak],
+        ));
+    }
+    found
+}
+
+/// Ranked insights for `month` ("YYYY-MM").
+pub async fn category_insights(pool: &SqlitePool, month: &str) -> Result<Vec<CategoryInsight>> {
+    let first = NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d")
+        .map_err(|_| Error::Validation("Month must be YYYY-MM".to_string()))?;
+    let earliest = first
+        .checked_sub_months(Months::new(HISTORY_MONTHS as u32))
+        .unwrap_or(first);
+
+    let rows: Vec<(Option<String>, Option<String>, String, f64)> = sqlx::query_as(&format!(
+        "SELECT e.category_id, c.name, strftime('%Y-%m', e.date) AS month,
+                COALESCE(SUM({NET_AMOUNT}), 0.0)
+         FROM expenses e
+         LEFT JOIN categories c ON c.id = e.category_id
+         WHERE e.deleted_at IS NULL AND strftime('%Y-%m', e.date) BETWEEN $1 AND $2
+         GROUP BY e.category_id, month"
+    ))
+    .bind(earliest.format("%Y-%m").to_string())
+    .bind(month)
+    .fetch_all(pool)
+    .await?;
+
+    // Scale a running month up to its full length.
+    let today = Local::now().date_naive();
+    let scale = if today.year() == first.year() && today.month() == first.month() {
+        let days_in_month = (first + Months::new(1) - first).num_days();
+        days_in_month as f64 / f64::from(today.day())
+    } else {
+        1.0
+    };
+
+    let mut categories: HashMap<Option<String>, (String, Vec<f64>)> = HashMap::new();
+    for (category_id, name, row_month, total) in rows {
+        let Ok(row_first) = NaiveDate::parse_from_str(&format!("{row_month}-01"), "%Y-%m-%d")
+        else {
+            continue;
+        };
+        let back = (first.year() - row_first.year()) * 12 + first.month() as i32
+            - row_first.month() as i32;
+        let entry = categories.entry(category_id).or_insert_with(|| {
+            (
+                name.unwrap_or_else(|| "Uncategorized".to_string()),
+                vec![0.0; HISTORY_MONTHS + 1],
+            )
+        });
+        if let Some(slot) = entry.1.get_mut(back as usize) {
+            *slot = if back == 0 { total * scale } else { total };
+        }
+    }
+
+    let mut insights: Vec<CategoryInsight> = categories
+        .into_iter()
+        .flat_map(|(category_id, (name, totals))| {
+            facts(&name, &totals)
+                .into_iter()
+                .map(move |(fact, message, score)| CategoryInsight {
+                    category_id: category_id.clone(),
+                    category_name: name.clone(),
+                    amount: totals[0],
+                    fact,
+                    message,
+                    score,
+                })
+        })
+        .collect();
+    insights.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(insights)
+}
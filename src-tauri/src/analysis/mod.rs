@@ -0,0 +1,12 @@
+//! Read-only analyses over the user's expense history.
+
+pub mod compare;
+pub mod funding;
+pub mod insights;
+pub mod patterns;
+pub mod price_changes;
+pub mod projection;
+pub mod recurring;
+pub mod reports;
+pub mod spending;
+pub mod year_review;
@@ -0,0 +1,218 @@
+//! When in the week (and day) the money goes.
+//!
+//! Expenses only carry a date, so the time of day comes from when the
+//! expense was entered. That is only trusted when it was entered on the day
+//! it is dated; anything logged later counts for the weekday alone.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, Timelike, Weekday};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::error::{Error, Result};
+
+/// Weekday/time-of-day combinations returned as hotspots.
+const MAX_HOTSPOTS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeOfDay {
+    /// 05:00 to 11:59.
+    Morning,
+    /// 12:00 to 16:59.
+    Afternoon,
+    /// 17:00 to 21:59.
+    Evening,
+    /// 22:00 to 04:59.
+    Night,
+}
+
+impl TimeOfDay {
+    fn from_hour(hour: u32) -> Self {
+        match hour {
+            5..=11 => TimeOfDay::Morning,
+            12..=16 => TimeOfDay::Afternoon,
+            17..=21 => TimeOfDay::Evening,
+            _ => TimeOfDay::Night,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct WeekdayTotal {
+    /// "monday" through "sunday".
+    pub weekday: String,
+    pub total: f64,
+    pub count: i64,
+    /// Spend per occurrence of this weekday in the range.
+    pub average: f64,
+    pub top_category_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct TimeOfDayTotal {
+    pub time_of_day: TimeOfDay,
+    pub total: f64,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct Hotspot {
+    pub weekday: String,
+    pub time_of_day: TimeOfDay,
+    pub total: f64,
+    pub count: i64,
+    pub top_category_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct WeekdayPatterns {
+    /// Monday first.
+    pub by_weekday: Vec<WeekdayTotal>,
+    /// Share of spending on Saturdays and Sundays, 0 to 1.
+    pub weekend_share: f64,
+    /// Only expenses entered on the day they're dated.
+    pub by_time_of_day: Vec<TimeOfDayTotal>,
+    /// How many expenses had a usable time.
+    pub timed_count: i64,
+    /// Biggest weekday/time-of-day combinations, largest first.
+    pub hotspots: Vec<Hotspot>,
+}
+
+#[derive(Default)]
+struct Acc {
+    total: f64,
+    count: i64,
+    categories: HashMap<Option<String>, f64>,
+}
+
+impl Acc {
+    fn add(&mut self, amount: f64, category_id: &Option<String>) {
+        self.total += amount;
+        self.count += 1;
+        *self.categories.entry(category_id.clone()).or_default() += amount;
+    }
+
+    fn top_category(&self) -> Option<String> {
+        self.categories
+            .iter()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .and_then(|(id, _)| id.clone())
+    }
+}
+
+fn weekday_name(day: Weekday) -> String {
+    match day {
+        Weekday::Mon => "monday",
+        Weekday::Tue => "tuesday",
+        Weekday::Wed => "wednesday",
+        Weekday::Thu => "thursday",
+        Weekday::Fri => "friday",
+        Weekday::Sat => "saturday",
+        Weekday::Sun => "sunday",
+    }
+    .to_string()
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate> {
+    date.get(..10)
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .ok_or_else(|| Error::Validation(format!("\"{date}\" is not a YYYY-MM-DD date")))
+}
+
+/// Patterns for expenses dated `start_date..=end_date` ("YYYY-MM-DD").
+pub async fn weekday_patterns(
+    pool: &SqlitePool,
+    start_date: &str,
+    end_date: &str,
+) -> Result<WeekdayPatterns> {
+    let start = parse_date(start_date)?;
+    let end = parse_date(end_date)?;
+
+    let rows: Vec<(String, f64, Option<String>, String)> = sqlx::query_as(
+        "SELECT date, amount, category_id, created_at FROM expenses
+         WHERE deleted_at IS NULL AND date >= $1 AND date <= $2
+           AND COALESCE(reimbursement_status, '') != 'received'",
+    )
+    .bind(start_date)
+    .bind(end_date)
+    .fetch_all(pool)
+    .await?;
+
+    let mut weekdays: [Acc; 7] = Default::default();
+    let mut times: HashMap<TimeOfDay, Acc> = HashMap::new();
+    let mut combos: HashMap<(Weekday, TimeOfDay), Acc> = HashMap::new();
+    let mut timed_count = 0;
+    for (date, amount, category_id, created_at) in rows {
+        let Ok(date) = parse_date(&date) else {
+            continue;
+        };
+        let weekday = date.weekday();
+        weekdays[weekday.num_days_from_monday() as usize].add(amount, &category_id);
+
+        let entered = DateTime::parse_from_rfc3339(&created_at)
+            .ok()
+            .map(|t| t.with_timezone(&Local));
+        if let Some(entered) = entered.filter(|t| t.date_naive() == date) {
+            let slot = TimeOfDay::from_hour(entered.hour());
+            times.entry(slot).or_default().add(amount, &category_id);
+            combos
+                .entry((weekday, slot))
+                .or_default()
+                .add(amount, &category_id);
+            timed_count += 1;
+        }
+    }
+
+    // How often each weekday occurs in the range, for per-day averages.
+    let mut occurrences = [0u32; 7];
+    for day in start.iter_days().take_while(|d| *d <= end) {
+        occurrences[day.weekday().num_days_from_monday() as usize] += 1;
+    }
+
+    let total: f64 = weekdays.iter().map(|acc| acc.total).sum();
+    let weekend: f64 = weekdays[5].total + weekdays[6].total;
+    let by_weekday = weekdays
+        .iter()
+        .enumerate()
+        .map(|(i, acc)| WeekdayTotal {
+            weekday: weekday_name(Weekday::try_from(i as u8).unwrap_or(Weekday::Mon)),
+            total: acc.total,
+            count: acc.count,
+            average: acc.total / f64::from(occurrences[i].max(1)),
+            top_category_id: acc.top_category(),
+        })
+        .collect();
+
+    let mut by_time_of_day: Vec<TimeOfDayTotal> = times
+        .into_iter()
+        .map(|(time_of_day, acc)| TimeOfDayTotal {
+            time_of_day,
+            total: acc.total,
+            count: acc.count,
+        })
+        .collect();
+    by_time_of_day.sort_by_key(|t| t.time_of_day as u8);
+
+    let mut hotspots: Vec<Hotspot> = combos
+        .into_iter()
+        .map(|((weekday, time_of_day), acc)| Hotspot {
+            weekday: weekday_name(weekday),
+            time_of_day,
+            total: acc.total,
+            count: acc.count,
+            top_category_id: acc.top_category(),
+        })
+        .collect();
+    hotspots.sort_by(|a, b| b.total.total_cmp(&a.total));
+    hotspots.truncate(MAX_HOTSPOTS);
+
+    Ok(WeekdayPatterns {
+        by_weekday,
+        weekend_share: if total > 0.0 { weekend / total } else { 0.0 },
+        by_time_of_day,
+        timed_count,
+        hotspots,
+    })
+}
@@ -0,0 +1,96 @@
+//! Spots subscriptions whose latest charge came in above the price the user
+//! had been paying.
+//!
+//! The history before the latest charge has to form a recurring pattern on
+//! its own, and the latest charge has to land on that pattern's next cycle,
+//! so a one-off bigger purchase at the same shop isn't reported.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use super::recurring::{detect, load_groups, Cadence, Charge};
+use crate::error::Result;
+
+/// Ignore changes below this fraction (rounding, currency conversion).
+const MIN_INCREASE: f64 = 0.02;
+
+/// Amounts within this fraction of the pattern's median are the old price.
+const PRICE_TOLERANCE: f64 = 0.02;
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct PriceIncrease {
+    pub match_key: String,
+    pub name: String,
+    pub category_id: Option<String>,
+    pub cadence: Cadence,
+    pub previous_amount: f64,
+    pub new_amount: f64,
+    pub delta: f64,
+    /// Increase as a fraction of the previous amount.
+    pub percent: f64,
+    /// Extra cost over a year at the new price.
+    pub annual_impact: f64,
+    /// Date of the more expensive charge.
+    pub charged_on: String,
+    pub expense_id: String,
+}
+
+fn round_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+/// Compare the newest charge in a group against the pattern before it.
+fn check(group: &[Charge], today: NaiveDate) -> Option<PriceIncrease> {
+    let (latest, history) = group.split_last()?;
+    let pattern = detect(history, latest.day)?;
+    let previous = history.last()?;
+
+    // Once the next cycle is due the increase is old news.
+    let cadence = pattern.cadence;
+    if (today - latest.day).num_days() > cadence.days() + cadence.tolerance() {
+        return None;
+    }
+
+    let expected = cadence.next_after(previous.day);
+    if (latest.day - expected).num_days().abs() > cadence.tolerance() {
+        return None;
+    }
+    // The charge before must still have been the usual price, otherwise the
+    // change was already reported (or the history is too noisy to tell).
+    if (previous.amount - pattern.amount).abs() > pattern.amount * PRICE_TOLERANCE {
+        return None;
+    }
+
+    let delta = latest.amount - previous.amount;
+    if previous.amount <= 0.0 || delta < previous.amount * MIN_INCREASE {
+        return None;
+    }
+
+    Some(PriceIncrease {
+        match_key: pattern.match_key,
+        name: latest.note.clone(),
+        category_id: latest.category_id.clone(),
+        cadence,
+        previous_amount: round_cents(previous.amount),
+        new_amount: round_cents(latest.amount),
+        delta: round_cents(delta),
+        percent: delta / previous.amount,
+        annual_impact: round_cents(delta * cadence.per_year()),
+        charged_on: latest.date.clone(),
+        expense_id: latest.id.clone(),
+    })
+}
+
+/// Subscriptions whose current charge is a price increase, largest yearly
+/// impact first.
+pub async fn find_increases(pool: &SqlitePool, today: NaiveDate) -> Result<Vec<PriceIncrease>> {
+    let groups = load_groups(pool).await?;
+
+    let mut increases: Vec<PriceIncrease> = groups
+        .values()
+        .filter_map(|group| check(group, today))
+        .collect();
+    increases.sort_by(|a, b| b.annual_impact.total_cmp(&a.annual_impact));
+    Ok(increases)
+}
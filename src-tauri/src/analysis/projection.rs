@@ -0,0 +1,257 @@
+//! When will a savings goal be reached?
+//!
+//! Same model as `getSavingsGoalWithStats()` on the frontend: the remaining
+//! amount is paid off at the goal's monthly contribution, starting this
+//! month. On top of that we compare the balance with what the plan says
+//! should have been saved by now, which tells us how far the timeline has
+//! slipped.
+//!
+//! `forecast` goes further for the goal detail screen: what it takes to hit
+//! the target date, how contributing less would play out, and optionally
+//! interest compounding monthly on the balance. Contributions are assumed
+//! to land at the end of each month.
+
+use chrono::{Datelike, Months, NaiveDate};
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::goals::SavingsGoal;
+
+/// Average weeks per month, for turning missed months into weeks.
+const WEEKS_PER_MONTH: f64 = 365.25 / 12.0 / 7.0;
+
+/// Forecasts give up on goals further out than this.
+const MAX_MONTHS: u32 = 100 * 12;
+
+/// Highest annual interest rate accepted, as a fraction.
+const MAX_INTEREST_RATE: f64 = 0.5;
+
+/// Shares of the planned contribution shown as shortfall scenarios.
+const SCENARIO_SHARES: [f64; 3] = [1.0, 0.75, 0.5];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GoalProjection {
+    pub goal_id: String,
+    pub total_saved: f64,
+    /// What the plan says should be saved by the end of last month.
+    pub expected_saved: f64,
+    /// First of the month the goal is reached; `None` without contributions.
+    pub projected_completion: Option<String>,
+    /// How much later than planned the goal will be reached; zero when on or
+    /// ahead of plan.
+    pub slip_weeks: f64,
+    /// Projected to finish by the target date.
+    pub on_track: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum GoalStatus {
+    Reached,
+    OnTrack,
+    Behind,
+    /// The target date has passed without reaching the goal.
+    Overdue,
+    /// Nothing is being contributed, so it will never be reached.
+    Stalled,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct GoalForecast {
+    pub goal_id: String,
+    pub status: GoalStatus,
+    pub total_saved: f64,
+    pub remaining: f64,
+    /// Contributions left before the target date, this month included.
+    pub months_left: u32,
+    /// What each of those months needs for the goal to be reached on time.
+    pub required_monthly: f64,
+    /// Annual rate the forecast assumes, as a fraction.
+    pub interest_rate: f64,
+    /// The planned contribution first, then the smaller ones.
+    pub scenarios: Vec<Scenario>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct Scenario {
+    pub monthly_contribution: f64,
+    /// First of the month the goal is reached; `None` if never.
+    pub projected_completion: Option<String>,
+    /// Balance on the target date.
+    pub saved_by_target: f64,
+    /// What would still be missing then.
+    pub shortfall: f64,
+    /// Interest earned by the target date.
+    pub interest: f64,
+}
+
+/// Balance after `months` end-of-month contributions at `rate` a month.
+fn future_value(balance: f64, monthly: f64, rate: f64, months: u32) -> f64 {
+    let n = months as i32;
+    if rate == 0.0 {
+        return balance + monthly * f64::from(n);
+    }
+    let growth = (1.0 + rate).powi(n);
+    balance * growth + monthly * (growth - 1.0) / rate
+}
+
+/// The monthly contribution that reaches `target` after `months`.
+fn required_monthly(balance: f64, target: f64, rate: f64, months: u32) -> f64 {
+    if balance >= target {
+        return 0.0;
+    }
+    if months == 0 {
+        return target - balance;
+    }
+    let n = months as i32;
+    let needed = if rate == 0.0 {
+        (target - balance) / f64::from(n)
+    } else {
+        let growth = (1.0 + rate).powi(n);
+        (target - balance * growth) * rate / (growth - 1.0)
+    };
+    needed.max(0.0)
+}
+
+/// Contributions until the balance reaches `target`, if within
+/// `MAX_MONTHS`.
+fn months_to_reach(balance: f64, target: f64, monthly: f64, rate: f64) -> Option<u32> {
+    if balance >= target {
+        return Some(0);
+    }
+    if monthly <= 0.0 && (rate <= 0.0 || balance <= 0.0) {
+        return None;
+    }
+    let mut saved = balance;
+    for month in 1..=MAX_MONTHS {
+        saved = saved * (1.0 + rate) + monthly;
+        if saved >= target {
+            return Some(month);
+        }
+    }
+    None
+}
+
+/// How the goal plays out from `total_saved` onwards, with interest at
+/// `annual_rate` (a fraction, 0.03 for 3%) compounding monthly.
+pub fn forecast(
+    goal: &SavingsGoal,
+    total_saved: f64,
+    annual_rate: Option<f64>,
+    today: NaiveDate,
+) -> Result<GoalForecast> {
+    let interest_rate = annual_rate.unwrap_or(0.0);
+    if !interest_rate.is_finite() || !(0.0..=MAX_INTEREST_RATE).contains(&interest_rate) {
+        return Err(Error::Validation(format!(
+            "Interest rate must be between 0% and {:.0}%",
+            MAX_INTEREST_RATE * 100.0
+        )));
+    }
+    let rate = interest_rate / 12.0;
+    let target_date = NaiveDate::parse_from_str(&goal.target_date, "%Y-%m-%d")
+        .map_err(|_| Error::Validation(format!("Invalid target date {}", goal.target_date)))?;
+    let start = month_start(today);
+    let months_left = months_between(today, target_date).max(0) as u32;
+
+    let scenarios = SCENARIO_SHARES
+        .iter()
+        .map(|share| {
+            let monthly = goal.monthly_contribution * share;
+            let saved_by_target = future_value(total_saved, monthly, rate, months_left);
+            let contributed = total_saved + monthly * f64::from(months_left);
+            Scenario {
+                monthly_contribution: monthly,
+                projected_completion: months_to_reach(
+                    total_saved,
+                    goal.target_amount,
+                    monthly,
+                    rate,
+                )
+                .and_then(|months| start.checked_add_months(Months::new(months)))
+                .map(|d| d.format("%Y-%m-%d").to_string()),
+                saved_by_target,
+                shortfall: (goal.target_amount - saved_by_target).max(0.0),
+                interest: saved_by_target - contributed,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let remaining = (goal.target_amount - total_saved).max(0.0);
+    let status = if remaining == 0.0 {
+        GoalStatus::Reached
+    } else if target_date < today {
+        GoalStatus::Overdue
+    } else if scenarios[0].projected_completion.is_none() {
+        GoalStatus::Stalled
+    } else if scenarios[0].shortfall > 0.0 {
+        GoalStatus::Behind
+    } else {
+        GoalStatus::OnTrack
+    };
+
+    Ok(GoalForecast {
+        goal_id: goal.id.clone(),
+        status,
+        total_saved,
+        remaining,
+        months_left,
+        required_monthly: required_monthly(total_saved, goal.target_amount, rate, months_left),
+        interest_rate,
+        scenarios,
+    })
+}
+
+fn month_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+/// Whole months from `from`'s month to `to`'s month.
+fn months_between(from: NaiveDate, to: NaiveDate) -> i64 {
+    i64::from(to.year() - from.year()) * 12 + i64::from(to.month()) - i64::from(from.month())
+}
+
+pub fn project(goal: &SavingsGoal, total_saved: f64, today: NaiveDate) -> GoalProjection {
+    let remaining = goal.target_amount - total_saved;
+    let projected_completion = if remaining <= 0.0 {
+        Some(today)
+    } else if goal.monthly_contribution > 0.0 {
+        let months = (remaining / goal.monthly_contribution).ceil() as u32;
+        month_start(today).checked_add_months(Months::new(months))
+    } else {
+        None
+    };
+
+    // Months the plan has been running, counting the month the goal was
+    // created and stopping at last month (this month isn't checked in yet).
+    let created = goal
+        .created_at
+        .get(..10)
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .unwrap_or(today);
+    let planned_months = months_between(created, today).max(0) as f64;
+    let expected_saved = (planned_months * goal.monthly_contribution).min(goal.target_amount);
+
+    let shortfall = (expected_saved - total_saved).max(0.0);
+    let slip_weeks = if goal.monthly_contribution > 0.0 {
+        shortfall / goal.monthly_contribution * WEEKS_PER_MONTH
+    } else {
+        0.0
+    };
+
+    let on_track = match (
+        projected_completion,
+        NaiveDate::parse_from_str(&goal.target_date, "%Y-%m-%d"),
+    ) {
+        (Some(projected), Ok(target)) => projected <= target,
+        _ => false,
+    };
+
+    GoalProjection {
+        goal_id: goal.id.clone(),
+        total_saved,
+        expected_saved,
+        projected_completion: projected_completion.map(|d| d.format("%Y-%m-%d").to_string()),
+        slip_weeks,
+        on_track,
+    }
+}
@@ -0,0 +1,258 @@
+//! Detects repeating charges (rent, gym, streaming) in past expenses so the
+//! user can turn them into recurring-expense entries with one tap.
+//!
+//! Expenses are grouped by a normalized note; a group is a candidate when its
+//! charges arrive at a steady cadence with a steady amount and haven't
+//! stopped.
+
+use std::collections::HashMap;
+
+use chrono::{Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::error::Result;
+
+/// A pattern needs this many occurrences before we believe it.
+const MIN_OCCURRENCES: usize = 3;
+
+/// Share of intervals/amounts that must agree with the typical value.
+const MIN_REGULARITY: f64 = 0.75;
+
+/// Amounts within this fraction of the median count as "the same".
+const AMOUNT_TOLERANCE: f64 = 0.15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "lowercase")]
+pub enum Cadence {
+    Weekly,
+    Biweekly,
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl Cadence {
+    /// Map a typical gap between charges to a cadence, if it is one.
+    fn from_interval(days: i64) -> Option<Self> {
+        match days {
+            6..=8 => Some(Self::Weekly),
+            13..=16 => Some(Self::Biweekly),
+            27..=33 => Some(Self::Monthly),
+            85..=95 => Some(Self::Quarterly),
+            355..=375 => Some(Self::Yearly),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Weekly => "weekly",
+            Self::Biweekly => "biweekly",
+            Self::Monthly => "monthly",
+            Self::Quarterly => "quarterly",
+            Self::Yearly => "yearly",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "weekly" => Some(Self::Weekly),
+            "biweekly" => Some(Self::Biweekly),
+            "monthly" => Some(Self::Monthly),
+            "quarterly" => Some(Self::Quarterly),
+            "yearly" => Some(Self::Yearly),
+            _ => None,
+        }
+    }
+
+    /// Nominal length in days, used for tolerances.
+    pub(crate) fn days(self) -> i64 {
+        match self {
+            Self::Weekly => 7,
+            Self::Biweekly => 14,
+            Self::Monthly => 30,
+            Self::Quarterly => 91,
+            Self::Yearly => 365,
+        }
+    }
+
+    /// How far a charge may drift from the cadence and still count.
+    pub(crate) fn tolerance(self) -> i64 {
+        (self.days() / 7).max(2)
+    }
+
+    /// Charges per year, for annualizing amounts.
+    pub fn per_year(self) -> f64 {
+        match self {
+            Self::Weekly => 52.0,
+            Self::Biweekly => 26.0,
+            Self::Monthly => 12.0,
+            Self::Quarterly => 4.0,
+            Self::Yearly => 1.0,
+        }
+    }
+
+    /// Calendar-aware step, so monthly charges on the 31st stay at month end.
+    pub fn next_after(self, date: NaiveDate) -> NaiveDate {
+        let next = match self {
+            Self::Weekly => date.checked_add_days(chrono::Days::new(7)),
+            Self::Biweekly => date.checked_add_days(chrono::Days::new(14)),
+            Self::Monthly => date.checked_add_months(Months::new(1)),
+            Self::Quarterly => date.checked_add_months(Months::new(3)),
+            Self::Yearly => date.checked_add_months(Months::new(12)),
+        };
+        next.unwrap_or(date)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct RecurringCandidate {
+    /// Normalized note shared by all occurrences.
+    pub match_key: String,
+    /// Most recent note as the user wrote it, used as the suggested name.
+    pub name: String,
+    pub category_id: Option<String>,
+    /// Median charge.
+    pub amount: f64,
+    pub cadence: Cadence,
+    pub occurrences: usize,
+    pub last_date: String,
+    pub next_date: String,
+    /// `0.0..=1.0`, how cleanly the history fits the pattern.
+    pub confidence: f64,
+    pub expense_ids: Vec<String>,
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct Charge {
+    pub id: String,
+    pub amount: f64,
+    pub category_id: Option<String>,
+    pub note: String,
+    pub date: String,
+    #[sqlx(skip)]
+    pub day: NaiveDate,
+}
+
+/// Lowercase, drop digits and punctuation so "Netflix 03/24" and
+/// "NETFLIX 04/24" land in the same group.
+pub fn match_key(note: &str) -> String {
+    note.to_lowercase()
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+/// Evaluate one group of same-key expenses, oldest first.
+pub(crate) fn detect(group: &[Charge], today: NaiveDate) -> Option<RecurringCandidate> {
+    if group.len() < MIN_OCCURRENCES {
+        return None;
+    }
+
+    let intervals: Vec<i64> = group
+        .windows(2)
+        .map(|w| (w[1].day - w[0].day).num_days())
+        .collect();
+    let mut sorted_intervals: Vec<f64> = intervals.iter().map(|d| *d as f64).collect();
+    let typical_interval = median(&mut sorted_intervals).round() as i64;
+    let cadence = Cadence::from_interval(typical_interval)?;
+
+    let interval_tolerance = cadence.tolerance();
+    let regular_intervals = intervals
+        .iter()
+        .filter(|d| (**d - typical_interval).abs() <= interval_tolerance)
+        .count() as f64
+        / intervals.len() as f64;
+
+    let mut amounts: Vec<f64> = group.iter().map(|c| c.amount).collect();
+    let amount = median(&mut amounts);
+    let steady_amounts = group
+        .iter()
+        .filter(|c| (c.amount - amount).abs() <= amount * AMOUNT_TOLERANCE)
+        .count() as f64
+        / group.len() as f64;
+
+    if regular_intervals < MIN_REGULARITY || steady_amounts < MIN_REGULARITY {
+        return None;
+    }
+
+    // Skipped two cycles in a row: the subscription was probably cancelled.
+    let last = group.last()?;
+    if (today - last.day).num_days() > cadence.days() * 2 + interval_tolerance {
+        return None;
+    }
+
+    let history_bonus = ((group.len() - MIN_OCCURRENCES) as f64 * 0.05).min(0.2);
+    let confidence = (regular_intervals * steady_amounts * 0.8 + history_bonus).min(1.0);
+
+    Some(RecurringCandidate {
+        match_key: match_key(&last.note),
+        name: last.note.clone(),
+        category_id: last.category_id.clone(),
+        amount: (amount * 100.0).round() / 100.0,
+        cadence,
+        occurrences: group.len(),
+        last_date: last.date.clone(),
+        next_date: cadence.next_after(last.day).format("%Y-%m-%d").to_string(),
+        confidence,
+        expense_ids: group.iter().map(|c| c.id.clone()).collect(),
+    })
+}
+
+/// Noted expenses grouped by match key, each group oldest first.
+pub(crate) async fn load_groups(pool: &SqlitePool) -> Result<HashMap<String, Vec<Charge>>> {
+    let rows = sqlx::query_as::<_, Charge>(
+        "SELECT id, amount, category_id, note, date FROM expenses
+         WHERE deleted_at IS NULL AND note IS NOT NULL AND TRIM(note) != ''
+         ORDER BY date ASC",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut groups: HashMap<String, Vec<Charge>> = HashMap::new();
+    for mut charge in rows {
+        let Ok(day) = NaiveDate::parse_from_str(&charge.date, "%Y-%m-%d") else {
+            continue;
+        };
+        charge.day = day;
+        let key = match_key(&charge.note);
+        if !key.is_empty() {
+            groups.entry(key).or_default().push(charge);
+        }
+    }
+    Ok(groups)
+}
+
+/// Recurring patterns in the expense history that aren't tracked yet, most
+/// confident first.
+pub async fn find_candidates(
+    pool: &SqlitePool,
+    today: NaiveDate,
+) -> Result<Vec<RecurringCandidate>> {
+    let groups = load_groups(pool).await?;
+    let tracked: Vec<(String,)> = sqlx::query_as(
+        "SELECT match_key FROM recurring_expenses WHERE deleted_at IS NULL AND match_key IS NOT NULL",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut candidates: Vec<RecurringCandidate> = groups
+        .iter()
+        .filter(|(key, _)| !tracked.iter().any(|(t,)| t == *key))
+        .filter_map(|(_, group)| detect(group, today))
+        .collect();
+    candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    Ok(candidates)
+}
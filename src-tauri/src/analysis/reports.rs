@@ -0,0 +1,351 @@
+//! Reports for the charts and statistics screens, aggregated in SQLite.
+//!
+//! Each report is one query over `expenses`, joined with `income_entries`
+//! for cashflow, so the frontend gets rows it can plot as they are instead
+//! of loading a year of expenses to sum them up. Amounts are net of reimbursements, like everywhere else. Month
+//! ranges are "YYYY-MM" and inclusive; months without spending still get
+//! a row where a chart needs one.
+
+use chrono::{Datelike, Months, NaiveDate};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use super::spending::NET_AMOUNT;
+use crate::error::{Error, Result};
+use crate::periods::{PaySchedule, Period};
+
+/// Longest range a report covers, in months or budget periods.
+const MAX_SPAN: u32 = 120;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct MonthCategoryTotal {
+    /// "YYYY-MM".
+    pub month: String,
+    pub category_id: Option<String>,
+    pub category_name: Option<String>,
+    pub category_color: Option<String>,
+    pub total: f64,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct MonthDelta {
+    pub month: String,
+    pub total: f64,
+    pub previous_total: f64,
+    pub delta: f64,
+    /// Change relative to the previous month; `None` when nothing was spent
+    /// then.
+    pub percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct DailyAverage {
+    /// "YYYY-MM-DD", inclusive. `end` is cut off at today.
+    pub start: String,
+    pub end: String,
+    pub days: i64,
+    pub total: f64,
+    /// `total / days`, counting days without spending.
+    pub average: f64,
+    /// Days with at least one expense.
+    pub spending_days: i64,
+    /// The most spent on a single day.
+    pub highest: f64,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct BudgetUtilization {
+    /// Budget period key.
+    pub period: String,
+    pub start: String,
+    pub end: String,
+    /// What the period is measured against; `None` without a budget.
+    pub budget_limit: Option<f64>,
+    pub spent: f64,
+    pub remaining: Option<f64>,
+    /// Share of the limit spent, 1.0 being exactly on budget.
+    pub used: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct MonthCashflow {
+    /// "YYYY-MM".
+    pub month: String,
+    pub income: f64,
+    pub expenses: f64,
+    /// `income - expenses`; negative when more went out than came in.
+    pub net: f64,
+    /// Share of the income left over; `None` without income.
+    pub savings_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct CashflowBaseline {
+    /// "YYYY-MM", inclusive: the full months averaged, up to last month.
+    pub from_month: String,
+    pub to_month: String,
+    pub average_income: f64,
+    pub average_expenses: f64,
+    pub average_net: f64,
+    /// The requested share of the average income, as a budget limit.
+    pub suggested_limit: Option<f64>,
+}
+
+fn parse_month(month: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d")
+        .map_err(|_| Error::Validation(format!("\"{month}\" is not a YYYY-MM month")))
+}
+
+/// First days of `from` and of the month after `to`.
+fn month_range(from: &str, to: &str) -> Result<(NaiveDate, NaiveDate)> {
+    let first = parse_month(from)?;
+    let last = parse_month(to)?;
+    if last < first {
+        return Err(Error::Validation(
+            "The range ends before it starts".to_string(),
+        ));
+    }
+    if first + Months::new(MAX_SPAN) <= last {
+        return Err(Error::Validation(format!(
+            "Reports cover at most {MAX_SPAN} months"
+        )));
+    }
+    Ok((first, last + Months::new(1)))
+}
+
+fn day(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+/// Spending per category per month, each month's largest first.
+pub async fn monthly_category_totals(
+    pool: &SqlitePool,
+    from: &str,
+    to: &str,
+) -> Result<Vec<MonthCategoryTotal>> {
+    let (start, end) = month_range(from, to)?;
+    Ok(sqlx::query_as::<_, MonthCategoryTotal>(&format!(
+        "SELECT substr(e.date, 1, 7) AS month, e.category_id, c.name AS category_name,
+                c.color AS category_color, SUM({NET_AMOUNT}) AS total, COUNT(*) AS count
+         FROM expenses e
+         LEFT JOIN categories c ON c.id = e.category_id
+         WHERE e.deleted_at IS NULL AND e.date >= $1 AND e.date < $2
+         GROUP BY month, e.category_id
+         ORDER BY month, total DESC"
+    ))
+    .bind(day(start))
+    .bind(day(end))
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Each month's total next to the month before it.
+pub async fn month_over_month(pool: &SqlitePool, from: &str, to: &str) -> Result<Vec<MonthDelta>> {
+    let (start, end) = month_range(from, to)?;
+    // Starts a month early so the first month has something to compare to.
+    let before = start - Months::new(1);
+    Ok(sqlx::query_as::<_, MonthDelta>(&format!(
+        "WITH RECURSIVE months(month) AS (
+           SELECT $1
+           UNION ALL
+           SELECT strftime('%Y-%m', month || '-01', '+1 month') FROM months WHERE month < $2
+         ),
+         totals AS (
+           SELECT substr(date, 1, 7) AS month, SUM({NET_AMOUNT}) AS total
+           FROM expenses
+           WHERE deleted_at IS NULL AND date >= $3 AND date < $4
+           GROUP BY month
+         ),
+         series AS (
+           SELECT m.month, COALESCE(t.total, 0.0) AS total,
+                  LAG(COALESCE(t.total, 0.0), 1, 0.0) OVER (ORDER BY m.month) AS previous_total
+           FROM months m
+           LEFT JOIN totals t ON t.month = m.month
+         )
+         SELECT month, total, previous_total, total - previous_total AS delta,
+                CASE WHEN previous_total > 0 THEN (total - previous_total) / previous_total END
+                  AS percent
+         FROM series
+         WHERE month >= $5
+         ORDER BY month"
+    ))
+    .bind(before.format("%Y-%m").to_string())
+    .bind(to)
+    .bind(day(before))
+    .bind(day(end))
+    .bind(from)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Average spend per day over `start..=end`, not counting days after
+/// `today`.
+pub async fn average_daily_spend(
+    pool: &SqlitePool,
+    start: NaiveDate,
+    end: NaiveDate,
+    today: NaiveDate,
+) -> Result<DailyAverage> {
+    let end = end.min(today);
+    if end < start {
+        return Err(Error::Validation(
+            "The range has no days up to today".to_string(),
+        ));
+    }
+    let (total, spending_days, highest): (f64, i64, f64) = sqlx::query_as(&format!(
+        "WITH daily AS (
+           SELECT SUM({NET_AMOUNT}) AS total
+           FROM expenses
+           WHERE deleted_at IS NULL AND date >= $1 AND date < $2
+           GROUP BY substr(date, 1, 10)
+         )
+         SELECT COALESCE(SUM(total), 0.0), COUNT(*), COALESCE(MAX(total), 0.0) FROM daily"
+    ))
+    .bind(day(start))
+    .bind(day(end + chrono::Days::new(1)))
+    .fetch_one(pool)
+    .await?;
+
+    let days = (end - start).num_days() + 1;
+    Ok(DailyAverage {
+        start: day(start),
+        end: day(end),
+        days,
+        total,
+        average: total / days as f64,
+        spending_days,
+        highest,
+    })
+}
+
+/// Spent against the budget for every budget period overlapping
+/// `start..=end`.
+pub async fn budget_utilization(
+    pool: &SqlitePool,
+    schedule: &PaySchedule,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<BudgetUtilization>> {
+    let mut periods: Vec<Period> = Vec::new();
+    let mut period = schedule.period_at(end);
+    while period.end >= start {
+        if periods.len() as u32 >= MAX_SPAN {
+            return Err(Error::Validation(format!(
+                "Reports cover at most {MAX_SPAN} budget periods"
+            )));
+        }
+        let previous = schedule.previous(&period);
+        periods.push(period);
+        period = previous;
+    }
+    let periods = serde_json::to_string(&periods)
+        .map_err(|e| Error::Validation(format!("failed to encode periods: {e}")))?;
+
+    Ok(sqlx::query_as::<_, BudgetUtilization>(&format!(
+        "WITH periods AS (
+           SELECT json_extract(value, '$.key') AS period,
+                  json_extract(value, '$.start') AS start,
+                  json_extract(value, '$.end') AS end
+           FROM json_each($1)
+         ),
+         measured AS (
+           SELECT p.period, p.start, p.end,
+                  COALESCE(b.spending_limit, b.total_amount) AS budget_limit,
+                  (SELECT COALESCE(SUM({NET_AMOUNT}), 0.0) FROM expenses
+                   WHERE deleted_at IS NULL AND date >= p.start AND date < date(p.end, '+1 day'))
+                    AS spent
+           FROM periods p
+           LEFT JOIN budgets b ON b.month = p.period AND b.deleted_at IS NULL
+         )
+         SELECT period, start, end, budget_limit, spent,
+                budget_limit - spent AS remaining,
+                spent / NULLIF(budget_limit, 0) AS used
+         FROM measured
+         ORDER BY start"
+    ))
+    .bind(periods)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Income against spending for each month.
+pub async fn monthly_cashflow(
+    pool: &SqlitePool,
+    from: &str,
+    to: &str,
+) -> Result<Vec<MonthCashflow>> {
+    let (start, end) = month_range(from, to)?;
+    Ok(sqlx::query_as::<_, MonthCashflow>(&format!(
+        "WITH RECURSIVE months(month) AS (
+           SELECT $1
+           UNION ALL
+           SELECT strftime('%Y-%m', month || '-01', '+1 month') FROM months WHERE month < $2
+         ),
+         income AS (
+           SELECT substr(date, 1, 7) AS month, SUM(amount) AS total
+           FROM income_entries
+           WHERE deleted_at IS NULL AND date >= $3 AND date < $4
+           GROUP BY month
+         ),
+         spending AS (
+           SELECT substr(date, 1, 7) AS month, SUM({NET_AMOUNT}) AS total
+           FROM expenses
+           WHERE deleted_at IS NULL AND date >= $3 AND date < $4
+           GROUP BY month
+         )
+         SELECT m.month, COALESCE(i.total, 0.0) AS income, COALESCE(s.total, 0.0) AS expenses,
+                COALESCE(i.total, 0.0) - COALESCE(s.total, 0.0) AS net,
+                CASE WHEN i.total > 0 THEN (i.total - COALESCE(s.total, 0.0)) / i.total END
+                  AS savings_rate
+         FROM months m
+         LEFT JOIN income i ON i.month = m.month
+         LEFT JOIN spending s ON s.month = m.month
+         ORDER BY m.month"
+    ))
+    .bind(from)
+    .bind(to)
+    .bind(day(start))
+    .bind(day(end))
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Average monthly cashflow over the `months` full months before the one
+/// `today` is in, with `share` of the average income as a budget limit to
+/// start from.
+pub async fn cashflow_baseline(
+    pool: &SqlitePool,
+    months: u32,
+    share: Option<f64>,
+    today: NaiveDate,
+) -> Result<CashflowBaseline> {
+    if months == 0 || months > MAX_SPAN {
+        return Err(Error::Validation(format!(
+            "Average over 1 to {MAX_SPAN} months"
+        )));
+    }
+    if share.is_some_and(|s| !(0.0..=1.0).contains(&s)) {
+        return Err(Error::Validation(
+            "The share of income must be between 0 and 1".to_string(),
+        ));
+    }
+    let this_month = today.with_day(1).unwrap_or(today);
+    let from_month = (this_month - Months::new(months))
+        .format("%Y-%m")
+        .to_string();
+    let to_month = (this_month - Months::new(1)).format("%Y-%m").to_string();
+    let rows = monthly_cashflow(pool, &from_month, &to_month).await?;
+
+    let count = rows.len().max(1) as f64;
+    let average_income = rows.iter().map(|r| r.income).sum::<f64>() / count;
+    let average_expenses = rows.iter().map(|r| r.expenses).sum::<f64>() / count;
+    Ok(CashflowBaseline {
+        from_month,
+        to_month,
+        average_income,
+        average_expenses,
+        average_net: average_income - average_expenses,
+        suggested_limit: share.map(|s| (average_income * s * 100.0).round() / 100.0),
+    })
+}
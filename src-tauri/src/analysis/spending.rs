@@ -0,0 +1,103 @@
+//! Spending totals over a date range.
+//!
+//! Net spend leaves out expenses that were paid back (see `reimbursements`),
+//! matching `getMonthlySpending()` on the frontend. In partner mode the
+//! totals cover the shared ledger and can be narrowed to one member.
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::error::Result;
+
+/// Net amount of an expense row, for use inside `SUM(...)`.
+pub(crate) const NET_AMOUNT: &str =
+    "CASE WHEN reimbursement_status = 'received' THEN 0 ELSE amount END";
+
+/// Expenses in `$1..=$2`, optionally only those entered by `$3`.
+const RANGE_FILTER: &str =
+    "deleted_at IS NULL AND date >= $1 AND date <= $2 AND ($3 IS NULL OR created_by = $3)";
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct SpendingSummary {
+    /// Everything spent in the range.
+    pub gross: f64,
+    /// Part of `gross` that was reimbursed.
+    pub reimbursed: f64,
+    pub net: f64,
+    pub count: i64,
+    /// Net spend per payment method, largest first.
+    pub by_payment_method: Vec<MethodTotal>,
+    /// Net spend per person who entered the expenses, largest first.
+    pub by_member: Vec<MemberTotal>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct MethodTotal {
+    /// cash, card, bank or other; `None` for expenses logged without one.
+    pub payment_method: Option<String>,
+    pub total: f64,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct MemberTotal {
+    /// User id; `None` for expenses logged before signing in.
+    pub created_by: Option<String>,
+    pub total: f64,
+    pub count: i64,
+}
+
+/// Totals for expenses dated `start_date..=end_date` ("YYYY-MM-DD"),
+/// optionally only those entered by `created_by`.
+pub async fn summary(
+    pool: &SqlitePool,
+    start_date: &str,
+    end_date: &str,
+    created_by: Option<&str>,
+) -> Result<SpendingSummary> {
+    let (gross, net, count): (f64, f64, i64) = sqlx::query_as(&format!(
+        "SELECT COALESCE(SUM(amount), 0.0), COALESCE(SUM({NET_AMOUNT}), 0.0), COUNT(*)
+         FROM expenses
+         WHERE {RANGE_FILTER}"
+    ))
+    .bind(start_date)
+    .bind(end_date)
+    .bind(created_by)
+    .fetch_one(pool)
+    .await?;
+
+    let by_payment_method = sqlx::query_as::<_, MethodTotal>(&format!(
+        "SELECT payment_method, COALESCE(SUM({NET_AMOUNT}), 0.0) AS total, COUNT(*) AS count
+         FROM expenses
+         WHERE {RANGE_FILTER}
+         GROUP BY payment_method
+         ORDER BY total DESC"
+    ))
+    .bind(start_date)
+    .bind(end_date)
+    .bind(created_by)
+    .fetch_all(pool)
+    .await?;
+
+    let by_member = sqlx::query_as::<_, MemberTotal>(&format!(
+        "SELECT created_by, COALESCE(SUM({NET_AMOUNT}), 0.0) AS total, COUNT(*) AS count
+         FROM expenses
+         WHERE {RANGE_FILTER}
+         GROUP BY created_by
+         ORDER BY total DESC"
+    ))
+    .bind(start_date)
+    .bind(end_date)
+    .bind(created_by)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(SpendingSummary {
+        gross,
+        reimbursed: gross - net,
+        net,
+        count,
+        by_payment_method,
+        by_member,
+    })
+}
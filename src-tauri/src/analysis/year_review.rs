@@ -0,0 +1,243 @@
+//! A year in review: the numbers behind the annual recap.
+//!
+//! Spending and income come from the same monthly cashflow the reports
+//! use, so the recap agrees with the charts. "Saved" is what went into
+//! savings goals during the year. A goal counts as completed in the month
+//! its contributions first added up to the target. Habit streaks are
+//! counted within the year only, from the months `habit_tracking` recorded.
+//! The month still running is left out of the best and worst months.
+
+use chrono::{Datelike, Months, NaiveDate};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use super::reports::{self, MonthCashflow};
+use super::spending::NET_AMOUNT;
+use crate::error::{Error, Result};
+
+/// How many categories the review ranks.
+const TOP_CATEGORIES: i64 = 5;
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct YearReview {
+    pub year: i32,
+    pub total_spent: f64,
+    pub total_income: f64,
+    /// Contributed to savings goals.
+    pub total_saved: f64,
+    pub expense_count: i64,
+    /// Largest first.
+    pub top_categories: Vec<YearCategory>,
+    /// The finished month with the least spending.
+    pub best_month: Option<MonthCashflow>,
+    /// The finished month with the most spending.
+    pub worst_month: Option<MonthCashflow>,
+    /// Every month of the year so far.
+    pub months: Vec<MonthCashflow>,
+    pub goals_completed: Vec<CompletedGoal>,
+    pub longest_habit_streak: Option<HabitRun>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct YearCategory {
+    pub category_id: Option<String>,
+    pub category_name: Option<String>,
+    pub category_color: Option<String>,
+    pub total: f64,
+    pub count: i64,
+    /// Share of the year's spending, 0 to 1.
+    pub share: f64,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct CompletedGoal {
+    pub goal_id: String,
+    pub name: String,
+    pub target_amount: f64,
+    /// "YYYY-MM" of the contribution that reached the target.
+    pub completed_month: String,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct HabitRun {
+    pub habit_goal_id: String,
+    pub name: String,
+    /// Compliant months in a row.
+    pub months: i64,
+    /// "YYYY-MM", inclusive.
+    pub from_month: String,
+    pub to_month: String,
+}
+
+fn month_key(date: NaiveDate) -> String {
+    date.format("%Y-%m").to_string()
+}
+
+/// The review of `year`, up to the month `today` is in for the current one.
+pub async fn build(pool: &SqlitePool, year: i32, today: NaiveDate) -> Result<YearReview> {
+    let first = NaiveDate::from_ymd_opt(year, 1, 1)
+        .ok_or_else(|| Error::Validation(format!("{year} is not a year")))?;
+    if first > today {
+        return Err(Error::Validation(format!("{year} hasn't started yet")));
+    }
+    let last_month = if year == today.year() {
+        today.with_day(1).unwrap_or(today)
+    } else {
+        first + Months::new(11)
+    };
+    let from = month_key(first);
+    let to = month_key(last_month);
+    let current = month_key(today);
+
+    let months = reports::monthly_cashflow(pool, &from, &to).await?;
+    let total_spent: f64 = months.iter().map(|m| m.expenses).sum();
+    let total_income: f64 = months.iter().map(|m| m.income).sum();
+    let finished = || {
+        months
+            .iter()
+            .filter(|m| m.month < current && m.expenses > 0.0)
+    };
+    let best_month = finished()
+        .min_by(|a, b| a.expenses.total_cmp(&b.expenses))
+        .cloned();
+    let worst_month = finished()
+        .max_by(|a, b| a.expenses.total_cmp(&b.expenses))
+        .cloned();
+
+    let start = first.format("%Y-%m-%d").to_string();
+    let end = (last_month + Months::new(1)).format("%Y-%m-%d").to_string();
+    let (expense_count,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM expenses WHERE deleted_at IS NULL AND date >= $1 AND date < $2",
+    )
+    .bind(&start)
+    .bind(&end)
+    .fetch_one(pool)
+    .await?;
+    let top_categories = sqlx::query_as::<_, YearCategory>(&format!(
+        "SELECT e.category_id, c.name AS category_name, c.color AS category_color,
+                SUM({NET_AMOUNT}) AS total, COUNT(*) AS count,
+                CASE WHEN $3 > 0 THEN SUM({NET_AMOUNT}) / $3 ELSE 0.0 END AS share
+         FROM expenses e
+         LEFT JOIN categories c ON c.id = e.category_id
+         WHERE e.deleted_at IS NULL AND e.date >= $1 AND e.date < $2
+         GROUP BY e.category_id
+         ORDER BY total DESC
+         LIMIT $4"
+    ))
+    .bind(&start)
+    .bind(&end)
+    .bind(total_spent)
+    .bind(TOP_CATEGORIES)
+    .fetch_all(pool)
+    .await?;
+
+    let (total_saved,): (f64,) = sqlx::query_as(
+        "SELECT COALESCE(SUM(s.amount), 0.0)
+         FROM savings_contributions s
+         JOIN savings_goals g ON g.id = s.goal_id AND g.deleted_at IS NULL
+         WHERE s.deleted_at IS NULL AND s.month >= $1 AND s.month <= $2",
+    )
+    .bind(&from)
+    .bind(&to)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(YearReview {
+        year,
+        total_spent,
+        total_income,
+        total_saved,
+        expense_count,
+        top_categories,
+        best_month,
+        worst_month,
+        goals_completed: goals_completed(pool, &from, &to).await?,
+        longest_habit_streak: longest_habit_run(pool, &from, &to).await?,
+        months,
+    })
+}
+
+/// Goals whose contributions reached the target in `from..=to`.
+async fn goals_completed(pool: &SqlitePool, from: &str, to: &str) -> Result<Vec<CompletedGoal>> {
+    let rows: Vec<(String, String, f64, String, f64)> = sqlx::query_as(
+        "SELECT g.id, g.name, g.target_amount, s.month, s.amount
+         FROM savings_goals g
+         JOIN savings_contributions s ON s.goal_id = g.id AND s.deleted_at IS NULL
+         WHERE g.deleted_at IS NULL AND g.target_amount > 0 AND s.month <= $1
+         ORDER BY g.id, s.month",
+    )
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+
+    let mut completed: Vec<CompletedGoal> = Vec::new();
+    let mut saved = 0.0;
+    let mut goal: Option<&str> = None;
+    for (id, name, target_amount, month, amount) in &rows {
+        if goal != Some(id.as_str()) {
+            goal = Some(id);
+            saved = 0.0;
+        }
+        let before = saved;
+        saved += amount;
+        if before < *target_amount && saved >= *target_amount && month.as_str() >= from {
+            completed.push(CompletedGoal {
+                goal_id: id.clone(),
+                name: name.clone(),
+                target_amount: *target_amount,
+                completed_month: month.clone(),
+            });
+        }
+    }
+    completed.sort_by(|a, b| a.completed_month.cmp(&b.completed_month));
+    Ok(completed)
+}
+
+/// The longest run of compliant months of any habit within `from..=to`.
+async fn longest_habit_run(pool: &SqlitePool, from: &str, to: &str) -> Result<Option<HabitRun>> {
+    let rows: Vec<(String, String, String, bool)> = sqlx::query_as(
+        "SELECT h.id, h.name, t.month, COALESCE(t.is_compliant, 0)
+         FROM habit_tracking t
+         JOIN habit_goals h ON h.id = t.habit_goal_id AND h.deleted_at IS NULL
+         WHERE t.deleted_at IS NULL AND t.month >= $1 AND t.month <= $2
+         ORDER BY h.id, t.month",
+    )
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+
+    let next_month = |month: &str| {
+        NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d")
+            .ok()
+            .map(|first| month_key(first + Months::new(1)))
+    };
+    let mut best: Option<HabitRun> = None;
+    let mut run: Option<HabitRun> = None;
+    for (id, name, month, compliant) in rows {
+        let continues = run.as_ref().is_some_and(|r| {
+            r.habit_goal_id == id && next_month(&r.to_month).as_deref() == Some(month.as_str())
+        });
+        run = match (compliant, continues, run) {
+            (false, ..) => None,
+            (true, true, Some(mut r)) => {
+                r.months += 1;
+                r.to_month = month;
+                Some(r)
+            }
+            (true, ..) => Some(HabitRun {
+                habit_goal_id: id,
+                name,
+                months: 1,
+                from_month: month.clone(),
+                to_month: month,
+            }),
+        };
+        if let Some(r) = &run {
+            if best.as_ref().is_none_or(|b| r.months > b.months) {
+                best = Some(r.clone());
+            }
+        }
+    }
+    Ok(best)
+}
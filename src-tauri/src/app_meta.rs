@@ -0,0 +1,93 @@
+//! Facts about this installation and its data, in the `app_meta` key-value
+//! table.
+//!
+//! Rust is the only writer. Migrations, sync and support tooling read the
+//! versions from here instead of each working them out on their own.
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::db::{new_id, now};
+use crate::error::Result;
+
+/// Bumped when stored values change meaning in a way the schema doesn't
+/// show, such as date formats or how records are keyed.
+pub const DATA_FORMAT_VERSION: u32 = 1;
+
+/// Name of the newest migration applied.
+const SCHEMA_VERSION: &str = "schema_version";
+const DATA_FORMAT: &str = "data_format_version";
+/// Random id for this installation, generated once.
+const INSTALL_ID: &str = "install_id";
+pub const LAST_BACKUP: &str = "last_backup_at";
+pub const LAST_MAINTENANCE: &str = "last_maintenance_at";
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct AppMeta {
+    pub app_version: String,
+    pub schema_version: Option<String>,
+    pub data_format_version: Option<u32>,
+    pub install_id: Option<String>,
+    pub last_backup_at: Option<String>,
+    pub last_maintenance_at: Option<String>,
+}
+
+pub async fn set(pool: &SqlitePool, key: &str, value: &str) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO app_meta (key, value, updated_at) VALUES ($1, $2, $3)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+    )
+    .bind(key)
+    .bind(value)
+    .bind(now())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get(pool: &SqlitePool, key: &str) -> Result<Option<String>> {
+    let row: Option<(String,)> = sqlx::query_as("SELECT value FROM app_meta WHERE key = $1")
+        .bind(key)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.map(|(value,)| value))
+}
+
+/// This installation's id, created if it doesn't exist yet.
+pub async fn install_id(pool: &SqlitePool) -> Result<Option<String>> {
+    if let Some(id) = get(pool, INSTALL_ID).await? {
+        return Ok(Some(id));
+    }
+    refresh(pool).await?;
+    get(pool, INSTALL_ID).await
+}
+
+/// Bring the versions up to date and create the install id on first run.
+pub async fn refresh(pool: &SqlitePool) -> Result<()> {
+    let (schema_version,): (Option<String>,) = sqlx::query_as("SELECT MAX(name) FROM _migrations")
+        .fetch_one(pool)
+        .await?;
+    if let Some(version) = schema_version {
+        set(pool, SCHEMA_VERSION, &version).await?;
+    }
+    set(pool, DATA_FORMAT, &DATA_FORMAT_VERSION.to_string()).await?;
+    sqlx::query("INSERT OR IGNORE INTO app_meta (key, value, updated_at) VALUES ($1, $2, $3)")
+        .bind(INSTALL_ID)
+        .bind(new_id())
+        .bind(now())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn load(pool: &SqlitePool) -> Result<AppMeta> {
+    refresh(pool).await?;
+    Ok(AppMeta {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_version: get(pool, SCHEMA_VERSION).await?,
+        data_format_version: get(pool, DATA_FORMAT).await?.and_then(|v| v.parse().ok()),
+        install_id: get(pool, INSTALL_ID).await?,
+        last_backup_at: get(pool, LAST_BACKUP).await?,
+        last_maintenance_at: get(pool, LAST_MAINTENANCE).await?,
+    })
+}
@@ -0,0 +1,378 @@
+//! Restoring a `.goaldy` archive.
+//!
+//! Import runs in two steps because the schema belongs to the TypeScript
+//! migration runner. `stage` decrypts the archive into a scratch folder and
+//! reports which migrations the archived database is missing; the frontend
+//! runs those on the staged copy; `apply` then copies the data into the live
+//! database, either replacing what is there or merging by `updated_at`.
+
+use std::collections::HashSet;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Connection, SqliteConnection, SqlitePool};
+use tauri::{AppHandle, Manager};
+
+use super::manifest::{FORMAT, FORMAT_VERSION};
+use super::{archive_error, Manifest, ATTACHMENTS_DIR, DATABASE_FILE, MANIFEST_FILE};
+use crate::db::{new_id, quote_ident};
+use crate::error::{Error, Result};
+use crate::{accounts, category_totals};
+
+/// Tables that describe this device or account rather than the user's data.
+const DEVICE_TABLES: &[&str] = &[
+    "_migrations",
+    "auth_state",
+    "sync_queue",
+    "sync_conflicts",
+    "sync_events",
+    "audit_log",
+    "partnership",
+    "households",
+    "household_members",
+    "household_expenses",
+    "household_budgets",
+    "sync_encryption",
+    "backup_targets",
+    "app_meta",
+    "entitlement_tokens",
+    "referral_attributions",
+    "telemetry_events",
+    "experiment_assignments",
+    "platform_notifications",
+    "orphaned_rows",
+    "sync_peers",
+    "webhooks",
+    "webhook_deliveries",
+    "bank_connections",
+    "bank_accounts",
+    "bank_transactions",
+    "bulk_undo",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Wipe local data and take the archive's.
+    Replace,
+    /// Keep local data; add archived rows, and take archived versions that
+    /// were updated more recently.
+    Merge,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct StagedArchive {
+    /// Pass back to `apply`.
+    pub id: String,
+    /// Staged database, for the frontend to migrate.
+    pub database_path: PathBuf,
+    pub manifest: Manifest,
+    /// Migrations applied locally but not in the archive, in order.
+    pub pending_migrations: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ArchiveImportReport {
+    pub mode: ImportMode,
+    pub tables: Vec<TableImport>,
+    pub attachments: usize,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct TableImport {
+    pub table: String,
+    /// Rows inserted or updated.
+    pub rows: u64,
+}
+
+fn staging_dir(app: &AppHandle, id: &str) -> Result<PathBuf> {
+    // Ids come back from the frontend; don't let one escape the folder.
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(Error::Validation("Invalid import id".to_string()));
+    }
+    Ok(app.path().app_cache_dir()?.join("imports").join(id))
+}
+
+async fn local_migrations(pool: &SqlitePool) -> Result<Vec<String>> {
+    let rows: Vec<(String,)> = sqlx::query_as("SELECT name FROM _migrations ORDER BY name ASC")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().map(|(name,)| name).collect())
+}
+
+/// Decrypt the archive at `path` and unpack it into a staging folder.
+pub async fn stage(
+    app: &AppHandle,
+    pool: &SqlitePool,
+    path: &Path,
+    passphrase: &str,
+) -> Result<StagedArchive> {
+    let plain = {
+        let path = path.to_path_buf();
+        let passphrase = passphrase.to_string();
+        tauri::async_runtime::spawn_blocking(move || super::decrypt(&path, &passphrase)).await??
+    };
+
+    let id = new_id();
+    let dir = staging_dir(app, &id)?;
+    let manifest = {
+        let dir = dir.clone();
+        tauri::async_runtime::spawn_blocking(move || unpack(&plain, &dir)).await??
+    };
+
+    let local = local_migrations(pool).await?;
+    let known: HashSet<&str> = local.iter().map(String::as_str).collect();
+    if manifest
+        .migrations
+        .iter()
+        .any(|m| !known.contains(m.as_str()))
+    {
+        std::fs::remove_dir_all(&dir)?;
+        return Err(Error::Validation(
+            "This archive was made by a newer version of Goaldy. Update the app and try again."
+                .to_string(),
+        ));
+    }
+    let archived: HashSet<&str> = manifest.migrations.iter().map(String::as_str).collect();
+    let pending_migrations = local
+        .iter()
+        .filter(|m| !archived.contains(m.as_str()))
+        .cloned()
+        .collect();
+
+    Ok(StagedArchive {
+        id,
+        database_path: dir.join(DATABASE_FILE),
+        manifest,
+        pending_migrations,
+    })
+}
+
+/// Check the manifest and extract the database and attachments into `dir`.
+fn unpack(plain: &[u8], dir: &Path) -> Result<Manifest> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(plain))
+        .map_err(|_| Error::Validation("Archive is damaged".to_string()))?;
+
+    let manifest: Manifest = {
+        let mut file = zip
+            .by_name(MANIFEST_FILE)
+            .map_err(|_| Error::Validation("Archive has no manifest".to_string()))?;
+        let mut json = Vec::new();
+        file.read_to_end(&mut json)?;
+        serde_json::from_slice(&json).map_err(archive_error)?
+    };
+    if manifest.format != FORMAT {
+        return Err(Error::Validation("Not a Goaldy archive".to_string()));
+    }
+    if manifest.format_version > FORMAT_VERSION {
+        return Err(Error::Validation(
+            "This archive was made by a newer version of Goaldy. Update the app and try again."
+                .to_string(),
+        ));
+    }
+
+    std::fs::create_dir_all(dir.join(ATTACHMENTS_DIR))?;
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i).map_err(archive_error)?;
+        if file.is_dir() {
+            continue;
+        }
+        // `enclosed_name` rejects absolute paths and `..`.
+        let Some(name) = file.enclosed_name() else {
+            return Err(Error::Validation("Archive is damaged".to_string()));
+        };
+        if name != Path::new(DATABASE_FILE) && !name.starts_with(ATTACHMENTS_DIR) {
+            continue;
+        }
+        let target = dir.join(&name);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut out = std::fs::File::create(&target)?;
+        std::io::copy(&mut file, &mut out)?;
+    }
+
+    if !dir.join(DATABASE_FILE).exists() {
+        return Err(Error::Validation("Archive has no database".to_string()));
+    }
+    Ok(manifest)
+}
+
+#[derive(sqlx::FromRow)]
+struct ColumnInfo {
+    name: String,
+    pk: i64,
+}
+
+async fn columns(
+    conn: &mut SqliteConnection,
+    schema: &str,
+    table: &str,
+) -> Result<Vec<ColumnInfo>> {
+    let columns = sqlx::query_as::<_, ColumnInfo>(&format!(
+        "SELECT name, pk FROM pragma_table_info('{table}', '{schema}')"
+    ))
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(columns)
+}
+
+/// Copy one table from `src` into `main`, limited to the columns both have.
+async fn import_table(conn: &mut SqliteConnection, table: &str, mode: ImportMode) -> Result<u64> {
+    let main_columns = columns(conn, "main", table).await?;
+    let src_columns = columns(conn, "src", table).await?;
+    let shared: Vec<&ColumnInfo> = main_columns
+        .iter()
+        .filter(|c| src_columns.iter().any(|s| s.name == c.name))
+        .collect();
+    if shared.is_empty() {
+        return Ok(0);
+    }
+
+    let t = quote_ident(table);
+    let column_list = shared
+        .iter()
+        .map(|c| quote_ident(&c.name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut rows = 0;
+
+    let keys: Vec<&str> = main_columns
+        .iter()
+        .filter(|c| c.pk > 0)
+        .map(|c| c.name.as_str())
+        .collect();
+    let has_updated_at = shared.iter().any(|c| c.name == "updated_at");
+    let keys_shared = !keys.is_empty() && keys.iter().all(|k| shared.iter().any(|c| c.name == *k));
+
+    match mode {
+        ImportMode::Replace => {
+            sqlx::query(&format!("DELETE FROM main.{t}"))
+                .execute(&mut *conn)
+                .await?;
+        }
+        ImportMode::Merge if has_updated_at && keys_shared => {
+            // Take archived rows that are newer than their local version.
+            let matches = keys
+                .iter()
+                .map(|k| format!("s.{0} = main.{t}.{0}", quote_ident(k)))
+                .collect::<Vec<_>>()
+                .join(" AND ");
+            rows += sqlx::query(&format!(
+                "UPDATE main.{t} SET ({column_list}) = (SELECT {column_list} FROM src.{t} s WHERE {matches})
+                 WHERE EXISTS (SELECT 1 FROM src.{t} s WHERE {matches} AND s.updated_at > main.{t}.updated_at)"
+            ))
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+        }
+        ImportMode::Merge => {}
+    }
+
+    rows += sqlx::query(&format!(
+        "INSERT OR IGNORE INTO main.{t} ({column_list}) SELECT {column_list} FROM src.{t}"
+    ))
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+    Ok(rows)
+}
+
+async fn import_tables(conn: &mut SqliteConnection, mode: ImportMode) -> Result<Vec<TableImport>> {
+    // The search index and its shadow tables are filled by triggers as the
+    // rows they index come in.
+    let tables: Vec<(String,)> = sqlx::query_as(
+        "SELECT name FROM src.sqlite_master
+         WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+           AND name NOT LIKE 'search_index%'
+           AND name IN (SELECT name FROM main.sqlite_master WHERE type = 'table')
+         ORDER BY name",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut tx = conn.begin().await?;
+    // Tables are copied in name order, not dependency order.
+    sqlx::query("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *tx)
+        .await?;
+    let mut report = Vec::new();
+    for (table,) in tables {
+        if DEVICE_TABLES.contains(&table.as_str()) {
+            continue;
+        }
+        let rows = import_table(&mut tx, &table, mode).await?;
+        report.push(TableImport { table, rows });
+    }
+    sqlx::query("INSERT INTO search_index(search_index) VALUES('rebuild')")
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(report)
+}
+
+/// Copy staged attachments into the app's attachments folder.
+fn import_attachments(staged: &Path, target: &Path, mode: ImportMode) -> Result<usize> {
+    if mode == ImportMode::Replace && target.exists() {
+        std::fs::remove_dir_all(target)?;
+    }
+    std::fs::create_dir_all(target)?;
+    let mut copied = 0;
+    for relative in super::list_files(staged)? {
+        let dest = target.join(&relative);
+        if dest.exists() {
+            continue;
+        }
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(staged.join(&relative), &dest)?;
+        copied += 1;
+    }
+    Ok(copied)
+}
+
+/// Copy a staged (and migrated) archive into the live database.
+pub async fn apply(
+    app: &AppHandle,
+    pool: &SqlitePool,
+    staging_id: &str,
+    mode: ImportMode,
+) -> Result<ArchiveImportReport> {
+    let dir = staging_dir(app, staging_id)?;
+    let database = dir.join(DATABASE_FILE);
+    if !database.exists() {
+        return Err(Error::Validation(
+            "Import expired, open the archive again".to_string(),
+        ));
+    }
+
+    // ATTACH is per connection, so everything runs on one. The empty key
+    // stops SQLCipher from applying the live database's key to the archive.
+    let mut conn = pool.acquire().await?;
+    sqlx::query("ATTACH DATABASE $1 AS src KEY ''")
+        .bind(database.to_string_lossy().as_ref())
+        .execute(&mut *conn)
+        .await?;
+    let tables = import_tables(&mut conn, mode).await;
+    sqlx::query("DETACH DATABASE src")
+        .execute(&mut *conn)
+        .await?;
+    let tables = tables?;
+
+    let attachments = import_attachments(
+        &dir.join(ATTACHMENTS_DIR),
+        &app.path().app_data_dir()?.join(ATTACHMENTS_DIR),
+        mode,
+    )?;
+    std::fs::remove_dir_all(&dir)?;
+    category_totals::rebuild(pool).await?;
+    accounts::recompute(pool).await?;
+
+    Ok(ArchiveImportReport {
+        mode,
+        tables,
+        attachments,
+    })
+}
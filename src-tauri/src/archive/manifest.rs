@@ -0,0 +1,53 @@
+//! `manifest.json`: what an archive contains and which schema its database
+//! is at, so an importer can tell whether it needs migrating.
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::db::now;
+use crate::error::Result;
+
+/// Identifies the file as a Goaldy archive.
+pub const FORMAT: &str = "goaldy-archive";
+
+/// Bumped when the archive layout (not the database schema) changes.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct Manifest {
+    pub format: String,
+    pub format_version: u32,
+    pub app_version: String,
+    /// Name of the newest migration applied to the archived database.
+    pub schema_version: Option<String>,
+    /// Every migration applied to the archived database, in order.
+    pub migrations: Vec<String>,
+    pub created_at: String,
+    pub database_size: u64,
+    pub attachment_count: usize,
+}
+
+impl Manifest {
+    pub(super) async fn collect(
+        pool: &SqlitePool,
+        database_size: u64,
+        attachment_count: usize,
+    ) -> Result<Self> {
+        let migrations: Vec<(String,)> =
+            sqlx::query_as("SELECT name FROM _migrations ORDER BY name ASC")
+                .fetch_all(pool)
+                .await?;
+        let migrations: Vec<String> = migrations.into_iter().map(|(name,)| name).collect();
+
+        Ok(Self {
+            format: FORMAT.to_string(),
+            format_version: FORMAT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            schema_version: migrations.last().cloned(),
+            migrations,
+            created_at: now(),
+            database_size,
+            attachment_count,
+        })
+    }
+}
@@ -0,0 +1,184 @@
+//! `.goaldy` archives: the canonical way to move all local data between
+//! devices without the cloud.
+//!
+//! An archive is an age file (passphrase/scrypt) wrapping a zip with
+//! `manifest.json`, a consistent copy of `goaldy.db` and the `attachments/`
+//! folder. The zip is assembled in memory, which is fine for a personal
+//! budget database.
+
+pub mod import;
+mod manifest;
+
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+
+use age::secrecy::SecretString;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Manager};
+use zip::write::SimpleFileOptions;
+
+use crate::db::new_id;
+use crate::error::{Error, Result};
+
+pub use manifest::Manifest;
+
+/// File extension of archives, without the dot.
+pub const EXTENSION: &str = "goaldy";
+
+/// Folder in the app data dir holding attachment files.
+pub const ATTACHMENTS_DIR: &str = "attachments";
+
+const MANIFEST_FILE: &str = "manifest.json";
+const DATABASE_FILE: &str = "goaldy.db";
+
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+fn archive_error(e: impl std::fmt::Display) -> Error {
+    Error::Validation(format!("archive error: {e}"))
+}
+
+pub(crate) fn check_passphrase(passphrase: &str) -> Result<()> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(Error::Validation(format!(
+            "Passphrase must be at least {MIN_PASSPHRASE_LEN} characters"
+        )));
+    }
+    Ok(())
+}
+
+/// Copy the live database to `dest` without blocking writers for long.
+/// An encrypted database is copied out in plaintext, so the archive opens
+/// anywhere; it is protected by the archive's own passphrase instead.
+async fn snapshot_database(pool: &SqlitePool, dest: &Path) -> Result<()> {
+    if crate::encryption::is_active() {
+        return crate::encryption::export(&mut *pool.acquire().await?, dest, "").await;
+    }
+    sqlx::query("VACUUM INTO $1")
+        .bind(dest.to_string_lossy().as_ref())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Files under `dir`, as paths relative to it.
+fn list_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if !dir.exists() {
+        return Ok(files);
+    }
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in std::fs::read_dir(&current)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if let Ok(relative) = path.strip_prefix(dir) {
+                files.push(relative.to_path_buf());
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Zip path for a file relative to the attachments folder.
+fn attachment_entry(relative: &Path) -> String {
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    format!("{ATTACHMENTS_DIR}/{}", parts.join("/"))
+}
+
+/// Write an encrypted archive of everything on this device to `path`.
+/// Returns the manifest that was stored in it.
+pub async fn export(
+    app: &AppHandle,
+    pool: &SqlitePool,
+    path: &Path,
+    passphrase: &str,
+) -> Result<Manifest> {
+    check_passphrase(passphrase)?;
+
+    let scratch = app.path().app_cache_dir()?;
+    std::fs::create_dir_all(&scratch)?;
+    let db_copy = scratch.join(format!("archive-{}.db", new_id()));
+    snapshot_database(pool, &db_copy).await?;
+    let database = std::fs::read(&db_copy);
+    std::fs::remove_file(&db_copy)?;
+    let database = database?;
+
+    let attachments_dir = app.path().app_data_dir()?.join(ATTACHMENTS_DIR);
+    let attachments = list_files(&attachments_dir)?;
+    let manifest = Manifest::collect(pool, database.len() as u64, attachments.len()).await?;
+
+    let plain = tauri::async_runtime::spawn_blocking({
+        let manifest = manifest.clone();
+        move || build_zip(&manifest, &database, &attachments_dir, &attachments)
+    })
+    .await??;
+
+    let path = path.to_path_buf();
+    let passphrase = SecretString::from(passphrase.to_string());
+    tauri::async_runtime::spawn_blocking(move || encrypt_to(&path, &plain, passphrase)).await??;
+
+    Ok(manifest)
+}
+
+fn build_zip(
+    manifest: &Manifest,
+    database: &[u8],
+    attachments_dir: &Path,
+    attachments: &[PathBuf],
+) -> Result<Vec<u8>> {
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default();
+
+    let manifest_json = serde_json::to_vec_pretty(manifest).map_err(archive_error)?;
+    zip.start_file(MANIFEST_FILE, options)
+        .map_err(archive_error)?;
+    zip.write_all(&manifest_json)?;
+
+    zip.start_file(DATABASE_FILE, options)
+        .map_err(archive_error)?;
+    zip.write_all(database)?;
+
+    for relative in attachments {
+        // Attachments are mostly photos that are already compressed.
+        let stored = options.compression_method(zip::CompressionMethod::Stored);
+        zip.start_file(attachment_entry(relative), stored)
+            .map_err(archive_error)?;
+        zip.write_all(&std::fs::read(attachments_dir.join(relative))?)?;
+    }
+
+    Ok(zip.finish().map_err(archive_error)?.into_inner())
+}
+
+fn encrypt_to(path: &Path, plain: &[u8], passphrase: SecretString) -> Result<()> {
+    // Write next to the target first so a failed export never leaves a
+    // truncated archive under the requested name.
+    let partial = path.with_extension(format!("{EXTENSION}.partial"));
+    let file = std::fs::File::create(&partial)?;
+    let mut writer = age::Encryptor::with_user_passphrase(passphrase).wrap_output(file)?;
+    writer.write_all(plain)?;
+    writer.finish()?.sync_all()?;
+    std::fs::rename(&partial, path)?;
+    Ok(())
+}
+
+/// Decrypt an archive into the plain zip bytes.
+fn decrypt(path: &Path, passphrase: &str) -> Result<Vec<u8>> {
+    let file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let decryptor = age::Decryptor::new_buffered(file)
+        .map_err(|_| Error::Validation("Not a Goaldy archive".to_string()))?;
+    if !decryptor.is_scrypt() {
+        return Err(Error::Validation("Not a Goaldy archive".to_string()));
+    }
+    let identity = age::scrypt::Identity::new(SecretString::from(passphrase.to_string()));
+    let mut reader = decryptor
+        .decrypt(std::iter::once(&identity as &dyn age::Identity))
+        .map_err(|_| Error::Validation("Wrong passphrase".to_string()))?;
+    let mut plain = Vec::new();
+    reader.read_to_end(&mut plain)?;
+    Ok(plain)
+}
@@ -0,0 +1,303 @@
+//! Saving, listing and removing the files attached to an expense.
+//!
+//! Photos are shrunk before they are stored: turned upright, scaled down to
+//! `MAX_DIMENSION` on the long edge and re-encoded as JPEG, which keeps a
+//! receipt legible at a fraction of a camera's file size. PDFs and formats
+//! we can't decode (HEIC) are stored as they are.
+//!
+//! Deleting an expense marks its attachments deleted (a trigger does it, so
+//! deletes made by the frontend count too). Once the expense is purged from
+//! the trash, `clean_up` removes the files that never left this device, and
+//! any file without a row; those with a remote copy wait for the sync pass
+//! to delete that first.
+
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageDecoder, ImageReader};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::AppHandle;
+
+use super::{mime_type, sha256_hex, SyncStatus, OF_TRASHED_EXPENSE};
+use crate::db::{new_id, now};
+use crate::error::{Error, Result};
+
+/// Longest edge of a stored photo, in pixels.
+pub const MAX_DIMENSION: u32 = 2048;
+
+const JPEG_QUALITY: u8 = 80;
+
+/// Files younger than this are never treated as strays: `save` writes the
+/// file just before its row.
+const STRAY_GRACE: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Largest file accepted, before shrinking.
+const MAX_FILE_BYTES: u64 = 25 * 1024 * 1024;
+
+const ACCEPTED: &[&str] = &["jpg", "jpeg", "png", "webp", "heic", "pdf"];
+
+/// Formats shrunk on the way in.
+const SHRUNK: &[&str] = &["jpg", "jpeg", "png", "webp"];
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct Attachment {
+    pub id: String,
+    pub expense_id: String,
+    pub file_name: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub sha256: String,
+    pub sync_status: String,
+    pub created_at: String,
+    /// Absolute path of the file on this device.
+    #[sqlx(skip)]
+    pub path: PathBuf,
+    /// False while a file added on another device is still downloading.
+    #[sqlx(skip)]
+    pub available: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, specta::Type)]
+pub struct CleanupReport {
+    /// Attachments of deleted expenses marked for removal.
+    pub marked: u64,
+    pub removed_files: usize,
+}
+
+const SELECT_ATTACHMENT: &str =
+    "SELECT id, expense_id, file_name, mime_type, size_bytes, sha256, sync_status, created_at
+     FROM attachments";
+
+fn locate(dir: &Path, mut attachment: Attachment) -> Attachment {
+    attachment.path = dir.join(&attachment.file_name);
+    attachment.available = attachment.path.is_file();
+    attachment
+}
+
+/// Blocking. Upright, scaled down and re-encoded as JPEG; `None` when the
+/// original is already smaller.
+fn shrink(data: &[u8]) -> Result<Option<Vec<u8>>> {
+    let unreadable = |e: image::ImageError| Error::Validation(format!("Unreadable image: {e}"));
+    let mut decoder = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()?
+        .into_decoder()
+        .map_err(unreadable)?;
+    let orientation = decoder.orientation().map_err(unreadable)?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(unreadable)?;
+    image.apply_orientation(orientation);
+
+    let resized = image.width().max(image.height()) > MAX_DIMENSION;
+    if resized {
+        image = image.resize(MAX_DIMENSION, MAX_DIMENSION, FilterType::Lanczos3);
+    }
+    let mut out = Vec::new();
+    JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY)
+        .encode_image(&image.to_rgb8())
+        .map_err(|e| Error::Validation(format!("Could not compress image: {e}")))?;
+    Ok((resized || out.len() < data.len()).then_some(out))
+}
+
+/// Copy the file at `source` into the attachments folder for `expense_id`.
+pub async fn save(
+    app: &AppHandle,
+    pool: &SqlitePool,
+    expense_id: &str,
+    source: &Path,
+) -> Result<Attachment> {
+    let (exists,): (bool,) = sqlx::query_as(
+        "SELECT EXISTS (SELECT 1 FROM expenses WHERE id = $1 AND deleted_at IS NULL)",
+    )
+    .bind(expense_id)
+    .fetch_one(pool)
+    .await?;
+    if !exists {
+        return Err(Error::Validation(format!("Expense {expense_id} not found")));
+    }
+
+    let extension = source
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .filter(|e| ACCEPTED.contains(&e.as_str()))
+        .ok_or_else(|| {
+            Error::Validation("Attach a photo (JPEG, PNG, WebP, HEIC) or a PDF".to_string())
+        })?;
+    if std::fs::metadata(source)?.len() > MAX_FILE_BYTES {
+        return Err(Error::Validation(format!(
+            "Attachments can be at most {} MB",
+            MAX_FILE_BYTES / 1024 / 1024
+        )));
+    }
+
+    let (data, extension) = {
+        let source = source.to_path_buf();
+        tauri::async_runtime::spawn_blocking(move || -> Result<(Vec<u8>, String)> {
+            let data = std::fs::read(&source)?;
+            if !SHRUNK.contains(&extension.as_str()) {
+                return Ok((data, extension));
+            }
+            Ok(match shrink(&data)? {
+                Some(jpeg) => (jpeg, "jpg".to_string()),
+                None => (data, extension),
+            })
+        })
+        .await??
+    };
+
+    let dir = super::dir(app)?;
+    std::fs::create_dir_all(&dir)?;
+    let id = new_id();
+    let file_name = format!("{id}.{extension}");
+    std::fs::write(dir.join(&file_name), &data)?;
+
+    let now = now();
+    let inserted = sqlx::query(
+        "INSERT INTO attachments
+            (id, expense_id, file_name, mime_type, size_bytes, sha256, sync_status,
+             created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $8)",
+    )
+    .bind(&id)
+    .bind(expense_id)
+    .bind(&file_name)
+    .bind(mime_type(&extension))
+    .bind(data.len() as i64)
+    .bind(sha256_hex(&data))
+    .bind(SyncStatus::Local.as_str())
+    .bind(&now)
+    .execute(pool)
+    .await;
+    if let Err(e) = inserted {
+        std::fs::remove_file(dir.join(&file_name))?;
+        return Err(e.into());
+    }
+
+    let attachment = sqlx::query_as::<_, Attachment>(&format!("{SELECT_ATTACHMENT} WHERE id = $1"))
+        .bind(&id)
+        .fetch_one(pool)
+        .await?;
+    Ok(locate(&dir, attachment))
+}
+
+/// The attachments of `expense_id`, oldest first.
+pub async fn for_expense(
+    app: &AppHandle,
+    pool: &SqlitePool,
+    expense_id: &str,
+) -> Result<Vec<Attachment>> {
+    let dir = super::dir(app)?;
+    let attachments = sqlx::query_as::<_, Attachment>(&format!(
+        "{SELECT_ATTACHMENT} WHERE expense_id = $1 AND deleted_at IS NULL ORDER BY created_at"
+    ))
+    .bind(expense_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(attachments.into_iter().map(|a| locate(&dir, a)).collect())
+}
+
+/// Delete an attachment. One that was never uploaded goes right away;
+/// otherwise the next sync pass removes the remote copy and then the file.
+pub async fn remove(app: &AppHandle, pool: &SqlitePool, id: &str) -> Result<()> {
+    let row: Option<(String, Option<String>)> =
+        sqlx::query_as("SELECT file_name, remote_path FROM attachments WHERE id = $1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+    let Some((file_name, remote_path)) = row else {
+        return Err(Error::Validation(format!("Attachment {id} not found")));
+    };
+    if remote_path.is_some() {
+        let now = now();
+        sqlx::query("UPDATE attachments SET deleted_at = $1, updated_at = $1 WHERE id = $2")
+            .bind(&now)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        return Ok(());
+    }
+    remove_file(&super::dir(app)?.join(file_name))?;
+    sqlx::query("DELETE FROM attachments WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+fn remove_file(path: &Path) -> Result<bool> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Remove the files of deleted expenses that exist only here, and files
+/// no row refers to.
+pub async fn clean_up(app: &AppHandle, pool: &SqlitePool) -> Result<CleanupReport> {
+    let mut report = CleanupReport::default();
+    let now = now();
+    // Deleted before the trigger existed. An attachment whose expense
+    // hasn't arrived from another device yet is left alone.
+    report.marked = sqlx::query(
+        "UPDATE attachments SET deleted_at = $1, updated_at = $1
+         WHERE deleted_at IS NULL
+           AND (expense_id IN (SELECT id FROM expenses WHERE deleted_at IS NOT NULL)
+                OR (remote_path IS NULL
+                    AND expense_id NOT IN (SELECT id FROM expenses)))",
+    )
+    .bind(&now)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    let dir = super::dir(app)?;
+    let local_only: Vec<(String, String)> = sqlx::query_as(&format!(
+        "SELECT id, file_name FROM attachments
+         WHERE deleted_at IS NOT NULL AND remote_path IS NULL AND NOT {OF_TRASHED_EXPENSE}"
+    ))
+    .fetch_all(pool)
+    .await?;
+    for (id, file_name) in local_only {
+        if remove_file(&dir.join(&file_name))? {
+            report.removed_files += 1;
+        }
+        sqlx::query("DELETE FROM attachments WHERE id = $1")
+            .bind(&id)
+            .execute(pool)
+            .await?;
+    }
+
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Ok(report);
+    };
+    let known: std::collections::HashSet<String> =
+        sqlx::query_as::<_, (String,)>("SELECT file_name FROM attachments")
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(|(name,)| name)
+            .collect();
+    for entry in entries {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let recent = entry
+            .metadata()?
+            .modified()?
+            .elapsed()
+            .is_ok_and(|age| age < STRAY_GRACE);
+        if recent {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let name = name.strip_suffix(".partial").unwrap_or(&name);
+        if !known.contains(name) && remove_file(&entry.path())? {
+            report.removed_files += 1;
+        }
+    }
+    Ok(report)
+}
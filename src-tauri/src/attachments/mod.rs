@@ -0,0 +1,73 @@
+//! Files attached to expenses, such as receipt photos.
+//!
+//! The files live in the attachments folder of the app data dir; the
+//! `attachments` table records each one's checksum and how far it got in
+//! syncing with the copy in Supabase Storage.
+
+pub mod files;
+pub mod storage;
+pub mod sync;
+
+use std::path::PathBuf;
+
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+
+use crate::archive::ATTACHMENTS_DIR;
+use crate::error::Result;
+
+/// Where an attachment stands relative to its remote copy
+/// (`attachments.sync_status`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncStatus {
+    /// Only on this device, waiting to be uploaded.
+    Local,
+    /// Partly uploaded; `upload_url` points at the resumable upload.
+    Uploading,
+    Synced,
+    /// Added on another device, not downloaded yet.
+    Remote,
+    /// Partly downloaded into a `.partial` file.
+    Downloading,
+    /// Gave up: the file changed on disk or failed its checksum.
+    Failed,
+}
+
+impl SyncStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SyncStatus::Local => "local",
+            SyncStatus::Uploading => "uploading",
+            SyncStatus::Synced => "synced",
+            SyncStatus::Remote => "remote",
+            SyncStatus::Downloading => "downloading",
+            SyncStatus::Failed => "failed",
+        }
+    }
+}
+
+/// Attachments of an expense still in the trash. They are kept, files and
+/// remote copies alike, until the expense is restored or purged.
+const OF_TRASHED_EXPENSE: &str =
+    "expense_id IN (SELECT id FROM expenses WHERE deleted_at IS NOT NULL)";
+
+pub fn dir(app: &AppHandle) -> Result<PathBuf> {
+    Ok(app.path().app_data_dir()?.join(ATTACHMENTS_DIR))
+}
+
+/// Lowercase hex SHA-256, as stored in `attachments.sha256`.
+pub fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// MIME type for the file extensions attachments come in.
+pub fn mime_type(extension: &str) -> &'static str {
+    match extension.to_ascii_lowercase().as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "webp" => "image/webp",
+        "heic" => "image/heic",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
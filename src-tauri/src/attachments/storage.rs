@@ -0,0 +1,270 @@
+//! Blocking client for the Supabase Storage bucket holding attachments.
+//!
+//! Uploads go through Storage's TUS endpoint, so an upload cut off halfway
+//! continues from the last chunk the server acknowledged; downloads resume
+//! with a `Range` request. With a rate limit set, both are paced to it.
+
+use std::fs::OpenOptions;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+use serde::Deserialize;
+
+use crate::auth::Session;
+use crate::backend::BackendConfig;
+use crate::error::{Error, Result};
+
+pub const BUCKET: &str = "attachments";
+
+/// Supabase requires every TUS chunk but the last to be exactly 6 MiB.
+pub const CHUNK_SIZE: usize = 6 * 1024 * 1024;
+
+const TUS_VERSION: &str = "1.0.0";
+
+const LIST_PAGE_SIZE: usize = 1000;
+
+/// Largest read between two pacing pauses.
+const PACE_SLICE: usize = 16 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct RemoteObject {
+    /// Name within the listed folder.
+    pub name: String,
+    pub size: u64,
+}
+
+#[derive(Deserialize)]
+struct ListEntry {
+    name: String,
+    /// Null for folders.
+    id: Option<String>,
+    metadata: Option<ListMetadata>,
+}
+
+#[derive(Deserialize)]
+struct ListMetadata {
+    size: Option<u64>,
+}
+
+#[derive(Clone)]
+pub struct Storage {
+    base_url: String,
+    anon_key: String,
+    access_token: String,
+    /// Bytes per second, or `None` for full speed.
+    rate_limit: Option<u64>,
+    agent: ureq::Agent,
+}
+
+fn remote_error(e: ureq::Error) -> Error {
+    match e {
+        ureq::Error::Status(code, response) => {
+            let body = response.into_string().unwrap_or_default();
+            Error::Remote(format!("storage returned {code}: {}", body.trim()))
+        }
+        ureq::Error::Transport(e) => Error::Remote(format!("storage unreachable: {e}")),
+    }
+}
+
+impl Storage {
+    pub fn new(config: &BackendConfig, session: &Session, rate_limit: Option<u64>) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(Duration::from_secs(10))
+            .timeout_read(Duration::from_secs(60))
+            .timeout_write(Duration::from_secs(60))
+            .build();
+        Self {
+            base_url: config.url.clone(),
+            anon_key: config.anon_key.clone(),
+            access_token: session.access_token.clone(),
+            rate_limit,
+            agent,
+        }
+    }
+
+    fn request(&self, method: &str, url: &str) -> ureq::Request {
+        self.agent
+            .request(method, url)
+            .set("apikey", &self.anon_key)
+            .set("Authorization", &format!("Bearer {}", self.access_token))
+    }
+
+    fn paced<R: Read>(&self, inner: R) -> Paced<R> {
+        Paced {
+            inner,
+            bytes_per_sec: self.rate_limit,
+            started: Instant::now(),
+            read: 0,
+        }
+    }
+
+    /// Files directly inside `folder`.
+    pub fn list(&self, folder: &str) -> Result<Vec<RemoteObject>> {
+        let url = format!("{}/storage/v1/object/list/{BUCKET}", self.base_url);
+        let mut objects = Vec::new();
+        let mut offset = 0;
+        loop {
+            let body = serde_json::json!({
+                "prefix": folder,
+                "limit": LIST_PAGE_SIZE,
+                "offset": offset,
+                "sortBy": { "column": "name", "order": "asc" },
+            });
+            let response = self
+                .request("POST", &url)
+                .set("Content-Type", "application/json")
+                .send_string(&body.to_string())
+                .map_err(remote_error)?;
+            let page: Vec<ListEntry> = serde_json::from_reader(response.into_reader())
+                .map_err(|e| Error::Remote(format!("unexpected storage listing: {e}")))?;
+
+            let count = page.len();
+            objects.extend(
+                page.into_iter()
+                    .filter(|e| e.id.is_some())
+                    .map(|e| RemoteObject {
+                        name: e.name,
+                        size: e.metadata.and_then(|m| m.size).unwrap_or(0),
+                    }),
+            );
+            if count < LIST_PAGE_SIZE {
+                return Ok(objects);
+            }
+            offset += count;
+        }
+    }
+
+    /// Start a resumable upload and return its URL.
+    pub fn create_upload(&self, path: &str, size: u64, mime_type: &str) -> Result<String> {
+        let metadata = [
+            ("bucketName", BUCKET),
+            ("objectName", path),
+            ("contentType", mime_type),
+        ]
+        .map(|(key, value)| format!("{key} {}", BASE64_STANDARD.encode(value)))
+        .join(",");
+
+        let response = self
+            .request(
+                "POST",
+                &format!("{}/storage/v1/upload/resumable", self.base_url),
+            )
+            .set("Tus-Resumable", TUS_VERSION)
+            .set("Upload-Length", &size.to_string())
+            .set("Upload-Metadata", &metadata)
+            .set("x-upsert", "true")
+            .call()
+            .map_err(remote_error)?;
+        let location = response
+            .header("Location")
+            .ok_or_else(|| Error::Remote("storage returned no upload URL".to_string()))?;
+        Ok(if location.starts_with('/') {
+            format!("{}{location}", self.base_url)
+        } else {
+            location.to_string()
+        })
+    }
+
+    /// How much of an upload the server has, or `None` if it expired.
+    pub fn upload_offset(&self, upload_url: &str) -> Result<Option<u64>> {
+        match self
+            .request("HEAD", upload_url)
+            .set("Tus-Resumable", TUS_VERSION)
+            .call()
+        {
+            Ok(response) => Ok(response
+                .header("Upload-Offset")
+                .and_then(|offset| offset.parse().ok())),
+            Err(ureq::Error::Status(404 | 410, _)) => Ok(None),
+            Err(e) => Err(remote_error(e)),
+        }
+    }
+
+    /// Send `chunk` at `offset` and return the offset the server confirms.
+    pub fn upload_chunk(&self, upload_url: &str, offset: u64, chunk: &[u8]) -> Result<u64> {
+        let response = self
+            .request("PATCH", upload_url)
+            .set("Tus-Resumable", TUS_VERSION)
+            .set("Upload-Offset", &offset.to_string())
+            .set("Content-Type", "application/offset+octet-stream")
+            .set("Content-Length", &chunk.len().to_string())
+            .send(self.paced(chunk))
+            .map_err(remote_error)?;
+        response
+            .header("Upload-Offset")
+            .and_then(|offset| offset.parse().ok())
+            .ok_or_else(|| Error::Remote("storage did not confirm the upload offset".to_string()))
+    }
+
+    /// Download `path` into `dest`, keeping the first `from` bytes already
+    /// there. Starts over if the server ignores the range.
+    pub fn download(&self, path: &str, dest: &Path, from: u64) -> Result<()> {
+        let url = format!(
+            "{}/storage/v1/object/authenticated/{BUCKET}/{path}",
+            self.base_url
+        );
+        let mut request = self.request("GET", &url);
+        if from > 0 {
+            request = request.set("Range", &format!("bytes={from}-"));
+        }
+        let response = match request.call() {
+            Ok(response) => response,
+            // Everything is already here.
+            Err(ureq::Error::Status(416, _)) if from > 0 => return Ok(()),
+            Err(e) => return Err(remote_error(e)),
+        };
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(dest)?;
+        if response.status() == 206 {
+            file.set_len(from)?;
+            file.seek(SeekFrom::Start(from))?;
+        } else {
+            file.set_len(0)?;
+        }
+        io::copy(&mut self.paced(response.into_reader()), &mut file)?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    pub fn delete(&self, path: &str) -> Result<()> {
+        let body = serde_json::json!({ "prefixes": [path] });
+        self.request(
+            "DELETE",
+            &format!("{}/storage/v1/object/{BUCKET}", self.base_url),
+        )
+        .set("Content-Type", "application/json")
+        .send_string(&body.to_string())
+        .map_err(remote_error)?;
+        Ok(())
+    }
+}
+
+/// A reader drained no faster than `bytes_per_sec`.
+struct Paced<R> {
+    inner: R,
+    bytes_per_sec: Option<u64>,
+    started: Instant,
+    read: u64,
+}
+
+impl<R: Read> Read for Paced<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some(rate) = self.bytes_per_sec else {
+            return self.inner.read(buf);
+        };
+        let len = buf.len().min(PACE_SLICE);
+        let n = self.inner.read(&mut buf[..len])?;
+        self.read += n as u64;
+        let due = Duration::from_secs_f64(self.read as f64 / rate as f64);
+        if let Some(wait) = due.checked_sub(self.started.elapsed()) {
+            std::thread::sleep(wait);
+        }
+        Ok(n)
+    }
+}
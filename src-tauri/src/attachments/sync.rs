@@ -0,0 +1,414 @@
+//! Moving attachment files between this device and Supabase Storage.
+//!
+//! A pass runs while online and signed in: removals first, then uploads of
+//! files added here, then downloads of files added elsewhere. On a metered
+//! connection transfers are throttled and large uploads wait for a better
+//! one. Progress is written to `attachments` after every chunk, so a
+//! transfer cut off by a lost connection or a closed app picks up where it
+//! stopped on the next pass. A file only counts as synced once its SHA-256
+//! matches: before an upload for the local copy, after a download for the
+//! received one.
+//!
+//! Objects are named `<user_id>/<expense_id>.<attachment_id>.<sha256>.<ext>`
+//! so another device can rebuild the row from a bucket listing alone.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Manager};
+
+use super::storage::{Storage, CHUNK_SIZE};
+use super::{mime_type, sha256_hex, SyncStatus, OF_TRASHED_EXPENSE};
+use crate::auth;
+use crate::backend::Backend;
+use crate::connectivity::Connectivity;
+use crate::db::{now, Db};
+use crate::error::Result;
+use crate::sync::policy;
+
+/// Transfer rate on a metered connection, in bytes per second.
+const METERED_BYTES_PER_SEC: u64 = 256 * 1024;
+
+/// Uploads larger than this wait for an unmetered connection.
+const LARGE_UPLOAD_BYTES: i64 = 1024 * 1024;
+
+/// Set while a pass runs, so the job and a manual trigger never overlap.
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+struct RunningGuard;
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        RUNNING.store(false, Ordering::Release);
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, specta::Type)]
+pub struct SyncReport {
+    pub uploaded: usize,
+    pub downloaded: usize,
+    pub deleted: usize,
+    pub failed: usize,
+    /// Large uploads held back on a metered connection.
+    pub deferred: usize,
+}
+
+#[derive(sqlx::FromRow)]
+struct AttachmentRow {
+    id: String,
+    expense_id: String,
+    file_name: String,
+    mime_type: String,
+    sha256: String,
+    sync_status: String,
+    size_bytes: i64,
+    remote_path: Option<String>,
+    upload_url: Option<String>,
+}
+
+async fn blocking<T, F>(f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(f).await?
+}
+
+/// Run one pass. Does nothing while offline, on a connection the sync
+/// policy rules out, signed out, without a configured backend or while
+/// another pass is still going.
+pub async fn run(app: &AppHandle) -> Result<SyncReport> {
+    let mut report = SyncReport::default();
+    let Some(config) = app.state::<Backend>().config() else {
+        return Ok(report);
+    };
+    let connectivity = app.state::<Connectivity>();
+    if !connectivity.is_online() || !policy::allows_sync(app).await? {
+        return Ok(report);
+    }
+    let db = app.state::<Db>();
+    let pool = db.pool();
+    let Some(session) = auth::session(pool).await? else {
+        return Ok(report);
+    };
+    if RUNNING.swap(true, Ordering::Acquire) {
+        return Ok(report);
+    }
+    let _guard = RunningGuard;
+
+    let unmetered = connectivity.is_unmetered();
+    let rate_limit = (!unmetered).then_some(METERED_BYTES_PER_SEC);
+    let storage = Storage::new(&config, &session, rate_limit);
+    let dir = super::dir(app)?;
+    std::fs::create_dir_all(&dir)?;
+
+    remove_deleted(pool, &storage, &dir, &mut report).await?;
+
+    for row in select(pool, &[SyncStatus::Local, SyncStatus::Uploading]).await? {
+        if !unmetered && row.size_bytes > LARGE_UPLOAD_BYTES {
+            report.deferred += 1;
+            continue;
+        }
+        let id = row.id.clone();
+        match upload(pool, &storage, &dir, &session.user_id, row).await {
+            Ok(true) => report.uploaded += 1,
+            Ok(false) => report.failed += 1,
+            Err(e) => {
+                record_error(pool, &id, &e.to_string()).await?;
+                report.failed += 1;
+            }
+        }
+    }
+
+    discover(pool, &storage, &session.user_id).await?;
+
+    for row in select(pool, &[SyncStatus::Remote, SyncStatus::Downloading]).await? {
+        let id = row.id.clone();
+        match download(pool, &storage, &dir, row).await {
+            Ok(true) => report.downloaded += 1,
+            Ok(false) => report.failed += 1,
+            Err(e) => {
+                record_error(pool, &id, &e.to_string()).await?;
+                report.failed += 1;
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+async fn select(pool: &SqlitePool, statuses: &[SyncStatus; 2]) -> Result<Vec<AttachmentRow>> {
+    Ok(sqlx::query_as::<_, AttachmentRow>(
+        "SELECT id, expense_id, file_name, mime_type, sha256, sync_status,
+                size_bytes, remote_path, upload_url
+         FROM attachments
+         WHERE sync_status IN ($1, $2) AND deleted_at IS NULL
+         ORDER BY created_at ASC",
+    )
+    .bind(statuses[0].as_str())
+    .bind(statuses[1].as_str())
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Note a transient failure; the row keeps its status and is retried.
+async fn record_error(pool: &SqlitePool, id: &str, error: &str) -> Result<()> {
+    sqlx::query("UPDATE attachments SET sync_error = $1 WHERE id = $2")
+        .bind(error)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Stop retrying a file that can't be synced as it is.
+async fn mark_failed(pool: &SqlitePool, id: &str, reason: &str) -> Result<()> {
+    sqlx::query(
+        "UPDATE attachments SET sync_status = $1, sync_error = $2, updated_at = $3 WHERE id = $4",
+    )
+    .bind(SyncStatus::Failed.as_str())
+    .bind(reason)
+    .bind(now())
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+fn remote_path(user_id: &str, row: &AttachmentRow) -> String {
+    let extension = Path::new(&row.file_name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("bin");
+    format!(
+        "{user_id}/{}.{}.{}.{extension}",
+        row.expense_id, row.id, row.sha256
+    )
+}
+
+/// Delete the remote copies of removed attachments, then the rows.
+async fn remove_deleted(
+    pool: &SqlitePool,
+    storage: &Storage,
+    dir: &Path,
+    report: &mut SyncReport,
+) -> Result<()> {
+    let rows: Vec<(String, String, Option<String>)> = sqlx::query_as(&format!(
+        "SELECT id, file_name, remote_path FROM attachments
+         WHERE deleted_at IS NOT NULL AND NOT {OF_TRASHED_EXPENSE}"
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    for (id, file_name, remote_path) in rows {
+        if let Some(path) = remote_path {
+            let storage = storage.clone();
+            if let Err(e) = blocking(move || storage.delete(&path)).await {
+                record_error(pool, &id, &e.to_string()).await?;
+                report.failed += 1;
+                continue;
+            }
+        }
+        match std::fs::remove_file(dir.join(&file_name)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        sqlx::query("DELETE FROM attachments WHERE id = $1")
+            .bind(&id)
+            .execute(pool)
+            .await?;
+        report.deleted += 1;
+    }
+    Ok(())
+}
+
+/// Upload one file, resuming an earlier attempt if the server still has it.
+/// Returns false if the file was marked failed.
+async fn upload(
+    pool: &SqlitePool,
+    storage: &Storage,
+    dir: &Path,
+    user_id: &str,
+    row: AttachmentRow,
+) -> Result<bool> {
+    let data = match std::fs::read(dir.join(&row.file_name)) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            mark_failed(pool, &row.id, "File is missing").await?;
+            return Ok(false);
+        }
+        Err(e) => return Err(e.into()),
+    };
+    if sha256_hex(&data) != row.sha256 {
+        mark_failed(pool, &row.id, "File changed since it was attached").await?;
+        return Ok(false);
+    }
+    let size = data.len() as u64;
+
+    let mut resumed = None;
+    if let Some(url) = row.upload_url.clone() {
+        let storage = storage.clone();
+        let head_url = url.clone();
+        if let Some(offset) = blocking(move || storage.upload_offset(&head_url)).await? {
+            resumed = Some((url, offset));
+        }
+    }
+    let (url, mut offset) = match resumed {
+        Some(resumed) => resumed,
+        None => {
+            let path = row
+                .remote_path
+                .clone()
+                .unwrap_or_else(|| remote_path(user_id, &row));
+            let url = {
+                let storage = storage.clone();
+                let path = path.clone();
+                let mime_type = row.mime_type.clone();
+                blocking(move || storage.create_upload(&path, size, &mime_type)).await?
+            };
+            sqlx::query(
+                "UPDATE attachments
+                 SET sync_status = $1, remote_path = $2, upload_url = $3,
+                     transferred_bytes = 0, updated_at = $4
+                 WHERE id = $5",
+            )
+            .bind(SyncStatus::Uploading.as_str())
+            .bind(&path)
+            .bind(&url)
+            .bind(now())
+            .bind(&row.id)
+            .execute(pool)
+            .await?;
+            (url, 0)
+        }
+    };
+
+    let data = Arc::new(data);
+    while offset < size {
+        let start = offset as usize;
+        let end = (start + CHUNK_SIZE).min(data.len());
+        let storage = storage.clone();
+        let chunk_url = url.clone();
+        let data = data.clone();
+        offset =
+            blocking(move || storage.upload_chunk(&chunk_url, offset, &data[start..end])).await?;
+        sqlx::query("UPDATE attachments SET transferred_bytes = $1 WHERE id = $2")
+            .bind(offset as i64)
+            .bind(&row.id)
+            .execute(pool)
+            .await?;
+    }
+
+    sqlx::query(
+        "UPDATE attachments
+         SET sync_status = $1, upload_url = NULL, transferred_bytes = $2,
+             sync_error = NULL, updated_at = $3
+         WHERE id = $4",
+    )
+    .bind(SyncStatus::Synced.as_str())
+    .bind(size as i64)
+    .bind(now())
+    .bind(&row.id)
+    .execute(pool)
+    .await?;
+    Ok(true)
+}
+
+/// Add rows for objects uploaded by other devices.
+async fn discover(pool: &SqlitePool, storage: &Storage, user_id: &str) -> Result<()> {
+    let objects = {
+        let storage = storage.clone();
+        let folder = user_id.to_string();
+        blocking(move || storage.list(&folder)).await?
+    };
+
+    let now = now();
+    for object in objects {
+        let parts: Vec<&str> = object.name.split('.').collect();
+        let [expense_id, id, sha256, extension] = parts[..] else {
+            continue;
+        };
+        if sha256.len() != 64 || !sha256.bytes().all(|b| b.is_ascii_hexdigit()) {
+            continue;
+        }
+        // Rows deleted here but not yet removed remotely are still present,
+        // so an object can't come back while its deletion is pending.
+        sqlx::query(
+            "INSERT INTO attachments
+                (id, expense_id, file_name, mime_type, size_bytes, sha256, sync_status,
+                 remote_path, created_at, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+             ON CONFLICT(id) DO NOTHING",
+        )
+        .bind(id)
+        .bind(expense_id)
+        .bind(format!("{id}.{extension}"))
+        .bind(mime_type(extension))
+        .bind(object.size as i64)
+        .bind(sha256)
+        .bind(SyncStatus::Remote.as_str())
+        .bind(format!("{user_id}/{}", object.name))
+        .bind(&now)
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Download one file into a `.partial` file and move it in place once its
+/// checksum matches. Returns false if the file was marked failed.
+async fn download(
+    pool: &SqlitePool,
+    storage: &Storage,
+    dir: &Path,
+    row: AttachmentRow,
+) -> Result<bool> {
+    let Some(path) = row.remote_path.clone() else {
+        mark_failed(pool, &row.id, "No remote copy").await?;
+        return Ok(false);
+    };
+    let partial = dir.join(format!("{}.partial", row.file_name));
+    let from = if row.sync_status == SyncStatus::Downloading.as_str() {
+        std::fs::metadata(&partial).map_or(0, |m| m.len())
+    } else {
+        0
+    };
+
+    sqlx::query("UPDATE attachments SET sync_status = $1, updated_at = $2 WHERE id = $3")
+        .bind(SyncStatus::Downloading.as_str())
+        .bind(now())
+        .bind(&row.id)
+        .execute(pool)
+        .await?;
+
+    {
+        let storage = storage.clone();
+        let partial = partial.clone();
+        blocking(move || storage.download(&path, &partial, from)).await?;
+    }
+
+    let data = std::fs::read(&partial)?;
+    if sha256_hex(&data) != row.sha256 {
+        std::fs::remove_file(&partial)?;
+        mark_failed(pool, &row.id, "Downloaded file failed its checksum").await?;
+        return Ok(false);
+    }
+    std::fs::rename(&partial, dir.join(&row.file_name))?;
+
+    sqlx::query(
+        "UPDATE attachments
+         SET sync_status = $1, size_bytes = $2, transferred_bytes = $2,
+             sync_error = NULL, updated_at = $3
+         WHERE id = $4",
+    )
+    .bind(SyncStatus::Synced.as_str())
+    .bind(data.len() as i64)
+    .bind(now())
+    .bind(&row.id)
+    .execute(pool)
+    .await?;
+    Ok(true)
+}
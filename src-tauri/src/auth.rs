@@ -0,0 +1,278 @@
+//! Access to the locally stored session (`auth_state`, written by the
+//! frontend's auth module).
+//!
+//! `auth_state` keeps who is signed in and until when; the tokens live in
+//! the platform keychain. Tokens still found in the table, written before
+//! the keychain was used, are moved there the next time they are read.
+//! Where there is no keychain they stay in the table.
+//!
+//! A background job refreshes the session shortly before it expires, so a
+//! long-running app keeps syncing without the frontend having to be awake.
+//! When the server refuses the refresh token the frontend is told through
+//! `auth:session-expired` and signs the user back in.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::backend::{Backend, BackendConfig};
+use crate::connectivity::Connectivity;
+use crate::db::{timestamp, Db};
+use crate::error::{Error, Result};
+use crate::secrets;
+
+pub const SESSION_EXPIRED_EVENT: &str = "auth:session-expired";
+
+/// Refresh once the access token has less than this left, the same margin
+/// the frontend uses.
+const REFRESH_MARGIN: chrono::Duration = chrono::Duration::minutes(5);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+const ACCESS_TOKEN: &str = "access_token";
+const REFRESH_TOKEN: &str = "refresh_token";
+
+/// Set once the missing keychain has been logged.
+static NO_KEYCHAIN_LOGGED: AtomicBool = AtomicBool::new(false);
+
+/// The last refresh token the server refused, so it isn't retried (and the
+/// frontend told again) every time the job runs.
+static REFUSED_TOKEN: Mutex<Option<String>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Default)]
+pub struct Tokens {
+    pub access_token: Option<String>,
+    pub refresh_token: Option<String>,
+}
+
+impl Tokens {
+    fn is_empty(&self) -> bool {
+        self.access_token.is_none() && self.refresh_token.is_none()
+    }
+}
+
+fn log_no_keychain(e: &crate::error::Error) {
+    if !NO_KEYCHAIN_LOGGED.swap(true, Ordering::Relaxed) {
+        eprintln!("[Auth] Keeping tokens in the database: {e}");
+    }
+}
+
+/// Store `tokens` (all `None` to sign out) in the keychain and clear them
+/// from `auth_state`, or keep them in `auth_state` if there is no keychain.
+pub(crate) async fn store_tokens(conn: &mut SqliteConnection, tokens: &Tokens) -> Result<()> {
+    let stored = match secrets::set(ACCESS_TOKEN, tokens.access_token.clone()).await {
+        Ok(()) => secrets::set(REFRESH_TOKEN, tokens.refresh_token.clone()).await,
+        Err(e) => Err(e),
+    };
+    let in_table = match stored {
+        Ok(()) => Tokens::default(),
+        Err(e) => {
+            log_no_keychain(&e);
+            tokens.clone()
+        }
+    };
+    sqlx::query("UPDATE auth_state SET access_token = $1, refresh_token = $2 WHERE id = 1")
+        .bind(&in_table.access_token)
+        .bind(&in_table.refresh_token)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// The stored tokens, moving any left in `auth_state` to the keychain.
+pub(crate) async fn load_tokens(conn: &mut SqliteConnection) -> Result<Tokens> {
+    let row: Option<(Option<String>, Option<String>)> =
+        sqlx::query_as("SELECT access_token, refresh_token FROM auth_state WHERE id = 1")
+            .fetch_optional(&mut *conn)
+            .await?;
+    let in_table = row.map_or_else(Tokens::default, |(access_token, refresh_token)| Tokens {
+        access_token,
+        refresh_token,
+    });
+    if !in_table.is_empty() {
+        store_tokens(conn, &in_table).await?;
+        return Ok(in_table);
+    }
+
+    let access_token = match secrets::get(ACCESS_TOKEN).await {
+        Ok(token) => token,
+        Err(e) => {
+            log_no_keychain(&e);
+            return Ok(Tokens::default());
+        }
+    };
+    Ok(Tokens {
+        access_token,
+        refresh_token: secrets::get(REFRESH_TOKEN).await.unwrap_or_default(),
+    })
+}
+
+/// The signed-in user, or `None` in offline-only mode.
+pub async fn current_user_id(pool: &SqlitePool) -> Result<Option<String>> {
+    let row: Option<(Option<String>,)> =
+        sqlx::query_as("SELECT user_id FROM auth_state WHERE id = 1")
+            .fetch_optional(pool)
+            .await?;
+    Ok(row.and_then(|(user_id,)| user_id))
+}
+
+/// `current_user_id` on a connection already in use, such as a transaction.
+pub async fn user_id_on(conn: &mut SqliteConnection) -> Result<Option<String>> {
+    let row: Option<(Option<String>,)> =
+        sqlx::query_as("SELECT user_id FROM auth_state WHERE id = 1")
+            .fetch_optional(&mut *conn)
+            .await?;
+    Ok(row.and_then(|(user_id,)| user_id))
+}
+
+/// A signed-in user with a token that is still valid.
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub user_id: String,
+    pub access_token: String,
+}
+
+/// The stored session, or `None` when signed out or the token has expired.
+/// `refresh_session` keeps the token from expiring while the app runs.
+pub async fn session(pool: &SqlitePool) -> Result<Option<Session>> {
+    let mut conn = pool.acquire().await?;
+    let row: Option<(Option<String>, Option<String>)> =
+        sqlx::query_as("SELECT user_id, expires_at FROM auth_state WHERE id = 1")
+            .fetch_optional(&mut *conn)
+            .await?;
+    let Some((Some(user_id), expires_at)) = row else {
+        return Ok(None);
+    };
+    let Some(access_token) = load_tokens(&mut conn).await?.access_token else {
+        return Ok(None);
+    };
+    let expired = expires_at
+        .and_then(|at| DateTime::parse_from_rfc3339(&at).ok())
+        .is_none_or(|at| at <= Utc::now());
+    Ok((!expired).then_some(Session {
+        user_id,
+        access_token,
+    }))
+}
+
+#[derive(Deserialize)]
+struct RefreshResponse {
+    access_token: String,
+    refresh_token: String,
+    /// Seconds the new access token is valid for.
+    expires_in: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SessionExpired {
+    user_id: String,
+    reason: String,
+}
+
+enum Refresh {
+    Renewed(RefreshResponse),
+    Refused(String),
+}
+
+/// Blocking. Trade `refresh_token` for a new pair of tokens.
+fn refresh_remote(config: &BackendConfig, refresh_token: &str) -> Result<Refresh> {
+    let result = ureq::AgentBuilder::new()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .post(&format!("{}/auth/v1/token", config.url))
+        .query("grant_type", "refresh_token")
+        .set("apikey", &config.anon_key)
+        .set("Content-Type", "application/json")
+        .send_string(&serde_json::json!({ "refresh_token": refresh_token }).to_string());
+    match result {
+        Ok(response) => serde_json::from_str(&response.into_string()?)
+            .map(Refresh::Renewed)
+            .map_err(|e| Error::Remote(format!("unexpected token response: {e}"))),
+        // Revoked, already used or expired refresh tokens.
+        Err(ureq::Error::Status(code @ (400 | 401 | 403), response)) => {
+            let body = response.into_string().unwrap_or_default();
+            Ok(Refresh::Refused(format!("{code}: {}", body.trim())))
+        }
+        Err(ureq::Error::Status(code, response)) => {
+            let body = response.into_string().unwrap_or_default();
+            Err(Error::Remote(format!(
+                "token refresh returned {code}: {}",
+                body.trim()
+            )))
+        }
+        Err(ureq::Error::Transport(e)) => {
+            Err(Error::Remote(format!("auth server unreachable: {e}")))
+        }
+    }
+}
+
+/// Refresh the stored session if its access token is about to expire.
+pub async fn refresh_session(app: &AppHandle) -> Result<()> {
+    let Some(config) = app.state::<Backend>().config() else {
+        return Ok(());
+    };
+    if !app.state::<Connectivity>().is_online() {
+        return Ok(());
+    }
+    let db = app.state::<Db>();
+    let pool = db.pool();
+
+    let mut conn = pool.acquire().await?;
+    let row: Option<(Option<String>, Option<String>)> =
+        sqlx::query_as("SELECT user_id, expires_at FROM auth_state WHERE id = 1")
+            .fetch_optional(&mut *conn)
+            .await?;
+    let Some((Some(user_id), Some(expires_at))) = row else {
+        return Ok(());
+    };
+    let due = DateTime::parse_from_rfc3339(&expires_at)
+        .map_or(true, |at| at - REFRESH_MARGIN <= Utc::now());
+    if !due {
+        return Ok(());
+    }
+    let Some(refresh_token) = load_tokens(&mut conn).await?.refresh_token else {
+        return Ok(());
+    };
+    drop(conn);
+    if REFUSED_TOKEN.lock().unwrap().as_deref() == Some(refresh_token.as_str()) {
+        return Ok(());
+    }
+
+    let used = refresh_token.clone();
+    let refresh =
+        tauri::async_runtime::spawn_blocking(move || refresh_remote(&config, &used)).await??;
+    let renewed = match refresh {
+        Refresh::Renewed(renewed) => renewed,
+        Refresh::Refused(reason) => {
+            eprintln!("[Auth] Session refresh refused: {reason}");
+            *REFUSED_TOKEN.lock().unwrap() = Some(refresh_token);
+            app.emit(SESSION_EXPIRED_EVENT, SessionExpired { user_id, reason })?;
+            return Ok(());
+        }
+    };
+
+    // The frontend may have refreshed or signed out while we were waiting;
+    // its state wins.
+    let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+    let current = load_tokens(&mut tx).await?;
+    if current.refresh_token.as_deref() != Some(refresh_token.as_str()) {
+        return Ok(());
+    }
+    let expires_at = timestamp(Utc::now() + chrono::Duration::seconds(renewed.expires_in));
+    sqlx::query("UPDATE auth_state SET expires_at = $1 WHERE id = 1")
+        .bind(&expires_at)
+        .execute(&mut *tx)
+        .await?;
+    let tokens = Tokens {
+        access_token: Some(renewed.access_token),
+        refresh_token: Some(renewed.refresh_token),
+    };
+    store_tokens(&mut tx, &tokens).await?;
+    tx.commit().await?;
+    Ok(())
+}
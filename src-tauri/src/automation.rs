@@ -0,0 +1,315 @@
+//! A REST API on localhost for launchers, Shortcuts and scripts.
+//!
+//! Off until turned on in settings. While on, the app answers on
+//! `http://127.0.0.1:17465` to requests carrying
+//! `Authorization: Bearer <token>`, with a random token shown in settings
+//! that can be replaced at any time:
+//!
+//! - `POST /v1/expenses` with `{"amount": 12.5, "category": "groceries",
+//!   "note": "...", "date": "YYYY-MM-DD"}` or `{"text": "12.50 coffee"}`
+//!   adds an expense, as a quick-add link would, and answers 201 with it.
+//! - `GET /v1/budget` answers with how the current period is going.
+//! - `GET /v1/goals` answers with the savings goals and what is saved.
+//!
+//! Only the loopback interface is bound, so nothing else on the network can
+//! reach it. Browsers can't send the header to another origin without a
+//! preflight, which is never answered.
+
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use base64::prelude::{Engine, BASE64_URL_SAFE_NO_PAD};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Manager};
+
+use crate::app_meta;
+use crate::budgets;
+use crate::currency;
+use crate::db::Db;
+use crate::drafts::{self, find_category, ExpenseDraft};
+use crate::error::{Error, Result};
+use crate::expenses::NewExpense;
+use crate::goals;
+use crate::periods;
+use crate::quick_add;
+
+pub const PORT: u16 = 17465;
+
+const ENABLED_KEY: &str = "automation_api_enabled";
+const TOKEN_KEY: &str = "automation_api_token";
+/// Largest request body accepted; larger ones are answered with 413.
+const MAX_BODY: u64 = 64 * 1024;
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct AutomationStatus {
+    pub enabled: bool,
+    /// Where the API answers, while it is up.
+    pub url: Option<String>,
+    /// For the `Authorization: Bearer` header; set once the API was turned
+    /// on.
+    pub token: Option<String>,
+}
+
+/// The server while the API is on.
+#[derive(Default)]
+pub struct AutomationApi {
+    server: Mutex<Option<Arc<tiny_http::Server>>>,
+}
+
+impl AutomationApi {
+    fn is_serving(&self, server: &Arc<tiny_http::Server>) -> bool {
+        self.server
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|current| Arc::ptr_eq(current, server))
+    }
+
+    /// Stop answering; the server thread notices within a second.
+    fn stop(&self) {
+        self.server.lock().unwrap().take();
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ExpenseRequest {
+    amount: Option<f64>,
+    category: Option<String>,
+    note: Option<String>,
+    date: Option<String>,
+    /// Free text such as "12.50 coffee"; the other fields win over it.
+    text: Option<String>,
+}
+
+pub async fn status(app: &AppHandle) -> Result<AutomationStatus> {
+    let db = app.state::<Db>();
+    let pool = db.pool();
+    let running = app
+        .state::<AutomationApi>()
+        .server
+        .lock()
+        .unwrap()
+        .is_some();
+    Ok(AutomationStatus {
+        enabled: app_meta::get(pool, ENABLED_KEY).await?.as_deref() == Some("1"),
+        url: running.then(|| format!("http://127.0.0.1:{PORT}")),
+        token: app_meta::get(pool, TOKEN_KEY).await?,
+    })
+}
+
+/// Turn the API on or off, creating a token the first time.
+pub async fn set_enabled(app: &AppHandle, enabled: bool) -> Result<AutomationStatus> {
+    let db = app.state::<Db>();
+    let pool = db.pool();
+    if enabled && app_meta::get(pool, TOKEN_KEY).await?.is_none() {
+        app_meta::set(pool, TOKEN_KEY, &new_token()).await?;
+    }
+    app_meta::set(pool, ENABLED_KEY, if enabled { "1" } else { "0" }).await?;
+    let api = app.state::<AutomationApi>();
+    let running = api.server.lock().unwrap().is_some();
+    if !enabled {
+        api.stop();
+    } else if !running {
+        listen(app)?;
+    }
+    status(app).await
+}
+
+/// Replace the token, locking out whatever used the old one.
+pub async fn regenerate_token(app: &AppHandle) -> Result<AutomationStatus> {
+    let db = app.state::<Db>();
+    app_meta::set(db.pool(), TOKEN_KEY, &new_token()).await?;
+    status(app).await
+}
+
+/// Start answering if the API was left on.
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let db = app.state::<Db>();
+        match app_meta::get(db.pool(), ENABLED_KEY).await {
+            Ok(Some(enabled)) if enabled == "1" => {
+                if let Err(e) = listen(&app) {
+                    eprintln!("[Automation] Failed to start: {e}");
+                }
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("[Automation] Failed to read settings: {e}"),
+        }
+    });
+}
+
+fn new_token() -> String {
+    BASE64_URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>())
+}
+
+fn listen(app: &AppHandle) -> Result<()> {
+    let server = tiny_http::Server::http(("127.0.0.1", PORT)).map_err(|e| {
+        Error::Unsupported(format!("Port {PORT} is not available for automation: {e}"))
+    })?;
+    let server = Arc::new(server);
+    *app.state::<AutomationApi>().server.lock().unwrap() = Some(server.clone());
+    let handle = app.clone();
+    std::thread::spawn(move || serve(&handle, &server));
+    Ok(())
+}
+
+/// Answer requests until the API is turned off.
+fn serve(app: &AppHandle, server: &Arc<tiny_http::Server>) {
+    while app.state::<AutomationApi>().is_serving(server) {
+        let mut request = match server.recv_timeout(Duration::from_secs(1)) {
+            Ok(Some(request)) => request,
+            Ok(None) => continue,
+            Err(e) => {
+                eprintln!("[Automation] Server error: {e}");
+                return;
+            }
+        };
+        let authorization = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("Authorization"))
+            .map(|h| h.value.as_str().to_string());
+        // One byte past the limit tells a body that is too large from one
+        // that just fits.
+        let mut body = Vec::new();
+        let read = request
+            .as_reader()
+            .take(MAX_BODY + 1)
+            .read_to_end(&mut body);
+        let (code, answer) = match read {
+            Err(_) => (400, json!({ "error": "Unreadable body" })),
+            Ok(_) if body.len() as u64 > MAX_BODY => (
+                413,
+                json!({
+                    "error": format!("Request body is larger than {} KB", MAX_BODY / 1024)
+                }),
+            ),
+            Ok(_) => {
+                let method = request.method().as_str().to_string();
+                let path = request.url().to_string();
+                tauri::async_runtime::block_on(answer(
+                    app,
+                    authorization.as_deref(),
+                    &method,
+                    &path,
+                    &body,
+                ))
+            }
+        };
+        let content_type = tiny_http::Header::from_bytes("Content-Type", "application/json")
+            .expect("static header");
+        let response = tiny_http::Response::from_string(answer.to_string())
+            .with_status_code(code)
+            .with_header(content_type);
+        if let Err(e) = request.respond(response) {
+            eprintln!("[Automation] Failed to respond: {e}");
+        }
+    }
+}
+
+/// The status code and JSON body for a request.
+async fn answer(
+    app: &AppHandle,
+    authorization: Option<&str>,
+    method: &str,
+    path: &str,
+    body: &[u8],
+) -> (u16, Value) {
+    let db = app.state::<Db>();
+    let pool = db.pool();
+    let token = match app_meta::get(pool, TOKEN_KEY).await {
+        Ok(token) => token,
+        Err(e) => return (500, json!({ "error": e.to_string() })),
+    };
+    let given = authorization.and_then(|a| a.strip_prefix("Bearer "));
+    if !matches!((token, given), (Some(token), Some(given)) if same(&token, given.trim())) {
+        return (401, json!({ "error": "Missing or wrong token" }));
+    }
+
+    let path = path.split('?').next().unwrap_or_default();
+    let result = match (method, path) {
+        ("POST", "/v1/expenses") => add_expense(app, pool, body).await.map(|v| (201, v)),
+        ("GET", "/v1/budget") => budget(pool).await.map(|v| (200, v)),
+        ("GET", "/v1/goals") => list_goals(pool).await.map(|v| (200, v)),
+        _ => Ok((404, json!({ "error": "Not found" }))),
+    };
+    match result {
+        Ok(answer) => answer,
+        Err(Error::Validation(message)) => (400, json!({ "error": message })),
+        Err(e) => {
+            eprintln!("[Automation] Failed to answer {method} {path}: {e}");
+            (500, json!({ "error": e.to_string() }))
+        }
+    }
+}
+
+/// Compare tokens without leaking where they differ through timing.
+fn same(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |acc, (x, y)| acc | (x ^ y))
+            == 0
+}
+
+async fn add_expense(app: &AppHandle, pool: &SqlitePool, body: &[u8]) -> Result<Value> {
+    let request: ExpenseRequest = serde_json::from_slice(body)
+        .map_err(|e| Error::Validation(format!("Invalid expense: {e}")))?;
+    let categories = drafts::load_categories(pool).await?;
+    let today = Local::now().date_naive();
+    let draft = match &request.text {
+        Some(text) => drafts::parse_expense_text(text, &categories, today),
+        None => ExpenseDraft {
+            amount: None,
+            category_id: None,
+            note: None,
+            date: today.format("%Y-%m-%d").to_string(),
+        },
+    };
+    let amount = request
+        .amount
+        .or(draft.amount)
+        .ok_or_else(|| Error::Validation("No amount given".to_string()))?;
+    let category_id = match &request.category {
+        Some(name) => Some(
+            find_category(name, &categories)
+                .ok_or_else(|| Error::Validation(format!("No category matches \"{name}\"")))?,
+        ),
+        None => draft.category_id,
+    };
+    let input = NewExpense {
+        amount,
+        category_id,
+        note: request.note.or(draft.note),
+        date: Some(request.date.unwrap_or(draft.date)),
+        payment_method: None,
+        currency: None,
+        account_id: None,
+    };
+    let expense = quick_add::save(app, pool, input).await?;
+    serde_json::to_value(expense).map_err(|e| Error::Validation(e.to_string()))
+}
+
+async fn budget(pool: &SqlitePool) -> Result<Value> {
+    let schedule = periods::schedule(pool).await?;
+    let status = budgets::status(pool, &schedule, Local::now().date_naive()).await?;
+    let mut answer = serde_json::to_value(status).map_err(|e| Error::Validation(e.to_string()))?;
+    answer["currency"] = Value::String(currency::base(pool).await?);
+    Ok(answer)
+}
+
+async fn list_goals(pool: &SqlitePool) -> Result<Value> {
+    let mut answer = Vec::new();
+    for goal in goals::list(pool).await? {
+        let saved = goals::total_saved(pool, &goal.id).await?;
+        let mut goal = serde_json::to_value(goal).map_err(|e| Error::Validation(e.to_string()))?;
+        goal["saved"] = json!(saved);
+        answer.push(goal);
+    }
+    Ok(Value::Array(answer))
+}
@@ -0,0 +1,59 @@
+//! Where the Supabase project lives.
+//!
+//! The URL and anon key are baked into the frontend bundle from its Vite env,
+//! so the frontend hands them over at startup rather than the Rust side
+//! carrying a second copy. Until then anything that talks to Supabase from
+//! Rust stays idle, as it does in offline-only builds.
+//!
+//! The last configuration is also kept in `app_meta`, for the CLI, which
+//! runs without the frontend.
+
+use std::sync::RwLock;
+
+use serde::Deserialize;
+use sqlx::SqlitePool;
+
+use crate::app_meta;
+use crate::error::Result;
+
+const URL_KEY: &str = "backend_url";
+const ANON_KEY_KEY: &str = "backend_anon_key";
+
+#[derive(Debug, Clone, Deserialize, specta::Type)]
+pub struct BackendConfig {
+    /// Project URL without a trailing slash.
+    pub url: String,
+    pub anon_key: String,
+}
+
+#[derive(Default)]
+pub struct Backend {
+    config: RwLock<Option<BackendConfig>>,
+}
+
+impl Backend {
+    pub fn configure(&self, mut config: BackendConfig) {
+        config.url = config.url.trim_end_matches('/').to_string();
+        *self.config.write().unwrap() = Some(config);
+    }
+
+    /// `None` until the frontend configured a project.
+    pub fn config(&self) -> Option<BackendConfig> {
+        self.config.read().unwrap().clone()
+    }
+}
+
+/// Keep `config` for runs without the frontend.
+pub async fn remember(pool: &SqlitePool, config: &BackendConfig) -> Result<()> {
+    app_meta::set(pool, URL_KEY, config.url.trim_end_matches('/')).await?;
+    app_meta::set(pool, ANON_KEY_KEY, &config.anon_key).await
+}
+
+/// The project the app was last configured with, if any.
+pub async fn remembered(pool: &SqlitePool) -> Result<Option<BackendConfig>> {
+    let url = app_meta::get(pool, URL_KEY).await?;
+    let anon_key = app_meta::get(pool, ANON_KEY_KEY).await?;
+    Ok(url
+        .zip(anon_key)
+        .map(|(url, anon_key)| BackendConfig { url, anon_key }))
+}
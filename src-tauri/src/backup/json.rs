@@ -0,0 +1,317 @@
+//! Plain JSON backups of the user's own records, readable without the app.
+//!
+//! Where an archive carries the whole database and has to be migrated on
+//! the way in, a JSON backup holds only the tables below, keyed by column
+//! name. Restoring takes the columns this schema knows and lets the rest
+//! fall back to their defaults, so an older backup fits a newer database.
+//! A backup from a newer schema or data format is refused. References
+//! between the rows are checked before anything is written, and restored
+//! rows are not queued for sync: the next full sync decides what the server
+//! is missing.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::AppHandle;
+
+use crate::app_meta::DATA_FORMAT_VERSION;
+use crate::category_totals;
+use crate::db::{now, quote_ident};
+use crate::error::{Error, Result};
+use crate::export;
+use crate::sync::table_columns;
+
+/// Identifies the file as a Goaldy JSON backup.
+pub const FORMAT: &str = "goaldy-backup";
+
+/// Bumped when the layout of the file (not the database schema) changes.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Backed up tables, parents before the tables referring to them.
+const TABLES: &[&str] = &[
+    "categories",
+    "budgets",
+    "category_budgets",
+    "expenses",
+    "savings_goals",
+    "savings_contributions",
+    "habit_goals",
+    "habit_tracking",
+];
+
+type Row = Map<String, Value>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonBackup {
+    pub format: String,
+    pub format_version: u32,
+    pub app_version: String,
+    /// Name of the newest migration applied when the backup was made.
+    pub schema_version: Option<String>,
+    pub data_format_version: u32,
+    pub created_at: String,
+    pub tables: BTreeMap<String, Vec<Row>>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct TableCount {
+    pub table: String,
+    pub rows: usize,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct BackupExport {
+    pub path: PathBuf,
+    pub tables: Vec<TableCount>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct BackupRestore {
+    pub schema_version: Option<String>,
+    pub created_at: String,
+    pub tables: Vec<TableCount>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ForeignKey {
+    table: String,
+    from: String,
+    to: Option<String>,
+}
+
+fn backup_error(e: impl std::fmt::Display) -> Error {
+    Error::Validation(format!("Could not read the backup: {e}"))
+}
+
+fn counts(tables: &BTreeMap<String, Vec<Row>>) -> Vec<TableCount> {
+    TABLES
+        .iter()
+        .map(|table| TableCount {
+            table: table.to_string(),
+            rows: tables.get(*table).map_or(0, Vec::len),
+        })
+        .collect()
+}
+
+async fn schema_version(conn: &mut SqliteConnection) -> Result<Option<String>> {
+    let (version,): (Option<String>,) = sqlx::query_as("SELECT MAX(name) FROM _migrations")
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok(version)
+}
+
+async fn read_table(conn: &mut SqliteConnection, table: &str) -> Result<Vec<Row>> {
+    let pairs = table_columns(conn, table)
+        .await?
+        .iter()
+        .map(|c| format!("'{}', {}", c.replace('\'', "''"), quote_ident(c)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let rows: Vec<(String,)> = sqlx::query_as(&format!(
+        "SELECT json_object({pairs}) FROM {} ORDER BY rowid",
+        quote_ident(table)
+    ))
+    .fetch_all(&mut *conn)
+    .await?;
+
+    rows.into_iter()
+        .map(|(json,)| match serde_json::from_str(&json) {
+            Ok(Value::Object(row)) => Ok(row),
+            _ => Err(Error::Validation(format!(
+                "Could not read a row of {table}"
+            ))),
+        })
+        .collect()
+}
+
+/// Write every backed up table to a dated JSON file in the export folder.
+pub async fn export(app: &AppHandle, pool: &SqlitePool) -> Result<BackupExport> {
+    // One read transaction, so the tables agree with each other.
+    let mut tx = pool.begin().await?;
+    let mut tables = BTreeMap::new();
+    for table in TABLES {
+        tables.insert(table.to_string(), read_table(&mut tx, table).await?);
+    }
+    let backup = JsonBackup {
+        format: FORMAT.to_string(),
+        format_version: FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_version: schema_version(&mut tx).await?,
+        data_format_version: DATA_FORMAT_VERSION,
+        created_at: now(),
+        tables,
+    };
+    tx.commit().await?;
+
+    let path = export::output_dir(app)?.join(format!(
+        "goaldy-backup-{}.json",
+        chrono::Local::now().format("%Y-%m-%d")
+    ));
+    let json = serde_json::to_vec_pretty(&backup)
+        .map_err(|e| Error::Validation(format!("failed to encode backup: {e}")))?;
+    std::fs::write(&path, json)?;
+    Ok(BackupExport {
+        tables: counts(&backup.tables),
+        path,
+    })
+}
+
+fn check_versions(backup: &JsonBackup, local_schema: Option<&str>) -> Result<()> {
+    if backup.format != FORMAT {
+        return Err(Error::Validation(
+            "This file is not a Goaldy backup".to_string(),
+        ));
+    }
+    if backup.format_version > FORMAT_VERSION
+        || backup.data_format_version > DATA_FORMAT_VERSION
+        || backup.schema_version.as_deref() > local_schema
+    {
+        return Err(Error::Validation(
+            "This backup was made by a newer version of Goaldy. Update the app and try again."
+                .to_string(),
+        ));
+    }
+    if let Some(table) = backup.tables.keys().find(|t| !TABLES.contains(&t.as_str())) {
+        return Err(Error::Validation(format!(
+            "The backup contains an unknown table \"{table}\""
+        )));
+    }
+    Ok(())
+}
+
+/// Every reference between backed up tables must point at a row in the
+/// backup.
+async fn check_references(conn: &mut SqliteConnection, backup: &JsonBackup) -> Result<()> {
+    let no_rows = Vec::new();
+    for table in TABLES {
+        let rows = backup.tables.get(*table).unwrap_or(&no_rows);
+        let keys: Vec<ForeignKey> =
+            sqlx::query_as("SELECT \"table\", \"from\", \"to\" FROM pragma_foreign_key_list($1)")
+                .bind(table)
+                .fetch_all(&mut *conn)
+                .await?;
+
+        for key in keys.iter().filter(|k| TABLES.contains(&k.table.as_str())) {
+            let to = key.to.as_deref().unwrap_or("id");
+            let parents: HashSet<&Value> = backup
+                .tables
+                .get(&key.table)
+                .unwrap_or(&no_rows)
+                .iter()
+                .filter_map(|row| row.get(to))
+                .collect();
+            for row in rows {
+                let Some(value) = row.get(&key.from).filter(|v| !v.is_null()) else {
+                    continue;
+                };
+                if !parents.contains(value) {
+                    let id = row.get("id").map_or_else(String::new, Value::to_string);
+                    return Err(Error::Validation(format!(
+                        "Row {id} in {table} refers to {} {value}, which is not in the backup",
+                        key.table
+                    )));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether anything beyond the default categories is stored.
+async fn has_data(conn: &mut SqliteConnection) -> Result<bool> {
+    for table in TABLES.iter().filter(|t| **t != "categories") {
+        let (found,): (bool,) = sqlx::query_as(&format!(
+            "SELECT EXISTS (SELECT 1 FROM {})",
+            quote_ident(table)
+        ))
+        .fetch_one(&mut *conn)
+        .await?;
+        if found {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+async fn insert_rows(conn: &mut SqliteConnection, table: &str, rows: &[Row]) -> Result<()> {
+    let local: HashSet<String> = table_columns(conn, table).await?.into_iter().collect();
+    for row in rows {
+        let columns: Vec<&String> = row.keys().filter(|c| local.contains(*c)).collect();
+        if columns.is_empty() {
+            continue;
+        }
+        let names = columns
+            .iter()
+            .map(|c| quote_ident(c))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let values = columns
+            .iter()
+            .map(|c| format!("json_extract($1, '$.\"{}\"')", c.replace('\'', "''")))
+            .collect::<Vec<_>>()
+            .join(", ");
+        sqlx::query(&format!(
+            "INSERT INTO {} ({names}) VALUES ({values})",
+            quote_ident(table)
+        ))
+        .bind(Value::Object(row.clone()).to_string())
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Restore the backup at `path`. Into a database that already holds data
+/// only with `overwrite`, which replaces every backed up table; rows in
+/// other tables that cascade from them (alert thresholds, goal reminders)
+/// go with them.
+pub async fn restore(pool: &SqlitePool, path: &Path, overwrite: bool) -> Result<BackupRestore> {
+    let bytes = std::fs::read(path)?;
+    let backup: JsonBackup = serde_json::from_slice(&bytes).map_err(backup_error)?;
+
+    let mut tx = pool.begin().await?;
+    check_versions(&backup, schema_version(&mut tx).await?.as_deref())?;
+    check_references(&mut tx, &backup).await?;
+    if !overwrite && has_data(&mut tx).await? {
+        return Err(Error::Validation(
+            "This device already has data. Restore with overwrite to replace it.".to_string(),
+        ));
+    }
+
+    sqlx::query("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *tx)
+        .await?;
+    for table in TABLES.iter().rev() {
+        sqlx::query(&format!("DELETE FROM {}", quote_ident(table)))
+            .execute(&mut *tx)
+            .await?;
+    }
+    for table in TABLES {
+        if let Some(rows) = backup.tables.get(*table) {
+            insert_rows(&mut tx, table, rows).await?;
+        }
+    }
+    for table in TABLES {
+        let (violations,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM pragma_foreign_key_check($1)")
+                .bind(table)
+                .fetch_one(&mut *tx)
+                .await?;
+        if violations > 0 {
+            return Err(Error::Validation(format!(
+                "Restoring would leave {violations} broken references in {table}"
+            )));
+        }
+    }
+    tx.commit().await?;
+
+    category_totals::rebuild(pool).await?;
+    Ok(BackupRestore {
+        tables: counts(&backup.tables),
+        schema_version: backup.schema_version,
+        created_at: backup.created_at,
+    })
+}
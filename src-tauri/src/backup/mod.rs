@@ -0,0 +1,414 @@
+//! Automatic encrypted backups to storage the user already trusts: their
+//! Dropbox or Google Drive, or a folder such as iCloud Drive picked with the
+//! platform's document picker.
+//!
+//! Each target receives a full `.goaldy` archive on its own schedule,
+//! encrypted with the passphrase chosen when it was set up. Files are named
+//! after the weekday, so a target holds the last seven days and stops
+//! growing there.
+//!
+//! `json` is the manual counterpart: an unencrypted, versioned file of the
+//! user's records that can be read outside the app and restored into a
+//! fresh install.
+
+pub mod json;
+pub mod oauth;
+mod providers;
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Manager};
+
+use crate::app_meta;
+use crate::archive::{self, EXTENSION};
+use crate::connectivity::Connectivity;
+use crate::db::{new_id, now};
+use crate::error::{Error, Result};
+use oauth::{Client, Endpoints, Tokens};
+
+const MIN_INTERVAL_HOURS: i64 = 1;
+const MAX_INTERVAL_HOURS: i64 = 24 * 30;
+
+/// Refresh an access token this long before it runs out.
+const TOKEN_MARGIN: chrono::Duration = chrono::Duration::minutes(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupProvider {
+    Dropbox,
+    GoogleDrive,
+    Folder,
+}
+
+impl BackupProvider {
+    fn as_str(self) -> &'static str {
+        match self {
+            BackupProvider::Dropbox => "dropbox",
+            BackupProvider::GoogleDrive => "google_drive",
+            BackupProvider::Folder => "folder",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "dropbox" => Some(BackupProvider::Dropbox),
+            "google_drive" => Some(BackupProvider::GoogleDrive),
+            "folder" => Some(BackupProvider::Folder),
+            _ => None,
+        }
+    }
+
+    fn endpoints(self) -> Option<&'static Endpoints> {
+        match self {
+            BackupProvider::Dropbox => Some(&providers::DROPBOX),
+            BackupProvider::GoogleDrive => Some(&providers::GOOGLE_DRIVE),
+            BackupProvider::Folder => None,
+        }
+    }
+}
+
+/// A target as shown in settings; credentials stay on the Rust side.
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct BackupTarget {
+    pub id: String,
+    pub provider: BackupProvider,
+    pub folder_path: Option<String>,
+    pub interval_hours: i64,
+    pub last_backup_at: Option<String>,
+    pub last_error: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct TargetRow {
+    id: String,
+    provider: String,
+    folder_path: Option<String>,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+    access_token: Option<String>,
+    refresh_token: Option<String>,
+    token_expires_at: Option<String>,
+    remote_folder_id: Option<String>,
+    passphrase: String,
+    interval_hours: i64,
+    last_backup_at: Option<String>,
+    last_error: Option<String>,
+    created_at: String,
+}
+
+impl TargetRow {
+    fn provider(&self) -> Result<BackupProvider> {
+        BackupProvider::parse(&self.provider)
+            .ok_or_else(|| Error::Validation(format!("Unknown backup provider {}", self.provider)))
+    }
+
+    fn to_target(&self) -> Result<BackupTarget> {
+        Ok(BackupTarget {
+            id: self.id.clone(),
+            provider: self.provider()?,
+            folder_path: self.folder_path.clone(),
+            interval_hours: self.interval_hours,
+            last_backup_at: self.last_backup_at.clone(),
+            last_error: self.last_error.clone(),
+            created_at: self.created_at.clone(),
+        })
+    }
+
+    fn client(&self) -> Result<Client<'_>> {
+        Ok(Client {
+            id: self
+                .client_id
+                .as_deref()
+                .ok_or_else(|| Error::Validation("Backup target has no client id".to_string()))?,
+            secret: self.client_secret.as_deref(),
+        })
+    }
+
+    /// Due if it never ran or its interval has passed.
+    fn is_due(&self, at: DateTime<Utc>) -> bool {
+        self.last_backup_at
+            .as_deref()
+            .and_then(|last| DateTime::parse_from_rfc3339(last).ok())
+            .is_none_or(|last| {
+                at - last.with_timezone(&Utc) >= chrono::Duration::hours(self.interval_hours)
+            })
+    }
+}
+
+const SELECT_TARGET: &str = "SELECT id, provider, folder_path, client_id, client_secret,
+        access_token, refresh_token, token_expires_at, remote_folder_id, passphrase,
+        interval_hours, last_backup_at, last_error, created_at
+     FROM backup_targets";
+
+async fn load(pool: &SqlitePool, id: &str) -> Result<TargetRow> {
+    sqlx::query_as::<_, TargetRow>(&format!("{SELECT_TARGET} WHERE id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| Error::Validation("Backup target not found".to_string()))
+}
+
+fn check_interval(interval_hours: i64) -> Result<()> {
+    if (MIN_INTERVAL_HOURS..=MAX_INTERVAL_HOURS).contains(&interval_hours) {
+        Ok(())
+    } else {
+        Err(Error::Validation(format!(
+            "Backup interval must be between {MIN_INTERVAL_HOURS} and {MAX_INTERVAL_HOURS} hours"
+        )))
+    }
+}
+
+pub async fn targets(pool: &SqlitePool) -> Result<Vec<BackupTarget>> {
+    sqlx::query_as::<_, TargetRow>(&format!("{SELECT_TARGET} ORDER BY created_at ASC"))
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(TargetRow::to_target)
+        .collect()
+}
+
+/// The iCloud Drive folder, on Macs signed in to iCloud.
+pub fn icloud_drive_dir() -> Option<PathBuf> {
+    if !cfg!(target_os = "macos") {
+        return None;
+    }
+    let home = std::env::var_os("HOME")?;
+    let dir = Path::new(&home).join("Library/Mobile Documents/com~apple~CloudDocs");
+    dir.is_dir().then_some(dir)
+}
+
+/// Back up into a local folder that some sync client mirrors.
+pub async fn add_folder(
+    pool: &SqlitePool,
+    folder: &Path,
+    passphrase: &str,
+    interval_hours: i64,
+) -> Result<BackupTarget> {
+    archive::check_passphrase(passphrase)?;
+    check_interval(interval_hours)?;
+    if !folder.is_dir() {
+        return Err(Error::Validation(format!(
+            "{} is not a folder",
+            folder.display()
+        )));
+    }
+
+    let id = new_id();
+    let now = now();
+    sqlx::query(
+        "INSERT INTO backup_targets
+            (id, provider, folder_path, passphrase, interval_hours, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $6)",
+    )
+    .bind(&id)
+    .bind(BackupProvider::Folder.as_str())
+    .bind(folder.to_string_lossy().as_ref())
+    .bind(passphrase)
+    .bind(interval_hours)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+    load(pool, &id).await?.to_target()
+}
+
+/// Sign in to a cloud provider in the browser and add it as a target.
+pub async fn connect(
+    app: &AppHandle,
+    pool: &SqlitePool,
+    provider: BackupProvider,
+    client: Client<'_>,
+    passphrase: &str,
+    interval_hours: i64,
+) -> Result<BackupTarget> {
+    archive::check_passphrase(passphrase)?;
+    check_interval(interval_hours)?;
+    let endpoints = provider
+        .endpoints()
+        .ok_or_else(|| Error::Validation("Pick a folder instead of signing in".to_string()))?;
+
+    let tokens = oauth::authorize(app, endpoints, &client).await?;
+    let id = new_id();
+    let now = now();
+    sqlx::query(
+        "INSERT INTO backup_targets
+            (id, provider, client_id, client_secret, access_token, refresh_token,
+             token_expires_at, passphrase, interval_hours, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $10)",
+    )
+    .bind(&id)
+    .bind(provider.as_str())
+    .bind(client.id)
+    .bind(client.secret)
+    .bind(&tokens.access_token)
+    .bind(&tokens.refresh_token)
+    .bind(&tokens.expires_at)
+    .bind(passphrase)
+    .bind(interval_hours)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+    load(pool, &id).await?.to_target()
+}
+
+pub async fn remove(pool: &SqlitePool, id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM backup_targets WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// An access token that is good for a while yet, refreshed if needed.
+async fn access_token(
+    pool: &SqlitePool,
+    row: &TargetRow,
+    provider: BackupProvider,
+) -> Result<String> {
+    let fresh = row
+        .token_expires_at
+        .as_deref()
+        .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+        .is_none_or(|at| at.with_timezone(&Utc) - TOKEN_MARGIN > Utc::now());
+    if let (true, Some(token)) = (fresh, &row.access_token) {
+        return Ok(token.clone());
+    }
+
+    let refresh_token = row.refresh_token.clone().ok_or_else(|| {
+        Error::Validation("Sign-in expired. Connect the backup target again.".to_string())
+    })?;
+    let endpoints = provider.endpoints().expect("cloud provider");
+    let client_id = row.client()?.id.to_string();
+    let client_secret = row.client_secret.clone();
+    let tokens: Tokens = tauri::async_runtime::spawn_blocking(move || {
+        let client = Client {
+            id: &client_id,
+            secret: client_secret.as_deref(),
+        };
+        oauth::refresh(endpoints, &client, &refresh_token)
+    })
+    .await??;
+
+    sqlx::query(
+        "UPDATE backup_targets
+         SET access_token = $1, refresh_token = $2, token_expires_at = $3, updated_at = $4
+         WHERE id = $5",
+    )
+    .bind(&tokens.access_token)
+    .bind(&tokens.refresh_token)
+    .bind(&tokens.expires_at)
+    .bind(now())
+    .bind(&row.id)
+    .execute(pool)
+    .await?;
+    Ok(tokens.access_token)
+}
+
+/// File name for a backup made today.
+fn file_name() -> String {
+    let weekday = chrono::Local::now().format("%A").to_string().to_lowercase();
+    format!("goaldy-{weekday}.{EXTENSION}")
+}
+
+async fn deliver(pool: &SqlitePool, row: &TargetRow, data: Vec<u8>) -> Result<()> {
+    let provider = row.provider()?;
+    let name = file_name();
+    match provider {
+        BackupProvider::Folder => {
+            let folder = PathBuf::from(row.folder_path.clone().unwrap_or_default());
+            tauri::async_runtime::spawn_blocking(move || {
+                providers::copy_to_folder(&folder, &name, &data)
+            })
+            .await??;
+        }
+        BackupProvider::Dropbox => {
+            let token = access_token(pool, row, provider).await?;
+            tauri::async_runtime::spawn_blocking(move || {
+                providers::upload_dropbox(&token, &name, &data)
+            })
+            .await??;
+        }
+        BackupProvider::GoogleDrive => {
+            let token = access_token(pool, row, provider).await?;
+            let folder_id = row.remote_folder_id.clone();
+            let folder_id = tauri::async_runtime::spawn_blocking(move || {
+                providers::upload_google_drive(&token, folder_id.as_deref(), &name, &data)
+            })
+            .await??;
+            sqlx::query("UPDATE backup_targets SET remote_folder_id = $1 WHERE id = $2")
+                .bind(folder_id)
+                .bind(&row.id)
+                .execute(pool)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Back up to one target now. The outcome is recorded on the target either
+/// way, so settings can show when the last backup succeeded.
+pub async fn run(app: &AppHandle, pool: &SqlitePool, id: &str) -> Result<BackupTarget> {
+    let row = load(pool, id).await?;
+    let scratch = app.path().app_cache_dir()?;
+    std::fs::create_dir_all(&scratch)?;
+    let path = scratch.join(format!("backup-{}.{EXTENSION}", new_id()));
+
+    let outcome = async {
+        archive::export(app, pool, &path, &row.passphrase).await?;
+        let data = std::fs::read(&path)?;
+        deliver(pool, &row, data).await
+    }
+    .await;
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+
+    let now = now();
+    match &outcome {
+        Ok(()) => {
+            sqlx::query(
+                "UPDATE backup_targets
+                 SET last_backup_at = $1, last_error = NULL, updated_at = $1
+                 WHERE id = $2",
+            )
+            .bind(&now)
+            .bind(id)
+            .execute(pool)
+            .await?;
+            app_meta::set(pool, app_meta::LAST_BACKUP, &now).await?;
+        }
+        Err(e) => {
+            sqlx::query("UPDATE backup_targets SET last_error = $1, updated_at = $2 WHERE id = $3")
+                .bind(e.to_string())
+                .bind(&now)
+                .bind(id)
+                .execute(pool)
+                .await?;
+        }
+    }
+    outcome?;
+    load(pool, id).await?.to_target()
+}
+
+/// Back up to every target whose interval has passed. Cloud targets wait
+/// while offline.
+pub async fn run_due(app: &AppHandle, pool: &SqlitePool) -> Result<()> {
+    let rows = sqlx::query_as::<_, TargetRow>(SELECT_TARGET)
+        .fetch_all(pool)
+        .await?;
+    let online = app.state::<Connectivity>().is_online();
+    let at = Utc::now();
+    for row in rows
+        .iter()
+        .filter(|row| row.is_due(at))
+        .filter(|row| online || row.provider == BackupProvider::Folder.as_str())
+    {
+        if let Err(e) = run(app, pool, &row.id).await {
+            eprintln!("[Backup] Backup to {} failed: {e}", row.provider);
+        }
+    }
+    Ok(())
+}
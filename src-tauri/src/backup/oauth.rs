@@ -0,0 +1,204 @@
+//! OAuth 2 authorization code flow with PKCE for installed apps.
+//!
+//! The provider's consent page opens in the browser and redirects back to a
+//! one-shot listener on 127.0.0.1, which hands over the code. No client
+//! secret is needed where the provider supports PKCE alone; Google still
+//! wants the (non-secret) one issued for desktop clients.
+
+use std::time::{Duration, Instant};
+
+use base64::prelude::{Engine, BASE64_URL_SAFE_NO_PAD};
+use chrono::Utc;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+use tauri_plugin_opener::OpenerExt;
+use url::Url;
+
+use crate::db::timestamp;
+use crate::error::{Error, Result};
+
+/// How long to wait for the user to finish in the browser.
+const CONSENT_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+const CALLBACK_PATH: &str = "/callback";
+
+const SIGNED_IN_PAGE: &str =
+    "<html><body><p>Goaldy is connected. You can close this tab.</p></body></html>";
+
+/// Where a provider authorizes and issues tokens.
+pub struct Endpoints {
+    pub authorize_url: &'static str,
+    pub token_url: &'static str,
+    pub scope: Option<&'static str>,
+    /// Provider-specific parameters asking for a refresh token.
+    pub offline_params: &'static [(&'static str, &'static str)],
+}
+
+/// The client registered with the provider.
+pub struct Client<'a> {
+    pub id: &'a str,
+    pub secret: Option<&'a str>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Tokens {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+}
+
+fn oauth_error(e: impl std::fmt::Display) -> Error {
+    Error::Remote(format!("sign-in failed: {e}"))
+}
+
+fn random_token() -> String {
+    BASE64_URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>())
+}
+
+/// Run the whole consent flow and return the first tokens.
+pub async fn authorize(
+    app: &AppHandle,
+    endpoints: &Endpoints,
+    client: &Client<'_>,
+) -> Result<Tokens> {
+    let server = tiny_http::Server::http("127.0.0.1:0").map_err(oauth_error)?;
+    let port = server
+        .server_addr()
+        .to_ip()
+        .map(|addr| addr.port())
+        .ok_or_else(|| oauth_error("no port"))?;
+    let redirect_uri = format!("http://127.0.0.1:{port}{CALLBACK_PATH}");
+
+    let verifier = random_token();
+    let challenge = BASE64_URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+    let state = random_token();
+
+    let mut params = vec![
+        ("client_id", client.id),
+        ("response_type", "code"),
+        ("redirect_uri", redirect_uri.as_str()),
+        ("code_challenge", challenge.as_str()),
+        ("code_challenge_method", "S256"),
+        ("state", state.as_str()),
+    ];
+    if let Some(scope) = endpoints.scope {
+        params.push(("scope", scope));
+    }
+    params.extend_from_slice(endpoints.offline_params);
+    let url = Url::parse_with_params(endpoints.authorize_url, &params).map_err(oauth_error)?;
+    app.opener()
+        .open_url(url.as_str(), None::<&str>)
+        .map_err(oauth_error)?;
+
+    let code =
+        tauri::async_runtime::spawn_blocking(move || wait_for_code(&server, &state)).await??;
+
+    let mut form = vec![
+        ("grant_type", "authorization_code"),
+        ("code", code.as_str()),
+        ("redirect_uri", redirect_uri.as_str()),
+        ("code_verifier", verifier.as_str()),
+        ("client_id", client.id),
+    ];
+    if let Some(secret) = client.secret {
+        form.push(("client_secret", secret));
+    }
+    let form: Vec<(String, String)> = form
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    let token_url = endpoints.token_url;
+    tauri::async_runtime::spawn_blocking(move || request_tokens(token_url, &form)).await?
+}
+
+/// Answer redirects until one carries a code for our `state`.
+fn wait_for_code(server: &tiny_http::Server, state: &str) -> Result<String> {
+    let deadline = Instant::now() + CONSENT_TIMEOUT;
+    while Instant::now() < deadline {
+        let request = match server.recv_timeout(Duration::from_secs(1)) {
+            Ok(Some(request)) => request,
+            Ok(None) => continue,
+            Err(e) => return Err(oauth_error(e)),
+        };
+        let Ok(url) = Url::parse(&format!("http://127.0.0.1{}", request.url())) else {
+            let _ = request.respond(tiny_http::Response::empty(400));
+            continue;
+        };
+        if url.path() != CALLBACK_PATH {
+            let _ = request.respond(tiny_http::Response::empty(404));
+            continue;
+        }
+
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        };
+        if param("state").as_deref() != Some(state) {
+            let _ = request.respond(tiny_http::Response::empty(400));
+            continue;
+        }
+        if let Some(error) = param("error") {
+            let _ = request.respond(tiny_http::Response::from_string(
+                "Sign-in was cancelled. You can close this tab.",
+            ));
+            return Err(oauth_error(error));
+        }
+        let Some(code) = param("code") else {
+            let _ = request.respond(tiny_http::Response::empty(400));
+            continue;
+        };
+        let page = tiny_http::Response::from_string(SIGNED_IN_PAGE).with_header(
+            tiny_http::Header::from_bytes("Content-Type", "text/html; charset=utf-8")
+                .expect("static header"),
+        );
+        let _ = request.respond(page);
+        return Ok(code);
+    }
+    Err(Error::Validation(
+        "Sign-in timed out. Try connecting again.".to_string(),
+    ))
+}
+
+fn request_tokens(token_url: &str, form: &[(String, String)]) -> Result<Tokens> {
+    let form: Vec<(&str, &str)> = form.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+    let response = ureq::post(token_url)
+        .timeout(Duration::from_secs(30))
+        .send_form(&form)
+        .map_err(oauth_error)?;
+    let body: TokenResponse =
+        serde_json::from_reader(response.into_reader()).map_err(oauth_error)?;
+    Ok(Tokens {
+        access_token: body.access_token,
+        refresh_token: body.refresh_token,
+        expires_at: body
+            .expires_in
+            .map(|secs| timestamp(Utc::now() + chrono::Duration::seconds(secs))),
+    })
+}
+
+/// Trade a refresh token for a new access token. Blocking.
+pub fn refresh(endpoints: &Endpoints, client: &Client<'_>, refresh_token: &str) -> Result<Tokens> {
+    let mut form = vec![
+        ("grant_type".to_string(), "refresh_token".to_string()),
+        ("refresh_token".to_string(), refresh_token.to_string()),
+        ("client_id".to_string(), client.id.to_string()),
+    ];
+    if let Some(secret) = client.secret {
+        form.push(("client_secret".to_string(), secret.to_string()));
+    }
+    let mut tokens = request_tokens(endpoints.token_url, &form)?;
+    // Most providers keep the refresh token valid and don't send it again.
+    tokens
+        .refresh_token
+        .get_or_insert_with(|| refresh_token.to_string());
+    Ok(tokens)
+}
@@ -0,0 +1,168 @@
+//! Putting a finished archive where each kind of target keeps it.
+//! Everything here is blocking.
+
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use super::oauth::Endpoints;
+use crate::error::{Error, Result};
+
+pub const DROPBOX: Endpoints = Endpoints {
+    authorize_url: "https://www.dropbox.com/oauth2/authorize",
+    token_url: "https://api.dropboxapi.com/oauth2/token",
+    // App-folder access is configured on the Dropbox app itself.
+    scope: None,
+    offline_params: &[("token_access_type", "offline")],
+};
+
+pub const GOOGLE_DRIVE: Endpoints = Endpoints {
+    authorize_url: "https://accounts.google.com/o/oauth2/v2/auth",
+    token_url: "https://oauth2.googleapis.com/token",
+    // Only files this app created are visible to it.
+    scope: Some("https://www.googleapis.com/auth/drive.file"),
+    offline_params: &[("access_type", "offline"), ("prompt", "consent")],
+};
+
+/// Name of the Google Drive folder backups go into.
+const DRIVE_FOLDER_NAME: &str = "Goaldy Backups";
+
+const DRIVE_FOLDER_MIME: &str = "application/vnd.google-apps.folder";
+
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+fn upload_error(e: ureq::Error) -> Error {
+    match e {
+        ureq::Error::Status(code, response) => {
+            let body = response.into_string().unwrap_or_default();
+            Error::Remote(format!("upload failed ({code}): {}", body.trim()))
+        }
+        ureq::Error::Transport(e) => Error::Remote(format!("upload failed: {e}")),
+    }
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(response: ureq::Response) -> Result<T> {
+    serde_json::from_reader(response.into_reader())
+        .map_err(|e| Error::Remote(format!("unexpected response: {e}")))
+}
+
+/// Write into a local folder (iCloud Drive or whatever the user picked),
+/// via a temporary name so a sync client never uploads half a file.
+pub fn copy_to_folder(folder: &Path, name: &str, data: &[u8]) -> Result<()> {
+    if !folder.is_dir() {
+        return Err(Error::Validation(format!(
+            "Backup folder {} is not available",
+            folder.display()
+        )));
+    }
+    let partial = folder.join(format!(".{name}.partial"));
+    std::fs::write(&partial, data)?;
+    std::fs::rename(&partial, folder.join(name))?;
+    Ok(())
+}
+
+/// Upload into the app folder, replacing the file of the same name.
+pub fn upload_dropbox(access_token: &str, name: &str, data: &[u8]) -> Result<()> {
+    let arg = serde_json::json!({
+        "path": format!("/{name}"),
+        "mode": "overwrite",
+        "mute": true,
+    });
+    ureq::post("https://content.dropboxapi.com/2/files/upload")
+        .timeout(UPLOAD_TIMEOUT)
+        .set("Authorization", &format!("Bearer {access_token}"))
+        .set("Dropbox-API-Arg", &arg.to_string())
+        .set("Content-Type", "application/octet-stream")
+        .send_bytes(data)
+        .map_err(upload_error)?;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct DriveFile {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct DriveFileList {
+    files: Vec<DriveFile>,
+}
+
+fn drive_find(access_token: &str, query: &str) -> Result<Option<String>> {
+    let response = ureq::get("https://www.googleapis.com/drive/v3/files")
+        .set("Authorization", &format!("Bearer {access_token}"))
+        .query("q", query)
+        .query("fields", "files(id)")
+        .call()
+        .map_err(upload_error)?;
+    let list: DriveFileList = read_json(response)?;
+    Ok(list.files.into_iter().next().map(|f| f.id))
+}
+
+fn drive_folder(access_token: &str) -> Result<String> {
+    let query = format!(
+        "name = '{DRIVE_FOLDER_NAME}' and mimeType = '{DRIVE_FOLDER_MIME}' and trashed = false"
+    );
+    if let Some(id) = drive_find(access_token, &query)? {
+        return Ok(id);
+    }
+    let metadata = serde_json::json!({ "name": DRIVE_FOLDER_NAME, "mimeType": DRIVE_FOLDER_MIME });
+    let response = ureq::post("https://www.googleapis.com/drive/v3/files")
+        .set("Authorization", &format!("Bearer {access_token}"))
+        .set("Content-Type", "application/json")
+        .send_string(&metadata.to_string())
+        .map_err(upload_error)?;
+    Ok(read_json::<DriveFile>(response)?.id)
+}
+
+/// Upload into the backups folder, replacing the file of the same name.
+/// Returns the folder id to reuse next time.
+pub fn upload_google_drive(
+    access_token: &str,
+    folder_id: Option<&str>,
+    name: &str,
+    data: &[u8],
+) -> Result<String> {
+    let folder_id = match folder_id {
+        Some(id) => id.to_string(),
+        None => drive_folder(access_token)?,
+    };
+    let query = format!("name = '{name}' and '{folder_id}' in parents and trashed = false");
+
+    if let Some(file_id) = drive_find(access_token, &query)? {
+        ureq::request(
+            "PATCH",
+            &format!("https://www.googleapis.com/upload/drive/v3/files/{file_id}"),
+        )
+        .timeout(UPLOAD_TIMEOUT)
+        .query("uploadType", "media")
+        .set("Authorization", &format!("Bearer {access_token}"))
+        .set("Content-Type", "application/octet-stream")
+        .send_bytes(data)
+        .map_err(upload_error)?;
+        return Ok(folder_id);
+    }
+
+    let boundary = format!("goaldy-{}", crate::db::new_id());
+    let metadata = serde_json::json!({ "name": name, "parents": [folder_id] });
+    let mut body = format!(
+        "--{boundary}\r\nContent-Type: application/json; charset=UTF-8\r\n\r\n{metadata}\r\n\
+         --{boundary}\r\nContent-Type: application/octet-stream\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(data);
+    body.extend_from_slice(format!("\r\n--{boundary}--").as_bytes());
+
+    ureq::post("https://www.googleapis.com/upload/drive/v3/files")
+        .timeout(UPLOAD_TIMEOUT)
+        .query("uploadType", "multipart")
+        .set("Authorization", &format!("Bearer {access_token}"))
+        .set(
+            "Content-Type",
+            &format!("multipart/related; boundary={boundary}"),
+        )
+        .send_bytes(&body)
+        .map_err(upload_error)?;
+    Ok(folder_id)
+}
@@ -0,0 +1,228 @@
+//! Blocking client for the GoCardless Bank Account Data API (formerly
+//! Nordigen).
+//!
+//! Every client starts from a fresh access token for the user's secret id
+//! and key; tokens last a day, far longer than a fetch takes.
+
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::error::{Error, Result};
+
+const BASE_URL: &str = "https://bankaccountdata.gocardless.com/api/v2";
+
+/// Days of history asked for; every bank gives at least this many.
+pub const HISTORY_DAYS: u32 = 90;
+
+/// Days a consent lasts; the most every bank allows.
+pub const ACCESS_DAYS: u32 = 90;
+
+/// User secrets from the GoCardless Bank Account Data portal.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct Credentials {
+    pub secret_id: String,
+    pub secret_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct Institution {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub bic: Option<String>,
+    #[serde(default)]
+    pub logo: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Requisition {
+    pub id: String,
+    /// "LN" once linked, "EX" expired, "RJ" rejected, "SU" suspended;
+    /// anything else is still in progress at the bank.
+    pub status: String,
+    pub link: String,
+    #[serde(default)]
+    pub accounts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Amount {
+    pub amount: String,
+    pub currency: String,
+}
+
+/// A booked transaction, as much of it as we use.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+    pub transaction_id: Option<String>,
+    pub internal_transaction_id: Option<String>,
+    pub booking_date: Option<String>,
+    pub value_date: Option<String>,
+    pub transaction_amount: Amount,
+    pub creditor_name: Option<String>,
+    pub remittance_information_unstructured: Option<String>,
+    #[serde(default)]
+    pub remittance_information_unstructured_array: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Token {
+    access: String,
+}
+
+#[derive(Deserialize)]
+struct TransactionsResponse {
+    transactions: Transactions,
+}
+
+#[derive(Deserialize)]
+struct Transactions {
+    #[serde(default)]
+    booked: Vec<Transaction>,
+}
+
+fn remote_error(e: ureq::Error) -> Error {
+    match e {
+        ureq::Error::Status(code, response) => {
+            let body = response.into_string().unwrap_or_default();
+            Error::Remote(format!("GoCardless returned {code}: {}", body.trim()))
+        }
+        ureq::Error::Transport(e) => Error::Remote(format!("GoCardless unreachable: {e}")),
+    }
+}
+
+fn parse<T: DeserializeOwned>(response: ureq::Response) -> Result<T> {
+    serde_json::from_reader(response.into_reader())
+        .map_err(|e| Error::Remote(format!("unexpected response from GoCardless: {e}")))
+}
+
+fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(10))
+        .timeout(Duration::from_secs(60))
+        .build()
+}
+
+pub struct Client {
+    agent: ureq::Agent,
+    token: String,
+}
+
+impl Client {
+    pub fn connect(credentials: &Credentials) -> Result<Self> {
+        let agent = agent();
+        let response = agent
+            .post(&format!("{BASE_URL}/token/new/"))
+            .set("Content-Type", "application/json")
+            .send_string(
+                &json!({
+                    "secret_id": credentials.secret_id,
+                    "secret_key": credentials.secret_key,
+                })
+                .to_string(),
+            )
+            .map_err(|e| match e {
+                ureq::Error::Status(401 | 403, _) => {
+                    Error::Validation("GoCardless didn't accept the secret id and key".to_string())
+                }
+                e => remote_error(e),
+            })?;
+        let token: Token = parse(response)?;
+        Ok(Self {
+            agent,
+            token: token.access,
+        })
+    }
+
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        self.agent
+            .request(method, &format!("{BASE_URL}{path}"))
+            .set("Authorization", &format!("Bearer {}", self.token))
+            .set("Accept", "application/json")
+            .set("Content-Type", "application/json")
+    }
+
+    /// Banks in `country`, a two-letter ISO code.
+    pub fn institutions(&self, country: &str) -> Result<Vec<Institution>> {
+        let response = self
+            .request("GET", "/institutions/")
+            .query("country", country)
+            .call()
+            .map_err(remote_error)?;
+        parse(response)
+    }
+
+    /// Start a consent at `institution_id`. The user approves it at the
+    /// requisition's link and is sent back to `redirect`.
+    pub fn create_requisition(
+        &self,
+        institution_id: &str,
+        reference: &str,
+        redirect: &str,
+    ) -> Result<Requisition> {
+        let agreement: Value = parse(
+            self.request("POST", "/agreements/enduser/")
+                .send_string(
+                    &json!({
+                        "institution_id": institution_id,
+                        "max_historical_days": HISTORY_DAYS,
+                        "access_valid_for_days": ACCESS_DAYS,
+                        "access_scope": ["transactions"],
+                    })
+                    .to_string(),
+                )
+                .map_err(remote_error)?,
+        )?;
+        let agreement = agreement["id"]
+            .as_str()
+            .ok_or_else(|| Error::Remote("GoCardless returned no agreement".to_string()))?;
+        parse(
+            self.request("POST", "/requisitions/")
+                .send_string(
+                    &json!({
+                        "institution_id": institution_id,
+                        "agreement": agreement,
+                        "reference": reference,
+                        "redirect": redirect,
+                    })
+                    .to_string(),
+                )
+                .map_err(remote_error)?,
+        )
+    }
+
+    pub fn requisition(&self, id: &str) -> Result<Requisition> {
+        parse(
+            self.request("GET", &format!("/requisitions/{id}/"))
+                .call()
+                .map_err(remote_error)?,
+        )
+    }
+
+    /// Revoke the consent. One that is already gone counts as revoked.
+    pub fn delete_requisition(&self, id: &str) -> Result<()> {
+        match self
+            .request("DELETE", &format!("/requisitions/{id}/"))
+            .call()
+        {
+            Ok(_) | Err(ureq::Error::Status(404, _)) => Ok(()),
+            Err(e) => Err(remote_error(e)),
+        }
+    }
+
+    /// Booked transactions of an account from `date_from` ("YYYY-MM-DD").
+    pub fn transactions(&self, account_id: &str, date_from: &str) -> Result<Vec<Transaction>> {
+        let response: TransactionsResponse = parse(
+            self.request("GET", &format!("/accounts/{account_id}/transactions/"))
+                .query("date_from", date_from)
+                .call()
+                .map_err(remote_error)?,
+        )?;
+        Ok(response.transactions.booked)
+    }
+}
@@ -0,0 +1,954 @@
+//! Open banking: spending fetched from the user's bank accounts through
+//! GoCardless Bank Account Data (formerly Nordigen), which covers most
+//! banks in the EU and UK.
+//!
+//! Nothing happens until the user enters the secret id and key of their
+//! own GoCardless account, kept in the keychain. Connecting a bank creates
+//! a requisition the user approves on the bank's site, which sends them
+//! back through `goaldy://bank-connected`. Linked connections are fetched
+//! every few hours, within the four calls a day banks allow per account.
+//! New debits go through the same duplicate check and categorization rules
+//! as file imports, but land in `bank_transactions` as pending: they only
+//! become expenses once the user confirms them, with the suggested category
+//! or another.
+//!
+//! Banks in the US and Canada come through Plaid instead, which needs no
+//! secrets of the user's: the backend holds them, so Plaid is there for
+//! signed-in users. Linking happens in Plaid Link in the frontend, and
+//! every account of the linked item gets an account here that confirmed
+//! transactions are paid from. Items sync from a cursor, so each fetch only
+//! brings what changed.
+
+pub mod gocardless;
+pub mod plaid;
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_deep_link::DeepLinkExt;
+use url::Url;
+
+use crate::accounts::{self, AccountType, NewAccount};
+use crate::auth::{self, Session};
+use crate::backend::{self, BackendConfig};
+use crate::db::{new_id, now, timestamp, Db};
+use crate::error::{Error, Result};
+use crate::import::{self, ImportRow};
+use crate::{app_meta, currency, secrets};
+use gocardless::{Client, Credentials, Institution, Requisition};
+
+/// Emitted with the number of new pending transactions after a fetch.
+pub const TRANSACTIONS_EVENT: &str = "banking://transactions";
+
+const CREDENTIALS_SECRET: &str = "gocardless_credentials";
+
+/// Where the bank sends the user after they approve access.
+const REDIRECT: &str = "goaldy://bank-connected";
+
+/// Least time between scheduled fetches of a connection.
+const FETCH_GAP: Duration = Duration::hours(6);
+
+/// Days fetched again before the last fetch, for bookings that show up
+/// late.
+const OVERLAP_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct BankConnection {
+    pub id: String,
+    pub institution_id: String,
+    pub institution_name: String,
+    /// "gocardless" or "plaid".
+    pub provider: String,
+    /// "pending" until approved at the bank, then "linked"; "expired" or
+    /// "failed" need a new connection.
+    pub status: String,
+    /// The bank's approval page, while pending.
+    pub link: String,
+    pub account_count: usize,
+    pub access_valid_until: Option<String>,
+    pub last_fetched_at: Option<String>,
+    pub last_error: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct ConnectionRow {
+    id: String,
+    institution_id: String,
+    institution_name: String,
+    provider: String,
+    /// The Plaid item id for Plaid connections.
+    requisition_id: String,
+    status: String,
+    link: String,
+    account_ids: String,
+    access_valid_until: Option<String>,
+    last_fetched_at: Option<String>,
+    last_error: Option<String>,
+    sync_cursor: Option<String>,
+    created_at: String,
+}
+
+impl ConnectionRow {
+    fn account_ids(&self) -> Vec<String> {
+        serde_json::from_str(&self.account_ids).unwrap_or_default()
+    }
+
+    fn last_fetched(&self) -> Option<DateTime<Utc>> {
+        self.last_fetched_at
+            .as_deref()
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+            .map(|at| at.to_utc())
+    }
+
+    fn into_connection(self) -> BankConnection {
+        BankConnection {
+            account_count: self.account_ids().len(),
+            id: self.id,
+            institution_id: self.institution_id,
+            institution_name: self.institution_name,
+            provider: self.provider,
+            status: self.status,
+            link: self.link,
+            access_valid_until: self.access_valid_until,
+            last_fetched_at: self.last_fetched_at,
+            last_error: self.last_error,
+            created_at: self.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct BankTransaction {
+    pub id: String,
+    pub connection_id: String,
+    pub institution_name: String,
+    /// The bank's name for the account it was paid from.
+    pub bank_account_name: Option<String>,
+    /// The account here it is paid from once confirmed.
+    pub account_id: Option<String>,
+    /// "YYYY-MM-DD".
+    pub date: String,
+    /// Positive, in `currency`.
+    pub amount: f64,
+    pub currency: String,
+    pub description: Option<String>,
+    /// Suggested by the categorization rules.
+    pub category_id: Option<String>,
+}
+
+/// A pending transaction to turn into an expense.
+#[derive(Debug, Clone, Deserialize, specta::Type)]
+pub struct ConfirmTransaction {
+    pub id: String,
+    /// The suggested category if not given.
+    pub category_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ConfirmReport {
+    pub imported: usize,
+    /// Already logged by hand since the fetch; marked, not imported.
+    pub duplicates: usize,
+}
+
+const SELECT_CONNECTION: &str =
+    "SELECT id, institution_id, institution_name, provider, requisition_id, status, link,
+            account_ids, access_valid_until, last_fetched_at, last_error, sync_cursor, created_at
+     FROM bank_connections";
+
+/// Store the GoCardless secrets, or remove them with `None`. Falls back to
+/// the database where there is no keychain.
+pub async fn set_credentials(pool: &SqlitePool, credentials: Option<Credentials>) -> Result<()> {
+    if let Some(credentials) = &credentials {
+        let client = credentials.clone();
+        tauri::async_runtime::spawn_blocking(move || Client::connect(&client)).await??;
+    }
+    let json = credentials
+        .map(|c| serde_json::to_string(&c))
+        .transpose()
+        .map_err(|e| Error::Validation(format!("Invalid credentials: {e}")))?;
+    match secrets::set(CREDENTIALS_SECRET, json.clone()).await {
+        Ok(()) => app_meta::set(pool, CREDENTIALS_SECRET, "").await,
+        Err(e) => {
+            eprintln!("[Banking] Keeping credentials in the database: {e}");
+            app_meta::set(
+                pool,
+                CREDENTIALS_SECRET,
+                json.as_deref().unwrap_or_default(),
+            )
+            .await
+        }
+    }
+}
+
+async fn credentials(pool: &SqlitePool) -> Result<Option<Credentials>> {
+    let json = match secrets::get(CREDENTIALS_SECRET).await {
+        Ok(Some(json)) => Some(json),
+        _ => app_meta::get(pool, CREDENTIALS_SECRET)
+            .await?
+            .filter(|json| !json.is_empty()),
+    };
+    Ok(json.and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+pub async fn has_credentials(pool: &SqlitePool) -> Result<bool> {
+    Ok(credentials(pool).await?.is_some())
+}
+
+/// A client signed in with the stored credentials.
+async fn client(pool: &SqlitePool) -> Result<Client> {
+    let credentials = credentials(pool).await?.ok_or_else(|| {
+        Error::Validation("Enter your GoCardless secret id and key first".to_string())
+    })?;
+    tauri::async_runtime::spawn_blocking(move || Client::connect(&credentials)).await?
+}
+
+/// Run a blocking call with the client and hand it back.
+async fn with_client<T, F>(client: Client, call: F) -> Result<(Client, T)>
+where
+    T: Send + 'static,
+    F: FnOnce(&Client) -> Result<T> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = call(&client)?;
+        Ok((client, result))
+    })
+    .await?
+}
+
+/// Banks in `country`, a two-letter code such as "DE".
+pub async fn institutions(pool: &SqlitePool, country: &str) -> Result<Vec<Institution>> {
+    let country = country.trim().to_uppercase();
+    if country.len() != 2 || !country.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(Error::Validation(format!(
+            "\"{country}\" is not a country code"
+        )));
+    }
+    let client = client(pool).await?;
+    let (_, institutions) = with_client(client, move |c| c.institutions(&country)).await?;
+    Ok(institutions)
+}
+
+pub async fn list(pool: &SqlitePool) -> Result<Vec<BankConnection>> {
+    Ok(
+        sqlx::query_as::<_, ConnectionRow>(&format!("{SELECT_CONNECTION} ORDER BY created_at"))
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(ConnectionRow::into_connection)
+            .collect(),
+    )
+}
+
+async fn get(pool: &SqlitePool, id: &str) -> Result<ConnectionRow> {
+    sqlx::query_as::<_, ConnectionRow>(&format!("{SELECT_CONNECTION} WHERE id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| Error::Validation("Bank connection not found".to_string()))
+}
+
+/// Start connecting a bank. The user approves access at the returned
+/// connection's `link`.
+pub async fn connect(
+    pool: &SqlitePool,
+    institution_id: &str,
+    institution_name: &str,
+) -> Result<BankConnection> {
+    let client = client(pool).await?;
+    let id = new_id();
+    let (reference, institution) = (id.clone(), institution_id.to_string());
+    let redirect = format!("{REDIRECT}?ref={id}");
+    let (_, requisition) = with_client(client, move |c| {
+        c.create_requisition(&institution, &reference, &redirect)
+    })
+    .await?;
+    let now = now();
+    sqlx::query(
+        "INSERT INTO bank_connections
+            (id, institution_id, institution_name, requisition_id, link, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $6)",
+    )
+    .bind(&id)
+    .bind(institution_id)
+    .bind(institution_name.trim())
+    .bind(&requisition.id)
+    .bind(&requisition.link)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+    Ok(get(pool, &id).await?.into_connection())
+}
+
+fn status_of(requisition: &Requisition) -> &'static str {
+    match requisition.status.as_str() {
+        "LN" => "linked",
+        "EX" => "expired",
+        "RJ" | "SU" => "failed",
+        _ => "pending",
+    }
+}
+
+/// Bring a connection's status and accounts up to date with the bank.
+/// Plaid items are linked from the start and report trouble when synced.
+pub async fn refresh(pool: &SqlitePool, id: &str) -> Result<BankConnection> {
+    let row = get(pool, id).await?;
+    if row.provider == "plaid" {
+        return Ok(row.into_connection());
+    }
+    let client = client(pool).await?;
+    let requisition_id = row.requisition_id.clone();
+    let (_, requisition) = with_client(client, move |c| c.requisition(&requisition_id)).await?;
+    let status = status_of(&requisition);
+    let valid_until = match (status, row.status.as_str()) {
+        ("linked", "pending") => Some(timestamp(
+            Utc::now() + Duration::days(i64::from(gocardless::ACCESS_DAYS)),
+        )),
+        _ => row.access_valid_until,
+    };
+    sqlx::query(
+        "UPDATE bank_connections
+         SET status = $1, account_ids = $2, access_valid_until = $3, updated_at = $4
+         WHERE id = $5",
+    )
+    .bind(status)
+    .bind(serde_json::to_string(&requisition.accounts).unwrap_or_else(|_| "[]".to_string()))
+    .bind(valid_until)
+    .bind(now())
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(get(pool, id).await?.into_connection())
+}
+
+/// Revoke access at the provider and forget the connection and its pending
+/// transactions. Expenses already confirmed, and the accounts of a Plaid
+/// item, stay.
+pub async fn disconnect(pool: &SqlitePool, id: &str) -> Result<()> {
+    let row = get(pool, id).await?;
+    if row.provider == "plaid" {
+        let (config, session) = plaid_backend(pool).await?;
+        let item_id = row.requisition_id.clone();
+        tauri::async_runtime::spawn_blocking(move || plaid::remove(&config, &session, &item_id))
+            .await??;
+    } else if row.status != "expired" {
+        let client = client(pool).await?;
+        let requisition_id = row.requisition_id.clone();
+        with_client(client, move |c| c.delete_requisition(&requisition_id)).await?;
+    }
+    sqlx::query("DELETE FROM bank_connections WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// The backend and session Plaid calls go through.
+async fn plaid_backend(pool: &SqlitePool) -> Result<(BackendConfig, Session)> {
+    let config = backend::remembered(pool)
+        .await?
+        .ok_or_else(|| Error::Unsupported("This build has no sync backend".to_string()))?;
+    let session = auth::session(pool)
+        .await?
+        .ok_or_else(|| Error::Validation("Sign in to connect a bank through Plaid".to_string()))?;
+    Ok((config, session))
+}
+
+/// A token to open Plaid Link with in the frontend.
+pub async fn plaid_link_token(pool: &SqlitePool) -> Result<String> {
+    let (config, session) = plaid_backend(pool).await?;
+    tauri::async_runtime::spawn_blocking(move || plaid::link_token(&config, &session)).await?
+}
+
+fn account_type(account: &plaid::Account) -> AccountType {
+    match (account.kind.as_str(), account.subtype.as_deref()) {
+        ("credit", _) => AccountType::CreditCard,
+        (_, Some("savings")) => AccountType::Savings,
+        _ => AccountType::Checking,
+    }
+}
+
+/// Finish linking with the public token Plaid Link handed back. Every
+/// checking, savings and credit card account of the item gets an account
+/// here, opened today at its current balance when that is in the base
+/// currency.
+pub async fn link_plaid(pool: &SqlitePool, public_token: &str) -> Result<BankConnection> {
+    let (config, session) = plaid_backend(pool).await?;
+    let token = public_token.trim().to_string();
+    let item =
+        tauri::async_runtime::spawn_blocking(move || plaid::exchange(&config, &session, &token))
+            .await??;
+    // Linking an item again, to repair its login, keeps its accounts.
+    let existing: Option<(String,)> =
+        sqlx::query_as("SELECT id FROM bank_connections WHERE requisition_id = $1")
+            .bind(&item.item_id)
+            .fetch_optional(pool)
+            .await?;
+    if let Some((id,)) = existing {
+        sqlx::query(
+            "UPDATE bank_connections SET status = 'linked', last_error = NULL, updated_at = $1
+             WHERE id = $2",
+        )
+        .bind(now())
+        .bind(&id)
+        .execute(pool)
+        .await?;
+        return Ok(get(pool, &id).await?.into_connection());
+    }
+
+    let spending: Vec<&plaid::Account> = item
+        .accounts
+        .iter()
+        .filter(|a| matches!(a.kind.as_str(), "depository" | "credit"))
+        .collect();
+    let account_ids: Vec<&str> = spending.iter().map(|a| a.account_id.as_str()).collect();
+    let id = new_id();
+    let now = now();
+    sqlx::query(
+        "INSERT INTO bank_connections
+            (id, institution_id, institution_name, provider, requisition_id, link, status,
+             account_ids, created_at, updated_at)
+         VALUES ($1, $2, $3, 'plaid', $4, '', 'linked', $5, $6, $6)",
+    )
+    .bind(&id)
+    .bind(&item.institution.institution_id)
+    .bind(&item.institution.name)
+    .bind(&item.item_id)
+    .bind(serde_json::to_string(&account_ids).unwrap_or_else(|_| "[]".to_string()))
+    .bind(&now)
+    .execute(pool)
+    .await?;
+
+    let base = currency::base(pool).await?;
+    let user_id = auth::current_user_id(pool).await?;
+    let today = Local::now().date_naive();
+    for account in spending {
+        let kind = account_type(account);
+        let balances = &account.balances;
+        let opening_balance = balances
+            .current
+            .filter(|_| balances.iso_currency_code.as_deref() == Some(base.as_str()))
+            .map(|b| {
+                if kind == AccountType::CreditCard {
+                    -b
+                } else {
+                    b
+                }
+            });
+        let name = match &account.mask {
+            Some(mask) => format!("{} {} ••{mask}", item.institution.name, account.name),
+            None => format!("{} {}", item.institution.name, account.name),
+        };
+        let local = accounts::create(
+            pool,
+            user_id.as_deref(),
+            NewAccount {
+                name,
+                kind,
+                opening_balance,
+                opening_date: None,
+            },
+            today,
+        )
+        .await?;
+        sqlx::query(
+            "INSERT INTO bank_accounts
+                (id, connection_id, bank_account_id, name, mask, account_id, created_at, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $7)",
+        )
+        .bind(new_id())
+        .bind(&id)
+        .bind(&account.account_id)
+        .bind(&account.name)
+        .bind(&account.mask)
+        .bind(&local.id)
+        .bind(&now)
+        .execute(pool)
+        .await?;
+    }
+    Ok(get(pool, &id).await?.into_connection())
+}
+
+/// A new debit from the bank.
+struct Fetched {
+    bank_account_id: String,
+    transaction_id: String,
+    date: NaiveDate,
+    amount: f64,
+    currency: String,
+    description: Option<String>,
+}
+
+fn fetched(bank_account_id: &str, t: gocardless::Transaction) -> Option<Fetched> {
+    let date = t
+        .booking_date
+        .as_deref()
+        .or(t.value_date.as_deref())
+        .and_then(|d| NaiveDate::parse_from_str(d.get(..10)?, "%Y-%m-%d").ok())?;
+    let amount = -t.transaction_amount.amount.trim().parse::<f64>().ok()?;
+    if amount <= 0.0 {
+        return None;
+    }
+    let description = t
+        .creditor_name
+        .or(t.remittance_information_unstructured)
+        .or_else(|| Some(t.remittance_information_unstructured_array.join(" ")))
+        .map(|d| d.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|d| !d.is_empty());
+    // Not every bank gives ids; the booking itself stands in.
+    let transaction_id = t
+        .transaction_id
+        .or(t.internal_transaction_id)
+        .unwrap_or_else(|| {
+            format!(
+                "{date}:{}:{}",
+                t.transaction_amount.amount,
+                description.as_deref().unwrap_or_default()
+            )
+        });
+    Some(Fetched {
+        bank_account_id: bank_account_id.to_string(),
+        transaction_id,
+        date,
+        amount,
+        currency: t.transaction_amount.currency.to_uppercase(),
+        description,
+    })
+}
+
+/// Plaid lists pending card payments too; they come again under a new id
+/// once booked, so only booked ones are kept.
+fn plaid_fetched(t: &plaid::Transaction) -> Option<Fetched> {
+    if t.pending || t.amount <= 0.0 {
+        return None;
+    }
+    let currency = t
+        .iso_currency_code
+        .as_deref()
+        .or(t.unofficial_currency_code.as_deref())?;
+    Some(Fetched {
+        bank_account_id: t.account_id.clone(),
+        transaction_id: t.transaction_id.clone(),
+        date: NaiveDate::parse_from_str(&t.date, "%Y-%m-%d").ok()?,
+        amount: t.amount,
+        currency: currency.to_uppercase(),
+        description: t
+            .merchant_name
+            .clone()
+            .or_else(|| t.name.clone())
+            .filter(|d| !d.trim().is_empty()),
+    })
+}
+
+/// Keep the fetched debits not seen before, checked against the expenses
+/// and categorized. Returns how many are pending.
+async fn store(pool: &SqlitePool, connection_id: &str, fetched: Vec<Fetched>) -> Result<usize> {
+    let mut new = Vec::new();
+    for f in fetched {
+        let (seen,): (bool,) = sqlx::query_as(
+            "SELECT EXISTS(SELECT 1 FROM bank_transactions
+                           WHERE bank_account_id = $1 AND transaction_id = $2)",
+        )
+        .bind(&f.bank_account_id)
+        .bind(&f.transaction_id)
+        .fetch_one(pool)
+        .await?;
+        if !seen {
+            new.push(f);
+        }
+    }
+
+    let mut by_currency: BTreeMap<String, Vec<Fetched>> = BTreeMap::new();
+    for f in new {
+        by_currency.entry(f.currency.clone()).or_default().push(f);
+    }
+    let mut pending = 0;
+    for (currency, fetched) in by_currency {
+        let rows = fetched
+            .iter()
+            .zip(0u64..)
+            .map(|(f, line)| ImportRow {
+                line,
+                date: f.date,
+                amount: f.amount,
+                description: f.description.clone(),
+                category_id: None,
+                duplicate: false,
+            })
+            .collect();
+        let report = import::save(pool, rows, 0, None, Some(&currency), None, true).await?;
+        let now = now();
+        for (f, row) in fetched.iter().zip(&report.rows) {
+            let status = if row.duplicate {
+                "duplicate"
+            } else {
+                "pending"
+            };
+            pending += usize::from(!row.duplicate);
+            sqlx::query(
+                "INSERT OR IGNORE INTO bank_transactions
+                    (id, connection_id, bank_account_id, transaction_id, date, amount, currency,
+                     description, category_id, status, created_at, updated_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $11)",
+            )
+            .bind(new_id())
+            .bind(connection_id)
+            .bind(&f.bank_account_id)
+            .bind(&f.transaction_id)
+            .bind(f.date.format("%Y-%m-%d").to_string())
+            .bind(f.amount)
+            .bind(&f.currency)
+            .bind(&f.description)
+            .bind(&row.category_id)
+            .bind(status)
+            .bind(&now)
+            .execute(pool)
+            .await?;
+        }
+    }
+    Ok(pending)
+}
+
+/// Fetch new transactions of every linked connection, skipping those
+/// fetched in the last few hours unless `force`. Returns how many new ones
+/// wait for confirmation.
+pub async fn fetch(pool: &SqlitePool, force: bool) -> Result<usize> {
+    let now_utc = Utc::now();
+    let (plaid, gocardless): (Vec<_>, Vec<_>) =
+        sqlx::query_as::<_, ConnectionRow>(&format!("{SELECT_CONNECTION} WHERE status = 'linked'"))
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .filter(|row| {
+                force
+                    || row
+                        .last_fetched()
+                        .is_none_or(|at| now_utc - at >= FETCH_GAP)
+            })
+            .partition(|row| row.provider == "plaid");
+    let mut pending = 0;
+    if !gocardless.is_empty() {
+        pending += fetch_gocardless(pool, gocardless).await?;
+    }
+    if !plaid.is_empty() {
+        pending += sync_plaid(pool, plaid).await?;
+    }
+    Ok(pending)
+}
+
+/// Note the outcome of fetching a connection.
+async fn fetched_at(pool: &SqlitePool, id: &str, error: Option<String>) -> Result<()> {
+    sqlx::query(
+        "UPDATE bank_connections
+         SET last_fetched_at = CASE WHEN $1 IS NULL THEN $2 ELSE last_fetched_at END,
+             last_error = $1, updated_at = $2
+         WHERE id = $3",
+    )
+    .bind(&error)
+    .bind(now())
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn fetch_gocardless(pool: &SqlitePool, rows: Vec<ConnectionRow>) -> Result<usize> {
+    let mut client = client(pool).await?;
+    let now_utc = Utc::now();
+    let today = Local::now().date_naive();
+    let mut pending = 0;
+    for row in rows {
+        let expired = row
+            .access_valid_until
+            .as_deref()
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+            .is_some_and(|at| at.to_utc() <= now_utc);
+        if expired {
+            sqlx::query(
+                "UPDATE bank_connections SET status = 'expired', updated_at = $1 WHERE id = $2",
+            )
+            .bind(now())
+            .bind(&row.id)
+            .execute(pool)
+            .await?;
+            continue;
+        }
+        let from = match row.last_fetched() {
+            Some(at) => at.date_naive() - Duration::days(OVERLAP_DAYS),
+            None => today - Duration::days(i64::from(gocardless::HISTORY_DAYS)),
+        }
+        .format("%Y-%m-%d")
+        .to_string();
+
+        let accounts = row.account_ids();
+        let result = with_client(client, move |c| {
+            let mut fetched = Vec::new();
+            for account in &accounts {
+                fetched.extend(
+                    c.transactions(account, &from)?
+                        .into_iter()
+                        .filter_map(|t| self::fetched(account, t)),
+                );
+            }
+            Ok(fetched)
+        })
+        .await;
+        let error = match result {
+            Ok((returned, fetched)) => {
+                client = returned;
+                pending += store(pool, &row.id, fetched).await?;
+                None
+            }
+            Err(e) => {
+                // The client is gone with the failed call; sign in again
+                // for the next connection.
+                client = self::client(pool).await?;
+                Some(e.to_string())
+            }
+        };
+        fetched_at(pool, &row.id, error).await?;
+    }
+    Ok(pending)
+}
+
+/// Apply what changed in a Plaid item since its cursor. Changes only reach
+/// transactions still pending here.
+async fn apply_plaid(
+    pool: &SqlitePool,
+    row: &ConnectionRow,
+    pages: Vec<plaid::SyncPage>,
+) -> Result<usize> {
+    let accounts = row.account_ids();
+    let now = now();
+    let mut fetched = Vec::new();
+    for page in pages {
+        for removed in &page.removed {
+            sqlx::query(
+                "DELETE FROM bank_transactions
+                 WHERE connection_id = $1 AND transaction_id = $2 AND status = 'pending'",
+            )
+            .bind(&row.id)
+            .bind(&removed.transaction_id)
+            .execute(pool)
+            .await?;
+        }
+        for t in page.modified.iter().filter_map(plaid_fetched) {
+            sqlx::query(
+                "UPDATE bank_transactions SET date = $1, amount = $2, description = $3, updated_at = $4
+                 WHERE bank_account_id = $5 AND transaction_id = $6 AND status = 'pending'",
+            )
+            .bind(t.date.format("%Y-%m-%d").to_string())
+            .bind(t.amount)
+            .bind(&t.description)
+            .bind(&now)
+            .bind(&t.bank_account_id)
+            .bind(&t.transaction_id)
+            .execute(pool)
+            .await?;
+        }
+        fetched.extend(
+            page.added
+                .iter()
+                .chain(&page.modified)
+                .filter_map(plaid_fetched)
+                .filter(|t| accounts.contains(&t.bank_account_id)),
+        );
+    }
+    store(pool, &row.id, fetched).await
+}
+
+async fn sync_plaid(pool: &SqlitePool, rows: Vec<ConnectionRow>) -> Result<usize> {
+    let (config, session) = plaid_backend(pool).await?;
+    let mut pending = 0;
+    for row in rows {
+        let (config, session) = (config.clone(), session.clone());
+        let (item_id, mut cursor) = (row.requisition_id.clone(), row.sync_cursor.clone());
+        // The cursor only moves once every page is in.
+        let result = tauri::async_runtime::spawn_blocking(move || {
+            let mut pages = Vec::new();
+            loop {
+                let page = plaid::sync(&config, &session, &item_id, cursor.as_deref())?;
+                cursor = Some(page.next_cursor.clone());
+                let more = page.has_more;
+                pages.push(page);
+                if !more {
+                    return Ok::<_, Error>((pages, cursor));
+                }
+            }
+        })
+        .await?;
+        let error = match result {
+            Ok((pages, cursor)) => {
+                pending += apply_plaid(pool, &row, pages).await?;
+                sqlx::query("UPDATE bank_connections SET sync_cursor = $1 WHERE id = $2")
+                    .bind(cursor)
+                    .bind(&row.id)
+                    .execute(pool)
+                    .await?;
+                None
+            }
+            Err(e) => Some(e.to_string()),
+        };
+        fetched_at(pool, &row.id, error).await?;
+    }
+    Ok(pending)
+}
+
+/// Transactions waiting for confirmation, newest first.
+pub async fn pending(pool: &SqlitePool) -> Result<Vec<BankTransaction>> {
+    Ok(sqlx::query_as::<_, BankTransaction>(
+        "SELECT t.id, t.connection_id, c.institution_name, a.name AS bank_account_name,
+                acc.id AS account_id, t.date, t.amount, t.currency, t.description, t.category_id
+         FROM bank_transactions t
+         JOIN bank_connections c ON c.id = t.connection_id
+         LEFT JOIN bank_accounts a ON a.bank_account_id = t.bank_account_id
+         LEFT JOIN accounts acc ON acc.id = a.account_id AND acc.deleted_at IS NULL
+         WHERE t.status = 'pending'
+         ORDER BY t.date DESC, t.created_at DESC",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Turn pending transactions into expenses, paid from the account of the
+/// bank account they came from where there is one.
+pub async fn confirm(pool: &SqlitePool, items: &[ConfirmTransaction]) -> Result<ConfirmReport> {
+    let chosen: HashMap<&str, Option<&str>> = items
+        .iter()
+        .map(|i| (i.id.as_str(), i.category_id.as_deref()))
+        .collect();
+    type Group = (String, Option<String>);
+    let mut groups: BTreeMap<Group, Vec<(String, ImportRow)>> = BTreeMap::new();
+    for transaction in pending(pool).await? {
+        let Some(category_id) = chosen.get(transaction.id.as_str()) else {
+            continue;
+        };
+        let Ok(date) = NaiveDate::parse_from_str(&transaction.date, "%Y-%m-%d") else {
+            continue;
+        };
+        let row = ImportRow {
+            line: 0,
+            date,
+            amount: transaction.amount,
+            description: transaction.description,
+            category_id: category_id.map(str::to_string).or(transaction.category_id),
+            duplicate: false,
+        };
+        groups
+            .entry((transaction.currency, transaction.account_id))
+            .or_default()
+            .push((transaction.id, row));
+    }
+
+    let mut report = ConfirmReport {
+        imported: 0,
+        duplicates: 0,
+    };
+    for ((currency, account_id), entries) in groups {
+        let (ids, rows): (Vec<String>, Vec<ImportRow>) = entries.into_iter().unzip();
+        let saved = import::save(
+            pool,
+            rows,
+            0,
+            None,
+            Some(&currency),
+            account_id.as_deref(),
+            false,
+        )
+        .await?;
+        report.imported += saved.imported;
+        report.duplicates += saved.duplicates;
+        let now = now();
+        for (id, row) in ids.iter().zip(&saved.rows) {
+            sqlx::query(
+                "UPDATE bank_transactions SET status = $1, category_id = $2, updated_at = $3
+                 WHERE id = $4",
+            )
+            .bind(if row.duplicate {
+                "duplicate"
+            } else {
+                "confirmed"
+            })
+            .bind(&row.category_id)
+            .bind(&now)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        }
+    }
+    Ok(report)
+}
+
+/// Drop pending transactions that aren't spending to log.
+pub async fn dismiss(pool: &SqlitePool, ids: &[String]) -> Result<()> {
+    let now = now();
+    for id in ids {
+        sqlx::query(
+            "UPDATE bank_transactions SET status = 'dismissed', updated_at = $1
+             WHERE id = $2 AND status = 'pending'",
+        )
+        .bind(&now)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Fetch and tell the frontend about anything new.
+pub async fn fetch_and_announce(app: &AppHandle, pool: &SqlitePool, force: bool) -> Result<usize> {
+    let pending = fetch(pool, force).await?;
+    if pending > 0 {
+        app.emit(TRANSACTIONS_EVENT, pending)?;
+    }
+    Ok(pending)
+}
+
+/// The connection id in `goaldy://bank-connected?ref=<id>`.
+fn connected_ref(url: &Url) -> Option<String> {
+    if url.scheme() != "goaldy" || url.host_str() != Some("bank-connected") {
+        return None;
+    }
+    url.query_pairs()
+        .find(|(key, _)| key == "ref")
+        .map(|(_, value)| value.into_owned())
+}
+
+/// Finish connecting when the bank sends the user back to the app.
+pub fn watch(app: &AppHandle) {
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            open_link(&handle, &url);
+        }
+    });
+    if let Ok(Some(urls)) = app.deep_link().get_current() {
+        for url in urls {
+            open_link(app, &url);
+        }
+    }
+}
+
+fn open_link(app: &AppHandle, url: &Url) {
+    let Some(id) = connected_ref(url) else {
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let db = app.state::<Db>();
+        let result = async {
+            if refresh(db.pool(), &id).await?.status == "linked" {
+                fetch_and_announce(&app, db.pool(), true).await?;
+            }
+            Ok::<_, Error>(())
+        }
+        .await;
+        if let Err(e) = result {
+            eprintln!("[Banking] Finishing connection {id} failed: {e}");
+        }
+    });
+}
@@ -0,0 +1,153 @@
+//! Blocking client for Plaid, through the `plaid` edge function.
+//!
+//! Plaid's client secret and the access tokens of linked items can't live
+//! in the app, so the backend holds them per account and passes calls on:
+//! `link-token` starts Plaid Link, `exchange` turns the public token Link
+//! hands back into an item, `sync` pages through `/transactions/sync` from
+//! a cursor and `remove` revokes the item. The app only ever sees item ids.
+
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::auth::Session;
+use crate::backend::BackendConfig;
+use crate::error::{Error, Result};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Institution {
+    pub institution_id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Balances {
+    pub current: Option<f64>,
+    pub iso_currency_code: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Account {
+    pub account_id: String,
+    pub name: String,
+    pub mask: Option<String>,
+    /// "depository", "credit", "loan", "investment" or "other".
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub subtype: Option<String>,
+    #[serde(default)]
+    pub balances: Balances,
+}
+
+/// A linked item and the accounts it grants.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Item {
+    pub item_id: String,
+    pub institution: Institution,
+    pub accounts: Vec<Account>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Transaction {
+    pub transaction_id: String,
+    pub account_id: String,
+    /// Positive for money going out.
+    pub amount: f64,
+    pub iso_currency_code: Option<String>,
+    pub unofficial_currency_code: Option<String>,
+    /// "YYYY-MM-DD".
+    pub date: String,
+    pub name: Option<String>,
+    pub merchant_name: Option<String>,
+    #[serde(default)]
+    pub pending: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Removed {
+    pub transaction_id: String,
+}
+
+/// One page of changes since a cursor.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SyncPage {
+    #[serde(default)]
+    pub added: Vec<Transaction>,
+    #[serde(default)]
+    pub modified: Vec<Transaction>,
+    #[serde(default)]
+    pub removed: Vec<Removed>,
+    pub next_cursor: String,
+    pub has_more: bool,
+}
+
+fn call<T: DeserializeOwned>(
+    config: &BackendConfig,
+    session: &Session,
+    path: &str,
+    body: Value,
+) -> Result<T> {
+    let response = ureq::AgentBuilder::new()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .post(&format!("{}/functions/v1/plaid/{path}", config.url))
+        .set("apikey", &config.anon_key)
+        .set("Authorization", &format!("Bearer {}", session.access_token))
+        .set("Content-Type", "application/json")
+        .send_string(&body.to_string())
+        .map_err(|e| match e {
+            ureq::Error::Status(code, response) => {
+                let body = response.into_string().unwrap_or_default();
+                Error::Remote(format!("Plaid {path} returned {code}: {}", body.trim()))
+            }
+            ureq::Error::Transport(e) => Error::Remote(format!("Plaid unreachable: {e}")),
+        })?;
+    serde_json::from_reader(response.into_reader())
+        .map_err(|e| Error::Remote(format!("unexpected response from Plaid: {e}")))
+}
+
+#[derive(Deserialize)]
+struct LinkToken {
+    link_token: String,
+}
+
+/// A token to open Plaid Link with.
+pub fn link_token(config: &BackendConfig, session: &Session) -> Result<String> {
+    let token: LinkToken = call(config, session, "link-token", json!({}))?;
+    Ok(token.link_token)
+}
+
+/// Link the item Plaid Link returned `public_token` for.
+pub fn exchange(config: &BackendConfig, session: &Session, public_token: &str) -> Result<Item> {
+    call(
+        config,
+        session,
+        "exchange",
+        json!({ "public_token": public_token }),
+    )
+}
+
+/// Changes to the item's transactions since `cursor`, or everything on the
+/// first call.
+pub fn sync(
+    config: &BackendConfig,
+    session: &Session,
+    item_id: &str,
+    cursor: Option<&str>,
+) -> Result<SyncPage> {
+    call(
+        config,
+        session,
+        "sync",
+        json!({ "item_id": item_id, "cursor": cursor }),
+    )
+}
+
+pub fn remove(config: &BackendConfig, session: &Session, item_id: &str) -> Result<()> {
+    let _: Value = call(config, session, "remove", json!({ "item_id": item_id }))?;
+    Ok(())
+}
@@ -0,0 +1,492 @@
+//! Bills: fixed charges due on the same day every month, such as rent or a
+//! phone plan.
+//!
+//! Like a trial, each bill keeps a reminder queued in
+//! `scheduled_notifications`, `remind_days_before` its open due date: this
+//! month's until it is paid, then next month's. The reminder is keyed by
+//! bill and due date, so queueing it again only moves it. Cycles count from
+//! the day the bill was added. One that goes by unpaid is overdue until the
+//! month is over, except for autopay bills, which the bank pays and which
+//! count as paid once their due date has passed.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Datelike, Days, Local, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::AppHandle;
+
+use crate::db::{new_id, now, timestamp};
+use crate::error::{Error, Result};
+use crate::expenses::{self, Expense, NewExpense};
+use crate::notify;
+
+/// Days before the due date to remind, unless the user picks.
+pub const DEFAULT_REMIND_DAYS_BEFORE: i64 = 3;
+
+/// Local hour reminders are sent at.
+const REMINDER_HOUR: u32 = 9;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct Bill {
+    pub id: String,
+    pub user_id: Option<String>,
+    pub name: String,
+    pub amount: f64,
+    /// Day of the month, 1 to 31.
+    pub due_day: i64,
+    pub autopay: bool,
+    pub category_id: Option<String>,
+    pub remind_days_before: i64,
+    pub reminder_id: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub deleted_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct BillDue {
+    #[serde(flatten)]
+    pub bill: Bill,
+    /// "YYYY-MM-DD" of the open cycle.
+    pub due_date: String,
+    /// The due date went by without the bill being paid.
+    pub overdue: bool,
+    pub last_paid_on: Option<String>,
+}
+
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct NewBill {
+    pub name: String,
+    pub amount: f64,
+    pub due_day: i64,
+    #[serde(default)]
+    pub autopay: bool,
+    pub category_id: Option<String>,
+    /// Three days if not given; 0 reminds on the due date.
+    pub remind_days_before: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct BillPayment {
+    pub id: String,
+    pub bill_id: String,
+    /// "YYYY-MM" of the cycle paid.
+    pub month: String,
+    pub amount: f64,
+    pub paid_on: String,
+    /// The expense logged for it, if one was.
+    pub expense_id: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Default, Deserialize, specta::Type)]
+pub struct MarkPaid {
+    /// "YYYY-MM" of the cycle paid; the open one if not given.
+    pub month: Option<String>,
+    /// The bill's amount if not given.
+    pub amount: Option<f64>,
+    /// "YYYY-MM-DD"; today if not given.
+    pub paid_on: Option<String>,
+    /// Also log the payment as an expense in the bill's category.
+    #[serde(default)]
+    pub record_expense: bool,
+}
+
+/// The bill's due date in the month of `date`, on the last day of the
+/// month if the month is too short.
+fn due_in(due_day: i64, date: NaiveDate) -> NaiveDate {
+    let first = date.with_day(1).unwrap_or(date);
+    let last = first
+        .checked_add_months(Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .unwrap_or(first);
+    let day = u32::try_from(due_day.clamp(1, 31))
+        .unwrap_or(1)
+        .min(last.day());
+    first.with_day(day).unwrap_or(last)
+}
+
+fn month_key(date: NaiveDate) -> String {
+    date.format("%Y-%m").to_string()
+}
+
+/// The first due date from this month's on that is neither paid nor from
+/// before the bill was added.
+fn open_due(bill: &Bill, paid: &HashSet<String>, today: NaiveDate) -> NaiveDate {
+    let added = bill
+        .created_at
+        .get(..10)
+        .and_then(|day| NaiveDate::parse_from_str(day, "%Y-%m-%d").ok())
+        .unwrap_or(today);
+    let mut due = due_in(bill.due_day, today);
+    while due < added || paid.contains(&month_key(due)) {
+        let Some(next) = due.checked_add_months(Months::new(1)) else {
+            break;
+        };
+        due = due_in(bill.due_day, next);
+    }
+    due
+}
+
+async fn paid_months(pool: &SqlitePool, bill_id: &str) -> Result<HashSet<String>> {
+    let rows: Vec<(String,)> = sqlx::query_as("SELECT month FROM bill_payments WHERE bill_id = $1")
+        .bind(bill_id)
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().map(|(month,)| month).collect())
+}
+
+async fn active(pool: &SqlitePool) -> Result<Vec<Bill>> {
+    Ok(sqlx::query_as::<_, Bill>(
+        "SELECT * FROM bills WHERE deleted_at IS NULL ORDER BY due_day ASC, name ASC",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+async fn get(pool: &SqlitePool, id: &str) -> Result<Bill> {
+    sqlx::query_as::<_, Bill>("SELECT * FROM bills WHERE id = $1 AND deleted_at IS NULL")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| Error::Validation("Bill not found".to_string()))
+}
+
+/// Every bill with its open cycle, soonest due first.
+pub async fn list(pool: &SqlitePool, today: NaiveDate) -> Result<Vec<BillDue>> {
+    let payments: Vec<(String, String, String)> =
+        sqlx::query_as("SELECT bill_id, month, paid_on FROM bill_payments")
+            .fetch_all(pool)
+            .await?;
+    let mut paid: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut last_paid: HashMap<String, String> = HashMap::new();
+    for (bill_id, month, paid_on) in payments {
+        let last = last_paid.entry(bill_id.clone()).or_default();
+        if paid_on > *last {
+            *last = paid_on;
+        }
+        paid.entry(bill_id).or_default().insert(month);
+    }
+
+    let mut bills: Vec<BillDue> = active(pool)
+        .await?
+        .into_iter()
+        .map(|bill| {
+            let due = open_due(&bill, paid.get(&bill.id).unwrap_or(&HashSet::new()), today);
+            BillDue {
+                due_date: due.format("%Y-%m-%d").to_string(),
+                overdue: !bill.autopay && due < today,
+                last_paid_on: last_paid.remove(&bill.id),
+                bill,
+            }
+        })
+        .collect();
+    bills.sort_by(|a, b| a.due_date.cmp(&b.due_date));
+    Ok(bills)
+}
+
+pub async fn payments(pool: &SqlitePool, bill_id: &str) -> Result<Vec<BillPayment>> {
+    Ok(sqlx::query_as::<_, BillPayment>(
+        "SELECT * FROM bill_payments WHERE bill_id = $1 ORDER BY month DESC",
+    )
+    .bind(bill_id)
+    .fetch_all(pool)
+    .await?)
+}
+
+async fn validate(pool: &SqlitePool, input: &NewBill) -> Result<()> {
+    if input.name.trim().is_empty() {
+        return Err(Error::Validation("Name is required".to_string()));
+    }
+    if !input.amount.is_finite() || input.amount <= 0.0 {
+        return Err(Error::Validation(
+            "Amount must be a positive number".to_string(),
+        ));
+    }
+    if !(1..=31).contains(&input.due_day) {
+        return Err(Error::Validation(
+            "Due day must be between 1 and 31".to_string(),
+        ));
+    }
+    if input.remind_days_before.is_some_and(|days| days < 0) {
+        return Err(Error::Validation(
+            "Reminders can't be sent after the due date".to_string(),
+        ));
+    }
+    if let Some(category_id) = &input.category_id {
+        let mut conn = pool.acquire().await?;
+        expenses::check_category(&mut conn, category_id).await?;
+    }
+    Ok(())
+}
+
+/// Queue the reminder for `due`, dropping the one for an earlier due date
+/// if it hasn't gone out. Overdue cycles get none; `check` tells about
+/// those.
+async fn remind(pool: &SqlitePool, bill: &Bill, due: NaiveDate, today: NaiveDate) -> Result<()> {
+    let key = format!("bill:{}:{due}", bill.id);
+    if let Some(previous) = bill.reminder_id.as_deref().filter(|id| *id != key) {
+        notify::cancel(pool, previous).await?;
+    }
+    if due < today {
+        return Ok(());
+    }
+
+    let remind_on = due
+        .checked_sub_days(Days::new(bill.remind_days_before.max(0) as u64))
+        .unwrap_or(due)
+        .max(today);
+    let now = Utc::now();
+    let at = remind_on
+        .and_hms_opt(REMINDER_HOUR, 0, 0)
+        .and_then(|t| t.and_local_timezone(Local).earliest())
+        .map_or(now, |t| t.with_timezone(&Utc))
+        .max(now);
+    let when = match (due - remind_on).num_days() {
+        0 => "today".to_string(),
+        1 => "tomorrow".to_string(),
+        days => format!("in {days} days"),
+    };
+    let day = due.format("%b %-d");
+    let (title, body) = if bill.autopay {
+        (
+            format!("{} goes out {when}", bill.name),
+            format!(
+                "{:.2} will be charged automatically on {day}. Make sure the account covers it.",
+                bill.amount
+            ),
+        )
+    } else {
+        (
+            format!("{} is due {when}", bill.name),
+            format!("{:.2} due on {day}.", bill.amount),
+        )
+    };
+    notify::schedule_once(pool, &key, "bill_due", &title, &body, &timestamp(at)).await?;
+
+    sqlx::query("UPDATE bills SET reminder_id = $1 WHERE id = $2")
+        .bind(&key)
+        .bind(&bill.id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+async fn remind_open(pool: &SqlitePool, bill: &Bill, today: NaiveDate) -> Result<()> {
+    let paid = paid_months(pool, &bill.id).await?;
+    remind(pool, bill, open_due(bill, &paid, today), today).await
+}
+
+pub async fn create(
+    pool: &SqlitePool,
+    user_id: Option<&str>,
+    input: NewBill,
+    today: NaiveDate,
+) -> Result<Bill> {
+    validate(pool, &input).await?;
+    let id = new_id();
+    let now = now();
+    sqlx::query(
+        "INSERT INTO bills
+            (id, user_id, name, amount, due_day, autopay, category_id, remind_days_before,
+             created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)",
+    )
+    .bind(&id)
+    .bind(user_id)
+    .bind(input.name.trim())
+    .bind(input.amount)
+    .bind(input.due_day)
+    .bind(input.autopay)
+    .bind(&input.category_id)
+    .bind(
+        input
+            .remind_days_before
+            .unwrap_or(DEFAULT_REMIND_DAYS_BEFORE),
+    )
+    .bind(&now)
+    .execute(pool)
+    .await?;
+
+    remind_open(pool, &get(pool, &id).await?, today).await?;
+    get(pool, &id).await
+}
+
+pub async fn update(pool: &SqlitePool, id: &str, input: NewBill, today: NaiveDate) -> Result<Bill> {
+    validate(pool, &input).await?;
+    let updated = sqlx::query(
+        "UPDATE bills
+         SET name = $1, amount = $2, due_day = $3, autopay = $4, category_id = $5,
+             remind_days_before = $6, updated_at = $7
+         WHERE id = $8 AND deleted_at IS NULL",
+    )
+    .bind(input.name.trim())
+    .bind(input.amount)
+    .bind(input.due_day)
+    .bind(input.autopay)
+    .bind(&input.category_id)
+    .bind(
+        input
+            .remind_days_before
+            .unwrap_or(DEFAULT_REMIND_DAYS_BEFORE),
+    )
+    .bind(now())
+    .bind(id)
+    .execute(pool)
+    .await?
+    .rows_affected();
+    if updated == 0 {
+        return Err(Error::Validation("Bill not found".to_string()));
+    }
+
+    remind_open(pool, &get(pool, id).await?, today).await?;
+    get(pool, id).await
+}
+
+pub async fn delete(pool: &SqlitePool, id: &str) -> Result<()> {
+    let bill = get(pool, id).await?;
+    if let Some(reminder_id) = &bill.reminder_id {
+        notify::cancel(pool, reminder_id).await?;
+    }
+    let now = now();
+    sqlx::query("UPDATE bills SET deleted_at = $1, updated_at = $1 WHERE id = $2")
+        .bind(&now)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+async fn record_payment(
+    pool: &SqlitePool,
+    bill_id: &str,
+    month: &str,
+    amount: f64,
+    paid_on: NaiveDate,
+    expense_id: Option<&str>,
+) -> Result<BillPayment> {
+    Ok(sqlx::query_as::<_, BillPayment>(
+        "INSERT INTO bill_payments (id, bill_id, month, amount, paid_on, expense_id, created_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         RETURNING *",
+    )
+    .bind(new_id())
+    .bind(bill_id)
+    .bind(month)
+    .bind(amount)
+    .bind(paid_on.format("%Y-%m-%d").to_string())
+    .bind(expense_id)
+    .bind(now())
+    .fetch_one(pool)
+    .await?)
+}
+
+/// Mark a cycle of the bill paid, logging the expense for it if asked,
+/// and move the reminder on to the next open cycle.
+pub async fn mark_paid(
+    pool: &SqlitePool,
+    id: &str,
+    input: MarkPaid,
+    today: NaiveDate,
+) -> Result<(BillPayment, Option<Expense>)> {
+    let bill = get(pool, id).await?;
+    let mut paid = paid_months(pool, id).await?;
+    let month = match input.month {
+        Some(month) => {
+            NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d")
+                .map_err(|_| Error::Validation("Month must be YYYY-MM".to_string()))?;
+            month
+        }
+        None => month_key(open_due(&bill, &paid, today)),
+    };
+    if paid.contains(&month) {
+        return Err(Error::Validation(format!(
+            "{} is already paid for {month}",
+            bill.name
+        )));
+    }
+    let amount = input.amount.unwrap_or(bill.amount);
+    if !amount.is_finite() || amount <= 0.0 {
+        return Err(Error::Validation(
+            "Amount must be a positive number".to_string(),
+        ));
+    }
+    let paid_on = match &input.paid_on {
+        Some(day) => expenses::parse_date(day)?,
+        None => today,
+    };
+
+    let expense = if input.record_expense {
+        let expense = NewExpense {
+            amount,
+            category_id: bill.category_id.clone(),
+            note: Some(bill.name.clone()),
+            date: Some(paid_on.format("%Y-%m-%d").to_string()),
+            payment_method: None,
+            currency: None,
+            account_id: None,
+        };
+        Some(expenses::create(pool, expense, today).await?)
+    } else {
+        None
+    };
+    let payment = record_payment(
+        pool,
+        id,
+        &month,
+        amount,
+        paid_on,
+        expense.as_ref().map(|e| e.id.as_str()),
+    )
+    .await?;
+
+    paid.insert(month);
+    remind(pool, &bill, open_due(&bill, &paid, today), today).await?;
+    Ok((payment, expense))
+}
+
+/// Undo marking a cycle paid. An expense logged for it is kept.
+pub async fn unmark_paid(pool: &SqlitePool, id: &str, month: &str, today: NaiveDate) -> Result<()> {
+    let bill = get(pool, id).await?;
+    sqlx::query("DELETE FROM bill_payments WHERE bill_id = $1 AND month = $2")
+        .bind(id)
+        .bind(month)
+        .execute(pool)
+        .await?;
+    remind_open(pool, &bill, today).await
+}
+
+/// Settle autopay bills whose due date went by, tell the user once about
+/// other overdue ones, and keep each bill's reminder queued.
+pub async fn check(app: &AppHandle, pool: &SqlitePool, today: NaiveDate) -> Result<()> {
+    for bill in active(pool).await? {
+        let mut paid = paid_months(pool, &bill.id).await?;
+        let mut due = open_due(&bill, &paid, today);
+        if due < today {
+            if bill.autopay {
+                let month = month_key(due);
+                record_payment(pool, &bill.id, &month, bill.amount, due, None).await?;
+                paid.insert(month);
+                due = open_due(&bill, &paid, today);
+            } else {
+                notify::send_once(
+                    app,
+                    pool,
+                    &format!("bill_overdue:{}:{due}", bill.id),
+                    "bill_overdue",
+                    None,
+                    &format!("{} is overdue", bill.name),
+                    &format!(
+                        "{:.2} was due on {}. Mark it paid once it's settled.",
+                        bill.amount,
+                        due.format("%b %-d")
+                    ),
+                )
+                .await?;
+            }
+        }
+        remind(pool, &bill, due, today).await?;
+    }
+    Ok(())
+}
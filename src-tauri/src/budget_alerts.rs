@@ -0,0 +1,229 @@
+//! Alerts for the period's budget running out.
+//!
+//! After every expense write we compare the period's spending with the
+//! budget's limit, and each category's spending with its allocation (see
+//! `category_budgets`), and warn at the user's thresholds: 80 and 100
+//! percent unless they pick others. Categories with an alert rule of their
+//! own are left to `category_alerts`. Like those, each threshold fires at
+//! most once per period, by the key it is recorded under in
+//! `scheduled_notifications`, and a job sweeps for expenses that arrived
+//! through sync.
+
+use chrono::NaiveDate;
+use serde_json::json;
+use sqlx::SqlitePool;
+use tauri::AppHandle;
+
+use crate::analysis::spending::NET_AMOUNT;
+use crate::app_meta;
+use crate::budgets;
+use crate::category_alerts;
+use crate::category_budgets;
+use crate::error::{Error, Result};
+use crate::notify;
+use crate::periods::{self, Period};
+use crate::webhooks::{self, WebhookEvent};
+
+const THRESHOLDS_KEY: &str = "budget_alert_thresholds";
+
+const DEFAULT_THRESHOLDS: [u32; 2] = [80, 100];
+
+/// Highest percentage a threshold may be set to.
+const MAX_THRESHOLD: u32 = 500;
+
+#[derive(Debug, Clone)]
+pub struct BudgetAlert {
+    /// `None` for the budget as a whole.
+    pub category_id: Option<String>,
+    pub category_name: Option<String>,
+    /// Budget period key.
+    pub period: String,
+    pub threshold: u32,
+    pub spent: f64,
+    pub limit_amount: f64,
+}
+
+/// Percentages of the limit to warn at, lowest first.
+pub async fn thresholds(pool: &SqlitePool) -> Result<Vec<u32>> {
+    Ok(app_meta::get(pool, THRESHOLDS_KEY)
+        .await?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_else(|| DEFAULT_THRESHOLDS.to_vec()))
+}
+
+/// Replace the thresholds; an empty list turns the alerts off.
+pub async fn set_thresholds(pool: &SqlitePool, mut thresholds: Vec<u32>) -> Result<Vec<u32>> {
+    if thresholds.iter().any(|t| *t == 0 || *t > MAX_THRESHOLD) {
+        return Err(Error::Validation(format!(
+            "Alert percentages must be between 1 and {MAX_THRESHOLD}"
+        )));
+    }
+    thresholds.sort_unstable();
+    thresholds.dedup();
+    app_meta::set(
+        pool,
+        THRESHOLDS_KEY,
+        &serde_json::Value::from(thresholds.clone()).to_string(),
+    )
+    .await?;
+    Ok(thresholds)
+}
+
+/// The highest threshold `spent` has reached of `limit`.
+fn crossed(thresholds: &[u32], spent: f64, limit: f64) -> Option<u32> {
+    if limit <= 0.0 {
+        return None;
+    }
+    let percent = spent / limit * 100.0;
+    thresholds
+        .iter()
+        .copied()
+        .filter(|t| percent >= f64::from(*t))
+        .max()
+}
+
+/// Net spending per category in `period`, with the category's name.
+async fn category_spending(
+    pool: &SqlitePool,
+    period: &Period,
+) -> Result<Vec<(String, String, f64)>> {
+    Ok(sqlx::query_as(&format!(
+        "SELECT e.category_id, c.name, COALESCE(SUM({NET_AMOUNT}), 0.0)
+         FROM expenses e
+         JOIN categories c ON c.id = e.category_id
+         WHERE e.deleted_at IS NULL AND substr(e.date, 1, 10) BETWEEN $1 AND $2
+         GROUP BY e.category_id"
+    ))
+    .bind(period.start.format("%Y-%m-%d").to_string())
+    .bind(period.end.format("%Y-%m-%d").to_string())
+    .fetch_all(pool)
+    .await?)
+}
+
+async fn send(app: &AppHandle, pool: &SqlitePool, alert: &BudgetAlert) -> Result<bool> {
+    let (key, subject) = match (&alert.category_id, &alert.category_name) {
+        (Some(id), name) => (
+            format!("budget_alert:{}:{id}:{}", alert.period, alert.threshold),
+            name.clone().unwrap_or_else(|| "A category".to_string()),
+        ),
+        (None, _) => (
+            format!("budget_alert:{}:{}", alert.period, alert.threshold),
+            "Your budget".to_string(),
+        ),
+    };
+    let (title, body) = if alert.threshold >= 100 {
+        (
+            "Over budget",
+            format!(
+                "{subject} is at {:.0}% of its limit for this period: {:.2} of {:.2}.",
+                alert.spent / alert.limit_amount * 100.0,
+                alert.spent,
+                alert.limit_amount
+            ),
+        )
+    } else {
+        (
+            "Budget running low",
+            format!(
+                "{subject} has used {}% of its limit for this period, {:.2} left.",
+                alert.threshold,
+                alert.limit_amount - alert.spent
+            ),
+        )
+    };
+    notify::send_once(app, pool, &key, "budget_alert", None, title, &body).await
+}
+
+/// Tell webhooks, once per period for the budget and for each category.
+async fn exceeded(pool: &SqlitePool, alert: &BudgetAlert) {
+    let key = match &alert.category_id {
+        Some(id) => format!("budget_exceeded:{}:{id}", alert.period),
+        None => format!("budget_exceeded:{}", alert.period),
+    };
+    webhooks::trigger(
+        pool,
+        WebhookEvent::BudgetExceeded,
+        &key,
+        json!({
+            "period": alert.period,
+            "category_id": alert.category_id,
+            "category_name": alert.category_name,
+            "spent": alert.spent,
+            "limit_amount": alert.limit_amount,
+        }),
+    )
+    .await;
+}
+
+/// Warn about the highest threshold crossed by the budget and by each
+/// category allocation in the period `today` falls in. Returns the alerts
+/// that went out.
+pub async fn check(
+    app: &AppHandle,
+    pool: &SqlitePool,
+    today: NaiveDate,
+) -> Result<Vec<BudgetAlert>> {
+    let thresholds = thresholds(pool).await?;
+    if thresholds.is_empty() {
+        return Ok(Vec::new());
+    }
+    let period = periods::schedule(pool).await?.period_at(today);
+    let mut candidates = Vec::new();
+
+    let mut conn = pool.acquire().await?;
+    if let Some(budget) = budgets::for_period(&mut conn, &period.key).await? {
+        let spent = budgets::period_spending(&mut conn, &period).await?;
+        if let Some(threshold) = crossed(&thresholds, spent, budget.limit()) {
+            candidates.push(BudgetAlert {
+                category_id: None,
+                category_name: None,
+                period: period.key.clone(),
+                threshold,
+                spent,
+                limit_amount: budget.limit(),
+            });
+        }
+    }
+    drop(conn);
+
+    let allocations = category_budgets::list(pool, &period.key).await?;
+    if !allocations.is_empty() {
+        let ruled = category_alerts::list(pool).await?;
+        let spending = category_spending(pool, &period).await?;
+        for allocation in allocations {
+            if ruled
+                .iter()
+                .any(|r| r.category_id == allocation.category_id)
+            {
+                continue;
+            }
+            let Some((_, name, spent)) = spending
+                .iter()
+                .find(|(id, _, _)| *id == allocation.category_id)
+            else {
+                continue;
+            };
+            if let Some(threshold) = crossed(&thresholds, *spent, allocation.amount) {
+                candidates.push(BudgetAlert {
+                    category_id: Some(allocation.category_id),
+                    category_name: Some(name.clone()),
+                    period: period.key.clone(),
+                    threshold,
+                    spent: *spent,
+                    limit_amount: allocation.amount,
+                });
+            }
+        }
+    }
+
+    let mut sent = Vec::new();
+    for alert in candidates {
+        if send(app, pool, &alert).await? {
+            if alert.spent >= alert.limit_amount {
+                exceeded(pool, &alert).await;
+            }
+            sent.push(alert);
+        }
+    }
+    Ok(sent)
+}
@@ -0,0 +1,289 @@
+//! Budgets and the template new periods start from.
+//!
+//! A budget covers one pay period (see `periods`), which by default is the
+//! calendar month.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::auth;
+use crate::db::{new_id, now};
+use crate::error::{Error, Result};
+use crate::periods::{self, PaySchedule, Period};
+use crate::sync::{self, Operation};
+
+/// What a new period's budget starts from. `None` amounts copy the previous
+/// period's budget.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::FromRow, specta::Type)]
+pub struct BudgetTemplate {
+    pub total_amount: Option<f64>,
+    pub spending_limit: Option<f64>,
+    /// Add last period's leftover (or overspend) to the new limit.
+    pub carry_over: bool,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct Budget {
+    pub id: String,
+    pub user_id: Option<String>,
+    /// Key of the budget period, "YYYY-MM" for calendar months.
+    pub month: String,
+    pub total_amount: f64,
+    pub spending_limit: Option<f64>,
+    pub created_by: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub deleted_at: Option<String>,
+    /// The household it is shared with; see `households`.
+    pub household_id: Option<String>,
+}
+
+/// Amounts for the current period's budget.
+#[derive(Debug, Clone, Deserialize, specta::Type)]
+pub struct BudgetInput {
+    pub total_amount: f64,
+    pub spending_limit: Option<f64>,
+}
+
+impl Budget {
+    /// What the period is measured against, as on the home screen.
+    pub fn limit(&self) -> f64 {
+        self.spending_limit.unwrap_or(self.total_amount)
+    }
+}
+
+pub async fn template(pool: &SqlitePool) -> Result<BudgetTemplate> {
+    Ok(sqlx::query_as::<_, BudgetTemplate>(
+        "SELECT total_amount, spending_limit, carry_over FROM budget_template WHERE id = 1",
+    )
+    .fetch_optional(pool)
+    .await?
+    .unwrap_or_default())
+}
+
+pub async fn save_template(pool: &SqlitePool, template: &BudgetTemplate) -> Result<()> {
+    if template.total_amount.is_some_and(|a| a <= 0.0)
+        || template.spending_limit.is_some_and(|a| a <= 0.0)
+    {
+        return Err(Error::Validation(
+            "Budget amounts must be positive".to_string(),
+        ));
+    }
+    sqlx::query(
+        "INSERT INTO budget_template (id, total_amount, spending_limit, carry_over, updated_at)
+         VALUES (1, $1, $2, $3, $4)
+         ON CONFLICT(id) DO UPDATE SET
+           total_amount = excluded.total_amount,
+           spending_limit = excluded.spending_limit,
+           carry_over = excluded.carry_over,
+           updated_at = excluded.updated_at",
+    )
+    .bind(template.total_amount)
+    .bind(template.spending_limit)
+    .bind(template.carry_over)
+    .bind(now())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Net spending in `month`: expenses that were paid back don't count.
+pub async fn month_spending(conn: &mut SqliteConnection, month: &str) -> Result<f64> {
+    let (total,): (f64,) = sqlx::query_as(
+        "SELECT COALESCE(SUM(amount), 0) FROM expenses
+         WHERE strftime('%Y-%m', date) = $1 AND deleted_at IS NULL
+           AND COALESCE(reimbursement_status, '') != 'received'",
+    )
+    .bind(month)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(total)
+}
+
+/// Net spending in `period`.
+pub async fn period_spending(conn: &mut SqliteConnection, period: &Period) -> Result<f64> {
+    let (total,): (f64,) = sqlx::query_as(
+        "SELECT COALESCE(SUM(amount), 0) FROM expenses
+         WHERE substr(date, 1, 10) BETWEEN $1 AND $2 AND deleted_at IS NULL
+           AND COALESCE(reimbursement_status, '') != 'received'",
+    )
+    .bind(period.start.format("%Y-%m-%d").to_string())
+    .bind(period.end.format("%Y-%m-%d").to_string())
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(total)
+}
+
+/// The budget for the period keyed `key`.
+pub async fn for_period(conn: &mut SqliteConnection, key: &str) -> Result<Option<Budget>> {
+    Ok(
+        sqlx::query_as::<_, Budget>(
+            "SELECT * FROM budgets WHERE month = $1 AND deleted_at IS NULL",
+        )
+        .bind(key)
+        .fetch_optional(&mut *conn)
+        .await?,
+    )
+}
+
+/// The budget for the period `today` falls in.
+pub async fn current(pool: &SqlitePool, today: NaiveDate) -> Result<Option<Budget>> {
+    let period = periods::schedule(pool).await?.period_at(today);
+    for_period(&mut *pool.acquire().await?, &period.key).await
+}
+
+/// Set the amounts of the current period's budget, creating it if needed.
+pub async fn save_current(
+    pool: &SqlitePool,
+    today: NaiveDate,
+    input: BudgetInput,
+) -> Result<Budget> {
+    if !input.total_amount.is_finite()
+        || input.total_amount <= 0.0
+        || input
+            .spending_limit
+            .is_some_and(|limit| !limit.is_finite() || limit <= 0.0)
+    {
+        return Err(Error::Validation(
+            "Budget amounts must be positive".to_string(),
+        ));
+    }
+    let period = periods::schedule(pool).await?.period_at(today);
+
+    let mut tx = pool.begin().await?;
+    let user_id = auth::user_id_on(&mut tx).await?;
+    let now = now();
+    let (id, operation) = match for_period(&mut tx, &period.key).await? {
+        Some(existing) => {
+            sqlx::query(
+                "UPDATE budgets SET total_amount = $1, spending_limit = $2, updated_at = $3, user_id = $4
+                 WHERE id = $5",
+            )
+            .bind(input.total_amount)
+            .bind(input.spending_limit)
+            .bind(&now)
+            .bind(&user_id)
+            .bind(&existing.id)
+            .execute(&mut *tx)
+            .await?;
+            (existing.id, Operation::Update)
+        }
+        None => {
+            let id = new_id();
+            sqlx::query(
+                "INSERT INTO budgets
+                    (id, user_id, month, total_amount, spending_limit, created_by, created_at, updated_at)
+                 VALUES ($1, $2, $3, $4, $5, $2, $6, $6)",
+            )
+            .bind(&id)
+            .bind(&user_id)
+            .bind(&period.key)
+            .bind(input.total_amount)
+            .bind(input.spending_limit)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+            (id, Operation::Insert)
+        }
+    };
+    sync::enqueue(&mut tx, "budgets", &id, operation).await?;
+    let budget = for_period(&mut tx, &period.key)
+        .await?
+        .ok_or_else(|| Error::Validation("Budget not found".to_string()))?;
+    tx.commit().await?;
+    Ok(budget)
+}
+
+/// Create the budget for the period keyed `key` from the template, falling back to `previous`
+/// for amounts the template leaves open, and add `rollover` if the
+/// template carries over. Returns `None` if there is nothing to start from.
+pub async fn create_from_template(
+    conn: &mut SqliteConnection,
+    template: &BudgetTemplate,
+    key: &str,
+    previous: Option<&Budget>,
+    rollover: f64,
+) -> Result<Option<String>> {
+    let (mut total_amount, mut spending_limit) = match (template.total_amount, previous) {
+        (Some(total), _) => (total, template.spending_limit),
+        (None, Some(previous)) => (
+            previous.total_amount,
+            template.spending_limit.or(previous.spending_limit),
+        ),
+        (None, None) => return Ok(None),
+    };
+    if template.carry_over {
+        match spending_limit.as_mut() {
+            Some(limit) => *limit = (*limit + rollover).max(0.0),
+            None => total_amount = (total_amount + rollover).max(0.0),
+        }
+    }
+
+    let user: Option<(Option<String>,)> =
+        sqlx::query_as("SELECT user_id FROM auth_state WHERE id = 1")
+            .fetch_optional(&mut *conn)
+            .await?;
+    let user_id = user.and_then(|(id,)| id);
+
+    let id = new_id();
+    let now = now();
+    sqlx::query(
+        "INSERT INTO budgets
+            (id, user_id, month, total_amount, spending_limit, created_by, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $2, $6, $6)",
+    )
+    .bind(&id)
+    .bind(&user_id)
+    .bind(key)
+    .bind(total_amount)
+    .bind(spending_limit)
+    .bind(&now)
+    .execute(&mut *conn)
+    .await?;
+    sync::enqueue(conn, "budgets", &id, Operation::Insert).await?;
+    Ok(Some(id))
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct BudgetStatus {
+    pub period: Period,
+    pub budget: Option<Budget>,
+    pub spent: f64,
+    /// Left to spend against the budget's limit; negative once overspent.
+    pub remaining: Option<f64>,
+    /// Days left in the period, counting today.
+    pub days_left: i64,
+    /// What can still go out each day without overspending.
+    pub safe_to_spend_per_day: Option<f64>,
+    /// Where spending ends up by the last day at the pace so far.
+    pub projected_spend: f64,
+}
+
+/// How the period `today` falls in is going.
+pub async fn status(
+    pool: &SqlitePool,
+    schedule: &PaySchedule,
+    today: NaiveDate,
+) -> Result<BudgetStatus> {
+    let period = schedule.period_at(today);
+    let mut conn = pool.acquire().await?;
+    let budget = for_period(&mut conn, &period.key).await?;
+    let spent = period_spending(&mut conn, &period).await?;
+
+    let days_left = period.days_left(today);
+    let days_passed = (period.days() - days_left + 1).max(1);
+    let projected_spend = spent / days_passed as f64 * period.days() as f64;
+    let remaining = budget.as_ref().map(|b| b.limit() - spent);
+    let safe_to_spend_per_day = remaining.map(|r| r.max(0.0) / days_left.max(1) as f64);
+
+    Ok(BudgetStatus {
+        period,
+        budget,
+        spent,
+        remaining,
+        days_left,
+        safe_to_spend_per_day,
+        projected_spend,
+    })
+}
@@ -0,0 +1,401 @@
+//! Changes to many expenses at once: recategorizing, deleting, moving
+//! dates and tagging a selection.
+//!
+//! Each change runs in one transaction and is all or nothing. What it
+//! replaced is kept under an undo token for a day; undoing puts every
+//! expense back as it was, except those changed again since, which are
+//! left as they are and counted. A token can be used once.
+
+use std::collections::HashSet;
+
+use chrono::{Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::accounts;
+use crate::category_totals::{self, Cell};
+use crate::currency;
+use crate::db::{new_id, now, timestamp};
+use crate::error::{Error, Result};
+use crate::expenses::{self, parse_date};
+use crate::sync::{self, Operation};
+
+/// How long a change can be undone.
+const UNDO_HOURS: i64 = 24;
+
+#[derive(Debug, Clone, Deserialize, specta::Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BulkOperation {
+    /// Move to `category_id`, or leave uncategorized with `null`.
+    Recategorize { category_id: Option<String> },
+    /// Into the trash, like deleting one by one.
+    Delete,
+    /// Move every date by `days`, earlier when negative. Spending in
+    /// another currency is converted again at the new date's rate.
+    ShiftDate { days: i64 },
+    /// Add a tag, or with `remove` take it off.
+    Tag {
+        tag_id: String,
+        #[serde(default)]
+        remove: bool,
+    },
+}
+
+impl BulkOperation {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Recategorize { .. } => "recategorize",
+            Self::Delete => "delete",
+            Self::ShiftDate { .. } => "shift_date",
+            Self::Tag { .. } => "tag",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct BulkResult {
+    /// The expenses that changed; those already as asked are left out.
+    pub expense_ids: Vec<String>,
+    /// `None` when nothing changed.
+    pub undo_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct UndoResult {
+    pub expense_ids: Vec<String>,
+    /// Changed again since, and left as they are.
+    pub skipped: usize,
+}
+
+/// The columns a bulk change touches, as they were.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+struct Before {
+    id: String,
+    amount: f64,
+    category_id: Option<String>,
+    date: String,
+    currency: Option<String>,
+    original_amount: Option<f64>,
+    account_id: Option<String>,
+}
+
+impl Before {
+    fn cell(&self) -> Cell {
+        Cell {
+            date: self.date.clone(),
+            category_id: self.category_id.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot {
+    /// `updated_at` of the changed expenses, to tell later edits apart.
+    changed_at: String,
+    expenses: Vec<Before>,
+    /// For tagging: the tag and the expenses it was added to or taken off.
+    tag_id: Option<String>,
+    #[serde(default)]
+    tagged: Vec<String>,
+    #[serde(default)]
+    removed: bool,
+}
+
+fn encode<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value)
+        .map_err(|e| Error::Validation(format!("failed to encode expenses: {e}")))
+}
+
+async fn load(conn: &mut SqliteConnection, ids: &str) -> Result<Vec<Before>> {
+    Ok(sqlx::query_as::<_, Before>(
+        "SELECT id, amount, category_id, date, currency, original_amount, account_id
+         FROM expenses
+         WHERE id IN (SELECT value FROM json_each($1)) AND deleted_at IS NULL",
+    )
+    .bind(ids)
+    .fetch_all(&mut *conn)
+    .await?)
+}
+
+fn shifted(date: &str, days: i64) -> Result<NaiveDate> {
+    parse_date(date)?
+        .checked_add_signed(Duration::days(days))
+        .ok_or_else(|| Error::Validation("Date out of range".to_string()))
+}
+
+/// Apply `operation` to the expenses among `ids` that aren't deleted.
+pub async fn apply(
+    pool: &SqlitePool,
+    ids: &[String],
+    operation: &BulkOperation,
+) -> Result<BulkResult> {
+    let mut unique: Vec<&String> = ids.iter().collect();
+    unique.sort();
+    unique.dedup();
+    if unique.is_empty() {
+        return Err(Error::Validation("Pick at least one expense".to_string()));
+    }
+    let ids = encode(&unique)?;
+
+    if let BulkOperation::ShiftDate { days } = operation {
+        if *days == 0 {
+            return Err(Error::Validation("Shift by at least one day".to_string()));
+        }
+        // Rates can't be fetched inside the transaction.
+        let mut conn = pool.acquire().await?;
+        for before in load(&mut conn, &ids).await? {
+            if let Some(code) = &before.currency {
+                currency::ensure(pool, code, shifted(&before.date, *days)?).await?;
+            }
+        }
+    }
+
+    let now = now();
+    let mut tx = pool.begin().await?;
+    let found = load(&mut tx, &ids).await?;
+    let mut snapshot = Snapshot {
+        changed_at: now.clone(),
+        expenses: Vec::new(),
+        tag_id: None,
+        tagged: Vec::new(),
+        removed: false,
+    };
+    let mut cells = Vec::new();
+    match operation {
+        BulkOperation::Recategorize { category_id } => {
+            if let Some(category_id) = category_id {
+                expenses::check_category(&mut tx, category_id).await?;
+            }
+            for before in found {
+                if before.category_id == *category_id {
+                    continue;
+                }
+                sqlx::query("UPDATE expenses SET category_id = $1, updated_at = $2 WHERE id = $3")
+                    .bind(category_id)
+                    .bind(&now)
+                    .bind(&before.id)
+                    .execute(&mut *tx)
+                    .await?;
+                sync::enqueue(&mut tx, "expenses", &before.id, Operation::Update).await?;
+                cells.push(before.cell());
+                cells.push(Cell {
+                    date: before.date.clone(),
+                    category_id: category_id.clone(),
+                });
+                snapshot.expenses.push(before);
+            }
+        }
+        BulkOperation::Delete => {
+            for before in found {
+                sqlx::query("UPDATE expenses SET deleted_at = $1, updated_at = $1 WHERE id = $2")
+                    .bind(&now)
+                    .bind(&before.id)
+                    .execute(&mut *tx)
+                    .await?;
+                sync::enqueue_delete(&mut tx, "expenses", &before.id, &now).await?;
+                cells.push(before.cell());
+                snapshot.expenses.push(before);
+            }
+        }
+        BulkOperation::ShiftDate { days } => {
+            for before in found {
+                let date = shifted(&before.date, *days)?;
+                let face_amount = before.original_amount.unwrap_or(before.amount);
+                let money =
+                    currency::convert_on(&mut tx, face_amount, before.currency.as_deref(), date)
+                        .await?;
+                let date = date.format("%Y-%m-%d").to_string();
+                sqlx::query(
+                    "UPDATE expenses
+                     SET date = $1, amount = $2, original_amount = $3, updated_at = $4
+                     WHERE id = $5",
+                )
+                .bind(&date)
+                .bind(money.amount)
+                .bind(money.original_amount)
+                .bind(&now)
+                .bind(&before.id)
+                .execute(&mut *tx)
+                .await?;
+                sync::enqueue(&mut tx, "expenses", &before.id, Operation::Update).await?;
+                cells.push(before.cell());
+                cells.push(Cell {
+                    date,
+                    category_id: before.category_id.clone(),
+                });
+                snapshot.expenses.push(before);
+            }
+        }
+        BulkOperation::Tag { tag_id, remove } => {
+            let tag: Option<(String,)> = sqlx::query_as("SELECT id FROM tags WHERE id = $1")
+                .bind(tag_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+            if tag.is_none() {
+                return Err(Error::Validation("Tag not found".to_string()));
+            }
+            let tagged: HashSet<String> = sqlx::query_as::<_, (String,)>(
+                "SELECT expense_id FROM expense_tags
+                 WHERE tag_id = $1 AND expense_id IN (SELECT value FROM json_each($2))",
+            )
+            .bind(tag_id)
+            .bind(&ids)
+            .fetch_all(&mut *tx)
+            .await?
+            .into_iter()
+            .map(|(id,)| id)
+            .collect();
+            for before in found {
+                if tagged.contains(&before.id) != *remove {
+                    continue;
+                }
+                let query = if *remove {
+                    "DELETE FROM expense_tags WHERE expense_id = $1 AND tag_id = $2"
+                } else {
+                    "INSERT INTO expense_tags (expense_id, tag_id, created_at) VALUES ($1, $2, $3)"
+                };
+                sqlx::query(query)
+                    .bind(&before.id)
+                    .bind(tag_id)
+                    .bind(&now)
+                    .execute(&mut *tx)
+                    .await?;
+                snapshot.tagged.push(before.id);
+            }
+            snapshot.tag_id = Some(tag_id.clone());
+            snapshot.removed = *remove;
+        }
+    }
+
+    let expense_ids: Vec<String> = match operation {
+        BulkOperation::Tag { .. } => snapshot.tagged.clone(),
+        _ => snapshot.expenses.iter().map(|b| b.id.clone()).collect(),
+    };
+    if expense_ids.is_empty() {
+        return Ok(BulkResult {
+            expense_ids,
+            undo_token: None,
+        });
+    }
+    let token = new_id();
+    sqlx::query(
+        "INSERT INTO bulk_undo (token, operation, snapshot, created_at) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(&token)
+    .bind(operation.as_str())
+    .bind(encode(&snapshot)?)
+    .bind(&now)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    finish(pool, &cells, &snapshot.expenses).await?;
+    Ok(BulkResult {
+        expense_ids,
+        undo_token: Some(token),
+    })
+}
+
+/// Refresh the totals and balances a change touched, and let go of undo
+/// tokens past their time.
+async fn finish(pool: &SqlitePool, cells: &[Cell], changed: &[Before]) -> Result<()> {
+    category_totals::refresh(pool, cells).await?;
+    let accounts: Vec<Option<String>> = changed.iter().map(|b| b.account_id.clone()).collect();
+    accounts::refresh(pool, &accounts).await?;
+    sqlx::query("DELETE FROM bulk_undo WHERE created_at < $1")
+        .bind(timestamp(Utc::now() - Duration::hours(UNDO_HOURS)))
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Put back what the change behind `token` replaced.
+pub async fn undo(pool: &SqlitePool, token: &str) -> Result<UndoResult> {
+    let cutoff = timestamp(Utc::now() - Duration::hours(UNDO_HOURS));
+    let mut tx = pool.begin().await?;
+    let row: Option<(String,)> =
+        sqlx::query_as("SELECT snapshot FROM bulk_undo WHERE token = $1 AND created_at >= $2")
+            .bind(token)
+            .bind(&cutoff)
+            .fetch_optional(&mut *tx)
+            .await?;
+    let Some((snapshot,)) = row else {
+        return Err(Error::Validation(
+            "This change can no longer be undone".to_string(),
+        ));
+    };
+    let snapshot: Snapshot = serde_json::from_str(&snapshot)
+        .map_err(|e| Error::Validation(format!("Unreadable undo record: {e}")))?;
+    sqlx::query("DELETE FROM bulk_undo WHERE token = $1")
+        .bind(token)
+        .execute(&mut *tx)
+        .await?;
+
+    let now = now();
+    let mut result = UndoResult {
+        expense_ids: Vec::new(),
+        skipped: 0,
+    };
+    if let Some(tag_id) = &snapshot.tag_id {
+        for expense_id in &snapshot.tagged {
+            let query = if snapshot.removed {
+                "INSERT OR IGNORE INTO expense_tags (expense_id, tag_id, created_at)
+                 SELECT id, $2, $3 FROM expenses WHERE id = $1"
+            } else {
+                "DELETE FROM expense_tags WHERE expense_id = $1 AND tag_id = $2"
+            };
+            let done = sqlx::query(query)
+                .bind(expense_id)
+                .bind(tag_id)
+                .bind(&now)
+                .execute(&mut *tx)
+                .await?;
+            if done.rows_affected() > 0 {
+                result.expense_ids.push(expense_id.clone());
+            } else {
+                result.skipped += 1;
+            }
+        }
+        tx.commit().await?;
+        return Ok(result);
+    }
+
+    let mut cells = Vec::new();
+    let mut restored = Vec::new();
+    for before in snapshot.expenses {
+        let current: Option<(String, Option<String>, String)> =
+            sqlx::query_as("SELECT date, category_id, updated_at FROM expenses WHERE id = $1")
+                .bind(&before.id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let Some((date, category_id, _)) =
+            current.filter(|(_, _, updated_at)| *updated_at == snapshot.changed_at)
+        else {
+            result.skipped += 1;
+            continue;
+        };
+        sqlx::query(
+            "UPDATE expenses
+             SET amount = $1, category_id = $2, date = $3, original_amount = $4,
+                 deleted_at = NULL, updated_at = $5
+             WHERE id = $6",
+        )
+        .bind(before.amount)
+        .bind(&before.category_id)
+        .bind(&before.date)
+        .bind(before.original_amount)
+        .bind(&now)
+        .bind(&before.id)
+        .execute(&mut *tx)
+        .await?;
+        sync::enqueue(&mut tx, "expenses", &before.id, Operation::Update).await?;
+        cells.push(Cell { date, category_id });
+        cells.push(before.cell());
+        result.expense_ids.push(before.id.clone());
+        restored.push(before);
+    }
+    tx.commit().await?;
+
+    finish(pool, &cells, &restored).await?;
+    Ok(result)
+}
@@ -0,0 +1,7 @@
+//! Category suggestions for new expenses, and the rules that pick one
+//! without asking.
+
+pub mod rules;
+mod suggest;
+
+pub use suggest::{suggest_categories, CategorySuggestion};
@@ -0,0 +1,217 @@
+//! Categorization rules: "always put NETFLIX in Subscriptions".
+//!
+//! A rule matches an expense's note, as a case-insensitive substring, or
+//! its merchant (see `merchants`), as a case-insensitive whole name. Rules
+//! only fill in a missing category, on manual entry and on import; an
+//! expense the user categorized keeps what they chose. Of several matching
+//! rules the one with the highest priority wins, then the longest pattern,
+//! being the most specific.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::db::{new_id, now};
+use crate::error::{Error, Result};
+use crate::expenses::{self, Expense, ExpenseUpdate};
+use crate::merchants;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchField {
+    Note,
+    Merchant,
+}
+
+impl MatchField {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Note => "note",
+            Self::Merchant => "merchant",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct CategorizationRule {
+    pub id: String,
+    pub pattern: String,
+    pub match_field: MatchField,
+    pub category_id: String,
+    pub priority: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize, specta::Type)]
+pub struct NewRule {
+    pub pattern: String,
+    pub match_field: MatchField,
+    pub category_id: String,
+    /// Defaults to 0; higher wins.
+    pub priority: Option<i64>,
+}
+
+type Row = (String, String, String, String, i64, String, String);
+
+fn from_row(
+    (id, pattern, field, category_id, priority, created_at, updated_at): Row,
+) -> CategorizationRule {
+    CategorizationRule {
+        id,
+        pattern,
+        match_field: if field == "merchant" {
+            MatchField::Merchant
+        } else {
+            MatchField::Note
+        },
+        category_id,
+        priority,
+        created_at,
+        updated_at,
+    }
+}
+
+/// Rules whose category still exists, in the order they are tried.
+pub async fn list(pool: &SqlitePool) -> Result<Vec<CategorizationRule>> {
+    let rows: Vec<Row> = sqlx::query_as(
+        "SELECT r.id, r.pattern, r.match_field, r.category_id, r.priority, r.created_at, r.updated_at
+         FROM categorization_rules r
+         JOIN categories c ON c.id = r.category_id
+         WHERE c.deleted_at IS NULL
+         ORDER BY r.priority DESC, length(r.pattern) DESC, r.created_at",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(from_row).collect())
+}
+
+/// Create a rule, or retarget the one with the same pattern.
+pub async fn save(pool: &SqlitePool, rule: NewRule) -> Result<CategorizationRule> {
+    let pattern = rule.pattern.trim();
+    if pattern.chars().count() < 2 {
+        return Err(Error::Validation(
+            "A rule needs a pattern of at least two characters".to_string(),
+        ));
+    }
+    let mut conn = pool.acquire().await?;
+    expenses::check_category(&mut conn, &rule.category_id).await?;
+
+    let now = now();
+    let row: Row = sqlx::query_as(
+        "INSERT INTO categorization_rules
+            (id, pattern, match_field, category_id, priority, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $6)
+         ON CONFLICT(match_field, pattern) DO UPDATE SET
+           category_id = excluded.category_id,
+           priority = excluded.priority,
+           updated_at = excluded.updated_at
+         RETURNING id, pattern, match_field, category_id, priority, created_at, updated_at",
+    )
+    .bind(new_id())
+    .bind(pattern)
+    .bind(rule.match_field.as_str())
+    .bind(&rule.category_id)
+    .bind(rule.priority.unwrap_or(0))
+    .bind(&now)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(from_row(row))
+}
+
+pub async fn delete(pool: &SqlitePool, id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM categorization_rules WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// A rule matching an existing expense: its note, or the merchant the
+/// note normalizes to. The category is the expense's unless another is
+/// given, in which case the expense is moved there too and returned.
+pub async fn from_expense(
+    pool: &SqlitePool,
+    expense_id: &str,
+    match_field: MatchField,
+    category_id: Option<String>,
+) -> Result<(CategorizationRule, Option<Expense>)> {
+    let row: Option<(Option<String>, Option<String>)> = sqlx::query_as(
+        "SELECT note, category_id FROM expenses WHERE id = $1 AND deleted_at IS NULL",
+    )
+    .bind(expense_id)
+    .fetch_optional(pool)
+    .await?;
+    let Some((note, current)) = row else {
+        return Err(Error::Validation("Expense not found".to_string()));
+    };
+    let note = note
+        .filter(|n| !n.trim().is_empty())
+        .ok_or_else(|| Error::Validation("The expense has no note to match on".to_string()))?;
+    let target = category_id
+        .clone()
+        .or_else(|| current.clone())
+        .ok_or_else(|| Error::Validation("Choose a category for the rule".to_string()))?;
+    let pattern = match match_field {
+        MatchField::Note => note.trim().to_string(),
+        MatchField::Merchant => {
+            merchants::normalize(&note, &merchants::load_corrections(pool).await?)
+        }
+    };
+    let rule = save(
+        pool,
+        NewRule {
+            pattern,
+            match_field,
+            category_id: target.clone(),
+            priority: None,
+        },
+    )
+    .await?;
+
+    let moved = if current.as_deref() != Some(target.as_str()) {
+        let update = ExpenseUpdate {
+            category_id: Some(Some(target)),
+            ..ExpenseUpdate::default()
+        };
+        Some(expenses::update(pool, expense_id, update).await?)
+    } else {
+        None
+    };
+    Ok((rule, moved))
+}
+
+/// The rules, loaded once to categorize many expenses.
+pub(crate) struct Rules {
+    rules: Vec<CategorizationRule>,
+    corrections: HashMap<String, String>,
+}
+
+impl Rules {
+    pub async fn load(pool: &SqlitePool) -> Result<Self> {
+        let rules = list(pool).await?;
+        let corrections = if rules.iter().any(|r| r.match_field == MatchField::Merchant) {
+            merchants::load_corrections(pool).await?
+        } else {
+            HashMap::new()
+        };
+        Ok(Self { rules, corrections })
+    }
+
+    /// The category the first matching rule gives `note`.
+    pub fn category_for(&self, note: Option<&str>) -> Option<&str> {
+        let note = note.map(str::trim).filter(|n| !n.is_empty())?;
+        let lowered = note.to_lowercase();
+        let mut merchant = None;
+        self.rules
+            .iter()
+            .find(|rule| match rule.match_field {
+                MatchField::Note => lowered.contains(&rule.pattern.to_lowercase()),
+                MatchField::Merchant => merchant
+                    .get_or_insert_with(|| merchants::normalize(note, &self.corrections))
+                    .eq_ignore_ascii_case(&rule.pattern),
+            })
+            .map(|rule| rule.category_id.as_str())
+    }
+}
@@ -0,0 +1,149 @@
+//! Naive Bayes category suggester trained on the user's own history.
+//!
+//! Every categorized expense is a training document made of its note words
+//! plus a coarse amount bucket (a €3 coffee and a €60 grocery run look very
+//! different). The model is cheap enough to train on each request, so it's
+//! always up to date with the user's latest corrections.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::error::Result;
+
+/// How much history to learn from.
+const TRAINING_LIMIT: i64 = 5000;
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct CategorySuggestion {
+    pub category_id: String,
+    /// Posterior probability among the ranked categories, `0.0..=1.0`.
+    pub score: f64,
+}
+
+#[derive(Default)]
+struct ClassStats {
+    docs: usize,
+    tokens: HashMap<String, usize>,
+    total_tokens: usize,
+}
+
+#[derive(Default)]
+struct Model {
+    classes: HashMap<String, ClassStats>,
+    vocab: HashSet<String>,
+    docs: usize,
+}
+
+impl Model {
+    fn add(&mut self, category_id: &str, features: Vec<String>) {
+        let stats = self.classes.entry(category_id.to_string()).or_default();
+        stats.docs += 1;
+        self.docs += 1;
+        for token in features {
+            stats.total_tokens += 1;
+            *stats.tokens.entry(token.clone()).or_default() += 1;
+            self.vocab.insert(token);
+        }
+    }
+
+    /// Multinomial naive Bayes with Laplace smoothing, normalized to
+    /// probabilities. Tokens never seen in training carry no signal and are
+    /// ignored.
+    fn rank(&self, features: &[String]) -> Vec<CategorySuggestion> {
+        let known: Vec<&String> = features
+            .iter()
+            .filter(|t| self.vocab.contains(*t))
+            .collect();
+        if known.is_empty() {
+            return Vec::new();
+        }
+
+        let vocab = self.vocab.len() as f64;
+        let log_scores: Vec<(&String, f64)> = self
+            .classes
+            .iter()
+            .map(|(id, stats)| {
+                let prior = (stats.docs as f64 / self.docs as f64).ln();
+                let denom = stats.total_tokens as f64 + vocab;
+                let likelihood: f64 = known
+                    .iter()
+                    .map(|t| {
+                        let count = stats.tokens.get(*t).copied().unwrap_or(0) as f64;
+                        ((count + 1.0) / denom).ln()
+                    })
+                    .sum();
+                (id, prior + likelihood)
+            })
+            .collect();
+
+        // Softmax in log space to avoid underflow.
+        let max = log_scores
+            .iter()
+            .map(|(_, s)| *s)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let total: f64 = log_scores.iter().map(|(_, s)| (s - max).exp()).sum();
+        let mut ranked: Vec<CategorySuggestion> = log_scores
+            .into_iter()
+            .map(|(id, s)| CategorySuggestion {
+                category_id: id.clone(),
+                score: (s - max).exp() / total,
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+        ranked
+    }
+}
+
+/// Note words plus an amount bucket on a rough log scale.
+fn features(note: Option<&str>, amount: Option<f64>) -> Vec<String> {
+    let mut features: Vec<String> = note
+        .unwrap_or_default()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 2 && !w.chars().all(|c| c.is_ascii_digit()))
+        .map(str::to_lowercase)
+        .collect();
+
+    if let Some(amount) = amount.filter(|a| *a > 0.0) {
+        let bucket = match amount {
+            a if a < 5.0 => "lt5",
+            a if a < 15.0 => "lt15",
+            a if a < 40.0 => "lt40",
+            a if a < 100.0 => "lt100",
+            a if a < 300.0 => "lt300",
+            _ => "gte300",
+        };
+        features.push(format!("__amount_{bucket}"));
+    }
+    features
+}
+
+/// Rank the visible categories for a new expense, most likely first.
+pub async fn suggest_categories(
+    pool: &SqlitePool,
+    note: Option<&str>,
+    amount: Option<f64>,
+    limit: usize,
+) -> Result<Vec<CategorySuggestion>> {
+    let rows: Vec<(String, Option<String>, f64)> = sqlx::query_as(
+        "SELECT e.category_id, e.note, e.amount
+         FROM expenses e
+         JOIN categories c ON c.id = e.category_id
+         WHERE e.deleted_at IS NULL AND c.deleted_at IS NULL AND c.is_hidden = 0
+         ORDER BY e.date DESC, e.created_at DESC
+         LIMIT $1",
+    )
+    .bind(TRAINING_LIMIT)
+    .fetch_all(pool)
+    .await?;
+
+    let mut model = Model::default();
+    for (category_id, note, amount) in rows {
+        model.add(&category_id, features(note.as_deref(), Some(amount)));
+    }
+
+    let mut ranked = model.rank(&features(note, amount));
+    ranked.truncate(limit);
+    Ok(ranked)
+}
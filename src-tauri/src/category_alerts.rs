@@ -0,0 +1,190 @@
+//! Per-category spending alerts.
+//!
+//! A rule gives a category a limit per budget period and the percentages of
+//! it to warn at: 60 and 90 for dining, say, or none at all for utilities.
+//! After every expense write the frontend asks us to check that category,
+//! and a job sweeps all rules so expenses pulled in by sync are caught too.
+//! Each threshold fires at most once per period. In a period where the
+//! category has a budget of its own (see `category_budgets`), the
+//! thresholds apply to that instead of the rule's standing limit.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::AppHandle;
+
+use crate::analysis::spending::NET_AMOUNT;
+use crate::category_budgets;
+use crate::db::now;
+use crate::error::{Error, Result};
+use crate::notify;
+use crate::periods;
+
+/// Highest percentage a threshold may be set to.
+const MAX_THRESHOLD: u32 = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct CategoryAlertRule {
+    pub category_id: String,
+    /// Spending limit for the category per budget period, for periods
+    /// without a category budget.
+    pub limit_amount: f64,
+    /// Percentages of the limit to warn at; empty never warns.
+    pub thresholds: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct CategoryAlert {
+    pub category_id: String,
+    pub category_name: String,
+    pub threshold: u32,
+    pub spent: f64,
+    pub limit_amount: f64,
+}
+
+fn parse_thresholds(json: &str) -> Vec<u32> {
+    serde_json::from_str(json).unwrap_or_default()
+}
+
+pub async fn list(pool: &SqlitePool) -> Result<Vec<CategoryAlertRule>> {
+    let rows: Vec<(String, f64, String)> = sqlx::query_as(
+        "SELECT category_id, limit_amount, thresholds FROM category_alert_thresholds
+         ORDER BY category_id",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(
+            |(category_id, limit_amount, thresholds)| CategoryAlertRule {
+                category_id,
+                limit_amount,
+                thresholds: parse_thresholds(&thresholds),
+            },
+        )
+        .collect())
+}
+
+async fn rule(pool: &SqlitePool, category_id: &str) -> Result<Option<CategoryAlertRule>> {
+    Ok(list(pool)
+        .await?
+        .into_iter()
+        .find(|rule| rule.category_id == category_id))
+}
+
+/// Create or replace the rule for a category.
+pub async fn save(pool: &SqlitePool, mut rule: CategoryAlertRule) -> Result<CategoryAlertRule> {
+    if rule.limit_amount <= 0.0 {
+        return Err(Error::Validation("Limit must be positive".to_string()));
+    }
+    if rule
+        .thresholds
+        .iter()
+        .any(|t| *t == 0 || *t > MAX_THRESHOLD)
+    {
+        return Err(Error::Validation(format!(
+            "Alert percentages must be between 1 and {MAX_THRESHOLD}"
+        )));
+    }
+    rule.thresholds.sort_unstable();
+    rule.thresholds.dedup();
+
+    let now = now();
+    sqlx::query(
+        "INSERT INTO category_alert_thresholds (category_id, limit_amount, thresholds, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $4)
+         ON CONFLICT(category_id) DO UPDATE SET
+           limit_amount = excluded.limit_amount,
+           thresholds = excluded.thresholds,
+           updated_at = excluded.updated_at",
+    )
+    .bind(&rule.category_id)
+    .bind(rule.limit_amount)
+    .bind(serde_json::Value::from(rule.thresholds.clone()).to_string())
+    .bind(&now)
+    .execute(pool)
+    .await?;
+    Ok(rule)
+}
+
+pub async fn remove(pool: &SqlitePool, category_id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM category_alert_thresholds WHERE category_id = $1")
+        .bind(category_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Warn about the highest threshold `category_id` has crossed this period,
+/// unless that was already done. Returns the alert if one went out.
+pub async fn check(
+    app: &AppHandle,
+    pool: &SqlitePool,
+    category_id: &str,
+    today: NaiveDate,
+) -> Result<Option<CategoryAlert>> {
+    let Some(rule) = rule(pool, category_id).await? else {
+        return Ok(None);
+    };
+    let period = periods::schedule(pool).await?.period_at(today);
+    let limit_amount =
+        category_budgets::allocation(&mut *pool.acquire().await?, &period.key, category_id)
+            .await?
+            .unwrap_or(rule.limit_amount);
+    let (name, spent): (Option<String>, f64) = sqlx::query_as(&format!(
+        "SELECT (SELECT name FROM categories WHERE id = $1),
+                COALESCE(SUM({NET_AMOUNT}), 0.0)
+         FROM expenses
+         WHERE category_id = $1 AND deleted_at IS NULL
+           AND substr(date, 1, 10) BETWEEN $2 AND $3"
+    ))
+    .bind(category_id)
+    .bind(period.start.format("%Y-%m-%d").to_string())
+    .bind(period.end.format("%Y-%m-%d").to_string())
+    .fetch_one(pool)
+    .await?;
+
+    let percent = spent / limit_amount * 100.0;
+    let Some(threshold) = rule
+        .thresholds
+        .iter()
+        .copied()
+        .filter(|t| percent >= f64::from(*t))
+        .max()
+    else {
+        return Ok(None);
+    };
+
+    let category_name = name.unwrap_or_else(|| "A category".to_string());
+    let body = if threshold >= 100 {
+        format!("{category_name} is at {percent:.0}% of its limit for this period.")
+    } else {
+        format!("{category_name} has used {threshold}% of its limit for this period.")
+    };
+    let sent = notify::send_once(
+        app,
+        pool,
+        &format!("category_alert:{category_id}:{}:{threshold}", period.key),
+        "category_alert",
+        None,
+        "Category spending alert",
+        &body,
+    )
+    .await?;
+
+    Ok(sent.then_some(CategoryAlert {
+        category_id: category_id.to_string(),
+        category_name,
+        threshold,
+        spent,
+        limit_amount,
+    }))
+}
+
+/// Check every category that has a rule.
+pub async fn check_all(app: &AppHandle, pool: &SqlitePool, today: NaiveDate) -> Result<()> {
+    for rule in list(pool).await? {
+        check(app, pool, &rule.category_id, today).await?;
+    }
+    Ok(())
+}
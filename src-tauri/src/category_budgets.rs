@@ -0,0 +1,180 @@
+//! Envelope budgeting: limits per category for a budget period.
+//!
+//! The period's budget (see `budgets`) still sets the overall limit;
+//! allocations split it up. Nothing forces them to add up to it, and the
+//! summary reports what is left unallocated, or over-allocated, instead.
+//! Allocations are keyed by period like budgets, so changing the pay
+//! schedule leaves earlier ones attached to the periods they were made for.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::analysis::spending::NET_AMOUNT;
+use crate::budgets;
+use crate::db::{new_id, now};
+use crate::error::{Error, Result};
+use crate::expenses;
+use crate::periods::{PaySchedule, Period};
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct CategoryBudget {
+    pub id: String,
+    /// Key of the budget period.
+    pub month: String,
+    pub category_id: String,
+    pub amount: f64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Spent against allocated for one category. Categories with spending but
+/// no allocation are included with `allocated: None`, and uncategorized
+/// spending has no `category_id`.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct Envelope {
+    pub category_id: Option<String>,
+    pub category_name: Option<String>,
+    pub category_icon: Option<String>,
+    pub category_color: Option<String>,
+    pub allocated: Option<f64>,
+    /// Net of reimbursed expenses.
+    pub spent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct EnvelopeSummary {
+    pub period: Period,
+    /// The overall limit of the period's budget, if one was set.
+    pub budget_limit: Option<f64>,
+    pub allocated: f64,
+    /// Budget limit minus allocations; negative when over-allocated.
+    pub unallocated: Option<f64>,
+    pub spent: f64,
+    pub envelopes: Vec<Envelope>,
+}
+
+/// The allocation for `category_id` in the period keyed `key`.
+pub async fn allocation(
+    conn: &mut SqliteConnection,
+    key: &str,
+    category_id: &str,
+) -> Result<Option<f64>> {
+    let amount: Option<(f64,)> =
+        sqlx::query_as("SELECT amount FROM category_budgets WHERE month = $1 AND category_id = $2")
+            .bind(key)
+            .bind(category_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+    Ok(amount.map(|(amount,)| amount))
+}
+
+/// Allocations for the period keyed `key`.
+pub async fn list(pool: &SqlitePool, key: &str) -> Result<Vec<CategoryBudget>> {
+    Ok(sqlx::query_as::<_, CategoryBudget>(
+        "SELECT b.* FROM category_budgets b
+         JOIN categories c ON c.id = b.category_id
+         WHERE b.month = $1
+         ORDER BY c.sort_order, c.name",
+    )
+    .bind(key)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Set what `category_id` may spend in the period keyed `key`.
+pub async fn allocate(
+    pool: &SqlitePool,
+    key: &str,
+    category_id: &str,
+    amount: f64,
+) -> Result<CategoryBudget> {
+    if !amount.is_finite() || amount <= 0.0 {
+        return Err(Error::Validation(
+            "Amount must be a positive number".to_string(),
+        ));
+    }
+    let mut tx = pool.begin().await?;
+    expenses::check_category(&mut tx, category_id).await?;
+    let now = now();
+    sqlx::query(
+        "INSERT INTO category_budgets (id, month, category_id, amount, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $5)
+         ON CONFLICT(month, category_id) DO UPDATE SET
+           amount = excluded.amount, updated_at = excluded.updated_at",
+    )
+    .bind(new_id())
+    .bind(key)
+    .bind(category_id)
+    .bind(amount)
+    .bind(&now)
+    .execute(&mut *tx)
+    .await?;
+    let budget = sqlx::query_as::<_, CategoryBudget>(
+        "SELECT * FROM category_budgets WHERE month = $1 AND category_id = $2",
+    )
+    .bind(key)
+    .bind(category_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(budget)
+}
+
+pub async fn remove(pool: &SqlitePool, key: &str, category_id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM category_budgets WHERE month = $1 AND category_id = $2")
+        .bind(key)
+        .bind(category_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Spent against allocated per category for the period `date` falls in.
+pub async fn envelopes(
+    pool: &SqlitePool,
+    schedule: &PaySchedule,
+    date: NaiveDate,
+) -> Result<EnvelopeSummary> {
+    let period = schedule.period_at(date);
+    let mut conn = pool.acquire().await?;
+    let envelopes = sqlx::query_as::<_, Envelope>(&format!(
+        "WITH spent AS (
+           SELECT category_id, SUM({NET_AMOUNT}) AS spent
+           FROM expenses
+           WHERE deleted_at IS NULL AND substr(date, 1, 10) BETWEEN $2 AND $3
+           GROUP BY category_id
+         ),
+         keys AS (
+           SELECT category_id FROM category_budgets WHERE month = $1
+           UNION
+           SELECT category_id FROM spent
+         )
+         SELECT k.category_id, c.name AS category_name, c.icon AS category_icon,
+                c.color AS category_color, b.amount AS allocated,
+                COALESCE(s.spent, 0.0) AS spent
+         FROM keys k
+         LEFT JOIN categories c ON c.id = k.category_id
+         LEFT JOIN category_budgets b ON b.month = $1 AND b.category_id = k.category_id
+         LEFT JOIN spent s ON s.category_id IS k.category_id
+         ORDER BY k.category_id IS NULL, c.sort_order, c.name"
+    ))
+    .bind(&period.key)
+    .bind(period.start.format("%Y-%m-%d").to_string())
+    .bind(period.end.format("%Y-%m-%d").to_string())
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let budget_limit = budgets::for_period(&mut conn, &period.key)
+        .await?
+        .map(|b| b.limit());
+    let allocated: f64 = envelopes.iter().filter_map(|e| e.allocated).sum();
+    Ok(EnvelopeSummary {
+        budget_limit,
+        allocated,
+        unallocated: budget_limit.map(|limit| limit - allocated),
+        spent: envelopes.iter().map(|e| e.spent).sum(),
+        envelopes,
+        period,
+    })
+}
@@ -0,0 +1,163 @@
+//! The `monthly_category_totals` cache.
+//!
+//! After an expense is written the frontend reports which (month, category)
+//! cells it touched: the old ones and the new ones, since an edit can move
+//! an expense between both. Those cells are recomputed from `expenses`
+//! rather than adjusted by the difference, so a missed or repeated report
+//! can't leave a cell permanently off. A full rebuild runs the first time
+//! the cache is read and with nightly maintenance.
+
+use chrono::{Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::analysis::spending::NET_AMOUNT;
+use crate::app_meta;
+use crate::db::now;
+use crate::error::{Error, Result};
+
+/// Set when the cache was last rebuilt from scratch.
+const BUILT_AT: &str = "category_totals_built_at";
+
+/// A cell to recompute.
+#[derive(Debug, Clone, Deserialize, specta::Type)]
+pub struct Cell {
+    /// The expense date; only its "YYYY-MM" part matters.
+    pub date: String,
+    pub category_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct CategoryTotal {
+    pub category_id: Option<String>,
+    pub category_name: Option<String>,
+    /// Net of reimbursed expenses.
+    pub total: f64,
+    pub gross: f64,
+    pub expense_count: i64,
+}
+
+/// First day of `month` and of the month after, as date strings, so the
+/// range can use the index on `expenses.date`.
+fn month_bounds(month: &str) -> Result<(String, String)> {
+    let first = NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d")
+        .map_err(|_| Error::Validation(format!("\"{month}\" is not a YYYY-MM month")))?;
+    let next = first + Months::new(1);
+    Ok((
+        first.format("%Y-%m-%d").to_string(),
+        next.format("%Y-%m-%d").to_string(),
+    ))
+}
+
+async fn refresh_cell(conn: &mut SqliteConnection, month: &str, category_key: &str) -> Result<()> {
+    let (start, end) = month_bounds(month)?;
+    let (total, gross, count): (f64, f64, i64) = sqlx::query_as(&format!(
+        "SELECT COALESCE(SUM({NET_AMOUNT}), 0.0), COALESCE(SUM(amount), 0.0), COUNT(*)
+         FROM expenses
+         WHERE deleted_at IS NULL AND date >= $1 AND date < $2
+           AND COALESCE(category_id, '') = $3"
+    ))
+    .bind(&start)
+    .bind(&end)
+    .bind(category_key)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if count == 0 {
+        sqlx::query("DELETE FROM monthly_category_totals WHERE month = $1 AND category_key = $2")
+            .bind(month)
+            .bind(category_key)
+            .execute(&mut *conn)
+            .await?;
+        return Ok(());
+    }
+    sqlx::query(
+        "INSERT INTO monthly_category_totals (month, category_key, total, gross, expense_count, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         ON CONFLICT(month, category_key) DO UPDATE SET
+           total = excluded.total, gross = excluded.gross,
+           expense_count = excluded.expense_count, updated_at = excluded.updated_at",
+    )
+    .bind(month)
+    .bind(category_key)
+    .bind(total)
+    .bind(gross)
+    .bind(count)
+    .bind(now())
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Recompute the given cells.
+pub async fn refresh(pool: &SqlitePool, cells: &[Cell]) -> Result<()> {
+    let mut keys: Vec<(String, String)> = cells
+        .iter()
+        .filter_map(|cell| {
+            let month = cell.date.get(..7)?.to_string();
+            Some((month, cell.category_id.clone().unwrap_or_default()))
+        })
+        .collect();
+    keys.sort();
+    keys.dedup();
+
+    let mut tx = pool.begin().await?;
+    for (month, category_key) in &keys {
+        refresh_cell(&mut tx, month, category_key).await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Recompute the cell `expense_id` is in, after a write on the Rust side.
+pub async fn refresh_expense(pool: &SqlitePool, expense_id: &str) -> Result<()> {
+    let cell: Option<(String, Option<String>)> =
+        sqlx::query_as("SELECT date, category_id FROM expenses WHERE id = $1")
+            .bind(expense_id)
+            .fetch_optional(pool)
+            .await?;
+    match cell {
+        Some((date, category_id)) => refresh(pool, &[Cell { date, category_id }]).await,
+        None => Ok(()),
+    }
+}
+
+/// Throw the cache away and compute every cell again.
+pub async fn rebuild(pool: &SqlitePool) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM monthly_category_totals")
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(&format!(
+        "INSERT INTO monthly_category_totals (month, category_key, total, gross, expense_count, updated_at)
+         SELECT substr(date, 1, 7), COALESCE(category_id, ''),
+                COALESCE(SUM({NET_AMOUNT}), 0.0), COALESCE(SUM(amount), 0.0), COUNT(*), $1
+         FROM expenses
+         WHERE deleted_at IS NULL
+         GROUP BY substr(date, 1, 7), COALESCE(category_id, '')"
+    ))
+    .bind(now())
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    app_meta::set(pool, BUILT_AT, &now()).await
+}
+
+/// Totals for `month` ("YYYY-MM"), largest first.
+pub async fn for_month(pool: &SqlitePool, month: &str) -> Result<Vec<CategoryTotal>> {
+    month_bounds(month)?;
+    if app_meta::get(pool, BUILT_AT).await?.is_none() {
+        rebuild(pool).await?;
+    }
+    Ok(sqlx::query_as::<_, CategoryTotal>(
+        "SELECT NULLIF(t.category_key, '') AS category_id, c.name AS category_name,
+                t.total, t.gross, t.expense_count
+         FROM monthly_category_totals t
+         LEFT JOIN categories c ON c.id = t.category_key
+         WHERE t.month = $1
+         ORDER BY t.total DESC",
+    )
+    .bind(month)
+    .fetch_all(pool)
+    .await?)
+}
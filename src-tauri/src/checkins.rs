@@ -0,0 +1,286 @@
+//! Follow-ups for unanswered monthly check-ins.
+//!
+//! The frontend sends the monthly check-in on its cron. If a goal still has
+//! no contribution for the month a few days later we send a gentler
+//! reminder, and if that goes unanswered too, a message with how far the
+//! goal's timeline slipped, instead of letting the month pass silently.
+//!
+//! `pending` works out what a month's check-in still asks for: savings
+//! goals without a contribution and habits without a tracking row, each
+//! with an amount to suggest. The check-in screen and the notification
+//! text both come from it.
+
+use chrono::{DateTime, Datelike, Local, Months, NaiveDate, Utc};
+use serde::Serialize;
+use serde_json::json;
+use sqlx::SqlitePool;
+use tauri::AppHandle;
+
+use crate::analysis::projection::{self, GoalStatus};
+use crate::error::Result;
+use crate::webhooks::{self, WebhookEvent};
+use crate::{goals, habits, notify};
+
+/// Days after the check-in (and again after the reminder) before following up.
+const FOLLOW_UP_DAYS: i64 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckinKind {
+    SavingsGoal,
+    HabitGoal,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct PendingCheckin {
+    pub kind: CheckinKind,
+    /// The savings goal or habit goal.
+    pub goal_id: String,
+    pub name: String,
+    /// "YYYY-MM".
+    pub month: String,
+    /// For a savings goal, the contribution that keeps it on plan; for a
+    /// habit, what its category has used so far.
+    pub suggested_amount: f64,
+    /// The planned monthly contribution, or the habit's limit.
+    pub target_amount: f64,
+    /// The amounts are numbers of purchases (`max_count` habits).
+    pub is_count: bool,
+}
+
+/// What the check-in for the month starting `month` still asks for,
+/// savings goals first.
+pub async fn pending(
+    pool: &SqlitePool,
+    month: NaiveDate,
+    today: NaiveDate,
+) -> Result<Vec<PendingCheckin>> {
+    let key = month.format("%Y-%m").to_string();
+    let mut pending = Vec::new();
+
+    for goal in goals::list(pool).await? {
+        if goal.created_at.get(..7).is_some_and(|m| m > key.as_str())
+            || goals::has_contribution(pool, &goal.id, &key).await?
+        {
+            continue;
+        }
+        let total_saved = goals::total_saved(pool, &goal.id).await?;
+        let forecast = projection::forecast(&goal, total_saved, None, today)?;
+        // Behind plan, suggest catching up; overdue, the usual amount
+        // rather than everything at once.
+        let suggested = match forecast.status {
+            GoalStatus::Reached => continue,
+            GoalStatus::Behind | GoalStatus::Stalled => forecast.required_monthly,
+            GoalStatus::OnTrack | GoalStatus::Overdue => goal.monthly_contribution,
+        };
+        pending.push(PendingCheckin {
+            kind: CheckinKind::SavingsGoal,
+            goal_id: goal.id,
+            name: goal.name,
+            month: key.clone(),
+            suggested_amount: suggested.min(forecast.remaining),
+            target_amount: goal.monthly_contribution,
+            is_count: false,
+        });
+    }
+
+    let previous = (month - Months::new(1)).format("%Y-%m").to_string();
+    for habit in habits::untracked(pool, &key, &previous).await? {
+        pending.push(PendingCheckin {
+            kind: CheckinKind::HabitGoal,
+            goal_id: habit.id,
+            name: habit.name,
+            month: key.clone(),
+            suggested_amount: habit.spent,
+            target_amount: habit.target,
+            is_count: habit.rule_type == "max_count",
+        });
+    }
+    Ok(pending)
+}
+
+/// The notification text asking for `pending`, which are for
+/// `month_name`; `None` when nothing is pending.
+pub fn summary(month_name: &str, pending: &[PendingCheckin]) -> Option<String> {
+    let savings: Vec<_> = pending
+        .iter()
+        .filter(|c| c.kind == CheckinKind::SavingsGoal)
+        .collect();
+    let habits = pending.len() - savings.len();
+    let habits = match habits {
+        0 => String::new(),
+        1 => " One habit is waiting too.".to_string(),
+        n => format!(" {n} habits are waiting too."),
+    };
+    match savings.as_slice() {
+        [] if habits.is_empty() => None,
+        [] => Some(format!(
+            "Time to check in on your habits for {month_name}!{habits}"
+        )),
+        [goal] => Some(format!(
+            "Time to record your savings for {month_name}! {:.2} keeps {} on plan.{habits}",
+            goal.suggested_amount, goal.name
+        )),
+        [first, rest @ ..] => Some(format!(
+            "Time to record your savings for {month_name}! {} goals are waiting, starting with {} ({:.2}).{habits}",
+            rest.len() + 1,
+            first.name,
+            first.suggested_amount
+        )),
+    }
+}
+
+async fn checkins_enabled(pool: &SqlitePool) -> Result<bool> {
+    let row: Option<(Option<i64>,)> =
+        sqlx::query_as("SELECT monthly_checkin_enabled FROM notification_preferences WHERE id = 1")
+            .fetch_optional(pool)
+            .await?;
+    Ok(row.and_then(|(enabled,)| enabled).unwrap_or(1) != 0)
+}
+
+/// When the latest monthly check-in notification went out.
+async fn last_checkin_sent(pool: &SqlitePool) -> Result<Option<DateTime<Utc>>> {
+    let (sent_at,): (Option<String>,) = sqlx::query_as(
+        "SELECT MAX(sent_at) FROM scheduled_notifications WHERE notification_type = 'monthly_checkin'",
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(sent_at
+        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+        .map(|t| t.with_timezone(&Utc)))
+}
+
+/// Ask for last month's contributions, once per month, if there are goals
+/// to check in for. Called when the month is closed.
+pub async fn send_monthly(app: &AppHandle, pool: &SqlitePool, month: NaiveDate) -> Result<bool> {
+    if !checkins_enabled(pool).await? {
+        return Ok(false);
+    }
+    let pending = pending(pool, month, Local::now().date_naive()).await?;
+    let Some(body) = summary(&month.format("%B").to_string(), &pending) else {
+        return Ok(false);
+    };
+    announce(pool, month, &pending).await;
+    notify::send_once(
+        app,
+        pool,
+        &format!("monthly_checkin:{}", month.format("%Y-%m")),
+        "monthly_checkin",
+        None,
+        "Monthly Savings Check-in",
+        &body,
+    )
+    .await
+}
+
+/// Tell webhooks the check-in for the month starting `month` is due.
+pub(crate) async fn announce(pool: &SqlitePool, month: NaiveDate, pending: &[PendingCheckin]) {
+    let month = month.format("%Y-%m").to_string();
+    webhooks::trigger(
+        pool,
+        WebhookEvent::CheckinDue,
+        &format!("checkin_due:{month}"),
+        json!({ "month": month, "pending": pending }),
+    )
+    .await;
+}
+
+fn days_since(at: &str, now: DateTime<Utc>) -> Option<i64> {
+    let at = DateTime::parse_from_rfc3339(at).ok()?;
+    Some((now - at.with_timezone(&Utc)).num_days())
+}
+
+/// Send whichever follow-up is due for check-ins nobody answered.
+pub async fn escalate(app: &AppHandle, pool: &SqlitePool) -> Result<()> {
+    if !notify::enabled(pool).await? || !checkins_enabled(pool).await? {
+        return Ok(());
+    }
+    let Some(sent) = last_checkin_sent(pool).await? else {
+        return Ok(());
+    };
+    let now = Utc::now();
+    if (now - sent).num_days() < FOLLOW_UP_DAYS {
+        return Ok(());
+    }
+
+    // The check-in asks about the month before the one it was sent in.
+    let sent_on = sent.with_timezone(&Local).date_naive();
+    let Some(checked) = sent_on
+        .with_day(1)
+        .and_then(|d| d.checked_sub_months(Months::new(1)))
+    else {
+        return Ok(());
+    };
+    let month = checked.format("%Y-%m").to_string();
+    let month_name = checked.format("%B").to_string();
+    let today = Local::now().date_naive();
+
+    for goal in goals::list(pool).await? {
+        // Goals created after that month have nothing to check in for.
+        if goal.created_at.get(..7).is_some_and(|m| m > month.as_str()) {
+            continue;
+        }
+        if goals::has_contribution(pool, &goal.id, &month).await? {
+            continue;
+        }
+
+        let reminder_key = format!("checkin_reminder:{}:{month}", goal.id);
+        let Some(reminder_sent) = notify::sent_at(pool, &reminder_key).await? else {
+            notify::send_once(
+                app,
+                pool,
+                &reminder_key,
+                "checkin_reminder",
+                Some(&goal.id),
+                &format!("Still time to log {month_name}"),
+                &format!(
+                    "How much did you put towards {} in {month_name}? Even a quick \"nothing this month\" keeps your plan honest.",
+                    goal.name
+                ),
+            )
+            .await?;
+            continue;
+        };
+        if days_since(&reminder_sent, now).is_none_or(|days| days < FOLLOW_UP_DAYS) {
+            continue;
+        }
+
+        let total_saved = goals::total_saved(pool, &goal.id).await?;
+        let projection = projection::project(&goal, total_saved, today);
+        notify::send_once(
+            app,
+            pool,
+            &format!("checkin_slipped:{}:{month}", goal.id),
+            "checkin_slipped",
+            Some(&goal.id),
+            &format!("{} is slipping", goal.name),
+            &slipped_body(
+                &goal.name,
+                &month_name,
+                projection.slip_weeks,
+                projection.projected_completion.as_deref(),
+            ),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+fn slipped_body(goal: &str, month: &str, slip_weeks: f64, completion: Option<&str>) -> String {
+    let weeks = slip_weeks.round() as i64;
+    let eta = completion
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .map(|d| format!(" You're now on course for {}.", d.format("%B %Y")))
+        .unwrap_or_default();
+    match weeks {
+        ..=0 => format!(
+            "No check-in for {month} yet, so {goal} can't tell if you're still on plan.{eta}"
+        ),
+        1 => format!(
+            "Without a {month} check-in, your {goal} timeline slipped by about a week.{eta}"
+        ),
+        _ => format!(
+            "Without a {month} check-in, your {goal} timeline slipped by about {weeks} weeks.{eta}"
+        ),
+    }
+}
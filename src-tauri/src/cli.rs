@@ -0,0 +1,288 @@
+//! `goaldy <command>`: headless use of the same database, for scripts and
+//! terminals.
+//!
+//! ```text
+//! goaldy add 12.50 coffee [--category NAME] [--note TEXT] [--date YYYY-MM-DD]
+//! goaldy export [--format csv|json] [--month YYYY-MM] [--output FILE]
+//! goaldy sync
+//! goaldy unlock
+//! ```
+//!
+//! Commands open `goaldy.db` where the app keeps it (`--db FILE` or
+//! `GOALDY_DB` point elsewhere) and go through the same data layer as the
+//! app's commands, so an expense added here is validated, categorized and
+//! queued for sync like any other. `sync` only pushes the queue, using the
+//! project and session the app last had; pulls are left to the app. The
+//! schema is the app's, so it has to have run once on the file first.
+//! `unlock` reads the passphrase of an encrypted database from stdin and
+//! puts its key back in the keychain, for when the keychain lost it.
+//!
+//! Release builds on Windows have no console, so the commands print
+//! nothing there.
+
+use std::path::PathBuf;
+
+use chrono::{Local, Months, NaiveDate};
+use sqlx::SqlitePool;
+
+use crate::backend;
+use crate::db::Db;
+use crate::drafts::{self, find_category};
+use crate::encryption;
+use crate::error::{Error, Result};
+use crate::expenses::{self, NewExpense};
+use crate::export;
+use crate::sync::worker;
+
+/// Same folder Tauri resolves `app_config_dir` to for this identifier.
+const APP_DIR: &str = "app.goaldy.budget";
+
+const USAGE: &str = "Usage:
+  goaldy add <amount and description> [--category NAME] [--note TEXT] [--date YYYY-MM-DD]
+  goaldy export [--format csv|json] [--month YYYY-MM] [--output FILE]
+  goaldy sync
+  goaldy unlock
+
+Options:
+  --db FILE   Use this database instead of the app's (also GOALDY_DB)";
+
+enum Command {
+    Add,
+    Export,
+    Sync,
+    Unlock,
+}
+
+/// Arguments after the command: free words and `--name value` options.
+struct Args {
+    words: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut words = Vec::new();
+        let mut options = Vec::new();
+        while let Some(arg) = args.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                words.push(arg);
+                continue;
+            };
+            let (name, value) = match name.split_once('=') {
+                Some((name, value)) => (name.to_string(), value.to_string()),
+                None => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| Error::Validation(format!("--{name} needs a value")))?;
+                    (name.to_string(), value)
+                }
+            };
+            options.push((name, value));
+        }
+        Ok(Self { words, options })
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn only(&self, allowed: &[&str]) -> Result<()> {
+        match self
+            .options
+            .iter()
+            .find(|(name, _)| name != "db" && !allowed.contains(&name.as_str()))
+        {
+            Some((name, _)) => Err(Error::Validation(format!("Unknown option --{name}"))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Run the command named on the command line, if any, and return the exit
+/// code. `None` means there is no command and the app should start.
+pub fn run() -> Option<i32> {
+    let mut args = std::env::args().skip(1);
+    let command = match args.next()?.as_str() {
+        "add" => Command::Add,
+        "export" => Command::Export,
+        "sync" => Command::Sync,
+        "unlock" => Command::Unlock,
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            return Some(0);
+        }
+        // Anything else, such as a deep link on Windows and Linux, is the
+        // app's.
+        _ => return None,
+    };
+    let result =
+        Args::parse(args).and_then(|args| tauri::async_runtime::block_on(execute(command, &args)));
+    match result {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("goaldy: {e}");
+            Some(1)
+        }
+    }
+}
+
+async fn execute(command: Command, args: &Args) -> Result<()> {
+    // Before opening the database, which needs the key.
+    if let Command::Unlock = command {
+        return unlock(args).await;
+    }
+    let db = Db::open_at(&db_path(args)?).await?;
+    let pool = db.pool();
+    let migrated: Option<(String,)> =
+        sqlx::query_as("SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'expenses'")
+            .fetch_optional(pool)
+            .await?;
+    if migrated.is_none() {
+        return Err(Error::Validation(
+            "The database has not been set up; open the app once first".to_string(),
+        ));
+    }
+    match command {
+        Command::Add => add(pool, args).await,
+        Command::Export => export(pool, args).await,
+        Command::Sync => sync(pool, args).await,
+        Command::Unlock => unreachable!("handled before opening the database"),
+    }
+}
+
+fn db_path(args: &Args) -> Result<PathBuf> {
+    if let Some(path) = args
+        .option("db")
+        .map(str::to_string)
+        .or_else(|| std::env::var("GOALDY_DB").ok())
+    {
+        return Ok(PathBuf::from(path));
+    }
+    let dir = dirs::config_dir()
+        .ok_or_else(|| Error::Unsupported("No config folder on this system".to_string()))?;
+    let path = dir.join(APP_DIR).join("goaldy.db");
+    if !path.exists() {
+        return Err(Error::Validation(format!(
+            "No database at {}; open the app once first or pass --db",
+            path.display()
+        )));
+    }
+    Ok(path)
+}
+
+async fn unlock(args: &Args) -> Result<()> {
+    args.only(&[])?;
+    let path = db_path(args)?;
+    eprint!("Passphrase: ");
+    let mut passphrase = String::new();
+    std::io::stdin().read_line(&mut passphrase)?;
+    let passphrase = passphrase.trim_end_matches(['\r', '\n']);
+    if passphrase.is_empty() {
+        return Err(Error::Validation("No passphrase given".to_string()));
+    }
+    encryption::unlock(&path, passphrase).await?;
+    println!("Unlocked; the app can open the database again");
+    Ok(())
+}
+
+async fn add(pool: &SqlitePool, args: &Args) -> Result<()> {
+    args.only(&["category", "note", "date"])?;
+    let categories = drafts::load_categories(pool).await?;
+    let today = Local::now().date_naive();
+    let draft = drafts::parse_expense_text(&args.words.join(" "), &categories, today);
+
+    let amount = draft
+        .amount
+        .ok_or_else(|| Error::Validation("No amount given".to_string()))?;
+    let category_id = match args.option("category") {
+        Some(name) => Some(
+            find_category(name, &categories)
+                .ok_or_else(|| Error::Validation(format!("No category matches \"{name}\"")))?,
+        ),
+        None => draft.category_id,
+    };
+    let input = NewExpense {
+        amount,
+        category_id,
+        note: args.option("note").map(str::to_string).or(draft.note),
+        date: Some(args.option("date").map_or(draft.date, str::to_string)),
+        payment_method: None,
+        currency: None,
+        account_id: None,
+    };
+    let expense = expenses::create(pool, input, today).await?;
+    let category = expense
+        .category_id
+        .as_ref()
+        .and_then(|id| categories.iter().find(|c| c.id == *id))
+        .map_or("uncategorized", |c| c.name.as_str());
+    println!(
+        "Added {:.2} on {} ({category}){}",
+        expense.amount,
+        expense.date,
+        expense
+            .note
+            .map(|note| format!(": {note}"))
+            .unwrap_or_default()
+    );
+    Ok(())
+}
+
+async fn export(pool: &SqlitePool, args: &Args) -> Result<()> {
+    args.only(&["format", "month", "output"])?;
+    let month = args
+        .option("month")
+        .map(str::to_string)
+        .unwrap_or_else(|| Local::now().format("%Y-%m").to_string());
+    let start = NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d")
+        .map_err(|_| Error::Validation(format!("{month} is not a YYYY-MM month")))?;
+    let end = start
+        .checked_add_months(Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .ok_or_else(|| Error::Validation(format!("{month} is out of range")))?;
+    let rows = expenses::list(
+        pool,
+        &start.format("%Y-%m-%d").to_string(),
+        &end.format("%Y-%m-%d").to_string(),
+    )
+    .await?;
+
+    let content = match args.option("format").unwrap_or("csv") {
+        "csv" => export::expenses::to_csv(&rows)?,
+        "json" => serde_json::to_string_pretty(&rows)
+            .map_err(|e| Error::Validation(format!("Failed to write JSON: {e}")))?,
+        other => {
+            return Err(Error::Validation(format!(
+                "Unknown format {other}; use csv or json"
+            )))
+        }
+    };
+    match args.option("output") {
+        Some(path) => {
+            std::fs::write(path, content)?;
+            eprintln!("Exported {} expenses to {path}", rows.len());
+        }
+        None => print!("{content}"),
+    }
+    Ok(())
+}
+
+async fn sync(pool: &SqlitePool, args: &Args) -> Result<()> {
+    args.only(&[])?;
+    let config = backend::remembered(pool).await?.ok_or_else(|| {
+        Error::Unsupported("Sync has not been set up; open the app and sign in".to_string())
+    })?;
+    let report = worker::push_headless(pool, &config).await?;
+    println!(
+        "Pushed {}, failed {}, deferred {}",
+        report.pushed, report.failed, report.deferred
+    );
+    for error in &report.errors {
+        eprintln!("  {error}");
+    }
+    Ok(())
+}
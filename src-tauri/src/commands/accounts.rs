@@ -0,0 +1,157 @@
+use chrono::{Local, NaiveDate};
+use tauri::{AppHandle, State};
+
+use crate::accounts::{self, Account, AccountEntry, NewAccount, NewTransfer, Transfer};
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_accounts(db: State<'_, Db>) -> Result<Vec<Account>> {
+    accounts::list(db.pool()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn create_account(
+    app: AppHandle,
+    db: State<'_, Db>,
+    input: NewAccount,
+) -> Result<Account> {
+    let user_id = crate::auth::current_user_id(db.pool()).await?;
+    let today = Local::now().date_naive();
+    let account = accounts::create(db.pool(), user_id.as_deref(), input, today).await?;
+    events::publish(
+        &app,
+        &DomainEvent::AccountSaved {
+            account_id: account.id.clone(),
+        },
+    )?;
+    Ok(account)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_account(
+    app: AppHandle,
+    db: State<'_, Db>,
+    id: String,
+    input: NewAccount,
+) -> Result<Account> {
+    let account = accounts::update(db.pool(), &id, input).await?;
+    events::publish(&app, &DomainEvent::AccountSaved { account_id: id })?;
+    Ok(account)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_account(app: AppHandle, db: State<'_, Db>, id: String) -> Result<()> {
+    accounts::delete(db.pool(), &id).await?;
+    events::publish(&app, &DomainEvent::AccountDeleted { account_id: id })
+}
+
+/// Set an account's balance to what the bank says it is today.
+#[tauri::command]
+#[specta::specta]
+pub async fn reconcile_account_balance(
+    app: AppHandle,
+    db: State<'_, Db>,
+    id: String,
+    balance: f64,
+) -> Result<Account> {
+    let account = accounts::reconcile(db.pool(), &id, balance).await?;
+    events::publish(&app, &DomainEvent::AccountSaved { account_id: id })?;
+    Ok(account)
+}
+
+/// Recompute every account balance from its opening balance and history.
+#[tauri::command]
+#[specta::specta]
+pub async fn recompute_account_balances(app: AppHandle, db: State<'_, Db>) -> Result<Vec<Account>> {
+    let accounts = accounts::recompute(db.pool()).await?;
+    for account in &accounts {
+        events::publish(
+            &app,
+            &DomainEvent::AccountSaved {
+                account_id: account.id.clone(),
+            },
+        )?;
+    }
+    Ok(accounts)
+}
+
+/// Expenses, income and transfers booked to an account between two days.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_account_activity(
+    db: State<'_, Db>,
+    id: String,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<AccountEntry>> {
+    accounts::activity(db.pool(), &id, start, end).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_transfers(
+    db: State<'_, Db>,
+    account_id: Option<String>,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<Transfer>> {
+    accounts::transfers(db.pool(), account_id.as_deref(), start, end).await
+}
+
+fn publish_transfer(app: &AppHandle, transfer: &Transfer) -> Result<()> {
+    events::publish(
+        app,
+        &DomainEvent::TransferSaved {
+            transfer_id: transfer.id.clone(),
+            from_account_id: transfer.from_account_id.clone(),
+            to_account_id: transfer.to_account_id.clone(),
+        },
+    )
+}
+
+/// Move money between two accounts. Not spending, so no expense.
+#[tauri::command]
+#[specta::specta]
+pub async fn create_transfer(
+    app: AppHandle,
+    db: State<'_, Db>,
+    input: NewTransfer,
+) -> Result<Transfer> {
+    let user_id = crate::auth::current_user_id(db.pool()).await?;
+    let today = Local::now().date_naive();
+    let transfer = accounts::create_transfer(db.pool(), user_id.as_deref(), input, today).await?;
+    publish_transfer(&app, &transfer)?;
+    Ok(transfer)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_transfer(app: AppHandle, db: State<'_, Db>, id: String) -> Result<()> {
+    accounts::delete_transfer(db.pool(), &id).await?;
+    events::publish(&app, &DomainEvent::TransferDeleted { transfer_id: id })
+}
+
+/// Replace an expense that was really a transfer, like a credit card
+/// payment, with a transfer to `to_account_id`.
+#[tauri::command]
+#[specta::specta]
+pub async fn convert_expense_to_transfer(
+    app: AppHandle,
+    db: State<'_, Db>,
+    expense_id: String,
+    to_account_id: String,
+) -> Result<Transfer> {
+    let user_id = crate::auth::current_user_id(db.pool()).await?;
+    let transfer =
+        accounts::transfer_from_expense(db.pool(), user_id.as_deref(), &expense_id, &to_account_id)
+            .await?;
+    events::publish(&app, &DomainEvent::ExpenseDeleted { expense_id })?;
+    publish_transfer(&app, &transfer)?;
+    Ok(transfer)
+}
@@ -0,0 +1,12 @@
+use tauri::State;
+
+use crate::app_meta::{self, AppMeta};
+use crate::db::Db;
+use crate::error::Result;
+
+/// Versions, install id and housekeeping timestamps.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_app_meta(db: State<'_, Db>) -> Result<AppMeta> {
+    app_meta::load(db.pool()).await
+}
@@ -0,0 +1,53 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, State};
+
+use crate::archive::import::{self, ArchiveImportReport, ImportMode, StagedArchive};
+use crate::archive::{self, Manifest};
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+
+/// Write an encrypted `.goaldy` archive of all local data to `path`.
+#[tauri::command]
+#[specta::specta]
+pub async fn export_archive(
+    app: AppHandle,
+    db: State<'_, Db>,
+    path: PathBuf,
+    passphrase: String,
+) -> Result<Manifest> {
+    archive::export(&app, db.pool(), &path, &passphrase).await
+}
+
+/// Decrypt an archive into a staging area and report which migrations its
+/// database still needs (run by the frontend before `import_archive`).
+#[tauri::command]
+#[specta::specta]
+pub async fn stage_archive(
+    app: AppHandle,
+    db: State<'_, Db>,
+    path: PathBuf,
+    passphrase: String,
+) -> Result<StagedArchive> {
+    import::stage(&app, db.pool(), &path, &passphrase).await
+}
+
+/// Replace or merge local data with a staged archive.
+#[tauri::command]
+#[specta::specta]
+pub async fn import_archive(
+    app: AppHandle,
+    db: State<'_, Db>,
+    staging_id: String,
+    mode: ImportMode,
+) -> Result<ArchiveImportReport> {
+    let report = import::apply(&app, db.pool(), &staging_id, mode).await?;
+    events::publish(
+        &app,
+        &DomainEvent::DataRestored {
+            source: "archive".to_string(),
+        },
+    )?;
+    Ok(report)
+}
@@ -0,0 +1,62 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, State};
+
+use crate::attachments::files::{self, Attachment, CleanupReport};
+use crate::attachments::sync::{self, SyncReport};
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+
+/// Upload and download pending attachment files now.
+#[tauri::command]
+#[specta::specta]
+pub async fn sync_attachments(app: AppHandle) -> Result<SyncReport> {
+    sync::run(&app).await
+}
+
+/// Attach the photo or PDF at `path` to an expense, shrinking photos.
+#[tauri::command]
+#[specta::specta]
+pub async fn save_attachment(
+    app: AppHandle,
+    db: State<'_, Db>,
+    expense_id: String,
+    path: PathBuf,
+) -> Result<Attachment> {
+    let attachment = files::save(&app, db.pool(), &expense_id, &path).await?;
+    events::publish(
+        &app,
+        &DomainEvent::AttachmentAdded {
+            attachment_id: attachment.id.clone(),
+            expense_id: attachment.expense_id.clone(),
+        },
+    )?;
+    Ok(attachment)
+}
+
+/// The files attached to an expense, with their paths on this device.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_expense_attachments(
+    app: AppHandle,
+    db: State<'_, Db>,
+    expense_id: String,
+) -> Result<Vec<Attachment>> {
+    files::for_expense(&app, db.pool(), &expense_id).await
+}
+
+/// Remove an attachment here and, on the next sync, remotely.
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_attachment(app: AppHandle, db: State<'_, Db>, id: String) -> Result<()> {
+    files::remove(&app, db.pool(), &id).await?;
+    events::publish(&app, &DomainEvent::AttachmentDeleted { attachment_id: id })
+}
+
+/// Remove files left behind by deleted expenses now.
+#[tauri::command]
+#[specta::specta]
+pub async fn clean_up_attachments(app: AppHandle, db: State<'_, Db>) -> Result<CleanupReport> {
+    files::clean_up(&app, db.pool()).await
+}
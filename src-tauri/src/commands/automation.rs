@@ -0,0 +1,24 @@
+use tauri::AppHandle;
+
+use crate::automation::{self, AutomationStatus};
+use crate::error::Result;
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_automation_status(app: AppHandle) -> Result<AutomationStatus> {
+    automation::status(&app).await
+}
+
+/// Turn the localhost API on or off.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_automation_enabled(app: AppHandle, enabled: bool) -> Result<AutomationStatus> {
+    automation::set_enabled(&app, enabled).await
+}
+
+/// Replace the API token; tools using the old one stop working.
+#[tauri::command]
+#[specta::specta]
+pub async fn regenerate_automation_token(app: AppHandle) -> Result<AutomationStatus> {
+    automation::regenerate_token(&app).await
+}
@@ -0,0 +1,17 @@
+use tauri::State;
+
+use crate::backend::{self, Backend, BackendConfig};
+use crate::db::Db;
+use crate::error::Result;
+
+/// Tell the backend which Supabase project to talk to.
+#[tauri::command]
+#[specta::specta]
+pub async fn configure_backend(
+    backend: State<'_, Backend>,
+    db: State<'_, Db>,
+    config: BackendConfig,
+) -> Result<()> {
+    backend.configure(config.clone());
+    backend::remember(db.pool(), &config).await
+}
@@ -0,0 +1,109 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, State};
+
+use crate::backup::json::{self, BackupExport, BackupRestore};
+use crate::backup::oauth::Client;
+use crate::backup::{self, BackupProvider, BackupTarget};
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+
+/// Configured backup targets with their last outcome.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_backup_targets(db: State<'_, Db>) -> Result<Vec<BackupTarget>> {
+    backup::targets(db.pool()).await
+}
+
+/// The iCloud Drive folder to suggest on macOS, if there is one.
+#[tauri::command]
+#[specta::specta]
+pub fn get_icloud_drive_dir() -> Option<String> {
+    backup::icloud_drive_dir().map(|dir| dir.to_string_lossy().into_owned())
+}
+
+/// Back up into a folder picked by the user.
+#[tauri::command]
+#[specta::specta]
+pub async fn add_backup_folder(
+    db: State<'_, Db>,
+    path: String,
+    passphrase: String,
+    interval_hours: i64,
+) -> Result<BackupTarget> {
+    backup::add_folder(
+        db.pool(),
+        std::path::Path::new(&path),
+        &passphrase,
+        interval_hours,
+    )
+    .await
+}
+
+/// Sign in to Dropbox or Google Drive in the browser and back up there.
+#[tauri::command]
+#[specta::specta]
+pub async fn connect_backup_provider(
+    app: AppHandle,
+    db: State<'_, Db>,
+    provider: BackupProvider,
+    client_id: String,
+    client_secret: Option<String>,
+    passphrase: String,
+    interval_hours: i64,
+) -> Result<BackupTarget> {
+    let client = Client {
+        id: &client_id,
+        secret: client_secret.as_deref(),
+    };
+    backup::connect(
+        &app,
+        db.pool(),
+        provider,
+        client,
+        &passphrase,
+        interval_hours,
+    )
+    .await
+}
+
+/// Stop backing up to a target. Backups already made stay where they are.
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_backup_target(db: State<'_, Db>, id: String) -> Result<()> {
+    backup::remove(db.pool(), &id).await
+}
+
+/// Back up to one target right away.
+#[tauri::command]
+#[specta::specta]
+pub async fn run_backup(app: AppHandle, db: State<'_, Db>, id: String) -> Result<BackupTarget> {
+    backup::run(&app, db.pool(), &id).await
+}
+
+/// Write a JSON backup of expenses, budgets, goals and habits to the export folder.
+#[tauri::command]
+#[specta::specta]
+pub async fn export_backup(app: AppHandle, db: State<'_, Db>) -> Result<BackupExport> {
+    json::export(&app, db.pool()).await
+}
+
+/// Restore a JSON backup, replacing existing data only with `overwrite`.
+#[tauri::command]
+#[specta::specta]
+pub async fn import_backup(
+    app: AppHandle,
+    db: State<'_, Db>,
+    path: PathBuf,
+    overwrite: Option<bool>,
+) -> Result<BackupRestore> {
+    let restore = json::restore(db.pool(), &path, overwrite.unwrap_or(false)).await?;
+    events::publish(
+        &app,
+        &DomainEvent::DataRestored {
+            source: "backup".to_string(),
+        },
+    )?;
+    Ok(restore)
+}
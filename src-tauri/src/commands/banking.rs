@@ -0,0 +1,114 @@
+use tauri::{AppHandle, State};
+
+use crate::banking::gocardless::{Credentials, Institution};
+use crate::banking::{self, BankConnection, BankTransaction, ConfirmReport, ConfirmTransaction};
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_bank_credentials_set(db: State<'_, Db>) -> Result<bool> {
+    banking::has_credentials(db.pool()).await
+}
+
+/// Store the GoCardless secret id and key after checking them, or remove
+/// them with `None`.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_bank_credentials(
+    db: State<'_, Db>,
+    credentials: Option<Credentials>,
+) -> Result<()> {
+    banking::set_credentials(db.pool(), credentials).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_bank_institutions(db: State<'_, Db>, country: String) -> Result<Vec<Institution>> {
+    banking::institutions(db.pool(), &country).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_bank_connections(db: State<'_, Db>) -> Result<Vec<BankConnection>> {
+    banking::list(db.pool()).await
+}
+
+/// Start connecting a bank; the frontend opens the returned link.
+#[tauri::command]
+#[specta::specta]
+pub async fn connect_bank(
+    db: State<'_, Db>,
+    institution_id: String,
+    institution_name: String,
+) -> Result<BankConnection> {
+    banking::connect(db.pool(), &institution_id, &institution_name).await
+}
+
+/// A token to open Plaid Link with, for banks in the US and Canada.
+#[tauri::command]
+#[specta::specta]
+pub async fn create_plaid_link_token(db: State<'_, Db>) -> Result<String> {
+    banking::plaid_link_token(db.pool()).await
+}
+
+/// Finish linking with the public token Plaid Link handed back.
+#[tauri::command]
+#[specta::specta]
+pub async fn link_plaid(db: State<'_, Db>, public_token: String) -> Result<BankConnection> {
+    banking::link_plaid(db.pool(), &public_token).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn refresh_bank_connection(db: State<'_, Db>, id: String) -> Result<BankConnection> {
+    banking::refresh(db.pool(), &id).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn disconnect_bank(db: State<'_, Db>, id: String) -> Result<()> {
+    banking::disconnect(db.pool(), &id).await
+}
+
+/// Fetch every linked account now. Returns how many new transactions wait
+/// for confirmation.
+#[tauri::command]
+#[specta::specta]
+pub async fn fetch_bank_transactions(app: AppHandle, db: State<'_, Db>) -> Result<usize> {
+    banking::fetch_and_announce(&app, db.pool(), true).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_pending_bank_transactions(db: State<'_, Db>) -> Result<Vec<BankTransaction>> {
+    banking::pending(db.pool()).await
+}
+
+/// Log the given pending transactions as expenses.
+#[tauri::command]
+#[specta::specta]
+pub async fn confirm_bank_transactions(
+    app: AppHandle,
+    db: State<'_, Db>,
+    transactions: Vec<ConfirmTransaction>,
+) -> Result<ConfirmReport> {
+    let report = banking::confirm(db.pool(), &transactions).await?;
+    if report.imported > 0 {
+        events::publish(
+            &app,
+            &DomainEvent::ExpensesImported {
+                source: "bank".to_string(),
+                count: report.imported,
+            },
+        )?;
+    }
+    Ok(report)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn dismiss_bank_transactions(db: State<'_, Db>, ids: Vec<String>) -> Result<()> {
+    banking::dismiss(db.pool(), &ids).await
+}
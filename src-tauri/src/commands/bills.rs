@@ -0,0 +1,107 @@
+use chrono::Local;
+use tauri::{AppHandle, State};
+
+use crate::bills::{self, Bill, BillDue, BillPayment, MarkPaid, NewBill};
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+use crate::notify::platform;
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_bills(db: State<'_, Db>) -> Result<Vec<BillDue>> {
+    bills::list(db.pool(), Local::now().date_naive()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_bill_payments(db: State<'_, Db>, bill_id: String) -> Result<Vec<BillPayment>> {
+    bills::payments(db.pool(), &bill_id).await
+}
+
+/// Add a bill and queue the reminder for its first due date.
+#[tauri::command]
+#[specta::specta]
+pub async fn create_bill(app: AppHandle, db: State<'_, Db>, input: NewBill) -> Result<Bill> {
+    let user_id = crate::auth::current_user_id(db.pool()).await?;
+    let today = Local::now().date_naive();
+    let bill = bills::create(db.pool(), user_id.as_deref(), input, today).await?;
+    platform::register(&app, db.pool()).await?;
+    events::publish(
+        &app,
+        &DomainEvent::BillSaved {
+            bill_id: bill.id.clone(),
+        },
+    )?;
+    Ok(bill)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_bill(
+    app: AppHandle,
+    db: State<'_, Db>,
+    id: String,
+    input: NewBill,
+) -> Result<Bill> {
+    let today = Local::now().date_naive();
+    let bill = bills::update(db.pool(), &id, input, today).await?;
+    platform::register(&app, db.pool()).await?;
+    events::publish(&app, &DomainEvent::BillSaved { bill_id: id })?;
+    Ok(bill)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_bill(app: AppHandle, db: State<'_, Db>, id: String) -> Result<()> {
+    bills::delete(db.pool(), &id).await?;
+    platform::register(&app, db.pool()).await?;
+    events::publish(&app, &DomainEvent::BillDeleted { bill_id: id })
+}
+
+/// Mark a bill paid, optionally logging the payment as an expense.
+#[tauri::command]
+#[specta::specta]
+pub async fn mark_bill_paid(
+    app: AppHandle,
+    db: State<'_, Db>,
+    id: String,
+    input: MarkPaid,
+) -> Result<BillPayment> {
+    let today = Local::now().date_naive();
+    let (payment, expense) = bills::mark_paid(db.pool(), &id, input, today).await?;
+    platform::register(&app, db.pool()).await?;
+    if let Some(expense) = expense {
+        events::publish(
+            &app,
+            &DomainEvent::ExpenseCreated {
+                expense_id: expense.id,
+                category_id: expense.category_id,
+                amount: expense.amount,
+                date: expense.date,
+            },
+        )?;
+    }
+    events::publish(
+        &app,
+        &DomainEvent::BillPaid {
+            bill_id: id,
+            month: payment.month.clone(),
+        },
+    )?;
+    Ok(payment)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn unmark_bill_paid(
+    app: AppHandle,
+    db: State<'_, Db>,
+    id: String,
+    month: String,
+) -> Result<()> {
+    let today = Local::now().date_naive();
+    bills::unmark_paid(db.pool(), &id, &month, today).await?;
+    platform::register(&app, db.pool()).await?;
+    events::publish(&app, &DomainEvent::BillSaved { bill_id: id })
+}
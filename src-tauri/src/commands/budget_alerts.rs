@@ -0,0 +1,21 @@
+use tauri::State;
+
+use crate::budget_alerts;
+use crate::db::Db;
+use crate::error::Result;
+
+/// Percentages of the budget to warn at.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_budget_alert_thresholds(db: State<'_, Db>) -> Result<Vec<u32>> {
+    budget_alerts::thresholds(db.pool()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_budget_alert_thresholds(
+    db: State<'_, Db>,
+    thresholds: Vec<u32>,
+) -> Result<Vec<u32>> {
+    budget_alerts::set_thresholds(db.pool(), thresholds).await
+}
@@ -0,0 +1,170 @@
+use chrono::{Local, NaiveDate};
+use tauri::{AppHandle, State};
+
+use crate::budgets::{self, Budget, BudgetInput, BudgetStatus, BudgetTemplate};
+use crate::category_budgets::{self, CategoryBudget, EnvelopeSummary};
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+use crate::month_close::{self, MonthClose};
+use crate::periods::{self, PaySchedule, Period};
+
+const DEFAULT_CLOSE_HISTORY: i64 = 12;
+
+/// The current period's budget, if one was set.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_current_budget(db: State<'_, Db>) -> Result<Option<Budget>> {
+    budgets::current(db.pool(), Local::now().date_naive()).await
+}
+
+/// Set the current period's budget.
+#[tauri::command]
+#[specta::specta]
+pub async fn save_budget(app: AppHandle, db: State<'_, Db>, input: BudgetInput) -> Result<Budget> {
+    let budget = budgets::save_current(db.pool(), Local::now().date_naive(), input).await?;
+    events::publish(
+        &app,
+        &DomainEvent::BudgetUpdated {
+            budget_id: budget.id.clone(),
+            month: budget.month.clone(),
+        },
+    )?;
+    Ok(budget)
+}
+
+/// What new periods' budgets start from.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_budget_template(db: State<'_, Db>) -> Result<BudgetTemplate> {
+    budgets::template(db.pool()).await
+}
+
+/// Change what new periods' budgets start from.
+#[tauri::command]
+#[specta::specta]
+pub async fn save_budget_template(
+    app: AppHandle,
+    db: State<'_, Db>,
+    template: BudgetTemplate,
+) -> Result<()> {
+    budgets::save_template(db.pool(), &template).await?;
+    events::publish(&app, &DomainEvent::BudgetSettingsUpdated)
+}
+
+/// Final numbers of past months and pay periods, newest first.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_month_closes(db: State<'_, Db>, limit: Option<i64>) -> Result<Vec<MonthClose>> {
+    month_close::list(db.pool(), limit.unwrap_or(DEFAULT_CLOSE_HISTORY)).await
+}
+
+/// How budget periods line up with paydays.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_pay_schedule(db: State<'_, Db>) -> Result<PaySchedule> {
+    periods::schedule(db.pool()).await
+}
+
+/// Change how budget periods line up with paydays.
+#[tauri::command]
+#[specta::specta]
+pub async fn save_pay_schedule(
+    app: AppHandle,
+    db: State<'_, Db>,
+    schedule: PaySchedule,
+) -> Result<()> {
+    periods::save_schedule(db.pool(), &schedule).await?;
+    events::publish(&app, &DomainEvent::BudgetSettingsUpdated)
+}
+
+/// The budget period `date` falls in, today if not given.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_budget_period(db: State<'_, Db>, date: Option<NaiveDate>) -> Result<Period> {
+    let schedule = periods::schedule(db.pool()).await?;
+    Ok(schedule.period_at(date.unwrap_or_else(|| Local::now().date_naive())))
+}
+
+/// Key of the period `date` falls in, today if not given.
+async fn period_key(db: &Db, date: Option<NaiveDate>) -> Result<String> {
+    let schedule = periods::schedule(db.pool()).await?;
+    Ok(schedule
+        .period_at(date.unwrap_or_else(|| Local::now().date_naive()))
+        .key)
+}
+
+/// Category allocations for the period `date` falls in, today if not given.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_category_budgets(
+    db: State<'_, Db>,
+    date: Option<NaiveDate>,
+) -> Result<Vec<CategoryBudget>> {
+    let key = period_key(&db, date).await?;
+    category_budgets::list(db.pool(), &key).await
+}
+
+/// Set what a category may spend in the period `date` falls in.
+#[tauri::command]
+#[specta::specta]
+pub async fn allocate_category_budget(
+    app: AppHandle,
+    db: State<'_, Db>,
+    category_id: String,
+    amount: f64,
+    date: Option<NaiveDate>,
+) -> Result<CategoryBudget> {
+    let key = period_key(&db, date).await?;
+    let budget = category_budgets::allocate(db.pool(), &key, &category_id, amount).await?;
+    events::publish(
+        &app,
+        &DomainEvent::CategoryBudgetUpdated {
+            month: key,
+            category_id,
+            amount: Some(amount),
+        },
+    )?;
+    Ok(budget)
+}
+
+/// Drop a category's allocation for the period `date` falls in.
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_category_budget(
+    app: AppHandle,
+    db: State<'_, Db>,
+    category_id: String,
+    date: Option<NaiveDate>,
+) -> Result<()> {
+    let key = period_key(&db, date).await?;
+    category_budgets::remove(db.pool(), &key, &category_id).await?;
+    events::publish(
+        &app,
+        &DomainEvent::CategoryBudgetUpdated {
+            month: key,
+            category_id,
+            amount: None,
+        },
+    )
+}
+
+/// Spent against allocated per category for the period `date` falls in.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_category_envelopes(
+    db: State<'_, Db>,
+    date: Option<NaiveDate>,
+) -> Result<EnvelopeSummary> {
+    let schedule = periods::schedule(db.pool()).await?;
+    let date = date.unwrap_or_else(|| Local::now().date_naive());
+    category_budgets::envelopes(db.pool(), &schedule, date).await
+}
+
+/// Spending, safe-to-spend and forecast for the current budget period.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_budget_status(db: State<'_, Db>) -> Result<BudgetStatus> {
+    let schedule = periods::schedule(db.pool()).await?;
+    budgets::status(db.pool(), &schedule, Local::now().date_naive()).await
+}
@@ -0,0 +1,47 @@
+use tauri::{AppHandle, State};
+
+use crate::bulk::{self, BulkOperation, BulkResult, UndoResult};
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+
+/// Recategorize, delete, move or tag the given expenses in one go. The
+/// result carries a token to undo it with.
+#[tauri::command]
+#[specta::specta]
+pub async fn bulk_update_expenses(
+    app: AppHandle,
+    db: State<'_, Db>,
+    expense_ids: Vec<String>,
+    operation: BulkOperation,
+) -> Result<BulkResult> {
+    let result = bulk::apply(db.pool(), &expense_ids, &operation).await?;
+    if !result.expense_ids.is_empty() {
+        events::publish(
+            &app,
+            &DomainEvent::ExpensesBulkChanged {
+                expense_ids: result.expense_ids.clone(),
+            },
+        )?;
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn undo_bulk_update(
+    app: AppHandle,
+    db: State<'_, Db>,
+    undo_token: String,
+) -> Result<UndoResult> {
+    let result = bulk::undo(db.pool(), &undo_token).await?;
+    if !result.expense_ids.is_empty() {
+        events::publish(
+            &app,
+            &DomainEvent::ExpensesBulkChanged {
+                expense_ids: result.expense_ids.clone(),
+            },
+        )?;
+    }
+    Ok(result)
+}
@@ -0,0 +1,92 @@
+use tauri::{AppHandle, State};
+
+use crate::categorize::rules::{self, CategorizationRule, MatchField, NewRule};
+use crate::categorize::{self, CategorySuggestion};
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+
+/// Suggestions shown above the category picker.
+const SUGGESTION_COUNT: usize = 3;
+
+/// Rank likely categories for an expense being entered, learned from the
+/// user's own categorized history.
+#[tauri::command]
+#[specta::specta]
+pub async fn suggest_categories(
+    db: State<'_, Db>,
+    note: Option<String>,
+    amount: Option<f64>,
+) -> Result<Vec<CategorySuggestion>> {
+    categorize::suggest_categories(db.pool(), note.as_deref(), amount, SUGGESTION_COUNT).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_categorization_rules(db: State<'_, Db>) -> Result<Vec<CategorizationRule>> {
+    rules::list(db.pool()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn save_categorization_rule(
+    app: AppHandle,
+    db: State<'_, Db>,
+    rule: NewRule,
+) -> Result<CategorizationRule> {
+    let rule = rules::save(db.pool(), rule).await?;
+    events::publish(
+        &app,
+        &DomainEvent::CategorizationRuleSaved {
+            rule_id: rule.id.clone(),
+        },
+    )?;
+    Ok(rule)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_categorization_rule(
+    app: AppHandle,
+    db: State<'_, Db>,
+    id: String,
+) -> Result<()> {
+    rules::delete(db.pool(), &id).await?;
+    events::publish(
+        &app,
+        &DomainEvent::CategorizationRuleDeleted { rule_id: id },
+    )
+}
+
+/// "Always categorize this as ...": a rule from the expense's note or
+/// merchant, recategorizing the expense itself if `category_id` differs.
+#[tauri::command]
+#[specta::specta]
+pub async fn create_rule_from_expense(
+    app: AppHandle,
+    db: State<'_, Db>,
+    expense_id: String,
+    match_field: MatchField,
+    category_id: Option<String>,
+) -> Result<CategorizationRule> {
+    let (rule, moved) =
+        rules::from_expense(db.pool(), &expense_id, match_field, category_id).await?;
+    events::publish(
+        &app,
+        &DomainEvent::CategorizationRuleSaved {
+            rule_id: rule.id.clone(),
+        },
+    )?;
+    if let Some(expense) = moved {
+        events::publish(
+            &app,
+            &DomainEvent::ExpenseUpdated {
+                expense_id: expense.id,
+                category_id: expense.category_id,
+                amount: expense.amount,
+                date: expense.date,
+            },
+        )?;
+    }
+    Ok(rule)
+}
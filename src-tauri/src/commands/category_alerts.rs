@@ -0,0 +1,52 @@
+use chrono::Local;
+use tauri::{AppHandle, State};
+
+use crate::category_alerts::{self, CategoryAlert, CategoryAlertRule};
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_category_alert_rules(db: State<'_, Db>) -> Result<Vec<CategoryAlertRule>> {
+    category_alerts::list(db.pool()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn save_category_alert_rule(
+    app: AppHandle,
+    db: State<'_, Db>,
+    rule: CategoryAlertRule,
+) -> Result<CategoryAlertRule> {
+    let rule = category_alerts::save(db.pool(), rule).await?;
+    events::publish(
+        &app,
+        &DomainEvent::CategoryAlertSaved {
+            category_id: rule.category_id.clone(),
+        },
+    )?;
+    Ok(rule)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_category_alert_rule(
+    app: AppHandle,
+    db: State<'_, Db>,
+    category_id: String,
+) -> Result<()> {
+    category_alerts::remove(db.pool(), &category_id).await?;
+    events::publish(&app, &DomainEvent::CategoryAlertDeleted { category_id })
+}
+
+/// Called after an expense in `category_id` is written.
+#[tauri::command]
+#[specta::specta]
+pub async fn check_category_alerts(
+    app: AppHandle,
+    db: State<'_, Db>,
+    category_id: String,
+) -> Result<Option<CategoryAlert>> {
+    category_alerts::check(&app, db.pool(), &category_id, Local::now().date_naive()).await
+}
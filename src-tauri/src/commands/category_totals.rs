@@ -0,0 +1,28 @@
+use tauri::State;
+
+use crate::category_totals::{self, CategoryTotal, Cell};
+use crate::db::Db;
+use crate::error::Result;
+
+/// Called after expense writes with the cells before and after the change.
+#[tauri::command]
+#[specta::specta]
+pub async fn update_category_totals(db: State<'_, Db>, cells: Vec<Cell>) -> Result<()> {
+    category_totals::refresh(db.pool(), &cells).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn rebuild_category_totals(db: State<'_, Db>) -> Result<()> {
+    category_totals::rebuild(db.pool()).await
+}
+
+/// Cached spending per category for `month` ("YYYY-MM").
+#[tauri::command]
+#[specta::specta]
+pub async fn get_monthly_category_totals(
+    db: State<'_, Db>,
+    month: String,
+) -> Result<Vec<CategoryTotal>> {
+    category_totals::for_month(db.pool(), &month).await
+}
@@ -0,0 +1,23 @@
+use chrono::{Datelike, Local, NaiveDate};
+use tauri::State;
+
+use crate::checkins::{self, PendingCheckin};
+use crate::db::Db;
+use crate::error::{Error, Result};
+
+/// Savings goals and habits the check-in for `month` ("YYYY-MM", this month
+/// by default) still asks about, with suggested amounts.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_pending_checkins(
+    db: State<'_, Db>,
+    month: Option<String>,
+) -> Result<Vec<PendingCheckin>> {
+    let today = Local::now().date_naive();
+    let month = match month {
+        Some(month) => NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d")
+            .map_err(|_| Error::Validation(format!("\"{month}\" is not a YYYY-MM month")))?,
+        None => today.with_day(1).unwrap_or(today),
+    };
+    checkins::pending(db.pool(), month, today).await
+}
@@ -0,0 +1,26 @@
+use tauri::{AppHandle, Manager};
+
+use crate::connectivity::{self, Connectivity, NetworkType};
+use crate::sync::worker::SyncWorker;
+
+/// Probe the network now and report whether it is reachable.
+#[tauri::command]
+#[specta::specta]
+pub async fn is_online(app: AppHandle) -> bool {
+    connectivity::check(&app).await
+}
+
+/// Record whether the current connection is metered and, where the
+/// platform says, what type it is. Pushes right away once a connection
+/// turns unmetered, in case sync waited for one.
+#[tauri::command]
+#[specta::specta]
+pub fn set_metered_connection(app: AppHandle, metered: bool, network_type: Option<NetworkType>) {
+    let connectivity = app.state::<Connectivity>();
+    let was_unmetered = connectivity.is_unmetered();
+    connectivity.set_metered(metered);
+    connectivity.set_network_type(network_type.unwrap_or_default());
+    if !was_unmetered && connectivity.is_unmetered() {
+        app.state::<SyncWorker>().wake();
+    }
+}
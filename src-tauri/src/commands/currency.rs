@@ -0,0 +1,47 @@
+use chrono::{Local, NaiveDate};
+use tauri::{AppHandle, State};
+
+use crate::currency::{self, Currency, ExchangeRate};
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+
+/// Known currencies, the base currency first.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_currencies(db: State<'_, Db>) -> Result<Vec<Currency>> {
+    currency::list(db.pool()).await
+}
+
+/// Make `code` the currency amounts are kept and budgeted in.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_base_currency(
+    app: AppHandle,
+    db: State<'_, Db>,
+    code: String,
+) -> Result<Vec<Currency>> {
+    let currencies = currency::set_base(db.pool(), &code).await?;
+    events::publish(&app, &DomainEvent::BaseCurrencyChanged { code })?;
+    Ok(currencies)
+}
+
+/// The rate from `from` to `to` on `date` ("YYYY-MM-DD", today if not given).
+#[tauri::command]
+#[specta::specta]
+pub async fn get_exchange_rate(
+    db: State<'_, Db>,
+    from: String,
+    to: String,
+    date: Option<NaiveDate>,
+) -> Result<ExchangeRate> {
+    let date = date.unwrap_or_else(|| Local::now().date_naive());
+    currency::rate(db.pool(), &from, &to, date).await
+}
+
+/// Fetch today's rates now. Returns the day they were published.
+#[tauri::command]
+#[specta::specta]
+pub async fn refresh_exchange_rates(db: State<'_, Db>) -> Result<String> {
+    currency::fetch(db.pool(), None).await
+}
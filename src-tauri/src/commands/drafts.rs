@@ -0,0 +1,76 @@
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+
+use crate::db::Db;
+use crate::drafts::{self, ExpenseDraft, ReceiptDraft};
+use crate::error::{Error, Result};
+use crate::{merchants, ocr, speech};
+
+#[derive(Debug, Serialize, specta::Type)]
+pub struct VoiceExpenseDraft {
+    pub transcript: String,
+    pub draft: ExpenseDraft,
+}
+
+/// Parse typed or dictated text ("12.50 coffee yesterday") into a draft.
+#[tauri::command]
+#[specta::specta]
+pub async fn parse_expense_text(db: State<'_, Db>, text: String) -> Result<ExpenseDraft> {
+    if text.trim().is_empty() {
+        return Err(Error::Validation("Text is empty".to_string()));
+    }
+    let categories = drafts::load_categories(db.pool()).await?;
+    let today = chrono::Local::now().date_naive();
+    Ok(drafts::parse_expense_text(&text, &categories, today))
+}
+
+/// Transcribe a WAV voice note on-device and turn it into an expense draft.
+#[tauri::command]
+#[specta::specta]
+pub async fn voice_note_to_expense(
+    app: AppHandle,
+    db: State<'_, Db>,
+    audio: Vec<u8>,
+) -> Result<VoiceExpenseDraft> {
+    let model_path = app
+        .path()
+        .app_data_dir()?
+        .join("models")
+        .join(speech::MODEL_FILE);
+    let transcript =
+        tauri::async_runtime::spawn_blocking(move || speech::transcribe_wav(&model_path, &audio))
+            .await??;
+
+    let categories = drafts::load_categories(db.pool()).await?;
+    let today = chrono::Local::now().date_naive();
+    let draft = drafts::parse_expense_text(&transcript, &categories, today);
+
+    Ok(VoiceExpenseDraft { transcript, draft })
+}
+
+/// Run OCR on a receipt photo and propose an expense with per-field confidence.
+#[tauri::command]
+#[specta::specta]
+pub async fn scan_receipt(
+    app: AppHandle,
+    db: State<'_, Db>,
+    image: Vec<u8>,
+) -> Result<ReceiptDraft> {
+    let tessdata = app.path().app_data_dir()?.join("models").join("tessdata");
+    let recognized =
+        tauri::async_runtime::spawn_blocking(move || ocr::recognize(&image, &tessdata)).await??;
+
+    let categories = drafts::load_categories(db.pool()).await?;
+    let today = chrono::Local::now().date_naive();
+    let mut receipt =
+        drafts::parse_receipt(&recognized.text, recognized.confidence, &categories, today);
+
+    // Receipt headers are as noisy as bank statements.
+    if let Some(raw) = receipt.merchant.take() {
+        let corrections = merchants::load_corrections(db.pool()).await?;
+        let merchant = merchants::normalize(&raw, &corrections);
+        receipt.draft.note = Some(merchant.clone());
+        receipt.merchant = Some(merchant);
+    }
+    Ok(receipt)
+}
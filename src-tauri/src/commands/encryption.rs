@@ -0,0 +1,26 @@
+use tauri::{AppHandle, State};
+
+use crate::db::{self, Db};
+use crate::encryption::{self, EncryptionStatus};
+use crate::error::Result;
+
+/// Whether the database is encrypted, and whether this build can do it.
+#[tauri::command]
+#[specta::specta]
+pub fn get_encryption_status(app: AppHandle) -> Result<EncryptionStatus> {
+    encryption::status(&db::path(&app)?)
+}
+
+/// Encrypt the database and restart into it. With a passphrase the key is
+/// derived from it; otherwise a random key is kept in the keychain.
+#[tauri::command]
+#[specta::specta]
+pub async fn enable_database_encryption(
+    app: AppHandle,
+    db: State<'_, Db>,
+    passphrase: Option<String>,
+) -> Result<EncryptionStatus> {
+    let status = encryption::enable(db.pool(), &db::path(&app)?, passphrase.as_deref()).await?;
+    app.request_restart();
+    Ok(status)
+}
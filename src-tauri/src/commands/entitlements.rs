@@ -0,0 +1,35 @@
+use chrono::Utc;
+use tauri::{AppHandle, State};
+
+use crate::db::Db;
+use crate::entitlements::license::Receipt;
+use crate::entitlements::{self, Entitlements, Feature};
+use crate::error::Result;
+
+/// Plan and premium features of the signed-in account.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_entitlements(db: State<'_, Db>) -> Result<Entitlements> {
+    entitlements::get(db.pool(), Utc::now()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn has_entitlement(db: State<'_, Db>, feature: Feature) -> Result<bool> {
+    Ok(entitlements::get(db.pool(), Utc::now())
+        .await?
+        .allows(feature))
+}
+
+/// Validate a purchase with the license server, or restore purchases
+/// when `receipt` is omitted.
+#[tauri::command]
+#[specta::specta]
+pub async fn verify_purchase(
+    app: AppHandle,
+    db: State<'_, Db>,
+    receipt: Option<Receipt>,
+) -> Result<Entitlements> {
+    entitlements::refresh(&app, db.pool(), receipt).await?;
+    entitlements::get(db.pool(), Utc::now()).await
+}
@@ -0,0 +1,11 @@
+use tauri::AppHandle;
+
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+
+/// Broadcast a write made by the frontend to every window.
+#[tauri::command]
+#[specta::specta]
+pub fn publish_domain_event(app: AppHandle, event: DomainEvent) -> Result<()> {
+    events::publish(&app, &event)
+}
@@ -0,0 +1,79 @@
+use chrono::Local;
+use tauri::{AppHandle, State};
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+use crate::expenses::{self, Expense, ExpenseUpdate, ExpenseWithCategory, NewExpense};
+
+const DEFAULT_RECENT_LIMIT: i64 = 10;
+
+/// Expenses dated between `start_date` and `end_date`, with their category.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_expenses(
+    db: State<'_, Db>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<ExpenseWithCategory>> {
+    expenses::list(db.pool(), &start_date, &end_date).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_recent_expenses(
+    db: State<'_, Db>,
+    limit: Option<i64>,
+) -> Result<Vec<ExpenseWithCategory>> {
+    expenses::recent(db.pool(), limit.unwrap_or(DEFAULT_RECENT_LIMIT)).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn create_expense(
+    app: AppHandle,
+    db: State<'_, Db>,
+    input: NewExpense,
+) -> Result<Expense> {
+    let expense = expenses::create(db.pool(), input, Local::now().date_naive()).await?;
+    events::publish(
+        &app,
+        &DomainEvent::ExpenseCreated {
+            expense_id: expense.id.clone(),
+            category_id: expense.category_id.clone(),
+            amount: expense.amount,
+            date: expense.date.clone(),
+        },
+    )?;
+    expenses::check_alerts(&app, db.pool(), expense.category_id.as_deref()).await;
+    Ok(expense)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_expense(
+    app: AppHandle,
+    db: State<'_, Db>,
+    id: String,
+    update: ExpenseUpdate,
+) -> Result<Expense> {
+    let expense = expenses::update(db.pool(), &id, update).await?;
+    events::publish(
+        &app,
+        &DomainEvent::ExpenseUpdated {
+            expense_id: expense.id.clone(),
+            category_id: expense.category_id.clone(),
+            amount: expense.amount,
+            date: expense.date.clone(),
+        },
+    )?;
+    expenses::check_alerts(&app, db.pool(), expense.category_id.as_deref()).await;
+    Ok(expense)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_expense(app: AppHandle, db: State<'_, Db>, id: String) -> Result<()> {
+    expenses::delete(db.pool(), &id).await?;
+    events::publish(&app, &DomainEvent::ExpenseDeleted { expense_id: id })
+}
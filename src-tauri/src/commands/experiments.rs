@@ -0,0 +1,19 @@
+use tauri::State;
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::experiments::{self, Assignment};
+
+/// This install's variant of `experiment`, or `None` if it isn't running.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_variant(db: State<'_, Db>, experiment: String) -> Result<Option<Assignment>> {
+    experiments::get_variant(db.pool(), &experiment).await
+}
+
+/// Record that the user was shown `experiment`.
+#[tauri::command]
+#[specta::specta]
+pub async fn log_experiment_exposure(db: State<'_, Db>, experiment: String) -> Result<()> {
+    experiments::log_exposure(db.pool(), &experiment).await
+}
@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+
+use chrono::Local;
+use tauri::{AppHandle, State};
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::export::{self, ical, ledger, snapshot, xlsx};
+
+/// Write a privacy-filtered HTML/JSON snapshot to the downloads folder.
+#[tauri::command]
+#[specta::specta]
+pub async fn export_snapshot(
+    app: AppHandle,
+    db: State<'_, Db>,
+    range: snapshot::DateRange,
+    privacy: snapshot::Privacy,
+) -> Result<snapshot::SnapshotFiles> {
+    let data = snapshot::build(db.pool(), &range, privacy).await?;
+    snapshot::write(&data, &export::output_dir(&app)?)
+}
+
+/// Every category, income source and account with the account name it is
+/// exported under.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_ledger_account_mapping(db: State<'_, Db>) -> Result<ledger::AccountMapping> {
+    ledger::resolved_mapping(db.pool()).await
+}
+
+/// Write a ledger-cli or beancount file to the downloads folder. A given
+/// mapping is remembered for the next export.
+#[tauri::command]
+#[specta::specta]
+pub async fn export_ledger(
+    app: AppHandle,
+    db: State<'_, Db>,
+    format: ledger::LedgerFormat,
+    range: snapshot::DateRange,
+    mapping: Option<ledger::AccountMapping>,
+) -> Result<ledger::LedgerExport> {
+    ledger::export(
+        db.pool(),
+        &export::output_dir(&app)?,
+        format,
+        &range,
+        mapping,
+    )
+    .await
+}
+
+/// Write an Excel workbook of expenses, budgets, goals and spending per
+/// category to the downloads folder.
+#[tauri::command]
+#[specta::specta]
+pub async fn export_xlsx(
+    app: AppHandle,
+    db: State<'_, Db>,
+    range: snapshot::DateRange,
+) -> Result<PathBuf> {
+    xlsx::export(db.pool(), &export::output_dir(&app)?, &range).await
+}
+
+/// Write check-ins, bill due dates and goal target dates to an .ics file in
+/// the downloads folder.
+#[tauri::command]
+#[specta::specta]
+pub async fn export_ical(app: AppHandle, db: State<'_, Db>) -> Result<PathBuf> {
+    let today = Local::now().date_naive();
+    ical::export(db.pool(), &export::output_dir(&app)?, today).await
+}
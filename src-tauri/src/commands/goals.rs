@@ -0,0 +1,133 @@
+use chrono::Local;
+use tauri::{AppHandle, State};
+
+use crate::analysis::funding::{self, FundingForecast};
+use crate::analysis::projection::{self, GoalForecast};
+use crate::db::Db;
+use crate::error::{Error, Result};
+use crate::events::{self, DomainEvent};
+use crate::goals::{self, Contribution, ContributionInput, GoalUpdate, NewGoal, SavingsGoal};
+use crate::webhooks;
+
+const DEFAULT_SURPLUS_MONTHS: u32 = 12;
+
+/// What past months left over for savings, and which goals ask for more
+/// than that usually allows.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_goal_funding_forecast(
+    db: State<'_, Db>,
+    months: Option<u32>,
+) -> Result<FundingForecast> {
+    funding::forecast(
+        db.pool(),
+        months.unwrap_or(DEFAULT_SURPLUS_MONTHS),
+        Local::now().date_naive(),
+    )
+    .await
+}
+
+/// Completion date, the contribution needed to finish on time and how
+/// smaller contributions would play out. `interest_rate` is annual, as a
+/// fraction.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_goal_forecast(
+    db: State<'_, Db>,
+    goal_id: String,
+    interest_rate: Option<f64>,
+) -> Result<GoalForecast> {
+    let goal = goals::get(db.pool(), &goal_id)
+        .await?
+        .ok_or_else(|| Error::Validation("Goal not found".to_string()))?;
+    let total_saved = goals::total_saved(db.pool(), &goal.id).await?;
+    projection::forecast(&goal, total_saved, interest_rate, Local::now().date_naive())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_savings_goals(db: State<'_, Db>) -> Result<Vec<SavingsGoal>> {
+    goals::list(db.pool()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_savings_goal(db: State<'_, Db>, id: String) -> Result<Option<SavingsGoal>> {
+    goals::get(db.pool(), &id).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn create_savings_goal(
+    app: AppHandle,
+    db: State<'_, Db>,
+    input: NewGoal,
+) -> Result<SavingsGoal> {
+    let goal = goals::create(db.pool(), input).await?;
+    events::publish(
+        &app,
+        &DomainEvent::GoalCreated {
+            goal_id: goal.id.clone(),
+        },
+    )?;
+    Ok(goal)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_savings_goal(
+    app: AppHandle,
+    db: State<'_, Db>,
+    id: String,
+    update: GoalUpdate,
+) -> Result<SavingsGoal> {
+    let goal = goals::update(db.pool(), &id, update).await?;
+    events::publish(
+        &app,
+        &DomainEvent::GoalUpdated {
+            goal_id: goal.id.clone(),
+        },
+    )?;
+    Ok(goal)
+}
+
+/// Move a goal and its contributions to the trash.
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_savings_goal(app: AppHandle, db: State<'_, Db>, id: String) -> Result<()> {
+    goals::delete(db.pool(), &id).await?;
+    events::publish(&app, &DomainEvent::GoalDeleted { goal_id: id })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_goal_contributions(
+    db: State<'_, Db>,
+    goal_id: String,
+) -> Result<Vec<Contribution>> {
+    goals::contributions(db.pool(), &goal_id).await
+}
+
+/// Record a month's contribution to a goal, replacing any already there.
+#[tauri::command]
+#[specta::specta]
+pub async fn save_goal_contribution(
+    app: AppHandle,
+    db: State<'_, Db>,
+    goal_id: String,
+    input: ContributionInput,
+) -> Result<Contribution> {
+    let saved_before = goals::total_saved(db.pool(), &goal_id).await?;
+    let contribution = goals::save_contribution(db.pool(), &goal_id, input).await?;
+    events::publish(
+        &app,
+        &DomainEvent::ContributionAdded {
+            goal_id: contribution.goal_id.clone(),
+            contribution_id: contribution.id.clone(),
+            month: contribution.month.clone(),
+            amount: contribution.amount,
+        },
+    )?;
+    webhooks::check_goal_milestone(db.pool(), &goal_id, saved_before).await;
+    Ok(contribution)
+}
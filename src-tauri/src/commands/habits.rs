@@ -0,0 +1,13 @@
+use chrono::Local;
+use tauri::State;
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::habits::streaks::{self, HabitStreak};
+
+/// Month and week streaks of every active habit, brought up to date.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_habit_streaks(db: State<'_, Db>) -> Result<Vec<HabitStreak>> {
+    streaks::refresh(db.pool(), Local::now().date_naive()).await
+}
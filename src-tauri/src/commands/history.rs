@@ -0,0 +1,16 @@
+use tauri::State;
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::history::{self, TimelineEntry};
+
+/// Edits, sync events and conflict resolutions for one record, oldest first.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_change_timeline(
+    db: State<'_, Db>,
+    table: String,
+    id: String,
+) -> Result<Vec<TimelineEntry>> {
+    history::timeline(db.pool(), &table, &id).await
+}
@@ -0,0 +1,82 @@
+use tauri::{AppHandle, State};
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+use crate::households::{self, Household, HouseholdBudget, HouseholdExpense, MemberSpending};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_households(db: State<'_, Db>) -> Result<Vec<Household>> {
+    households::list(db.pool()).await
+}
+
+/// Share an expense with a household, or with no household stop sharing it.
+#[tauri::command]
+#[specta::specta]
+pub async fn share_expense_with_household(
+    app: AppHandle,
+    db: State<'_, Db>,
+    expense_id: String,
+    household_id: Option<String>,
+) -> Result<()> {
+    households::share_expense(db.pool(), &expense_id, household_id.as_deref()).await?;
+    events::publish(
+        &app,
+        &DomainEvent::HouseholdShareChanged {
+            record_id: expense_id,
+            household_id,
+        },
+    )
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn share_budget_with_household(
+    app: AppHandle,
+    db: State<'_, Db>,
+    budget_id: String,
+    household_id: Option<String>,
+) -> Result<()> {
+    households::share_budget(db.pool(), &budget_id, household_id.as_deref()).await?;
+    events::publish(
+        &app,
+        &DomainEvent::HouseholdShareChanged {
+            record_id: budget_id,
+            household_id,
+        },
+    )
+}
+
+/// Every member's shared expenses, each with its owner.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_household_expenses(
+    db: State<'_, Db>,
+    household_id: String,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<HouseholdExpense>> {
+    households::ledger(db.pool(), &household_id, &start_date, &end_date).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_household_spending_by_member(
+    db: State<'_, Db>,
+    household_id: String,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<MemberSpending>> {
+    households::spending_by_member(db.pool(), &household_id, &start_date, &end_date).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_household_budgets(
+    db: State<'_, Db>,
+    household_id: String,
+    month: String,
+) -> Result<Vec<HouseholdBudget>> {
+    households::budgets(db.pool(), &household_id, &month).await
+}
@@ -0,0 +1,97 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, State};
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+use crate::import::apps::{self, AppExportPreview, AppFormat, AppImportOptions};
+use crate::import::csv::{self, ColumnMapping};
+use crate::import::{self, ImportReport, StatementFormat, StatementOptions};
+
+/// Import spending from a CSV file with the given column mapping. With
+/// `dry_run` nothing is written and the report is a preview.
+#[tauri::command]
+#[specta::specta]
+pub async fn import_csv(
+    app: AppHandle,
+    db: State<'_, Db>,
+    path: PathBuf,
+    mapping: ColumnMapping,
+    dry_run: bool,
+) -> Result<ImportReport> {
+    let report = csv::import(db.pool(), &path, &mapping, dry_run).await?;
+    if !report.dry_run && report.imported > 0 {
+        events::publish(
+            &app,
+            &DomainEvent::ExpensesImported {
+                source: "csv".to_string(),
+                count: report.imported,
+            },
+        )?;
+    }
+    Ok(report)
+}
+
+/// Import spending from an OFX or QIF bank statement, detecting which if
+/// `format` is not given. With `dry_run` nothing is written.
+#[tauri::command]
+#[specta::specta]
+pub async fn import_bank_file(
+    app: AppHandle,
+    db: State<'_, Db>,
+    path: PathBuf,
+    format: Option<StatementFormat>,
+    options: StatementOptions,
+    dry_run: bool,
+) -> Result<ImportReport> {
+    let (format, report) =
+        import::import_statement(db.pool(), &path, format, &options, dry_run).await?;
+    if !report.dry_run && report.imported > 0 {
+        events::publish(
+            &app,
+            &DomainEvent::ExpensesImported {
+                source: format.as_str().to_string(),
+                count: report.imported,
+            },
+        )?;
+    }
+    Ok(report)
+}
+
+/// Read a YNAB or Mint export and list its categories with suggested
+/// matches, for the mapping step before importing.
+#[tauri::command]
+#[specta::specta]
+pub async fn preview_app_export(
+    db: State<'_, Db>,
+    path: PathBuf,
+    format: Option<AppFormat>,
+    date_format: Option<String>,
+) -> Result<AppExportPreview> {
+    apps::preview(db.pool(), &path, format, date_format.as_deref()).await
+}
+
+/// Import spending from a YNAB or Mint export with its categories mapped.
+/// With `dry_run` nothing is written.
+#[tauri::command]
+#[specta::specta]
+pub async fn import_app_export(
+    app: AppHandle,
+    db: State<'_, Db>,
+    path: PathBuf,
+    options: AppImportOptions,
+    dry_run: bool,
+) -> Result<ImportReport> {
+    let (format, report) = apps::import(db.pool(), &path, &options, dry_run).await?;
+    if !report.dry_run && report.imported > 0 {
+        events::publish(
+            &app,
+            &DomainEvent::ExpensesImported {
+                source: format.as_str().to_string(),
+                count: report.imported,
+            },
+        )?;
+    }
+    Ok(report)
+}
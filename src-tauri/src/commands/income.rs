@@ -0,0 +1,90 @@
+use chrono::Local;
+use tauri::{AppHandle, State};
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+use crate::expenses::parse_date;
+use crate::income::{
+    self, IncomeEntry, IncomeSource, IncomeVariance, NewIncomeEntry, NewIncomeSource,
+};
+use crate::periods;
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_income_sources(db: State<'_, Db>) -> Result<Vec<IncomeSource>> {
+    income::list_sources(db.pool()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn create_income_source(
+    app: AppHandle,
+    db: State<'_, Db>,
+    input: NewIncomeSource,
+) -> Result<IncomeSource> {
+    let user_id = crate::auth::current_user_id(db.pool()).await?;
+    let source = income::create_source(db.pool(), user_id.as_deref(), input).await?;
+    events::publish(
+        &app,
+        &DomainEvent::IncomeSourceCreated {
+            source_id: source.id.clone(),
+        },
+    )?;
+    Ok(source)
+}
+
+/// Stop expecting income from a source; its logged income is kept.
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_income_source(app: AppHandle, db: State<'_, Db>, id: String) -> Result<()> {
+    income::delete_source(db.pool(), &id).await?;
+    events::publish(&app, &DomainEvent::IncomeSourceDeleted { source_id: id })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_income_entries(
+    db: State<'_, Db>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<IncomeEntry>> {
+    income::entries(db.pool(), parse_date(&start_date)?, parse_date(&end_date)?).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_income_entry(app: AppHandle, db: State<'_, Db>, id: String) -> Result<()> {
+    income::delete_entry(db.pool(), &id).await?;
+    events::publish(&app, &DomainEvent::IncomeEntryDeleted { entry_id: id })
+}
+
+/// Record money received from a source.
+#[tauri::command]
+#[specta::specta]
+pub async fn log_income(
+    app: AppHandle,
+    db: State<'_, Db>,
+    input: NewIncomeEntry,
+) -> Result<IncomeEntry> {
+    let user_id = crate::auth::current_user_id(db.pool()).await?;
+    let entry = income::log(db.pool(), user_id.as_deref(), input).await?;
+    events::publish(
+        &app,
+        &DomainEvent::IncomeLogged {
+            entry_id: entry.id.clone(),
+            source_id: entry.source_id.clone(),
+            amount: entry.amount,
+            date: entry.date.clone(),
+        },
+    )?;
+    Ok(entry)
+}
+
+/// Expected against received income per source this budget period.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_income_variance(db: State<'_, Db>) -> Result<Vec<IncomeVariance>> {
+    let schedule = periods::schedule(db.pool()).await?;
+    income::variance(db.pool(), &schedule, Local::now().date_naive()).await
+}
@@ -0,0 +1,73 @@
+use tauri::{AppHandle, State};
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+use crate::merchants::{self, Merchant, MerchantMonth, MerchantStats};
+
+/// Clean merchant name for a raw bank description, honoring corrections.
+#[tauri::command]
+#[specta::specta]
+pub async fn normalize_merchant(db: State<'_, Db>, raw: String) -> Result<String> {
+    let corrections = merchants::load_corrections(db.pool()).await?;
+    Ok(merchants::normalize(&raw, &corrections))
+}
+
+/// Store a user correction; returns the raw key it was saved under.
+#[tauri::command]
+#[specta::specta]
+pub async fn save_merchant_correction(
+    db: State<'_, Db>,
+    raw: String,
+    merchant_name: String,
+) -> Result<String> {
+    merchants::save_correction(db.pool(), &raw, &merchant_name).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_merchant_stats(
+    db: State<'_, Db>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<MerchantStats>> {
+    merchants::merchant_stats(db.pool(), &start_date, &end_date).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_merchants(db: State<'_, Db>) -> Result<Vec<Merchant>> {
+    merchants::list(db.pool()).await
+}
+
+/// Rename a merchant, merging it into another of the same name; returns
+/// the id it ends up with.
+#[tauri::command]
+#[specta::specta]
+pub async fn rename_merchant(
+    app: AppHandle,
+    db: State<'_, Db>,
+    id: String,
+    name: String,
+) -> Result<String> {
+    let merchant_id = merchants::rename(db.pool(), &id, &name).await?;
+    events::publish(
+        &app,
+        &DomainEvent::MerchantRenamed {
+            previous_id: id,
+            merchant_id: merchant_id.clone(),
+        },
+    )?;
+    Ok(merchant_id)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_merchant_monthly_spending(
+    db: State<'_, Db>,
+    merchant_id: String,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<MerchantMonth>> {
+    merchants::monthly_spending(db.pool(), &merchant_id, &start_date, &end_date).await
+}
@@ -0,0 +1,55 @@
+//! Tauri command handlers, grouped by feature.
+//!
+//! Handlers stay thin: they pull managed state, call into the feature module
+//! and return typed results. Everything is registered in `lib.rs`.
+
+pub mod accounts;
+pub mod app_meta;
+pub mod archive;
+pub mod attachments;
+pub mod automation;
+pub mod backend;
+pub mod backup;
+pub mod banking;
+pub mod bills;
+pub mod budget_alerts;
+pub mod budgets;
+pub mod bulk;
+pub mod categorize;
+pub mod category_alerts;
+pub mod category_totals;
+pub mod checkins;
+pub mod connectivity;
+pub mod currency;
+pub mod drafts;
+pub mod encryption;
+pub mod entitlements;
+pub mod events;
+pub mod expenses;
+pub mod experiments;
+pub mod export;
+pub mod goals;
+pub mod habits;
+pub mod history;
+pub mod households;
+pub mod import;
+pub mod income;
+pub mod merchants;
+pub mod net_worth;
+pub mod notify;
+pub mod preferences;
+pub mod quick_add;
+pub mod reconcile;
+pub mod recurring;
+pub mod referrals;
+pub mod reimbursements;
+pub mod reports;
+pub mod search;
+pub mod spending;
+pub mod splits;
+pub mod sync;
+pub mod tags;
+pub mod transfer;
+pub mod trash;
+pub mod trials;
+pub mod webhooks;
@@ -0,0 +1,51 @@
+use chrono::Local;
+use tauri::{AppHandle, State};
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+use crate::net_worth::{self, AccountBalance, NetWorthPoint, NewAccountBalance};
+
+const DEFAULT_HISTORY_MONTHS: u32 = 12;
+
+/// Enter an account's balance on a day.
+#[tauri::command]
+#[specta::specta]
+pub async fn record_account_balance(
+    app: AppHandle,
+    db: State<'_, Db>,
+    input: NewAccountBalance,
+) -> Result<()> {
+    let user_id = crate::auth::current_user_id(db.pool()).await?;
+    let account_name = input.account_name.clone();
+    net_worth::record(
+        db.pool(),
+        user_id.as_deref(),
+        input,
+        Local::now().date_naive(),
+    )
+    .await?;
+    events::publish(&app, &DomainEvent::BalanceRecorded { account_name })
+}
+
+/// Every account with its latest balance.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_account_balances(db: State<'_, Db>) -> Result<Vec<AccountBalance>> {
+    net_worth::latest(db.pool()).await
+}
+
+/// Month-end net worth, oldest first, ending with today.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_net_worth_history(
+    db: State<'_, Db>,
+    months: Option<u32>,
+) -> Result<Vec<NetWorthPoint>> {
+    net_worth::history(
+        db.pool(),
+        months.unwrap_or(DEFAULT_HISTORY_MONTHS),
+        Local::now().date_naive(),
+    )
+    .await
+}
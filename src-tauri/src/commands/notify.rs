@@ -0,0 +1,29 @@
+use tauri::{AppHandle, State};
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::notify::{platform, scheduler};
+
+/// Hand the next pending notifications to the OS again after the queue
+/// changed. A no-op off mobile.
+#[tauri::command]
+#[specta::specta]
+pub async fn register_platform_notifications(app: AppHandle, db: State<'_, Db>) -> Result<usize> {
+    platform::register(&app, db.pool()).await
+}
+
+/// Line the recurring notifications up with the preferences.
+#[tauri::command]
+#[specta::specta]
+pub async fn reschedule_notifications(app: AppHandle, db: State<'_, Db>) -> Result<()> {
+    scheduler::reschedule(db.pool()).await?;
+    platform::register(&app, db.pool()).await?;
+    Ok(())
+}
+
+/// Show the notifications that are due now.
+#[tauri::command]
+#[specta::specta]
+pub async fn dispatch_due_notifications(app: AppHandle, db: State<'_, Db>) -> Result<usize> {
+    scheduler::dispatch(&app, db.pool()).await
+}
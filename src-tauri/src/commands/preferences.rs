@@ -0,0 +1,48 @@
+use tauri::{AppHandle, State};
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+use crate::notify::{platform, scheduler};
+use crate::preferences::{
+    self, AuthState, AuthStateUpdate, NotificationPreferences, NotificationPreferencesUpdate,
+};
+
+/// The stored session row, created empty if missing.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_or_create_auth_state(db: State<'_, Db>) -> Result<AuthState> {
+    preferences::get_or_create_auth_state(db.pool()).await
+}
+
+/// Replace the stored session; all fields empty signs out.
+#[tauri::command]
+#[specta::specta]
+pub async fn update_auth_state(db: State<'_, Db>, update: AuthStateUpdate) -> Result<AuthState> {
+    preferences::update_auth_state(db.pool(), update).await
+}
+
+/// Notification preferences, created with the defaults if missing.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_or_create_notification_preferences(
+    db: State<'_, Db>,
+) -> Result<NotificationPreferences> {
+    preferences::get_or_create_notification_preferences(db.pool()).await
+}
+
+/// Change some notification preferences, queue them for sync and move the
+/// recurring notifications, here and with the OS, to the new schedule.
+#[tauri::command]
+#[specta::specta]
+pub async fn update_notification_preferences(
+    app: AppHandle,
+    db: State<'_, Db>,
+    update: NotificationPreferencesUpdate,
+) -> Result<NotificationPreferences> {
+    let preferences = preferences::update_notification_preferences(db.pool(), update).await?;
+    scheduler::reschedule(db.pool()).await?;
+    platform::register(&app, db.pool()).await?;
+    events::publish(&app, &DomainEvent::NotificationPreferencesUpdated)?;
+    Ok(preferences)
+}
@@ -0,0 +1,80 @@
+use tauri::{AppHandle, State};
+
+use crate::db::Db;
+use crate::drafts::ExpenseDraft;
+use crate::error::Result;
+use crate::expenses::{Expense, NewExpense};
+use crate::quick_add::{self, window, QuickAdd};
+
+/// Whether complete quick-add links are saved without opening the form.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_quick_add_auto_save(db: State<'_, Db>) -> Result<bool> {
+    quick_add::auto_save(db.pool()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_quick_add_auto_save(db: State<'_, Db>, auto_save: bool) -> Result<()> {
+    quick_add::set_auto_save(db.pool(), auto_save).await
+}
+
+/// The draft of the link the app was opened with, once. Check on startup,
+/// as `quick-add://draft` may have fired before anyone listened.
+#[tauri::command]
+#[specta::specta]
+pub fn take_quick_add_draft(quick_add: State<'_, QuickAdd>) -> Option<ExpenseDraft> {
+    quick_add.take_pending()
+}
+
+/// Handle text shared from another app like a quick-add link. Returns the
+/// expense if it was saved rather than opened in the form.
+#[tauri::command]
+#[specta::specta]
+pub async fn quick_add_shared_text(app: AppHandle, text: String) -> Result<Option<Expense>> {
+    quick_add::handle_text(&app, &text).await
+}
+
+/// Save the expense entered in the quick-add window and hide it.
+#[tauri::command]
+#[specta::specta]
+pub async fn quick_add_expense(
+    app: AppHandle,
+    db: State<'_, Db>,
+    input: NewExpense,
+) -> Result<Expense> {
+    let expense = quick_add::save(&app, db.pool(), input).await?;
+    window::hide(&app)?;
+    Ok(expense)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn open_quick_add_window(app: AppHandle) -> Result<()> {
+    window::show(&app)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn close_quick_add_window(app: AppHandle) -> Result<()> {
+    window::hide(&app)
+}
+
+/// The global shortcut that opens the quick-add window.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_quick_add_shortcut(db: State<'_, Db>) -> Result<String> {
+    window::shortcut(db.pool()).await
+}
+
+/// Change the shortcut, e.g. to `Alt+Space`. Fails if the combination is
+/// invalid or another app holds it.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_quick_add_shortcut(
+    app: AppHandle,
+    db: State<'_, Db>,
+    shortcut: String,
+) -> Result<String> {
+    window::set_shortcut(&app, db.pool(), &shortcut).await
+}
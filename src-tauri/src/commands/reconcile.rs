@@ -0,0 +1,27 @@
+use tauri::State;
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::reconcile::{self, ReconciliationReport, StatementImport, StatementRange};
+
+/// Import a bank statement CSV export for reconciliation.
+#[tauri::command]
+#[specta::specta]
+pub async fn import_statement(
+    db: State<'_, Db>,
+    name: String,
+    content: String,
+) -> Result<StatementImport> {
+    reconcile::import(db.pool(), &name, &content).await
+}
+
+/// Match a statement's lines to expenses and report what is unmatched.
+#[tauri::command]
+#[specta::specta]
+pub async fn reconcile_statement(
+    db: State<'_, Db>,
+    import_id: String,
+    range: Option<StatementRange>,
+) -> Result<ReconciliationReport> {
+    reconcile::reconcile(db.pool(), &import_id, range).await
+}
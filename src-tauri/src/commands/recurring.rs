@@ -0,0 +1,112 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, State};
+
+use crate::analysis::price_changes::{self, PriceIncrease};
+use crate::analysis::recurring::{self, RecurringCandidate};
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+use crate::recurring::detected::{self, DetectedSubscription};
+use crate::recurring::ics::{self, IcsImport};
+use crate::recurring::{NewRecurringExpense, RecurringExpense};
+
+/// Repeating charges found in the expense history that could become
+/// recurring-expense entries.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_recurring_candidates(db: State<'_, Db>) -> Result<Vec<RecurringCandidate>> {
+    let today = chrono::Local::now().date_naive();
+    recurring::find_candidates(db.pool(), today).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_recurring_expenses(db: State<'_, Db>) -> Result<Vec<RecurringExpense>> {
+    crate::recurring::list(db.pool()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn create_recurring_expense(
+    app: AppHandle,
+    db: State<'_, Db>,
+    input: NewRecurringExpense,
+) -> Result<RecurringExpense> {
+    let user_id = crate::auth::current_user_id(db.pool()).await?;
+    let recurring = crate::recurring::create(db.pool(), user_id.as_deref(), input).await?;
+    events::publish(
+        &app,
+        &DomainEvent::RecurringCreated {
+            recurring_ids: vec![recurring.id.clone()],
+        },
+    )?;
+    Ok(recurring)
+}
+
+/// Subscriptions found by the background scan and not yet tracked or
+/// dismissed.
+#[tauri::command]
+#[specta::specta]
+pub async fn detected_subscriptions(db: State<'_, Db>) -> Result<Vec<DetectedSubscription>> {
+    detected::list(db.pool()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn dismiss_detected_subscription(db: State<'_, Db>, match_key: String) -> Result<()> {
+    detected::dismiss(db.pool(), &match_key).await
+}
+
+/// Track a detected subscription as a recurring expense with renewal
+/// reminders.
+#[tauri::command]
+#[specta::specta]
+pub async fn track_detected_subscription(
+    app: AppHandle,
+    db: State<'_, Db>,
+    match_key: String,
+    remind_days_before: Option<i64>,
+) -> Result<RecurringExpense> {
+    let user_id = crate::auth::current_user_id(db.pool()).await?;
+    let recurring = detected::track(
+        db.pool(),
+        user_id.as_deref(),
+        &match_key,
+        remind_days_before,
+    )
+    .await?;
+    events::publish(
+        &app,
+        &DomainEvent::RecurringCreated {
+            recurring_ids: vec![recurring.id.clone()],
+        },
+    )?;
+    Ok(recurring)
+}
+
+/// Subscriptions whose latest charge is a price increase.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_price_increases(db: State<'_, Db>) -> Result<Vec<PriceIncrease>> {
+    let today = chrono::Local::now().date_naive();
+    price_changes::find_increases(db.pool(), today).await
+}
+
+/// Create recurring expenses from the repeating events in an ICS calendar.
+#[tauri::command]
+#[specta::specta]
+pub async fn import_ics(app: AppHandle, db: State<'_, Db>, path: PathBuf) -> Result<IcsImport> {
+    let user_id = crate::auth::current_user_id(db.pool()).await?;
+    let today = chrono::Local::now().date_naive();
+    let import = ics::import(db.pool(), user_id.as_deref(), &path, today).await?;
+    if !import.created.is_empty() {
+        events::publish(
+            &app,
+            &DomainEvent::RecurringCreated {
+                recurring_ids: import.created.iter().map(|r| r.id.clone()).collect(),
+            },
+        )?;
+    }
+    Ok(import)
+}
@@ -0,0 +1,24 @@
+use tauri::{AppHandle, State};
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::referrals::{self, Referral};
+
+/// Redeem an invite code typed in by hand rather than opened as a link.
+#[tauri::command]
+#[specta::specta]
+pub async fn redeem_invite_code(
+    app: AppHandle,
+    db: State<'_, Db>,
+    code: String,
+) -> Result<Referral> {
+    let referral = referrals::record(db.pool(), &code).await?;
+    referrals::redeem_pending(&app, db.pool()).await?;
+    referrals::get(db.pool(), &referral.code).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_referrals(db: State<'_, Db>) -> Result<Vec<Referral>> {
+    referrals::list(db.pool()).await
+}
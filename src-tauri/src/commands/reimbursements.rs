@@ -0,0 +1,30 @@
+use tauri::{AppHandle, State};
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+use crate::reimbursements::{self, Outstanding, ReimbursementStatus};
+
+/// Mark an expense as owed back, repaid or written off; `None` clears it.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_reimbursement_status(
+    app: AppHandle,
+    db: State<'_, Db>,
+    expense_id: String,
+    status: Option<ReimbursementStatus>,
+    owed_by: Option<String>,
+) -> Result<()> {
+    reimbursements::set_status(db.pool(), &expense_id, status, owed_by).await?;
+    events::publish(
+        &app,
+        &DomainEvent::ReimbursementUpdated { expense_id, status },
+    )
+}
+
+/// Money other people still owe the user.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_outstanding_reimbursements(db: State<'_, Db>) -> Result<Outstanding> {
+    reimbursements::outstanding(db.pool()).await
+}
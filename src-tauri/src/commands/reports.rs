@@ -0,0 +1,103 @@
+use chrono::{Datelike, Local};
+use tauri::State;
+
+use crate::analysis::reports::{
+    self, BudgetUtilization, CashflowBaseline, DailyAverage, MonthCashflow, MonthCategoryTotal,
+    MonthDelta,
+};
+use crate::analysis::year_review::{self, YearReview};
+use crate::db::Db;
+use crate::error::Result;
+use crate::expenses::parse_date;
+use crate::periods;
+
+/// Spending per category for each month from `from_month` to `to_month`
+/// ("YYYY-MM").
+#[tauri::command]
+#[specta::specta]
+pub async fn get_monthly_category_report(
+    db: State<'_, Db>,
+    from_month: String,
+    to_month: String,
+) -> Result<Vec<MonthCategoryTotal>> {
+    reports::monthly_category_totals(db.pool(), &from_month, &to_month).await
+}
+
+/// Each month's total compared with the month before.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_month_over_month(
+    db: State<'_, Db>,
+    from_month: String,
+    to_month: String,
+) -> Result<Vec<MonthDelta>> {
+    reports::month_over_month(db.pool(), &from_month, &to_month).await
+}
+
+/// Average spend per day between `start_date` and `end_date`.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_average_daily_spend(
+    db: State<'_, Db>,
+    start_date: String,
+    end_date: String,
+) -> Result<DailyAverage> {
+    let today = Local::now().date_naive();
+    reports::average_daily_spend(
+        db.pool(),
+        parse_date(&start_date)?,
+        parse_date(&end_date)?,
+        today,
+    )
+    .await
+}
+
+/// Spent against budget for each budget period between the two dates.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_budget_utilization(
+    db: State<'_, Db>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<BudgetUtilization>> {
+    let schedule = periods::schedule(db.pool()).await?;
+    reports::budget_utilization(
+        db.pool(),
+        &schedule,
+        parse_date(&start_date)?,
+        parse_date(&end_date)?,
+    )
+    .await
+}
+
+/// Income minus spending for each month from `from_month` to `to_month`.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_monthly_cashflow(
+    db: State<'_, Db>,
+    from_month: String,
+    to_month: String,
+) -> Result<Vec<MonthCashflow>> {
+    reports::monthly_cashflow(db.pool(), &from_month, &to_month).await
+}
+
+/// Average income and spending over the last full months (three unless
+/// given), for setting the budget to a `share` of what comes in.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_cashflow_baseline(
+    db: State<'_, Db>,
+    months: Option<u32>,
+    share: Option<f64>,
+) -> Result<CashflowBaseline> {
+    let today = Local::now().date_naive();
+    reports::cashflow_baseline(db.pool(), months.unwrap_or(3), share, today).await
+}
+
+/// The year-in-review numbers for `year`, the current one unless given.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_year_review(db: State<'_, Db>, year: Option<i32>) -> Result<YearReview> {
+    let today = Local::now().date_naive();
+    year_review::build(db.pool(), year.unwrap_or(today.year()), today).await
+}
@@ -0,0 +1,26 @@
+use tauri::State;
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::search::{self, SearchQuery, SearchResults};
+
+/// Expenses (and optionally feedback notes) matching `query`, best first.
+#[tauri::command]
+#[specta::specta]
+pub async fn search_expenses(
+    db: State<'_, Db>,
+    query: String,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    limit: Option<i64>,
+    include_notes: Option<bool>,
+) -> Result<SearchResults> {
+    let query = SearchQuery {
+        query,
+        start_date,
+        end_date,
+        limit,
+        include_notes: include_notes.unwrap_or(false),
+    };
+    search::search(db.pool(), query).await
+}
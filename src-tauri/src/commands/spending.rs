@@ -0,0 +1,51 @@
+use tauri::State;
+
+use crate::analysis::compare::{self, MonthComparison};
+use crate::analysis::insights::{self, CategoryInsight};
+use crate::analysis::patterns::{self, WeekdayPatterns};
+use crate::analysis::spending::{self, SpendingSummary};
+use crate::db::Db;
+use crate::error::Result;
+
+/// Spending totals for a date range; `created_by` narrows a shared ledger
+/// to one member.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_spending_summary(
+    db: State<'_, Db>,
+    start_date: String,
+    end_date: String,
+    created_by: Option<String>,
+) -> Result<SpendingSummary> {
+    spending::summary(db.pool(), &start_date, &end_date, created_by.as_deref()).await
+}
+
+/// Notable per-category facts about `month` ("YYYY-MM"), most significant
+/// first.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_category_insights(
+    db: State<'_, Db>,
+    month: String,
+) -> Result<Vec<CategoryInsight>> {
+    insights::category_insights(db.pool(), &month).await
+}
+
+/// Month `a` against month `b` ("YYYY-MM"): category deltas, merchants that
+/// came and went, and budget adherence.
+#[tauri::command]
+#[specta::specta]
+pub async fn compare_months(db: State<'_, Db>, a: String, b: String) -> Result<MonthComparison> {
+    compare::compare_months(db.pool(), &a, &b).await
+}
+
+/// Spending by weekday and time of day for a date range.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_weekday_patterns(
+    db: State<'_, Db>,
+    start_date: String,
+    end_date: String,
+) -> Result<WeekdayPatterns> {
+    patterns::weekday_patterns(db.pool(), &start_date, &end_date).await
+}
@@ -0,0 +1,81 @@
+use chrono::Local;
+use tauri::{AppHandle, State};
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+use crate::splits::{self, ExpenseSplit, NewSettlement, NewSplit, SettleUp, Settlement};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_expense_split(
+    db: State<'_, Db>,
+    expense_id: String,
+) -> Result<Option<ExpenseSplit>> {
+    splits::get(db.pool(), &expense_id).await
+}
+
+/// Share an expense with other people, replacing any earlier split.
+#[tauri::command]
+#[specta::specta]
+pub async fn split_expense(
+    app: AppHandle,
+    db: State<'_, Db>,
+    expense_id: String,
+    input: NewSplit,
+) -> Result<ExpenseSplit> {
+    let split = splits::split(db.pool(), &expense_id, input).await?;
+    events::publish(&app, &DomainEvent::SplitSaved { expense_id })?;
+    Ok(split)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn unsplit_expense(app: AppHandle, db: State<'_, Db>, expense_id: String) -> Result<()> {
+    splits::unsplit(db.pool(), &expense_id).await?;
+    events::publish(&app, &DomainEvent::SplitSaved { expense_id })
+}
+
+/// Names used in splits and settlements so far, for suggestions.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_split_people(db: State<'_, Db>) -> Result<Vec<String>> {
+    splits::people(db.pool()).await
+}
+
+/// Who owes whom, and the payments that would settle it.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_settle_up(db: State<'_, Db>) -> Result<SettleUp> {
+    splits::settle_up(db.pool()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_settlements(db: State<'_, Db>) -> Result<Vec<Settlement>> {
+    splits::settlements(db.pool()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn record_settlement(
+    app: AppHandle,
+    db: State<'_, Db>,
+    input: NewSettlement,
+) -> Result<Settlement> {
+    let settlement = splits::settle(db.pool(), input, Local::now().date_naive()).await?;
+    events::publish(
+        &app,
+        &DomainEvent::SettlementRecorded {
+            settlement_id: settlement.id.clone(),
+        },
+    )?;
+    Ok(settlement)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_settlement(app: AppHandle, db: State<'_, Db>, id: String) -> Result<()> {
+    splits::delete_settlement(db.pool(), &id).await?;
+    events::publish(&app, &DomainEvent::SettlementDeleted { settlement_id: id })
+}
@@ -0,0 +1,329 @@
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Manager, State};
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+use crate::sync::backoff::{self, FailureOutcome};
+use crate::sync::compact::{self, CompactReport};
+use crate::sync::conflicts::{self, Conflict, PullOutcome, Resolution, Strategy, TableStrategy};
+use crate::sync::e2ee::{self, KeyRequest, RecoveryKit, SyncEncryptionStatus};
+use crate::sync::peer::{
+    self, DiscoveredPeer, PairingOffer, Peer, PeerStatus, PeerSync, PeerSyncReport,
+};
+use crate::sync::policy::{self, SyncPolicy};
+use crate::sync::queue::{self, QueueFilter, QueueItem};
+use crate::sync::resync::{self, ResyncReport};
+use crate::sync::settings::{self, TableSyncSetting};
+use crate::sync::status::{self, SyncHealth, SyncRun};
+use crate::sync::worker::{self, PushReport, SyncWorker};
+
+/// Unresolved sync conflicts with local and remote values side by side.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_pending_conflicts(db: State<'_, Db>) -> Result<Vec<Conflict>> {
+    conflicts::pending(db.pool()).await
+}
+
+/// A single unresolved conflict.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_conflict(db: State<'_, Db>, id: String) -> Result<Conflict> {
+    conflicts::get(db.pool(), &id).await
+}
+
+/// Settle a conflict by keeping the local or the remote version.
+#[tauri::command]
+#[specta::specta]
+pub async fn resolve_conflict(
+    app: AppHandle,
+    db: State<'_, Db>,
+    id: String,
+    resolution: Resolution,
+) -> Result<()> {
+    let (table_name, record_id) = conflicts::resolve(db.pool(), &id, resolution).await?;
+    if let Resolution::KeepRemote = resolution {
+        events::publish(
+            &app,
+            &DomainEvent::RemoteVersionKept {
+                table_name,
+                record_id,
+            },
+        )?;
+    }
+    Ok(())
+}
+
+/// Sync queue items for the debug screen, optionally filtered by table and
+/// status.
+#[tauri::command]
+#[specta::specta]
+pub async fn inspect_sync_queue(
+    db: State<'_, Db>,
+    filter: Option<QueueFilter>,
+) -> Result<Vec<QueueItem>> {
+    queue::inspect(db.pool(), &filter.unwrap_or_default()).await
+}
+
+/// Reset a queue item so the next sync tries it again.
+#[tauri::command]
+#[specta::specta]
+pub async fn retry_item(db: State<'_, Db>, id: String) -> Result<()> {
+    queue::retry_item(db.pool(), &id).await
+}
+
+/// Reset every queue item that ran out of attempts.
+#[tauri::command]
+#[specta::specta]
+pub async fn retry_all_failed(db: State<'_, Db>) -> Result<u64> {
+    queue::retry_all_failed(db.pool()).await
+}
+
+/// Drop a queue item without pushing it.
+#[tauri::command]
+#[specta::specta]
+pub async fn discard_item(db: State<'_, Db>, id: String) -> Result<()> {
+    queue::discard_item(db.pool(), &id).await
+}
+
+/// Merge queued changes to the same record now; the worker also does this
+/// before every push.
+#[tauri::command]
+#[specta::specta]
+pub async fn compact_sync_queue(db: State<'_, Db>) -> Result<CompactReport> {
+    compact::run(db.pool()).await
+}
+
+/// Record a failed push and schedule the item's next attempt.
+#[tauri::command]
+#[specta::specta]
+pub async fn record_sync_failure(
+    db: State<'_, Db>,
+    id: String,
+    error: String,
+) -> Result<FailureOutcome> {
+    backoff::record_failure(db.pool(), &id, &error).await
+}
+
+/// Push the sync queue now instead of waiting for the worker's next pass.
+#[tauri::command]
+#[specta::specta]
+pub async fn push_sync_queue(app: AppHandle) -> Result<PushReport> {
+    worker::push(&app).await
+}
+
+/// How conflicts are settled, for every table that can have them.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_conflict_strategies(db: State<'_, Db>) -> Result<Vec<TableStrategy>> {
+    conflicts::strategies(db.pool()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_conflict_strategy(
+    db: State<'_, Db>,
+    table_name: String,
+    strategy: Strategy,
+) -> Result<()> {
+    conflicts::set_strategy(db.pool(), &table_name, strategy).await
+}
+
+/// Sync state, queue depth and the last push and pull of each table.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_sync_health(db: State<'_, Db>) -> Result<SyncHealth> {
+    status::health(db.pool()).await
+}
+
+/// Recent sync runs, newest first.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_sync_log(db: State<'_, Db>, limit: Option<i64>) -> Result<Vec<SyncRun>> {
+    status::log(db.pool(), limit).await
+}
+
+/// Compare everything with the server and repair what drifted apart. Run
+/// with `dry_run` first to show the user what would change.
+#[tauri::command]
+#[specta::specta]
+pub async fn force_full_resync(app: AppHandle, dry_run: bool) -> Result<ResyncReport> {
+    resync::run(&app, dry_run).await
+}
+
+/// The "sync only on Wi-Fi" preference and whether the current connection
+/// passes it.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_sync_policy(app: AppHandle) -> Result<SyncPolicy> {
+    policy::current(&app).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_sync_wifi_only(app: AppHandle, wifi_only: bool) -> Result<SyncPolicy> {
+    policy::set_wifi_only(app.state::<Db>().pool(), wifi_only).await?;
+    app.state::<SyncWorker>().wake();
+    policy::current(&app).await
+}
+
+/// Whether this device is serving peer sync, and under which name.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_peer_sync_status(
+    db: State<'_, Db>,
+    peers: State<'_, PeerSync>,
+) -> Result<PeerStatus> {
+    peer::status(db.pool(), &peers).await
+}
+
+/// Let paired devices on the same network sync with this one.
+#[tauri::command]
+#[specta::specta]
+pub async fn start_peer_sync(app: AppHandle, device_name: String) -> Result<PeerStatus> {
+    peer::start(&app, &device_name).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn stop_peer_sync(db: State<'_, Db>, peers: State<'_, PeerSync>) -> Result<PeerStatus> {
+    peers.stop();
+    peer::status(db.pool(), &peers).await
+}
+
+/// Show a pairing code for another device to scan.
+#[tauri::command]
+#[specta::specta]
+pub async fn offer_peer_pairing(app: AppHandle) -> Result<PairingOffer> {
+    peer::offer_pairing(&app).await
+}
+
+/// Pair with the device that showed a scanned pairing code.
+#[tauri::command]
+#[specta::specta]
+pub async fn pair_with_peer(app: AppHandle, payload: String) -> Result<Peer> {
+    peer::pair(&app, &payload).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_sync_peers(db: State<'_, Db>) -> Result<Vec<Peer>> {
+    peer::list(db.pool()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_sync_peer(db: State<'_, Db>, id: String) -> Result<()> {
+    peer::remove(db.pool(), &id).await
+}
+
+/// Devices with peer sync on in the same network, paired or not.
+#[tauri::command]
+#[specta::specta]
+pub async fn discover_sync_peers(db: State<'_, Db>) -> Result<Vec<DiscoveredPeer>> {
+    peer::discover(db.pool()).await
+}
+
+/// Exchange changes with a paired device.
+#[tauri::command]
+#[specta::specta]
+pub async fn sync_with_peer(app: AppHandle, id: String) -> Result<PeerSyncReport> {
+    peer::sync_with(&app, &id).await
+}
+
+/// Whether each synced table is on, in push order.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_sync_settings(db: State<'_, Db>) -> Result<Vec<TableSyncSetting>> {
+    settings::list(db.pool()).await
+}
+
+/// Keep a table out of cloud sync, or bring it back.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_table_sync(
+    db: State<'_, Db>,
+    table_name: String,
+    enabled: bool,
+) -> Result<Vec<TableSyncSetting>> {
+    settings::set(db.pool(), &table_name, enabled).await
+}
+
+/// Settle a pulled row against unsynced local edits before it is merged.
+#[tauri::command]
+#[specta::specta]
+pub async fn reconcile_pulled_row(
+    db: State<'_, Db>,
+    table_name: String,
+    remote: Map<String, Value>,
+) -> Result<PullOutcome> {
+    conflicts::reconcile_pulled(db.pool(), &table_name, remote).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_sync_encryption_status(db: State<'_, Db>) -> Result<SyncEncryptionStatus> {
+    e2ee::status(db.pool()).await
+}
+
+/// Encrypt synced data with a new key. The recovery code is shown once.
+#[tauri::command]
+#[specta::specta]
+pub async fn enable_sync_encryption(app: AppHandle) -> Result<RecoveryKit> {
+    let kit = e2ee::enable(&app).await?;
+    app.state::<SyncWorker>().wake();
+    Ok(kit)
+}
+
+/// The sync key, for a backup the user keeps themselves.
+#[tauri::command]
+#[specta::specta]
+pub async fn export_sync_key() -> Result<String> {
+    e2ee::export_key().await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn import_sync_key(app: AppHandle, key: String) -> Result<SyncEncryptionStatus> {
+    e2ee::import_key(&app, &key).await
+}
+
+/// Restore the sync key on this device with the recovery code.
+#[tauri::command]
+#[specta::specta]
+pub async fn recover_sync_key(app: AppHandle, code: String) -> Result<SyncEncryptionStatus> {
+    e2ee::recover(&app, &code).await
+}
+
+/// Ask a device that has the sync key to share it with this one.
+#[tauri::command]
+#[specta::specta]
+pub async fn request_sync_key(app: AppHandle, device_name: String) -> Result<KeyRequest> {
+    e2ee::request_key(&app, &device_name).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_sync_key_requests(app: AppHandle) -> Result<Vec<KeyRequest>> {
+    e2ee::key_requests(&app).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn approve_sync_key_request(app: AppHandle, id: String) -> Result<()> {
+    e2ee::approve(&app, &id).await
+}
+
+/// Take the sync key once another device approved this one's request.
+#[tauri::command]
+#[specta::specta]
+pub async fn receive_sync_key(app: AppHandle) -> Result<SyncEncryptionStatus> {
+    e2ee::receive_key(&app).await
+}
+
+/// Decrypt the sealed rows of a pull before they are merged.
+#[tauri::command]
+#[specta::specta]
+pub async fn open_sync_rows(rows: Vec<Map<String, Value>>) -> Result<Vec<Map<String, Value>>> {
+    e2ee::open_rows(rows).await
+}
@@ -0,0 +1,164 @@
+use tauri::{AppHandle, State};
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+use crate::expenses::ExpenseWithCategory;
+use crate::tags::{self, Tag, TagFilter, TagTotal, TaggedCategoryTotal};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_tags(db: State<'_, Db>) -> Result<Vec<Tag>> {
+    tags::list(db.pool()).await
+}
+
+/// Create a tag, or return the existing one with the same name.
+#[tauri::command]
+#[specta::specta]
+pub async fn create_tag(
+    app: AppHandle,
+    db: State<'_, Db>,
+    name: String,
+    color: Option<String>,
+) -> Result<Tag> {
+    let tag = tags::create(db.pool(), &name, color).await?;
+    events::publish(
+        &app,
+        &DomainEvent::TagSaved {
+            tag_id: tag.id.clone(),
+        },
+    )?;
+    Ok(tag)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_tag_color(
+    app: AppHandle,
+    db: State<'_, Db>,
+    id: String,
+    color: Option<String>,
+) -> Result<Tag> {
+    let tag = tags::set_color(db.pool(), &id, color).await?;
+    events::publish(&app, &DomainEvent::TagSaved { tag_id: id })?;
+    Ok(tag)
+}
+
+/// Rename a tag, merging it into another tag that already has the name.
+#[tauri::command]
+#[specta::specta]
+pub async fn rename_tag(
+    app: AppHandle,
+    db: State<'_, Db>,
+    id: String,
+    name: String,
+) -> Result<Tag> {
+    let tag = tags::rename(db.pool(), &id, &name).await?;
+    if tag.id != id {
+        events::publish(&app, &DomainEvent::TagDeleted { tag_id: id })?;
+    }
+    events::publish(
+        &app,
+        &DomainEvent::TagSaved {
+            tag_id: tag.id.clone(),
+        },
+    )?;
+    Ok(tag)
+}
+
+/// Fold the `sources` tags into `target`.
+#[tauri::command]
+#[specta::specta]
+pub async fn merge_tags(
+    app: AppHandle,
+    db: State<'_, Db>,
+    sources: Vec<String>,
+    target: String,
+) -> Result<Tag> {
+    let tag = tags::merge(db.pool(), &sources, &target).await?;
+    for tag_id in sources.into_iter().filter(|s| *s != target) {
+        events::publish(&app, &DomainEvent::TagDeleted { tag_id })?;
+    }
+    events::publish(&app, &DomainEvent::TagSaved { tag_id: target })?;
+    Ok(tag)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_tag(app: AppHandle, db: State<'_, Db>, id: String) -> Result<()> {
+    tags::delete(db.pool(), &id).await?;
+    events::publish(&app, &DomainEvent::TagDeleted { tag_id: id })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_expense_tags(db: State<'_, Db>, expense_id: String) -> Result<Vec<Tag>> {
+    tags::for_expense(db.pool(), &expense_id).await
+}
+
+/// Replace an expense's tags with the ones named.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_expense_tags(
+    app: AppHandle,
+    db: State<'_, Db>,
+    expense_id: String,
+    names: Vec<String>,
+) -> Result<Vec<Tag>> {
+    let tags = tags::set_for_expense(db.pool(), &expense_id, &names).await?;
+    events::publish(
+        &app,
+        &DomainEvent::ExpensesTagged {
+            expense_ids: vec![expense_id],
+        },
+    )?;
+    Ok(tags)
+}
+
+/// Add a tag to, or with `remove` take it off, several expenses.
+#[tauri::command]
+#[specta::specta]
+pub async fn tag_expenses(
+    app: AppHandle,
+    db: State<'_, Db>,
+    tag_id: String,
+    expense_ids: Vec<String>,
+    remove: bool,
+) -> Result<u64> {
+    let changed = tags::tag_expenses(db.pool(), &tag_id, &expense_ids, remove).await?;
+    events::publish(&app, &DomainEvent::ExpensesTagged { expense_ids })?;
+    Ok(changed)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_tagged_expenses(
+    db: State<'_, Db>,
+    filter: TagFilter,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<ExpenseWithCategory>> {
+    tags::expenses(db.pool(), &filter, &start_date, &end_date).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_tag_totals(
+    db: State<'_, Db>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<TagTotal>> {
+    tags::totals(db.pool(), &start_date, &end_date).await
+}
+
+/// Tagged spending broken down by category.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_tagged_category_totals(
+    db: State<'_, Db>,
+    filter: TagFilter,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<TaggedCategoryTotal>> {
+    tags::by_category(db.pool(), &filter, &start_date, &end_date).await
+}
@@ -0,0 +1,40 @@
+use tauri::{AppHandle, State};
+
+use crate::archive::import::StagedArchive;
+use crate::db::Db;
+use crate::error::Result;
+use crate::transfer::{self, ScannedCode, TransferOffer, Transfers};
+
+/// Start serving all local data to another device on the same network.
+#[tauri::command]
+#[specta::specta]
+pub async fn start_transfer(app: AppHandle, db: State<'_, Db>) -> Result<TransferOffer> {
+    transfer::offer(&app, db.pool()).await
+}
+
+/// Stop serving the current transfer offer.
+#[tauri::command]
+#[specta::specta]
+pub fn cancel_transfer(transfers: State<'_, Transfers>) {
+    transfers.cancel();
+}
+
+/// Download the archive behind a scanned transfer code and stage it for
+/// `import_archive`.
+#[tauri::command]
+#[specta::specta]
+pub async fn receive_transfer(
+    app: AppHandle,
+    db: State<'_, Db>,
+    payload: String,
+) -> Result<StagedArchive> {
+    transfer::receive(&app, db.pool(), &payload).await
+}
+
+/// The transfer or pairing code the app was opened with, once. Check on
+/// startup, as `transfer://scanned` may have fired before anyone listened.
+#[tauri::command]
+#[specta::specta]
+pub fn take_scanned_code(transfers: State<'_, Transfers>) -> Option<ScannedCode> {
+    transfers.take_scanned()
+}
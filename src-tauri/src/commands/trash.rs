@@ -0,0 +1,51 @@
+use tauri::{AppHandle, State};
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+use crate::trash::{self, PurgeReport, TrashKind, TrashedItem};
+
+/// Deleted items that can still be restored, most recent first.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_trash(db: State<'_, Db>) -> Result<Vec<TrashedItem>> {
+    trash::list(db.pool()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn restore_from_trash(
+    app: AppHandle,
+    db: State<'_, Db>,
+    kind: TrashKind,
+    id: String,
+) -> Result<()> {
+    trash::restore(db.pool(), kind, &id).await?;
+    events::publish(
+        &app,
+        &DomainEvent::TrashRestored {
+            kind,
+            record_id: id,
+        },
+    )
+}
+
+/// How many days deleted items stay in the trash.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_trash_retention(db: State<'_, Db>) -> Result<u32> {
+    trash::retention_days(db.pool()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_trash_retention(db: State<'_, Db>, days: u32) -> Result<()> {
+    trash::set_retention_days(db.pool(), days).await
+}
+
+/// Delete expired items for good now instead of on the next daily run.
+#[tauri::command]
+#[specta::specta]
+pub async fn purge_trash(db: State<'_, Db>) -> Result<PurgeReport> {
+    trash::purge(db.pool()).await
+}
@@ -0,0 +1,38 @@
+use tauri::{AppHandle, State};
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+use crate::notify::platform;
+use crate::trials::{self, NewTrial, Trial};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_trials(db: State<'_, Db>) -> Result<Vec<Trial>> {
+    trials::list(db.pool()).await
+}
+
+/// Record a free trial and queue a reminder before it converts to paid.
+#[tauri::command]
+#[specta::specta]
+pub async fn create_trial(app: AppHandle, db: State<'_, Db>, input: NewTrial) -> Result<Trial> {
+    let user_id = crate::auth::current_user_id(db.pool()).await?;
+    let trial = trials::create(db.pool(), user_id.as_deref(), input).await?;
+    platform::register(&app, db.pool()).await?;
+    events::publish(
+        &app,
+        &DomainEvent::TrialCreated {
+            trial_id: trial.id.clone(),
+        },
+    )?;
+    Ok(trial)
+}
+
+/// Mark a trial as cancelled and drop its pending reminder.
+#[tauri::command]
+#[specta::specta]
+pub async fn cancel_trial(app: AppHandle, db: State<'_, Db>, id: String) -> Result<()> {
+    trials::cancel(db.pool(), &id).await?;
+    platform::register(&app, db.pool()).await?;
+    events::publish(&app, &DomainEvent::TrialCancelled { trial_id: id })
+}
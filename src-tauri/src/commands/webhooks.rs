@@ -0,0 +1,46 @@
+use tauri::State;
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::webhooks::{self, Webhook, WebhookDelivery, WebhookInput};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_webhooks(db: State<'_, Db>) -> Result<Vec<Webhook>> {
+    webhooks::list(db.pool()).await
+}
+
+/// Add a webhook; it gets its own signing secret.
+#[tauri::command]
+#[specta::specta]
+pub async fn create_webhook(db: State<'_, Db>, input: WebhookInput) -> Result<Webhook> {
+    webhooks::create(db.pool(), input).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_webhook(db: State<'_, Db>, id: String, input: WebhookInput) -> Result<Webhook> {
+    webhooks::update(db.pool(), &id, input).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_webhook(db: State<'_, Db>, id: String) -> Result<()> {
+    webhooks::delete(db.pool(), &id).await
+}
+
+/// POST a `webhook.test` event to the webhook now.
+#[tauri::command]
+#[specta::specta]
+pub async fn test_webhook(db: State<'_, Db>, id: String) -> Result<()> {
+    webhooks::send_test(db.pool(), &id).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_webhook_deliveries(
+    db: State<'_, Db>,
+    webhook_id: String,
+) -> Result<Vec<WebhookDelivery>> {
+    webhooks::deliveries(db.pool(), &webhook_id).await
+}
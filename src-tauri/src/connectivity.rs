@@ -0,0 +1,178 @@
+//! Network reachability, checked in one place.
+//!
+//! `navigator.onLine` only says whether an interface is up, not whether
+//! anything answers. A background task opens TCP connections to a few
+//! well-known anycast addresses instead and emits `connectivity://changed`
+//! whenever the answer flips, so the offline banner and sync share one view.
+//!
+//! Whether the connection is metered can't be probed; the frontend reports
+//! it from the Network Information API where the platform has one, along
+//! with the network type. Where it has none, Linux desktops tell the type
+//! from the interface the default route goes through.
+
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::net::TcpStream;
+
+/// Emitted with `{ "online": bool }` when reachability changes.
+pub const CHANGED_EVENT: &str = "connectivity://changed";
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Public resolvers reachable by IP, so a probe needs no DNS lookup.
+const PROBE_TARGETS: &[&str] = &["1.1.1.1:443", "8.8.8.8:443", "9.9.9.9:443"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkType {
+    Wifi,
+    Ethernet,
+    Cellular,
+    /// A VPN or something else that hides what is underneath.
+    Other,
+    #[default]
+    Unknown,
+}
+
+/// Last known reachability. Starts optimistic so nothing is held back
+/// before the first probe.
+pub struct Connectivity {
+    online: AtomicBool,
+    metered: AtomicBool,
+    /// As reported by the frontend.
+    reported: Mutex<NetworkType>,
+    /// As detected here, used while the frontend can't tell.
+    detected: Mutex<NetworkType>,
+}
+
+impl Default for Connectivity {
+    fn default() -> Self {
+        Self {
+            online: AtomicBool::new(true),
+            metered: AtomicBool::new(false),
+            reported: Mutex::new(NetworkType::Unknown),
+            detected: Mutex::new(NetworkType::Unknown),
+        }
+    }
+}
+
+impl Connectivity {
+    pub fn is_online(&self) -> bool {
+        self.online.load(Ordering::Relaxed)
+    }
+
+    /// True on cellular data or with data saver on, as last reported.
+    pub fn is_metered(&self) -> bool {
+        self.metered.load(Ordering::Relaxed)
+    }
+
+    pub fn set_metered(&self, metered: bool) {
+        self.metered.store(metered, Ordering::Relaxed);
+    }
+
+    pub fn network_type(&self) -> NetworkType {
+        match *self.reported.lock().unwrap() {
+            NetworkType::Unknown => *self.detected.lock().unwrap(),
+            reported => reported,
+        }
+    }
+
+    pub fn set_network_type(&self, network_type: NetworkType) {
+        *self.reported.lock().unwrap() = network_type;
+    }
+
+    /// Neither cellular nor metered. An unknown network counts as
+    /// unmetered, so platforms that can't tell never hold anything back.
+    pub fn is_unmetered(&self) -> bool {
+        !self.is_metered() && self.network_type() != NetworkType::Cellular
+    }
+}
+
+/// The type of the interface the default route goes through.
+#[cfg(target_os = "linux")]
+fn detect() -> NetworkType {
+    let Ok(routes) = std::fs::read_to_string("/proc/net/route") else {
+        return NetworkType::Unknown;
+    };
+    // Columns are Iface, Destination, ...; the default route goes to 0.
+    let default = routes.lines().skip(1).find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let iface = fields.next()?;
+        (fields.next()? == "00000000").then_some(iface)
+    });
+    let Some(iface) = default else {
+        return NetworkType::Unknown;
+    };
+    if std::path::Path::new("/sys/class/net")
+        .join(iface)
+        .join("wireless")
+        .exists()
+    {
+        NetworkType::Wifi
+    } else if ["ww", "ppp", "rmnet"].iter().any(|p| iface.starts_with(p)) {
+        NetworkType::Cellular
+    } else if ["tun", "tap", "wg"].iter().any(|p| iface.starts_with(p)) {
+        NetworkType::Other
+    } else {
+        NetworkType::Ethernet
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn detect() -> NetworkType {
+    NetworkType::Unknown
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ChangedPayload {
+    online: bool,
+}
+
+/// True if any probe target accepts a connection.
+async fn probe() -> bool {
+    for target in PROBE_TARGETS {
+        let Ok(addr) = target.parse::<SocketAddr>() else {
+            continue;
+        };
+        if let Ok(Ok(_)) = tokio::time::timeout(PROBE_TIMEOUT, TcpStream::connect(addr)).await {
+            return true;
+        }
+    }
+    false
+}
+
+/// Probe now, store the result and emit if it changed.
+pub async fn check(app: &AppHandle) -> bool {
+    let online = probe().await;
+    let connectivity = app.state::<Connectivity>();
+    *connectivity.detected.lock().unwrap() = detect();
+    let previous = connectivity.online.swap(online, Ordering::Relaxed);
+    if previous != online {
+        eprintln!(
+            "[Connectivity] Now {}",
+            if online { "online" } else { "offline" }
+        );
+        if let Err(e) = app.emit(CHANGED_EVENT, ChangedPayload { online }) {
+            eprintln!("[Connectivity] Failed to emit {CHANGED_EVENT}: {e}");
+        }
+    }
+    online
+}
+
+/// Keep checking for the life of the app.
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            check(&app).await;
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
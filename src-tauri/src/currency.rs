@@ -0,0 +1,321 @@
+//! Currencies and the exchange rates used to bring foreign spending into
+//! the base currency.
+//!
+//! Rates are the ECB reference rates (via Frankfurter), cached per
+//! publishing day in `exchange_rates` against the euro; other pairs go
+//! through it. A conversion uses the rate published on or shortly before
+//! the spending date. When that isn't cached and can't be fetched, the
+//! closest cached rate stands in, so logging spending abroad works offline
+//! once any rate has been seen.
+
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::{AppHandle, Manager};
+
+use crate::connectivity::Connectivity;
+use crate::db::now;
+use crate::error::{Error, Result};
+
+const RATES_URL: &str = "https://api.frankfurter.dev/v1";
+
+/// The currency the reference rates are quoted against.
+const ANCHOR: &str = "EUR";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A cached rate this much older than the date it's wanted for still
+/// counts; rates aren't published on weekends and holidays.
+const MAX_RATE_AGE_DAYS: i64 = 4;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct Currency {
+    pub code: String,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: i64,
+    pub is_base: bool,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ExchangeRate {
+    pub from: String,
+    pub to: String,
+    pub rate: f64,
+    /// Publishing day of the rates used, which may be before the date asked
+    /// for.
+    pub rate_date: String,
+}
+
+/// An amount ready to store on an expense.
+#[derive(Debug, Clone)]
+pub struct Converted {
+    /// In the base currency.
+    pub amount: f64,
+    /// Set only for spending in another currency.
+    pub currency: Option<String>,
+    pub original_amount: Option<f64>,
+}
+
+impl Converted {
+    pub fn base(amount: f64) -> Self {
+        Self {
+            amount,
+            currency: None,
+            original_amount: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RatesResponse {
+    date: String,
+    rates: std::collections::HashMap<String, f64>,
+}
+
+pub async fn list(pool: &SqlitePool) -> Result<Vec<Currency>> {
+    Ok(sqlx::query_as(
+        "SELECT code, name, symbol, decimals, is_base FROM currencies ORDER BY is_base DESC, code",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn base_on(conn: &mut SqliteConnection) -> Result<String> {
+    let base: Option<(String,)> = sqlx::query_as("SELECT code FROM currencies WHERE is_base = 1")
+        .fetch_optional(&mut *conn)
+        .await?;
+    Ok(base.map_or_else(|| ANCHOR.to_string(), |(code,)| code))
+}
+
+pub async fn base(pool: &SqlitePool) -> Result<String> {
+    base_on(&mut *pool.acquire().await?).await
+}
+
+/// Upper-cased `code` if it's a known currency.
+async fn known(conn: &mut SqliteConnection, code: &str) -> Result<String> {
+    let code = code.trim().to_uppercase();
+    let found: Option<(String,)> = sqlx::query_as("SELECT code FROM currencies WHERE code = $1")
+        .bind(&code)
+        .fetch_optional(&mut *conn)
+        .await?;
+    found
+        .map(|(code,)| code)
+        .ok_or_else(|| Error::Validation(format!("Unknown currency \"{code}\"")))
+}
+
+/// Make `code` the base currency. Stored amounts are not converted, so this
+/// is refused once any expense has been converted into the current base.
+pub async fn set_base(pool: &SqlitePool, code: &str) -> Result<Vec<Currency>> {
+    let mut tx = pool.begin().await?;
+    let code = known(&mut tx, code).await?;
+    if base_on(&mut tx).await? != code {
+        let (converted,): (bool,) =
+            sqlx::query_as("SELECT EXISTS (SELECT 1 FROM expenses WHERE currency IS NOT NULL)")
+                .fetch_one(&mut *tx)
+                .await?;
+        if converted {
+            return Err(Error::Validation(
+                "The base currency can't change once expenses have been converted into it"
+                    .to_string(),
+            ));
+        }
+        sqlx::query("UPDATE currencies SET is_base = (code = $1)")
+            .bind(&code)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+    list(pool).await
+}
+
+/// Blocking. Rates published on `date`, or the latest ones.
+fn fetch_remote(date: Option<NaiveDate>) -> Result<RatesResponse> {
+    let day = date.map_or_else(
+        || "latest".to_string(),
+        |d| d.format("%Y-%m-%d").to_string(),
+    );
+    let response = ureq::AgentBuilder::new()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .get(&format!("{RATES_URL}/{day}"))
+        .query("base", ANCHOR)
+        .call()
+        .map_err(|e| Error::Remote(format!("exchange rates unavailable: {e}")))?;
+    serde_json::from_str(&response.into_string()?)
+        .map_err(|e| Error::Remote(format!("unexpected exchange rate response: {e}")))
+}
+
+/// Fetch and cache the rates for `date` (the latest if `None`). Returns the
+/// publishing day they are for.
+pub async fn fetch(pool: &SqlitePool, date: Option<NaiveDate>) -> Result<String> {
+    let response = tauri::async_runtime::spawn_blocking(move || fetch_remote(date)).await??;
+    let fetched_at = now();
+    let mut tx = pool.begin().await?;
+    for (quote, rate) in &response.rates {
+        sqlx::query(
+            "INSERT INTO exchange_rates (quote, rate_date, rate, fetched_at) VALUES ($1, $2, $3, $4)
+             ON CONFLICT(quote, rate_date) DO UPDATE SET rate = excluded.rate, fetched_at = excluded.fetched_at",
+        )
+        .bind(quote)
+        .bind(&response.date)
+        .bind(rate)
+        .bind(&fetched_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(response.date)
+}
+
+/// Units of `code` per euro, from the closest cached day on or before
+/// `date`, else the closest after it.
+async fn anchor_rate(
+    conn: &mut SqliteConnection,
+    code: &str,
+    date: &str,
+) -> Result<Option<(f64, String)>> {
+    if code == ANCHOR {
+        return Ok(Some((1.0, date.to_string())));
+    }
+    let before: Option<(f64, String)> = sqlx::query_as(
+        "SELECT rate, rate_date FROM exchange_rates
+         WHERE quote = $1 AND rate_date <= $2 ORDER BY rate_date DESC LIMIT 1",
+    )
+    .bind(code)
+    .bind(date)
+    .fetch_optional(&mut *conn)
+    .await?;
+    if before.is_some() {
+        return Ok(before);
+    }
+    Ok(sqlx::query_as(
+        "SELECT rate, rate_date FROM exchange_rates
+         WHERE quote = $1 AND rate_date > $2 ORDER BY rate_date ASC LIMIT 1",
+    )
+    .bind(code)
+    .bind(date)
+    .fetch_optional(&mut *conn)
+    .await?)
+}
+
+/// Whether the cache has a rate for `code` close enough to `date`.
+async fn is_cached(pool: &SqlitePool, code: &str, date: NaiveDate) -> Result<bool> {
+    if code == ANCHOR {
+        return Ok(true);
+    }
+    let oldest = date - chrono::Duration::days(MAX_RATE_AGE_DAYS);
+    let (cached,): (bool,) = sqlx::query_as(
+        "SELECT EXISTS (
+           SELECT 1 FROM exchange_rates WHERE quote = $1 AND rate_date BETWEEN $2 AND $3
+         )",
+    )
+    .bind(code)
+    .bind(oldest.format("%Y-%m-%d").to_string())
+    .bind(date.format("%Y-%m-%d").to_string())
+    .fetch_one(pool)
+    .await?;
+    Ok(cached)
+}
+
+/// Fetch rates for converting `code` on `date` unless they are cached. A
+/// failed fetch is logged and left to the cached fallback.
+pub async fn ensure(pool: &SqlitePool, code: &str, date: NaiveDate) -> Result<()> {
+    let date = date.min(chrono::Local::now().date_naive());
+    let code = code.trim().to_uppercase();
+    let base = base(pool).await?;
+    if code == base || (is_cached(pool, &code, date).await? && is_cached(pool, &base, date).await?)
+    {
+        return Ok(());
+    }
+    if let Err(e) = fetch(pool, Some(date)).await {
+        eprintln!("[Currency] Could not fetch rates for {date}: {e}");
+    }
+    Ok(())
+}
+
+/// The cached rate from `from` to `to` for `date`.
+pub async fn rate_on(
+    conn: &mut SqliteConnection,
+    from: &str,
+    to: &str,
+    date: NaiveDate,
+) -> Result<ExchangeRate> {
+    let from = known(conn, from).await?;
+    let to = known(conn, to).await?;
+    let day = date.format("%Y-%m-%d").to_string();
+    if from == to {
+        return Ok(ExchangeRate {
+            from,
+            to,
+            rate: 1.0,
+            rate_date: day,
+        });
+    }
+    let missing = || {
+        Error::Validation(format!(
+            "No exchange rate for {from} to {to} yet. Connect to the internet once to load rates."
+        ))
+    };
+    let (from_rate, from_date) = anchor_rate(conn, &from, &day).await?.ok_or_else(missing)?;
+    let (to_rate, to_date) = anchor_rate(conn, &to, &day).await?.ok_or_else(missing)?;
+    Ok(ExchangeRate {
+        rate: to_rate / from_rate,
+        rate_date: from_date.min(to_date),
+        from,
+        to,
+    })
+}
+
+/// The rate from `from` to `to` for `date`, fetching it first if needed.
+pub async fn rate(
+    pool: &SqlitePool,
+    from: &str,
+    to: &str,
+    date: NaiveDate,
+) -> Result<ExchangeRate> {
+    ensure(pool, from, date).await?;
+    ensure(pool, to, date).await?;
+    rate_on(&mut *pool.acquire().await?, from, to, date).await
+}
+
+/// `amount` in `currency` (the base currency if `None`) as stored on an
+/// expense dated `date`. Only reads the cache; call `ensure` before
+/// starting the transaction.
+pub async fn convert_on(
+    conn: &mut SqliteConnection,
+    amount: f64,
+    currency: Option<&str>,
+    date: NaiveDate,
+) -> Result<Converted> {
+    let base = base_on(conn).await?;
+    let Some(currency) = currency.filter(|c| !c.trim().eq_ignore_ascii_case(&base)) else {
+        return Ok(Converted::base(amount));
+    };
+    let rate = rate_on(conn, currency, &base, date).await?;
+    Ok(Converted {
+        amount: (amount * rate.rate * 100.0).round() / 100.0,
+        currency: Some(rate.from),
+        original_amount: Some(amount),
+    })
+}
+
+/// Cache today's rates once a day while online.
+pub async fn refresh_latest(app: &AppHandle, pool: &SqlitePool) -> Result<()> {
+    if !app.state::<Connectivity>().is_online() {
+        return Ok(());
+    }
+    let today = chrono::Utc::now().date_naive();
+    let (cached,): (bool,) = sqlx::query_as(
+        "SELECT EXISTS (SELECT 1 FROM exchange_rates WHERE substr(fetched_at, 1, 10) = $1)",
+    )
+    .bind(today.format("%Y-%m-%d").to_string())
+    .fetch_one(pool)
+    .await?;
+    if !cached {
+        fetch(pool, None).await?;
+    }
+    Ok(())
+}
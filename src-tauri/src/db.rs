@@ -0,0 +1,92 @@
+//! Rust-side access to the local SQLite database.
+//!
+//! The frontend talks to `goaldy.db` through tauri-plugin-sql; Rust commands
+//! open their own pool on the same file. The schema itself is still owned by
+//! the TypeScript migration runner (src/lib/migrations.ts).
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Deserializer};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use tauri::{AppHandle, Manager};
+
+use crate::error::Result;
+
+/// File name used by the frontend in `Database.load("sqlite:goaldy.db")`.
+const DB_FILE: &str = "goaldy.db";
+
+/// Managed state wrapping the connection pool.
+pub struct Db(SqlitePool);
+
+impl Db {
+    /// Open the pool on the same file tauri-plugin-sql uses (it resolves
+    /// relative paths against the app config dir).
+    pub async fn open(app: &AppHandle) -> Result<Self> {
+        Self::open_at(&path(app)?).await
+    }
+
+    /// Open the pool on the database at `path`, as the CLI does without an
+    /// app.
+    pub async fn open_at(path: &Path) -> Result<Self> {
+        crate::encryption::prepare(path).await?;
+        let url = format!("sqlite:{}", path.display());
+
+        let options = SqliteConnectOptions::from_str(&url)?
+            .create_if_missing(true)
+            .busy_timeout(Duration::from_secs(5))
+            // sqlx turns this on already; spelled out because the schema
+            // and `orphans` rely on it.
+            .foreign_keys(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(4)
+            .connect_with(options)
+            .await?;
+
+        Ok(Self(pool))
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.0
+    }
+}
+
+/// Where `goaldy.db` lives, creating its folder if needed.
+pub fn path(app: &AppHandle) -> Result<PathBuf> {
+    let dir = app.path().app_config_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir.join(DB_FILE))
+}
+
+/// Current time in the same format the frontend writes (`Date.toISOString()`).
+pub fn now() -> String {
+    timestamp(Utc::now())
+}
+
+/// Format a point in time the way `Date.toISOString()` does.
+pub fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// New record id, matching `generateId()` on the frontend.
+pub fn new_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Quote an identifier for SQL built at runtime from table or column names.
+pub fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+/// For `#[serde(default, deserialize_with = "nullable")]` on an
+/// `Option<Option<T>>` update field: missing leaves the value alone
+/// (`None`), `null` clears it (`Some(None)`).
+pub fn nullable<'de, D, T>(deserializer: D) -> std::result::Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
@@ -0,0 +1,66 @@
+//! Quick-add links: `goaldy://add?amount=12.50&category=groceries`.
+//!
+//! Besides `amount` and `category` (an id, a name or a keyword such as
+//! "coffee"), a link may carry `note`, `date` ("YYYY-MM-DD") and `text`, free
+//! text as shared from another app ("12.50 coffee") that is parsed like a
+//! typed description. Explicit parameters win over what the text says.
+
+use chrono::NaiveDate;
+use url::Url;
+
+use super::text::parse_number;
+use super::{find_category, parse_expense_text, CategoryRef, ExpenseDraft};
+use crate::error::{Error, Result};
+
+/// Longest note a link may set, in characters.
+const MAX_NOTE: usize = 500;
+
+/// Whether `url` is a quick-add link at all.
+pub fn is_add_link(url: &Url) -> bool {
+    url.scheme() == "goaldy" && url.host_str() == Some("add")
+}
+
+/// The draft a quick-add link describes. Fails on malformed values;
+/// a category that matches nothing is left for the user to pick.
+pub fn parse_add_link(
+    url: &Url,
+    categories: &[CategoryRef],
+    today: NaiveDate,
+) -> Result<ExpenseDraft> {
+    if !is_add_link(url) {
+        return Err(Error::Validation("Not a quick-add link".to_string()));
+    }
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    let mut draft = match param("text") {
+        Some(text) => parse_expense_text(&text, categories, today),
+        None => ExpenseDraft {
+            amount: None,
+            category_id: None,
+            note: None,
+            date: today.format("%Y-%m-%d").to_string(),
+        },
+    };
+    if let Some(amount) = param("amount") {
+        let amount = parse_number(&amount)
+            .ok_or_else(|| Error::Validation(format!("{amount} is not an amount")))?;
+        draft.amount = Some((amount * 100.0).round() / 100.0);
+    }
+    if let Some(category) = param("category") {
+        draft.category_id = find_category(&category, categories);
+    }
+    if let Some(note) = param("note") {
+        draft.note = Some(note.chars().take(MAX_NOTE).collect());
+    }
+    if let Some(date) = param("date") {
+        NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|_| Error::Validation(format!("{date} is not a YYYY-MM-DD date")))?;
+        draft.date = date;
+    }
+    Ok(draft)
+}
@@ -0,0 +1,45 @@
+//! Expense drafts proposed from unstructured input (voice, free text,
+//! receipt photos, quick-add links).
+//!
+//! A draft is never written to the database here; the frontend shows it
+//! pre-filled in the expense form so the user can confirm or correct it.
+
+mod link;
+mod receipt;
+mod text;
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::error::Result;
+
+pub use link::{is_add_link, parse_add_link};
+pub use receipt::{parse_receipt, ReceiptDraft};
+pub use text::{find_category, parse_expense_text};
+
+/// Pre-filled expense fields. Anything we couldn't recognise is left `None`.
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ExpenseDraft {
+    pub amount: Option<f64>,
+    pub category_id: Option<String>,
+    pub note: Option<String>,
+    /// "YYYY-MM-DD", defaults to today.
+    pub date: String,
+}
+
+/// The subset of a category the parser matches against.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CategoryRef {
+    pub id: String,
+    pub name: String,
+}
+
+/// Visible categories, same filter as `getCategories()` on the frontend.
+pub async fn load_categories(pool: &SqlitePool) -> Result<Vec<CategoryRef>> {
+    let categories = sqlx::query_as::<_, CategoryRef>(
+        "SELECT id, name FROM categories WHERE is_hidden = 0 AND deleted_at IS NULL ORDER BY sort_order ASC",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(categories)
+}
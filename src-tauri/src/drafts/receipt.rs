@@ -0,0 +1,194 @@
+//! Receipt parser: pulls total, date and merchant out of OCR text.
+//!
+//! Every field gets a confidence in `0.0..=1.0` (heuristic strength scaled by
+//! the OCR engine's own confidence) so the UI knows what to ask about.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use super::text::{match_category, parse_number, tokenize};
+use super::{CategoryRef, ExpenseDraft};
+
+/// Lines that carry the amount actually paid.
+const TOTAL_KEYWORDS: &[&str] = &[
+    "total",
+    "summe",
+    "gesamt",
+    "betrag",
+    "amount due",
+    "to pay",
+    "zu zahlen",
+    "balance due",
+];
+
+/// Lines that look like totals but aren't the amount paid.
+const NOT_TOTAL_KEYWORDS: &[&str] = &[
+    "subtotal",
+    "sub total",
+    "zwischensumme",
+    "netto",
+    "change",
+    "rückgeld",
+];
+
+/// Tax lines: the tax alone ("MwSt 19%", "VAT amount") unless they say the
+/// tax is included, as in "Total incl. tax".
+const TAX_KEYWORDS: &[&str] = &["tax", "vat", "mwst"];
+
+const INCLUDED_KEYWORDS: &[&str] = &["incl", "inkl", "including", "inklusive"];
+
+/// Header words that are never the merchant name.
+const NOT_MERCHANT_KEYWORDS: &[&str] = &[
+    "receipt",
+    "rechnung",
+    "kassenbon",
+    "beleg",
+    "invoice",
+    "welcome",
+];
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ReceiptDraft {
+    pub draft: ExpenseDraft,
+    pub merchant: Option<String>,
+    pub confidence: ReceiptConfidence,
+    /// Raw OCR text, handy when the user wants to correct a field.
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ReceiptConfidence {
+    pub amount: f32,
+    pub date: f32,
+    pub merchant: f32,
+}
+
+/// Build a draft from OCR output. `ocr_confidence` is the engine's mean
+/// confidence in `0.0..=1.0`.
+pub fn parse_receipt(
+    text: &str,
+    ocr_confidence: f32,
+    categories: &[CategoryRef],
+    today: NaiveDate,
+) -> ReceiptDraft {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+
+    let (amount, amount_confidence) = find_total(&lines);
+    let (date, date_confidence) = find_date(&lines, today);
+    let merchant = find_merchant(&lines);
+    let merchant_confidence = if merchant.is_some() { 0.6 } else { 0.0 };
+
+    let category_id = merchant
+        .as_deref()
+        .and_then(|m| match_category(&tokenize(m), categories));
+
+    ReceiptDraft {
+        draft: ExpenseDraft {
+            amount,
+            category_id,
+            note: merchant.clone(),
+            date: date.format("%Y-%m-%d").to_string(),
+        },
+        merchant,
+        confidence: ReceiptConfidence {
+            amount: amount_confidence * ocr_confidence,
+            date: date_confidence * ocr_confidence,
+            merchant: merchant_confidence * ocr_confidence,
+        },
+        text: text.to_string(),
+    }
+}
+
+fn amounts_in(line: &str) -> Vec<f64> {
+    line.split_whitespace().filter_map(parse_number).collect()
+}
+
+/// The last amount on the last "total" line; failing that, the largest
+/// amount on the receipt.
+fn find_total(lines: &[&str]) -> (Option<f64>, f32) {
+    let keyword_total = lines.iter().rev().find_map(|line| {
+        let lower = line.to_lowercase();
+        let has = |keywords: &[&str]| keywords.iter().any(|k| lower.contains(k));
+        let is_total = has(TOTAL_KEYWORDS)
+            && !has(NOT_TOTAL_KEYWORDS)
+            && (!has(TAX_KEYWORDS) || has(INCLUDED_KEYWORDS));
+        if is_total {
+            amounts_in(line).last().copied()
+        } else {
+            None
+        }
+    });
+    if let Some(total) = keyword_total {
+        return (Some(total), 0.9);
+    }
+
+    let largest = lines
+        .iter()
+        .flat_map(|line| amounts_in(line))
+        .filter(|a| a.fract() != 0.0)
+        .fold(None, |max: Option<f64>, a| {
+            Some(max.map_or(a, |m| m.max(a)))
+        });
+    (largest, if largest.is_some() { 0.4 } else { 0.0 })
+}
+
+/// First plausible date (not in the future), falling back to today.
+fn find_date(lines: &[&str], today: NaiveDate) -> (NaiveDate, f32) {
+    // Two-digit years first: `%Y` would happily read "24" as the year 24.
+    const FORMATS: &[&str] = &[
+        "%d.%m.%y", "%d/%m/%y", "%d.%m.%Y", "%d/%m/%Y", "%Y-%m-%d", "%d-%m-%Y",
+    ];
+
+    lines
+        .iter()
+        .flat_map(|line| line.split_whitespace())
+        .find_map(|token| {
+            let token = token.trim_matches(|c: char| !c.is_ascii_digit());
+            FORMATS
+                .iter()
+                .find_map(|f| NaiveDate::parse_from_str(token, f).ok())
+                .filter(|d| *d <= today)
+        })
+        .map_or((today, 0.0), |d| (d, 0.9))
+}
+
+/// Merchants print their name at the top: take the first of the leading
+/// lines that is mostly letters.
+fn find_merchant(lines: &[&str]) -> Option<String> {
+    lines.iter().take(5).find_map(|line| {
+        let lower = line.to_lowercase();
+        if NOT_MERCHANT_KEYWORDS.iter().any(|k| lower.contains(k)) {
+            return None;
+        }
+        let letters = line.chars().filter(|c| c.is_alphabetic()).count();
+        let digits = line.chars().filter(|c| c.is_ascii_digit()).count();
+        (letters >= 3 && letters > digits * 2).then(|| line.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn total_including_tax_is_the_total() {
+        let lines = ["Subtotal 10.50", "Tax 2.00", "Total incl. tax 12.50"];
+        assert_eq!(find_total(&lines).0, Some(12.50));
+
+        let lines = ["Zwischensumme 20,00", "Gesamt inkl. MwSt 23,80"];
+        assert_eq!(find_total(&lines).0, Some(23.80));
+    }
+
+    #[test]
+    fn tax_lines_are_not_the_total() {
+        let lines = ["Gesamt 23,80", "MwSt 19% 3,80", "MwSt-Betrag 3,80"];
+        assert_eq!(find_total(&lines).0, Some(23.80));
+
+        let lines = ["Total 12.50", "Tax 2.00", "Total tax 2.00"];
+        assert_eq!(find_total(&lines).0, Some(12.50));
+    }
+}
@@ -0,0 +1,277 @@
+//! Free-text expense parser: "12.50 coffee yesterday" becomes a draft with
+//! amount, category, note and date filled in.
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+use super::{CategoryRef, ExpenseDraft};
+
+/// Keywords for the built-in categories, tried after exact category names.
+const KEYWORDS: &[(&str, &[&str])] = &[
+    (
+        "cat_groceries",
+        &[
+            "grocery",
+            "groceries",
+            "supermarket",
+            "aldi",
+            "lidl",
+            "rewe",
+            "edeka",
+            "bakery",
+        ],
+    ),
+    (
+        "cat_dining",
+        &[
+            "coffee",
+            "lunch",
+            "dinner",
+            "breakfast",
+            "restaurant",
+            "cafe",
+            "pizza",
+            "burger",
+            "sushi",
+            "drinks",
+            "beer",
+            "bar",
+            "takeaway",
+            "snack",
+        ],
+    ),
+    (
+        "cat_transport",
+        &[
+            "taxi", "uber", "bus", "train", "tram", "metro", "ticket", "fuel", "petrol", "parking",
+        ],
+    ),
+    (
+        "cat_entertainment",
+        &[
+            "cinema", "movie", "movies", "concert", "theatre", "theater", "museum", "bowling",
+        ],
+    ),
+    (
+        "cat_shopping",
+        &["clothes", "shoes", "amazon", "shirt", "jacket", "gift"],
+    ),
+    (
+        "cat_health",
+        &[
+            "pharmacy", "doctor", "dentist", "medicine", "gym", "vitamins",
+        ],
+    ),
+    (
+        "cat_utilities",
+        &["electricity", "water", "internet", "phone", "heating"],
+    ),
+    (
+        "cat_subscriptions",
+        &[
+            "netflix",
+            "spotify",
+            "subscription",
+            "disney",
+            "prime",
+            "icloud",
+            "youtube",
+        ],
+    ),
+];
+
+const CURRENCY_WORDS: &[&str] = &[
+    "euro", "euros", "eur", "dollar", "dollars", "usd", "bucks", "pound", "pounds", "gbp", "cent",
+    "cents",
+];
+
+/// Words people say around an amount that shouldn't end up in the note.
+const FILLER_WORDS: &[&str] = &[
+    "i", "spent", "paid", "bought", "for", "on", "at", "just", "and",
+];
+
+/// Parse a spoken or typed expense description into a draft.
+pub fn parse_expense_text(
+    text: &str,
+    categories: &[CategoryRef],
+    today: NaiveDate,
+) -> ExpenseDraft {
+    let tokens = tokenize(text);
+    let mut used = vec![false; tokens.len()];
+
+    let amount = take_amount(&tokens, &mut used);
+    let date = take_date(&tokens, &mut used, today);
+    let category_id = match_category(&tokens, categories);
+
+    for (i, (_, lower)) in tokens.iter().enumerate() {
+        if CURRENCY_WORDS.contains(&lower.as_str()) {
+            used[i] = true;
+        }
+    }
+
+    let remaining: Vec<(&str, &str)> = tokens
+        .iter()
+        .zip(&used)
+        .filter(|(_, used)| !**used)
+        .map(|((orig, lower), _)| (*orig, lower.as_str()))
+        .collect();
+    let start = remaining
+        .iter()
+        .position(|(_, lower)| !FILLER_WORDS.contains(lower))
+        .unwrap_or(remaining.len());
+    let end = remaining
+        .iter()
+        .rposition(|(_, lower)| !FILLER_WORDS.contains(lower))
+        .map_or(start, |i| i + 1);
+    let note = remaining[start..end.max(start)]
+        .iter()
+        .map(|(orig, _)| *orig)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    ExpenseDraft {
+        amount,
+        category_id,
+        note: (!note.is_empty()).then_some(note),
+        date: date.format("%Y-%m-%d").to_string(),
+    }
+}
+
+/// Split into `(original, lowercase)` words with surrounding punctuation removed.
+pub(super) fn tokenize(text: &str) -> Vec<(&str, String)> {
+    text.split_whitespace()
+        .map(|t| {
+            let trimmed =
+                t.trim_matches(|c: char| matches!(c, ',' | '.' | '!' | '?' | ';' | ':' | '"'));
+            (trimmed, trimmed.to_lowercase())
+        })
+        .filter(|(t, _)| !t.is_empty())
+        .collect()
+}
+
+/// Find the first number in the text, accepting "12.50", "12,50", "€12" and
+/// the spoken form "12 euros 50".
+fn take_amount(tokens: &[(&str, String)], used: &mut [bool]) -> Option<f64> {
+    for (i, (_, lower)) in tokens.iter().enumerate() {
+        let Some(mut value) = parse_number(lower) else {
+            continue;
+        };
+        used[i] = true;
+
+        let followed_by_currency = tokens
+            .get(i + 1)
+            .is_some_and(|(_, next)| CURRENCY_WORDS.contains(&next.as_str()));
+        if followed_by_currency && value.fract() == 0.0 {
+            if let Some(cents) = tokens.get(i + 2).and_then(|(_, t)| parse_number(t)) {
+                if cents.fract() == 0.0 && cents < 100.0 {
+                    value += cents / 100.0;
+                    used[i + 2] = true;
+                }
+            }
+        }
+        return Some((value * 100.0).round() / 100.0);
+    }
+    None
+}
+
+pub(super) fn parse_number(token: &str) -> Option<f64> {
+    let stripped = token
+        .trim_start_matches(['€', '$', '£'])
+        .trim_end_matches(['€', '$', '£'])
+        .trim_end_matches("eur");
+    if stripped.is_empty()
+        || !stripped
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == ',')
+    {
+        return None;
+    }
+
+    // A single comma followed by at most two digits is a decimal comma.
+    let normalized = match stripped.rsplit_once(',') {
+        Some((whole, frac)) if frac.len() <= 2 && !whole.contains(',') => {
+            format!("{}.{}", whole.replace('.', ""), frac)
+        }
+        _ => stripped.replace(',', ""),
+    };
+    normalized.parse::<f64>().ok().filter(|v| *v > 0.0)
+}
+
+/// Resolve "today", "yesterday" and weekday names (most recent occurrence).
+fn take_date(tokens: &[(&str, String)], used: &mut [bool], today: NaiveDate) -> NaiveDate {
+    for (i, (_, lower)) in tokens.iter().enumerate() {
+        let date = match lower.as_str() {
+            "today" | "tonight" => today,
+            "yesterday" => today - Duration::days(1),
+            word => match parse_weekday(word) {
+                Some(weekday) => {
+                    let back = (today.weekday().num_days_from_monday() + 7
+                        - weekday.num_days_from_monday())
+                        % 7;
+                    today - Duration::days(i64::from(back))
+                }
+                None => continue,
+            },
+        };
+        used[i] = true;
+        if i > 0 && matches!(tokens[i - 1].1.as_str(), "on" | "last") {
+            used[i - 1] = true;
+        }
+        return date;
+    }
+    today
+}
+
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    match word {
+        "monday" => Some(Weekday::Mon),
+        "tuesday" => Some(Weekday::Tue),
+        "wednesday" => Some(Weekday::Wed),
+        "thursday" => Some(Weekday::Thu),
+        "friday" => Some(Weekday::Fri),
+        "saturday" => Some(Weekday::Sat),
+        "sunday" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// The category `query` names: its id, its name or a keyword such as
+/// "coffee".
+pub fn find_category(query: &str, categories: &[CategoryRef]) -> Option<String> {
+    let lower = query.to_lowercase();
+    categories
+        .iter()
+        .find(|c| c.id == query || c.name.to_lowercase() == lower)
+        .map(|c| c.id.clone())
+        .or_else(|| match_category(&tokenize(query), categories))
+}
+
+/// Exact category names win over keywords, so custom categories like "Coffee"
+/// beat the built-in "coffee" -> Dining mapping.
+pub(super) fn match_category(
+    tokens: &[(&str, String)],
+    categories: &[CategoryRef],
+) -> Option<String> {
+    let text = tokens
+        .iter()
+        .map(|(_, lower)| lower.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let by_name = categories.iter().find(|c| {
+        let name = c.name.to_lowercase();
+        text.split(' ').any(|word| word == name) || (name.contains(' ') && text.contains(&name))
+    });
+    if let Some(category) = by_name {
+        return Some(category.id.clone());
+    }
+
+    KEYWORDS
+        .iter()
+        .filter(|(id, _)| categories.iter().any(|c| c.id == *id))
+        .find(|(_, words)| {
+            tokens
+                .iter()
+                .any(|(_, lower)| words.contains(&lower.as_str()))
+        })
+        .map(|(id, _)| id.to_string())
+}
@@ -0,0 +1,380 @@
+//! Opt-in encryption of `goaldy.db` at rest, with SQLCipher.
+//!
+//! Only builds with the `encryption` feature link SQLCipher; others report
+//! it as unavailable. The 256-bit key lives in the keychain. It is either
+//! random, or derived from a passphrase the user picks (PBKDF2, salted in
+//! `encryption.json`), so that whoever knows the passphrase can derive it
+//! again. When the keychain loses a passphrase key, as after an OS reset
+//! or a move to a new machine, `goaldy unlock` asks for the passphrase and
+//! puts the key back.
+//!
+//! Every connection this process opens to `goaldy.db`, ours and
+//! tauri-plugin-sql's alike, is keyed by an SQLite auto-extension before
+//! first use, so the frontend doesn't know the difference. Other files,
+//! such as staged archives, are left alone.
+//!
+//! Turning encryption on exports the live database into an encrypted copy
+//! next to it. The copy replaces the plaintext file on the next launch,
+//! before anything has it open, and the app restarts right away so nothing
+//! is written in between.
+
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{ConnectOptions, SqliteConnection, SqlitePool};
+
+use crate::db::now;
+use crate::error::{Error, Result};
+use crate::secrets;
+
+/// Written next to the database, which can't be read without it.
+const CONFIG_FILE: &str = "encryption.json";
+
+/// Suffix of the encrypted copy waiting to replace the database.
+const PENDING_SUFFIX: &str = "encrypted";
+
+const KEY_SECRET: &str = "database_key";
+
+const KEY_LEN: usize = 32;
+
+const PBKDF2_ITERATIONS: u32 = 600_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySource {
+    /// A random key only the keychain knows.
+    Keychain,
+    /// Derived from the user's passphrase, and cached in the keychain.
+    Passphrase,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Config {
+    key_source: KeySource,
+    /// Base64 PBKDF2 salt for passphrase keys.
+    salt: Option<String>,
+    enabled_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct EncryptionStatus {
+    /// Whether this build can encrypt at all.
+    pub available: bool,
+    pub enabled: bool,
+    pub key_source: Option<KeySource>,
+    /// The encrypted copy is waiting for a restart to take over.
+    pub restart_required: bool,
+}
+
+fn config_path(db_path: &Path) -> PathBuf {
+    db_path.with_file_name(CONFIG_FILE)
+}
+
+fn pending_path(db_path: &Path) -> PathBuf {
+    db_path.with_extension(format!("db.{PENDING_SUFFIX}"))
+}
+
+fn read_config(db_path: &Path) -> Result<Option<Config>> {
+    let path = config_path(db_path);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let config = serde_json::from_slice(&std::fs::read(path)?)
+        .map_err(|e| Error::Validation(format!("Unreadable {CONFIG_FILE}: {e}")))?;
+    Ok(Some(config))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// SQLCipher's notation for a raw key, which skips its own derivation.
+fn raw_key(hex: &str) -> String {
+    format!("x'{hex}'")
+}
+
+/// Whether connections to the database are being keyed.
+pub fn is_active() -> bool {
+    cipher::is_active()
+}
+
+pub fn status(db_path: &Path) -> Result<EncryptionStatus> {
+    let config = read_config(db_path)?;
+    Ok(EncryptionStatus {
+        available: cfg!(feature = "encryption"),
+        enabled: config.is_some(),
+        key_source: config.map(|c| c.key_source),
+        restart_required: pending_path(db_path).exists(),
+    })
+}
+
+/// Make an encrypted database usable before the first connection opens:
+/// swap in a pending encrypted copy and key every connection to it. Does
+/// nothing for a plaintext database.
+pub async fn prepare(db_path: &Path) -> Result<()> {
+    let Some(config) = read_config(db_path)? else {
+        return Ok(());
+    };
+    let missing = match config.key_source {
+        KeySource::Passphrase => {
+            "The database key is not in the keychain; run `goaldy unlock` to restore it"
+        }
+        KeySource::Keychain => "The database is encrypted but its key is not in the keychain",
+    };
+    let key = secrets::get(KEY_SECRET)
+        .await?
+        .ok_or_else(|| Error::Validation(missing.to_string()))?;
+    cipher::install(db_path, &raw_key(&key))?;
+
+    let pending = pending_path(db_path);
+    if pending.exists() {
+        for suffix in ["-wal", "-shm"] {
+            let sidecar = PathBuf::from(format!("{}{suffix}", db_path.display()));
+            if sidecar.exists() {
+                std::fs::remove_file(sidecar)?;
+            }
+        }
+        std::fs::rename(&pending, db_path)?;
+    }
+    Ok(())
+}
+
+/// Copy the database open on `conn` to a new file at `dest`, encrypted
+/// with `key`, or in plaintext for an empty key.
+pub(crate) async fn export(conn: &mut SqliteConnection, dest: &Path, key: &str) -> Result<()> {
+    // ATTACH is per connection, so everything runs on `conn`.
+    sqlx::query("ATTACH DATABASE $1 AS export KEY $2")
+        .bind(dest.to_string_lossy().as_ref())
+        .bind(key)
+        .execute(&mut *conn)
+        .await?;
+    let exported = async {
+        sqlx::query("SELECT sqlcipher_export('export')")
+            .execute(&mut *conn)
+            .await?;
+        let (version,): (i64,) = sqlx::query_as("PRAGMA main.user_version")
+            .fetch_one(&mut *conn)
+            .await?;
+        sqlx::query(&format!("PRAGMA export.user_version = {version}"))
+            .execute(&mut *conn)
+            .await?;
+        Ok::<_, Error>(())
+    }
+    .await;
+    sqlx::query("DETACH DATABASE export")
+        .execute(&mut *conn)
+        .await?;
+    exported
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> [u8; KEY_LEN] {
+    let mut key = [0u8; KEY_LEN];
+    ring::pbkdf2::derive(
+        ring::pbkdf2::PBKDF2_HMAC_SHA256,
+        NonZeroU32::new(PBKDF2_ITERATIONS).expect("iterations are non-zero"),
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    key
+}
+
+/// Derive a passphrase key again from the stored salt and, if it opens the
+/// database, put it back in the keychain.
+pub async fn unlock(db_path: &Path, passphrase: &str) -> Result<()> {
+    if !cfg!(feature = "encryption") {
+        return Err(Error::Unsupported(
+            "This build of Goaldy can't open an encrypted database".to_string(),
+        ));
+    }
+    let config = read_config(db_path)?
+        .ok_or_else(|| Error::Validation("The database is not encrypted".to_string()))?;
+    let salt = match (config.key_source, &config.salt) {
+        (KeySource::Passphrase, Some(salt)) => BASE64
+            .decode(salt)
+            .map_err(|e| Error::Validation(format!("Unreadable salt in {CONFIG_FILE}: {e}")))?,
+        _ => {
+            return Err(Error::Validation(
+                "The database key is random, not derived from a passphrase".to_string(),
+            ))
+        }
+    };
+    let key = {
+        let passphrase = passphrase.to_string();
+        tauri::async_runtime::spawn_blocking(move || derive_key(&passphrase, &salt)).await?
+    };
+    let hex = to_hex(&key);
+
+    // The encrypted copy, if it hasn't replaced the database yet.
+    let pending = pending_path(db_path);
+    let file = if pending.exists() {
+        pending
+    } else {
+        db_path.to_path_buf()
+    };
+    let opened = async {
+        let mut conn = SqliteConnectOptions::from_str(&format!("sqlite:{}", file.display()))?
+            .read_only(true)
+            .pragma("key", format!("\"{}\"", raw_key(&hex)))
+            .connect()
+            .await?;
+        sqlx::query("SELECT count(*) FROM sqlite_master")
+            .fetch_one(&mut conn)
+            .await?;
+        Ok::<_, sqlx::Error>(())
+    }
+    .await;
+    if opened.is_err() {
+        return Err(Error::Validation(
+            "Wrong passphrase for this database".to_string(),
+        ));
+    }
+    secrets::set(KEY_SECRET, Some(hex)).await
+}
+
+/// Encrypt the database, with a key derived from `passphrase` if one is
+/// given. Takes effect on the next launch.
+pub async fn enable(
+    pool: &SqlitePool,
+    db_path: &Path,
+    passphrase: Option<&str>,
+) -> Result<EncryptionStatus> {
+    if !cfg!(feature = "encryption") {
+        return Err(Error::Unsupported(
+            "This build of Goaldy can't encrypt its database".to_string(),
+        ));
+    }
+    if read_config(db_path)?.is_some() {
+        return Err(Error::Validation(
+            "The database is already encrypted".to_string(),
+        ));
+    }
+
+    let (key, config) = match passphrase {
+        Some(passphrase) => {
+            crate::archive::check_passphrase(passphrase)?;
+            let salt = rand::random::<[u8; 16]>();
+            let key = {
+                let passphrase = passphrase.to_string();
+                tauri::async_runtime::spawn_blocking(move || derive_key(&passphrase, &salt)).await?
+            };
+            let config = Config {
+                key_source: KeySource::Passphrase,
+                salt: Some(BASE64.encode(salt)),
+                enabled_at: now(),
+            };
+            (key, config)
+        }
+        None => (
+            rand::random::<[u8; KEY_LEN]>(),
+            Config {
+                key_source: KeySource::Keychain,
+                salt: None,
+                enabled_at: now(),
+            },
+        ),
+    };
+    let hex = to_hex(&key);
+    secrets::set(KEY_SECRET, Some(hex.clone())).await?;
+
+    let pending = pending_path(db_path);
+    if pending.exists() {
+        std::fs::remove_file(&pending)?;
+    }
+    let mut conn = pool.acquire().await?;
+    if let Err(e) = export(&mut conn, &pending, &raw_key(&hex)).await {
+        if pending.exists() {
+            std::fs::remove_file(&pending)?;
+        }
+        return Err(e);
+    }
+
+    let json = serde_json::to_vec_pretty(&config)
+        .map_err(|e| Error::Validation(format!("failed to encode {CONFIG_FILE}: {e}")))?;
+    std::fs::write(config_path(db_path), json)?;
+    status(db_path)
+}
+
+#[cfg(feature = "encryption")]
+mod cipher {
+    use std::ffi::{c_char, c_int, CStr, CString};
+    use std::path::Path;
+    use std::sync::OnceLock;
+
+    use libsqlite3_sys::{
+        sqlite3, sqlite3_api_routines, sqlite3_auto_extension, sqlite3_db_filename, sqlite3_exec,
+        SQLITE_OK,
+    };
+
+    use crate::error::{Error, Result};
+
+    /// The database file and the statement that keys a connection to it.
+    static KEYED: OnceLock<(CString, CString)> = OnceLock::new();
+
+    pub fn is_active() -> bool {
+        KEYED.get().is_some()
+    }
+
+    unsafe extern "C" fn apply_key(
+        db: *mut sqlite3,
+        _err: *mut *mut c_char,
+        _api: *const sqlite3_api_routines,
+    ) -> c_int {
+        let Some((path, pragma)) = KEYED.get() else {
+            return SQLITE_OK;
+        };
+        // SAFETY: SQLite hands us an open connection; both strings are
+        // NUL-terminated and outlive the call.
+        let filename = sqlite3_db_filename(db, c"main".as_ptr());
+        if filename.is_null() || CStr::from_ptr(filename) != path.as_c_str() {
+            return SQLITE_OK;
+        }
+        sqlite3_exec(
+            db,
+            pragma.as_ptr(),
+            None,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    }
+
+    pub fn install(db_path: &Path, key: &str) -> Result<()> {
+        let invalid = |_| Error::Validation("Invalid database path".to_string());
+        let path = CString::new(db_path.to_string_lossy().as_bytes()).map_err(invalid)?;
+        let pragma = CString::new(format!("PRAGMA key = \"{key}\";")).map_err(invalid)?;
+        if KEYED.set((path, pragma)).is_err() {
+            return Ok(());
+        }
+        // SAFETY: `apply_key` matches the entry point signature and only
+        // reads `KEYED`, which is set for the life of the process.
+        let rc = unsafe { sqlite3_auto_extension(Some(apply_key)) };
+        if rc != SQLITE_OK {
+            return Err(Error::Unsupported(format!(
+                "could not register the database key (SQLite error {rc})"
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "encryption"))]
+mod cipher {
+    use std::path::Path;
+
+    use crate::error::{Error, Result};
+
+    pub fn is_active() -> bool {
+        false
+    }
+
+    pub fn install(_db_path: &Path, _key: &str) -> Result<()> {
+        Err(Error::Unsupported(
+            "The database is encrypted, but this build of Goaldy can't open it".to_string(),
+        ))
+    }
+}
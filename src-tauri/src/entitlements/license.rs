@@ -0,0 +1,112 @@
+//! Signed entitlement tokens and the server endpoint that issues them.
+//!
+//! Store receipts and Stripe sessions can only be checked with secrets that
+//! don't belong in the app, so the `verify-entitlement` edge function does
+//! that and answers with a token: the entitlement as JSON, signed with the
+//! server's Ed25519 key. Tokens are `<payload>.<signature>`, both base64url
+//! without padding. The public key is baked in at build time from
+//! `GOALDY_LICENSE_PUBLIC_KEY`; builds without it can't verify anything and
+//! treat every account as free.
+
+use std::time::Duration;
+
+use base64::prelude::{Engine, BASE64_URL_SAFE_NO_PAD};
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::Session;
+use crate::backend::BackendConfig;
+use crate::error::{Error, Result};
+
+/// Base64url Ed25519 public key matching the server's signing key.
+const PUBLIC_KEY: Option<&str> = option_env!("GOALDY_LICENSE_PUBLIC_KEY");
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    AppStore,
+    PlayStore,
+    Stripe,
+}
+
+/// Proof of a purchase as handed over by the store SDK or checkout.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub struct Receipt {
+    pub source: Source,
+    /// App Store receipt or transaction JWS, Play purchase token, or
+    /// Stripe checkout session id.
+    pub token: String,
+    pub product_id: Option<String>,
+}
+
+/// What a token grants.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Claims {
+    pub user_id: String,
+    pub plan: String,
+    #[serde(default)]
+    pub features: Vec<String>,
+    pub source: Option<Source>,
+    /// RFC 3339; end of the paid period.
+    pub expires_at: String,
+}
+
+pub fn can_verify() -> bool {
+    PUBLIC_KEY.is_some()
+}
+
+/// Check the signature and return the claims of `token`.
+pub fn verify(token: &str) -> Result<Claims> {
+    let key = PUBLIC_KEY
+        .ok_or_else(|| Error::Unsupported("This build can't verify licenses".to_string()))?;
+    let invalid = || Error::Validation("Invalid entitlement token".to_string());
+    let key = BASE64_URL_SAFE_NO_PAD
+        .decode(key.trim())
+        .map_err(|_| invalid())?;
+    let (payload, signature) = token.split_once('.').ok_or_else(invalid)?;
+    let signature = BASE64_URL_SAFE_NO_PAD
+        .decode(signature)
+        .map_err(|_| invalid())?;
+    UnparsedPublicKey::new(&ED25519, &key)
+        .verify(payload.as_bytes(), &signature)
+        .map_err(|_| invalid())?;
+    let payload = BASE64_URL_SAFE_NO_PAD
+        .decode(payload)
+        .map_err(|_| invalid())?;
+    serde_json::from_slice(&payload).map_err(|_| invalid())
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    token: String,
+}
+
+/// Ask the server for a fresh token, validating `receipt` first when given.
+/// Without one the server looks up purchases it already knows of.
+/// Blocking.
+pub fn request(
+    config: &BackendConfig,
+    session: &Session,
+    receipt: Option<&Receipt>,
+) -> Result<String> {
+    let response = ureq::AgentBuilder::new()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .post(&format!("{}/functions/v1/verify-entitlement", config.url))
+        .set("apikey", &config.anon_key)
+        .set("Authorization", &format!("Bearer {}", session.access_token))
+        .set("Content-Type", "application/json")
+        .send_string(&serde_json::json!({ "receipt": receipt }).to_string())
+        .map_err(|e| match e {
+            ureq::Error::Status(code, response) => {
+                let body = response.into_string().unwrap_or_default();
+                Error::Remote(format!("license check returned {code}: {}", body.trim()))
+            }
+            ureq::Error::Transport(e) => Error::Remote(format!("license server unreachable: {e}")),
+        })?;
+    let body: TokenResponse = serde_json::from_reader(response.into_reader())
+        .map_err(|e| Error::Remote(format!("unexpected response: {e}")))?;
+    Ok(body.token)
+}
@@ -0,0 +1,177 @@
+//! Premium entitlements.
+//!
+//! The webview can write anything to the database, so it can't be what
+//! decides whether an account has paid. Entitlements are only ever read
+//! from a token signed by the server (see [`license`]), which is checked
+//! again on every read. The latest token is cached per account so premium
+//! features keep working offline, and for a grace period after the paid
+//! period ends in case the renewal simply hasn't reached this device yet.
+
+pub mod license;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Manager};
+
+use crate::auth;
+use crate::backend::Backend;
+use crate::connectivity::Connectivity;
+use crate::db::now;
+use crate::error::{Error, Result};
+use license::Receipt;
+
+/// How long past its expiry a cached token still counts.
+const OFFLINE_GRACE_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    BankSync,
+}
+
+impl Feature {
+    fn as_str(self) -> &'static str {
+        match self {
+            Feature::BankSync => "bank_sync",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    /// No valid token: the free plan.
+    None,
+    Active,
+    /// Past the paid period but within the offline grace.
+    Grace,
+    Expired,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct Entitlements {
+    pub plan: String,
+    pub status: Status,
+    pub features: Vec<String>,
+    pub source: Option<license::Source>,
+    pub expires_at: Option<String>,
+    /// Last day of the offline grace, while in it.
+    pub grace_until: Option<String>,
+}
+
+impl Entitlements {
+    fn free() -> Self {
+        Entitlements {
+            plan: "free".to_string(),
+            status: Status::None,
+            features: Vec::new(),
+            source: None,
+            expires_at: None,
+            grace_until: None,
+        }
+    }
+
+    pub fn allows(&self, feature: Feature) -> bool {
+        matches!(self.status, Status::Active | Status::Grace)
+            && self.features.iter().any(|f| f == feature.as_str())
+    }
+}
+
+/// Entitlements of the signed-in account at `at`.
+pub async fn get(pool: &SqlitePool, at: DateTime<Utc>) -> Result<Entitlements> {
+    let Some(user_id) = auth::current_user_id(pool).await? else {
+        return Ok(Entitlements::free());
+    };
+    if !license::can_verify() {
+        return Ok(Entitlements::free());
+    }
+    let row: Option<(String,)> =
+        sqlx::query_as("SELECT token FROM entitlement_tokens WHERE user_id = $1")
+            .bind(&user_id)
+            .fetch_optional(pool)
+            .await?;
+    let Some((token,)) = row else {
+        return Ok(Entitlements::free());
+    };
+    let claims = match license::verify(&token) {
+        Ok(claims) if claims.user_id == user_id => claims,
+        Ok(_) => return Ok(Entitlements::free()),
+        Err(e) => {
+            eprintln!("[Entitlements] Ignoring cached token: {e}");
+            return Ok(Entitlements::free());
+        }
+    };
+    let Some(expires) = DateTime::parse_from_rfc3339(&claims.expires_at)
+        .ok()
+        .map(|at| at.with_timezone(&Utc))
+    else {
+        return Ok(Entitlements::free());
+    };
+    let grace_end = expires + Duration::days(OFFLINE_GRACE_DAYS);
+    let status = if at < expires {
+        Status::Active
+    } else if at < grace_end {
+        Status::Grace
+    } else {
+        Status::Expired
+    };
+    Ok(Entitlements {
+        plan: claims.plan,
+        status,
+        features: claims.features,
+        source: claims.source,
+        expires_at: Some(claims.expires_at),
+        grace_until: (status == Status::Grace).then(|| crate::db::timestamp(grace_end)),
+    })
+}
+
+/// Fetch a new token from the server, with a purchase to validate or
+/// without to pick up renewals and refunds, and cache it. Does nothing
+/// while offline, signed out or without a configured backend.
+pub async fn refresh(app: &AppHandle, pool: &SqlitePool, receipt: Option<Receipt>) -> Result<()> {
+    let (Some(config), Some(session)) =
+        (app.state::<Backend>().config(), auth::session(pool).await?)
+    else {
+        return match receipt {
+            Some(_) => Err(Error::Validation(
+                "Sign in to restore or complete a purchase".to_string(),
+            )),
+            None => Ok(()),
+        };
+    };
+    if !app.state::<Connectivity>().is_online() {
+        return match receipt {
+            Some(_) => Err(Error::Remote(
+                "Purchases can't be verified offline".to_string(),
+            )),
+            None => Ok(()),
+        };
+    }
+    if !license::can_verify() {
+        return Ok(());
+    }
+
+    let user_id = session.user_id.clone();
+    let token = tauri::async_runtime::spawn_blocking(move || {
+        license::request(&config, &session, receipt.as_ref())
+    })
+    .await??;
+    let claims = license::verify(&token)?;
+    if claims.user_id != user_id {
+        return Err(Error::Validation(
+            "The license server answered for another account".to_string(),
+        ));
+    }
+
+    sqlx::query(
+        "INSERT INTO entitlement_tokens (user_id, token, updated_at) VALUES ($1, $2, $3)
+         ON CONFLICT(user_id) DO UPDATE SET token = excluded.token, updated_at = excluded.updated_at",
+    )
+    .bind(&user_id)
+    .bind(&token)
+    .bind(now())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,46 @@
+//! Error type shared by all Tauri commands.
+//!
+//! Commands return `Result<T>`; errors are serialized as plain strings so the
+//! frontend sees the same `Error.message` shape it gets from the TS data layer.
+
+use serde::{Serialize, Serializer};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Tauri(#[from] tauri::Error),
+
+    /// Input from the frontend failed validation.
+    #[error("{0}")]
+    Validation(String),
+
+    /// A feature that isn't compiled into this build or isn't available on
+    /// this platform.
+    #[error("{0}")]
+    Unsupported(String),
+
+    /// A request to the sync backend failed or was refused.
+    #[error("{0}")]
+    Remote(String),
+}
+
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+/// Typed as the string it serializes to.
+impl specta::Type for Error {
+    fn inline(types: &mut specta::TypeCollection, generics: specta::Generics) -> specta::DataType {
+        String::inline(types, generics)
+    }
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
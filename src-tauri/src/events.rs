@@ -0,0 +1,258 @@
+//! Domain events, broadcast to every window.
+//!
+//! The tray, quick-add window, widgets and main UI each keep their own
+//! state. Instead of polling the database they listen for these, each
+//! emitted under its own name with the event itself, `type` included, as
+//! payload. Writes made on the Rust side publish directly; the frontend
+//! publishes its own writes through `publish_domain_event` so that every
+//! event, wherever it started, reaches all windows the same way.
+//!
+//! Every command that creates, changes or deletes user data publishes one
+//! after the write succeeds. Events carry ids and whatever a listener
+//! needs to decide whether to reload, not whole records.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::error::Result;
+use crate::reimbursements::ReimbursementStatus;
+use crate::trash::TrashKind;
+
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+#[serde(tag = "type")]
+pub enum DomainEvent {
+    #[serde(rename = "expense:created")]
+    ExpenseCreated {
+        expense_id: String,
+        category_id: Option<String>,
+        amount: f64,
+        date: String,
+    },
+    #[serde(rename = "expense:updated")]
+    ExpenseUpdated {
+        expense_id: String,
+        category_id: Option<String>,
+        amount: f64,
+        date: String,
+    },
+    #[serde(rename = "expense:deleted")]
+    ExpenseDeleted { expense_id: String },
+    /// Several expenses written at once.
+    #[serde(rename = "expense:imported")]
+    ExpensesImported {
+        /// "csv", "ofx", "qif", "ynab", "mint" or "bank".
+        source: String,
+        count: usize,
+    },
+    /// Several expenses changed at once, or such a change undone.
+    #[serde(rename = "expense:bulk_changed")]
+    ExpensesBulkChanged { expense_ids: Vec<String> },
+    #[serde(rename = "expense:reimbursement_updated")]
+    ReimbursementUpdated {
+        expense_id: String,
+        status: Option<ReimbursementStatus>,
+    },
+    #[serde(rename = "attachment:added")]
+    AttachmentAdded {
+        attachment_id: String,
+        expense_id: String,
+    },
+    #[serde(rename = "attachment:deleted")]
+    AttachmentDeleted { attachment_id: String },
+    #[serde(rename = "budget:updated")]
+    BudgetUpdated {
+        budget_id: String,
+        /// Budget period key.
+        month: String,
+    },
+    /// The budget template or pay schedule changed.
+    #[serde(rename = "budget:settings_updated")]
+    BudgetSettingsUpdated,
+    #[serde(rename = "budget:category_updated")]
+    CategoryBudgetUpdated {
+        month: String,
+        category_id: String,
+        /// `None` once the allocation is removed.
+        amount: Option<f64>,
+    },
+    #[serde(rename = "goal:created")]
+    GoalCreated { goal_id: String },
+    #[serde(rename = "goal:updated")]
+    GoalUpdated { goal_id: String },
+    #[serde(rename = "goal:deleted")]
+    GoalDeleted { goal_id: String },
+    #[serde(rename = "goal:contribution_added")]
+    ContributionAdded {
+        goal_id: String,
+        contribution_id: String,
+        month: String,
+        amount: f64,
+    },
+    #[serde(rename = "recurring:created")]
+    RecurringCreated { recurring_ids: Vec<String> },
+    #[serde(rename = "income:source_created")]
+    IncomeSourceCreated { source_id: String },
+    #[serde(rename = "income:source_deleted")]
+    IncomeSourceDeleted { source_id: String },
+    #[serde(rename = "income:logged")]
+    IncomeLogged {
+        entry_id: String,
+        source_id: String,
+        amount: f64,
+        date: String,
+    },
+    #[serde(rename = "income:entry_deleted")]
+    IncomeEntryDeleted { entry_id: String },
+    #[serde(rename = "net_worth:balance_recorded")]
+    BalanceRecorded { account_name: String },
+    #[serde(rename = "trial:created")]
+    TrialCreated { trial_id: String },
+    #[serde(rename = "trial:cancelled")]
+    TrialCancelled { trial_id: String },
+    /// A bill was added or changed, or a payment taken back.
+    #[serde(rename = "bill:saved")]
+    BillSaved { bill_id: String },
+    #[serde(rename = "bill:deleted")]
+    BillDeleted { bill_id: String },
+    #[serde(rename = "bill:paid")]
+    BillPaid {
+        bill_id: String,
+        /// "YYYY-MM" of the cycle paid.
+        month: String,
+    },
+    /// An expense was split, or its split changed or removed.
+    #[serde(rename = "split:saved")]
+    SplitSaved { expense_id: String },
+    #[serde(rename = "split:settlement_recorded")]
+    SettlementRecorded { settlement_id: String },
+    #[serde(rename = "split:settlement_deleted")]
+    SettlementDeleted { settlement_id: String },
+    /// An expense or budget was shared with a household, or stopped being.
+    #[serde(rename = "household:share_changed")]
+    HouseholdShareChanged {
+        record_id: String,
+        household_id: Option<String>,
+    },
+    /// A tag was added, renamed or recolored, or others merged into it.
+    #[serde(rename = "tag:saved")]
+    TagSaved { tag_id: String },
+    #[serde(rename = "tag:deleted")]
+    TagDeleted { tag_id: String },
+    #[serde(rename = "tag:expenses_tagged")]
+    ExpensesTagged { expense_ids: Vec<String> },
+    /// A merchant was renamed, or merged into the one already so named.
+    #[serde(rename = "merchant:renamed")]
+    MerchantRenamed {
+        /// The id before the rename.
+        previous_id: String,
+        merchant_id: String,
+    },
+    /// An account was added or changed, or its balance recomputed.
+    #[serde(rename = "account:saved")]
+    AccountSaved { account_id: String },
+    #[serde(rename = "account:deleted")]
+    AccountDeleted { account_id: String },
+    #[serde(rename = "account:transfer_saved")]
+    TransferSaved {
+        transfer_id: String,
+        from_account_id: String,
+        to_account_id: String,
+    },
+    #[serde(rename = "account:transfer_deleted")]
+    TransferDeleted { transfer_id: String },
+    /// A categorization rule was added or changed.
+    #[serde(rename = "categorization_rule:saved")]
+    CategorizationRuleSaved { rule_id: String },
+    #[serde(rename = "categorization_rule:deleted")]
+    CategorizationRuleDeleted { rule_id: String },
+    #[serde(rename = "category_alert:saved")]
+    CategoryAlertSaved { category_id: String },
+    #[serde(rename = "category_alert:deleted")]
+    CategoryAlertDeleted { category_id: String },
+    #[serde(rename = "preferences:notifications_updated")]
+    NotificationPreferencesUpdated,
+    #[serde(rename = "currency:base_changed")]
+    BaseCurrencyChanged { code: String },
+    /// A backup or archive replaced or merged local data; reload
+    /// everything.
+    #[serde(rename = "data:restored")]
+    DataRestored {
+        /// "backup" or "archive".
+        source: String,
+    },
+    #[serde(rename = "trash:restored")]
+    TrashRestored { kind: TrashKind, record_id: String },
+    #[serde(rename = "sync:applied_remote_changes")]
+    RemoteChangesApplied {
+        /// Tables that received changes.
+        tables: Vec<String>,
+        count: usize,
+    },
+    /// A sync conflict was settled by keeping the remote version, which
+    /// overwrote the local row.
+    #[serde(rename = "sync:remote_kept")]
+    RemoteVersionKept {
+        table_name: String,
+        record_id: String,
+    },
+}
+
+impl DomainEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DomainEvent::ExpenseCreated { .. } => "expense:created",
+            DomainEvent::ExpenseUpdated { .. } => "expense:updated",
+            DomainEvent::ExpenseDeleted { .. } => "expense:deleted",
+            DomainEvent::ExpensesImported { .. } => "expense:imported",
+            DomainEvent::ExpensesBulkChanged { .. } => "expense:bulk_changed",
+            DomainEvent::ReimbursementUpdated { .. } => "expense:reimbursement_updated",
+            DomainEvent::AttachmentAdded { .. } => "attachment:added",
+            DomainEvent::AttachmentDeleted { .. } => "attachment:deleted",
+            DomainEvent::BudgetUpdated { .. } => "budget:updated",
+            DomainEvent::BudgetSettingsUpdated => "budget:settings_updated",
+            DomainEvent::CategoryBudgetUpdated { .. } => "budget:category_updated",
+            DomainEvent::GoalCreated { .. } => "goal:created",
+            DomainEvent::GoalUpdated { .. } => "goal:updated",
+            DomainEvent::GoalDeleted { .. } => "goal:deleted",
+            DomainEvent::ContributionAdded { .. } => "goal:contribution_added",
+            DomainEvent::RecurringCreated { .. } => "recurring:created",
+            DomainEvent::IncomeSourceCreated { .. } => "income:source_created",
+            DomainEvent::IncomeSourceDeleted { .. } => "income:source_deleted",
+            DomainEvent::IncomeLogged { .. } => "income:logged",
+            DomainEvent::IncomeEntryDeleted { .. } => "income:entry_deleted",
+            DomainEvent::BalanceRecorded { .. } => "net_worth:balance_recorded",
+            DomainEvent::TrialCreated { .. } => "trial:created",
+            DomainEvent::TrialCancelled { .. } => "trial:cancelled",
+            DomainEvent::BillSaved { .. } => "bill:saved",
+            DomainEvent::BillDeleted { .. } => "bill:deleted",
+            DomainEvent::BillPaid { .. } => "bill:paid",
+            DomainEvent::SplitSaved { .. } => "split:saved",
+            DomainEvent::SettlementRecorded { .. } => "split:settlement_recorded",
+            DomainEvent::SettlementDeleted { .. } => "split:settlement_deleted",
+            DomainEvent::HouseholdShareChanged { .. } => "household:share_changed",
+            DomainEvent::TagSaved { .. } => "tag:saved",
+            DomainEvent::TagDeleted { .. } => "tag:deleted",
+            DomainEvent::ExpensesTagged { .. } => "tag:expenses_tagged",
+            DomainEvent::MerchantRenamed { .. } => "merchant:renamed",
+            DomainEvent::AccountSaved { .. } => "account:saved",
+            DomainEvent::AccountDeleted { .. } => "account:deleted",
+            DomainEvent::TransferSaved { .. } => "account:transfer_saved",
+            DomainEvent::TransferDeleted { .. } => "account:transfer_deleted",
+            DomainEvent::CategorizationRuleSaved { .. } => "categorization_rule:saved",
+            DomainEvent::CategorizationRuleDeleted { .. } => "categorization_rule:deleted",
+            DomainEvent::CategoryAlertSaved { .. } => "category_alert:saved",
+            DomainEvent::CategoryAlertDeleted { .. } => "category_alert:deleted",
+            DomainEvent::NotificationPreferencesUpdated => "preferences:notifications_updated",
+            DomainEvent::BaseCurrencyChanged { .. } => "currency:base_changed",
+            DomainEvent::DataRestored { .. } => "data:restored",
+            DomainEvent::TrashRestored { .. } => "trash:restored",
+            DomainEvent::RemoteChangesApplied { .. } => "sync:applied_remote_changes",
+            DomainEvent::RemoteVersionKept { .. } => "sync:remote_kept",
+        }
+    }
+}
+
+pub fn publish(app: &AppHandle, event: &DomainEvent) -> Result<()> {
+    app.emit(event.name(), event)?;
+    Ok(())
+}
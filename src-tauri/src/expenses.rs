@@ -0,0 +1,401 @@
+//! Expenses, read and written on behalf of the frontend.
+//!
+//! Every write validates its input, queues the row for sync and refreshes
+//! the `monthly_category_totals` cells it touched. Spending in another
+//! currency is converted on the way in, so `amount` is always in the base
+//! currency. New expenses without a category get one from the
+//! categorization rules if one matches, and writes naming an account
+//! refresh its balance. Deletes only mark the row, signed in or not, so it
+//! sits in the trash until restored or purged.
+
+use std::slice;
+
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::AppHandle;
+
+use crate::accounts;
+use crate::auth;
+use crate::budget_alerts;
+use crate::categorize::rules::Rules;
+use crate::category_alerts;
+use crate::category_totals::{self, Cell};
+use crate::currency::{self, Converted};
+use crate::db::{new_id, now, nullable};
+use crate::error::{Error, Result};
+use crate::merchants;
+use crate::sync::{self, Operation};
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct Expense {
+    pub id: String,
+    pub user_id: Option<String>,
+    pub amount: f64,
+    pub category_id: Option<String>,
+    pub note: Option<String>,
+    /// "YYYY-MM-DD".
+    pub date: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub synced_at: Option<String>,
+    pub deleted_at: Option<String>,
+    pub reimbursement_status: Option<String>,
+    pub reimbursement_owed_by: Option<String>,
+    pub reimbursed_at: Option<String>,
+    pub payment_method: Option<String>,
+    pub created_by: Option<String>,
+    /// Set when the expense was paid in another currency.
+    pub currency: Option<String>,
+    /// What was paid, in `currency`.
+    pub original_amount: Option<f64>,
+    /// The merchant the note names; see `merchants`.
+    pub merchant_id: Option<String>,
+    /// The account it was paid from; see `accounts`.
+    pub account_id: Option<String>,
+    /// The household it is shared with; see `households`.
+    pub household_id: Option<String>,
+}
+
+impl Expense {
+    pub(crate) fn cell(&self) -> Cell {
+        Cell {
+            date: self.date.clone(),
+            category_id: self.category_id.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct ExpenseWithCategory {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub expense: Expense,
+    pub category_name: Option<String>,
+    pub category_icon: Option<String>,
+    pub category_color: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentMethod {
+    Cash,
+    Card,
+    Bank,
+    Other,
+}
+
+impl PaymentMethod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Cash => "cash",
+            Self::Card => "card",
+            Self::Bank => "bank",
+            Self::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, specta::Type)]
+pub struct NewExpense {
+    pub amount: f64,
+    pub category_id: Option<String>,
+    pub note: Option<String>,
+    /// "YYYY-MM-DD"; today if not given.
+    pub date: Option<String>,
+    pub payment_method: Option<PaymentMethod>,
+    /// Currency of `amount`; the base currency if not given.
+    pub currency: Option<String>,
+    pub account_id: Option<String>,
+}
+
+/// Fields to change; missing fields are left alone and `null` clears the
+/// optional ones.
+#[derive(Debug, Clone, Default, Deserialize, specta::Type)]
+pub struct ExpenseUpdate {
+    pub amount: Option<f64>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub category_id: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub note: Option<Option<String>>,
+    pub date: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub payment_method: Option<Option<PaymentMethod>>,
+    /// Currency of the new `amount`, or of the current one if no amount is
+    /// given; `null` means the base currency.
+    #[serde(default, deserialize_with = "nullable")]
+    pub currency: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub account_id: Option<Option<String>>,
+}
+
+pub(crate) const SELECT_WITH_CATEGORY: &str =
+    "SELECT e.*, c.name AS category_name, c.icon AS category_icon, c.color AS category_color
+     FROM expenses e
+     LEFT JOIN categories c ON e.category_id = c.id";
+
+fn check_amount(amount: f64) -> Result<()> {
+    if !amount.is_finite() || amount <= 0.0 {
+        return Err(Error::Validation(
+            "Amount must be a positive number".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) fn parse_date(date: &str) -> Result<NaiveDate> {
+    date.get(..10)
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .ok_or_else(|| Error::Validation(format!("\"{date}\" is not a YYYY-MM-DD date")))
+}
+
+fn clean_note(note: Option<String>) -> Option<String> {
+    note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty())
+}
+
+pub(crate) async fn check_category(conn: &mut SqliteConnection, category_id: &str) -> Result<()> {
+    let found: Option<(String,)> =
+        sqlx::query_as("SELECT id FROM categories WHERE id = $1 AND deleted_at IS NULL")
+            .bind(category_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+    if found.is_none() {
+        return Err(Error::Validation("Unknown category".to_string()));
+    }
+    Ok(())
+}
+
+async fn fetch(conn: &mut SqliteConnection, id: &str) -> Result<Expense> {
+    sqlx::query_as::<_, Expense>("SELECT * FROM expenses WHERE id = $1 AND deleted_at IS NULL")
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| Error::Validation("Expense not found".to_string()))
+}
+
+/// Expenses dated `start..=end` ("YYYY-MM-DD"), newest first.
+pub async fn list(pool: &SqlitePool, start: &str, end: &str) -> Result<Vec<ExpenseWithCategory>> {
+    Ok(sqlx::query_as::<_, ExpenseWithCategory>(&format!(
+        "{SELECT_WITH_CATEGORY}
+         WHERE substr(e.date, 1, 10) BETWEEN $1 AND $2 AND e.deleted_at IS NULL
+         ORDER BY e.date DESC, e.created_at DESC"
+    ))
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn recent(pool: &SqlitePool, limit: i64) -> Result<Vec<ExpenseWithCategory>> {
+    Ok(sqlx::query_as::<_, ExpenseWithCategory>(&format!(
+        "{SELECT_WITH_CATEGORY}
+         WHERE e.deleted_at IS NULL
+         ORDER BY e.date DESC, e.created_at DESC
+         LIMIT $1"
+    ))
+    .bind(limit.max(0))
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Warn if a write pushed the budget or the category over one of its
+/// thresholds. The expense is saved either way, so a failure here is only
+/// logged.
+pub(crate) async fn check_alerts(app: &AppHandle, pool: &SqlitePool, category_id: Option<&str>) {
+    let today = Local::now().date_naive();
+    if let Err(e) = budget_alerts::check(app, pool, today).await {
+        eprintln!("[Expenses] Budget alert check failed: {e}");
+    }
+    let Some(category_id) = category_id else {
+        return;
+    };
+    if let Err(e) = category_alerts::check(app, pool, category_id, today).await {
+        eprintln!("[Expenses] Category alert check failed: {e}");
+    }
+}
+
+pub async fn create(pool: &SqlitePool, mut input: NewExpense, today: NaiveDate) -> Result<Expense> {
+    check_amount(input.amount)?;
+    let date = match &input.date {
+        Some(date) => parse_date(date)?,
+        None => today,
+    };
+    if let Some(code) = &input.currency {
+        currency::ensure(pool, code, date).await?;
+    }
+    if input.category_id.is_none() {
+        let rules = Rules::load(pool).await?;
+        input.category_id = rules
+            .category_for(input.note.as_deref())
+            .map(str::to_string);
+    }
+
+    let mut tx = pool.begin().await?;
+    if let Some(category_id) = &input.category_id {
+        check_category(&mut tx, category_id).await?;
+    }
+    if let Some(account_id) = &input.account_id {
+        accounts::check_account(&mut tx, account_id).await?;
+    }
+    let user_id = auth::user_id_on(&mut tx).await?;
+    let money =
+        currency::convert_on(&mut tx, input.amount, input.currency.as_deref(), date).await?;
+    let expense = insert(&mut tx, user_id.as_deref(), &input, &money, date).await?;
+    tx.commit().await?;
+
+    category_totals::refresh(pool, &[expense.cell()]).await?;
+    accounts::refresh(pool, slice::from_ref(&expense.account_id)).await?;
+    Ok(expense)
+}
+
+/// Insert and queue an already validated expense on `conn`, with `money`
+/// standing in for the input's amount and currency. Leaves the category
+/// totals to the caller.
+pub(crate) async fn insert(
+    conn: &mut SqliteConnection,
+    user_id: Option<&str>,
+    input: &NewExpense,
+    money: &Converted,
+    date: NaiveDate,
+) -> Result<Expense> {
+    let id = new_id();
+    let now = now();
+    let note = clean_note(input.note.clone());
+    let merchant_id = merchants::link(conn, note.as_deref()).await?;
+    sqlx::query(
+        "INSERT INTO expenses
+            (id, user_id, amount, category_id, note, date, payment_method, currency, original_amount,
+             merchant_id, account_id, created_by, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $2, $12, $12)",
+    )
+    .bind(&id)
+    .bind(user_id)
+    .bind(money.amount)
+    .bind(&input.category_id)
+    .bind(note)
+    .bind(date.format("%Y-%m-%d").to_string())
+    .bind(input.payment_method.map(PaymentMethod::as_str))
+    .bind(&money.currency)
+    .bind(money.original_amount)
+    .bind(merchant_id)
+    .bind(&input.account_id)
+    .bind(&now)
+    .execute(&mut *conn)
+    .await?;
+    sync::enqueue(&mut *conn, "expenses", &id, Operation::Insert).await?;
+    fetch(conn, &id).await
+}
+
+pub async fn update(pool: &SqlitePool, id: &str, update: ExpenseUpdate) -> Result<Expense> {
+    let reprice = update.amount.is_some() || update.currency.is_some() || update.date.is_some();
+    if reprice {
+        // Rates can't be fetched inside the transaction.
+        let current: Option<(Option<String>, String)> =
+            sqlx::query_as("SELECT currency, date FROM expenses WHERE id = $1")
+                .bind(id)
+                .fetch_optional(pool)
+                .await?;
+        if let Some((code, date)) = current {
+            let code = update.currency.clone().unwrap_or(code);
+            if let Some(code) = code {
+                let date = parse_date(update.date.as_deref().unwrap_or(&date))?;
+                currency::ensure(pool, &code, date).await?;
+            }
+        }
+    }
+
+    let mut tx = pool.begin().await?;
+    let before = fetch(&mut tx, id).await?;
+
+    let face_amount = update
+        .amount
+        .unwrap_or(before.original_amount.unwrap_or(before.amount));
+    check_amount(face_amount)?;
+    let category_id = match update.category_id {
+        Some(Some(category_id)) if before.category_id.as_ref() != Some(&category_id) => {
+            check_category(&mut tx, &category_id).await?;
+            Some(category_id)
+        }
+        Some(category_id) => category_id,
+        None => before.category_id.clone(),
+    };
+    let date = match &update.date {
+        Some(date) => parse_date(date)?.format("%Y-%m-%d").to_string(),
+        None => before.date.clone(),
+    };
+    let (note, merchant_id) = match update.note {
+        Some(note) => {
+            let note = clean_note(note);
+            let merchant_id = merchants::link(&mut tx, note.as_deref()).await?;
+            (note, merchant_id)
+        }
+        None => (before.note.clone(), before.merchant_id.clone()),
+    };
+    let account_id = match update.account_id {
+        Some(Some(account_id)) if before.account_id.as_ref() != Some(&account_id) => {
+            accounts::check_account(&mut tx, &account_id).await?;
+            Some(account_id)
+        }
+        Some(account_id) => account_id,
+        None => before.account_id.clone(),
+    };
+    let payment_method = match update.payment_method {
+        Some(method) => method.map(|m| m.as_str().to_string()),
+        None => before.payment_method.clone(),
+    };
+    let money = if reprice {
+        let code = update.currency.unwrap_or_else(|| before.currency.clone());
+        currency::convert_on(&mut tx, face_amount, code.as_deref(), parse_date(&date)?).await?
+    } else {
+        Converted {
+            amount: before.amount,
+            currency: before.currency.clone(),
+            original_amount: before.original_amount,
+        }
+    };
+
+    sqlx::query(
+        "UPDATE expenses
+         SET amount = $1, category_id = $2, note = $3, date = $4, payment_method = $5,
+             currency = $6, original_amount = $7, merchant_id = $8, account_id = $9,
+             updated_at = $10
+         WHERE id = $11",
+    )
+    .bind(money.amount)
+    .bind(&category_id)
+    .bind(note)
+    .bind(date)
+    .bind(payment_method)
+    .bind(&money.currency)
+    .bind(money.original_amount)
+    .bind(merchant_id)
+    .bind(&account_id)
+    .bind(now())
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+    sync::enqueue(&mut tx, "expenses", id, Operation::Update).await?;
+    let expense = fetch(&mut tx, id).await?;
+    tx.commit().await?;
+
+    category_totals::refresh(pool, &[before.cell(), expense.cell()]).await?;
+    accounts::refresh(pool, &[before.account_id, expense.account_id.clone()]).await?;
+    Ok(expense)
+}
+
+pub async fn delete(pool: &SqlitePool, id: &str) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    let before = fetch(&mut tx, id).await?;
+    // Kept as a tombstone until the trash is purged.
+    let now = now();
+    sqlx::query("UPDATE expenses SET deleted_at = $1, updated_at = $1 WHERE id = $2")
+        .bind(&now)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    sync::enqueue_delete(&mut tx, "expenses", id, &now).await?;
+    tx.commit().await?;
+
+    category_totals::refresh(pool, &[before.cell()]).await?;
+    accounts::refresh(pool, &[before.account_id]).await
+}
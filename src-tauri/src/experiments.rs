@@ -0,0 +1,143 @@
+//! A/B experiments.
+//!
+//! Each install lands in a variant by hashing its install id with the
+//! experiment key, so the split is deterministic and needs no server. The
+//! first assignment is stored and kept even if the weights change later;
+//! only a variant that was removed from the experiment gets redrawn.
+//! Exposure, the first time the variant actually made a difference on
+//! screen, goes to the telemetry queue once.
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+
+use crate::app_meta;
+use crate::db::now;
+use crate::error::Result;
+use crate::telemetry;
+
+/// Hash space the weights are spread over.
+const BUCKETS: u64 = 10_000;
+
+struct Experiment {
+    key: &'static str,
+    /// Variant names with relative weights.
+    variants: &'static [(&'static str, u32)],
+}
+
+/// Running experiments. Add an entry to start one; remove it to end it,
+/// after which `get_variant` returns `None` for it.
+const EXPERIMENTS: &[Experiment] = &[];
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct Assignment {
+    pub experiment: String,
+    pub variant: String,
+    pub assigned_at: String,
+    pub exposed_at: Option<String>,
+}
+
+fn find(key: &str) -> Option<&'static Experiment> {
+    EXPERIMENTS.iter().find(|e| e.key == key)
+}
+
+/// Pick a variant for `install_id` in proportion to the weights.
+fn draw(experiment: &Experiment, install_id: &str) -> Option<&'static str> {
+    let total: u64 = experiment.variants.iter().map(|(_, w)| u64::from(*w)).sum();
+    if total == 0 {
+        return None;
+    }
+    let digest = Sha256::digest(format!("{}:{install_id}", experiment.key));
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    let point = u64::from_be_bytes(bytes) % BUCKETS * total / BUCKETS;
+
+    let mut upper = 0;
+    for (name, weight) in experiment.variants {
+        upper += u64::from(*weight);
+        if point < upper {
+            return Some(name);
+        }
+    }
+    None
+}
+
+async fn stored(pool: &SqlitePool, experiment: &str) -> Result<Option<Assignment>> {
+    let row: Option<(String, String, Option<String>)> = sqlx::query_as(
+        "SELECT variant, assigned_at, exposed_at FROM experiment_assignments WHERE experiment = $1",
+    )
+    .bind(experiment)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|(variant, assigned_at, exposed_at)| Assignment {
+        experiment: experiment.to_string(),
+        variant,
+        assigned_at,
+        exposed_at,
+    }))
+}
+
+/// This install's variant, assigning one on first use. `None` when no such
+/// experiment is running.
+pub async fn get_variant(pool: &SqlitePool, key: &str) -> Result<Option<Assignment>> {
+    let Some(experiment) = find(key) else {
+        return Ok(None);
+    };
+    if let Some(assignment) = stored(pool, key).await? {
+        if experiment
+            .variants
+            .iter()
+            .any(|(name, _)| *name == assignment.variant)
+        {
+            return Ok(Some(assignment));
+        }
+    }
+
+    let Some(install_id) = app_meta::install_id(pool).await? else {
+        return Ok(None);
+    };
+    let Some(variant) = draw(experiment, &install_id) else {
+        return Ok(None);
+    };
+    sqlx::query(
+        "INSERT INTO experiment_assignments (experiment, variant, assigned_at) VALUES ($1, $2, $3)
+         ON CONFLICT(experiment) DO UPDATE SET
+           variant = excluded.variant, assigned_at = excluded.assigned_at, exposed_at = NULL",
+    )
+    .bind(key)
+    .bind(variant)
+    .bind(now())
+    .execute(pool)
+    .await?;
+    stored(pool, key).await
+}
+
+/// Note that the user saw the experiment. Only the first exposure is
+/// queued.
+pub async fn log_exposure(pool: &SqlitePool, key: &str) -> Result<()> {
+    let Some(assignment) = get_variant(pool, key).await? else {
+        return Ok(());
+    };
+    if assignment.exposed_at.is_some() {
+        return Ok(());
+    }
+    let first = sqlx::query(
+        "UPDATE experiment_assignments SET exposed_at = $1
+         WHERE experiment = $2 AND exposed_at IS NULL",
+    )
+    .bind(now())
+    .bind(key)
+    .execute(pool)
+    .await?
+    .rows_affected()
+        > 0;
+    if first {
+        telemetry::record(
+            pool,
+            "experiment_exposure",
+            serde_json::json!({ "experiment": key, "variant": assignment.variant }),
+        )
+        .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,44 @@
+//! Expenses as CSV, one row each, for spreadsheets and scripts.
+
+use crate::error::{Error, Result};
+use crate::expenses::ExpenseWithCategory;
+
+const HEADER: [&str; 8] = [
+    "date",
+    "amount",
+    "category",
+    "note",
+    "payment_method",
+    "currency",
+    "original_amount",
+    "id",
+];
+
+/// `expenses` as CSV with a header row. Amounts are in the base currency;
+/// `currency` and `original_amount` are set for spending paid in another.
+pub fn to_csv(expenses: &[ExpenseWithCategory]) -> Result<String> {
+    let error = |e: csv::Error| Error::Validation(format!("Failed to write CSV: {e}"));
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(HEADER).map_err(error)?;
+    for row in expenses {
+        let e = &row.expense;
+        writer
+            .write_record([
+                e.date.as_str(),
+                &format!("{:.2}", e.amount),
+                row.category_name.as_deref().unwrap_or_default(),
+                e.note.as_deref().unwrap_or_default(),
+                e.payment_method.as_deref().unwrap_or_default(),
+                e.currency.as_deref().unwrap_or_default(),
+                &e.original_amount
+                    .map(|a| format!("{a:.2}"))
+                    .unwrap_or_default(),
+                &e.id,
+            ])
+            .map_err(error)?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| Error::Validation(format!("Failed to write CSV: {e}")))?;
+    String::from_utf8(bytes).map_err(|e| Error::Validation(format!("Failed to write CSV: {e}")))
+}
@@ -0,0 +1,174 @@
+//! The dates the app reminds about, as an iCalendar file for calendar apps.
+//!
+//! The next year of monthly check-ins comes from the check-in schedule,
+//! one event each, since a cron expression doesn't translate into a
+//! repeat rule. Every bill is a monthly event from its open due date with
+//! an alarm `remind_days_before` it. Savings goals appear on their target
+//! date. UIDs are stable, so importing a fresh file updates the events
+//! instead of duplicating them.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use sqlx::SqlitePool;
+
+use crate::error::Result;
+use crate::notify::scheduler;
+use crate::{bills, currency, goals, preferences};
+
+/// Check-ins listed ahead.
+const CHECKINS: usize = 12;
+
+const FILE_NAME: &str = "goaldy-calendar.ics";
+
+/// Escape a TEXT value.
+fn text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Append `line` folded at 75 octets, with CRLF endings.
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn date(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+fn date_time(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// The repeat rule of a bill due on `due_day`. Late days fall back to the
+/// last day of shorter months, as the bill's due date does.
+fn monthly_rule(due_day: i64) -> String {
+    let day = due_day.clamp(1, 31);
+    if day <= 28 {
+        return format!("RRULE:FREQ=MONTHLY;BYMONTHDAY={day}");
+    }
+    let days: Vec<String> = (28..=day).map(|d| d.to_string()).collect();
+    format!(
+        "RRULE:FREQ=MONTHLY;BYMONTHDAY={};BYSETPOS=-1",
+        days.join(",")
+    )
+}
+
+/// The calendar as of `today`.
+pub async fn build(pool: &SqlitePool, today: NaiveDate) -> Result<String> {
+    let stamp = date_time(Utc::now());
+    let base = currency::base(pool).await?;
+    let mut lines: Vec<String> = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Goaldy//Goaldy//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "X-WR-CALNAME:Goaldy".to_string(),
+    ];
+
+    let preferences = preferences::get_or_create_notification_preferences(pool).await?;
+    if preferences.monthly_checkin_enabled {
+        let mut after = Local::now();
+        for _ in 0..CHECKINS {
+            let at = scheduler::next_fire(&preferences.monthly_checkin_cron, after)?;
+            lines.extend([
+                "BEGIN:VEVENT".to_string(),
+                format!("UID:checkin-{}@goaldy.app", at.format("%Y%m%d")),
+                format!("DTSTAMP:{stamp}"),
+                format!("DTSTART:{}", date_time(at)),
+                "DURATION:PT15M".to_string(),
+                "SUMMARY:Goaldy monthly check-in".to_string(),
+                "DESCRIPTION:Log last month's savings and habits.".to_string(),
+                "END:VEVENT".to_string(),
+            ]);
+            after = at.with_timezone(&Local);
+        }
+    }
+
+    for due in bills::list(pool, today).await? {
+        let bill = &due.bill;
+        let Ok(start) = NaiveDate::parse_from_str(&due.due_date, "%Y-%m-%d") else {
+            continue;
+        };
+        let mut event = vec![
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:bill-{}@goaldy.app", bill.id),
+            format!("DTSTAMP:{stamp}"),
+            format!("DTSTART;VALUE=DATE:{}", date(start)),
+            monthly_rule(bill.due_day),
+            format!(
+                "SUMMARY:{}",
+                text(&format!("{} due ({:.2} {base})", bill.name, bill.amount))
+            ),
+        ];
+        if bill.autopay {
+            event.push("DESCRIPTION:Paid automatically.".to_string());
+        } else {
+            event.extend([
+                "BEGIN:VALARM".to_string(),
+                "ACTION:DISPLAY".to_string(),
+                format!("DESCRIPTION:{}", text(&format!("{} is due", bill.name))),
+                format!("TRIGGER:-P{}D", bill.remind_days_before.max(0)),
+                "END:VALARM".to_string(),
+            ]);
+        }
+        event.push("END:VEVENT".to_string());
+        lines.extend(event);
+    }
+
+    for goal in goals::list(pool).await? {
+        let Ok(target) = NaiveDate::parse_from_str(&goal.target_date, "%Y-%m-%d") else {
+            continue;
+        };
+        let saved = goals::total_saved(pool, &goal.id).await?;
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:goal-{}@goaldy.app", goal.id),
+            format!("DTSTAMP:{stamp}"),
+            format!("DTSTART;VALUE=DATE:{}", date(target)),
+            format!("SUMMARY:{}", text(&format!("Savings goal: {}", goal.name))),
+            format!(
+                "DESCRIPTION:{}",
+                text(&format!(
+                    "Target {:.2} {base}, saved {saved:.2} {base}.",
+                    goal.target_amount
+                ))
+            ),
+            "TRANSP:TRANSPARENT".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+    let mut out = String::new();
+    for line in &lines {
+        push_line(&mut out, line);
+    }
+    Ok(out)
+}
+
+/// Write the calendar to `dir`, replacing the last one.
+pub async fn export(pool: &SqlitePool, dir: &Path, today: NaiveDate) -> Result<PathBuf> {
+    let path = dir.join(FILE_NAME);
+    std::fs::write(&path, build(pool, today).await?)?;
+    Ok(path)
+}
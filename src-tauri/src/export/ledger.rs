@@ -0,0 +1,457 @@
+//! Plain-text accounting files, for people who keep their books in
+//! ledger-cli or beancount and use the app to capture spending.
+//!
+//! Expenses, income and transfers between accounts become balanced
+//! transactions, along with the opening balance of accounts opened in the
+//! range. Every category, income source and account is booked to an
+//! account name: "Expenses:<category>", "Income:<source>", and
+//! "Assets:<account>" or "Liabilities:<card>" unless the mapping says
+//! otherwise. Spending not tied to an account comes out of
+//! `default_account`. The mapping is kept for the next export.
+//!
+//! Amounts are in the base currency; spending paid in another currency is
+//! booked in that currency at its total cost in the base one.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use super::snapshot::{parse_range, DateRange};
+use crate::app_meta;
+use crate::currency;
+use crate::error::{Error, Result};
+
+const MAPPING_KEY: &str = "ledger_account_mapping";
+
+const DEFAULT_ACCOUNT: &str = "Assets:Cash";
+const UNCATEGORIZED: &str = "Expenses:Uncategorized";
+const OPENING_BALANCES: &str = "Equity:Opening-Balances";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "lowercase")]
+pub enum LedgerFormat {
+    Ledger,
+    Beancount,
+}
+
+impl LedgerFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Ledger => "ledger",
+            Self::Beancount => "beancount",
+        }
+    }
+}
+
+/// Account names by id. Anything left out gets its default name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, specta::Type)]
+pub struct AccountMapping {
+    /// Category id to account, such as "Expenses:Food:Groceries".
+    pub categories: HashMap<String, String>,
+    /// Income source id to account.
+    pub income_sources: HashMap<String, String>,
+    /// The app's accounts by id.
+    pub accounts: HashMap<String, String>,
+    /// For spending and income without an account; "Assets:Cash" if not
+    /// given.
+    pub default_account: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct LedgerExport {
+    pub path: PathBuf,
+    pub transactions: usize,
+}
+
+struct Posting {
+    account: String,
+    /// In the base currency.
+    amount: f64,
+    /// What was paid and in which currency, when not the base one.
+    original: Option<(f64, String)>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ExpenseRow {
+    date: String,
+    amount: f64,
+    note: Option<String>,
+    category_id: Option<String>,
+    currency: Option<String>,
+    original_amount: Option<f64>,
+    account_id: Option<String>,
+    category_name: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct IncomeRow {
+    date: String,
+    amount: f64,
+    note: Option<String>,
+    source_id: String,
+    account_id: Option<String>,
+    source_name: String,
+}
+
+struct Transaction {
+    date: String,
+    description: String,
+    postings: [Posting; 2],
+}
+
+/// One component of an account name: letters, digits and dashes, starting
+/// with a capital.
+fn component(name: &str) -> String {
+    let mut out = String::new();
+    let mut dash = false;
+    for c in name.trim().chars() {
+        if c.is_alphanumeric() {
+            if dash && !out.is_empty() {
+                out.push('-');
+            }
+            dash = false;
+            if out.is_empty() {
+                out.extend(c.to_uppercase());
+            } else {
+                out.push(c);
+            }
+        } else {
+            dash = true;
+        }
+    }
+    if out.is_empty() {
+        "Other".to_string()
+    } else {
+        out
+    }
+}
+
+/// `name` as a valid account name, each `:`-separated part cleaned up.
+fn account_name(name: &str) -> String {
+    name.split(':').map(component).collect::<Vec<_>>().join(":")
+}
+
+/// The stored mapping, or an empty one.
+pub async fn mapping(pool: &SqlitePool) -> Result<AccountMapping> {
+    Ok(app_meta::get(pool, MAPPING_KEY)
+        .await?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+async fn save_mapping(pool: &SqlitePool, mapping: &AccountMapping) -> Result<()> {
+    let json = serde_json::to_string(mapping)
+        .map_err(|e| Error::Validation(format!("Invalid account mapping: {e}")))?;
+    app_meta::set(pool, MAPPING_KEY, &json).await
+}
+
+/// Every category, income source and account with the name it is exported
+/// under, for showing and editing the mapping.
+pub async fn resolved_mapping(pool: &SqlitePool) -> Result<AccountMapping> {
+    let stored = mapping(pool).await?;
+    let categories: Vec<(String, String)> =
+        sqlx::query_as("SELECT id, name FROM categories WHERE deleted_at IS NULL")
+            .fetch_all(pool)
+            .await?;
+    let sources: Vec<(String, String)> =
+        sqlx::query_as("SELECT id, name FROM income_sources WHERE deleted_at IS NULL")
+            .fetch_all(pool)
+            .await?;
+    let accounts: Vec<(String, String, String)> =
+        sqlx::query_as("SELECT id, name, kind FROM accounts WHERE deleted_at IS NULL")
+            .fetch_all(pool)
+            .await?;
+    let pick = |map: &HashMap<String, String>, id: &str, default: String| {
+        map.get(id).map_or(default, |name| account_name(name))
+    };
+    Ok(AccountMapping {
+        categories: categories
+            .into_iter()
+            .map(|(id, name)| {
+                let account = pick(&stored.categories, &id, category_default(&name));
+                (id, account)
+            })
+            .collect(),
+        income_sources: sources
+            .into_iter()
+            .map(|(id, name)| {
+                let account = pick(&stored.income_sources, &id, source_default(&name));
+                (id, account)
+            })
+            .collect(),
+        accounts: accounts
+            .into_iter()
+            .map(|(id, name, kind)| {
+                let account = pick(&stored.accounts, &id, account_default(&name, &kind));
+                (id, account)
+            })
+            .collect(),
+        default_account: Some(account_name(
+            stored.default_account.as_deref().unwrap_or(DEFAULT_ACCOUNT),
+        )),
+    })
+}
+
+fn category_default(name: &str) -> String {
+    format!("Expenses:{}", component(name))
+}
+
+fn source_default(name: &str) -> String {
+    format!("Income:{}", component(name))
+}
+
+fn account_default(name: &str, kind: &str) -> String {
+    let root = if kind == "credit_card" {
+        "Liabilities"
+    } else {
+        "Assets"
+    };
+    format!("{root}:{}", component(name))
+}
+
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Expenses, income, transfers and opening balances in `range`, oldest
+/// first.
+async fn transactions(
+    pool: &SqlitePool,
+    range: &DateRange,
+    mapping: &AccountMapping,
+) -> Result<Vec<Transaction>> {
+    let (start, end) = (&range.start_date, &range.end_date);
+    let funding = mapping
+        .default_account
+        .clone()
+        .unwrap_or_else(|| DEFAULT_ACCOUNT.to_string());
+    let account = |id: &Option<String>| {
+        id.as_ref()
+            .and_then(|id| mapping.accounts.get(id))
+            .cloned()
+            .unwrap_or_else(|| funding.clone())
+    };
+    let mut out = Vec::new();
+
+    let expenses = sqlx::query_as::<_, ExpenseRow>(
+        "SELECT substr(e.date, 1, 10) AS date, e.amount, e.note, e.category_id, e.currency,
+                e.original_amount, e.account_id, c.name AS category_name
+         FROM expenses e
+         LEFT JOIN categories c ON c.id = e.category_id
+         WHERE e.deleted_at IS NULL AND substr(e.date, 1, 10) BETWEEN $1 AND $2",
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await?;
+    for expense in expenses {
+        let target = expense
+            .category_id
+            .as_ref()
+            .and_then(|id| mapping.categories.get(id))
+            .cloned()
+            .unwrap_or_else(|| UNCATEGORIZED.to_string());
+        let description = expense
+            .note
+            .or(expense.category_name)
+            .map_or_else(|| "Expense".to_string(), |d| one_line(&d));
+        out.push(Transaction {
+            date: expense.date,
+            description,
+            postings: [
+                Posting {
+                    account: target,
+                    amount: expense.amount,
+                    original: expense.original_amount.zip(expense.currency),
+                },
+                Posting {
+                    account: account(&expense.account_id),
+                    amount: -expense.amount,
+                    original: None,
+                },
+            ],
+        });
+    }
+
+    let income = sqlx::query_as::<_, IncomeRow>(
+        "SELECT substr(i.date, 1, 10) AS date, i.amount, i.note, i.source_id, i.account_id,
+                s.name AS source_name
+         FROM income_entries i
+         JOIN income_sources s ON s.id = i.source_id
+         WHERE i.deleted_at IS NULL AND substr(i.date, 1, 10) BETWEEN $1 AND $2",
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await?;
+    for entry in income {
+        let from = mapping
+            .income_sources
+            .get(&entry.source_id)
+            .cloned()
+            .unwrap_or_else(|| source_default(&entry.source_name));
+        out.push(Transaction {
+            date: entry.date,
+            description: one_line(&entry.note.unwrap_or(entry.source_name)),
+            postings: [
+                Posting {
+                    account: account(&entry.account_id),
+                    amount: entry.amount,
+                    original: None,
+                },
+                Posting {
+                    account: from,
+                    amount: -entry.amount,
+                    original: None,
+                },
+            ],
+        });
+    }
+
+    let transfers: Vec<(String, f64, Option<String>, String, String)> = sqlx::query_as(
+        "SELECT substr(date, 1, 10), amount, note, from_account_id, to_account_id
+         FROM account_transfers
+         WHERE deleted_at IS NULL AND substr(date, 1, 10) BETWEEN $1 AND $2",
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await?;
+    for (date, amount, note, from, to) in transfers {
+        out.push(Transaction {
+            date,
+            description: note.map_or_else(|| "Transfer".to_string(), |n| one_line(&n)),
+            postings: [
+                Posting {
+                    account: account(&Some(to)),
+                    amount,
+                    original: None,
+                },
+                Posting {
+                    account: account(&Some(from)),
+                    amount: -amount,
+                    original: None,
+                },
+            ],
+        });
+    }
+
+    let openings: Vec<(String, String, f64, String)> = sqlx::query_as(
+        "SELECT id, name, opening_balance, substr(opening_date, 1, 10) FROM accounts
+         WHERE deleted_at IS NULL AND opening_balance != 0
+           AND substr(opening_date, 1, 10) BETWEEN $1 AND $2",
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await?;
+    for (id, name, balance, date) in openings {
+        out.push(Transaction {
+            date,
+            description: format!("Opening balance of {}", one_line(&name)),
+            postings: [
+                Posting {
+                    account: account(&Some(id)),
+                    amount: balance,
+                    original: None,
+                },
+                Posting {
+                    account: OPENING_BALANCES.to_string(),
+                    amount: -balance,
+                    original: None,
+                },
+            ],
+        });
+    }
+
+    out.sort_by(|a, b| a.date.cmp(&b.date));
+    Ok(out)
+}
+
+fn amount(posting: &Posting, base: &str) -> String {
+    match &posting.original {
+        Some((original, code)) if code != base => {
+            format!("{original:.2} {code} @@ {:.2} {base}", posting.amount.abs())
+        }
+        _ => format!("{:.2} {base}", posting.amount),
+    }
+}
+
+fn render(format: LedgerFormat, base: &str, transactions: &[Transaction]) -> String {
+    let mut out = String::new();
+    match format {
+        LedgerFormat::Ledger => {
+            let _ = writeln!(out, "; Exported from Goaldy\n");
+        }
+        LedgerFormat::Beancount => {
+            let _ = writeln!(out, "; Exported from Goaldy");
+            let _ = writeln!(out, "option \"operating_currency\" \"{base}\"\n");
+            let opened: BTreeSet<&str> = transactions
+                .iter()
+                .flat_map(|t| t.postings.iter().map(|p| p.account.as_str()))
+                .collect();
+            if let Some(first) = transactions.first() {
+                for account in &opened {
+                    let _ = writeln!(out, "{} open {account}", first.date);
+                }
+                out.push('\n');
+            }
+        }
+    }
+    for transaction in transactions {
+        match format {
+            LedgerFormat::Ledger => {
+                let _ = writeln!(out, "{} * {}", transaction.date, transaction.description);
+            }
+            LedgerFormat::Beancount => {
+                let _ = writeln!(
+                    out,
+                    "{} * \"{}\"",
+                    transaction.date,
+                    transaction
+                        .description
+                        .replace('\\', "\\\\")
+                        .replace('"', "\\\"")
+                );
+            }
+        }
+        for posting in &transaction.postings {
+            let _ = writeln!(out, "  {}  {}", posting.account, amount(posting, base));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Write the transactions in `range` to `dir` as a ledger or beancount
+/// file. A given mapping is stored and used from then on.
+pub async fn export(
+    pool: &SqlitePool,
+    dir: &Path,
+    format: LedgerFormat,
+    range: &DateRange,
+    mapping: Option<AccountMapping>,
+) -> Result<LedgerExport> {
+    parse_range(range)?;
+    if let Some(mapping) = &mapping {
+        save_mapping(pool, mapping).await?;
+    }
+    let resolved = resolved_mapping(pool).await?;
+    let transactions = transactions(pool, range, &resolved).await?;
+    let base = currency::base(pool).await?;
+
+    let path = dir.join(format!(
+        "goaldy-{}-to-{}.{}",
+        range.start_date,
+        range.end_date,
+        format.extension()
+    ));
+    std::fs::write(&path, render(format, &base, &transactions))?;
+    Ok(LedgerExport {
+        path,
+        transactions: transactions.len(),
+    })
+}
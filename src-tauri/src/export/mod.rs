@@ -0,0 +1,40 @@
+//! Files generated for people or tools outside the app.
+
+pub mod expenses;
+pub mod ical;
+pub mod ledger;
+pub mod snapshot;
+pub mod xlsx;
+
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Manager};
+
+use crate::error::Result;
+
+/// Where exports are written: the user's downloads folder, falling back to
+/// `<app data>/exports` on platforms without one.
+pub fn output_dir(app: &AppHandle) -> Result<PathBuf> {
+    let dir = match app.path().download_dir() {
+        Ok(dir) => dir,
+        Err(_) => app.path().app_data_dir()?.join("exports"),
+    };
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Minimal HTML escaping for text we put into generated pages.
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,304 @@
+//! Read-only snapshot of the user's finances to share with a coach or
+//! partner who doesn't use the app.
+//!
+//! The snapshot is a static HTML page plus the same data as JSON. The
+//! privacy level decides what leaves the device: never individual expenses,
+//! and at the stricter levels no absolute amounts or goal names either.
+
+use std::path::PathBuf;
+
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use super::escape_html;
+use crate::analysis::{projection, spending};
+use crate::db::now;
+use crate::error::{Error, Result};
+use crate::goals;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "lowercase")]
+pub enum Privacy {
+    /// Amounts, categories and goal names.
+    Full,
+    /// Percentages and shares only, no amounts.
+    Relative,
+    /// Like `Relative`, with goal names replaced by "Goal 1", "Goal 2", ...
+    Anonymous,
+}
+
+impl Privacy {
+    fn shows_amounts(self) -> bool {
+        self == Self::Full
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, specta::Type)]
+pub struct DateRange {
+    /// "YYYY-MM-DD", inclusive.
+    pub start_date: String,
+    /// "YYYY-MM-DD", inclusive.
+    pub end_date: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Snapshot {
+    pub generated_at: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub privacy: Privacy,
+    pub spending: SpendingSnapshot,
+    pub goals: Vec<GoalSnapshot>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SpendingSnapshot {
+    pub net: Option<f64>,
+    pub budget: Option<f64>,
+    /// Net spend as a fraction of the budget for the months in range.
+    pub budget_used: Option<f64>,
+    pub categories: Vec<CategoryShare>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryShare {
+    pub name: String,
+    pub amount: Option<f64>,
+    /// Fraction of net spend.
+    pub share: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GoalSnapshot {
+    pub name: String,
+    /// Fraction of the target saved, `0.0..=1.0`.
+    pub progress: f64,
+    pub on_track: bool,
+    pub projected_completion: Option<String>,
+    pub saved: Option<f64>,
+    pub target: Option<f64>,
+}
+
+/// Paths of the files written by `export_snapshot`.
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct SnapshotFiles {
+    pub html: PathBuf,
+    pub json: PathBuf,
+}
+
+#[derive(sqlx::FromRow)]
+struct CategoryRow {
+    name: String,
+    total: f64,
+}
+
+pub(super) fn parse_range(range: &DateRange) -> Result<(NaiveDate, NaiveDate)> {
+    let parse = |d: &str| {
+        NaiveDate::parse_from_str(d, "%Y-%m-%d")
+            .map_err(|_| Error::Validation(format!("Invalid date: {d}")))
+    };
+    let (start, end) = (parse(&range.start_date)?, parse(&range.end_date)?);
+    if start > end {
+        return Err(Error::Validation(
+            "Start date must be before end date".to_string(),
+        ));
+    }
+    Ok((start, end))
+}
+
+pub async fn build(pool: &SqlitePool, range: &DateRange, privacy: Privacy) -> Result<Snapshot> {
+    let (start, end) = parse_range(range)?;
+    let summary = spending::summary(pool, &range.start_date, &range.end_date, None).await?;
+
+    let (budget,): (Option<f64>,) = sqlx::query_as(
+        "SELECT SUM(COALESCE(spending_limit, total_amount)) FROM budgets
+         WHERE deleted_at IS NULL AND month >= $1 AND month <= $2",
+    )
+    .bind(start.format("%Y-%m").to_string())
+    .bind(end.format("%Y-%m").to_string())
+    .fetch_one(pool)
+    .await?;
+
+    let rows = sqlx::query_as::<_, CategoryRow>(
+        "SELECT COALESCE(c.name, 'Uncategorized') AS name,
+                SUM(CASE WHEN e.reimbursement_status = 'received' THEN 0 ELSE e.amount END) AS total
+         FROM expenses e
+         LEFT JOIN categories c ON e.category_id = c.id
+         WHERE e.deleted_at IS NULL AND e.date >= $1 AND e.date <= $2
+         GROUP BY COALESCE(c.name, 'Uncategorized')
+         HAVING total > 0
+         ORDER BY total DESC",
+    )
+    .bind(&range.start_date)
+    .bind(&range.end_date)
+    .fetch_all(pool)
+    .await?;
+
+    let amounts = privacy.shows_amounts();
+    let categories = rows
+        .into_iter()
+        .map(|row| CategoryShare {
+            share: if summary.net > 0.0 {
+                row.total / summary.net
+            } else {
+                0.0
+            },
+            amount: amounts.then_some(row.total),
+            name: row.name,
+        })
+        .collect();
+
+    let today = Local::now().date_naive();
+    let mut goal_snapshots = Vec::new();
+    for (i, goal) in goals::list(pool).await?.into_iter().enumerate() {
+        let saved = goals::total_saved(pool, &goal.id).await?;
+        let projection = projection::project(&goal, saved, today);
+        goal_snapshots.push(GoalSnapshot {
+            name: if privacy == Privacy::Anonymous {
+                format!("Goal {}", i + 1)
+            } else {
+                goal.name.clone()
+            },
+            progress: if goal.target_amount > 0.0 {
+                (saved / goal.target_amount).clamp(0.0, 1.0)
+            } else {
+                0.0
+            },
+            on_track: projection.on_track,
+            projected_completion: projection.projected_completion,
+            saved: amounts.then_some(saved),
+            target: amounts.then_some(goal.target_amount),
+        });
+    }
+
+    Ok(Snapshot {
+        generated_at: now(),
+        start_date: range.start_date.clone(),
+        end_date: range.end_date.clone(),
+        privacy,
+        spending: SpendingSnapshot {
+            net: amounts.then_some(summary.net),
+            budget: budget.filter(|_| amounts),
+            budget_used: budget.filter(|b| *b > 0.0).map(|b| summary.net / b),
+            categories,
+        },
+        goals: goal_snapshots,
+    })
+}
+
+fn percent(fraction: f64) -> String {
+    format!("{:.0}%", fraction * 100.0)
+}
+
+fn money(amount: f64) -> String {
+    format!("{amount:.2}")
+}
+
+/// Render the snapshot as a self-contained page (inline CSS, no scripts).
+pub fn render_html(snapshot: &Snapshot) -> String {
+    let mut html = String::new();
+    html.push_str(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>Goaldy snapshot</title>\n<style>\n\
+         body{font-family:system-ui,sans-serif;max-width:40rem;margin:2rem auto;padding:0 1rem;color:#111}\n\
+         h1{font-size:1.5rem}h2{font-size:1.1rem;margin-top:2rem}\n\
+         table{width:100%;border-collapse:collapse}td{padding:.35rem 0;border-bottom:1px solid #eee}\n\
+         td.num{text-align:right;font-variant-numeric:tabular-nums}\n\
+         .bar{height:.5rem;background:#eee;border-radius:.25rem}.bar>div{height:100%;background:#22c55e;border-radius:.25rem}\n\
+         .muted{color:#6b7280;font-size:.875rem}\n\
+         </style>\n</head>\n<body>\n",
+    );
+    html.push_str(&format!(
+        "<h1>Snapshot {} to {}</h1>\n<p class=\"muted\">Generated {} from Goaldy. Read-only.</p>\n",
+        escape_html(&snapshot.start_date),
+        escape_html(&snapshot.end_date),
+        escape_html(
+            snapshot
+                .generated_at
+                .get(..10)
+                .unwrap_or(&snapshot.generated_at)
+        ),
+    ));
+
+    let spending = &snapshot.spending;
+    html.push_str("<h2>Spending</h2>\n<table>\n");
+    if let Some(net) = spending.net {
+        html.push_str(&format!(
+            "<tr><td>Spent</td><td class=\"num\">{}</td></tr>\n",
+            money(net)
+        ));
+    }
+    if let Some(budget) = spending.budget {
+        html.push_str(&format!(
+            "<tr><td>Budget</td><td class=\"num\">{}</td></tr>\n",
+            money(budget)
+        ));
+    }
+    if let Some(used) = spending.budget_used {
+        html.push_str(&format!(
+            "<tr><td>Budget used</td><td class=\"num\">{}</td></tr>\n",
+            percent(used)
+        ));
+    }
+    html.push_str("</table>\n");
+
+    if !spending.categories.is_empty() {
+        html.push_str("<h2>By category</h2>\n<table>\n");
+        for category in &spending.categories {
+            let amount = category
+                .amount
+                .map(|a| format!("{} · ", money(a)))
+                .unwrap_or_default();
+            html.push_str(&format!(
+                "<tr><td>{}</td><td class=\"num\">{amount}{}</td></tr>\n",
+                escape_html(&category.name),
+                percent(category.share),
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+
+    if !snapshot.goals.is_empty() {
+        html.push_str("<h2>Savings goals</h2>\n");
+        for goal in &snapshot.goals {
+            let amounts = match (goal.saved, goal.target) {
+                (Some(saved), Some(target)) => format!(" · {} of {}", money(saved), money(target)),
+                _ => String::new(),
+            };
+            let status = match (&goal.projected_completion, goal.on_track) {
+                (Some(date), true) => format!("On track, expected {}", escape_html(date)),
+                (Some(date), false) => format!("Behind, expected {}", escape_html(date)),
+                (None, _) => "No monthly contribution set".to_string(),
+            };
+            html.push_str(&format!(
+                "<p><strong>{}</strong> {}{amounts}<br><span class=\"muted\">{status}</span></p>\n\
+                 <div class=\"bar\"><div style=\"width:{:.0}%\"></div></div>\n",
+                escape_html(&goal.name),
+                percent(goal.progress),
+                goal.progress * 100.0,
+            ));
+        }
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Write `<stem>.html` and `<stem>.json` into `dir`.
+pub fn write(snapshot: &Snapshot, dir: &std::path::Path) -> Result<SnapshotFiles> {
+    let stem = format!(
+        "goaldy-snapshot-{}-to-{}",
+        snapshot.start_date, snapshot.end_date
+    );
+    let files = SnapshotFiles {
+        html: dir.join(format!("{stem}.html")),
+        json: dir.join(format!("{stem}.json")),
+    };
+    let json = serde_json::to_string_pretty(snapshot)
+        .map_err(|e| Error::Validation(format!("failed to encode snapshot: {e}")))?;
+    std::fs::write(&files.html, render_html(snapshot))?;
+    std::fs::write(&files.json, json)?;
+    Ok(files)
+}
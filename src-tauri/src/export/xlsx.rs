@@ -0,0 +1,319 @@
+//! An Excel workbook of a date range, for sharing finances with people who
+//! live in spreadsheets.
+//!
+//! Four sheets: every expense, the budgets of periods starting in the range
+//! with what was spent, the savings goals with their progress, and a
+//! summary of spending per category and month. Amounts are in the base
+//! currency and, like the reports, spending that was paid back doesn't
+//! count in the summary.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+use sqlx::SqlitePool;
+
+use super::snapshot::{parse_range, DateRange};
+use crate::analysis::spending::NET_AMOUNT;
+use crate::budgets;
+use crate::currency;
+use crate::error::{Error, Result};
+use crate::expenses::{self, ExpenseWithCategory};
+use crate::goals::{self, SavingsGoal};
+use crate::periods::{self, Period};
+
+struct Formats {
+    header: Format,
+    money: Format,
+    date: Format,
+    percent: Format,
+    total: Format,
+}
+
+impl Formats {
+    fn new() -> Self {
+        Self {
+            header: Format::new().set_bold(),
+            money: Format::new().set_num_format("#,##0.00"),
+            date: Format::new().set_num_format("yyyy-mm-dd"),
+            percent: Format::new().set_num_format("0%"),
+            total: Format::new().set_bold().set_num_format("#,##0.00"),
+        }
+    }
+}
+
+struct BudgetRow {
+    period: Period,
+    total_amount: f64,
+    spending_limit: Option<f64>,
+    spent: f64,
+}
+
+struct GoalRow {
+    goal: SavingsGoal,
+    saved: f64,
+}
+
+/// Spending per category (by name) and month.
+struct Summary {
+    months: Vec<String>,
+    categories: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+fn sheet(name: &str, columns: &[&str], formats: &Formats) -> Result<Worksheet, XlsxError> {
+    let mut sheet = Worksheet::new();
+    sheet.set_name(name)?;
+    for (col, title) in (0u16..).zip(columns) {
+        sheet.write_string_with_format(0, col, *title, &formats.header)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    Ok(sheet)
+}
+
+/// `text` as a date cell, or as it is if it isn't a date.
+fn write_date(
+    sheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    text: &str,
+    f: &Formats,
+) -> Result<(), XlsxError> {
+    match NaiveDate::parse_from_str(text.get(..10).unwrap_or(text), "%Y-%m-%d") {
+        Ok(date) => sheet.write_date_with_format(row, col, date, &f.date)?,
+        Err(_) => sheet.write_string(row, col, text)?,
+    };
+    Ok(())
+}
+
+fn expenses_sheet(
+    rows: &[ExpenseWithCategory],
+    base: &str,
+    f: &Formats,
+) -> Result<Worksheet, XlsxError> {
+    let amount = format!("Amount ({base})");
+    let mut sheet = sheet(
+        "Expenses",
+        &[
+            "Date",
+            &amount,
+            "Category",
+            "Note",
+            "Payment method",
+            "Paid in",
+            "Amount paid",
+        ],
+        f,
+    )?;
+    for (row, e) in (1u32..).zip(rows) {
+        let expense = &e.expense;
+        write_date(&mut sheet, row, 0, &expense.date, f)?;
+        sheet.write_number_with_format(row, 1, expense.amount, &f.money)?;
+        sheet.write_string(row, 2, e.category_name.as_deref().unwrap_or_default())?;
+        sheet.write_string(row, 3, expense.note.as_deref().unwrap_or_default())?;
+        sheet.write_string(
+            row,
+            4,
+            expense.payment_method.as_deref().unwrap_or_default(),
+        )?;
+        if let (Some(code), Some(paid)) = (&expense.currency, expense.original_amount) {
+            sheet.write_string(row, 5, code)?;
+            sheet.write_number_with_format(row, 6, paid, &f.money)?;
+        }
+    }
+    sheet.autofilter(0, 0, rows.len() as u32, 6)?;
+    sheet.autofit();
+    Ok(sheet)
+}
+
+fn budgets_sheet(rows: &[BudgetRow], f: &Formats) -> Result<Worksheet, XlsxError> {
+    let mut sheet = sheet(
+        "Budgets",
+        &[
+            "Period start",
+            "Period end",
+            "Budget",
+            "Spending limit",
+            "Spent",
+            "Left",
+            "Used",
+        ],
+        f,
+    )?;
+    for (row, budget) in (1u32..).zip(rows) {
+        let limit = budget.spending_limit.unwrap_or(budget.total_amount);
+        sheet.write_date_with_format(row, 0, budget.period.start, &f.date)?;
+        sheet.write_date_with_format(row, 1, budget.period.end, &f.date)?;
+        sheet.write_number_with_format(row, 2, budget.total_amount, &f.money)?;
+        if let Some(spending_limit) = budget.spending_limit {
+            sheet.write_number_with_format(row, 3, spending_limit, &f.money)?;
+        }
+        sheet.write_number_with_format(row, 4, budget.spent, &f.money)?;
+        sheet.write_number_with_format(row, 5, limit - budget.spent, &f.money)?;
+        if limit > 0.0 {
+            sheet.write_number_with_format(row, 6, budget.spent / limit, &f.percent)?;
+        }
+    }
+    sheet.autofit();
+    Ok(sheet)
+}
+
+fn goals_sheet(rows: &[GoalRow], f: &Formats) -> Result<Worksheet, XlsxError> {
+    let mut sheet = sheet(
+        "Goals",
+        &[
+            "Goal",
+            "Target",
+            "Target date",
+            "Monthly contribution",
+            "Saved",
+            "Progress",
+        ],
+        f,
+    )?;
+    for (row, GoalRow { goal, saved }) in (1u32..).zip(rows) {
+        sheet.write_string(row, 0, &goal.name)?;
+        sheet.write_number_with_format(row, 1, goal.target_amount, &f.money)?;
+        write_date(&mut sheet, row, 2, &goal.target_date, f)?;
+        sheet.write_number_with_format(row, 3, goal.monthly_contribution, &f.money)?;
+        sheet.write_number_with_format(row, 4, *saved, &f.money)?;
+        if goal.target_amount > 0.0 {
+            sheet.write_number_with_format(row, 5, saved / goal.target_amount, &f.percent)?;
+        }
+    }
+    sheet.autofit();
+    Ok(sheet)
+}
+
+/// Categories down, months across, with totals for both.
+fn summary_sheet(summary: &Summary, f: &Formats) -> Result<Worksheet, XlsxError> {
+    let mut columns: Vec<&str> = vec!["Category"];
+    columns.extend(summary.months.iter().map(String::as_str));
+    columns.push("Total");
+    let mut sheet = sheet("By category", &columns, f)?;
+    let total_col = summary.months.len() as u16 + 1;
+
+    let mut month_totals = vec![0.0; summary.months.len()];
+    let mut row = 1u32;
+    for (category, by_month) in &summary.categories {
+        sheet.write_string(row, 0, category)?;
+        let mut total = 0.0;
+        for (col, month) in (1u16..).zip(&summary.months) {
+            let amount = by_month.get(month).copied().unwrap_or_default();
+            sheet.write_number_with_format(row, col, amount, &f.money)?;
+            month_totals[usize::from(col) - 1] += amount;
+            total += amount;
+        }
+        sheet.write_number_with_format(row, total_col, total, &f.total)?;
+        row += 1;
+    }
+    sheet.write_string_with_format(row, 0, "Total", &f.header)?;
+    for (col, total) in (1u16..).zip(&month_totals) {
+        sheet.write_number_with_format(row, col, *total, &f.total)?;
+    }
+    sheet.write_number_with_format(row, total_col, month_totals.iter().sum::<f64>(), &f.total)?;
+    sheet.set_freeze_panes(1, 1)?;
+    sheet.autofit();
+    Ok(sheet)
+}
+
+/// Budgets of the periods that start in `start..=end`.
+async fn budget_rows(
+    pool: &SqlitePool,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<BudgetRow>> {
+    let schedule = periods::schedule(pool).await?;
+    let budgets: Vec<(String, f64, Option<f64>)> = sqlx::query_as(
+        "SELECT month, total_amount, spending_limit FROM budgets
+         WHERE deleted_at IS NULL ORDER BY month",
+    )
+    .fetch_all(pool)
+    .await?;
+    let mut conn = pool.acquire().await?;
+    let mut rows = Vec::new();
+    for (key, total_amount, spending_limit) in budgets {
+        // Calendar months are keyed "YYYY-MM", other periods by their first day.
+        let period = match NaiveDate::parse_from_str(&format!("{key}-01"), "%Y-%m-%d") {
+            Ok(first) => Period::month_of(first),
+            Err(_) => match NaiveDate::parse_from_str(&key, "%Y-%m-%d") {
+                Ok(first) => schedule.period_at(first),
+                Err(_) => continue,
+            },
+        };
+        if period.start < start || period.start > end {
+            continue;
+        }
+        let spent = budgets::period_spending(&mut conn, &period).await?;
+        rows.push(BudgetRow {
+            period,
+            total_amount,
+            spending_limit,
+            spent,
+        });
+    }
+    Ok(rows)
+}
+
+async fn goal_rows(pool: &SqlitePool) -> Result<Vec<GoalRow>> {
+    let mut rows = Vec::new();
+    for goal in goals::list(pool).await? {
+        let saved = goals::total_saved(pool, &goal.id).await?;
+        rows.push(GoalRow { goal, saved });
+    }
+    Ok(rows)
+}
+
+async fn summary(pool: &SqlitePool, range: &DateRange) -> Result<Summary> {
+    let rows: Vec<(String, String, f64)> = sqlx::query_as(&format!(
+        "SELECT COALESCE(c.name, 'Uncategorized'), substr(e.date, 1, 7), SUM({NET_AMOUNT})
+         FROM expenses e
+         LEFT JOIN categories c ON c.id = e.category_id
+         WHERE e.deleted_at IS NULL AND substr(e.date, 1, 10) BETWEEN $1 AND $2
+         GROUP BY 1, 2"
+    ))
+    .bind(&range.start_date)
+    .bind(&range.end_date)
+    .fetch_all(pool)
+    .await?;
+
+    let mut months: Vec<String> = rows.iter().map(|(_, month, _)| month.clone()).collect();
+    months.sort();
+    months.dedup();
+    let mut categories: BTreeMap<String, BTreeMap<String, f64>> = BTreeMap::new();
+    for (category, month, total) in rows {
+        *categories
+            .entry(category)
+            .or_default()
+            .entry(month)
+            .or_default() += total;
+    }
+    Ok(Summary { months, categories })
+}
+
+/// Write the workbook for `range` to `dir`.
+pub async fn export(pool: &SqlitePool, dir: &Path, range: &DateRange) -> Result<PathBuf> {
+    let (start, end) = parse_range(range)?;
+    let base = currency::base(pool).await?;
+    let mut expenses = expenses::list(pool, &range.start_date, &range.end_date).await?;
+    expenses.reverse();
+    let budgets = budget_rows(pool, start, end).await?;
+    let goals = goal_rows(pool).await?;
+    let summary = summary(pool, range).await?;
+
+    let path = dir.join(format!(
+        "goaldy-{}-to-{}.xlsx",
+        range.start_date, range.end_date
+    ));
+    let formats = Formats::new();
+    let mut workbook = Workbook::new();
+    let written = (|| {
+        workbook.push_worksheet(expenses_sheet(&expenses, &base, &formats)?);
+        workbook.push_worksheet(budgets_sheet(&budgets, &formats)?);
+        workbook.push_worksheet(goals_sheet(&goals, &formats)?);
+        workbook.push_worksheet(summary_sheet(&summary, &formats)?);
+        workbook.save(&path)
+    })();
+    written.map_err(|e| Error::Validation(format!("Failed to write spreadsheet: {e}")))?;
+    Ok(path)
+}
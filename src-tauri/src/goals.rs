@@ -0,0 +1,350 @@
+//! Savings goals and their monthly contributions.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::auth;
+use crate::db::{new_id, now, nullable};
+use crate::error::{Error, Result};
+use crate::expenses::parse_date;
+use crate::sync::{self, Operation};
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct SavingsGoal {
+    pub id: String,
+    pub user_id: Option<String>,
+    pub name: String,
+    pub target_amount: f64,
+    /// "YYYY-MM-DD".
+    pub target_date: String,
+    pub monthly_contribution: f64,
+    pub why_statement: Option<String>,
+    pub privacy_level: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub deleted_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum PrivacyLevel {
+    Private,
+    ProgressOnly,
+    Full,
+}
+
+impl PrivacyLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Private => "private",
+            Self::ProgressOnly => "progress_only",
+            Self::Full => "full",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, specta::Type)]
+pub struct NewGoal {
+    pub name: String,
+    pub target_amount: f64,
+    /// "YYYY-MM-DD".
+    pub target_date: String,
+    pub monthly_contribution: f64,
+    pub why_statement: Option<String>,
+}
+
+/// Fields to change; missing fields are left alone.
+#[derive(Debug, Clone, Default, Deserialize, specta::Type)]
+pub struct GoalUpdate {
+    pub name: Option<String>,
+    pub target_amount: Option<f64>,
+    pub target_date: Option<String>,
+    pub monthly_contribution: Option<f64>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub why_statement: Option<Option<String>>,
+    pub privacy_level: Option<PrivacyLevel>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct Contribution {
+    pub id: String,
+    pub user_id: Option<String>,
+    pub goal_id: String,
+    /// "YYYY-MM".
+    pub month: String,
+    pub amount: f64,
+    /// 1 when the planned monthly amount was saved in full.
+    pub is_full_amount: Option<i64>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub deleted_at: Option<String>,
+}
+
+/// A month's contribution to record; replaces one already there.
+#[derive(Debug, Clone, Deserialize, specta::Type)]
+pub struct ContributionInput {
+    /// "YYYY-MM".
+    pub month: String,
+    pub amount: f64,
+    pub is_full_amount: bool,
+}
+
+fn check_goal(name: &str, target_amount: f64, target_date: &str, monthly: f64) -> Result<()> {
+    if name.is_empty() {
+        return Err(Error::Validation("Goal name can't be empty".to_string()));
+    }
+    if !target_amount.is_finite() || target_amount <= 0.0 {
+        return Err(Error::Validation(
+            "Target amount must be positive".to_string(),
+        ));
+    }
+    if !monthly.is_finite() || monthly < 0.0 {
+        return Err(Error::Validation(
+            "Monthly contribution can't be negative".to_string(),
+        ));
+    }
+    parse_date(target_date)?;
+    Ok(())
+}
+
+fn clean_why(why: Option<String>) -> Option<String> {
+    why.map(|w| w.trim().to_string()).filter(|w| !w.is_empty())
+}
+
+async fn fetch(conn: &mut SqliteConnection, id: &str) -> Result<SavingsGoal> {
+    sqlx::query_as::<_, SavingsGoal>(
+        "SELECT * FROM savings_goals WHERE id = $1 AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| Error::Validation("Goal not found".to_string()))
+}
+
+pub async fn list(pool: &SqlitePool) -> Result<Vec<SavingsGoal>> {
+    let goals = sqlx::query_as::<_, SavingsGoal>(
+        "SELECT * FROM savings_goals WHERE deleted_at IS NULL ORDER BY created_at DESC",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(goals)
+}
+
+pub async fn get(pool: &SqlitePool, id: &str) -> Result<Option<SavingsGoal>> {
+    Ok(sqlx::query_as::<_, SavingsGoal>(
+        "SELECT * FROM savings_goals WHERE id = $1 AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?)
+}
+
+pub async fn create(pool: &SqlitePool, input: NewGoal) -> Result<SavingsGoal> {
+    let name = input.name.trim();
+    check_goal(
+        name,
+        input.target_amount,
+        &input.target_date,
+        input.monthly_contribution,
+    )?;
+
+    let mut tx = pool.begin().await?;
+    let user_id = auth::user_id_on(&mut tx).await?;
+    let id = new_id();
+    sqlx::query(
+        "INSERT INTO savings_goals
+            (id, user_id, name, target_amount, target_date, monthly_contribution, why_statement,
+             privacy_level, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, 'private', $8, $8)",
+    )
+    .bind(&id)
+    .bind(&user_id)
+    .bind(name)
+    .bind(input.target_amount)
+    .bind(&input.target_date)
+    .bind(input.monthly_contribution)
+    .bind(clean_why(input.why_statement))
+    .bind(now())
+    .execute(&mut *tx)
+    .await?;
+    sync::enqueue(&mut tx, "savings_goals", &id, Operation::Insert).await?;
+    let goal = fetch(&mut tx, &id).await?;
+    tx.commit().await?;
+    Ok(goal)
+}
+
+pub async fn update(pool: &SqlitePool, id: &str, update: GoalUpdate) -> Result<SavingsGoal> {
+    let mut tx = pool.begin().await?;
+    let before = fetch(&mut tx, id).await?;
+
+    let name = update
+        .name
+        .map(|n| n.trim().to_string())
+        .unwrap_or(before.name);
+    let target_amount = update.target_amount.unwrap_or(before.target_amount);
+    let target_date = update.target_date.unwrap_or(before.target_date);
+    let monthly_contribution = update
+        .monthly_contribution
+        .unwrap_or(before.monthly_contribution);
+    check_goal(&name, target_amount, &target_date, monthly_contribution)?;
+    let why_statement = match update.why_statement {
+        Some(why) => clean_why(why),
+        None => before.why_statement,
+    };
+    let privacy_level = update
+        .privacy_level
+        .map(|p| p.as_str().to_string())
+        .or(before.privacy_level);
+
+    sqlx::query(
+        "UPDATE savings_goals
+         SET name = $1, target_amount = $2, target_date = $3, monthly_contribution = $4,
+             why_statement = $5, privacy_level = $6, updated_at = $7
+         WHERE id = $8",
+    )
+    .bind(name)
+    .bind(target_amount)
+    .bind(target_date)
+    .bind(monthly_contribution)
+    .bind(why_statement)
+    .bind(privacy_level)
+    .bind(now())
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+    sync::enqueue(&mut tx, "savings_goals", id, Operation::Update).await?;
+    let goal = fetch(&mut tx, id).await?;
+    tx.commit().await?;
+    Ok(goal)
+}
+
+/// Move a goal and its contributions to the trash.
+pub async fn delete(pool: &SqlitePool, id: &str) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    fetch(&mut tx, id).await?;
+    let now = now();
+    sqlx::query("UPDATE savings_goals SET deleted_at = $1, updated_at = $1 WHERE id = $2")
+        .bind(&now)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    sync::enqueue_delete(&mut tx, "savings_goals", id, &now).await?;
+    sqlx::query(
+        "UPDATE savings_contributions SET deleted_at = $1, updated_at = $1
+         WHERE goal_id = $2 AND deleted_at IS NULL",
+    )
+    .bind(&now)
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// A goal's contributions, newest month first.
+pub async fn contributions(pool: &SqlitePool, goal_id: &str) -> Result<Vec<Contribution>> {
+    Ok(sqlx::query_as::<_, Contribution>(
+        "SELECT * FROM savings_contributions
+         WHERE goal_id = $1 AND deleted_at IS NULL
+         ORDER BY month DESC",
+    )
+    .bind(goal_id)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Record what was saved towards a goal in a month.
+pub async fn save_contribution(
+    pool: &SqlitePool,
+    goal_id: &str,
+    input: ContributionInput,
+) -> Result<Contribution> {
+    if NaiveDate::parse_from_str(&format!("{}-01", input.month), "%Y-%m-%d").is_err() {
+        return Err(Error::Validation(format!(
+            "\"{}\" is not a YYYY-MM month",
+            input.month
+        )));
+    }
+    if !input.amount.is_finite() || input.amount < 0.0 {
+        return Err(Error::Validation(
+            "Contribution can't be negative".to_string(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+    fetch(&mut tx, goal_id).await?;
+    let existing: Option<(String,)> = sqlx::query_as(
+        "SELECT id FROM savings_contributions
+         WHERE goal_id = $1 AND month = $2 AND deleted_at IS NULL",
+    )
+    .bind(goal_id)
+    .bind(&input.month)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let now = now();
+    let (id, operation) = match existing {
+        Some((id,)) => {
+            sqlx::query(
+                "UPDATE savings_contributions SET amount = $1, is_full_amount = $2, updated_at = $3
+                 WHERE id = $4",
+            )
+            .bind(input.amount)
+            .bind(input.is_full_amount)
+            .bind(&now)
+            .bind(&id)
+            .execute(&mut *tx)
+            .await?;
+            (id, Operation::Update)
+        }
+        None => {
+            let id = new_id();
+            let user_id = auth::user_id_on(&mut tx).await?;
+            sqlx::query(
+                "INSERT INTO savings_contributions
+                    (id, user_id, goal_id, month, amount, is_full_amount, created_at, updated_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $7)",
+            )
+            .bind(&id)
+            .bind(user_id)
+            .bind(goal_id)
+            .bind(&input.month)
+            .bind(input.amount)
+            .bind(input.is_full_amount)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+            (id, Operation::Insert)
+        }
+    };
+    sync::enqueue(&mut tx, "savings_contributions", &id, operation).await?;
+    let contribution =
+        sqlx::query_as::<_, Contribution>("SELECT * FROM savings_contributions WHERE id = $1")
+            .bind(&id)
+            .fetch_one(&mut *tx)
+            .await?;
+    tx.commit().await?;
+    Ok(contribution)
+}
+
+pub async fn total_saved(pool: &SqlitePool, goal_id: &str) -> Result<f64> {
+    let (total,): (f64,) = sqlx::query_as(
+        "SELECT COALESCE(SUM(amount), 0.0) FROM savings_contributions WHERE goal_id = $1 AND deleted_at IS NULL",
+    )
+    .bind(goal_id)
+    .fetch_one(pool)
+    .await?;
+    Ok(total)
+}
+
+/// Whether the user checked in for `month` ("YYYY-MM"), even with zero.
+pub async fn has_contribution(pool: &SqlitePool, goal_id: &str, month: &str) -> Result<bool> {
+    let row: Option<(String,)> = sqlx::query_as(
+        "SELECT id FROM savings_contributions WHERE goal_id = $1 AND month = $2 AND deleted_at IS NULL",
+    )
+    .bind(goal_id)
+    .bind(month)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.is_some())
+}
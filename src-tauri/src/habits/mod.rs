@@ -0,0 +1,311 @@
+//! Spending habits (`habit_goals`) and how each month went against them
+//! (`habit_tracking`). Mirrors the rules in src/lib/database.ts so a month
+//! can be finalized without the app open.
+//!
+//! Rules: `max_amount`, `max_percentage` of the month's spending,
+//! `reduce_by` a percentage of last month, `no_spend` in the category at
+//! all, and `max_count` purchases. For `max_count` the tracked "amounts" are
+//! numbers of expenses.
+//!
+//! The running month is snapshotted too, so other devices and the widgets
+//! see progress without working it out themselves, and a habit nearing its
+//! limit warns once a month.
+
+pub mod streaks;
+
+use chrono::{Datelike, Months, NaiveDate};
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::AppHandle;
+
+use crate::budgets;
+use crate::db::{new_id, now};
+use crate::error::Result;
+use crate::notify;
+use crate::sync::{self, Operation};
+
+/// Share of the limit from which a habit counts as at risk.
+const AT_RISK_SHARE: f64 = 0.8;
+
+#[derive(sqlx::FromRow)]
+struct HabitGoal {
+    id: String,
+    user_id: Option<String>,
+    name: String,
+    category_id: String,
+    rule_type: String,
+    rule_value: f64,
+}
+
+/// How a habit stands for a month.
+struct Snapshot {
+    spent: f64,
+    target: f64,
+}
+
+impl Snapshot {
+    fn compliant(&self) -> bool {
+        self.spent <= self.target
+    }
+}
+
+async fn category_count(
+    conn: &mut SqliteConnection,
+    category_id: &str,
+    month: &str,
+) -> Result<f64> {
+    let (count,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM expenses
+         WHERE category_id = $1 AND strftime('%Y-%m', date) = $2 AND deleted_at IS NULL",
+    )
+    .bind(category_id)
+    .bind(month)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(count as f64)
+}
+
+async fn category_spending(
+    conn: &mut SqliteConnection,
+    category_id: &str,
+    month: &str,
+) -> Result<f64> {
+    let (total,): (f64,) = sqlx::query_as(
+        "SELECT COALESCE(SUM(amount), 0) FROM expenses
+         WHERE category_id = $1 AND strftime('%Y-%m', date) = $2 AND deleted_at IS NULL",
+    )
+    .bind(category_id)
+    .bind(month)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(total)
+}
+
+/// The most a habit allowed for `month`. `previous_month` is the one
+/// `reduce_by` compares against.
+async fn target_amount(
+    conn: &mut SqliteConnection,
+    goal: &HabitGoal,
+    month: &str,
+    previous_month: &str,
+) -> Result<f64> {
+    match goal.rule_type.as_str() {
+        "max_percentage" => {
+            let mut base = budgets::month_spending(conn, month).await?;
+            if base == 0.0 {
+                let budget: Option<(f64,)> = sqlx::query_as(
+                    "SELECT total_amount FROM budgets WHERE month = $1 AND deleted_at IS NULL",
+                )
+                .bind(month)
+                .fetch_optional(&mut *conn)
+                .await?;
+                base = budget.map_or(0.0, |(total,)| total);
+            }
+            Ok(goal.rule_value / 100.0 * base)
+        }
+        "reduce_by" => {
+            let previous = category_spending(conn, &goal.category_id, previous_month).await?;
+            if previous == 0.0 {
+                // Nothing to reduce from; treat the value as a cap.
+                Ok(goal.rule_value)
+            } else {
+                Ok(previous * (1.0 - goal.rule_value / 100.0))
+            }
+        }
+        "no_spend" => Ok(0.0),
+        _ => Ok(goal.rule_value),
+    }
+}
+
+async fn measure(
+    conn: &mut SqliteConnection,
+    goal: &HabitGoal,
+    month: &str,
+    previous_month: &str,
+) -> Result<Snapshot> {
+    let spent = if goal.rule_type == "max_count" {
+        category_count(conn, &goal.category_id, month).await?
+    } else {
+        category_spending(conn, &goal.category_id, month).await?
+    };
+    let target = target_amount(conn, goal, month, previous_month).await?;
+    Ok(Snapshot { spent, target })
+}
+
+/// A habit active in a month that has no tracking row for it yet.
+pub(crate) struct Untracked {
+    pub id: String,
+    pub name: String,
+    pub rule_type: String,
+    pub spent: f64,
+    pub target: f64,
+}
+
+/// Habits active in `month` without a `habit_tracking` row for it, measured
+/// as they stand now.
+pub(crate) async fn untracked(
+    pool: &SqlitePool,
+    month: &str,
+    previous_month: &str,
+) -> Result<Vec<Untracked>> {
+    let mut conn = pool.acquire().await?;
+    let goals = sqlx::query_as::<_, HabitGoal>(
+        "SELECT id, user_id, name, category_id, rule_type, rule_value
+         FROM habit_goals g
+         WHERE deleted_at IS NULL AND substr(start_date, 1, 7) <= $1
+           AND NOT EXISTS (
+             SELECT 1 FROM habit_tracking t
+             WHERE t.habit_goal_id = g.id AND t.month = $1 AND t.deleted_at IS NULL
+           )
+         ORDER BY name",
+    )
+    .bind(month)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut untracked = Vec::with_capacity(goals.len());
+    for goal in goals {
+        let snapshot = measure(&mut conn, &goal, month, previous_month).await?;
+        untracked.push(Untracked {
+            id: goal.id,
+            name: goal.name,
+            rule_type: goal.rule_type,
+            spent: snapshot.spent,
+            target: snapshot.target,
+        });
+    }
+    Ok(untracked)
+}
+
+/// Record the final spending and compliance of every active habit for
+/// `month`, updating rows already there. Returns how many were written.
+pub async fn finalize_month(pool: &SqlitePool, month: &str, previous_month: &str) -> Result<usize> {
+    Ok(record_month(pool, month, previous_month).await?.len())
+}
+
+/// Measure every habit active in `month` and store the result in
+/// `habit_tracking`.
+async fn record_month(
+    pool: &SqlitePool,
+    month: &str,
+    previous_month: &str,
+) -> Result<Vec<(HabitGoal, Snapshot)>> {
+    let mut tx = pool.begin().await?;
+    let goals = sqlx::query_as::<_, HabitGoal>(
+        "SELECT id, user_id, name, category_id, rule_type, rule_value
+         FROM habit_goals
+         WHERE deleted_at IS NULL AND substr(start_date, 1, 7) <= $1",
+    )
+    .bind(month)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let now = now();
+    let mut recorded = Vec::with_capacity(goals.len());
+    for goal in goals {
+        let snapshot = measure(&mut tx, &goal, month, previous_month).await?;
+
+        let existing: Option<(String,)> = sqlx::query_as(
+            "SELECT id FROM habit_tracking
+             WHERE habit_goal_id = $1 AND month = $2 AND deleted_at IS NULL",
+        )
+        .bind(&goal.id)
+        .bind(month)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let (id, operation) = match existing {
+            Some((id,)) => {
+                let changed = sqlx::query(
+                    "UPDATE habit_tracking
+                     SET spent_amount = $1, target_amount = $2, is_compliant = $3, updated_at = $4
+                     WHERE id = $5
+                       AND (spent_amount IS NOT $1 OR target_amount IS NOT $2
+                            OR is_compliant IS NOT $3)",
+                )
+                .bind(snapshot.spent)
+                .bind(snapshot.target)
+                .bind(snapshot.compliant())
+                .bind(&now)
+                .bind(&id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected()
+                    > 0;
+                if !changed {
+                    recorded.push((goal, snapshot));
+                    continue;
+                }
+                (id, Operation::Update)
+            }
+            None => {
+                let id = new_id();
+                sqlx::query(
+                    "INSERT INTO habit_tracking
+                        (id, user_id, habit_goal_id, month, spent_amount, target_amount,
+                         is_compliant, created_at, updated_at)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $8)",
+                )
+                .bind(&id)
+                .bind(&goal.user_id)
+                .bind(&goal.id)
+                .bind(month)
+                .bind(snapshot.spent)
+                .bind(snapshot.target)
+                .bind(snapshot.compliant())
+                .bind(&now)
+                .execute(&mut *tx)
+                .await?;
+                (id, Operation::Insert)
+            }
+        };
+        sync::enqueue(&mut tx, "habit_tracking", &id, operation).await?;
+        recorded.push((goal, snapshot));
+    }
+
+    tx.commit().await?;
+    Ok(recorded)
+}
+
+/// Snapshot the month `today` is in and warn about habits nearing their
+/// limit.
+pub async fn evaluate(app: &AppHandle, pool: &SqlitePool, today: NaiveDate) -> Result<()> {
+    let first = today.with_day(1).unwrap_or(today);
+    let previous = first - Months::new(1);
+    let month = first.format("%Y-%m").to_string();
+    let recorded = record_month(pool, &month, &previous.format("%Y-%m").to_string()).await?;
+
+    for (goal, snapshot) in recorded {
+        // A no-spend habit has no limit to approach.
+        if snapshot.target <= 0.0 || snapshot.spent < snapshot.target * AT_RISK_SHARE {
+            continue;
+        }
+        let progress = if goal.rule_type == "max_count" {
+            format!(
+                "{:.0} of {:.0} purchases so far this month.",
+                snapshot.spent, snapshot.target
+            )
+        } else {
+            format!(
+                "{:.2} of {:.2} spent so far this month.",
+                snapshot.spent, snapshot.target
+            )
+        };
+        let title = if snapshot.compliant() {
+            format!("{} is at risk", goal.name)
+        } else {
+            format!("{} is over its limit", goal.name)
+        };
+        notify::send_once(
+            app,
+            pool,
+            &format!("habit_at_risk:{}:{month}", goal.id),
+            "habit_at_risk",
+            None,
+            &title,
+            &progress,
+        )
+        .await?;
+    }
+    streaks::refresh(pool, today).await?;
+    Ok(())
+}
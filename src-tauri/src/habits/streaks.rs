@@ -0,0 +1,229 @@
+//! Streaks of compliant months and weeks per habit, cached in
+//! `habit_streaks`.
+//!
+//! Months are read from `habit_tracking`; a month without a row breaks the
+//! streak like a month over the limit. Weeks (Monday to Sunday) are measured
+//! from expenses against the week's share of its month's limit. Only
+//! finished months and weeks count, so the running one never breaks a
+//! streak.
+//!
+//! Every `FREEZE_EVERY` compliant months in a row earn a freeze, up to
+//! `MAX_FREEZES`. A missed month spends one instead of ending the streak;
+//! it keeps the streak alive without adding to it. History is walked from
+//! the start each time, so freezes are the same on every device.
+
+use std::collections::HashMap;
+
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::db::now;
+use crate::error::{Error, Result};
+
+/// Compliant months in a row that earn a freeze.
+const FREEZE_EVERY: i64 = 3;
+
+const MAX_FREEZES: i64 = 2;
+
+#[derive(Debug, Clone, Default, Serialize, specta::Type)]
+pub struct HabitStreak {
+    pub habit_goal_id: String,
+    pub current_months: i64,
+    pub longest_months: i64,
+    pub current_weeks: i64,
+    pub longest_weeks: i64,
+    pub freezes_available: i64,
+    /// Months a freeze bridged, "YYYY-MM".
+    pub frozen_months: Vec<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct Habit {
+    id: String,
+    category_id: String,
+    rule_type: String,
+    rule_value: f64,
+    start_date: String,
+}
+
+/// Limit and compliance recorded for one month.
+struct Tracked {
+    target: f64,
+    compliant: bool,
+}
+
+fn month_key(date: NaiveDate) -> String {
+    date.format("%Y-%m").to_string()
+}
+
+fn days_in_month(first: NaiveDate) -> u32 {
+    (first + Months::new(1))
+        .signed_duration_since(first)
+        .num_days() as u32
+}
+
+/// Compliant months up to and including `last_month`.
+fn month_streak(
+    start: NaiveDate,
+    last_month: NaiveDate,
+    tracked: &HashMap<String, Tracked>,
+) -> HabitStreak {
+    let mut streak = HabitStreak::default();
+    let mut month = start.with_day(1).unwrap_or(start);
+    while month <= last_month {
+        let key = month_key(month);
+        if tracked.get(&key).is_some_and(|t| t.compliant) {
+            streak.current_months += 1;
+            if streak.current_months % FREEZE_EVERY == 0 {
+                streak.freezes_available = (streak.freezes_available + 1).min(MAX_FREEZES);
+            }
+        } else if streak.current_months > 0 && streak.freezes_available > 0 {
+            streak.freezes_available -= 1;
+            streak.frozen_months.push(key);
+        } else {
+            streak.current_months = 0;
+        }
+        streak.longest_months = streak.longest_months.max(streak.current_months);
+        month = month + Months::new(1);
+    }
+    streak
+}
+
+/// What a week may use: its share of the limit of the month it starts in.
+fn week_allowance(habit: &Habit, monday: NaiveDate, tracked: &HashMap<String, Tracked>) -> f64 {
+    let first = monday.with_day(1).unwrap_or(monday);
+    let monthly = match tracked.get(&month_key(first)) {
+        Some(t) => t.target,
+        None if habit.rule_type == "no_spend" => 0.0,
+        None => habit.rule_value,
+    };
+    monthly * 7.0 / f64::from(days_in_month(first))
+}
+
+/// Current and longest run of compliant weeks ending before `this_monday`.
+async fn week_streak(
+    pool: &SqlitePool,
+    habit: &Habit,
+    start: NaiveDate,
+    this_monday: NaiveDate,
+    tracked: &HashMap<String, Tracked>,
+) -> Result<(i64, i64)> {
+    let first_monday = start.week(Weekday::Mon).first_day();
+    let days: Vec<(String, f64, i64)> = sqlx::query_as(
+        "SELECT substr(date, 1, 10) AS day, SUM(amount), COUNT(*)
+         FROM expenses
+         WHERE category_id = $1 AND deleted_at IS NULL AND date >= $2 AND date < $3
+         GROUP BY day",
+    )
+    .bind(&habit.category_id)
+    .bind(first_monday.format("%Y-%m-%d").to_string())
+    .bind(this_monday.format("%Y-%m-%d").to_string())
+    .fetch_all(pool)
+    .await?;
+
+    let mut used: HashMap<NaiveDate, f64> = HashMap::new();
+    for (day, amount, count) in days {
+        let Ok(day) = NaiveDate::parse_from_str(&day, "%Y-%m-%d") else {
+            continue;
+        };
+        let value = if habit.rule_type == "max_count" {
+            count as f64
+        } else {
+            amount
+        };
+        *used.entry(day.week(Weekday::Mon).first_day()).or_default() += value;
+    }
+
+    let (mut current, mut longest) = (0, 0);
+    let mut monday = first_monday;
+    while monday < this_monday {
+        let spent = used.get(&monday).copied().unwrap_or(0.0);
+        if spent <= week_allowance(habit, monday, tracked) {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+        monday = monday + Days::new(7);
+    }
+    Ok((current, longest))
+}
+
+async fn compute(pool: &SqlitePool, habit: &Habit, today: NaiveDate) -> Result<HabitStreak> {
+    let start = NaiveDate::parse_from_str(habit.start_date.get(..10).unwrap_or(""), "%Y-%m-%d")
+        .map_err(|_| Error::Validation(format!("Invalid start date {}", habit.start_date)))?;
+    let rows: Vec<(String, f64, Option<i64>)> = sqlx::query_as(
+        "SELECT month, target_amount, is_compliant FROM habit_tracking
+         WHERE habit_goal_id = $1 AND deleted_at IS NULL",
+    )
+    .bind(&habit.id)
+    .fetch_all(pool)
+    .await?;
+    let tracked: HashMap<String, Tracked> = rows
+        .into_iter()
+        .map(|(month, target, compliant)| {
+            let tracked = Tracked {
+                target,
+                compliant: compliant == Some(1),
+            };
+            (month, tracked)
+        })
+        .collect();
+
+    let this_month = today.with_day(1).unwrap_or(today);
+    let mut streak = month_streak(start, this_month - Months::new(1), &tracked);
+    let this_monday = today.week(Weekday::Mon).first_day();
+    (streak.current_weeks, streak.longest_weeks) =
+        week_streak(pool, habit, start, this_monday, &tracked).await?;
+    streak.habit_goal_id = habit.id.clone();
+    Ok(streak)
+}
+
+/// Recompute the streaks of every active habit and store them.
+pub async fn refresh(pool: &SqlitePool, today: NaiveDate) -> Result<Vec<HabitStreak>> {
+    let habits = sqlx::query_as::<_, Habit>(
+        "SELECT id, category_id, rule_type, rule_value, start_date
+         FROM habit_goals WHERE deleted_at IS NULL",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut streaks = Vec::with_capacity(habits.len());
+    for habit in &habits {
+        streaks.push(compute(pool, habit, today).await?);
+    }
+
+    let now = now();
+    let mut tx = pool.begin().await?;
+    for streak in &streaks {
+        let frozen = serde_json::to_string(&streak.frozen_months)
+            .map_err(|e| Error::Validation(format!("failed to encode frozen months: {e}")))?;
+        sqlx::query(
+            "INSERT INTO habit_streaks
+                (habit_goal_id, current_months, longest_months, current_weeks, longest_weeks,
+                 freezes_available, frozen_months, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+             ON CONFLICT(habit_goal_id) DO UPDATE SET
+                current_months = excluded.current_months,
+                longest_months = excluded.longest_months,
+                current_weeks = excluded.current_weeks,
+                longest_weeks = excluded.longest_weeks,
+                freezes_available = excluded.freezes_available,
+                frozen_months = excluded.frozen_months,
+                updated_at = excluded.updated_at",
+        )
+        .bind(&streak.habit_goal_id)
+        .bind(streak.current_months)
+        .bind(streak.longest_months)
+        .bind(streak.current_weeks)
+        .bind(streak.longest_weeks)
+        .bind(streak.freezes_available)
+        .bind(frozen)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(streaks)
+}
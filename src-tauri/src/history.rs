@@ -0,0 +1,176 @@
+//! Per-record change history, for answering "why did this change?".
+//!
+//! Three sources are merged into one timeline: `audit_log` (written by
+//! triggers on every insert, update and delete of tracked columns),
+//! `sync_events` (rows pushed or pulled by the sync engine, or queued
+//! changes discarded by hand) and
+//! `sync_conflicts` (conflicts parked and how they were settled). An update
+//! directly followed by a pull came from another device.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sqlx::SqlitePool;
+
+use crate::error::{Error, Result};
+use crate::sync::conflicts::CONFLICT_TABLES;
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct TimelineEntry {
+    pub at: String,
+    #[serde(flatten)]
+    pub event: TimelineEvent,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TimelineEvent {
+    Created {
+        values: Map<String, Value>,
+    },
+    Updated {
+        changes: Vec<FieldChange>,
+    },
+    /// Removed from the table outright (soft deletes show up as an update
+    /// of `deleted_at`).
+    Deleted {
+        values: Map<String, Value>,
+    },
+    Pushed {
+        operation: Option<String>,
+    },
+    Pulled,
+    /// A queued change that was dropped without reaching the server.
+    Discarded {
+        operation: Option<String>,
+    },
+    ConflictDetected {
+        remote_updated_at: String,
+    },
+    ConflictResolved {
+        kept: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+#[derive(sqlx::FromRow)]
+struct AuditRow {
+    action: String,
+    old_values: Option<String>,
+    new_values: Option<String>,
+    changed_at: String,
+}
+
+fn parse_values(json: Option<&str>) -> Map<String, Value> {
+    match json.map(serde_json::from_str) {
+        Some(Ok(Value::Object(map))) => map,
+        _ => Map::new(),
+    }
+}
+
+fn audit_event(row: &AuditRow) -> Option<TimelineEvent> {
+    let old = parse_values(row.old_values.as_deref());
+    let new = parse_values(row.new_values.as_deref());
+    match row.action.as_str() {
+        "insert" => Some(TimelineEvent::Created { values: new }),
+        "delete" => Some(TimelineEvent::Deleted { values: old }),
+        "update" => {
+            let changes: Vec<FieldChange> = new
+                .into_iter()
+                .filter_map(|(field, new)| {
+                    let old = old.get(&field).cloned().unwrap_or(Value::Null);
+                    (old != new).then_some(FieldChange { field, old, new })
+                })
+                .collect();
+            (!changes.is_empty()).then_some(TimelineEvent::Updated { changes })
+        }
+        _ => None,
+    }
+}
+
+/// Everything known about how one record got to its current state, oldest
+/// first.
+pub async fn timeline(
+    pool: &SqlitePool,
+    table: &str,
+    record_id: &str,
+) -> Result<Vec<TimelineEntry>> {
+    if !CONFLICT_TABLES.contains(&table) {
+        return Err(Error::Validation(format!("No history is kept for {table}")));
+    }
+
+    let mut entries = Vec::new();
+
+    let audit = sqlx::query_as::<_, AuditRow>(
+        "SELECT action, old_values, new_values, changed_at
+         FROM audit_log
+         WHERE table_name = $1 AND record_id = $2
+         ORDER BY id ASC",
+    )
+    .bind(table)
+    .bind(record_id)
+    .fetch_all(pool)
+    .await?;
+    entries.extend(audit.iter().filter_map(|row| {
+        audit_event(row).map(|event| TimelineEntry {
+            at: row.changed_at.clone(),
+            event,
+        })
+    }));
+
+    let sync_events: Vec<(String, Option<String>, String)> = sqlx::query_as(
+        "SELECT direction, operation, occurred_at
+         FROM sync_events
+         WHERE table_name = $1 AND record_id = $2
+         ORDER BY id ASC",
+    )
+    .bind(table)
+    .bind(record_id)
+    .fetch_all(pool)
+    .await?;
+    entries.extend(
+        sync_events
+            .into_iter()
+            .map(|(direction, operation, at)| TimelineEntry {
+                at,
+                event: match direction.as_str() {
+                    "pull" => TimelineEvent::Pulled,
+                    "discard" => TimelineEvent::Discarded { operation },
+                    _ => TimelineEvent::Pushed { operation },
+                },
+            }),
+    );
+
+    let conflicts: Vec<(String, String, Option<String>, Option<String>)> = sqlx::query_as(
+        "SELECT detected_at, remote_updated_at, resolved_at, resolution
+         FROM sync_conflicts
+         WHERE table_name = $1 AND record_id = $2",
+    )
+    .bind(table)
+    .bind(record_id)
+    .fetch_all(pool)
+    .await?;
+    for (detected_at, remote_updated_at, resolved_at, resolution) in conflicts {
+        entries.push(TimelineEntry {
+            at: detected_at,
+            event: TimelineEvent::ConflictDetected { remote_updated_at },
+        });
+        if let (Some(at), Some(kept)) = (resolved_at, resolution) {
+            entries.push(TimelineEntry {
+                at,
+                event: TimelineEvent::ConflictResolved { kept },
+            });
+        }
+    }
+
+    // All timestamps are `toISOString()`-style UTC, so they sort as text.
+    // The sort is stable: within the same millisecond the audit entry stays
+    // ahead of the sync event that caused it.
+    entries.sort_by(|a, b| a.at.cmp(&b.at));
+    Ok(entries)
+}
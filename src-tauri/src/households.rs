@@ -0,0 +1,265 @@
+//! Households: sharing some expenses and budgets with other accounts.
+//!
+//! Unlike partner mode, every member keeps a ledger of their own and marks
+//! the rows to share with `household_id`, which syncs like any other column.
+//! Creating and joining households and pulling the other members' shared
+//! rows happen in the frontend (`src/lib/household.ts`), next to the rest of
+//! the pull; the pulled rows land in `household_expenses` and
+//! `household_budgets`. Here the user shares their own rows and reads the
+//! combined view, where each record says whose it is.
+
+use serde::Serialize;
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::auth;
+use crate::db::now;
+use crate::error::{Error, Result};
+use crate::sync::{self, Operation};
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct Household {
+    pub id: String,
+    pub name: String,
+    pub owner_id: String,
+    /// Code other people join with.
+    pub invite_code: String,
+    pub created_at: String,
+    #[sqlx(skip)]
+    pub members: Vec<HouseholdMember>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct HouseholdMember {
+    pub household_id: String,
+    pub user_id: String,
+    pub display_name: Option<String>,
+    /// "owner" or "member".
+    pub role: String,
+    pub joined_at: Option<String>,
+    /// Whether this is the signed-in user.
+    pub is_me: bool,
+}
+
+/// An expense in the combined ledger, the user's own or another member's.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct HouseholdExpense {
+    pub id: String,
+    pub owner_id: String,
+    pub owner_name: Option<String>,
+    /// Whether the user owns, and so may change, the expense.
+    pub mine: bool,
+    pub amount: f64,
+    pub category_id: Option<String>,
+    pub category_name: Option<String>,
+    pub category_icon: Option<String>,
+    pub category_color: Option<String>,
+    pub note: Option<String>,
+    /// "YYYY-MM-DD".
+    pub date: String,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct MemberSpending {
+    pub owner_id: String,
+    pub owner_name: Option<String>,
+    pub mine: bool,
+    pub total: f64,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct HouseholdBudget {
+    pub id: String,
+    pub owner_id: String,
+    pub owner_name: Option<String>,
+    pub mine: bool,
+    pub month: String,
+    pub total_amount: f64,
+    pub spending_limit: Option<f64>,
+}
+
+/// Shared expenses of everyone in the household: the user's own, owned by
+/// whoever wrote them, and the pulled ones. `$1` is the household, `$2` the
+/// user.
+const LEDGER: &str = "SELECT e.id, COALESCE(e.created_by, $2) AS owner_id, e.amount,
+            e.category_id, c.name AS category_name, c.icon AS category_icon,
+            c.color AS category_color, e.note, substr(e.date, 1, 10) AS date
+     FROM expenses e
+     LEFT JOIN categories c ON e.category_id = c.id
+     WHERE e.household_id = $1 AND e.deleted_at IS NULL
+     UNION ALL
+     SELECT id, owner_id, amount, category_id, category_name, category_icon,
+            category_color, note, substr(date, 1, 10)
+     FROM household_expenses
+     WHERE household_id = $1 AND deleted_at IS NULL";
+
+async fn me(pool: &SqlitePool) -> Result<String> {
+    auth::current_user_id(pool)
+        .await?
+        .ok_or_else(|| Error::Validation("Sign in to use households".to_string()))
+}
+
+async fn check_household(conn: &mut SqliteConnection, id: &str) -> Result<()> {
+    let found: Option<(String,)> = sqlx::query_as("SELECT id FROM households WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    if found.is_none() {
+        return Err(Error::Validation("Household not found".to_string()));
+    }
+    Ok(())
+}
+
+/// The households the user belongs to, with their members.
+pub async fn list(pool: &SqlitePool) -> Result<Vec<Household>> {
+    let me = auth::current_user_id(pool).await?;
+    let mut households = sqlx::query_as::<_, Household>(
+        "SELECT id, name, owner_id, invite_code, created_at FROM households ORDER BY name",
+    )
+    .fetch_all(pool)
+    .await?;
+    let members = sqlx::query_as::<_, HouseholdMember>(
+        "SELECT household_id, user_id, display_name, role, joined_at,
+                user_id = $1 AS is_me
+         FROM household_members
+         ORDER BY role = 'owner' DESC, joined_at",
+    )
+    .bind(&me)
+    .fetch_all(pool)
+    .await?;
+    for member in members {
+        if let Some(household) = households.iter_mut().find(|h| h.id == member.household_id) {
+            household.members.push(member);
+        }
+    }
+    Ok(households)
+}
+
+/// Share one of the user's rows with a household, or with `None` stop
+/// sharing it.
+async fn share(pool: &SqlitePool, table: &str, id: &str, household_id: Option<&str>) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    if let Some(household_id) = household_id {
+        check_household(&mut tx, household_id).await?;
+        // Sharing goes through sync.
+        if !sync::settings::is_enabled(&mut tx, table).await? {
+            return Err(Error::Validation(format!(
+                "Turn on sync of {table} to share them with a household"
+            )));
+        }
+    }
+    let updated = sqlx::query(&format!(
+        "UPDATE {table} SET household_id = $1, updated_at = $2
+         WHERE id = $3 AND deleted_at IS NULL"
+    ))
+    .bind(household_id)
+    .bind(now())
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+    if updated.rows_affected() == 0 {
+        let what = if table == "budgets" {
+            "Budget"
+        } else {
+            "Expense"
+        };
+        return Err(Error::Validation(format!("{what} not found")));
+    }
+    sync::enqueue(&mut tx, table, id, Operation::Update).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn share_expense(
+    pool: &SqlitePool,
+    expense_id: &str,
+    household_id: Option<&str>,
+) -> Result<()> {
+    share(pool, "expenses", expense_id, household_id).await
+}
+
+pub async fn share_budget(
+    pool: &SqlitePool,
+    budget_id: &str,
+    household_id: Option<&str>,
+) -> Result<()> {
+    share(pool, "budgets", budget_id, household_id).await
+}
+
+/// The household's shared expenses between two dates, newest first.
+pub async fn ledger(
+    pool: &SqlitePool,
+    household_id: &str,
+    start: &str,
+    end: &str,
+) -> Result<Vec<HouseholdExpense>> {
+    let me = me(pool).await?;
+    Ok(sqlx::query_as::<_, HouseholdExpense>(&format!(
+        "SELECT l.*, m.display_name AS owner_name, l.owner_id = $2 AS mine
+         FROM ({LEDGER}) l
+         LEFT JOIN household_members m ON m.household_id = $1 AND m.user_id = l.owner_id
+         WHERE l.date BETWEEN $3 AND $4
+         ORDER BY l.date DESC, l.id"
+    ))
+    .bind(household_id)
+    .bind(&me)
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// What each member put into the household between two dates.
+pub async fn spending_by_member(
+    pool: &SqlitePool,
+    household_id: &str,
+    start: &str,
+    end: &str,
+) -> Result<Vec<MemberSpending>> {
+    let me = me(pool).await?;
+    Ok(sqlx::query_as::<_, MemberSpending>(&format!(
+        "SELECT l.owner_id, m.display_name AS owner_name, l.owner_id = $2 AS mine,
+                SUM(l.amount) AS total, COUNT(*) AS count
+         FROM ({LEDGER}) l
+         LEFT JOIN household_members m ON m.household_id = $1 AND m.user_id = l.owner_id
+         WHERE l.date BETWEEN $3 AND $4
+         GROUP BY l.owner_id
+         ORDER BY total DESC"
+    ))
+    .bind(household_id)
+    .bind(&me)
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Every member's budget shared with the household for a period.
+pub async fn budgets(
+    pool: &SqlitePool,
+    household_id: &str,
+    month: &str,
+) -> Result<Vec<HouseholdBudget>> {
+    let me = me(pool).await?;
+    Ok(sqlx::query_as::<_, HouseholdBudget>(
+        "SELECT b.id, b.owner_id, m.display_name AS owner_name, b.owner_id = $2 AS mine,
+                b.month, b.total_amount, b.spending_limit
+         FROM (
+             SELECT id, COALESCE(created_by, $2) AS owner_id, month, total_amount,
+                    spending_limit
+             FROM budgets
+             WHERE household_id = $1 AND month = $3 AND deleted_at IS NULL
+             UNION ALL
+             SELECT id, owner_id, month, total_amount, spending_limit
+             FROM household_budgets
+             WHERE household_id = $1 AND month = $3 AND deleted_at IS NULL
+         ) b
+         LEFT JOIN household_members m ON m.household_id = $1 AND m.user_id = b.owner_id
+         ORDER BY b.owner_id = $2 DESC, owner_name",
+    )
+    .bind(household_id)
+    .bind(&me)
+    .bind(month)
+    .fetch_all(pool)
+    .await?)
+}
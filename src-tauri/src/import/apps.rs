@@ -0,0 +1,328 @@
+//! History from the budgeting apps people switch from: YNAB's register
+//! export and Mint's transaction CSV.
+//!
+//! Both files say which column is which, so only the format has to be
+//! known, and it is told from the headers. Transfers between accounts and
+//! money coming in are skipped; what's left is spending. Each app's own
+//! categories come along by name: `preview` lists them with a suggested
+//! category here, the user confirms or changes the mapping, and `import`
+//! files every row under its mapped category. Unmapped ones go through the
+//! categorization rules. The mapping is kept per app for the next import.
+//! Both apps are American, so dates are read month first unless a format
+//! is given.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use super::{ImportReport, ImportRow};
+use crate::app_meta;
+use crate::drafts::{self, find_category};
+use crate::error::{Error, Result};
+use crate::reconcile::statement;
+
+/// Mint's categories for money moved between accounts.
+const MINT_TRANSFERS: &[&str] = &[
+    "transfer",
+    "credit card payment",
+    "transfer for cash spending",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AppFormat {
+    Ynab,
+    Mint,
+}
+
+impl AppFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ynab => "ynab",
+            Self::Mint => "mint",
+        }
+    }
+
+    fn detect(headers: &[String]) -> Option<Self> {
+        let has = |name: &str| headers.iter().any(|h| h == name);
+        if has("outflow") && has("inflow") {
+            Some(Self::Ynab)
+        } else if has("transaction type") && has("original description") {
+            Some(Self::Mint)
+        } else {
+            None
+        }
+    }
+
+    fn mapping_key(self) -> String {
+        format!("{}_category_mapping", self.as_str())
+    }
+}
+
+/// One of the app's categories as found in the file.
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct AppCategory {
+    pub name: String,
+    pub count: usize,
+    pub total: f64,
+    /// Last import's choice, or the category with a matching name.
+    pub suggested_category_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct AppExportPreview {
+    pub format: AppFormat,
+    /// Most spending first.
+    pub categories: Vec<AppCategory>,
+    pub rows: usize,
+    /// Transfers, income and rows that couldn't be read.
+    pub skipped: usize,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, specta::Type)]
+pub struct AppImportOptions {
+    /// Told from the headers if not given.
+    pub format: Option<AppFormat>,
+    /// The app's category name to a category id here.
+    #[serde(default)]
+    pub category_map: HashMap<String, String>,
+    /// Currency of the amounts; the base currency if not given.
+    pub currency: Option<String>,
+    /// A chrono format such as "%d.%m.%Y" for files written with another
+    /// locale.
+    pub date_format: Option<String>,
+}
+
+/// A spending row and the app's category for it.
+struct AppRow {
+    row: ImportRow,
+    category: Option<String>,
+}
+
+fn parse_date(value: &str, format: Option<&str>) -> Option<NaiveDate> {
+    match format {
+        Some(format) => NaiveDate::parse_from_str(value.trim(), format).ok(),
+        None => NaiveDate::parse_from_str(value.trim(), "%m/%d/%Y")
+            .ok()
+            .or_else(|| statement::parse_date(value)),
+    }
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+/// Read `content` into spending rows, not yet checked for duplicates.
+/// Returns the format, the rows and how many were skipped.
+fn read(
+    content: &str,
+    format: Option<AppFormat>,
+    date_format: Option<&str>,
+) -> Result<(AppFormat, Vec<AppRow>, usize)> {
+    let content = content.trim_start_matches('\u{feff}');
+    let first_line = content.lines().next().unwrap_or_default();
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(statement::delimiter(first_line))
+        .flexible(true)
+        .from_reader(content.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| Error::Validation(format!("Could not read the file: {e}")))?
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let format = format
+        .or_else(|| AppFormat::detect(&headers))
+        .ok_or_else(|| {
+            Error::Validation("The file is neither a YNAB nor a Mint export".to_string())
+        })?;
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let required = |name: &str| {
+        column(name)
+            .ok_or_else(|| Error::Validation(format!("The export has no \"{name}\" column")))
+    };
+
+    let date = required("date")?;
+    let mut rows = Vec::new();
+    let mut skipped = 0;
+    match format {
+        AppFormat::Ynab => {
+            let (outflow, inflow) = (required("outflow")?, required("inflow")?);
+            let payee = column("payee");
+            let memo = column("memo");
+            // YNAB 4 has "Master: Sub" in Category and the sub name apart.
+            let category = column("sub category").or_else(|| column("category"));
+            for record in reader.records() {
+                let Ok(record) = record else {
+                    skipped += 1;
+                    continue;
+                };
+                let payee = non_empty(payee.and_then(|i| record.get(i)));
+                let category = non_empty(category.and_then(|i| record.get(i)));
+                let is_transfer = payee
+                    .as_deref()
+                    .is_some_and(|p| p.starts_with("Transfer :"));
+                let is_income = category.as_deref().is_some_and(|c| c.starts_with("Inflow"));
+                let amount = record
+                    .get(outflow)
+                    .and_then(statement::parse_amount)
+                    .unwrap_or(0.0)
+                    - record
+                        .get(inflow)
+                        .and_then(statement::parse_amount)
+                        .unwrap_or(0.0);
+                let date = record.get(date).and_then(|d| parse_date(d, date_format));
+                let Some(date) = date.filter(|_| !is_transfer && !is_income && amount > 0.0) else {
+                    skipped += 1;
+                    continue;
+                };
+                rows.push(AppRow {
+                    row: ImportRow {
+                        line: record.position().map_or(0, |p| p.line()),
+                        date,
+                        amount,
+                        description: payee.or_else(|| non_empty(memo.and_then(|i| record.get(i)))),
+                        category_id: None,
+                        duplicate: false,
+                    },
+                    category,
+                });
+            }
+        }
+        AppFormat::Mint => {
+            let amount = required("amount")?;
+            let kind = required("transaction type")?;
+            let description = column("description");
+            let category = column("category");
+            for record in reader.records() {
+                let Ok(record) = record else {
+                    skipped += 1;
+                    continue;
+                };
+                let category = non_empty(category.and_then(|i| record.get(i)));
+                let is_debit = record
+                    .get(kind)
+                    .is_some_and(|k| k.trim().eq_ignore_ascii_case("debit"));
+                let is_transfer = category
+                    .as_deref()
+                    .is_some_and(|c| MINT_TRANSFERS.contains(&c.to_lowercase().as_str()));
+                let amount = record
+                    .get(amount)
+                    .and_then(statement::parse_amount)
+                    .map(f64::abs)
+                    .filter(|a| *a > 0.0 && is_debit && !is_transfer);
+                let date = record.get(date).and_then(|d| parse_date(d, date_format));
+                let (Some(date), Some(amount)) = (date, amount) else {
+                    skipped += 1;
+                    continue;
+                };
+                rows.push(AppRow {
+                    row: ImportRow {
+                        line: record.position().map_or(0, |p| p.line()),
+                        date,
+                        amount,
+                        description: non_empty(description.and_then(|i| record.get(i))),
+                        category_id: None,
+                        duplicate: false,
+                    },
+                    category,
+                });
+            }
+        }
+    }
+    Ok((format, rows, skipped))
+}
+
+async fn stored_mapping(pool: &SqlitePool, format: AppFormat) -> Result<HashMap<String, String>> {
+    Ok(app_meta::get(pool, &format.mapping_key())
+        .await?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+/// Read the export at `path` and list its categories for the mapping step.
+pub async fn preview(
+    pool: &SqlitePool,
+    path: &Path,
+    format: Option<AppFormat>,
+    date_format: Option<&str>,
+) -> Result<AppExportPreview> {
+    let bytes = std::fs::read(path)?;
+    let (format, rows, skipped) = read(&String::from_utf8_lossy(&bytes), format, date_format)?;
+    let stored = stored_mapping(pool, format).await?;
+    let categories = drafts::load_categories(pool).await?;
+
+    let mut found: HashMap<String, (usize, f64)> = HashMap::new();
+    for row in &rows {
+        if let Some(name) = &row.category {
+            let entry = found.entry(name.clone()).or_default();
+            entry.0 += 1;
+            entry.1 += row.row.amount;
+        }
+    }
+    let mut listed: Vec<AppCategory> = found
+        .into_iter()
+        .map(|(name, (count, total))| AppCategory {
+            suggested_category_id: stored
+                .get(&name)
+                .filter(|id| categories.iter().any(|c| c.id == **id))
+                .cloned()
+                .or_else(|| find_category(&name, &categories)),
+            name,
+            count,
+            total,
+        })
+        .collect();
+    listed.sort_by(|a, b| b.total.total_cmp(&a.total));
+    Ok(AppExportPreview {
+        format,
+        categories: listed,
+        rows: rows.len(),
+        skipped,
+    })
+}
+
+/// Import the spending in the export at `path` with the categories mapped
+/// as given, or with `dry_run` only report what would be imported.
+pub async fn import(
+    pool: &SqlitePool,
+    path: &Path,
+    options: &AppImportOptions,
+    dry_run: bool,
+) -> Result<(AppFormat, ImportReport)> {
+    let bytes = std::fs::read(path)?;
+    let (format, rows, skipped) = read(
+        &String::from_utf8_lossy(&bytes),
+        options.format,
+        options.date_format.as_deref(),
+    )?;
+    let rows = rows
+        .into_iter()
+        .map(|AppRow { mut row, category }| {
+            row.category_id = category.and_then(|c| options.category_map.get(&c).cloned());
+            row
+        })
+        .collect();
+    let report = super::save(
+        pool,
+        rows,
+        skipped,
+        None,
+        options.currency.as_deref(),
+        None,
+        dry_run,
+    )
+    .await?;
+    if !dry_run {
+        let json = serde_json::to_string(&options.category_map)
+            .map_err(|e| Error::Validation(format!("Invalid category mapping: {e}")))?;
+        app_meta::set(pool, &format.mapping_key(), &json).await?;
+    }
+    Ok((format, report))
+}
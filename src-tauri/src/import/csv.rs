@@ -0,0 +1,155 @@
+//! Bank statement CSVs imported as expenses, with the columns chosen by the
+//! user.
+//!
+//! Unlike the statement reader used for reconciliation, nothing is guessed
+//! about which column holds what: the mapping names them by header or by
+//! position. Dates and amounts are read just as leniently though, and the
+//! sign follows the same rule: if every amount has the same sign they are
+//! all spending, otherwise only the negative ones are.
+
+use std::path::Path;
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+use sqlx::SqlitePool;
+
+use super::{ImportReport, ImportRow};
+use crate::error::{Error, Result};
+use crate::reconcile::statement;
+
+/// A column by its header or by zero-based position.
+#[derive(Debug, Clone, Deserialize, specta::Type)]
+#[serde(untagged)]
+pub enum Column {
+    Index(usize),
+    Header(String),
+}
+
+#[derive(Debug, Clone, Deserialize, specta::Type)]
+pub struct ColumnMapping {
+    pub date: Column,
+    pub amount: Column,
+    pub description: Option<Column>,
+    /// A chrono format such as "%d.%m.%Y"; common formats are tried if not
+    /// given.
+    pub date_format: Option<String>,
+    /// Defaults to true. Without headers, columns can only be given by
+    /// position.
+    pub has_headers: Option<bool>,
+    /// Category for every imported expense.
+    pub category_id: Option<String>,
+    /// Currency of the amounts, such as a card statement from abroad; the
+    /// base currency if not given.
+    pub currency: Option<String>,
+}
+
+fn resolve(column: &Column, headers: Option<&[String]>) -> Result<usize> {
+    match column {
+        Column::Index(index) => Ok(*index),
+        Column::Header(name) => {
+            let wanted = name.trim().to_lowercase();
+            headers
+                .and_then(|h| h.iter().position(|h| *h == wanted))
+                .ok_or_else(|| Error::Validation(format!("The file has no \"{name}\" column")))
+        }
+    }
+}
+
+fn parse_date(value: &str, format: Option<&str>) -> Option<NaiveDate> {
+    match format {
+        Some(format) => NaiveDate::parse_from_str(value.trim(), format).ok(),
+        None => statement::parse_date(value),
+    }
+}
+
+/// Read `content` into spending rows, not yet checked for duplicates.
+fn read(content: &str, mapping: &ColumnMapping) -> Result<(Vec<ImportRow>, usize)> {
+    let content = content.trim_start_matches('\u{feff}');
+    let has_headers = mapping.has_headers.unwrap_or(true);
+    let first_line = content.lines().next().unwrap_or_default();
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(statement::delimiter(first_line))
+        .has_headers(has_headers)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+
+    let headers: Option<Vec<String>> = if has_headers {
+        Some(
+            reader
+                .headers()
+                .map_err(|e| Error::Validation(format!("Could not read the file: {e}")))?
+                .iter()
+                .map(|h| h.trim().to_lowercase())
+                .collect(),
+        )
+    } else {
+        None
+    };
+    let date_column = resolve(&mapping.date, headers.as_deref())?;
+    let amount_column = resolve(&mapping.amount, headers.as_deref())?;
+    let description_column = mapping
+        .description
+        .as_ref()
+        .map(|c| resolve(c, headers.as_deref()))
+        .transpose()?;
+
+    let mut rows = Vec::new();
+    let mut skipped = 0;
+    for record in reader.records() {
+        let Ok(record) = record else {
+            skipped += 1;
+            continue;
+        };
+        let date = record
+            .get(date_column)
+            .and_then(|d| parse_date(d, mapping.date_format.as_deref()));
+        let amount = record.get(amount_column).and_then(statement::parse_amount);
+        let (Some(date), Some(amount)) = (date, amount) else {
+            skipped += 1;
+            continue;
+        };
+        let description = description_column
+            .and_then(|i| record.get(i))
+            .map(|d| d.trim().to_string())
+            .filter(|d| !d.is_empty());
+        rows.push(ImportRow {
+            line: record.position().map_or(0, |p| p.line()),
+            date,
+            amount,
+            description,
+            category_id: None,
+            duplicate: false,
+        });
+    }
+
+    if rows.iter().all(|r| r.amount >= 0.0) {
+        let before = rows.len();
+        rows.retain(|row| row.amount > 0.0);
+        skipped += before - rows.len();
+    } else {
+        skipped += super::keep_debits(&mut rows);
+    }
+    Ok((rows, skipped))
+}
+
+/// Import the spending in the CSV file at `path`, or with `dry_run` only
+/// report what would be imported.
+pub async fn import(
+    pool: &SqlitePool,
+    path: &Path,
+    mapping: &ColumnMapping,
+    dry_run: bool,
+) -> Result<ImportReport> {
+    let bytes = std::fs::read(path)?;
+    let (rows, skipped) = read(&String::from_utf8_lossy(&bytes), mapping)?;
+    super::save(
+        pool,
+        rows,
+        skipped,
+        mapping.category_id.as_deref(),
+        mapping.currency.as_deref(),
+        None,
+        dry_run,
+    )
+    .await
+}
@@ -0,0 +1,281 @@
+//! Bringing transactions from banks and other apps in as expenses.
+//!
+//! Each format only reads its file into `ImportRow`s. Checking them against
+//! what is already logged and saving the rest is shared: a row matching an
+//! existing expense on day, amount and note is a duplicate, counted as
+//! often as such expenses exist, so importing a file twice adds nothing
+//! while two identical payments on one day still both come in the first
+//! time. Amounts in another currency are converted at each row's date, and
+//! rows without a category chosen for the import go through the
+//! categorization rules.
+
+pub mod apps;
+pub mod csv;
+pub mod ofx;
+pub mod qif;
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::accounts;
+use crate::auth;
+use crate::categorize::rules::Rules;
+use crate::category_totals;
+use crate::currency;
+use crate::error::{Error, Result};
+use crate::expenses::{self, NewExpense};
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ImportRow {
+    /// Line in the file, for pointing at it in a preview.
+    pub line: u64,
+    pub date: NaiveDate,
+    /// Positive, like expense amounts.
+    pub amount: f64,
+    pub description: Option<String>,
+    /// The category the expense gets: the one chosen for the import or
+    /// mapped from the source's own, or what a categorization rule gives it.
+    pub category_id: Option<String>,
+    /// Already logged; not imported.
+    pub duplicate: bool,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub rows: Vec<ImportRow>,
+    /// Expenses created, or that would be on a dry run.
+    pub imported: usize,
+    pub duplicates: usize,
+    /// Credits and rows that couldn't be read.
+    pub skipped: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum StatementFormat {
+    Ofx,
+    Qif,
+}
+
+impl StatementFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ofx => "ofx",
+            Self::Qif => "qif",
+        }
+    }
+
+    /// Tell the format from the file's content.
+    fn detect(content: &str) -> Option<Self> {
+        let start = content.trim_start_matches('\u{feff}').trim_start();
+        let head: String = start.chars().take(512).collect::<String>().to_uppercase();
+        if head.starts_with("OFXHEADER") || head.contains("<OFX>") || head.contains("<?OFX") {
+            Some(Self::Ofx)
+        } else if start.starts_with('!') {
+            Some(Self::Qif)
+        } else {
+            None
+        }
+    }
+}
+
+/// Settings for formats that say which column is which themselves.
+#[derive(Debug, Clone, Default, Deserialize, specta::Type)]
+pub struct StatementOptions {
+    /// Category for every imported expense.
+    pub category_id: Option<String>,
+    /// Currency of the amounts; for OFX the statement's own if not given,
+    /// otherwise the base currency.
+    pub currency: Option<String>,
+    /// A chrono format such as "%d/%m/%Y" for QIF dates, which banks write
+    /// any which way; common formats are tried if not given.
+    pub date_format: Option<String>,
+}
+
+/// Same day, same cents, same note regardless of case.
+fn key(date: NaiveDate, amount: f64, note: Option<&str>) -> (NaiveDate, i64, String) {
+    (
+        date,
+        (amount * 100.0).round() as i64,
+        note.unwrap_or_default().trim().to_lowercase(),
+    )
+}
+
+/// Mark duplicates among `rows` and, unless `dry_run`, save the others as
+/// expenses in `category_id`, or the category a row already has, paid from
+/// `account_id`. `skipped` is carried into the report.
+pub(crate) async fn save(
+    pool: &SqlitePool,
+    mut rows: Vec<ImportRow>,
+    skipped: usize,
+    category_id: Option<&str>,
+    currency: Option<&str>,
+    account_id: Option<&str>,
+    dry_run: bool,
+) -> Result<ImportReport> {
+    let mut report = ImportReport {
+        dry_run,
+        rows: Vec::new(),
+        imported: 0,
+        duplicates: 0,
+        skipped,
+    };
+    let (Some(first), Some(last)) = (
+        rows.iter().map(|r| r.date).min(),
+        rows.iter().map(|r| r.date).max(),
+    ) else {
+        return Ok(report);
+    };
+
+    let base = currency::base(pool).await?;
+    let code = currency
+        .map(|c| c.trim().to_uppercase())
+        .filter(|c| *c != base);
+
+    // Only expenses paid in the file's currency can be the same payment.
+    let existing: Vec<(String, f64, Option<String>)> = sqlx::query_as(
+        "SELECT substr(date, 1, 10), COALESCE(original_amount, amount), note FROM expenses
+         WHERE deleted_at IS NULL AND substr(date, 1, 10) BETWEEN $1 AND $2 AND currency IS $3",
+    )
+    .bind(first.format("%Y-%m-%d").to_string())
+    .bind(last.format("%Y-%m-%d").to_string())
+    .bind(&code)
+    .fetch_all(pool)
+    .await?;
+    let mut logged: HashMap<_, usize> = HashMap::new();
+    for (date, amount, note) in &existing {
+        if let Ok(date) = expenses::parse_date(date) {
+            *logged
+                .entry(key(date, *amount, note.as_deref()))
+                .or_default() += 1;
+        }
+    }
+    for row in &mut rows {
+        if let Some(count) = logged
+            .get_mut(&key(row.date, row.amount, row.description.as_deref()))
+            .filter(|c| **c > 0)
+        {
+            *count -= 1;
+            row.duplicate = true;
+        }
+    }
+    let rules = Rules::load(pool).await?;
+    let mut chosen: Vec<String> = category_id.map(str::to_string).into_iter().collect();
+    for row in &mut rows {
+        row.category_id = category_id
+            .map(str::to_string)
+            .or_else(|| row.category_id.take());
+        match &row.category_id {
+            Some(id) if !row.duplicate => chosen.push(id.clone()),
+            Some(_) => {}
+            None => {
+                row.category_id = rules
+                    .category_for(row.description.as_deref())
+                    .map(str::to_string);
+            }
+        }
+    }
+    chosen.sort();
+    chosen.dedup();
+    report.duplicates = rows.iter().filter(|r| r.duplicate).count();
+    report.imported = rows.len() - report.duplicates;
+
+    if !dry_run && report.imported > 0 {
+        if let Some(code) = &code {
+            let mut days: Vec<NaiveDate> = rows
+                .iter()
+                .filter(|r| !r.duplicate)
+                .map(|r| r.date)
+                .collect();
+            days.sort();
+            days.dedup();
+            for day in days {
+                currency::ensure(pool, code, day).await?;
+            }
+        }
+        let mut tx = pool.begin().await?;
+        for category_id in &chosen {
+            expenses::check_category(&mut tx, category_id).await?;
+        }
+        if let Some(account_id) = account_id {
+            accounts::check_account(&mut tx, account_id).await?;
+        }
+        let user_id = auth::user_id_on(&mut tx).await?;
+        let mut cells = Vec::new();
+        for row in rows.iter().filter(|r| !r.duplicate) {
+            let input = NewExpense {
+                amount: row.amount,
+                category_id: row.category_id.clone(),
+                note: row.description.clone(),
+                date: None,
+                payment_method: None,
+                currency: code.clone(),
+                account_id: account_id.map(str::to_string),
+            };
+            let money =
+                currency::convert_on(&mut tx, row.amount, code.as_deref(), row.date).await?;
+            let expense =
+                expenses::insert(&mut tx, user_id.as_deref(), &input, &money, row.date).await?;
+            cells.push(expense.cell());
+        }
+        tx.commit().await?;
+        category_totals::refresh(pool, &cells).await?;
+        accounts::refresh(pool, &[account_id.map(str::to_string)]).await?;
+    }
+
+    report.rows = rows;
+    Ok(report)
+}
+
+/// Import the spending in the OFX or QIF file at `path`, or with `dry_run`
+/// only report what would be imported. The format is detected if not given.
+pub async fn import_statement(
+    pool: &SqlitePool,
+    path: &Path,
+    format: Option<StatementFormat>,
+    options: &StatementOptions,
+    dry_run: bool,
+) -> Result<(StatementFormat, ImportReport)> {
+    let bytes = std::fs::read(path)?;
+    let content = String::from_utf8_lossy(&bytes);
+    let format = format
+        .or_else(|| StatementFormat::detect(&content))
+        .ok_or_else(|| {
+            Error::Validation("The file is neither an OFX nor a QIF statement".to_string())
+        })?;
+    let (rows, skipped, file_currency) = match format {
+        StatementFormat::Ofx => ofx::read(&content)?,
+        StatementFormat::Qif => {
+            let (rows, skipped) = qif::read(&content, options.date_format.as_deref())?;
+            (rows, skipped, None)
+        }
+    };
+    let currency = options.currency.clone().or(file_currency);
+    let report = save(
+        pool,
+        rows,
+        skipped,
+        options.category_id.as_deref(),
+        currency.as_deref(),
+        None,
+        dry_run,
+    )
+    .await?;
+    Ok((format, report))
+}
+
+/// Keep the debits among signed transactions, as positive amounts. Returns
+/// how many credits were dropped.
+pub(crate) fn keep_debits(rows: &mut Vec<ImportRow>) -> usize {
+    let before = rows.len();
+    rows.retain_mut(|row| {
+        row.amount = -row.amount;
+        row.amount > 0.0
+    });
+    before - rows.len()
+}
@@ -0,0 +1,117 @@
+//! OFX statements, the format behind most banks' "download for Quicken or
+//! Money" buttons.
+//!
+//! OFX 1.x is SGML whose leaf tags are never closed, 2.x is XML; one tag
+//! scanner reads both, taking the text after a tag up to the next one as
+//! its value. Every `<STMTTRN>` is a transaction, in bank and credit card
+//! statements alike. `TRNAMT` is signed from the account's side, so debits
+//! are the negative ones. `CURDEF` says which currency the statement is in.
+
+use chrono::NaiveDate;
+
+use super::ImportRow;
+use crate::error::{Error, Result};
+use crate::reconcile::statement;
+
+/// A tag and the text after it, with the line it starts on.
+struct Tag<'a> {
+    line: u64,
+    name: String,
+    value: &'a str,
+}
+
+fn tags(content: &str) -> Vec<Tag<'_>> {
+    let mut tags = Vec::new();
+    let mut line = 1;
+    let mut rest = content;
+    while let Some(open) = rest.find('<') {
+        line += rest[..open].matches('\n').count() as u64;
+        rest = &rest[open + 1..];
+        let Some(close) = rest.find('>') else {
+            break;
+        };
+        let name = rest[..close].trim().to_uppercase();
+        rest = &rest[close + 1..];
+        let end = rest.find('<').unwrap_or(rest.len());
+        tags.push(Tag {
+            line,
+            name,
+            value: rest[..end].trim(),
+        });
+    }
+    tags
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// "20240131", "20240131120000" or "20240131120000.000[-5:EST]".
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()
+}
+
+#[derive(Default)]
+struct Transaction {
+    line: u64,
+    date: Option<NaiveDate>,
+    amount: Option<f64>,
+    name: Option<String>,
+    memo: Option<String>,
+}
+
+/// Read the debits in `content`, with the number of transactions skipped
+/// and the statement's currency.
+pub fn read(content: &str) -> Result<(Vec<ImportRow>, usize, Option<String>)> {
+    let tags = tags(content);
+    if !tags.iter().any(|t| t.name == "OFX") {
+        return Err(Error::Validation("This is not an OFX file".to_string()));
+    }
+
+    let mut rows = Vec::new();
+    let mut skipped = 0;
+    let mut currency = None;
+    let mut current: Option<Transaction> = None;
+    for tag in tags {
+        let text = || Some(unescape(tag.value)).filter(|v| !v.is_empty());
+        match (tag.name.as_str(), current.as_mut()) {
+            ("CURDEF", _) if currency.is_none() => currency = text(),
+            ("STMTTRN", _) => {
+                current = Some(Transaction {
+                    line: tag.line,
+                    ..Transaction::default()
+                });
+            }
+            ("/STMTTRN", Some(_)) => {
+                let Some(transaction) = current.take() else {
+                    continue;
+                };
+                let (Some(date), Some(amount)) = (transaction.date, transaction.amount) else {
+                    skipped += 1;
+                    continue;
+                };
+                rows.push(ImportRow {
+                    line: transaction.line,
+                    date,
+                    amount,
+                    description: transaction.name.or(transaction.memo),
+                    category_id: None,
+                    duplicate: false,
+                });
+            }
+            ("DTPOSTED", Some(t)) => t.date = parse_date(tag.value),
+            ("TRNAMT", Some(t)) => t.amount = statement::parse_amount(tag.value),
+            ("NAME", Some(t)) => t.name = text(),
+            ("MEMO", Some(t)) => t.memo = text(),
+            _ => {}
+        }
+    }
+
+    skipped += super::keep_debits(&mut rows);
+    Ok((rows, skipped, currency))
+}
@@ -0,0 +1,118 @@
+//! QIF files, the older Quicken export many banks still offer.
+//!
+//! A QIF file is a run of `!Type:` sections, each a list of records ending
+//! in `^`, one field per line keyed by its first letter: `D` date, `T`
+//! amount, `P` payee, `M` memo. Only the cash-like sections hold spending;
+//! investment, category and account lists are passed over. As in OFX,
+//! negative amounts are money going out. Dates are the trouble: Quicken
+//! writes "1/31'24" for 2024, and banks pick day or month first as they
+//! like, so month first is tried before day first unless the user says.
+
+use chrono::NaiveDate;
+
+use super::ImportRow;
+use crate::error::{Error, Result};
+use crate::reconcile::statement;
+
+/// Section types that hold transactions of an account.
+const CASH_TYPES: &[&str] = &["bank", "cash", "ccard", "oth a", "oth l"];
+
+const DATE_FORMATS: &[&str] = &["%m/%d/%Y", "%d/%m/%Y", "%d.%m.%Y", "%Y-%m-%d", "%m-%d-%Y"];
+
+/// "1/31'24", "1/31' 4" and "01/31/24" as "1/31/2024".
+fn normalize_date(value: &str) -> String {
+    let value = value.trim().replace("' ", "'0").replace('\'', "/");
+    let mut parts: Vec<String> = value.split(['/', '.', '-']).map(str::to_string).collect();
+    let separator = if value.contains('.') { "." } else { "/" };
+    if parts.len() == 3 && parts[0].len() <= 2 && parts[2].len() <= 2 {
+        let year: u32 = parts[2].parse().unwrap_or(0);
+        parts[2] = (if year < 70 { 2000 + year } else { 1900 + year }).to_string();
+        return parts.join(separator);
+    }
+    value
+}
+
+fn parse_date(value: &str, format: Option<&str>) -> Option<NaiveDate> {
+    if let Some(format) = format {
+        return NaiveDate::parse_from_str(value.trim(), format).ok();
+    }
+    let value = normalize_date(value);
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(&value, format).ok())
+        .or_else(|| statement::parse_date(&value))
+}
+
+#[derive(Default)]
+struct Record {
+    line: u64,
+    date: Option<NaiveDate>,
+    amount: Option<f64>,
+    payee: Option<String>,
+    memo: Option<String>,
+}
+
+/// Read the debits in `content`, with the number of records skipped.
+/// `date_format` is a chrono format for the `D` lines.
+pub fn read(content: &str, date_format: Option<&str>) -> Result<(Vec<ImportRow>, usize)> {
+    let content = content.trim_start_matches('\u{feff}');
+    if !content.trim_start().starts_with('!') {
+        return Err(Error::Validation("This is not a QIF file".to_string()));
+    }
+
+    let mut rows = Vec::new();
+    let mut skipped = 0;
+    let mut in_cash_section = false;
+    let mut record = Record::default();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim_end();
+        let Some(field) = line.chars().next() else {
+            continue;
+        };
+        let value = line[field.len_utf8()..].trim();
+        if field == '!' {
+            let header = value.to_lowercase();
+            if let Some(kind) = header.strip_prefix("type:") {
+                in_cash_section = CASH_TYPES.contains(&kind.trim());
+            } else if header != "option:autoswitch" && header != "clear:autoswitch" {
+                // `!Account` and the like start lists that aren't
+                // transactions.
+                in_cash_section = false;
+            }
+            record = Record::default();
+            continue;
+        }
+        if !in_cash_section {
+            continue;
+        }
+        if record.line == 0 {
+            record.line = index as u64 + 1;
+        }
+        match field {
+            'D' => record.date = parse_date(value, date_format),
+            // `U` is the same amount, in more precision where there is one.
+            'T' => record.amount = statement::parse_amount(value),
+            'U' if record.amount.is_none() => record.amount = statement::parse_amount(value),
+            'P' => record.payee = Some(value.to_string()).filter(|v| !v.is_empty()),
+            'M' => record.memo = Some(value.to_string()).filter(|v| !v.is_empty()),
+            '^' => {
+                let done = std::mem::take(&mut record);
+                match (done.date, done.amount) {
+                    (Some(date), Some(amount)) => rows.push(ImportRow {
+                        line: done.line,
+                        date,
+                        amount,
+                        description: done.payee.or(done.memo),
+                        category_id: None,
+                        duplicate: false,
+                    }),
+                    _ => skipped += 1,
+                }
+            }
+            _ => {}
+        }
+    }
+
+    skipped += super::keep_debits(&mut rows);
+    Ok((rows, skipped))
+}
@@ -0,0 +1,314 @@
+//! Income sources and what actually came in from them.
+//!
+//! Each source has an amount it is expected to pay per budget period and
+//! the day of the period it usually arrives. Comparing that with the logged
+//! income gives the variance ("salary arrived €200 short"), and a source
+//! with nothing logged past its usual day gets a reminder.
+
+use std::slice;
+
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::AppHandle;
+
+use crate::accounts;
+use crate::db::{new_id, now};
+use crate::error::{Error, Result};
+use crate::notify;
+use crate::periods::{PaySchedule, Period};
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct IncomeSource {
+    pub id: String,
+    pub user_id: Option<String>,
+    pub name: String,
+    pub expected_amount: f64,
+    /// Day of the period the money usually arrives, 1 being its first day.
+    pub expected_day: i64,
+    pub created_at: String,
+    pub updated_at: String,
+    pub deleted_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct NewIncomeSource {
+    pub name: String,
+    pub expected_amount: f64,
+    pub expected_day: i64,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct IncomeEntry {
+    pub id: String,
+    pub user_id: Option<String>,
+    pub source_id: String,
+    pub amount: f64,
+    /// "YYYY-MM-DD".
+    pub date: String,
+    pub note: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub deleted_at: Option<String>,
+    /// The account it was paid into; see `accounts`.
+    pub account_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct NewIncomeEntry {
+    pub source_id: String,
+    pub amount: f64,
+    pub date: NaiveDate,
+    pub note: Option<String>,
+    pub account_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct IncomeVariance {
+    pub source_id: String,
+    pub name: String,
+    pub expected: f64,
+    pub received: f64,
+    /// `received - expected`; negative when short.
+    pub difference: f64,
+    /// When the money usually arrives in this period.
+    pub expected_by: NaiveDate,
+    /// Nothing logged yet and the usual day has passed.
+    pub overdue: bool,
+}
+
+pub async fn list_sources(pool: &SqlitePool) -> Result<Vec<IncomeSource>> {
+    Ok(sqlx::query_as::<_, IncomeSource>(
+        "SELECT * FROM income_sources WHERE deleted_at IS NULL ORDER BY name ASC",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn create_source(
+    pool: &SqlitePool,
+    user_id: Option<&str>,
+    input: NewIncomeSource,
+) -> Result<IncomeSource> {
+    let name = input.name.trim();
+    if name.is_empty() {
+        return Err(Error::Validation("Name is required".to_string()));
+    }
+    if input.expected_amount < 0.0 {
+        return Err(Error::Validation(
+            "Expected amount can't be negative".to_string(),
+        ));
+    }
+    if !(1..=31).contains(&input.expected_day) {
+        return Err(Error::Validation(
+            "Expected day must be between 1 and 31".to_string(),
+        ));
+    }
+
+    let id = new_id();
+    let now = now();
+    sqlx::query(
+        "INSERT INTO income_sources (id, user_id, name, expected_amount, expected_day, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $6)",
+    )
+    .bind(&id)
+    .bind(user_id)
+    .bind(name)
+    .bind(input.expected_amount)
+    .bind(input.expected_day)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+
+    Ok(IncomeSource {
+        id,
+        user_id: user_id.map(str::to_string),
+        name: name.to_string(),
+        expected_amount: input.expected_amount,
+        expected_day: input.expected_day,
+        created_at: now.clone(),
+        updated_at: now,
+        deleted_at: None,
+    })
+}
+
+/// Stop expecting income from a source. What it paid stays logged.
+pub async fn delete_source(pool: &SqlitePool, id: &str) -> Result<()> {
+    let now = now();
+    let deleted = sqlx::query(
+        "UPDATE income_sources SET deleted_at = $1, updated_at = $1
+         WHERE id = $2 AND deleted_at IS NULL",
+    )
+    .bind(&now)
+    .bind(id)
+    .execute(pool)
+    .await?
+    .rows_affected();
+    if deleted == 0 {
+        return Err(Error::Validation("Unknown income source".to_string()));
+    }
+    Ok(())
+}
+
+/// Income received between two days (inclusive), newest first.
+pub async fn entries(
+    pool: &SqlitePool,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<IncomeEntry>> {
+    Ok(sqlx::query_as::<_, IncomeEntry>(
+        "SELECT * FROM income_entries
+         WHERE deleted_at IS NULL AND date >= $1 AND date <= $2
+         ORDER BY date DESC, created_at DESC",
+    )
+    .bind(start.format("%Y-%m-%d").to_string())
+    .bind(end.format("%Y-%m-%d").to_string())
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn log(
+    pool: &SqlitePool,
+    user_id: Option<&str>,
+    input: NewIncomeEntry,
+) -> Result<IncomeEntry> {
+    if input.amount <= 0.0 {
+        return Err(Error::Validation("Amount must be positive".to_string()));
+    }
+    let source: Option<(String,)> =
+        sqlx::query_as("SELECT id FROM income_sources WHERE id = $1 AND deleted_at IS NULL")
+            .bind(&input.source_id)
+            .fetch_optional(pool)
+            .await?;
+    if source.is_none() {
+        return Err(Error::Validation("Unknown income source".to_string()));
+    }
+    if let Some(account_id) = &input.account_id {
+        accounts::check_account(&mut *pool.acquire().await?, account_id).await?;
+    }
+
+    let id = new_id();
+    let now = now();
+    let date = input.date.format("%Y-%m-%d").to_string();
+    sqlx::query(
+        "INSERT INTO income_entries
+            (id, user_id, source_id, amount, date, note, account_id, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $8)",
+    )
+    .bind(&id)
+    .bind(user_id)
+    .bind(&input.source_id)
+    .bind(input.amount)
+    .bind(&date)
+    .bind(&input.note)
+    .bind(&input.account_id)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+    accounts::refresh(pool, slice::from_ref(&input.account_id)).await?;
+
+    Ok(IncomeEntry {
+        id,
+        user_id: user_id.map(str::to_string),
+        source_id: input.source_id,
+        amount: input.amount,
+        date,
+        note: input.note,
+        created_at: now.clone(),
+        updated_at: now,
+        deleted_at: None,
+        account_id: input.account_id,
+    })
+}
+
+pub async fn delete_entry(pool: &SqlitePool, id: &str) -> Result<()> {
+    let now = now();
+    let deleted: Option<(Option<String>,)> = sqlx::query_as(
+        "UPDATE income_entries SET deleted_at = $1, updated_at = $1
+         WHERE id = $2 AND deleted_at IS NULL
+         RETURNING account_id",
+    )
+    .bind(&now)
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+    let Some((account_id,)) = deleted else {
+        return Err(Error::Validation("Income entry not found".to_string()));
+    };
+    accounts::refresh(pool, &[account_id]).await
+}
+
+/// The usual arrival day of `source` within `period`.
+fn expected_by(source: &IncomeSource, period: &Period) -> NaiveDate {
+    let offset = (source.expected_day.max(1) - 1) as u64;
+    period
+        .start
+        .checked_add_days(Days::new(offset))
+        .map_or(period.end, |date| date.min(period.end))
+}
+
+/// Expected against received income per source for the period `today`
+/// falls in.
+pub async fn variance(
+    pool: &SqlitePool,
+    schedule: &PaySchedule,
+    today: NaiveDate,
+) -> Result<Vec<IncomeVariance>> {
+    let period = schedule.period_at(today);
+    let sources = list_sources(pool).await?;
+    let mut variances = Vec::with_capacity(sources.len());
+    for source in sources {
+        let (received, count): (f64, i64) = sqlx::query_as(
+            "SELECT COALESCE(SUM(amount), 0.0), COUNT(*) FROM income_entries
+             WHERE source_id = $1 AND date BETWEEN $2 AND $3 AND deleted_at IS NULL",
+        )
+        .bind(&source.id)
+        .bind(period.start.format("%Y-%m-%d").to_string())
+        .bind(period.end.format("%Y-%m-%d").to_string())
+        .fetch_one(pool)
+        .await?;
+
+        let expected_by = expected_by(&source, &period);
+        variances.push(IncomeVariance {
+            source_id: source.id,
+            name: source.name,
+            expected: source.expected_amount,
+            received,
+            difference: received - source.expected_amount,
+            expected_by,
+            overdue: count == 0 && source.expected_amount > 0.0 && today > expected_by,
+        });
+    }
+    Ok(variances)
+}
+
+/// Remind once per period about each source whose money hasn't been
+/// logged by its usual day.
+pub async fn notify_overdue(
+    app: &AppHandle,
+    pool: &SqlitePool,
+    schedule: &PaySchedule,
+    today: NaiveDate,
+) -> Result<()> {
+    let period = schedule.period_at(today);
+    for missing in variance(pool, schedule, today).await? {
+        if !missing.overdue {
+            continue;
+        }
+        notify::send_once(
+            app,
+            pool,
+            &format!("income_missing:{}:{}", missing.source_id, period.key),
+            "income_missing",
+            None,
+            "Income not logged yet",
+            &format!(
+                "{} usually arrives by {}. Has it come in?",
+                missing.name,
+                missing.expected_by.format("%B %-d")
+            ),
+        )
+        .await?;
+    }
+    Ok(())
+}
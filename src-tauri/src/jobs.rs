@@ -0,0 +1,383 @@
+//! Background jobs started from `setup`.
+//!
+//! Jobs run for the life of the app and only log their failures. On first
+//! launch the frontend may not have run its migrations yet, so a failed run
+//! is simply retried on the next tick.
+//!
+//! A few jobs also run when a window regains focus, since their results are
+//! about to be looked at.
+
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::analysis::price_changes;
+use crate::db::Db;
+use crate::error::Result;
+
+/// Emitted with a `PriceIncrease` payload the first time an increase is seen.
+pub const PRICE_INCREASED_EVENT: &str = "subscription://price-increased";
+
+/// Give the frontend time to open the database and migrate before the first run.
+const STARTUP_DELAY: Duration = Duration::from_secs(60);
+
+const PRICE_CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+const SUBSCRIPTION_SCAN_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+const CHECKIN_FOLLOW_UP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+const ATTACHMENT_SYNC_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+const BACKUP_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+const MONTH_CLOSE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+const INCOME_CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+const BILL_REMINDER_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+const CATEGORY_ALERT_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+const ENTITLEMENT_REFRESH_INTERVAL: Duration = Duration::from_secs(12 * 60 * 60);
+
+const REFERRAL_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+const PLATFORM_NOTIFICATION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+const NOTIFICATION_DISPATCH_INTERVAL: Duration = Duration::from_secs(60);
+
+const NOTIFICATION_SCHEDULE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+const EXCHANGE_RATE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Access tokens last an hour; this leaves plenty of runs inside the
+/// refresh margin.
+const TOKEN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+const HABIT_CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Least time between runs triggered by focus.
+const RESUME_MIN_GAP: Duration = Duration::from_secs(15 * 60);
+
+static LAST_RESUME: Mutex<Option<Instant>> = Mutex::new(None);
+
+const TRASH_PURGE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Triggers send right away; this picks up the retries.
+const WEBHOOK_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Banks allow four fetches a day per account.
+const BANK_FETCH_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+pub fn start(app: &AppHandle) {
+    // The first focus comes with launch, before the frontend has migrated.
+    *LAST_RESUME.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+    spawn_job(
+        app,
+        "Price increase check",
+        PRICE_CHECK_INTERVAL,
+        check_price_increases,
+    );
+    spawn_job(
+        app,
+        "Subscription scan",
+        SUBSCRIPTION_SCAN_INTERVAL,
+        scan_subscriptions,
+    );
+    spawn_job(
+        app,
+        "Check-in follow-up",
+        CHECKIN_FOLLOW_UP_INTERVAL,
+        follow_up_checkins,
+    );
+    spawn_job(
+        app,
+        "Attachment sync",
+        ATTACHMENT_SYNC_INTERVAL,
+        sync_attachments,
+    );
+    spawn_job(app, "Cloud backup", BACKUP_CHECK_INTERVAL, run_due_backups);
+    spawn_job(app, "Month close", MONTH_CLOSE_INTERVAL, close_month);
+    spawn_job(app, "Income check", INCOME_CHECK_INTERVAL, check_income);
+    spawn_job(app, "Bill reminders", BILL_REMINDER_INTERVAL, remind_bills);
+    spawn_job(
+        app,
+        "Spending alerts",
+        CATEGORY_ALERT_INTERVAL,
+        check_category_alerts,
+    );
+    spawn_job(
+        app,
+        "Entitlement refresh",
+        ENTITLEMENT_REFRESH_INTERVAL,
+        refresh_entitlements,
+    );
+    spawn_job(
+        app,
+        "Referral redemption",
+        REFERRAL_INTERVAL,
+        redeem_referrals,
+    );
+    spawn_job(
+        app,
+        "Platform notifications",
+        PLATFORM_NOTIFICATION_INTERVAL,
+        register_platform_notifications,
+    );
+    spawn_job(
+        app,
+        "Notification dispatch",
+        NOTIFICATION_DISPATCH_INTERVAL,
+        dispatch_notifications,
+    );
+    spawn_job(
+        app,
+        "Notification schedule",
+        NOTIFICATION_SCHEDULE_INTERVAL,
+        reschedule_notifications,
+    );
+    spawn_job(
+        app,
+        "Exchange rates",
+        EXCHANGE_RATE_INTERVAL,
+        refresh_exchange_rates,
+    );
+    spawn_job(
+        app,
+        "Token refresh",
+        TOKEN_REFRESH_INTERVAL,
+        refresh_session,
+    );
+    spawn_job(app, "Habit check", HABIT_CHECK_INTERVAL, evaluate_habits);
+    spawn_job(app, "Trash purge", TRASH_PURGE_INTERVAL, purge_trash);
+    spawn_job(app, "Maintenance", MAINTENANCE_INTERVAL, run_maintenance);
+    spawn_job(
+        app,
+        "Webhook delivery",
+        WEBHOOK_RETRY_INTERVAL,
+        deliver_webhooks,
+    );
+    spawn_job(
+        app,
+        "Bank transactions",
+        BANK_FETCH_INTERVAL,
+        fetch_bank_transactions,
+    );
+}
+
+/// Called when a window gains focus.
+pub fn on_resume(app: &AppHandle) {
+    {
+        let mut last = LAST_RESUME.lock().unwrap_or_else(|e| e.into_inner());
+        if last.is_some_and(|at| at.elapsed() < RESUME_MIN_GAP) {
+            return;
+        }
+        *last = Some(Instant::now());
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = evaluate_habits(app).await {
+            eprintln!("[Jobs] Habit check failed: {e}");
+        }
+    });
+}
+
+/// Run `job` every `interval` after the startup delay.
+fn spawn_job<F, Fut>(app: &AppHandle, name: &'static str, interval: Duration, job: F)
+where
+    F: Fn(AppHandle) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<()>> + Send,
+{
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(STARTUP_DELAY).await;
+        loop {
+            if let Err(e) = job(app.clone()).await {
+                eprintln!("[Jobs] {name} failed: {e}");
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+/// Notify about subscription price increases we haven't reported yet.
+async fn check_price_increases(app: AppHandle) -> Result<()> {
+    let db = app.state::<Db>();
+    let today = chrono::Local::now().date_naive();
+
+    for increase in price_changes::find_increases(db.pool(), today).await? {
+        let key = format!("price_increase:{}", increase.expense_id);
+        let title = format!("{} got more expensive", increase.name);
+        let body = format!(
+            "Now {:.2} instead of {:.2} (+{:.0}%), about {:.2} more per year.",
+            increase.new_amount,
+            increase.previous_amount,
+            increase.percent * 100.0,
+            increase.annual_impact,
+        );
+        let first_time =
+            crate::notify::send_once(&app, db.pool(), &key, "price_increase", None, &title, &body)
+                .await?;
+        if first_time {
+            app.emit(PRICE_INCREASED_EVENT, &increase)?;
+        }
+    }
+    Ok(())
+}
+
+async fn follow_up_checkins(app: AppHandle) -> Result<()> {
+    let db = app.state::<Db>();
+    crate::checkins::escalate(&app, db.pool()).await
+}
+
+async fn deliver_webhooks(app: AppHandle) -> Result<()> {
+    let db = app.state::<Db>();
+    crate::webhooks::deliver_due(db.pool()).await?;
+    Ok(())
+}
+
+async fn fetch_bank_transactions(app: AppHandle) -> Result<()> {
+    let db = app.state::<Db>();
+    crate::banking::fetch_and_announce(&app, db.pool(), false).await?;
+    Ok(())
+}
+
+async fn sync_attachments(app: AppHandle) -> Result<()> {
+    let report = crate::attachments::sync::run(&app).await?;
+    if report.failed > 0 {
+        eprintln!("[Jobs] {} attachment transfer(s) failed", report.failed);
+    }
+    Ok(())
+}
+
+async fn run_due_backups(app: AppHandle) -> Result<()> {
+    let db = app.state::<Db>();
+    crate::backup::run_due(&app, db.pool()).await
+}
+
+async fn close_month(app: AppHandle) -> Result<()> {
+    let db = app.state::<Db>();
+    crate::month_close::close_previous(&app, db.pool()).await?;
+    Ok(())
+}
+
+async fn check_income(app: AppHandle) -> Result<()> {
+    let db = app.state::<Db>();
+    let schedule = crate::periods::schedule(db.pool()).await?;
+    let today = chrono::Local::now().date_naive();
+    crate::income::notify_overdue(&app, db.pool(), &schedule, today).await
+}
+
+/// Point the user at subscriptions found for the first time, in one
+/// notification a day at most.
+async fn scan_subscriptions(app: AppHandle) -> Result<()> {
+    let db = app.state::<Db>();
+    let today = chrono::Local::now().date_naive();
+    let new = crate::recurring::detected::scan(db.pool(), today).await?;
+    let (title, body) = match new.as_slice() {
+        [] => return Ok(()),
+        [one] => (
+            format!("{} looks like a subscription", one.name),
+            format!(
+                "{:.2} {}. Track it to be reminded before it renews.",
+                one.amount, one.cadence
+            ),
+        ),
+        many => (
+            format!("Found {} possible subscriptions", many.len()),
+            "Track them to be reminded before they renew.".to_string(),
+        ),
+    };
+    crate::notify::send_once(
+        &app,
+        db.pool(),
+        &format!("subscriptions_detected:{today}"),
+        "subscriptions_detected",
+        None,
+        &title,
+        &body,
+    )
+    .await?;
+    Ok(())
+}
+
+async fn remind_bills(app: AppHandle) -> Result<()> {
+    let db = app.state::<Db>();
+    let today = chrono::Local::now().date_naive();
+    crate::recurring::send_due_reminders(&app, db.pool(), today).await?;
+    crate::bills::check(&app, db.pool(), today).await
+}
+
+/// Catches expenses that arrived through sync rather than the frontend.
+async fn check_category_alerts(app: AppHandle) -> Result<()> {
+    let db = app.state::<Db>();
+    let today = chrono::Local::now().date_naive();
+    crate::category_alerts::check_all(&app, db.pool(), today).await?;
+    crate::budget_alerts::check(&app, db.pool(), today).await?;
+    Ok(())
+}
+
+async fn refresh_entitlements(app: AppHandle) -> Result<()> {
+    let db = app.state::<Db>();
+    crate::entitlements::refresh(&app, db.pool(), None).await
+}
+
+/// Retry invite codes opened while offline or signed out.
+async fn redeem_referrals(app: AppHandle) -> Result<()> {
+    let db = app.state::<Db>();
+    crate::referrals::redeem_pending(&app, db.pool()).await
+}
+
+async fn dispatch_notifications(app: AppHandle) -> Result<()> {
+    let db = app.state::<Db>();
+    crate::notify::scheduler::dispatch(&app, db.pool()).await?;
+    Ok(())
+}
+
+/// Picks up cron changes pulled from another device and refreshes the text
+/// of progress updates.
+async fn reschedule_notifications(app: AppHandle) -> Result<()> {
+    let db = app.state::<Db>();
+    crate::notify::scheduler::reschedule(db.pool()).await
+}
+
+/// Keep the OS holding the next notifications as earlier ones go out.
+async fn register_platform_notifications(app: AppHandle) -> Result<()> {
+    let db = app.state::<Db>();
+    crate::notify::platform::register(&app, db.pool()).await?;
+    Ok(())
+}
+
+/// Keeps a recent rate cached for converting spending while offline.
+async fn refresh_exchange_rates(app: AppHandle) -> Result<()> {
+    let db = app.state::<Db>();
+    crate::currency::refresh_latest(&app, db.pool()).await
+}
+
+async fn refresh_session(app: AppHandle) -> Result<()> {
+    crate::auth::refresh_session(&app).await
+}
+
+async fn evaluate_habits(app: AppHandle) -> Result<()> {
+    let db = app.state::<Db>();
+    let today = chrono::Local::now().date_naive();
+    crate::habits::evaluate(&app, db.pool(), today).await
+}
+
+async fn purge_trash(app: AppHandle) -> Result<()> {
+    let db = app.state::<Db>();
+    crate::trash::purge(db.pool()).await?;
+    Ok(())
+}
+
+async fn run_maintenance(app: AppHandle) -> Result<()> {
+    let db = app.state::<Db>();
+    crate::maintenance::run(db.pool()).await?;
+    crate::attachments::files::clean_up(&app, db.pool()).await?;
+    Ok(())
+}
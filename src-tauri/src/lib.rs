@@ -3,17 +3,418 @@
 // Database migrations are now handled by the TypeScript migration runner
 // in src/lib/migrations.ts, which reads from supabase/migrations/ as the
 // single source of truth for both local SQLite and remote Supabase schemas.
+//
+// Rust commands share the same goaldy.db file through their own pool (db.rs).
+
+mod accounts;
+mod analysis;
+mod app_meta;
+mod archive;
+mod attachments;
+mod auth;
+mod automation;
+mod backend;
+mod backup;
+mod banking;
+mod bills;
+mod budget_alerts;
+mod budgets;
+mod bulk;
+mod categorize;
+mod category_alerts;
+mod category_budgets;
+mod category_totals;
+mod checkins;
+#[cfg(desktop)]
+pub mod cli;
+mod commands;
+mod connectivity;
+mod currency;
+mod db;
+mod drafts;
+mod encryption;
+mod entitlements;
+mod error;
+mod events;
+mod expenses;
+mod experiments;
+mod export;
+mod goals;
+mod habits;
+mod history;
+mod households;
+mod import;
+mod income;
+mod jobs;
+mod maintenance;
+mod merchants;
+mod month_close;
+mod net_worth;
+mod notify;
+mod ocr;
+mod orphans;
+mod periods;
+mod preferences;
+mod quick_add;
+mod reconcile;
+mod recurring;
+mod referrals;
+mod reimbursements;
+mod search;
+mod secrets;
+mod speech;
+mod splits;
+mod sync;
+mod tags;
+mod telemetry;
+mod transfer;
+mod trash;
+#[cfg(desktop)]
+mod tray;
+mod trials;
+mod webhooks;
+
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let specta = specta_builder();
+    #[cfg(debug_assertions)]
+    export_bindings(&specta);
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(
             tauri_plugin_sql::Builder::default()
                 // No migrations here - they are handled by TypeScript
                 .build(),
         )
+        .setup(|app| {
+            let db = tauri::async_runtime::block_on(db::Db::open(app.handle()))?;
+            app.manage(db);
+            app.manage(backend::Backend::default());
+            app.manage(transfer::Transfers::default());
+            app.manage(quick_add::QuickAdd::default());
+            app.manage(connectivity::Connectivity::default());
+            app.manage(sync::worker::SyncWorker::default());
+            app.manage(sync::peer::PeerSync::default());
+            app.manage(automation::AutomationApi::default());
+            connectivity::watch(app.handle());
+            referrals::watch(app.handle());
+            banking::watch(app.handle());
+            transfer::watch(app.handle());
+            quick_add::watch(app.handle());
+            #[cfg(desktop)]
+            quick_add::window::setup(app.handle())?;
+            #[cfg(desktop)]
+            tray::setup(app.handle())?;
+            jobs::start(app.handle());
+            sync::worker::start(app.handle());
+            sync::realtime::start(app.handle());
+            automation::start(app.handle());
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::Focused(true) = event {
+                jobs::on_resume(window.app_handle());
+                // Picks up a new budget period since the last write.
+                #[cfg(desktop)]
+                tray::refresh(window.app_handle());
+            }
+        })
+        .invoke_handler(specta.invoke_handler())
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+/// Every command the frontend can invoke, with the types they take and
+/// return.
+fn specta_builder() -> tauri_specta::Builder<tauri::Wry> {
+    tauri_specta::Builder::<tauri::Wry>::new().commands(tauri_specta::collect_commands![
+        commands::drafts::parse_expense_text,
+        commands::quick_add::get_quick_add_auto_save,
+        commands::quick_add::set_quick_add_auto_save,
+        commands::quick_add::take_quick_add_draft,
+        commands::quick_add::quick_add_shared_text,
+        commands::quick_add::quick_add_expense,
+        commands::quick_add::open_quick_add_window,
+        commands::quick_add::close_quick_add_window,
+        commands::quick_add::get_quick_add_shortcut,
+        commands::quick_add::set_quick_add_shortcut,
+        commands::automation::get_automation_status,
+        commands::automation::set_automation_enabled,
+        commands::automation::regenerate_automation_token,
+        commands::webhooks::get_webhooks,
+        commands::webhooks::create_webhook,
+        commands::webhooks::update_webhook,
+        commands::webhooks::delete_webhook,
+        commands::webhooks::test_webhook,
+        commands::webhooks::get_webhook_deliveries,
+        commands::banking::get_bank_credentials_set,
+        commands::banking::set_bank_credentials,
+        commands::banking::get_bank_institutions,
+        commands::banking::get_bank_connections,
+        commands::banking::connect_bank,
+        commands::banking::create_plaid_link_token,
+        commands::banking::link_plaid,
+        commands::banking::refresh_bank_connection,
+        commands::banking::disconnect_bank,
+        commands::banking::fetch_bank_transactions,
+        commands::banking::get_pending_bank_transactions,
+        commands::banking::confirm_bank_transactions,
+        commands::banking::dismiss_bank_transactions,
+        commands::drafts::voice_note_to_expense,
+        commands::drafts::scan_receipt,
+        commands::categorize::suggest_categories,
+        commands::categorize::get_categorization_rules,
+        commands::categorize::save_categorization_rule,
+        commands::categorize::delete_categorization_rule,
+        commands::categorize::create_rule_from_expense,
+        commands::recurring::get_recurring_candidates,
+        commands::recurring::get_recurring_expenses,
+        commands::recurring::create_recurring_expense,
+        commands::recurring::detected_subscriptions,
+        commands::recurring::dismiss_detected_subscription,
+        commands::recurring::track_detected_subscription,
+        commands::recurring::get_price_increases,
+        commands::recurring::import_ics,
+        commands::merchants::normalize_merchant,
+        commands::merchants::save_merchant_correction,
+        commands::merchants::get_merchant_stats,
+        commands::merchants::get_merchants,
+        commands::merchants::rename_merchant,
+        commands::merchants::get_merchant_monthly_spending,
+        commands::trials::get_trials,
+        commands::trials::create_trial,
+        commands::trials::cancel_trial,
+        commands::accounts::get_accounts,
+        commands::accounts::create_account,
+        commands::accounts::update_account,
+        commands::accounts::delete_account,
+        commands::accounts::reconcile_account_balance,
+        commands::accounts::recompute_account_balances,
+        commands::accounts::get_account_activity,
+        commands::accounts::get_transfers,
+        commands::accounts::create_transfer,
+        commands::accounts::delete_transfer,
+        commands::accounts::convert_expense_to_transfer,
+        commands::tags::get_tags,
+        commands::tags::create_tag,
+        commands::tags::set_tag_color,
+        commands::tags::rename_tag,
+        commands::tags::merge_tags,
+        commands::tags::delete_tag,
+        commands::tags::get_expense_tags,
+        commands::tags::set_expense_tags,
+        commands::tags::tag_expenses,
+        commands::tags::get_tagged_expenses,
+        commands::tags::get_tag_totals,
+        commands::tags::get_tagged_category_totals,
+        commands::splits::get_expense_split,
+        commands::splits::split_expense,
+        commands::splits::unsplit_expense,
+        commands::splits::get_split_people,
+        commands::splits::get_settle_up,
+        commands::splits::get_settlements,
+        commands::splits::record_settlement,
+        commands::splits::delete_settlement,
+        commands::households::get_households,
+        commands::households::share_expense_with_household,
+        commands::households::share_budget_with_household,
+        commands::households::get_household_expenses,
+        commands::households::get_household_spending_by_member,
+        commands::households::get_household_budgets,
+        commands::bills::get_bills,
+        commands::bills::get_bill_payments,
+        commands::bills::create_bill,
+        commands::bills::update_bill,
+        commands::bills::delete_bill,
+        commands::bills::mark_bill_paid,
+        commands::bills::unmark_bill_paid,
+        commands::reimbursements::set_reimbursement_status,
+        commands::reimbursements::get_outstanding_reimbursements,
+        commands::reports::get_monthly_category_report,
+        commands::reports::get_month_over_month,
+        commands::reports::get_average_daily_spend,
+        commands::reports::get_budget_utilization,
+        commands::reports::get_monthly_cashflow,
+        commands::reports::get_cashflow_baseline,
+        commands::reports::get_year_review,
+        commands::spending::get_spending_summary,
+        commands::spending::get_category_insights,
+        commands::spending::compare_months,
+        commands::spending::get_weekday_patterns,
+        commands::export::export_snapshot,
+        commands::export::get_ledger_account_mapping,
+        commands::export::export_ledger,
+        commands::export::export_xlsx,
+        commands::export::export_ical,
+        commands::archive::export_archive,
+        commands::archive::stage_archive,
+        commands::archive::import_archive,
+        commands::transfer::start_transfer,
+        commands::transfer::cancel_transfer,
+        commands::transfer::receive_transfer,
+        commands::transfer::take_scanned_code,
+        commands::trash::get_trash,
+        commands::trash::restore_from_trash,
+        commands::trash::get_trash_retention,
+        commands::trash::set_trash_retention,
+        commands::trash::purge_trash,
+        commands::habits::get_habit_streaks,
+        commands::checkins::get_pending_checkins,
+        commands::sync::get_pending_conflicts,
+        commands::sync::get_conflict,
+        commands::sync::resolve_conflict,
+        commands::sync::inspect_sync_queue,
+        commands::sync::retry_item,
+        commands::sync::retry_all_failed,
+        commands::sync::discard_item,
+        commands::sync::compact_sync_queue,
+        commands::sync::record_sync_failure,
+        commands::history::get_change_timeline,
+        commands::connectivity::is_online,
+        commands::connectivity::set_metered_connection,
+        commands::backend::configure_backend,
+        commands::attachments::sync_attachments,
+        commands::attachments::save_attachment,
+        commands::attachments::get_expense_attachments,
+        commands::attachments::delete_attachment,
+        commands::attachments::clean_up_attachments,
+        commands::backup::get_backup_targets,
+        commands::backup::get_icloud_drive_dir,
+        commands::backup::add_backup_folder,
+        commands::backup::connect_backup_provider,
+        commands::backup::remove_backup_target,
+        commands::backup::run_backup,
+        commands::budgets::get_budget_template,
+        commands::budgets::save_budget_template,
+        commands::budgets::get_month_closes,
+        commands::budgets::get_pay_schedule,
+        commands::budgets::save_pay_schedule,
+        commands::budgets::get_budget_period,
+        commands::budgets::get_budget_status,
+        commands::income::get_income_sources,
+        commands::income::create_income_source,
+        commands::income::delete_income_source,
+        commands::income::get_income_entries,
+        commands::income::log_income,
+        commands::income::delete_income_entry,
+        commands::income::get_income_variance,
+        commands::net_worth::record_account_balance,
+        commands::net_worth::get_account_balances,
+        commands::net_worth::get_net_worth_history,
+        commands::goals::get_goal_funding_forecast,
+        commands::goals::get_goal_forecast,
+        commands::app_meta::get_app_meta,
+        commands::preferences::get_or_create_auth_state,
+        commands::preferences::update_auth_state,
+        commands::preferences::get_or_create_notification_preferences,
+        commands::preferences::update_notification_preferences,
+        commands::category_alerts::get_category_alert_rules,
+        commands::category_alerts::save_category_alert_rule,
+        commands::category_alerts::delete_category_alert_rule,
+        commands::category_alerts::check_category_alerts,
+        commands::budget_alerts::get_budget_alert_thresholds,
+        commands::budget_alerts::set_budget_alert_thresholds,
+        commands::reconcile::import_statement,
+        commands::reconcile::reconcile_statement,
+        commands::entitlements::get_entitlements,
+        commands::entitlements::has_entitlement,
+        commands::entitlements::verify_purchase,
+        commands::referrals::redeem_invite_code,
+        commands::referrals::get_referrals,
+        commands::experiments::get_variant,
+        commands::experiments::log_experiment_exposure,
+        commands::category_totals::update_category_totals,
+        commands::category_totals::rebuild_category_totals,
+        commands::category_totals::get_monthly_category_totals,
+        commands::events::publish_domain_event,
+        commands::notify::register_platform_notifications,
+        commands::expenses::get_expenses,
+        commands::expenses::get_recent_expenses,
+        commands::search::search_expenses,
+        commands::expenses::create_expense,
+        commands::expenses::update_expense,
+        commands::expenses::delete_expense,
+        commands::budgets::get_current_budget,
+        commands::budgets::save_budget,
+        commands::goals::get_savings_goals,
+        commands::goals::get_savings_goal,
+        commands::goals::create_savings_goal,
+        commands::goals::update_savings_goal,
+        commands::goals::delete_savings_goal,
+        commands::goals::get_goal_contributions,
+        commands::goals::save_goal_contribution,
+        commands::sync::push_sync_queue,
+        commands::sync::get_conflict_strategies,
+        commands::sync::set_conflict_strategy,
+        commands::sync::get_sync_settings,
+        commands::sync::set_table_sync,
+        commands::sync::get_sync_health,
+        commands::sync::get_sync_log,
+        commands::sync::force_full_resync,
+        commands::sync::get_sync_policy,
+        commands::sync::set_sync_wifi_only,
+        commands::sync::get_peer_sync_status,
+        commands::sync::start_peer_sync,
+        commands::sync::stop_peer_sync,
+        commands::sync::offer_peer_pairing,
+        commands::sync::pair_with_peer,
+        commands::sync::get_sync_peers,
+        commands::sync::remove_sync_peer,
+        commands::sync::discover_sync_peers,
+        commands::sync::sync_with_peer,
+        commands::sync::reconcile_pulled_row,
+        commands::sync::get_sync_encryption_status,
+        commands::sync::enable_sync_encryption,
+        commands::sync::export_sync_key,
+        commands::sync::import_sync_key,
+        commands::sync::recover_sync_key,
+        commands::sync::request_sync_key,
+        commands::sync::get_sync_key_requests,
+        commands::sync::approve_sync_key_request,
+        commands::sync::receive_sync_key,
+        commands::sync::open_sync_rows,
+        commands::notify::reschedule_notifications,
+        commands::notify::dispatch_due_notifications,
+        commands::import::import_csv,
+        commands::import::import_bank_file,
+        commands::import::preview_app_export,
+        commands::import::import_app_export,
+        commands::backup::export_backup,
+        commands::backup::import_backup,
+        commands::currency::get_currencies,
+        commands::currency::set_base_currency,
+        commands::currency::get_exchange_rate,
+        commands::currency::refresh_exchange_rates,
+        commands::budgets::get_category_budgets,
+        commands::budgets::allocate_category_budget,
+        commands::budgets::remove_category_budget,
+        commands::budgets::get_category_envelopes,
+        commands::bulk::bulk_update_expenses,
+        commands::bulk::undo_bulk_update,
+        commands::encryption::get_encryption_status,
+        commands::encryption::enable_database_encryption,
+    ])
+}
+
+/// Write the typed invoke client to `src/lib/bindings.ts`, so a change to a
+/// command shows up as a type error on the frontend. The file is checked in;
+/// debug builds keep it current.
+#[cfg(debug_assertions)]
+fn export_bindings(specta: &tauri_specta::Builder<tauri::Wry>) {
+    use specta_typescript::{BigIntExportBehavior, Typescript};
+
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../src/lib/bindings.ts");
+    let typescript = Typescript::default()
+        .bigint(BigIntExportBehavior::Number)
+        .header(
+            "// Generated by tauri-specta from the Rust commands. Do not edit.\n// @ts-nocheck",
+        );
+    if let Err(e) = specta.export(typescript, path) {
+        eprintln!("[Bindings] Failed to export TypeScript bindings: {e}");
+    }
+}
@@ -2,5 +2,8 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    if let Some(code) = goaldy_lib::cli::run() {
+        std::process::exit(code);
+    }
     goaldy_lib::run()
 }
@@ -0,0 +1,24 @@
+//! Routine upkeep of the local database.
+
+use sqlx::SqlitePool;
+
+use crate::accounts;
+use crate::app_meta;
+use crate::category_totals;
+use crate::db::now;
+use crate::error::Result;
+use crate::merchants;
+use crate::orphans;
+
+/// Let SQLite refresh its query planner statistics, repair dangling
+/// references, rebuild cached aggregates and account balances, link
+/// expenses synced in to their merchants and record the run.
+pub async fn run(pool: &SqlitePool) -> Result<()> {
+    sqlx::query("PRAGMA optimize").execute(pool).await?;
+    orphans::repair(pool).await?;
+    category_totals::rebuild(pool).await?;
+    accounts::recompute(pool).await?;
+    merchants::relink(pool, true).await?;
+    app_meta::refresh(pool).await?;
+    app_meta::set(pool, app_meta::LAST_MAINTENANCE, &now()).await
+}
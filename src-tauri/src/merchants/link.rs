@@ -0,0 +1,103 @@
+//! Linking expenses to rows of `merchants`.
+//!
+//! The link follows from the note and the corrections, so it is redone
+//! whenever either changes: an expense is linked as it is written, all of
+//! them when a correction is saved, and expenses that arrived some other
+//! way, by sync or from before merchants were tracked, when found without
+//! one. `merchant_id` is never synced, so relinking touches neither
+//! `updated_at` nor the sync queue.
+
+use std::collections::HashMap;
+
+use sqlx::{SqliteConnection, SqlitePool};
+
+use super::{load_corrections, normalize, raw_key};
+use crate::db::{new_id, now};
+use crate::error::Result;
+
+/// The id of the merchant called `name`, created if new.
+async fn merchant_id(conn: &mut SqliteConnection, name: &str) -> Result<String> {
+    let now = now();
+    sqlx::query(
+        "INSERT INTO merchants (id, name, created_at, updated_at) VALUES ($1, $2, $3, $3)
+         ON CONFLICT(name) DO NOTHING",
+    )
+    .bind(new_id())
+    .bind(name)
+    .bind(&now)
+    .execute(&mut *conn)
+    .await?;
+    let (id,): (String,) = sqlx::query_as("SELECT id FROM merchants WHERE name = $1")
+        .bind(name)
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok(id)
+}
+
+/// The merchant an expense with `note` belongs to, if it has a note.
+pub(crate) async fn link(
+    conn: &mut SqliteConnection,
+    note: Option<&str>,
+) -> Result<Option<String>> {
+    let Some(note) = note.map(str::trim).filter(|n| !n.is_empty()) else {
+        return Ok(None);
+    };
+    let key = raw_key(note);
+    let correction: Option<(String,)> =
+        sqlx::query_as("SELECT merchant_name FROM merchant_aliases WHERE raw_key = $1")
+            .bind(&key)
+            .fetch_optional(&mut *conn)
+            .await?;
+    let corrections: HashMap<String, String> =
+        correction.map(|(name,)| (key, name)).into_iter().collect();
+    let id = merchant_id(conn, &normalize(note, &corrections)).await?;
+    Ok(Some(id))
+}
+
+/// Link every expense with a note to its merchant again, or with
+/// `only_missing` just those not linked yet, then drop merchants no
+/// expense links to. Returns how many expenses changed merchant.
+pub(crate) async fn relink(pool: &SqlitePool, only_missing: bool) -> Result<usize> {
+    let corrections = load_corrections(pool).await?;
+    let rows: Vec<(String, String, Option<String>)> = sqlx::query_as(
+        "SELECT e.id, e.note, m.name FROM expenses e
+         LEFT JOIN merchants m ON m.id = e.merchant_id
+         WHERE e.note IS NOT NULL AND TRIM(e.note) != ''
+           AND (e.merchant_id IS NULL OR NOT $1)",
+    )
+    .bind(only_missing)
+    .fetch_all(pool)
+    .await?;
+
+    let mut tx = pool.begin().await?;
+    let mut ids: HashMap<String, String> = HashMap::new();
+    let mut changed = 0;
+    for (expense_id, note, current) in rows {
+        let name = normalize(&note, &corrections);
+        if current.as_deref() == Some(name.as_str()) {
+            continue;
+        }
+        let id = match ids.get(&name) {
+            Some(id) => id.clone(),
+            None => {
+                let id = merchant_id(&mut tx, &name).await?;
+                ids.insert(name, id.clone());
+                id
+            }
+        };
+        sqlx::query("UPDATE expenses SET merchant_id = $1 WHERE id = $2")
+            .bind(&id)
+            .bind(&expense_id)
+            .execute(&mut *tx)
+            .await?;
+        changed += 1;
+    }
+    sqlx::query(
+        "DELETE FROM merchants
+         WHERE id NOT IN (SELECT merchant_id FROM expenses WHERE merchant_id IS NOT NULL)",
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(changed)
+}
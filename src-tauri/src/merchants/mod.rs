@@ -0,0 +1,214 @@
+//! Merchant names: normalization of bank-statement descriptions plus the
+//! user's stored corrections, the `merchants` they produce, and
+//! per-merchant spending statistics.
+
+mod link;
+mod normalize;
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::db::now;
+use crate::error::{Error, Result};
+
+pub(crate) use link::{link, relink};
+pub use normalize::{normalize, raw_key};
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct Merchant {
+    pub id: String,
+    pub name: String,
+    /// Expenses linked to it, not counting deleted ones.
+    pub expense_count: i64,
+}
+
+/// Corrections the user made, keyed by [`raw_key`].
+pub async fn load_corrections(pool: &SqlitePool) -> Result<HashMap<String, String>> {
+    let rows: Vec<(String, String)> =
+        sqlx::query_as("SELECT raw_key, merchant_name FROM merchant_aliases")
+            .fetch_all(pool)
+            .await?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Remember that descriptions like `raw` belong to `merchant_name`. Applies
+/// to every future import and statistic with the same raw key.
+pub async fn save_correction(pool: &SqlitePool, raw: &str, merchant_name: &str) -> Result<String> {
+    let key = raw_key(raw);
+    let name = merchant_name.trim();
+    if key.is_empty() || name.is_empty() {
+        return Err(Error::Validation(
+            "Both the original description and the merchant name are required".to_string(),
+        ));
+    }
+
+    let now = now();
+    sqlx::query(
+        "INSERT INTO merchant_aliases (raw_key, merchant_name, created_at, updated_at)
+         VALUES ($1, $2, $3, $3)
+         ON CONFLICT(raw_key) DO UPDATE SET merchant_name = $2, updated_at = $3",
+    )
+    .bind(&key)
+    .bind(name)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+    relink(pool, false).await?;
+    Ok(key)
+}
+
+/// Every merchant, by name.
+pub async fn list(pool: &SqlitePool) -> Result<Vec<Merchant>> {
+    relink(pool, true).await?;
+    Ok(sqlx::query_as::<_, Merchant>(
+        "SELECT m.id, m.name, COUNT(e.id) AS expense_count
+         FROM merchants m
+         LEFT JOIN expenses e ON e.merchant_id = m.id AND e.deleted_at IS NULL
+         GROUP BY m.id
+         ORDER BY m.name",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Rename a merchant, merging it into the one already called `name` if
+/// there is one. Saved as corrections for the descriptions behind it, so
+/// new expenses from it get the name too. Returns the merchant's id.
+pub async fn rename(pool: &SqlitePool, id: &str, name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(Error::Validation("The merchant needs a name".to_string()));
+    }
+    let notes: Vec<(String,)> = sqlx::query_as(
+        "SELECT DISTINCT note FROM expenses WHERE merchant_id = $1 AND note IS NOT NULL",
+    )
+    .bind(id)
+    .fetch_all(pool)
+    .await?;
+    if notes.is_empty() {
+        return Err(Error::Validation("Merchant not found".to_string()));
+    }
+    let mut keys: Vec<String> = notes.iter().map(|(note,)| raw_key(note)).collect();
+    keys.sort();
+    keys.dedup();
+
+    let now = now();
+    let mut tx = pool.begin().await?;
+    for key in keys.iter().filter(|k| !k.is_empty()) {
+        sqlx::query(
+            "INSERT INTO merchant_aliases (raw_key, merchant_name, created_at, updated_at)
+             VALUES ($1, $2, $3, $3)
+             ON CONFLICT(raw_key) DO UPDATE SET merchant_name = $2, updated_at = $3",
+        )
+        .bind(key)
+        .bind(name)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    relink(pool, false).await?;
+
+    let (id,): (String,) = sqlx::query_as("SELECT id FROM merchants WHERE name = $1")
+        .bind(name)
+        .fetch_one(pool)
+        .await?;
+    Ok(id)
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct MerchantStats {
+    pub merchant_id: String,
+    pub merchant: String,
+    pub total: f64,
+    pub count: i64,
+    pub average: f64,
+    pub last_date: String,
+    /// Category used most often for this merchant.
+    pub category_id: Option<String>,
+}
+
+/// Spending per merchant between two dates (inclusive), largest total
+/// first. Expenses without a note have no merchant and are left out.
+pub async fn merchant_stats(
+    pool: &SqlitePool,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<MerchantStats>> {
+    relink(pool, true).await?;
+    let totals: Vec<(String, String, f64, i64, String)> = sqlx::query_as(
+        "SELECT m.id, m.name, SUM(e.amount), COUNT(*), MAX(e.date)
+         FROM expenses e
+         JOIN merchants m ON m.id = e.merchant_id
+         WHERE e.deleted_at IS NULL AND e.date >= $1 AND e.date <= $2
+         GROUP BY m.id
+         ORDER BY SUM(e.amount) DESC",
+    )
+    .bind(start_date)
+    .bind(end_date)
+    .fetch_all(pool)
+    .await?;
+    // Counted most first, so the first seen per merchant is its usual one.
+    let categories: Vec<(String, Option<String>)> = sqlx::query_as(
+        "SELECT merchant_id, category_id FROM expenses
+         WHERE deleted_at IS NULL AND merchant_id IS NOT NULL AND date >= $1 AND date <= $2
+         GROUP BY merchant_id, category_id
+         ORDER BY COUNT(*) DESC",
+    )
+    .bind(start_date)
+    .bind(end_date)
+    .fetch_all(pool)
+    .await?;
+    let mut usual: HashMap<String, Option<String>> = HashMap::new();
+    for (merchant_id, category_id) in categories {
+        usual.entry(merchant_id).or_insert(category_id);
+    }
+
+    Ok(totals
+        .into_iter()
+        .map(
+            |(merchant_id, merchant, total, count, last_date)| MerchantStats {
+                category_id: usual.remove(&merchant_id).flatten(),
+                merchant_id,
+                merchant,
+                total,
+                count,
+                average: total / count as f64,
+                last_date,
+            },
+        )
+        .collect())
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct MerchantMonth {
+    /// "YYYY-MM".
+    pub month: String,
+    pub total: f64,
+    pub count: i64,
+}
+
+/// What was spent at one merchant month by month between two dates
+/// (inclusive), oldest first. Months without spending are left out.
+pub async fn monthly_spending(
+    pool: &SqlitePool,
+    merchant_id: &str,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<MerchantMonth>> {
+    relink(pool, true).await?;
+    Ok(sqlx::query_as::<_, MerchantMonth>(
+        "SELECT substr(date, 1, 7) AS month, SUM(amount) AS total, COUNT(*) AS count
+         FROM expenses
+         WHERE deleted_at IS NULL AND merchant_id = $1 AND date >= $2 AND date <= $3
+         GROUP BY month
+         ORDER BY month",
+    )
+    .bind(merchant_id)
+    .bind(start_date)
+    .bind(end_date)
+    .fetch_all(pool)
+    .await?)
+}
@@ -0,0 +1,128 @@
+//! Turns bank-statement noise ("AMZN Mktp DE*2K4LQ0XY5", "PAYPAL *SPOTIFY
+//! 35314369001") into a clean merchant name.
+//!
+//! Steps: strip payment-processor prefixes and trailing reference codes to get
+//! a stable raw key, then look the key up in the user's corrections, then in
+//! the built-in dictionary, and finally fall back to title case.
+
+use std::collections::HashMap;
+
+/// Payment processors that prefix the real merchant.
+const PROCESSOR_PREFIXES: &[&str] = &[
+    "PAYPAL *", "PAYPAL*", "PP*", "SQ *", "SQ*", "SUMUP *", "SUMUP*", "ZETTLE_*", "IZ *", "SP *",
+    "TST*", "CKO*", "KLARNA*", "GOOGLE *",
+];
+
+/// Known merchants, matched as a prefix of the cleaned key. Longer, more
+/// specific entries come first ("UBER EATS" before "UBER").
+const DICTIONARY: &[(&str, &str)] = &[
+    ("AMZN MKTP", "Amazon"),
+    ("AMZN", "Amazon"),
+    ("AMAZON PRIME", "Amazon Prime"),
+    ("AMAZON", "Amazon"),
+    ("APPLE.COM/BILL", "Apple"),
+    ("APPLE COM BILL", "Apple"),
+    ("NETFLIX", "Netflix"),
+    ("SPOTIFY", "Spotify"),
+    ("DISNEY PLUS", "Disney+"),
+    ("DISNEYPLUS", "Disney+"),
+    ("UBER EATS", "Uber Eats"),
+    ("UBER", "Uber"),
+    ("LYFT", "Lyft"),
+    ("BOLT", "Bolt"),
+    ("DB VERTRIEB", "Deutsche Bahn"),
+    ("DB BAHN", "Deutsche Bahn"),
+    ("LIDL", "Lidl"),
+    ("ALDI", "Aldi"),
+    ("REWE", "REWE"),
+    ("EDEKA", "Edeka"),
+    ("KAUFLAND", "Kaufland"),
+    ("DM DROGERIE", "dm"),
+    ("DM-DROGERIE", "dm"),
+    ("ROSSMANN", "Rossmann"),
+    ("IKEA", "IKEA"),
+    ("MCDONALDS", "McDonald's"),
+    ("MC DONALDS", "McDonald's"),
+    ("STARBUCKS", "Starbucks"),
+    ("SHELL", "Shell"),
+    ("ARAL", "Aral"),
+    ("TESCO", "Tesco"),
+    ("SAINSBURYS", "Sainsbury's"),
+    ("WALMART", "Walmart"),
+    ("TARGET", "Target"),
+    ("COSTCO", "Costco"),
+];
+
+/// Trailing tokens that carry no merchant information.
+const NOISE_TOKENS: &[&str] = &[
+    "DE", "GB", "UK", "US", "NL", "FR", "AT", "CH", "IE", "LU", "GMBH", "LTD", "INC", "LLC", "BV",
+    "SARL", "AG", "EU", "CO", "COM", "WWW", "POS", "CARD", "PURCHASE", "DEBIT",
+];
+
+/// Stable key for a raw description: uppercase, processor prefix removed,
+/// cut at the first `*` reference and stripped of codes and noise words.
+pub fn raw_key(raw: &str) -> String {
+    let mut text = raw.trim().to_uppercase();
+
+    for prefix in PROCESSOR_PREFIXES {
+        if let Some(rest) = text.strip_prefix(prefix) {
+            text = rest.trim().to_string();
+            break;
+        }
+    }
+    if let Some((head, _)) = text.split_once('*') {
+        if !head.trim().is_empty() {
+            text = head.to_string();
+        }
+    }
+
+    let mut tokens: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+        .map(|t| t.trim_matches(|c: char| matches!(c, '.' | '-' | '#' | ':' | '/')))
+        .filter(|t| !t.is_empty() && !is_code(t))
+        .collect();
+    while tokens.len() > 1 && tokens.last().is_some_and(|t| NOISE_TOKENS.contains(t)) {
+        tokens.pop();
+    }
+    tokens.join(" ")
+}
+
+/// Reference numbers, card suffixes, dates: anything with a digit.
+fn is_code(token: &str) -> bool {
+    token.chars().any(|c| c.is_ascii_digit())
+}
+
+/// Clean merchant name for `raw`, preferring the user's own corrections
+/// (keyed by [`raw_key`]).
+pub fn normalize(raw: &str, corrections: &HashMap<String, String>) -> String {
+    let key = raw_key(raw);
+    if key.is_empty() {
+        return raw.trim().to_string();
+    }
+    if let Some(name) = corrections.get(&key) {
+        return name.clone();
+    }
+    if let Some((_, name)) = DICTIONARY
+        .iter()
+        .find(|(prefix, _)| key.starts_with(prefix))
+    {
+        return name.to_string();
+    }
+    title_case(&key)
+}
+
+fn title_case(key: &str) -> String {
+    key.split(' ')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first
+                    .to_uppercase()
+                    .chain(chars.flat_map(char::to_lowercase))
+                    .collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
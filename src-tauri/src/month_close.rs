@@ -0,0 +1,181 @@
+//! Closing a finished month or pay period.
+//!
+//! On the first run after a budget period rolls over, the job wraps the
+//! previous one up: its final spending and rollover are written to
+//! `month_closes` and the new period's budget is created from the template
+//! unless the user already set one. Once a calendar month is over, habit
+//! compliance is recomputed from the final numbers, derived account
+//! balances are snapshotted and the monthly check-in goes out. With the
+//! default schedule both happen together. The `month_closes` row marks a
+//! period done, so nothing runs twice.
+
+use chrono::{Days, Local};
+use serde::Serialize;
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::AppHandle;
+
+use crate::budgets;
+use crate::checkins;
+use crate::db::now;
+use crate::error::Result;
+use crate::events::{self, DomainEvent};
+use crate::habits;
+use crate::net_worth;
+use crate::periods::{self, Period};
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct MonthClose {
+    /// Period key: "YYYY-MM" for calendar months, the first day otherwise.
+    pub month: String,
+    pub budget_id: Option<String>,
+    pub budget_limit: Option<f64>,
+    pub spent_amount: f64,
+    /// Left over (negative if overspent) against the budget's limit.
+    pub rollover_amount: f64,
+    /// The following period's budget, created here or already present.
+    pub next_budget_id: Option<String>,
+    pub closed_at: String,
+}
+
+/// Past closes, newest first.
+pub async fn list(pool: &SqlitePool, limit: i64) -> Result<Vec<MonthClose>> {
+    Ok(sqlx::query_as::<_, MonthClose>(
+        "SELECT month, budget_id, budget_limit, spent_amount, rollover_amount, next_budget_id, closed_at
+         FROM month_closes
+         ORDER BY month DESC
+         LIMIT $1",
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Close the last budget period, and the last calendar month where that
+/// is a separate thing, if that hasn't happened yet. Returns the closes that
+/// ran now.
+pub async fn close_previous(app: &AppHandle, pool: &SqlitePool) -> Result<Vec<MonthClose>> {
+    let today = Local::now().date_naive();
+    let schedule = periods::schedule(pool).await?;
+    let current = schedule.period_at(today);
+    let previous = schedule.previous(&current);
+
+    let mut closes = Vec::new();
+    if let Some(close) = close_period(pool, &previous, &current).await? {
+        if let Some(budget_id) = &close.next_budget_id {
+            events::publish(
+                app,
+                &DomainEvent::BudgetUpdated {
+                    budget_id: budget_id.clone(),
+                    month: current.key.clone(),
+                },
+            )?;
+        }
+        closes.push(close);
+    }
+
+    // Habits and check-ins go by calendar month whatever the pay schedule.
+    let month = Period::month_of(Period::month_of(today).start - Days::new(1));
+    if month.key != previous.key {
+        if let Some(close) = close_month(pool, &month).await? {
+            closes.push(close);
+        }
+    }
+    if closes.iter().any(|close| close.month == month.key) {
+        let before = Period::month_of(month.start - Days::new(1));
+        habits::finalize_month(pool, &month.key, &before.key).await?;
+        net_worth::snapshot_derived(pool, month.end).await?;
+        checkins::send_monthly(app, pool, month.start).await?;
+    }
+    Ok(closes)
+}
+
+async fn is_closed(conn: &mut SqliteConnection, key: &str) -> Result<bool> {
+    let closed: Option<(String,)> =
+        sqlx::query_as("SELECT month FROM month_closes WHERE month = $1")
+            .bind(key)
+            .fetch_optional(&mut *conn)
+            .await?;
+    Ok(closed.is_some())
+}
+
+async fn insert(conn: &mut SqliteConnection, close: &MonthClose) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO month_closes
+            (month, budget_id, budget_limit, spent_amount, rollover_amount, next_budget_id, closed_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)",
+    )
+    .bind(&close.month)
+    .bind(&close.budget_id)
+    .bind(close.budget_limit)
+    .bind(close.spent_amount)
+    .bind(close.rollover_amount)
+    .bind(&close.next_budget_id)
+    .bind(&close.closed_at)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Snapshot `period`'s budget and start `next` from the template.
+async fn close_period(
+    pool: &SqlitePool,
+    period: &Period,
+    next: &Period,
+) -> Result<Option<MonthClose>> {
+    let template = budgets::template(pool).await?;
+    let mut tx = pool.begin().await?;
+    if is_closed(&mut tx, &period.key).await? {
+        return Ok(None);
+    }
+
+    let budget = budgets::for_period(&mut tx, &period.key).await?;
+    let spent = budgets::period_spending(&mut tx, period).await?;
+    let rollover = budget.as_ref().map_or(0.0, |b| b.limit() - spent);
+
+    let next_budget_id = match budgets::for_period(&mut tx, &next.key).await? {
+        Some(existing) => Some(existing.id),
+        None => {
+            budgets::create_from_template(&mut tx, &template, &next.key, budget.as_ref(), rollover)
+                .await?
+        }
+    };
+
+    let close = MonthClose {
+        month: period.key.clone(),
+        budget_id: budget.as_ref().map(|b| b.id.clone()),
+        budget_limit: budget.as_ref().map(budgets::Budget::limit),
+        spent_amount: spent,
+        rollover_amount: rollover,
+        next_budget_id,
+        closed_at: now(),
+    };
+    insert(&mut tx, &close).await?;
+    tx.commit().await?;
+
+    eprintln!(
+        "[MonthClose] Closed {}: spent {spent:.2}, rollover {rollover:.2}",
+        period.key
+    );
+    Ok(Some(close))
+}
+
+/// Record a calendar month's spending when budgets follow another schedule.
+async fn close_month(pool: &SqlitePool, month: &Period) -> Result<Option<MonthClose>> {
+    let mut tx = pool.begin().await?;
+    if is_closed(&mut tx, &month.key).await? {
+        return Ok(None);
+    }
+    let close = MonthClose {
+        month: month.key.clone(),
+        budget_id: None,
+        budget_limit: None,
+        spent_amount: budgets::period_spending(&mut tx, month).await?,
+        rollover_amount: 0.0,
+        next_budget_id: None,
+        closed_at: now(),
+    };
+    insert(&mut tx, &close).await?;
+    tx.commit().await?;
+    eprintln!("[MonthClose] Closed {}", month.key);
+    Ok(Some(close))
+}
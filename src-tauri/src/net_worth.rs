@@ -0,0 +1,236 @@
+//! Net worth from account balance snapshots.
+//!
+//! Balances are snapshots taken on a date: manual ones the user enters for
+//! their bank accounts, loans and the like, and derived ones we compute
+//! from the app's own data (currently what has been put towards each
+//! savings goal). An account's balance on any day is its latest snapshot up
+//! to that day, so history doesn't need a snapshot for every date.
+
+use chrono::{Datelike, Days, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::db::{new_id, now};
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountKind {
+    Asset,
+    Liability,
+}
+
+impl AccountKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            AccountKind::Asset => "asset",
+            AccountKind::Liability => "liability",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct AccountBalance {
+    pub id: String,
+    pub user_id: Option<String>,
+    /// Identifies the account across snapshots.
+    pub account_key: String,
+    pub account_name: String,
+    /// asset or liability; liabilities are stored as positive amounts.
+    pub kind: String,
+    pub balance: f64,
+    /// "YYYY-MM-DD".
+    pub as_of: String,
+    /// manual or derived.
+    pub source: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub deleted_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct NewAccountBalance {
+    pub account_name: String,
+    pub kind: AccountKind,
+    pub balance: f64,
+    /// Defaults to today.
+    pub as_of: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct NetWorthPoint {
+    pub date: NaiveDate,
+    pub assets: f64,
+    pub liabilities: f64,
+    pub net_worth: f64,
+}
+
+fn date_str(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+struct Snapshot<'a> {
+    account_key: &'a str,
+    account_name: &'a str,
+    kind: AccountKind,
+    balance: f64,
+    as_of: &'a str,
+    source: &'a str,
+}
+
+async fn upsert(pool: &SqlitePool, user_id: Option<&str>, snapshot: Snapshot<'_>) -> Result<()> {
+    let now = now();
+    sqlx::query(
+        "INSERT INTO account_balances
+            (id, user_id, account_key, account_name, kind, balance, as_of, source, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+         ON CONFLICT(account_key, as_of) DO UPDATE SET
+           account_name = excluded.account_name,
+           kind = excluded.kind,
+           balance = excluded.balance,
+           updated_at = excluded.updated_at,
+           deleted_at = NULL",
+    )
+    .bind(new_id())
+    .bind(user_id)
+    .bind(snapshot.account_key)
+    .bind(snapshot.account_name)
+    .bind(snapshot.kind.as_str())
+    .bind(snapshot.balance)
+    .bind(snapshot.as_of)
+    .bind(snapshot.source)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Record a manual balance, replacing one already entered for the same
+/// account and day.
+pub async fn record(
+    pool: &SqlitePool,
+    user_id: Option<&str>,
+    input: NewAccountBalance,
+    today: NaiveDate,
+) -> Result<()> {
+    let name = input.account_name.trim();
+    if name.is_empty() {
+        return Err(Error::Validation("Account name is required".to_string()));
+    }
+    if input.balance < 0.0 {
+        return Err(Error::Validation(
+            "Enter debts as a liability with a positive balance".to_string(),
+        ));
+    }
+    let as_of = input.as_of.unwrap_or(today);
+    if as_of > today {
+        return Err(Error::Validation(
+            "Balances can't be dated in the future".to_string(),
+        ));
+    }
+    let key = format!("manual:{}", name.to_lowercase());
+    upsert(
+        pool,
+        user_id,
+        Snapshot {
+            account_key: &key,
+            account_name: name,
+            kind: input.kind,
+            balance: input.balance,
+            as_of: &date_str(as_of),
+            source: "manual",
+        },
+    )
+    .await
+}
+
+/// Snapshot the balances we can work out ourselves as of `as_of`.
+pub async fn snapshot_derived(pool: &SqlitePool, as_of: NaiveDate) -> Result<()> {
+    let month = as_of.format("%Y-%m").to_string();
+    let goals: Vec<(String, Option<String>, String, f64)> = sqlx::query_as(
+        "SELECT g.id, g.user_id, g.name, COALESCE(SUM(c.amount), 0.0)
+         FROM savings_goals g
+         LEFT JOIN savings_contributions c
+           ON c.goal_id = g.id AND c.deleted_at IS NULL AND c.month <= $1
+         WHERE g.deleted_at IS NULL
+         GROUP BY g.id",
+    )
+    .bind(&month)
+    .fetch_all(pool)
+    .await?;
+
+    let as_of = date_str(as_of);
+    for (goal_id, user_id, name, saved) in goals {
+        upsert(
+            pool,
+            user_id.as_deref(),
+            Snapshot {
+                account_key: &format!("goal:{goal_id}"),
+                account_name: &name,
+                kind: AccountKind::Asset,
+                balance: saved,
+                as_of: &as_of,
+                source: "derived",
+            },
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// The latest snapshot of every account, largest first.
+pub async fn latest(pool: &SqlitePool) -> Result<Vec<AccountBalance>> {
+    Ok(sqlx::query_as::<_, AccountBalance>(
+        "SELECT * FROM account_balances b
+         WHERE deleted_at IS NULL
+           AND as_of = (SELECT MAX(as_of) FROM account_balances
+                        WHERE account_key = b.account_key AND deleted_at IS NULL)
+         ORDER BY balance DESC",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+async fn point(pool: &SqlitePool, date: NaiveDate) -> Result<NetWorthPoint> {
+    let (assets, liabilities): (f64, f64) = sqlx::query_as(
+        "SELECT COALESCE(SUM(CASE WHEN kind = 'asset' THEN balance END), 0.0),
+                COALESCE(SUM(CASE WHEN kind = 'liability' THEN balance END), 0.0)
+         FROM account_balances b
+         WHERE deleted_at IS NULL
+           AND as_of = (SELECT MAX(as_of) FROM account_balances
+                        WHERE account_key = b.account_key AND deleted_at IS NULL AND as_of <= $1)",
+    )
+    .bind(date_str(date))
+    .fetch_one(pool)
+    .await?;
+    Ok(NetWorthPoint {
+        date,
+        assets,
+        liabilities,
+        net_worth: assets - liabilities,
+    })
+}
+
+/// Net worth at the end of each of the last `months` months, the current
+/// one ending today. Oldest first.
+pub async fn history(
+    pool: &SqlitePool,
+    months: u32,
+    today: NaiveDate,
+) -> Result<Vec<NetWorthPoint>> {
+    snapshot_derived(pool, today).await?;
+
+    let first_of_month = today.with_day(1).unwrap_or(today);
+    let mut points = Vec::with_capacity(months as usize);
+    for back in (1..months).rev() {
+        let Some(month_end) = first_of_month
+            .checked_sub_months(Months::new(back - 1))
+            .and_then(|d| d.checked_sub_days(Days::new(1)))
+        else {
+            continue;
+        };
+        points.push(point(pool, month_end).await?);
+    }
+    points.push(point(pool, today).await?);
+    Ok(points)
+}
@@ -0,0 +1,160 @@
+//! Local notifications raised from Rust.
+//!
+//! Everything goes through `scheduled_notifications`. Immediate notifications
+//! are recorded as already sent under a caller-chosen key, which is how we
+//! avoid telling the user the same thing twice; future ones are left unsent
+//! for `scheduler` to deliver when due, and on mobile also registered with
+//! the OS (see `platform`). During quiet hours an immediate notification is
+//! queued for the end of the window instead.
+
+pub mod platform;
+pub mod scheduler;
+
+use chrono::{Local, Utc};
+use sqlx::SqlitePool;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::db::{new_id, now, timestamp};
+use crate::error::Result;
+use crate::preferences;
+
+/// Whether the user has notifications switched on. A missing preferences
+/// row means the defaults, which are on.
+pub async fn enabled(pool: &SqlitePool) -> Result<bool> {
+    let row: Option<(Option<i64>,)> =
+        sqlx::query_as("SELECT notifications_enabled FROM notification_preferences WHERE id = 1")
+            .fetch_optional(pool)
+            .await?;
+    Ok(row.and_then(|(enabled,)| enabled).unwrap_or(1) != 0)
+}
+
+/// Record and show a notification unless one with `key` was already sent.
+/// During quiet hours it is left for `scheduler::dispatch` to show when the
+/// window ends. Returns `true` when this call recorded it.
+pub async fn send_once(
+    app: &AppHandle,
+    pool: &SqlitePool,
+    key: &str,
+    notification_type: &str,
+    goal_id: Option<&str>,
+    title: &str,
+    body: &str,
+) -> Result<bool> {
+    let user_id = crate::auth::current_user_id(pool).await?;
+    let preferences = preferences::get_or_create_notification_preferences(pool).await?;
+    let local_now = Local::now();
+    let deliver_at = scheduler::defer(&preferences, local_now);
+    let quiet = deliver_at != local_now;
+    let now = now();
+    let inserted = sqlx::query(
+        "INSERT OR IGNORE INTO scheduled_notifications
+         (id, user_id, notification_type, goal_id, title, body, scheduled_at, sent_at, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+    )
+    .bind(key)
+    .bind(user_id)
+    .bind(notification_type)
+    .bind(goal_id)
+    .bind(title)
+    .bind(body)
+    .bind(timestamp(deliver_at.with_timezone(&Utc)))
+    .bind((!quiet).then_some(&now))
+    .bind(&now)
+    .bind(&now)
+    .execute(pool)
+    .await?
+    .rows_affected()
+        > 0;
+
+    if inserted && !quiet && preferences.notifications_enabled {
+        if let Err(e) = app.notification().builder().title(title).body(body).show() {
+            eprintln!("[Notifications] Failed to show \"{title}\": {e}");
+        }
+    }
+    Ok(inserted)
+}
+
+/// When the notification recorded under `key` went out, if it did.
+pub async fn sent_at(pool: &SqlitePool, key: &str) -> Result<Option<String>> {
+    let row: Option<(Option<String>,)> =
+        sqlx::query_as("SELECT sent_at FROM scheduled_notifications WHERE id = $1")
+            .bind(key)
+            .fetch_optional(pool)
+            .await?;
+    Ok(row.and_then(|(sent_at,)| sent_at))
+}
+
+/// Queue a notification for `scheduler::dispatch` to send at `at` (an ISO
+/// timestamp). Returns the id of the queued row.
+pub async fn schedule(
+    pool: &SqlitePool,
+    notification_type: &str,
+    title: &str,
+    body: &str,
+    at: &str,
+) -> Result<String> {
+    let user_id = crate::auth::current_user_id(pool).await?;
+    let id = new_id();
+    let now = now();
+    sqlx::query(
+        "INSERT INTO scheduled_notifications
+         (id, user_id, notification_type, title, body, scheduled_at, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+    )
+    .bind(&id)
+    .bind(user_id)
+    .bind(notification_type)
+    .bind(title)
+    .bind(body)
+    .bind(at)
+    .bind(&now)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+    Ok(id)
+}
+
+/// Queue a notification under `key`, or move the one already queued under
+/// it to `at` with the new text. Once it has gone out, the key stays sent.
+pub async fn schedule_once(
+    pool: &SqlitePool,
+    key: &str,
+    notification_type: &str,
+    title: &str,
+    body: &str,
+    at: &str,
+) -> Result<()> {
+    let user_id = crate::auth::current_user_id(pool).await?;
+    let now = now();
+    sqlx::query(
+        "INSERT INTO scheduled_notifications
+         (id, user_id, notification_type, title, body, scheduled_at, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $7)
+         ON CONFLICT(id) DO UPDATE SET
+           title = excluded.title,
+           body = excluded.body,
+           scheduled_at = excluded.scheduled_at,
+           updated_at = excluded.updated_at
+         WHERE sent_at IS NULL",
+    )
+    .bind(key)
+    .bind(user_id)
+    .bind(notification_type)
+    .bind(title)
+    .bind(body)
+    .bind(at)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Drop a queued notification that hasn't gone out yet.
+pub async fn cancel(pool: &SqlitePool, id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM scheduled_notifications WHERE id = $1 AND sent_at IS NULL")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
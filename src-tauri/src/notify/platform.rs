@@ -0,0 +1,160 @@
+//! Pending notifications handed to the OS scheduler on mobile.
+//!
+//! The in-app scheduler only runs while the process does, and iOS and
+//! Android suspend or kill it as they please. So the next few pending rows
+//! of `scheduled_notifications` are also registered with the platform
+//! (UNUserNotificationCenter, AlarmManager), and registered again from
+//! scratch whenever preferences or the queue change. `platform_notifications`
+//! remembers what the OS holds, both to cancel it and so the in-app scheduler
+//! doesn't show those a second time. On desktop the in-app scheduler lives
+//! as long as the app is open, which is all we need there.
+
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use tauri::AppHandle;
+
+use crate::db::now;
+use crate::error::Result;
+
+/// iOS holds at most 64 pending notifications per app; leave some room.
+const MAX_REGISTERED: i64 = 48;
+
+#[derive(sqlx::FromRow)]
+struct Pending {
+    id: String,
+    title: String,
+    body: String,
+    scheduled_at: String,
+}
+
+/// The OS wants a positive `i32` where we have string ids.
+fn platform_id(notification_id: &str) -> i32 {
+    let digest = Sha256::digest(notification_id.as_bytes());
+    i32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]) & i32::MAX
+}
+
+/// Replace what the OS holds with the next pending notifications. Returns
+/// how many were registered.
+pub async fn register(app: &AppHandle, pool: &SqlitePool) -> Result<usize> {
+    if !cfg!(mobile) {
+        return Ok(0);
+    }
+
+    let registered: Vec<(i64,)> = sqlx::query_as("SELECT platform_id FROM platform_notifications")
+        .fetch_all(pool)
+        .await?;
+    if !registered.is_empty() {
+        let ids = registered.into_iter().map(|(id,)| id as i32).collect();
+        if let Err(e) = os::cancel(app, ids) {
+            eprintln!("[Notifications] Failed to cancel platform notifications: {e}");
+        }
+        sqlx::query("DELETE FROM platform_notifications")
+            .execute(pool)
+            .await?;
+    }
+    if !super::enabled(pool).await? {
+        return Ok(0);
+    }
+
+    let now = now();
+    let pending: Vec<Pending> = sqlx::query_as(
+        "SELECT id, title, body, scheduled_at FROM scheduled_notifications
+         WHERE sent_at IS NULL AND deleted_at IS NULL AND scheduled_at > $1
+         ORDER BY scheduled_at
+         LIMIT $2",
+    )
+    .bind(&now)
+    .bind(MAX_REGISTERED)
+    .fetch_all(pool)
+    .await?;
+
+    let mut count = 0;
+    for notification in pending {
+        let Ok(at) = DateTime::parse_from_rfc3339(&notification.scheduled_at) else {
+            continue;
+        };
+        let id = platform_id(&notification.id);
+        if let Err(e) = os::schedule(
+            app,
+            id,
+            &notification.title,
+            &notification.body,
+            at.with_timezone(&Utc),
+        ) {
+            eprintln!(
+                "[Notifications] Failed to register \"{}\": {e}",
+                notification.title
+            );
+            continue;
+        }
+        sqlx::query(
+            "INSERT OR REPLACE INTO platform_notifications
+             (notification_id, platform_id, scheduled_at, registered_at)
+             VALUES ($1, $2, $3, $4)",
+        )
+        .bind(&notification.id)
+        .bind(id)
+        .bind(&notification.scheduled_at)
+        .bind(&now)
+        .execute(pool)
+        .await?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(mobile)]
+mod os {
+    use chrono::{DateTime, Utc};
+    use tauri::AppHandle;
+    use tauri_plugin_notification::{NotificationExt, Result, Schedule};
+
+    pub fn cancel(app: &AppHandle, ids: Vec<i32>) -> Result<()> {
+        app.notification().cancel(ids)
+    }
+
+    pub fn schedule(
+        app: &AppHandle,
+        id: i32,
+        title: &str,
+        body: &str,
+        at: DateTime<Utc>,
+    ) -> Result<()> {
+        let date = time::OffsetDateTime::from_unix_timestamp(at.timestamp())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        app.notification()
+            .builder()
+            .id(id)
+            .title(title)
+            .body(body)
+            .schedule(Schedule::At {
+                date,
+                repeating: false,
+                allow_while_idle: true,
+            })
+            .show()
+    }
+}
+
+/// Never reached: `register` returns early off mobile.
+#[cfg(desktop)]
+mod os {
+    use chrono::{DateTime, Utc};
+    use tauri::AppHandle;
+    use tauri_plugin_notification::Result;
+
+    pub fn cancel(_app: &AppHandle, _ids: Vec<i32>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn schedule(
+        _app: &AppHandle,
+        _id: i32,
+        _title: &str,
+        _body: &str,
+        _at: DateTime<Utc>,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
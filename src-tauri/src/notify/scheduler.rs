@@ -0,0 +1,441 @@
+//! Recurring notifications from the cron expressions in
+//! `notification_preferences`, and delivery of whatever comes due.
+//!
+//! Each recurring kind keeps one pending row in `scheduled_notifications`
+//! at the next time its cron matches, in local time like the frontend's
+//! cron-parser. `reschedule` lines those rows up with the preferences and
+//! refreshes their text; `dispatch` shows what is due, marks it sent and
+//! queues the next occurrence.
+//!
+//! Nothing goes out during quiet hours. A row due inside the window, queued
+//! by us or by anyone else, is moved to the end of it, which also moves it
+//! for the OS on mobile the next time `platform::register` runs.
+
+use chrono::{DateTime, Datelike, Days, Local, NaiveTime, TimeZone, Utc};
+use croner::Cron;
+use sqlx::SqlitePool;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::checkins::{self, CheckinKind};
+use crate::db::{new_id, now, timestamp};
+use crate::error::{Error, Result};
+use crate::goals;
+use crate::preferences::{self, NotificationPreferences};
+
+struct Content {
+    title: String,
+    body: String,
+    goal_id: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct Due {
+    id: String,
+    user_id: Option<String>,
+    notification_type: String,
+    goal_id: Option<String>,
+    title: String,
+    body: String,
+    cron_expression: Option<String>,
+    delivered_by_os: bool,
+}
+
+/// The first time after `after` that `cron` (five fields) matches.
+pub fn next_fire(cron: &str, after: DateTime<Local>) -> Result<DateTime<Utc>> {
+    let invalid = || Error::Validation(format!("Invalid cron expression \"{cron}\""));
+    let parsed = Cron::new(cron).parse().map_err(|_| invalid())?;
+    let next = parsed
+        .find_next_occurrence(&after, false)
+        .map_err(|_| invalid())?;
+    Ok(next.with_timezone(&Utc))
+}
+
+/// When something due at `at` may go out: right then, or at the end of the
+/// quiet window `at` falls in. A window spanning midnight, 22:00 to 08:00
+/// say, ends the next morning for times before midnight.
+pub fn defer(preferences: &NotificationPreferences, at: DateTime<Local>) -> DateTime<Local> {
+    if !preferences.quiet_hours_enabled {
+        return at;
+    }
+    let parse = |hhmm: &str| NaiveTime::parse_from_str(hhmm, "%H:%M").ok();
+    let (Some(start), Some(end)) = (
+        parse(&preferences.quiet_hours_start),
+        parse(&preferences.quiet_hours_end),
+    ) else {
+        return at;
+    };
+    let time = at.time();
+    let ends_on = if start > end {
+        if time >= start {
+            at.date_naive() + Days::new(1)
+        } else if time < end {
+            at.date_naive()
+        } else {
+            return at;
+        }
+    } else if time >= start && time < end {
+        at.date_naive()
+    } else {
+        return at;
+    };
+    // An end that falls in a DST gap is taken an hour later.
+    let end = ends_on.and_time(end);
+    Local
+        .from_local_datetime(&end)
+        .earliest()
+        .or_else(|| {
+            Local
+                .from_local_datetime(&(end + chrono::Duration::hours(1)))
+                .earliest()
+        })
+        .unwrap_or(at)
+}
+
+/// Move pending rows that would go out during quiet hours to the end of
+/// the window. Returns how many moved.
+async fn defer_pending(pool: &SqlitePool, preferences: &NotificationPreferences) -> Result<usize> {
+    if !preferences.quiet_hours_enabled {
+        return Ok(0);
+    }
+    let pending: Vec<(String, String)> = sqlx::query_as(
+        "SELECT id, scheduled_at FROM scheduled_notifications
+         WHERE sent_at IS NULL AND deleted_at IS NULL",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let now = now();
+    let mut moved = 0;
+    for (id, scheduled_at) in pending {
+        let Ok(at) = DateTime::parse_from_rfc3339(&scheduled_at) else {
+            continue;
+        };
+        let at = at.with_timezone(&Local);
+        let deferred = defer(preferences, at);
+        if deferred == at {
+            continue;
+        }
+        sqlx::query(
+            "UPDATE scheduled_notifications SET scheduled_at = $1, updated_at = $2
+             WHERE id = $3 AND sent_at IS NULL",
+        )
+        .bind(timestamp(deferred.with_timezone(&Utc)))
+        .bind(&now)
+        .bind(&id)
+        .execute(pool)
+        .await?;
+        moved += 1;
+    }
+    Ok(moved)
+}
+
+fn progress_message(name: &str, percentage: f64, goal_count: usize) -> String {
+    let context = if goal_count > 1 {
+        format!(" ({goal_count} goals total)")
+    } else {
+        String::new()
+    };
+    let percentage = percentage.round();
+    if percentage >= 100.0 {
+        format!("Congratulations! You've reached your goal \"{name}\"!{context}")
+    } else if percentage >= 75.0 {
+        format!("Amazing! You're {percentage}% toward \"{name}\". Almost there!{context}")
+    } else if percentage >= 50.0 {
+        format!("Halfway there! You're {percentage}% toward \"{name}\". Keep going!{context}")
+    } else if percentage >= 25.0 {
+        format!("Great progress! You're {percentage}% toward \"{name}\".{context}")
+    } else if percentage > 0.0 {
+        format!("You're {percentage}% toward your goal \"{name}\". Every step counts!{context}")
+    } else {
+        format!(
+            "Ready to start saving toward \"{name}\"? Your journey begins with one step.{context}"
+        )
+    }
+}
+
+/// What a recurring kind says right now; `None` if there is nothing to say.
+async fn content(pool: &SqlitePool, notification_type: &str) -> Result<Option<Content>> {
+    match notification_type {
+        "monthly_checkin" => {
+            let today = Local::now().date_naive();
+            let month = today.with_day(1).unwrap_or(today);
+            let pending = checkins::pending(pool, month, today).await?;
+            let goal_id = match pending.as_slice() {
+                [only] if only.kind == CheckinKind::SavingsGoal => Some(only.goal_id.clone()),
+                _ => None,
+            };
+            Ok(
+                checkins::summary(&month.format("%B").to_string(), &pending).map(|body| Content {
+                    title: "Monthly Savings Check-in".to_string(),
+                    body,
+                    goal_id,
+                }),
+            )
+        }
+        "progress_update" => {
+            let goals = goals::list(pool).await?;
+            let mut top: Option<(&goals::SavingsGoal, f64)> = None;
+            for goal in &goals {
+                let saved = goals::total_saved(pool, &goal.id).await?;
+                let percentage = if goal.target_amount > 0.0 {
+                    saved / goal.target_amount * 100.0
+                } else {
+                    0.0
+                };
+                if top.is_none_or(|(_, best)| percentage > best) {
+                    top = Some((goal, percentage));
+                }
+            }
+            Ok(top.map(|(goal, percentage)| Content {
+                title: "Savings Progress Update".to_string(),
+                body: progress_message(&goal.name, percentage, goals.len()),
+                goal_id: Some(goal.id.clone()),
+            }))
+        }
+        "why_reminder" => {
+            let goals: Vec<_> = goals::list(pool)
+                .await?
+                .into_iter()
+                .filter(|g| {
+                    g.why_statement
+                        .as_deref()
+                        .is_some_and(|w| !w.trim().is_empty())
+                })
+                .collect();
+            if goals.is_empty() {
+                return Ok(None);
+            }
+            // Rotate by day of year so the pick is stable within a day.
+            let goal = &goals[Local::now().ordinal() as usize % goals.len()];
+            Ok(Some(Content {
+                title: format!("Remember: {}", goal.name),
+                body: format!("\"{}\"", goal.why_statement.as_deref().unwrap_or_default()),
+                goal_id: Some(goal.id.clone()),
+            }))
+        }
+        _ => Ok(None),
+    }
+}
+
+async fn cancel_type(pool: &SqlitePool, notification_type: &str) -> Result<()> {
+    sqlx::query(
+        "DELETE FROM scheduled_notifications WHERE notification_type = $1 AND sent_at IS NULL",
+    )
+    .bind(notification_type)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Keep one pending row of `notification_type` on `cron`. A row already on
+/// the same cron keeps its time and only gets the new text.
+async fn keep_scheduled(
+    pool: &SqlitePool,
+    notification_type: &str,
+    cron: &str,
+    content: Content,
+) -> Result<()> {
+    let pending: Vec<(String, Option<String>)> = sqlx::query_as(
+        "SELECT id, cron_expression FROM scheduled_notifications
+         WHERE notification_type = $1 AND sent_at IS NULL AND deleted_at IS NULL",
+    )
+    .bind(notification_type)
+    .fetch_all(pool)
+    .await?;
+    let now = now();
+    if let [(id, Some(current))] = pending.as_slice() {
+        if current == cron {
+            sqlx::query(
+                "UPDATE scheduled_notifications SET title = $1, body = $2, goal_id = $3, updated_at = $4
+                 WHERE id = $5",
+            )
+            .bind(&content.title)
+            .bind(&content.body)
+            .bind(&content.goal_id)
+            .bind(&now)
+            .bind(id)
+            .execute(pool)
+            .await?;
+            return Ok(());
+        }
+    }
+
+    let at = next_fire(cron, Local::now())?;
+    cancel_type(pool, notification_type).await?;
+    sqlx::query(
+        "INSERT INTO scheduled_notifications
+         (id, user_id, notification_type, goal_id, title, body, scheduled_at, cron_expression, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)",
+    )
+    .bind(new_id())
+    .bind(crate::auth::current_user_id(pool).await?)
+    .bind(notification_type)
+    .bind(&content.goal_id)
+    .bind(&content.title)
+    .bind(&content.body)
+    .bind(timestamp(at))
+    .bind(cron)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Bring the recurring notifications in line with the preferences.
+pub async fn reschedule(pool: &SqlitePool) -> Result<()> {
+    let preferences = preferences::get_or_create_notification_preferences(pool).await?;
+    let kinds = [
+        (
+            "monthly_checkin",
+            preferences.monthly_checkin_enabled,
+            &preferences.monthly_checkin_cron,
+        ),
+        (
+            "progress_update",
+            preferences.progress_updates_enabled,
+            &preferences.progress_updates_cron,
+        ),
+        (
+            "why_reminder",
+            preferences.why_reminders_enabled,
+            &preferences.why_reminders_cron,
+        ),
+    ];
+
+    for (notification_type, enabled, cron) in kinds {
+        let content = if preferences.notifications_enabled && enabled {
+            content(pool, notification_type).await?
+        } else {
+            None
+        };
+        let Some(content) = content else {
+            cancel_type(pool, notification_type).await?;
+            continue;
+        };
+        if let Err(e) = keep_scheduled(pool, notification_type, cron, content).await {
+            eprintln!("[Notifications] Could not schedule {notification_type}: {e}");
+        }
+    }
+    defer_pending(pool, &preferences).await?;
+    Ok(())
+}
+
+/// Show every notification that is due, unless the OS already did. Returns
+/// how many were handled.
+pub async fn dispatch(app: &AppHandle, pool: &SqlitePool) -> Result<usize> {
+    let preferences = preferences::get_or_create_notification_preferences(pool).await?;
+    let local_now = Local::now();
+    if !preferences.notifications_enabled {
+        return Ok(0);
+    }
+    let deferred = defer_pending(pool, &preferences).await?;
+
+    let now = now();
+    let due: Vec<Due> = sqlx::query_as(
+        "SELECT n.id, n.user_id, n.notification_type, n.goal_id, n.title, n.body, n.cron_expression,
+                EXISTS (
+                  SELECT 1 FROM platform_notifications p
+                  WHERE p.notification_id = n.id AND p.scheduled_at = n.scheduled_at
+                ) AS delivered_by_os
+         FROM scheduled_notifications n
+         WHERE n.scheduled_at <= $1 AND n.sent_at IS NULL AND n.deleted_at IS NULL
+         ORDER BY n.scheduled_at ASC",
+    )
+    .bind(&now)
+    .fetch_all(pool)
+    .await?;
+
+    let month_start = Local
+        .with_ymd_and_hms(local_now.year(), local_now.month(), 1, 0, 0, 0)
+        .earliest()
+        .map_or_else(|| now.clone(), |at| timestamp(at.with_timezone(&Utc)));
+
+    let mut handled = 0;
+    for notification in due {
+        // Claimed before showing, so a concurrent pass can't show it too.
+        let claimed = sqlx::query(
+            "UPDATE scheduled_notifications SET sent_at = $1, updated_at = $1
+             WHERE id = $2 AND sent_at IS NULL",
+        )
+        .bind(&now)
+        .bind(&notification.id)
+        .execute(pool)
+        .await?
+        .rows_affected()
+            > 0;
+        if !claimed {
+            continue;
+        }
+        handled += 1;
+
+        // The month-end close may have asked for this month's check-in already.
+        let (checked_in,): (bool,) = sqlx::query_as(
+            "SELECT EXISTS (
+               SELECT 1 FROM scheduled_notifications
+               WHERE notification_type = 'monthly_checkin' AND sent_at >= $1 AND id != $2
+             )",
+        )
+        .bind(&month_start)
+        .bind(&notification.id)
+        .fetch_one(pool)
+        .await?;
+        let duplicate = notification.delivered_by_os
+            || (notification.notification_type == "monthly_checkin" && checked_in);
+        if notification.notification_type == "monthly_checkin" && !checked_in {
+            let today = local_now.date_naive();
+            let month = today.with_day(1).unwrap_or(today);
+            let pending = checkins::pending(pool, month, today).await?;
+            if !pending.is_empty() {
+                checkins::announce(pool, month, &pending).await;
+            }
+        }
+        if !duplicate {
+            if let Err(e) = app
+                .notification()
+                .builder()
+                .title(&notification.title)
+                .body(&notification.body)
+                .show()
+            {
+                eprintln!(
+                    "[Notifications] Failed to show \"{}\": {e}",
+                    notification.title
+                );
+            }
+        }
+
+        let Some(cron) = notification.cron_expression else {
+            continue;
+        };
+        match next_fire(&cron, local_now) {
+            Ok(at) => {
+                let at = defer(&preferences, at.with_timezone(&Local)).with_timezone(&Utc);
+                sqlx::query(
+                    "INSERT INTO scheduled_notifications
+                     (id, user_id, notification_type, goal_id, title, body, scheduled_at, cron_expression, created_at, updated_at)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)",
+                )
+                .bind(new_id())
+                .bind(&notification.user_id)
+                .bind(&notification.notification_type)
+                .bind(&notification.goal_id)
+                .bind(&notification.title)
+                .bind(&notification.body)
+                .bind(timestamp(at))
+                .bind(&cron)
+                .bind(&now)
+                .execute(pool)
+                .await?;
+            }
+            Err(e) => eprintln!("[Notifications] Not rescheduling: {e}"),
+        }
+    }
+
+    // Recurring ones moved on, or quiet hours moved some, and the window the
+    // OS holds follows.
+    if handled > 0 || deferred > 0 {
+        super::platform::register(app, pool).await?;
+    }
+    Ok(handled)
+}
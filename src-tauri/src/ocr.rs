@@ -0,0 +1,44 @@
+//! On-device OCR for receipt photos.
+//!
+//! Recognition uses Tesseract (via leptess) and is only compiled in with the
+//! `ocr` cargo feature, since it links against the system Tesseract and
+//! Leptonica libraries. Trained data is read from `<app data>/models/tessdata`
+//! when present, otherwise from the system install.
+
+use std::path::Path;
+
+use crate::error::{Error, Result};
+
+/// Languages passed to Tesseract; receipts are mostly English or German.
+#[cfg(feature = "ocr")]
+const LANGUAGES: &str = "eng+deu";
+
+pub struct OcrText {
+    pub text: String,
+    /// Mean word confidence in `0.0..=1.0`.
+    pub confidence: f32,
+}
+
+/// Recognize text in an encoded image (PNG, JPEG, ...).
+#[cfg(feature = "ocr")]
+pub fn recognize(image: &[u8], tessdata: &Path) -> Result<OcrText> {
+    let data_path = tessdata.exists().then(|| tessdata.to_string_lossy());
+    let mut tess = leptess::LepTess::new(data_path.as_deref(), LANGUAGES)
+        .map_err(|e| Error::Unsupported(format!("failed to start OCR engine: {e}")))?;
+    tess.set_image_from_mem(image)
+        .map_err(|e| Error::Validation(format!("unreadable image: {e}")))?;
+
+    let text = tess
+        .get_utf8_text()
+        .map_err(|e| Error::Validation(format!("OCR produced invalid text: {e}")))?;
+    let confidence = (tess.mean_text_conf().clamp(0, 100) as f32) / 100.0;
+
+    Ok(OcrText { text, confidence })
+}
+
+#[cfg(not(feature = "ocr"))]
+pub fn recognize(_image: &[u8], _tessdata: &Path) -> Result<OcrText> {
+    Err(Error::Unsupported(
+        "receipt scanning is not available in this build".to_string(),
+    ))
+}
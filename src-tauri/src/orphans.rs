@@ -0,0 +1,223 @@
+//! Rows whose foreign keys point nowhere.
+//!
+//! Older installs wrote through connections that didn't enforce foreign
+//! keys, and a soft-deleted parent never trips them anyway, so some rows
+//! reference a goal or category that is gone. `repair` walks SQLite's own
+//! `foreign_key_check`: a nullable reference is cleared, anything else is
+//! moved to `orphaned_rows` as JSON, where it can still be looked at or put
+//! back by hand. Children of soft-deleted parents whose key cascades are
+//! soft-deleted too, the way the frontend does when deleting a goal.
+
+use serde::Serialize;
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::db::{new_id, now, quote_ident};
+use crate::error::Result;
+
+#[derive(Debug, Clone, Default, Serialize, specta::Type)]
+pub struct OrphanReport {
+    /// References set to NULL.
+    pub cleared: u64,
+    /// Rows moved to `orphaned_rows`.
+    pub quarantined: u64,
+    /// Rows soft-deleted along with their parent.
+    pub soft_deleted: u64,
+}
+
+#[derive(sqlx::FromRow)]
+struct Violation {
+    table: String,
+    rowid: Option<i64>,
+    parent: String,
+    fkid: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct ForeignKey {
+    id: i64,
+    table: String,
+    from: String,
+    to: Option<String>,
+    on_delete: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct Column {
+    name: String,
+    notnull: bool,
+}
+
+async fn tables(conn: &mut SqliteConnection) -> Result<Vec<String>> {
+    let rows: Vec<(String,)> = sqlx::query_as(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(rows.into_iter().map(|(name,)| name).collect())
+}
+
+async fn columns(conn: &mut SqliteConnection, table: &str) -> Result<Vec<Column>> {
+    Ok(
+        sqlx::query_as("SELECT name, \"notnull\" FROM pragma_table_info($1)")
+            .bind(table)
+            .fetch_all(&mut *conn)
+            .await?,
+    )
+}
+
+async fn foreign_keys(conn: &mut SqliteConnection, table: &str) -> Result<Vec<ForeignKey>> {
+    Ok(sqlx::query_as(
+        "SELECT id, \"table\", \"from\", \"to\", on_delete FROM pragma_foreign_key_list($1)",
+    )
+    .bind(table)
+    .fetch_all(&mut *conn)
+    .await?)
+}
+
+fn has_column(columns: &[Column], name: &str) -> bool {
+    columns.iter().any(|c| c.name == name)
+}
+
+/// Soft-delete live rows whose cascading parent is soft-deleted.
+async fn cascade_soft_deletes(conn: &mut SqliteConnection, now: &str) -> Result<u64> {
+    let mut count = 0;
+    for table in tables(conn).await? {
+        let child_columns = columns(conn, &table).await?;
+        if !has_column(&child_columns, "deleted_at") {
+            continue;
+        }
+        for fk in foreign_keys(conn, &table).await? {
+            if fk.on_delete != "CASCADE"
+                || !has_column(&columns(conn, &fk.table).await?, "deleted_at")
+            {
+                continue;
+            }
+            let touch = if has_column(&child_columns, "updated_at") {
+                ", updated_at = $1"
+            } else {
+                ""
+            };
+            count += sqlx::query(&format!(
+                "UPDATE {child} SET deleted_at = $1{touch}
+                 WHERE deleted_at IS NULL
+                   AND {from} IN (SELECT {to} FROM {parent} WHERE deleted_at IS NOT NULL)",
+                child = quote_ident(&table),
+                from = quote_ident(&fk.from),
+                to = quote_ident(fk.to.as_deref().unwrap_or("id")),
+                parent = quote_ident(&fk.table),
+            ))
+            .bind(now)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+        }
+    }
+    Ok(count)
+}
+
+/// Move a row into `orphaned_rows` and delete it.
+async fn quarantine(
+    conn: &mut SqliteConnection,
+    table: &str,
+    parent: &str,
+    rowid: i64,
+    columns: &[Column],
+    now: &str,
+) -> Result<u64> {
+    let fields = columns
+        .iter()
+        .map(|c| format!("'{}', {}", c.name.replace('\'', "''"), quote_ident(&c.name)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let record_id = if has_column(columns, "id") {
+        "CAST(id AS TEXT)"
+    } else {
+        "NULL"
+    };
+    let moved = sqlx::query(&format!(
+        "INSERT INTO orphaned_rows (id, table_name, record_id, parent_table, row_data, quarantined_at)
+         SELECT $1, $2, {record_id}, $3, json_object({fields}), $4
+         FROM {table} WHERE rowid = $5",
+        table = quote_ident(table),
+    ))
+    .bind(new_id())
+    .bind(table)
+    .bind(parent)
+    .bind(now)
+    .bind(rowid)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+    sqlx::query(&format!(
+        "DELETE FROM {} WHERE rowid = $1",
+        quote_ident(table)
+    ))
+    .bind(rowid)
+    .execute(&mut *conn)
+    .await?;
+    Ok(moved)
+}
+
+/// Fix every dangling reference in the database.
+pub async fn repair(pool: &SqlitePool) -> Result<OrphanReport> {
+    let now = now();
+    let mut report = OrphanReport::default();
+    let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+    report.soft_deleted = cascade_soft_deletes(&mut tx, &now).await?;
+
+    let violations: Vec<Violation> =
+        sqlx::query_as("SELECT \"table\", rowid, parent, fkid FROM pragma_foreign_key_check")
+            .fetch_all(&mut *tx)
+            .await?;
+    for violation in violations {
+        // Tables without rowids can't be addressed row by row; we have none.
+        let Some(rowid) = violation.rowid else {
+            continue;
+        };
+        let key: Vec<ForeignKey> = foreign_keys(&mut tx, &violation.table)
+            .await?
+            .into_iter()
+            .filter(|fk| fk.id == violation.fkid)
+            .collect();
+        let columns = columns(&mut tx, &violation.table).await?;
+        let nullable = !key.is_empty()
+            && key
+                .iter()
+                .all(|fk| columns.iter().any(|c| c.name == fk.from && !c.notnull));
+
+        if nullable {
+            let set = key
+                .iter()
+                .map(|fk| format!("{} = NULL", quote_ident(&fk.from)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            report.cleared += sqlx::query(&format!(
+                "UPDATE {} SET {set} WHERE rowid = $1",
+                quote_ident(&violation.table)
+            ))
+            .bind(rowid)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        } else {
+            report.quarantined += quarantine(
+                &mut tx,
+                &violation.table,
+                &violation.parent,
+                rowid,
+                &columns,
+                &now,
+            )
+            .await?;
+        }
+    }
+    tx.commit().await?;
+
+    if report.cleared + report.quarantined + report.soft_deleted > 0 {
+        eprintln!(
+            "[Maintenance] Orphaned rows: {} cleared, {} quarantined, {} soft-deleted",
+            report.cleared, report.quarantined, report.soft_deleted
+        );
+    }
+    Ok(report)
+}
@@ -0,0 +1,181 @@
+//! Budget periods.
+//!
+//! A budget runs from one payday to the next. With the default schedule
+//! that is the calendar month, keyed "YYYY-MM" as budgets always were; any
+//! other schedule keys a period by its first day ("YYYY-MM-DD"). The key is
+//! what `budgets.month` and `month_closes.month` hold.
+
+use chrono::{Datelike, Days, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::db::now;
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum PayFrequency {
+    #[default]
+    Monthly,
+    /// Paid on the 1st and the 16th.
+    Semimonthly,
+    Biweekly,
+    Weekly,
+}
+
+impl PayFrequency {
+    fn as_str(self) -> &'static str {
+        match self {
+            PayFrequency::Monthly => "monthly",
+            PayFrequency::Semimonthly => "semimonthly",
+            PayFrequency::Biweekly => "biweekly",
+            PayFrequency::Weekly => "weekly",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "monthly" => Some(PayFrequency::Monthly),
+            "semimonthly" => Some(PayFrequency::Semimonthly),
+            "biweekly" => Some(PayFrequency::Biweekly),
+            "weekly" => Some(PayFrequency::Weekly),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, specta::Type)]
+pub struct PaySchedule {
+    pub frequency: PayFrequency,
+    /// A known payday. Monthly schedules use its day of the month (none
+    /// means the 1st), weekly and biweekly ones count from it.
+    pub anchor_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, specta::Type)]
+pub struct Period {
+    pub key: String,
+    pub start: NaiveDate,
+    /// Last day, inclusive.
+    pub end: NaiveDate,
+}
+
+impl Period {
+    fn from_range(start: NaiveDate, end: NaiveDate, calendar_month: bool) -> Period {
+        let key = if calendar_month {
+            start.format("%Y-%m").to_string()
+        } else {
+            start.format("%Y-%m-%d").to_string()
+        };
+        Period { key, start, end }
+    }
+
+    /// The calendar month `date` falls in.
+    pub fn month_of(date: NaiveDate) -> Period {
+        PaySchedule::default().period_at(date)
+    }
+
+    pub fn days(&self) -> i64 {
+        (self.end - self.start).num_days() + 1
+    }
+
+    /// Days from `today` to the end, counting today.
+    pub fn days_left(&self, today: NaiveDate) -> i64 {
+        ((self.end - today.max(self.start)).num_days() + 1).max(0)
+    }
+}
+
+fn first_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+/// `day` of the month `date` is in, clamped to the month's last day.
+fn day_in_month(date: NaiveDate, day: u32) -> NaiveDate {
+    let first = first_of_month(date);
+    let last = first + Months::new(1) - Days::new(1);
+    first.with_day(day.min(last.day())).unwrap_or(last)
+}
+
+impl PaySchedule {
+    /// The period `date` falls in.
+    pub fn period_at(&self, date: NaiveDate) -> Period {
+        match self.frequency {
+            PayFrequency::Monthly => {
+                let payday = self.anchor_date.map_or(1, |d| d.day());
+                let mut start = day_in_month(date, payday);
+                if start > date {
+                    start = day_in_month(date - Months::new(1), payday);
+                }
+                let end = day_in_month(start + Months::new(1), payday) - Days::new(1);
+                Period::from_range(start, end, payday == 1)
+            }
+            PayFrequency::Semimonthly => {
+                let first = first_of_month(date);
+                let middle = first + Days::new(15);
+                if date < middle {
+                    Period::from_range(first, middle - Days::new(1), false)
+                } else {
+                    Period::from_range(middle, first + Months::new(1) - Days::new(1), false)
+                }
+            }
+            PayFrequency::Biweekly | PayFrequency::Weekly => {
+                let length = if self.frequency == PayFrequency::Weekly {
+                    7
+                } else {
+                    14
+                };
+                let anchor = self.anchor_date.unwrap_or(date);
+                let offset = (date - anchor).num_days().div_euclid(length) * length;
+                let start = anchor + chrono::Duration::days(offset);
+                Period::from_range(start, start + chrono::Duration::days(length - 1), false)
+            }
+        }
+    }
+
+    pub fn previous(&self, period: &Period) -> Period {
+        self.period_at(period.start - Days::new(1))
+    }
+}
+
+pub async fn schedule(pool: &SqlitePool) -> Result<PaySchedule> {
+    let row: Option<(String, Option<String>)> =
+        sqlx::query_as("SELECT frequency, anchor_date FROM pay_schedule WHERE id = 1")
+            .fetch_optional(pool)
+            .await?;
+    Ok(row
+        .map(|(frequency, anchor)| PaySchedule {
+            frequency: PayFrequency::parse(&frequency).unwrap_or_default(),
+            anchor_date: anchor.and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok()),
+        })
+        .unwrap_or_default())
+}
+
+pub async fn save_schedule(pool: &SqlitePool, schedule: &PaySchedule) -> Result<()> {
+    if matches!(
+        schedule.frequency,
+        PayFrequency::Weekly | PayFrequency::Biweekly
+    ) && schedule.anchor_date.is_none()
+    {
+        return Err(Error::Validation(
+            "Pick a payday to count weekly periods from".to_string(),
+        ));
+    }
+    sqlx::query(
+        "INSERT INTO pay_schedule (id, frequency, anchor_date, updated_at)
+         VALUES (1, $1, $2, $3)
+         ON CONFLICT(id) DO UPDATE SET
+           frequency = excluded.frequency,
+           anchor_date = excluded.anchor_date,
+           updated_at = excluded.updated_at",
+    )
+    .bind(schedule.frequency.as_str())
+    .bind(
+        schedule
+            .anchor_date
+            .map(|d| d.format("%Y-%m-%d").to_string()),
+    )
+    .bind(now())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
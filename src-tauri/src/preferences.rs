@@ -0,0 +1,246 @@
+//! The single-row settings tables, `auth_state` and
+//! `notification_preferences`. The session tokens that belong with
+//! `auth_state` are kept in the keychain by `auth`.
+//!
+//! Both hold one row with id 1. Reading them never fails for a missing row:
+//! it is created with the defaults first. Writes are upserts run inside a
+//! `BEGIN IMMEDIATE` transaction, which takes SQLite's write lock before
+//! reading, so two writers (us and the frontend's plugin connection, or two
+//! commands) queue up instead of both seeing no row and both inserting, or
+//! overwriting each other's half of an update.
+
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::auth::{self, Tokens};
+use crate::db::now;
+use crate::error::Result;
+use crate::sync::{self, Operation};
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct AuthState {
+    pub user_id: Option<String>,
+    pub email: Option<String>,
+    pub access_token: Option<String>,
+    pub refresh_token: Option<String>,
+    pub expires_at: Option<String>,
+    pub last_sync_at: Option<String>,
+}
+
+/// A new session, or all `None` to sign out. The sync timestamp is kept.
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct AuthStateUpdate {
+    pub user_id: Option<String>,
+    pub email: Option<String>,
+    pub access_token: Option<String>,
+    pub refresh_token: Option<String>,
+    pub expires_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct NotificationPreferences {
+    pub id: i64,
+    pub user_id: Option<String>,
+    pub notifications_enabled: bool,
+    pub monthly_checkin_enabled: bool,
+    pub monthly_checkin_cron: String,
+    pub progress_updates_enabled: bool,
+    pub progress_updates_cron: String,
+    pub why_reminders_enabled: bool,
+    pub why_reminders_cron: String,
+    pub quiet_hours_enabled: bool,
+    pub quiet_hours_start: String,
+    pub quiet_hours_end: String,
+    pub timezone: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Fields to change; `None` leaves a field as it is.
+#[derive(Debug, Default, Deserialize, specta::Type)]
+pub struct NotificationPreferencesUpdate {
+    pub notifications_enabled: Option<bool>,
+    pub monthly_checkin_enabled: Option<bool>,
+    pub monthly_checkin_cron: Option<String>,
+    pub progress_updates_enabled: Option<bool>,
+    pub progress_updates_cron: Option<String>,
+    pub why_reminders_enabled: Option<bool>,
+    pub why_reminders_cron: Option<String>,
+    pub quiet_hours_enabled: Option<bool>,
+    pub quiet_hours_start: Option<String>,
+    pub quiet_hours_end: Option<String>,
+    pub timezone: Option<String>,
+}
+
+/// Columns the table's defaults fill in are only NOT NULL by convention,
+/// so read them with the same defaults.
+const SELECT_PREFERENCES: &str = "SELECT id, user_id,
+        COALESCE(notifications_enabled, 1) AS notifications_enabled,
+        COALESCE(monthly_checkin_enabled, 1) AS monthly_checkin_enabled,
+        COALESCE(monthly_checkin_cron, '0 9 2 * *') AS monthly_checkin_cron,
+        COALESCE(progress_updates_enabled, 1) AS progress_updates_enabled,
+        COALESCE(progress_updates_cron, '0 10 * * 1') AS progress_updates_cron,
+        COALESCE(why_reminders_enabled, 1) AS why_reminders_enabled,
+        COALESCE(why_reminders_cron, '0 19 * * 1') AS why_reminders_cron,
+        COALESCE(quiet_hours_enabled, 0) AS quiet_hours_enabled,
+        COALESCE(quiet_hours_start, '22:00') AS quiet_hours_start,
+        COALESCE(quiet_hours_end, '08:00') AS quiet_hours_end,
+        COALESCE(timezone, 'UTC') AS timezone,
+        created_at, updated_at
+     FROM notification_preferences WHERE id = 1";
+
+const SELECT_AUTH: &str =
+    "SELECT user_id, email, access_token, refresh_token, expires_at, last_sync_at
+     FROM auth_state WHERE id = 1";
+
+async fn ensure_auth_row(conn: &mut SqliteConnection) -> Result<()> {
+    sqlx::query("INSERT OR IGNORE INTO auth_state (id) VALUES (1)")
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Returns whether the row had to be created.
+async fn ensure_preferences_row(conn: &mut SqliteConnection) -> Result<bool> {
+    let user: Option<(Option<String>,)> =
+        sqlx::query_as("SELECT user_id FROM auth_state WHERE id = 1")
+            .fetch_optional(&mut *conn)
+            .await?;
+    let now = now();
+    let inserted = sqlx::query(
+        "INSERT OR IGNORE INTO notification_preferences (id, user_id, created_at, updated_at)
+         VALUES (1, $1, $2, $2)",
+    )
+    .bind(user.and_then(|(id,)| id))
+    .bind(&now)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+    Ok(inserted > 0)
+}
+
+/// The row with the tokens filled in from wherever they are kept.
+async fn read_auth_state(conn: &mut SqliteConnection) -> Result<AuthState> {
+    let tokens = auth::load_tokens(conn).await?;
+    let state = sqlx::query_as::<_, AuthState>(SELECT_AUTH)
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok(AuthState {
+        access_token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
+        ..state
+    })
+}
+
+pub async fn get_or_create_auth_state(pool: &SqlitePool) -> Result<AuthState> {
+    let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+    ensure_auth_row(&mut tx).await?;
+    let state = read_auth_state(&mut tx).await?;
+    tx.commit().await?;
+    Ok(state)
+}
+
+pub async fn update_auth_state(pool: &SqlitePool, update: AuthStateUpdate) -> Result<AuthState> {
+    let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+    ensure_auth_row(&mut tx).await?;
+    sqlx::query("UPDATE auth_state SET user_id = $1, email = $2, expires_at = $3 WHERE id = 1")
+        .bind(&update.user_id)
+        .bind(&update.email)
+        .bind(&update.expires_at)
+        .execute(&mut *tx)
+        .await?;
+    let tokens = Tokens {
+        access_token: update.access_token,
+        refresh_token: update.refresh_token,
+    };
+    auth::store_tokens(&mut tx, &tokens).await?;
+    let state = read_auth_state(&mut tx).await?;
+    tx.commit().await?;
+    Ok(state)
+}
+
+pub async fn get_or_create_notification_preferences(
+    pool: &SqlitePool,
+) -> Result<NotificationPreferences> {
+    let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+    ensure_preferences_row(&mut tx).await?;
+    let preferences = sqlx::query_as::<_, NotificationPreferences>(SELECT_PREFERENCES)
+        .fetch_one(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(preferences)
+}
+
+/// Apply `update` and queue the result for sync.
+pub async fn update_notification_preferences(
+    pool: &SqlitePool,
+    update: NotificationPreferencesUpdate,
+) -> Result<NotificationPreferences> {
+    let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+    let created = ensure_preferences_row(&mut tx).await?;
+    sqlx::query(
+        "UPDATE notification_preferences SET
+           notifications_enabled = COALESCE($1, notifications_enabled),
+           monthly_checkin_enabled = COALESCE($2, monthly_checkin_enabled),
+           monthly_checkin_cron = COALESCE($3, monthly_checkin_cron),
+           progress_updates_enabled = COALESCE($4, progress_updates_enabled),
+           progress_updates_cron = COALESCE($5, progress_updates_cron),
+           why_reminders_enabled = COALESCE($6, why_reminders_enabled),
+           why_reminders_cron = COALESCE($7, why_reminders_cron),
+           quiet_hours_enabled = COALESCE($8, quiet_hours_enabled),
+           quiet_hours_start = COALESCE($9, quiet_hours_start),
+           quiet_hours_end = COALESCE($10, quiet_hours_end),
+           timezone = COALESCE($11, timezone),
+           user_id = COALESCE((SELECT user_id FROM auth_state WHERE id = 1), user_id),
+           updated_at = $12
+         WHERE id = 1",
+    )
+    .bind(update.notifications_enabled)
+    .bind(update.monthly_checkin_enabled)
+    .bind(&update.monthly_checkin_cron)
+    .bind(update.progress_updates_enabled)
+    .bind(&update.progress_updates_cron)
+    .bind(update.why_reminders_enabled)
+    .bind(&update.why_reminders_cron)
+    .bind(update.quiet_hours_enabled)
+    .bind(&update.quiet_hours_start)
+    .bind(&update.quiet_hours_end)
+    .bind(&update.timezone)
+    .bind(now())
+    .execute(&mut *tx)
+    .await?;
+
+    let preferences = sqlx::query_as::<_, NotificationPreferences>(SELECT_PREFERENCES)
+        .fetch_one(&mut *tx)
+        .await?;
+    // The server keys the row by user.
+    if let Some(user_id) = &preferences.user_id {
+        let operation = if created {
+            Operation::Insert
+        } else {
+            Operation::Update
+        };
+        sync::enqueue_payload(
+            &mut tx,
+            "notification_preferences",
+            user_id,
+            operation,
+            serde_json::json!({
+                "notifications_enabled": preferences.notifications_enabled,
+                "monthly_checkin_enabled": preferences.monthly_checkin_enabled,
+                "monthly_checkin_cron": preferences.monthly_checkin_cron,
+                "progress_updates_enabled": preferences.progress_updates_enabled,
+                "progress_updates_cron": preferences.progress_updates_cron,
+                "why_reminders_enabled": preferences.why_reminders_enabled,
+                "why_reminders_cron": preferences.why_reminders_cron,
+                "quiet_hours_enabled": preferences.quiet_hours_enabled,
+                "quiet_hours_start": preferences.quiet_hours_start,
+                "quiet_hours_end": preferences.quiet_hours_end,
+                "updated_at": preferences.updated_at,
+            }),
+        )
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(preferences)
+}
@@ -0,0 +1,175 @@
+//! Logging an expense without going through the main screen.
+//!
+//! `goaldy://add?...` links (see `drafts::parse_add_link`) come from
+//! launchers and shortcuts, and text shared from other apps is handled the
+//! same way. Each opens the expense form
+//! pre-filled through `DRAFT_EVENT`. Users who turned on auto-save get
+//! links with an amount and a known category saved straight away instead;
+//! it is off by default since any web page can open such a link.
+//!
+//! On desktop, a global shortcut also opens a small window for entering
+//! an expense (see `window`).
+
+#[cfg(desktop)]
+pub mod window;
+
+/// There is no quick-add window on mobile; launchers open links instead.
+#[cfg(mobile)]
+pub mod window {
+    use sqlx::SqlitePool;
+    use tauri::AppHandle;
+
+    use crate::error::{Error, Result};
+
+    fn unsupported() -> Error {
+        Error::Unsupported("The quick-add window is only available on desktop".to_string())
+    }
+
+    pub async fn shortcut(_pool: &SqlitePool) -> Result<String> {
+        Err(unsupported())
+    }
+
+    pub async fn set_shortcut(_app: &AppHandle, _pool: &SqlitePool, _new: &str) -> Result<String> {
+        Err(unsupported())
+    }
+
+    pub fn show(_app: &AppHandle) -> Result<()> {
+        Err(unsupported())
+    }
+
+    pub fn hide(_app: &AppHandle) -> Result<()> {
+        Ok(())
+    }
+}
+
+use std::sync::Mutex;
+
+use chrono::Local;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_deep_link::DeepLinkExt;
+use url::Url;
+
+use crate::app_meta;
+use crate::db::Db;
+use crate::drafts::{self, ExpenseDraft};
+use crate::error::{Error, Result};
+use crate::events::{self, DomainEvent};
+use crate::expenses::{self, Expense, NewExpense};
+
+/// Emitted with an `ExpenseDraft` for the frontend to show in the expense
+/// form.
+pub const DRAFT_EVENT: &str = "quick-add://draft";
+
+const AUTO_SAVE_KEY: &str = "quick_add_auto_save";
+
+/// The last draft from a link, for a frontend that wasn't listening yet.
+#[derive(Default)]
+pub struct QuickAdd {
+    pending: Mutex<Option<ExpenseDraft>>,
+}
+
+impl QuickAdd {
+    pub fn take_pending(&self) -> Option<ExpenseDraft> {
+        self.pending.lock().unwrap().take()
+    }
+}
+
+pub async fn auto_save(pool: &SqlitePool) -> Result<bool> {
+    Ok(app_meta::get(pool, AUTO_SAVE_KEY).await?.as_deref() == Some("1"))
+}
+
+pub async fn set_auto_save(pool: &SqlitePool, auto_save: bool) -> Result<()> {
+    app_meta::set(pool, AUTO_SAVE_KEY, if auto_save { "1" } else { "0" }).await
+}
+
+/// Save an expense entered outside the main screen and tell every window,
+/// as `create_expense` does.
+pub async fn save(app: &AppHandle, pool: &SqlitePool, input: NewExpense) -> Result<Expense> {
+    let expense = expenses::create(pool, input, Local::now().date_naive()).await?;
+    events::publish(
+        app,
+        &DomainEvent::ExpenseCreated {
+            expense_id: expense.id.clone(),
+            category_id: expense.category_id.clone(),
+            amount: expense.amount,
+            date: expense.date.clone(),
+        },
+    )?;
+    expenses::check_alerts(app, pool, expense.category_id.as_deref()).await;
+    Ok(expense)
+}
+
+/// Handle quick-add links the app is opened with, now and while running.
+pub fn watch(app: &AppHandle) {
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            open_link(&handle, url);
+        }
+    });
+    match app.deep_link().get_current() {
+        Ok(urls) => {
+            for url in urls.unwrap_or_default() {
+                open_link(app, url);
+            }
+        }
+        Err(e) => eprintln!("[QuickAdd] Could not read the launch URL: {e}"),
+    }
+}
+
+fn open_link(app: &AppHandle, url: Url) {
+    if !drafts::is_add_link(&url) {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = handle_link(&app, &url).await {
+            eprintln!("[QuickAdd] Ignored {url}: {e}");
+        }
+    });
+}
+
+/// Save the link's expense or open it in the form.
+async fn handle_link(app: &AppHandle, url: &Url) -> Result<Option<Expense>> {
+    let db = app.state::<Db>();
+    let categories = drafts::load_categories(db.pool()).await?;
+    let draft = drafts::parse_add_link(url, &categories, Local::now().date_naive())?;
+    handle_draft(app, draft).await
+}
+
+/// Text shared from another app ("12.50 coffee"), handled like a link.
+pub async fn handle_text(app: &AppHandle, text: &str) -> Result<Option<Expense>> {
+    if text.trim().is_empty() {
+        return Err(Error::Validation("Text is empty".to_string()));
+    }
+    let db = app.state::<Db>();
+    let categories = drafts::load_categories(db.pool()).await?;
+    let draft = drafts::parse_expense_text(text, &categories, Local::now().date_naive());
+    handle_draft(app, draft).await
+}
+
+/// Save a complete draft if auto-save is on, or hand it to the form.
+/// Returns the expense if it was saved.
+async fn handle_draft(app: &AppHandle, draft: ExpenseDraft) -> Result<Option<Expense>> {
+    let db = app.state::<Db>();
+    let pool = db.pool();
+    if let (Some(amount), Some(category_id)) = (draft.amount, &draft.category_id) {
+        if auto_save(pool).await? {
+            let input = NewExpense {
+                amount,
+                category_id: Some(category_id.clone()),
+                note: draft.note.clone(),
+                date: Some(draft.date.clone()),
+                payment_method: None,
+                currency: None,
+                account_id: None,
+            };
+            return save(app, pool, input).await.map(Some);
+        }
+    }
+
+    *app.state::<QuickAdd>().pending.lock().unwrap() = Some(draft.clone());
+    app.emit(DRAFT_EVENT, &draft)?;
+    Ok(None)
+}
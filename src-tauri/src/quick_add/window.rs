@@ -0,0 +1,124 @@
+//! The desktop quick-add window and the global shortcut that opens it.
+//!
+//! The window is small, undecorated and stays on top; it only asks for an
+//! amount and a category and saves through `quick_add_expense`. Pressing
+//! the shortcut again hides it. The shortcut is `CommandOrControl+Shift+E`
+//! unless the user picked another one.
+
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::app_meta;
+use crate::db::Db;
+use crate::error::{Error, Result};
+
+pub const LABEL: &str = "quick-add";
+pub const DEFAULT_SHORTCUT: &str = "CommandOrControl+Shift+E";
+
+const SHORTCUT_KEY: &str = "quick_add_shortcut";
+
+fn shortcut_error(e: impl std::fmt::Display) -> Error {
+    Error::Validation(format!("shortcut error: {e}"))
+}
+
+/// The configured shortcut, in the form `set_shortcut` accepts.
+pub async fn shortcut(pool: &SqlitePool) -> Result<String> {
+    Ok(app_meta::get(pool, SHORTCUT_KEY)
+        .await?
+        .unwrap_or_else(|| DEFAULT_SHORTCUT.to_string()))
+}
+
+fn parse(shortcut: &str) -> Result<Shortcut> {
+    shortcut
+        .parse::<Shortcut>()
+        .map_err(|_| Error::Validation(format!("{shortcut} is not a valid shortcut")))
+}
+
+fn register(app: &AppHandle, shortcut: Shortcut) -> Result<()> {
+    app.global_shortcut()
+        .on_shortcut(shortcut, |app, _, event| {
+            if event.state() == ShortcutState::Pressed {
+                if let Err(e) = toggle(app) {
+                    eprintln!("[QuickAdd] Failed to toggle the window: {e}");
+                }
+            }
+        })
+        .map_err(shortcut_error)
+}
+
+/// Install the shortcut plugin and register the configured shortcut. A
+/// shortcut another app already holds is logged, not fatal.
+pub fn setup(app: &AppHandle) -> Result<()> {
+    app.plugin(tauri_plugin_global_shortcut::Builder::new().build())?;
+    let db = app.state::<Db>();
+    let configured = tauri::async_runtime::block_on(shortcut(db.pool()))?;
+    if let Err(e) = parse(&configured).and_then(|s| register(app, s)) {
+        eprintln!("[QuickAdd] Could not register {configured}: {e}");
+    }
+    Ok(())
+}
+
+/// Replace the shortcut. The old one stays if the new one can't be
+/// registered.
+pub async fn set_shortcut(app: &AppHandle, pool: &SqlitePool, new: &str) -> Result<String> {
+    let new = new.trim();
+    let parsed = parse(new)?;
+    let old = shortcut(pool).await?;
+    let shortcuts = app.global_shortcut();
+    if let Ok(old) = parse(&old) {
+        if old == parsed {
+            return Ok(old.to_string());
+        }
+        if shortcuts.is_registered(old) {
+            shortcuts.unregister(old).map_err(shortcut_error)?;
+        }
+    }
+    if let Err(e) = register(app, parsed) {
+        if let Ok(old) = parse(&old) {
+            let _ = register(app, old);
+        }
+        return Err(e);
+    }
+    app_meta::set(pool, SHORTCUT_KEY, new).await?;
+    Ok(new.to_string())
+}
+
+/// Show the window, creating it the first time.
+pub fn show(app: &AppHandle) -> Result<()> {
+    if let Some(window) = app.get_webview_window(LABEL) {
+        window.show()?;
+        window.set_focus()?;
+        return Ok(());
+    }
+    WebviewWindowBuilder::new(
+        app,
+        LABEL,
+        WebviewUrl::App("index.html?window=quick-add".into()),
+    )
+    .title("Add expense")
+    .inner_size(360.0, 240.0)
+    .resizable(false)
+    .decorations(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .center()
+    .focused(true)
+    .build()?;
+    Ok(())
+}
+
+/// Hide the window, keeping it around so it opens instantly next time.
+pub fn hide(app: &AppHandle) -> Result<()> {
+    if let Some(window) = app.get_webview_window(LABEL) {
+        window.hide()?;
+    }
+    Ok(())
+}
+
+fn toggle(app: &AppHandle) -> Result<()> {
+    match app.get_webview_window(LABEL) {
+        Some(window) if window.is_visible()? => hide(app),
+        _ => show(app),
+    }
+}
@@ -0,0 +1,331 @@
+//! Reconciling expenses against a bank statement.
+//!
+//! A statement is imported once, then `reconcile` pairs its lines with
+//! expenses: amounts have to agree within a few cents and dates within a few
+//! days, since card payments often post a day or two after the purchase.
+//! Among candidates the closest date wins, then the closest amount, then a
+//! description whose merchant matches the expense note. Each expense is
+//! matched at most once, and matches are kept so a later run only has to
+//! look at what is still open.
+
+pub(crate) mod statement;
+
+use std::collections::HashSet;
+
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::db::{new_id, now};
+use crate::error::{Error, Result};
+use crate::merchants;
+
+/// Largest difference in amount still treated as the same payment.
+const AMOUNT_TOLERANCE: f64 = 0.05;
+/// Days a statement line may be dated away from its expense.
+const DATE_TOLERANCE_DAYS: i64 = 3;
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct StatementImport {
+    pub id: String,
+    pub name: String,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub line_count: i64,
+    /// Rows that weren't debits or couldn't be read.
+    pub skipped: usize,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, specta::Type)]
+pub struct StatementRange {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct StatementLine {
+    pub id: String,
+    pub date: NaiveDate,
+    pub amount: f64,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct UnmatchedExpense {
+    pub id: String,
+    pub date: NaiveDate,
+    pub amount: f64,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct Match {
+    pub line: StatementLine,
+    pub expense_id: String,
+    /// Expense amount minus statement amount.
+    pub amount_difference: f64,
+    /// Days the statement line is dated after the expense.
+    pub days_apart: i64,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ReconciliationReport {
+    pub import_id: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    /// Pairs found by this run.
+    pub new_matches: Vec<Match>,
+    /// Lines matched before, by this or an earlier run.
+    pub matched_count: usize,
+    /// On the statement but not logged as an expense.
+    pub unmatched_lines: Vec<StatementLine>,
+    /// Logged but not on the statement.
+    pub unmatched_expenses: Vec<UnmatchedExpense>,
+    pub statement_total: f64,
+    pub expense_total: f64,
+}
+
+fn parse_day(date: &str) -> Option<NaiveDate> {
+    date.get(..10)
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+}
+
+fn format_day(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+/// Store the debits from a CSV statement export.
+pub async fn import(pool: &SqlitePool, name: &str, content: &str) -> Result<StatementImport> {
+    let (lines, skipped) = statement::parse(content)?;
+    if lines.is_empty() {
+        return Err(Error::Validation(
+            "The statement has no payments to reconcile".to_string(),
+        ));
+    }
+    let start_date = lines.iter().map(|l| l.date).min();
+    let end_date = lines.iter().map(|l| l.date).max();
+    let name = match name.trim() {
+        "" => "Statement",
+        name => name,
+    };
+
+    let id = new_id();
+    let now = now();
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "INSERT INTO statement_imports (id, name, start_date, end_date, line_count, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $6)",
+    )
+    .bind(&id)
+    .bind(name)
+    .bind(start_date.map(format_day))
+    .bind(end_date.map(format_day))
+    .bind(lines.len() as i64)
+    .bind(&now)
+    .execute(&mut *tx)
+    .await?;
+    for line in &lines {
+        sqlx::query(
+            "INSERT INTO statement_lines (id, import_id, date, amount, description)
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(new_id())
+        .bind(&id)
+        .bind(format_day(line.date))
+        .bind(line.amount)
+        .bind(&line.description)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(StatementImport {
+        id,
+        name: name.to_string(),
+        start_date,
+        end_date,
+        line_count: lines.len() as i64,
+        skipped,
+    })
+}
+
+/// Match the import's lines in `range` (default: the whole statement) to
+/// expenses and report what is left over on either side.
+pub async fn reconcile(
+    pool: &SqlitePool,
+    import_id: &str,
+    range: Option<StatementRange>,
+) -> Result<ReconciliationReport> {
+    let bounds: Option<(Option<String>, Option<String>)> =
+        sqlx::query_as("SELECT start_date, end_date FROM statement_imports WHERE id = $1")
+            .bind(import_id)
+            .fetch_optional(pool)
+            .await?;
+    let Some((first, last)) = bounds else {
+        return Err(Error::Validation(format!(
+            "Statement import {import_id} not found"
+        )));
+    };
+    let range =
+        match range {
+            Some(range) => range,
+            None => StatementRange {
+                start_date: first.as_deref().and_then(parse_day).ok_or_else(|| {
+                    Error::Validation("The statement has no dated lines".to_string())
+                })?,
+                end_date: last.as_deref().and_then(parse_day).ok_or_else(|| {
+                    Error::Validation("The statement has no dated lines".to_string())
+                })?,
+            },
+        };
+    if range.end_date < range.start_date {
+        return Err(Error::Validation(
+            "The range ends before it starts".to_string(),
+        ));
+    }
+
+    let lines: Vec<(String, String, f64, String, Option<String>)> = sqlx::query_as(
+        "SELECT id, date, amount, description, matched_expense_id FROM statement_lines
+         WHERE import_id = $1 AND date BETWEEN $2 AND $3
+         ORDER BY date, amount",
+    )
+    .bind(import_id)
+    .bind(format_day(range.start_date))
+    .bind(format_day(range.end_date))
+    .fetch_all(pool)
+    .await?;
+    // Lines near the edges may belong to expenses just outside the range.
+    let slack = Days::new(DATE_TOLERANCE_DAYS as u64);
+    let expenses: Vec<(String, String, f64, Option<String>)> = sqlx::query_as(
+        "SELECT id, date, amount, note FROM expenses
+         WHERE deleted_at IS NULL AND substr(date, 1, 10) BETWEEN $1 AND $2
+         ORDER BY date, amount",
+    )
+    .bind(format_day(range.start_date - slack))
+    .bind(format_day(range.end_date + slack))
+    .fetch_all(pool)
+    .await?;
+    // Expenses already claimed by a line of any statement.
+    let claimed: HashSet<String> = sqlx::query_as::<_, (String,)>(
+        "SELECT matched_expense_id FROM statement_lines WHERE matched_expense_id IS NOT NULL",
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|(id,)| id)
+    .collect();
+
+    let in_range = |date: NaiveDate| date >= range.start_date && date <= range.end_date;
+    let statement_total = lines.iter().map(|l| l.2).sum();
+    let expense_total = expenses
+        .iter()
+        .filter(|e| parse_day(&e.1).is_some_and(in_range))
+        .map(|e| e.2)
+        .sum();
+    let matched_before = lines.iter().filter(|l| l.4.is_some()).count();
+
+    let open_lines: Vec<StatementLine> = lines
+        .into_iter()
+        .filter(|(.., matched)| matched.is_none())
+        .filter_map(|(id, date, amount, description, _)| {
+            Some(StatementLine {
+                id,
+                date: parse_day(&date)?,
+                amount,
+                description,
+            })
+        })
+        .collect();
+    let open_expenses: Vec<UnmatchedExpense> = expenses
+        .into_iter()
+        .filter(|(id, ..)| !claimed.contains(id))
+        .filter_map(|(id, date, amount, note)| {
+            Some(UnmatchedExpense {
+                id,
+                date: parse_day(&date)?,
+                amount,
+                note,
+            })
+        })
+        .collect();
+
+    let corrections = merchants::load_corrections(pool).await?;
+    let merchant = |text: &str| merchants::normalize(text, &corrections).to_lowercase();
+
+    // Every plausible pairing, best first, then taken greedily.
+    let mut candidates = Vec::new();
+    for (li, line) in open_lines.iter().enumerate() {
+        let line_merchant = merchant(&line.description);
+        for (ei, expense) in open_expenses.iter().enumerate() {
+            let amount_difference = expense.amount - line.amount;
+            let days_apart = (line.date - expense.date).num_days();
+            if amount_difference.abs() > AMOUNT_TOLERANCE || days_apart.abs() > DATE_TOLERANCE_DAYS
+            {
+                continue;
+            }
+            let same_merchant = expense
+                .note
+                .as_deref()
+                .is_some_and(|note| !line_merchant.is_empty() && merchant(note) == line_merchant);
+            candidates.push((
+                days_apart.abs(),
+                amount_difference.abs(),
+                !same_merchant,
+                li,
+                ei,
+            ));
+        }
+    }
+    candidates.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)).then(a.2.cmp(&b.2)));
+
+    let mut used_lines = vec![false; open_lines.len()];
+    let mut used_expenses = vec![false; open_expenses.len()];
+    let mut new_matches = Vec::new();
+    let now = now();
+    let mut tx = pool.begin().await?;
+    for (.., li, ei) in candidates {
+        if used_lines[li] || used_expenses[ei] {
+            continue;
+        }
+        used_lines[li] = true;
+        used_expenses[ei] = true;
+        let line = open_lines[li].clone();
+        let expense = &open_expenses[ei];
+        let expense_id = expense.id.clone();
+        sqlx::query(
+            "UPDATE statement_lines SET matched_expense_id = $1, matched_at = $2 WHERE id = $3",
+        )
+        .bind(&expense_id)
+        .bind(&now)
+        .bind(&line.id)
+        .execute(&mut *tx)
+        .await?;
+        new_matches.push(Match {
+            amount_difference: expense.amount - line.amount,
+            days_apart: (line.date - expense.date).num_days(),
+            line,
+            expense_id,
+        });
+    }
+    tx.commit().await?;
+
+    Ok(ReconciliationReport {
+        import_id: import_id.to_string(),
+        start_date: range.start_date,
+        end_date: range.end_date,
+        matched_count: matched_before + new_matches.len(),
+        new_matches,
+        unmatched_lines: open_lines
+            .into_iter()
+            .zip(used_lines)
+            .filter_map(|(line, used)| (!used).then_some(line))
+            .collect(),
+        unmatched_expenses: open_expenses
+            .into_iter()
+            .zip(used_expenses)
+            .filter_map(|(expense, used)| (!used && in_range(expense.date)).then_some(expense))
+            .collect(),
+        statement_total,
+        expense_total,
+    })
+}
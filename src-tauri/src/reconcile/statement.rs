@@ -0,0 +1,167 @@
+//! Reading bank statement CSV exports.
+//!
+//! Banks disagree on everything: column names, separators, date formats,
+//! decimal commas and whether money going out is negative. Columns are found
+//! by their header, the separator by which candidate splits the header into
+//! the most fields, and the sign by looking at the whole file: when every
+//! amount has the same sign they are all taken as spending.
+
+use chrono::NaiveDate;
+
+use crate::error::{Error, Result};
+
+const DATE_HEADERS: &[&str] = &[
+    "date",
+    "booking date",
+    "transaction date",
+    "posted date",
+    "buchungstag",
+    "datum",
+];
+const AMOUNT_HEADERS: &[&str] = &["amount", "value", "betrag", "umsatz"];
+const DEBIT_HEADERS: &[&str] = &["debit", "withdrawal", "money out", "soll"];
+const DESCRIPTION_HEADERS: &[&str] = &[
+    "description",
+    "payee",
+    "merchant",
+    "name",
+    "memo",
+    "details",
+    "verwendungszweck",
+    "beguenstigter/zahlungspflichtiger",
+];
+
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%d.%m.%Y", "%d/%m/%Y", "%m/%d/%Y", "%d-%m-%Y"];
+
+/// One debit from a statement.
+#[derive(Debug, Clone)]
+pub struct Line {
+    pub date: NaiveDate,
+    /// Positive, like expense amounts.
+    pub amount: f64,
+    pub description: String,
+}
+
+struct Columns {
+    date: usize,
+    amount: Option<usize>,
+    debit: Option<usize>,
+    description: Option<usize>,
+}
+
+fn find(headers: &[String], names: &[&str]) -> Option<usize> {
+    headers.iter().position(|h| names.contains(&h.as_str()))
+}
+
+fn columns(headers: &[String]) -> Result<Columns> {
+    let date = find(headers, DATE_HEADERS)
+        .ok_or_else(|| Error::Validation("The statement has no date column".to_string()))?;
+    let amount = find(headers, AMOUNT_HEADERS);
+    let debit = find(headers, DEBIT_HEADERS);
+    if amount.is_none() && debit.is_none() {
+        return Err(Error::Validation(
+            "The statement has no amount column".to_string(),
+        ));
+    }
+    Ok(Columns {
+        date,
+        amount,
+        debit,
+        description: find(headers, DESCRIPTION_HEADERS),
+    })
+}
+
+pub(crate) fn delimiter(header_line: &str) -> u8 {
+    [b',', b';', b'\t']
+        .into_iter()
+        .max_by_key(|d| header_line.bytes().filter(|b| b == d).count())
+        .unwrap_or(b',')
+}
+
+pub(crate) fn parse_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+}
+
+/// "-1.234,56 €", "(12.50)" and "12.50-" all come out as numbers.
+pub(crate) fn parse_amount(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let negative = value.starts_with('-')
+        || value.ends_with('-')
+        || (value.starts_with('(') && value.ends_with(')'));
+    let mut digits: String = value
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '.' || *c == ',')
+        .collect();
+    // Whichever separator comes last is the decimal one.
+    match (digits.rfind('.'), digits.rfind(',')) {
+        (Some(dot), Some(comma)) if comma > dot => {
+            digits = digits.replace('.', "").replace(',', ".");
+        }
+        (_, Some(_)) if digits.matches(',').count() == 1 && !digits.contains('.') => {
+            digits = digits.replace(',', ".");
+        }
+        _ => digits = digits.replace(',', ""),
+    }
+    let amount: f64 = digits.parse().ok()?;
+    Some(if negative { -amount } else { amount })
+}
+
+/// Parse `content` into debits. Credits and rows that can't be read are
+/// skipped; the second value counts them.
+pub fn parse(content: &str) -> Result<(Vec<Line>, usize)> {
+    let content = content.trim_start_matches('\u{feff}');
+    let header_line = content.lines().next().unwrap_or_default();
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter(header_line))
+        .flexible(true)
+        .from_reader(content.as_bytes());
+
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| Error::Validation(format!("Could not read the statement: {e}")))?
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let columns = columns(&headers)?;
+
+    let mut rows = Vec::new();
+    let mut skipped = 0;
+    for record in reader.records() {
+        let Ok(record) = record else {
+            skipped += 1;
+            continue;
+        };
+        let field = |i: Option<usize>| i.and_then(|i| record.get(i)).unwrap_or_default();
+        let date = parse_date(field(Some(columns.date)));
+        // A debit column holds spending as positive numbers.
+        let amount = parse_amount(field(columns.debit))
+            .filter(|a| *a != 0.0)
+            .map(|a| -a.abs())
+            .or_else(|| parse_amount(field(columns.amount)));
+        match (date, amount) {
+            (Some(date), Some(amount)) => {
+                rows.push((date, amount, field(columns.description).to_string()))
+            }
+            _ => skipped += 1,
+        }
+    }
+
+    let all_positive = rows.iter().all(|(_, amount, _)| *amount >= 0.0);
+    let mut lines = Vec::new();
+    for (date, amount, description) in rows {
+        let spent = if all_positive { amount } else { -amount };
+        if spent > 0.0 {
+            lines.push(Line {
+                date,
+                amount: spent,
+                description: description.trim().to_string(),
+            });
+        } else {
+            skipped += 1;
+        }
+    }
+    Ok((lines, skipped))
+}
@@ -0,0 +1,229 @@
+//! Subscriptions found in the expense history by the background scan.
+//!
+//! Charges are grouped by merchant rather than by note, so "PAYPAL *SPOTIFY
+//! 4471" and "Spotify AB" count as one subscription; each group then has
+//! to pass the same cadence and amount checks as recurring candidates.
+//! Results are kept with a status: the user can dismiss one, or track it,
+//! turning it into a recurring expense with a renewal reminder.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use super::{NewRecurringExpense, RecurringExpense};
+use crate::analysis::recurring::{detect, match_key, Cadence, Charge};
+use crate::db::now;
+use crate::error::{Error, Result};
+use crate::merchants;
+
+/// Days before a renewal to remind about it, unless the user picks.
+pub const DEFAULT_REMIND_DAYS_BEFORE: i64 = 3;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct DetectedSubscription {
+    /// Match key of the merchant name.
+    pub match_key: String,
+    pub name: String,
+    pub merchant_id: Option<String>,
+    pub category_id: Option<String>,
+    /// Median charge.
+    pub amount: f64,
+    pub cadence: String,
+    pub occurrences: i64,
+    pub last_date: String,
+    /// When the next charge is expected.
+    pub next_date: String,
+    pub confidence: f64,
+    /// "new", "dismissed" or "tracked".
+    pub status: String,
+    /// The recurring expense it became, once tracked.
+    pub recurring_id: Option<String>,
+    pub detected_at: String,
+    pub updated_at: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct MerchantCharge {
+    #[sqlx(flatten)]
+    charge: Charge,
+    merchant_id: String,
+    merchant: String,
+}
+
+/// Look for subscriptions and store what was found. Returns the ones seen
+/// for the first time.
+pub async fn scan(pool: &SqlitePool, today: NaiveDate) -> Result<Vec<DetectedSubscription>> {
+    merchants::relink(pool, true).await?;
+    let rows = sqlx::query_as::<_, MerchantCharge>(
+        "SELECT e.id, e.amount, e.category_id, e.note, e.date, m.id AS merchant_id,
+                m.name AS merchant
+         FROM expenses e
+         JOIN merchants m ON m.id = e.merchant_id
+         WHERE e.deleted_at IS NULL
+         ORDER BY e.date ASC",
+    )
+    .fetch_all(pool)
+    .await?;
+    let mut groups: HashMap<String, (String, Vec<Charge>)> = HashMap::new();
+    for mut row in rows {
+        let Ok(day) = NaiveDate::parse_from_str(&row.charge.date, "%Y-%m-%d") else {
+            continue;
+        };
+        row.charge.day = day;
+        groups
+            .entry(row.merchant_id)
+            .or_insert_with(|| (row.merchant, Vec::new()))
+            .1
+            .push(row.charge);
+    }
+
+    let known: Vec<(String,)> = sqlx::query_as("SELECT match_key FROM detected_subscriptions")
+        .fetch_all(pool)
+        .await?;
+    let tracked: Vec<(String,)> = sqlx::query_as(
+        "SELECT match_key FROM recurring_expenses WHERE deleted_at IS NULL AND match_key IS NOT NULL",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let now = now();
+    let mut found = Vec::new();
+    let mut tx = pool.begin().await?;
+    for (merchant_id, (name, group)) in &groups {
+        let Some(candidate) = detect(group, today) else {
+            continue;
+        };
+        let key = match_key(name);
+        if key.is_empty() || tracked.iter().any(|(t,)| *t == key) {
+            continue;
+        }
+        sqlx::query(
+            "INSERT INTO detected_subscriptions
+                (match_key, name, merchant_id, category_id, amount, cadence, occurrences,
+                 last_date, next_date, confidence, detected_at, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $11)
+             ON CONFLICT(match_key) DO UPDATE SET
+               name = excluded.name,
+               merchant_id = excluded.merchant_id,
+               category_id = excluded.category_id,
+               amount = excluded.amount,
+               cadence = excluded.cadence,
+               occurrences = excluded.occurrences,
+               last_date = excluded.last_date,
+               next_date = excluded.next_date,
+               confidence = excluded.confidence,
+               updated_at = excluded.updated_at",
+        )
+        .bind(&key)
+        .bind(name)
+        .bind(merchant_id)
+        .bind(&candidate.category_id)
+        .bind(candidate.amount)
+        .bind(candidate.cadence.as_str())
+        .bind(candidate.occurrences as i64)
+        .bind(&candidate.last_date)
+        .bind(&candidate.next_date)
+        .bind(candidate.confidence)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+        found.push(key);
+    }
+
+    // Charges that stopped were cancelled; what the user decided on stays.
+    let stale: Vec<(String,)> =
+        sqlx::query_as("SELECT match_key FROM detected_subscriptions WHERE status = 'new'")
+            .fetch_all(&mut *tx)
+            .await?;
+    for (key,) in stale.iter().filter(|(k,)| !found.contains(k)) {
+        sqlx::query("DELETE FROM detected_subscriptions WHERE match_key = $1")
+            .bind(key)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    let mut new = Vec::new();
+    for key in found.iter().filter(|k| !known.iter().any(|(n,)| n == *k)) {
+        new.push(get(pool, key).await?);
+    }
+    Ok(new)
+}
+
+async fn get(pool: &SqlitePool, match_key: &str) -> Result<DetectedSubscription> {
+    sqlx::query_as::<_, DetectedSubscription>(
+        "SELECT * FROM detected_subscriptions WHERE match_key = $1",
+    )
+    .bind(match_key)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| Error::Validation("Subscription not found".to_string()))
+}
+
+/// Subscriptions the user hasn't decided on yet, most confident first.
+pub async fn list(pool: &SqlitePool) -> Result<Vec<DetectedSubscription>> {
+    Ok(sqlx::query_as::<_, DetectedSubscription>(
+        "SELECT * FROM detected_subscriptions WHERE status = 'new'
+         ORDER BY confidence DESC, amount DESC",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Stop proposing a subscription, for as long as it keeps being charged.
+pub async fn dismiss(pool: &SqlitePool, match_key: &str) -> Result<()> {
+    sqlx::query(
+        "UPDATE detected_subscriptions SET status = 'dismissed', updated_at = $1
+         WHERE match_key = $2",
+    )
+    .bind(now())
+    .bind(match_key)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Turn a detected subscription into a recurring expense, reminded of
+/// `remind_days_before` its renewals or a few days if not given.
+pub async fn track(
+    pool: &SqlitePool,
+    user_id: Option<&str>,
+    match_key: &str,
+    remind_days_before: Option<i64>,
+) -> Result<RecurringExpense> {
+    let detected = get(pool, match_key).await?;
+    if detected.status == "tracked" {
+        return Err(Error::Validation(
+            "This subscription is already tracked".to_string(),
+        ));
+    }
+    let cadence = Cadence::parse(&detected.cadence)
+        .ok_or_else(|| Error::Validation(format!("Unknown cadence {}", detected.cadence)))?;
+    let recurring = super::create(
+        pool,
+        user_id,
+        NewRecurringExpense {
+            name: detected.name,
+            amount: detected.amount,
+            category_id: detected.category_id,
+            cadence,
+            next_due_date: detected.next_date,
+            match_key: Some(detected.match_key),
+            remind_days_before: Some(remind_days_before.unwrap_or(DEFAULT_REMIND_DAYS_BEFORE)),
+            calendar_uid: None,
+        },
+    )
+    .await?;
+    sqlx::query(
+        "UPDATE detected_subscriptions SET status = 'tracked', recurring_id = $1, updated_at = $2
+         WHERE match_key = $3",
+    )
+    .bind(&recurring.id)
+    .bind(now())
+    .bind(match_key)
+    .execute(pool)
+    .await?;
+    Ok(recurring)
+}
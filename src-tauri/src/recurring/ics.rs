@@ -0,0 +1,408 @@
+//! Importing bills from an ICS calendar.
+//!
+//! People who already keep "Rent" or "Car insurance" as repeating calendar
+//! events can bring them over instead of typing them in again. Only
+//! recurring events whose rule maps onto one of our cadences are taken.
+//! Calendars have no field for an amount, so it is read from the title or
+//! the description ("Rent 950", "Insurance €45.20"); events without one are
+//! reported back rather than guessed. An alarm on the event sets how early
+//! the reminder comes.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use chrono::{Days, Months, NaiveDate};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use super::{NewRecurringExpense, RecurringExpense};
+use crate::analysis::recurring::{match_key, Cadence};
+use crate::error::{Error, Result};
+
+/// Reminder lead time for events without an alarm.
+const DEFAULT_REMIND_DAYS: i64 = 1;
+
+const CURRENCY_WORDS: &[&str] = &["€", "$", "£", "EUR", "USD", "GBP", "CHF"];
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct SkippedEvent {
+    pub name: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct IcsImport {
+    pub created: Vec<RecurringExpense>,
+    pub skipped: Vec<SkippedEvent>,
+}
+
+#[derive(Default)]
+struct Event {
+    uid: Option<String>,
+    summary: String,
+    description: String,
+    start: Option<NaiveDate>,
+    rrule: Option<String>,
+    /// A changed single occurrence of another event.
+    is_override: bool,
+    cancelled: bool,
+    remind_days: Option<i64>,
+}
+
+struct Rule {
+    cadence: Cadence,
+    count: Option<u32>,
+    until: Option<NaiveDate>,
+}
+
+/// Join continuation lines, which start with a space or tab.
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// "DTSTART;TZID=Europe/Berlin:20240301T090000" into its name and value.
+fn split_property(line: &str) -> Option<(String, &str)> {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ':' if !quoted => {
+                let name = line[..i].split(';').next().unwrap_or_default();
+                return Some((name.to_ascii_uppercase(), &line[i + 1..]));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+/// Dates and date-times alike; the time is irrelevant for a due date.
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()
+}
+
+/// Whole days before the event for triggers like "-P2D", "-P1W" or
+/// "-PT36H", rounded up. Triggers after the start or at a fixed time don't
+/// count.
+fn trigger_days(value: &str) -> Option<i64> {
+    let duration = value.trim().strip_prefix('-')?.strip_prefix('P')?;
+    let (date_part, time_part) = duration.split_once('T').unwrap_or((duration, ""));
+    let mut days = 0;
+    let mut hours = 0;
+    let mut number = String::new();
+    for (c, in_time) in date_part
+        .chars()
+        .map(|c| (c, false))
+        .chain(time_part.chars().map(|c| (c, true)))
+    {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let n: i64 = number.parse().ok()?;
+        number.clear();
+        match (c, in_time) {
+            ('W', false) => days += n * 7,
+            ('D', false) => days += n,
+            ('H', true) => hours += n,
+            ('M' | 'S', true) => {}
+            _ => return None,
+        }
+    }
+    Some(days + (hours + 23) / 24)
+}
+
+fn parse_events(content: &str) -> Vec<Event> {
+    let mut events = Vec::new();
+    let mut current: Option<Event> = None;
+    let mut in_alarm = false;
+    for line in unfold(content) {
+        let Some((name, value)) = split_property(&line) else {
+            continue;
+        };
+        let component = value.trim().to_ascii_uppercase();
+        match (name.as_str(), component.as_str()) {
+            ("BEGIN", "VEVENT") => {
+                current = Some(Event::default());
+                continue;
+            }
+            ("END", "VEVENT") => {
+                events.extend(current.take());
+                continue;
+            }
+            ("BEGIN", "VALARM") => in_alarm = true,
+            ("END", "VALARM") => in_alarm = false,
+            _ => {}
+        }
+        let Some(event) = current.as_mut() else {
+            continue;
+        };
+        if in_alarm {
+            if name == "TRIGGER" {
+                if let Some(days) = trigger_days(value) {
+                    event.remind_days = Some(event.remind_days.map_or(days, |d| d.max(days)));
+                }
+            }
+            continue;
+        }
+        match name.as_str() {
+            "UID" => event.uid = Some(value.trim().to_string()),
+            "SUMMARY" => event.summary = unescape(value).trim().to_string(),
+            "DESCRIPTION" => event.description = unescape(value),
+            "DTSTART" => event.start = parse_date(value),
+            "RRULE" => event.rrule = Some(component),
+            "RECURRENCE-ID" => event.is_override = true,
+            "STATUS" => event.cancelled = component == "CANCELLED",
+            _ => {}
+        }
+    }
+    events
+}
+
+fn parse_rule(rrule: &str) -> std::result::Result<Rule, &'static str> {
+    let mut freq = None;
+    let mut interval: u32 = 1;
+    let mut count = None;
+    let mut until = None;
+    for part in rrule.split(';') {
+        let Some((key, value)) = part.split_once('=') else {
+            continue;
+        };
+        match key {
+            "FREQ" => freq = Some(value),
+            "INTERVAL" => interval = value.parse().map_err(|_| "has an invalid interval")?,
+            "COUNT" => count = value.parse().ok(),
+            "UNTIL" => until = parse_date(value),
+            _ => {}
+        }
+    }
+    let cadence = match (freq, interval) {
+        (Some("WEEKLY"), 1) => Cadence::Weekly,
+        (Some("WEEKLY"), 2) => Cadence::Biweekly,
+        (Some("MONTHLY"), 1) => Cadence::Monthly,
+        (Some("MONTHLY"), 3) => Cadence::Quarterly,
+        (Some("YEARLY"), 1) => Cadence::Yearly,
+        _ => return Err("repeats on a schedule that isn't supported"),
+    };
+    Ok(Rule {
+        cadence,
+        count,
+        until,
+    })
+}
+
+/// The `n`th occurrence, counted from `start` so month ends don't drift.
+fn occurrence(start: NaiveDate, cadence: Cadence, n: u32) -> Option<NaiveDate> {
+    match cadence {
+        Cadence::Weekly => start.checked_add_days(Days::new(7 * u64::from(n))),
+        Cadence::Biweekly => start.checked_add_days(Days::new(14 * u64::from(n))),
+        Cadence::Monthly => start.checked_add_months(Months::new(n)),
+        Cadence::Quarterly => start.checked_add_months(Months::new(3 * n)),
+        Cadence::Yearly => start.checked_add_months(Months::new(12 * n)),
+    }
+}
+
+/// First occurrence on or after `today`, unless the series has ended.
+fn next_occurrence(start: NaiveDate, rule: &Rule, today: NaiveDate) -> Option<NaiveDate> {
+    let mut n = 0;
+    loop {
+        if rule.count.is_some_and(|count| n >= count) {
+            return None;
+        }
+        let date = occurrence(start, rule.cadence, n)?;
+        if rule.until.is_some_and(|until| date > until) {
+            return None;
+        }
+        if date >= today {
+            return Some(date);
+        }
+        n += 1;
+    }
+}
+
+/// "45.20", "45,20", "1.200,00" or "1,200.00" as a number.
+fn parse_money(word: &str) -> Option<f64> {
+    let digits = word.trim_matches(|c: char| !c.is_ascii_digit());
+    if digits.is_empty()
+        || !digits
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == ',')
+    {
+        return None;
+    }
+    let normalized = match (digits.rfind('.'), digits.rfind(',')) {
+        (Some(dot), Some(comma)) if comma > dot => digits.replace('.', "").replace(',', "."),
+        (Some(_), Some(_)) => digits.replace(',', ""),
+        // A lone separator followed by three digits groups thousands.
+        (Some(i), None) | (None, Some(i)) if digits.len() - i == 4 => {
+            digits.replace([',', '.'], "")
+        }
+        _ => digits.replace(',', "."),
+    };
+    normalized.parse().ok().filter(|amount: &f64| *amount > 0.0)
+}
+
+fn is_currency(word: &str) -> bool {
+    CURRENCY_WORDS.contains(&word)
+}
+
+/// The amount in `text` and what is left of the text without it. Amounts
+/// next to a currency win over plain numbers, so "Insurance 2024 €45" is 45.
+fn split_amount(text: &str) -> Option<(f64, String)> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let marked = |i: usize| {
+        let word = words[i];
+        word.contains(['€', '$', '£'])
+            || (i > 0 && is_currency(words[i - 1]))
+            || words.get(i + 1).is_some_and(|w| is_currency(w))
+    };
+    let (index, amount) = words
+        .iter()
+        .enumerate()
+        .filter_map(|(i, word)| Some((i, parse_money(word)?)))
+        .max_by_key(|(i, _)| (marked(*i), std::cmp::Reverse(*i)))?;
+    let rest = words
+        .iter()
+        .enumerate()
+        .filter(|(i, word)| {
+            *i != index && !(is_currency(word) && (*i + 1 == index || *i == index + 1))
+        })
+        .map(|(_, word)| *word)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let rest = rest.trim_matches(|c: char| c.is_whitespace() || "-:,".contains(c));
+    Some((amount, rest.to_string()))
+}
+
+/// Create recurring expenses for the bills in the calendar file at `path`.
+/// Events already imported, or already tracked under the same name, are
+/// skipped.
+pub async fn import(
+    pool: &SqlitePool,
+    user_id: Option<&str>,
+    path: &Path,
+    today: NaiveDate,
+) -> Result<IcsImport> {
+    let content = std::fs::read_to_string(path)?;
+    if !content.contains("BEGIN:VCALENDAR") {
+        return Err(Error::Validation(
+            "The file is not an ICS calendar".to_string(),
+        ));
+    }
+
+    let mut uids: HashSet<String> = sqlx::query_as::<_, (String,)>(
+        "SELECT calendar_uid FROM recurring_expenses
+         WHERE calendar_uid IS NOT NULL AND deleted_at IS NULL",
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|(uid,)| uid)
+    .collect();
+    let mut keys: HashSet<String> = super::list(pool)
+        .await?
+        .into_iter()
+        .filter_map(|expense| expense.match_key)
+        .collect();
+
+    let mut created = Vec::new();
+    let mut skipped = Vec::new();
+    for event in parse_events(&content) {
+        let Some(rrule) = event.rrule.as_deref() else {
+            continue;
+        };
+        if event.is_override || event.cancelled {
+            continue;
+        }
+        let mut skip = |name: &str, reason: &str| {
+            skipped.push(SkippedEvent {
+                name: name.to_string(),
+                reason: reason.to_string(),
+            })
+        };
+        if event.uid.as_ref().is_some_and(|uid| uids.contains(uid)) {
+            skip(&event.summary, "was already imported");
+            continue;
+        }
+        let rule = match parse_rule(rrule) {
+            Ok(rule) => rule,
+            Err(reason) => {
+                skip(&event.summary, reason);
+                continue;
+            }
+        };
+        let Some(start) = event.start else {
+            skip(&event.summary, "has no start date");
+            continue;
+        };
+        let Some(due) = next_occurrence(start, &rule, today) else {
+            skip(&event.summary, "has no upcoming dates");
+            continue;
+        };
+        let from_title = split_amount(&event.summary);
+        let amount = from_title
+            .as_ref()
+            .map(|(amount, _)| *amount)
+            .or_else(|| split_amount(&event.description).map(|(amount, _)| amount));
+        let Some(amount) = amount else {
+            skip(&event.summary, "has no amount in its title or description");
+            continue;
+        };
+        let name = from_title
+            .map(|(_, rest)| rest)
+            .filter(|rest| !rest.is_empty())
+            .unwrap_or_else(|| event.summary.clone());
+        if name.is_empty() {
+            skip(&event.summary, "has no name");
+            continue;
+        }
+        if !keys.insert(match_key(&name)) {
+            skip(&name, "is already a recurring expense");
+            continue;
+        }
+
+        let expense = super::create(
+            pool,
+            user_id,
+            NewRecurringExpense {
+                name,
+                amount,
+                category_id: None,
+                cadence: rule.cadence,
+                next_due_date: due.format("%Y-%m-%d").to_string(),
+                match_key: None,
+                remind_days_before: Some(event.remind_days.unwrap_or(DEFAULT_REMIND_DAYS)),
+                calendar_uid: event.uid.clone(),
+            },
+        )
+        .await?;
+        uids.extend(event.uid);
+        created.push(expense);
+    }
+
+    Ok(IcsImport { created, skipped })
+}
@@ -0,0 +1,170 @@
+//! Recurring expenses: rent, subscriptions and other charges the user expects
+//! every cycle.
+
+pub mod detected;
+pub mod ics;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::AppHandle;
+
+use crate::analysis::recurring::{match_key, Cadence};
+use crate::db::{new_id, now};
+use crate::error::{Error, Result};
+use crate::notify;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct RecurringExpense {
+    pub id: String,
+    pub user_id: Option<String>,
+    pub name: String,
+    pub amount: f64,
+    pub category_id: Option<String>,
+    pub cadence: String,
+    pub next_due_date: String,
+    pub match_key: Option<String>,
+    /// Days before the due date to remind; `None` for no reminder.
+    pub remind_days_before: Option<i64>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub deleted_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct NewRecurringExpense {
+    pub name: String,
+    pub amount: f64,
+    pub category_id: Option<String>,
+    pub cadence: Cadence,
+    pub next_due_date: String,
+    /// Key of the detected pattern this entry was created from, so the
+    /// candidate isn't proposed again after a rename.
+    pub match_key: Option<String>,
+    #[serde(default)]
+    pub remind_days_before: Option<i64>,
+    /// UID of the calendar event this entry was imported from.
+    #[serde(skip)]
+    pub calendar_uid: Option<String>,
+}
+
+pub async fn list(pool: &SqlitePool) -> Result<Vec<RecurringExpense>> {
+    let rows = sqlx::query_as::<_, RecurringExpense>(
+        "SELECT * FROM recurring_expenses WHERE deleted_at IS NULL ORDER BY next_due_date ASC",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+pub async fn create(
+    pool: &SqlitePool,
+    user_id: Option<&str>,
+    input: NewRecurringExpense,
+) -> Result<RecurringExpense> {
+    let name = input.name.trim();
+    if name.is_empty() {
+        return Err(Error::Validation("Name is required".to_string()));
+    }
+    if input.amount <= 0.0 {
+        return Err(Error::Validation("Amount must be positive".to_string()));
+    }
+    if NaiveDate::parse_from_str(&input.next_due_date, "%Y-%m-%d").is_err() {
+        return Err(Error::Validation(
+            "Next due date must be YYYY-MM-DD".to_string(),
+        ));
+    }
+    if input.remind_days_before.is_some_and(|days| days < 0) {
+        return Err(Error::Validation(
+            "Reminders can't be sent after the due date".to_string(),
+        ));
+    }
+
+    let id = new_id();
+    let now = now();
+    let key = input.match_key.unwrap_or_else(|| match_key(name));
+    sqlx::query(
+        "INSERT INTO recurring_expenses (id, user_id, name, amount, category_id, cadence, next_due_date, match_key, remind_days_before, calendar_uid, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+    )
+    .bind(&id)
+    .bind(user_id)
+    .bind(name)
+    .bind(input.amount)
+    .bind(&input.category_id)
+    .bind(input.cadence.as_str())
+    .bind(&input.next_due_date)
+    .bind(&key)
+    .bind(input.remind_days_before)
+    .bind(&input.calendar_uid)
+    .bind(&now)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+
+    Ok(RecurringExpense {
+        id,
+        user_id: user_id.map(str::to_string),
+        name: name.to_string(),
+        amount: input.amount,
+        category_id: input.category_id,
+        cadence: input.cadence.as_str().to_string(),
+        next_due_date: input.next_due_date,
+        match_key: Some(key),
+        remind_days_before: input.remind_days_before,
+        created_at: now.clone(),
+        updated_at: now,
+        deleted_at: None,
+    })
+}
+
+/// First due date on or after `today`. `next_due_date` isn't moved forward
+/// when a charge is paid, so it may lie in the past.
+fn upcoming_due_date(expense: &RecurringExpense, today: NaiveDate) -> Option<NaiveDate> {
+    let cadence = Cadence::parse(&expense.cadence)?;
+    let mut due = NaiveDate::parse_from_str(&expense.next_due_date, "%Y-%m-%d").ok()?;
+    while due < today {
+        let next = cadence.next_after(due);
+        if next == due {
+            return None;
+        }
+        due = next;
+    }
+    Some(due)
+}
+
+/// Remind about entries whose reminder window has started, once per due date.
+pub async fn send_due_reminders(
+    app: &AppHandle,
+    pool: &SqlitePool,
+    today: NaiveDate,
+) -> Result<()> {
+    for expense in list(pool).await? {
+        let Some(days_before) = expense.remind_days_before else {
+            continue;
+        };
+        let Some(due) = upcoming_due_date(&expense, today) else {
+            continue;
+        };
+        let days_left = (due - today).num_days();
+        if days_left > days_before {
+            continue;
+        }
+        let when = match days_left {
+            0 => "today".to_string(),
+            1 => "tomorrow".to_string(),
+            days => format!("in {days} days"),
+        };
+        notify::send_once(
+            app,
+            pool,
+            &format!("bill_due:{}:{due}", expense.id),
+            "bill_due",
+            None,
+            &format!("{} is due {when}", expense.name),
+            &format!("{:.2} due on {}.", expense.amount, due.format("%b %-d")),
+        )
+        .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,249 @@
+//! Invite links: `goaldy://invite/<code>`.
+//!
+//! Opening a link records the code straight away, so the attribution
+//! survives being offline or signed out at the time. Pending codes are then
+//! redeemed with the backend, which validates them and grants the referral
+//! reward to the account; the reward reaches this device as a regular
+//! entitlement refresh. An install is only ever attributed once: codes that
+//! arrive after one was redeemed are kept but ignored.
+
+use std::time::Duration;
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_deep_link::DeepLinkExt;
+use url::Url;
+
+use crate::auth;
+use crate::backend::Backend;
+use crate::connectivity::Connectivity;
+use crate::db::{now, Db};
+use crate::entitlements;
+use crate::error::{Error, Result};
+
+/// Emitted with the `Referral` once its code was redeemed.
+pub const REDEEMED_EVENT: &str = "referral://redeemed";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct Referral {
+    pub code: String,
+    /// "pending", "redeemed", "rejected" or "ignored".
+    pub status: String,
+    pub received_at: String,
+    pub redeemed_at: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Uppercase letters and digits, as the backend issues them.
+fn normalize_code(code: &str) -> Option<String> {
+    let code = code.trim().to_ascii_uppercase();
+    let valid = (4..=32).contains(&code.len()) && code.chars().all(|c| c.is_ascii_alphanumeric());
+    valid.then_some(code)
+}
+
+/// The code in `goaldy://invite/<code>`, if `url` is an invite link.
+fn invite_code(url: &Url) -> Option<String> {
+    if url.scheme() != "goaldy" || url.host_str() != Some("invite") {
+        return None;
+    }
+    normalize_code(url.path().trim_matches('/'))
+}
+
+/// Handle invite links the app is opened with, now and while running.
+pub fn watch(app: &AppHandle) {
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            open_link(&handle, &url);
+        }
+    });
+    match app.deep_link().get_current() {
+        Ok(urls) => {
+            for url in urls.unwrap_or_default() {
+                open_link(app, &url);
+            }
+        }
+        Err(e) => eprintln!("[Referrals] Could not read the launch URL: {e}"),
+    }
+}
+
+fn open_link(app: &AppHandle, url: &Url) {
+    let Some(code) = invite_code(url) else {
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let db = app.state::<Db>();
+        let result = async {
+            record(db.pool(), &code).await?;
+            redeem_pending(&app, db.pool()).await
+        }
+        .await;
+        if let Err(e) = result {
+            eprintln!("[Referrals] Handling invite {code} failed: {e}");
+        }
+    });
+}
+
+/// Remember `code` for redemption. Opening the same link again is a no-op.
+pub async fn record(pool: &SqlitePool, code: &str) -> Result<Referral> {
+    let code = normalize_code(code)
+        .ok_or_else(|| Error::Validation("That is not a valid invite code".to_string()))?;
+    let now = now();
+    sqlx::query(
+        "INSERT OR IGNORE INTO referral_attributions (code, status, received_at, updated_at)
+         VALUES ($1, 'pending', $2, $2)",
+    )
+    .bind(&code)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+    get(pool, &code).await
+}
+
+pub async fn get(pool: &SqlitePool, code: &str) -> Result<Referral> {
+    Ok(sqlx::query_as::<_, Referral>(
+        "SELECT code, status, received_at, redeemed_at, error FROM referral_attributions
+         WHERE code = $1",
+    )
+    .bind(code)
+    .fetch_one(pool)
+    .await?)
+}
+
+pub async fn list(pool: &SqlitePool) -> Result<Vec<Referral>> {
+    Ok(sqlx::query_as::<_, Referral>(
+        "SELECT code, status, received_at, redeemed_at, error FROM referral_attributions
+         ORDER BY received_at DESC",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+async fn set_status(
+    pool: &SqlitePool,
+    code: &str,
+    status: &str,
+    error: Option<&str>,
+) -> Result<()> {
+    let now = now();
+    sqlx::query(
+        "UPDATE referral_attributions
+         SET status = $1, error = $2, updated_at = $3,
+             redeemed_at = CASE WHEN $1 = 'redeemed' THEN $3 ELSE redeemed_at END
+         WHERE code = $4",
+    )
+    .bind(status)
+    .bind(error)
+    .bind(&now)
+    .bind(code)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+enum Outcome {
+    Redeemed,
+    Rejected(String),
+}
+
+/// Blocking.
+fn redeem_remote(url: &str, anon_key: &str, access_token: &str, code: &str) -> Result<Outcome> {
+    let result = ureq::AgentBuilder::new()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .post(&format!("{url}/rest/v1/rpc/redeem_referral"))
+        .set("apikey", anon_key)
+        .set("Authorization", &format!("Bearer {access_token}"))
+        .set("Content-Type", "application/json")
+        .send_string(&serde_json::json!({ "code": code }).to_string());
+    match result {
+        // The function answers `false` for codes it doesn't accept.
+        Ok(response) => match response.into_string()?.trim() {
+            "false" => Ok(Outcome::Rejected("Invite code not accepted".to_string())),
+            _ => Ok(Outcome::Redeemed),
+        },
+        // The backend refuses unknown, used-up and self-referral codes.
+        Err(ureq::Error::Status(code @ 400..=499, response)) if code != 401 && code != 429 => {
+            let body = response.into_string().unwrap_or_default();
+            Ok(Outcome::Rejected(body.trim().to_string()))
+        }
+        Err(ureq::Error::Status(code, response)) => {
+            let body = response.into_string().unwrap_or_default();
+            Err(Error::Remote(format!(
+                "referral check returned {code}: {}",
+                body.trim()
+            )))
+        }
+        Err(ureq::Error::Transport(e)) => {
+            Err(Error::Remote(format!("referral check unreachable: {e}")))
+        }
+    }
+}
+
+/// Redeem pending codes with the backend. Does nothing while offline,
+/// signed out or without a configured backend; the codes wait until then.
+pub async fn redeem_pending(app: &AppHandle, pool: &SqlitePool) -> Result<()> {
+    let pending: Vec<(String,)> = sqlx::query_as(
+        "SELECT code FROM referral_attributions WHERE status = 'pending' ORDER BY received_at",
+    )
+    .fetch_all(pool)
+    .await?;
+    if pending.is_empty() {
+        return Ok(());
+    }
+    let (Some(config), Some(session)) =
+        (app.state::<Backend>().config(), auth::session(pool).await?)
+    else {
+        return Ok(());
+    };
+    if !app.state::<Connectivity>().is_online() {
+        return Ok(());
+    }
+
+    for (code,) in pending {
+        let (already,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM referral_attributions WHERE status = 'redeemed'")
+                .fetch_one(pool)
+                .await?;
+        if already > 0 {
+            set_status(
+                pool,
+                &code,
+                "ignored",
+                Some("This install was already referred"),
+            )
+            .await?;
+            continue;
+        }
+
+        let (url, anon_key, token, remote_code) = (
+            config.url.clone(),
+            config.anon_key.clone(),
+            session.access_token.clone(),
+            code.clone(),
+        );
+        let outcome = tauri::async_runtime::spawn_blocking(move || {
+            redeem_remote(&url, &anon_key, &token, &remote_code)
+        })
+        .await??;
+        match outcome {
+            Outcome::Redeemed => {
+                set_status(pool, &code, "redeemed", None).await?;
+                // The reward is granted server-side; pick it up now.
+                if let Err(e) = entitlements::refresh(app, pool, None).await {
+                    eprintln!("[Referrals] Entitlement refresh after redeeming failed: {e}");
+                }
+                let referral = get(pool, &code).await?;
+                app.emit(REDEEMED_EVENT, &referral)?;
+            }
+            Outcome::Rejected(reason) => {
+                set_status(pool, &code, "rejected", Some(&reason)).await?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,103 @@
+//! Expenses someone else is expected to pay back.
+//!
+//! An expense with a reimbursement status moves from `pending` to either
+//! `received` or `written_off`. Received money no longer counts towards net
+//! spend; written-off amounts stay spent.
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::db::now;
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ReimbursementStatus {
+    Pending,
+    Received,
+    WrittenOff,
+}
+
+impl ReimbursementStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Received => "received",
+            Self::WrittenOff => "written_off",
+        }
+    }
+}
+
+/// Pending reimbursements, in total and per person.
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct Outstanding {
+    pub total: f64,
+    pub count: i64,
+    pub by_person: Vec<OwedBy>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct OwedBy {
+    /// `None` when the user didn't say who owes the money.
+    pub owed_by: Option<String>,
+    pub total: f64,
+    pub count: i64,
+    /// Date of the oldest unpaid expense, to spot debts going stale.
+    pub oldest_date: String,
+}
+
+/// Set or clear (`status: None`) an expense's reimbursement status.
+/// `owed_by` is kept as is when not given.
+pub async fn set_status(
+    pool: &SqlitePool,
+    expense_id: &str,
+    status: Option<ReimbursementStatus>,
+    owed_by: Option<String>,
+) -> Result<()> {
+    let now = now();
+    let reimbursed_at = (status == Some(ReimbursementStatus::Received)).then(|| now.clone());
+    let owed_by = owed_by
+        .map(|o| o.trim().to_string())
+        .filter(|o| !o.is_empty());
+
+    let updated = sqlx::query(
+        "UPDATE expenses SET
+           reimbursement_status = $1,
+           reimbursement_owed_by = CASE WHEN $1 IS NULL THEN NULL ELSE COALESCE($2, reimbursement_owed_by) END,
+           reimbursed_at = $3,
+           updated_at = $4
+         WHERE id = $5 AND deleted_at IS NULL",
+    )
+    .bind(status.map(ReimbursementStatus::as_str))
+    .bind(owed_by)
+    .bind(reimbursed_at)
+    .bind(&now)
+    .bind(expense_id)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if updated == 0 {
+        return Err(Error::Validation("Expense not found".to_string()));
+    }
+    // Paid-back expenses drop out of the net total.
+    crate::category_totals::refresh_expense(pool, expense_id).await
+}
+
+pub async fn outstanding(pool: &SqlitePool) -> Result<Outstanding> {
+    let by_person = sqlx::query_as::<_, OwedBy>(
+        "SELECT reimbursement_owed_by AS owed_by, SUM(amount) AS total, COUNT(*) AS count, MIN(date) AS oldest_date
+         FROM expenses
+         WHERE deleted_at IS NULL AND reimbursement_status = 'pending'
+         GROUP BY reimbursement_owed_by
+         ORDER BY total DESC",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(Outstanding {
+        total: by_person.iter().map(|p| p.total).sum(),
+        count: by_person.iter().map(|p| p.count).sum(),
+        by_person,
+    })
+}
@@ -0,0 +1,157 @@
+//! Full-text search over expenses and feedback notes.
+//!
+//! `search_index` (FTS5) holds the note and category name of every live
+//! expense and the text of every feedback note; triggers keep it in step
+//! with writes from either side. Each word typed is matched as a prefix,
+//! so "groc sup" finds "Grocery supplies", and results come back best
+//! match first, with a note match counting more than a category match.
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::error::Result;
+use crate::expenses::{parse_date, ExpenseWithCategory};
+
+const DEFAULT_LIMIT: i64 = 50;
+
+const MAX_LIMIT: i64 = 500;
+
+/// Words of context around the match in a snippet.
+const SNIPPET_TOKENS: i64 = 12;
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    pub query: String,
+    /// "YYYY-MM-DD", inclusive.
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub limit: Option<i64>,
+    /// Search feedback notes too.
+    pub include_notes: bool,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct ExpenseHit {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub expense: ExpenseWithCategory,
+    /// The matching part of the note, or the note's start for a category
+    /// match.
+    pub snippet: Option<String>,
+    /// Lower is better.
+    pub rank: f64,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct NoteHit {
+    pub id: String,
+    pub content: String,
+    pub created_at: String,
+    pub snippet: Option<String>,
+    pub rank: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, specta::Type)]
+pub struct SearchResults {
+    /// The words searched for, for highlighting.
+    pub terms: Vec<String>,
+    pub expenses: Vec<ExpenseHit>,
+    pub feedback_notes: Vec<NoteHit>,
+}
+
+/// Words of `query` without FTS5 syntax.
+fn terms(query: &str) -> Vec<String> {
+    query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// An FTS5 query matching every term as a prefix.
+fn match_expression(terms: &[String]) -> String {
+    terms
+        .iter()
+        .map(|t| format!("\"{t}\"*"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub async fn search(pool: &SqlitePool, query: SearchQuery) -> Result<SearchResults> {
+    let terms = terms(&query.query);
+    if terms.is_empty() {
+        return Ok(SearchResults::default());
+    }
+    let start = query.start_date.as_deref().map(parse_date).transpose()?;
+    let end = query.end_date.as_deref().map(parse_date).transpose()?;
+    let start = start.map(|d| d.format("%Y-%m-%d").to_string());
+    let end = end.map(|d| d.format("%Y-%m-%d").to_string());
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let expression = match_expression(&terms);
+
+    let expenses = sqlx::query_as::<_, ExpenseHit>(&format!(
+        "WITH hits AS (
+           SELECT record_id,
+                  snippet(search_index, 0, '', '', '…', {SNIPPET_TOKENS}) AS snippet,
+                  bm25(search_index, 4.0, 1.0) AS rank
+           FROM search_index
+           WHERE search_index MATCH $1 AND kind = 'expense'
+             AND ($2 IS NULL OR date >= $2) AND ($3 IS NULL OR date <= $3)
+         ),
+         best AS (
+           SELECT *, ROW_NUMBER() OVER (PARTITION BY record_id ORDER BY rank) AS pick
+           FROM hits
+         )
+         SELECT e.*, c.name AS category_name, c.icon AS category_icon,
+                c.color AS category_color, best.snippet, best.rank
+         FROM best
+         JOIN expenses e ON e.id = best.record_id
+         LEFT JOIN categories c ON c.id = e.category_id
+         WHERE best.pick = 1 AND e.deleted_at IS NULL
+         ORDER BY best.rank, e.date DESC
+         LIMIT $4"
+    ))
+    .bind(&expression)
+    .bind(&start)
+    .bind(&end)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    let feedback_notes = if query.include_notes {
+        sqlx::query_as::<_, NoteHit>(&format!(
+            "WITH hits AS (
+               SELECT record_id,
+                      snippet(search_index, 0, '', '', '…', {SNIPPET_TOKENS}) AS snippet,
+                      bm25(search_index) AS rank
+               FROM search_index
+               WHERE search_index MATCH $1 AND kind = 'feedback_note'
+                 AND ($2 IS NULL OR date >= $2) AND ($3 IS NULL OR date <= $3)
+             ),
+             best AS (
+               SELECT *, ROW_NUMBER() OVER (PARTITION BY record_id ORDER BY rank) AS pick
+               FROM hits
+             )
+             SELECT n.id, n.content, n.created_at, best.snippet, best.rank
+             FROM best
+             JOIN feedback_notes n ON n.id = best.record_id
+             WHERE best.pick = 1 AND n.deleted_at IS NULL
+             ORDER BY best.rank
+             LIMIT $4"
+        ))
+        .bind(&expression)
+        .bind(&start)
+        .bind(&end)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?
+    } else {
+        Vec::new()
+    };
+
+    Ok(SearchResults {
+        terms,
+        expenses,
+        feedback_notes,
+    })
+}
@@ -0,0 +1,58 @@
+//! Secrets in the platform keychain: Keychain on Apple platforms, the
+//! Credential Manager on Windows and the Secret Service on Linux.
+//!
+//! Android has no keychain this crate can reach, and a Linux desktop may
+//! run without a Secret Service. Callers get `Error::Unsupported` there and
+//! decide for themselves what to fall back to.
+
+use crate::error::{Error, Result};
+
+/// Keychain service the entries are filed under, the app's identifier.
+const SERVICE: &str = "app.goaldy.budget";
+
+#[cfg(not(target_os = "android"))]
+fn keychain_error(e: keyring::Error) -> Error {
+    Error::Unsupported(format!("keychain unavailable: {e}"))
+}
+
+/// Blocking. The secret stored as `name`, if any.
+#[cfg(not(target_os = "android"))]
+fn read(name: &str) -> Result<Option<String>> {
+    let entry = keyring::Entry::new(SERVICE, name).map_err(keychain_error)?;
+    match entry.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(keychain_error(e)),
+    }
+}
+
+/// Blocking. Store `secret` as `name`, or remove the entry for `None`.
+#[cfg(not(target_os = "android"))]
+fn write(name: &str, secret: Option<&str>) -> Result<()> {
+    let entry = keyring::Entry::new(SERVICE, name).map_err(keychain_error)?;
+    match secret {
+        Some(secret) => entry.set_password(secret).map_err(keychain_error),
+        None => match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(keychain_error(e)),
+        },
+    }
+}
+
+#[cfg(target_os = "android")]
+fn read(_name: &str) -> Result<Option<String>> {
+    Err(Error::Unsupported("no keychain on Android".to_string()))
+}
+
+#[cfg(target_os = "android")]
+fn write(_name: &str, _secret: Option<&str>) -> Result<()> {
+    Err(Error::Unsupported("no keychain on Android".to_string()))
+}
+
+pub async fn get(name: &'static str) -> Result<Option<String>> {
+    tauri::async_runtime::spawn_blocking(move || read(name)).await?
+}
+
+pub async fn set(name: &'static str, secret: Option<String>) -> Result<()> {
+    tauri::async_runtime::spawn_blocking(move || write(name, secret.as_deref())).await?
+}
@@ -0,0 +1,109 @@
+//! On-device speech-to-text for voice-logged expenses.
+//!
+//! Transcription uses whisper.cpp (via whisper-rs) and is only compiled in
+//! with the `whisper` cargo feature, since it pulls in a native build and a
+//! model file. The model is read from `<app data>/models/ggml-base.bin`.
+
+use std::path::Path;
+
+use crate::error::{Error, Result};
+
+/// Model file looked up inside the app data dir's `models/` folder.
+pub const MODEL_FILE: &str = "ggml-base.bin";
+
+/// Transcribe a WAV recording (any sample rate, mono or stereo, 16-bit PCM).
+#[cfg(feature = "whisper")]
+pub fn transcribe_wav(model_path: &Path, wav: &[u8]) -> Result<String> {
+    use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+    if !model_path.exists() {
+        return Err(Error::Unsupported(format!(
+            "speech model not found at {}",
+            model_path.display()
+        )));
+    }
+
+    let samples = decode_wav(wav)?;
+    let model = model_path.to_string_lossy();
+    let ctx = WhisperContext::new_with_params(&model, WhisperContextParameters::default())
+        .map_err(|e| Error::Unsupported(format!("failed to load speech model: {e}")))?;
+    let mut state = ctx
+        .create_state()
+        .map_err(|e| Error::Unsupported(format!("failed to start speech model: {e}")))?;
+
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    params.set_language(Some("auto"));
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_special(false);
+    params.set_print_timestamps(false);
+    params.set_suppress_blank(true);
+
+    state
+        .full(params, &samples)
+        .map_err(|e| Error::Validation(format!("transcription failed: {e}")))?;
+
+    let segments = state
+        .full_n_segments()
+        .map_err(|e| Error::Validation(format!("transcription failed: {e}")))?;
+    let mut transcript = String::new();
+    for i in 0..segments {
+        if let Ok(text) = state.full_get_segment_text_lossy(i) {
+            transcript.push_str(text.trim());
+            transcript.push(' ');
+        }
+    }
+    Ok(transcript.trim().to_string())
+}
+
+#[cfg(not(feature = "whisper"))]
+pub fn transcribe_wav(_model_path: &Path, _wav: &[u8]) -> Result<String> {
+    Err(Error::Unsupported(
+        "speech recognition is not available in this build".to_string(),
+    ))
+}
+
+/// Whisper expects 16 kHz mono f32 samples.
+#[cfg(feature = "whisper")]
+fn decode_wav(wav: &[u8]) -> Result<Vec<f32>> {
+    const TARGET_RATE: u32 = 16_000;
+
+    let mut reader = hound::WavReader::new(std::io::Cursor::new(wav))
+        .map_err(|e| Error::Validation(format!("invalid WAV recording: {e}")))?;
+    let spec = reader.spec();
+    if spec.sample_format != hound::SampleFormat::Int || spec.bits_per_sample != 16 {
+        return Err(Error::Validation(
+            "recording must be 16-bit PCM WAV".to_string(),
+        ));
+    }
+
+    let interleaved: Vec<i16> = reader
+        .samples::<i16>()
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|e| Error::Validation(format!("invalid WAV recording: {e}")))?;
+    let channels = usize::from(spec.channels.max(1));
+    let mono: Vec<f32> = interleaved
+        .chunks(channels)
+        .map(|frame| {
+            frame.iter().map(|s| f32::from(*s) / 32768.0).sum::<f32>() / frame.len() as f32
+        })
+        .collect();
+
+    if spec.sample_rate == TARGET_RATE {
+        return Ok(mono);
+    }
+
+    // Linear resampling is plenty for speech at these rates.
+    let ratio = f64::from(spec.sample_rate) / f64::from(TARGET_RATE);
+    let len = (mono.len() as f64 / ratio) as usize;
+    Ok((0..len)
+        .map(|i| {
+            let pos = i as f64 * ratio;
+            let idx = pos as usize;
+            let frac = (pos - idx as f64) as f32;
+            let a = mono[idx];
+            let b = *mono.get(idx + 1).unwrap_or(&a);
+            a + (b - a) * frac
+        })
+        .collect())
+}
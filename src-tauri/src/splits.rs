@@ -0,0 +1,398 @@
+//! Expenses shared with other people, and who owes whom because of them.
+//!
+//! A split names the people an expense was shared with and their shares;
+//! the user's own share is what is left. Whoever paid the whole expense is
+//! owed the other shares, so over many expenses each person ends up owed
+//! or owing a net amount. Settling up records money paid back, and the
+//! debts are reduced to the fewest payments that even everyone out.
+//! Throughout, `None` as a person stands for the user.
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::db::{new_id, now};
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum SplitMethod {
+    /// Everyone, the user included, pays the same.
+    Equal,
+    /// Each participant's value is what they owe.
+    Amounts,
+    /// Each participant's value is the percentage of the expense they owe.
+    Percentages,
+}
+
+#[derive(Debug, Clone, Deserialize, specta::Type)]
+pub struct Participant {
+    pub name: String,
+    /// Amount or percentage, per the method; ignored for equal splits.
+    pub value: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize, specta::Type)]
+pub struct NewSplit {
+    /// Who paid the expense; the user if not given.
+    pub paid_by: Option<String>,
+    pub method: SplitMethod,
+    /// The people other than the user.
+    pub participants: Vec<Participant>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct SplitShare {
+    pub participant: String,
+    pub share: f64,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ExpenseSplit {
+    pub expense_id: String,
+    pub amount: f64,
+    pub paid_by: Option<String>,
+    pub shares: Vec<SplitShare>,
+    /// What the other shares leave for the user.
+    pub my_share: f64,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct PersonBalance {
+    pub person: Option<String>,
+    /// Positive when owed money, negative when owing.
+    pub net: f64,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct Debt {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct SettleUp {
+    /// Everyone with something outstanding, most owed first.
+    pub balances: Vec<PersonBalance>,
+    /// The payments that would settle everything.
+    pub debts: Vec<Debt>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct Settlement {
+    pub id: String,
+    pub from_person: Option<String>,
+    pub to_person: Option<String>,
+    pub amount: f64,
+    /// "YYYY-MM-DD".
+    pub date: String,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize, specta::Type)]
+pub struct NewSettlement {
+    pub from_person: Option<String>,
+    pub to_person: Option<String>,
+    pub amount: f64,
+    /// Defaults to today.
+    pub date: Option<NaiveDate>,
+    pub note: Option<String>,
+}
+
+fn cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+fn person(name: Option<String>) -> Option<String> {
+    name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty())
+}
+
+/// The shares `split` gives each participant of an expense of `amount`.
+fn shares(amount: f64, split: &NewSplit) -> Result<Vec<SplitShare>> {
+    let mut shares: Vec<SplitShare> = Vec::new();
+    for participant in &split.participants {
+        let Some(name) = person(Some(participant.name.clone())) else {
+            return Err(Error::Validation(
+                "Every participant needs a name".to_string(),
+            ));
+        };
+        if shares
+            .iter()
+            .any(|s| s.participant.eq_ignore_ascii_case(&name))
+        {
+            return Err(Error::Validation(format!("{name} is in the split twice")));
+        }
+        let share = match split.method {
+            SplitMethod::Equal => amount / (split.participants.len() + 1) as f64,
+            SplitMethod::Amounts => participant.value.unwrap_or(0.0),
+            SplitMethod::Percentages => amount * participant.value.unwrap_or(0.0) / 100.0,
+        };
+        if !share.is_finite() || share < 0.0 {
+            return Err(Error::Validation(format!(
+                "The share of {name} can't be negative"
+            )));
+        }
+        shares.push(SplitShare {
+            participant: name,
+            share: cents(share),
+        });
+    }
+    if shares.is_empty() {
+        return Err(Error::Validation(
+            "Add someone to split the expense with".to_string(),
+        ));
+    }
+    if shares.iter().map(|s| s.share).sum::<f64>() > amount + 0.005 {
+        return Err(Error::Validation(
+            "The shares add up to more than the expense".to_string(),
+        ));
+    }
+    Ok(shares)
+}
+
+/// How an expense is split, if it is.
+pub async fn get(pool: &SqlitePool, expense_id: &str) -> Result<Option<ExpenseSplit>> {
+    let (amount,): (f64,) =
+        sqlx::query_as("SELECT amount FROM expenses WHERE id = $1 AND deleted_at IS NULL")
+            .bind(expense_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| Error::Validation("Expense not found".to_string()))?;
+    let rows: Vec<(String, f64, Option<String>)> = sqlx::query_as(
+        "SELECT participant, share, paid_by FROM expense_splits
+         WHERE expense_id = $1 ORDER BY participant",
+    )
+    .bind(expense_id)
+    .fetch_all(pool)
+    .await?;
+    let Some(paid_by) = rows.first().map(|(_, _, paid_by)| paid_by.clone()) else {
+        return Ok(None);
+    };
+    let shares: Vec<SplitShare> = rows
+        .into_iter()
+        .map(|(participant, share, _)| SplitShare { participant, share })
+        .collect();
+    Ok(Some(ExpenseSplit {
+        expense_id: expense_id.to_string(),
+        amount,
+        paid_by,
+        my_share: cents(amount - shares.iter().map(|s| s.share).sum::<f64>()),
+        shares,
+    }))
+}
+
+/// Split an expense, replacing any split it had.
+pub async fn split(pool: &SqlitePool, expense_id: &str, input: NewSplit) -> Result<ExpenseSplit> {
+    let amount: Option<(f64,)> =
+        sqlx::query_as("SELECT amount FROM expenses WHERE id = $1 AND deleted_at IS NULL")
+            .bind(expense_id)
+            .fetch_optional(pool)
+            .await?;
+    let Some((amount,)) = amount else {
+        return Err(Error::Validation("Expense not found".to_string()));
+    };
+    let shares = shares(amount, &input)?;
+    let paid_by = person(input.paid_by);
+
+    let now = now();
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM expense_splits WHERE expense_id = $1")
+        .bind(expense_id)
+        .execute(&mut *tx)
+        .await?;
+    for share in &shares {
+        sqlx::query(
+            "INSERT INTO expense_splits (id, expense_id, participant, share, paid_by, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(new_id())
+        .bind(expense_id)
+        .bind(&share.participant)
+        .bind(share.share)
+        .bind(&paid_by)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    get(pool, expense_id)
+        .await?
+        .ok_or_else(|| Error::Validation("Expense not found".to_string()))
+}
+
+/// Stop sharing an expense; it is the user's alone again.
+pub async fn unsplit(pool: &SqlitePool, expense_id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM expense_splits WHERE expense_id = $1")
+        .bind(expense_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Everyone the user has split an expense or settled up with, by name.
+pub async fn people(pool: &SqlitePool) -> Result<Vec<String>> {
+    let rows: Vec<(String,)> = sqlx::query_as(
+        "SELECT participant FROM expense_splits
+         UNION SELECT paid_by FROM expense_splits WHERE paid_by IS NOT NULL
+         UNION SELECT from_person FROM split_settlements WHERE from_person IS NOT NULL
+         UNION SELECT to_person FROM split_settlements WHERE to_person IS NOT NULL
+         ORDER BY 1 COLLATE NOCASE",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(|(name,)| name).collect())
+}
+
+pub async fn settlements(pool: &SqlitePool) -> Result<Vec<Settlement>> {
+    Ok(sqlx::query_as::<_, Settlement>(
+        "SELECT * FROM split_settlements ORDER BY date DESC, created_at DESC",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Record that `from_person` paid `to_person` back.
+pub async fn settle(
+    pool: &SqlitePool,
+    input: NewSettlement,
+    today: NaiveDate,
+) -> Result<Settlement> {
+    if !input.amount.is_finite() || input.amount <= 0.0 {
+        return Err(Error::Validation(
+            "Amount must be a positive number".to_string(),
+        ));
+    }
+    let from = person(input.from_person);
+    let to = person(input.to_person);
+    let same = match (&from, &to) {
+        (Some(from), Some(to)) => from.eq_ignore_ascii_case(to),
+        (None, None) => true,
+        _ => false,
+    };
+    if same {
+        return Err(Error::Validation(
+            "A settlement needs two different people".to_string(),
+        ));
+    }
+    Ok(sqlx::query_as::<_, Settlement>(
+        "INSERT INTO split_settlements (id, from_person, to_person, amount, date, note, created_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         RETURNING *",
+    )
+    .bind(new_id())
+    .bind(from)
+    .bind(to)
+    .bind(cents(input.amount))
+    .bind(input.date.unwrap_or(today).format("%Y-%m-%d").to_string())
+    .bind(person(input.note))
+    .bind(now())
+    .fetch_one(pool)
+    .await?)
+}
+
+pub async fn delete_settlement(pool: &SqlitePool, id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM split_settlements WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Net balances keyed by lowercased name, each with the name as first
+/// written.
+#[derive(Default)]
+struct Ledger(BTreeMap<Option<String>, (Option<String>, f64)>);
+
+impl Ledger {
+    fn add(&mut self, who: &Option<String>, amount: f64) {
+        let key = who.as_ref().map(|w| w.to_lowercase());
+        self.0.entry(key).or_insert_with(|| (who.clone(), 0.0)).1 += amount;
+    }
+}
+
+/// What everyone is owed or owes over all split expenses and
+/// settlements, and how to settle it.
+pub async fn settle_up(pool: &SqlitePool) -> Result<SettleUp> {
+    let mut ledger = Ledger::default();
+    let shares: Vec<(String, f64)> = sqlx::query_as(
+        "SELECT s.participant, s.share FROM expense_splits s
+         JOIN expenses e ON e.id = s.expense_id AND e.deleted_at IS NULL",
+    )
+    .fetch_all(pool)
+    .await?;
+    for (participant, share) in shares {
+        ledger.add(&Some(participant), -share);
+    }
+    // The payer is owed the whole expense, the user's share included.
+    let expenses: Vec<(f64, Option<String>, f64)> = sqlx::query_as(
+        "SELECT e.amount, MAX(s.paid_by), SUM(s.share) FROM expense_splits s
+         JOIN expenses e ON e.id = s.expense_id AND e.deleted_at IS NULL
+         GROUP BY s.expense_id",
+    )
+    .fetch_all(pool)
+    .await?;
+    for (amount, paid_by, shared) in expenses {
+        ledger.add(&paid_by, amount);
+        ledger.add(&None, -(amount - shared));
+    }
+
+    for settlement in settlements(pool).await? {
+        ledger.add(&settlement.from_person, settlement.amount);
+        ledger.add(&settlement.to_person, -settlement.amount);
+    }
+
+    let mut balances: Vec<PersonBalance> = ledger
+        .0
+        .into_values()
+        .map(|(person, net)| PersonBalance {
+            person,
+            net: cents(net),
+        })
+        .filter(|b| b.net != 0.0)
+        .collect();
+    balances.sort_by(|a, b| b.net.total_cmp(&a.net));
+    let debts = simplify(&balances);
+    Ok(SettleUp { balances, debts })
+}
+
+/// Pair the largest debtor with the largest creditor until everyone is
+/// even, which needs at most one payment fewer than there are people.
+fn simplify(balances: &[PersonBalance]) -> Vec<Debt> {
+    let mut owed: Vec<(Option<String>, f64)> = balances
+        .iter()
+        .filter(|b| b.net > 0.0)
+        .map(|b| (b.person.clone(), b.net))
+        .collect();
+    let mut owing: Vec<(Option<String>, f64)> = balances
+        .iter()
+        .filter(|b| b.net < 0.0)
+        .map(|b| (b.person.clone(), -b.net))
+        .collect();
+    let mut debts = Vec::new();
+    while let (Some(creditor), Some(debtor)) = (owed.first_mut(), owing.first_mut()) {
+        let amount = cents(creditor.1.min(debtor.1));
+        if amount > 0.0 {
+            debts.push(Debt {
+                from: debtor.0.clone(),
+                to: creditor.0.clone(),
+                amount,
+            });
+        }
+        creditor.1 -= amount;
+        debtor.1 -= amount;
+        if creditor.1 < 0.005 {
+            owed.remove(0);
+        }
+        if debtor.1 < 0.005 {
+            owing.remove(0);
+        }
+        owed.sort_by(|a, b| b.1.total_cmp(&a.1));
+        owing.sort_by(|a, b| b.1.total_cmp(&a.1));
+    }
+    debts
+}
@@ -0,0 +1,83 @@
+//! Retry policy for failed sync pushes.
+//!
+//! Each failure pushes the item's `next_attempt_at` out exponentially, from
+//! `BASE_DELAY` up to `MAX_DELAY`, with half of the delay randomized so
+//! devices that failed together (say, during a server outage) don't all
+//! retry in the same second. After `MAX_ATTEMPTS` the item is dead-lettered.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use rand::Rng;
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::db::timestamp;
+use crate::error::{Error, Result};
+
+/// Same as `MAX_RETRY_ATTEMPTS` in src/lib/sync.ts.
+pub const MAX_ATTEMPTS: i64 = 5;
+
+const BASE_DELAY: Duration = Duration::from_secs(30);
+
+const MAX_DELAY: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// How long to wait after the `attempts`-th failure: between half and all of
+/// `BASE_DELAY * 2^(attempts - 1)`, capped at `MAX_DELAY`.
+pub fn delay(attempts: i64) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 20) as u32;
+    let ceiling = BASE_DELAY.saturating_mul(1 << exponent).min(MAX_DELAY);
+    let jitter = rand::thread_rng().gen_range(0.5..=1.0);
+    ceiling.mul_f64(jitter)
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct FailureOutcome {
+    pub attempts: i64,
+    /// When the item becomes due again; `None` once dead-lettered.
+    pub next_attempt_at: Option<String>,
+    pub dead_lettered: bool,
+}
+
+/// Record a failed push of queue item `id` and schedule its next attempt.
+pub async fn record_failure(pool: &SqlitePool, id: &str, error: &str) -> Result<FailureOutcome> {
+    let attempts: Option<(i64,)> =
+        sqlx::query_as("SELECT COALESCE(attempts, 0) + 1 FROM sync_queue WHERE id = $1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+    let Some((attempts,)) = attempts else {
+        return Err(Error::Validation("Queue item not found".to_string()));
+    };
+
+    let now = Utc::now();
+    let dead_lettered = attempts >= MAX_ATTEMPTS;
+    let next_attempt_at = (!dead_lettered).then(|| {
+        let wait = chrono::Duration::from_std(delay(attempts)).unwrap_or_default();
+        timestamp(now + wait)
+    });
+
+    sqlx::query(
+        "UPDATE sync_queue
+         SET attempts = $1, last_attempt_at = $2, error_message = $3,
+             next_attempt_at = $4, dead_lettered_at = $5
+         WHERE id = $6",
+    )
+    .bind(attempts)
+    .bind(timestamp(now))
+    .bind(error)
+    .bind(&next_attempt_at)
+    .bind(dead_lettered.then(|| timestamp(now)))
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    if dead_lettered {
+        eprintln!("[Sync] Dead-lettered queue item {id} after {attempts} attempts: {error}");
+    }
+    Ok(FailureOutcome {
+        attempts,
+        next_attempt_at,
+        dead_lettered,
+    })
+}
@@ -0,0 +1,109 @@
+//! Folding several queued changes to one record into a single change.
+//!
+//! Every edit queues a copy of the row, so a record edited five times
+//! before the next push would go out five times, and each extra request is
+//! another chance to collide with a remote edit. Before a pass, the live
+//! items of each record are merged: payloads are overlaid oldest to newest,
+//! and the operation follows from the sequence.
+//!
+//! - inserts and updates: an insert if the first was one, else an update;
+//! - ending in a delete after an insert: an insert of the tombstoned row,
+//!   so rows referencing it still find it on the server;
+//! - ending in a delete otherwise: just the delete.
+//!
+//! The merged item keeps the place and the retry state of the oldest one.
+//! Dead-lettered items are left for the user to retry or discard.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sqlx::SqlitePool;
+
+use crate::error::Result;
+
+#[derive(Debug, Clone, Default, Serialize, specta::Type)]
+pub struct CompactReport {
+    /// Records whose items were merged.
+    pub records: usize,
+    /// Items removed by merging.
+    pub removed: usize,
+}
+
+#[derive(sqlx::FromRow)]
+struct Item {
+    id: String,
+    user_id: String,
+    table_name: String,
+    record_id: String,
+    operation: String,
+    payload: String,
+}
+
+impl Item {
+    fn key(&self) -> (&str, &str, &str) {
+        (&self.user_id, &self.table_name, &self.record_id)
+    }
+}
+
+/// The single operation and payload standing for `items`, oldest first.
+/// `None` if a payload isn't a JSON object, which is then left alone.
+fn merge(items: &[Item]) -> Option<(&'static str, Map<String, Value>)> {
+    let mut payload = Map::new();
+    for item in items {
+        match serde_json::from_str(&item.payload) {
+            Ok(Value::Object(fields)) => payload.extend(fields),
+            _ => return None,
+        }
+    }
+    let inserted = items.first()?.operation == "insert";
+    let operation = match (inserted, items.last()?.operation.as_str()) {
+        (true, _) => "insert",
+        (false, "delete") => {
+            let last = serde_json::from_str(&items.last()?.payload).ok()?;
+            return Some(("delete", last));
+        }
+        (false, _) => "update",
+    };
+    Some((operation, payload))
+}
+
+/// Merge the live queue items of every record with more than one.
+pub async fn run(pool: &SqlitePool) -> Result<CompactReport> {
+    let mut report = CompactReport::default();
+    let mut tx = pool.begin().await?;
+    let items = sqlx::query_as::<_, Item>(
+        "SELECT id, user_id, table_name, record_id, operation, payload FROM sync_queue
+         WHERE dead_lettered_at IS NULL
+           AND (user_id, table_name, record_id) IN (
+             SELECT user_id, table_name, record_id FROM sync_queue
+             WHERE dead_lettered_at IS NULL
+             GROUP BY user_id, table_name, record_id
+             HAVING COUNT(*) > 1
+           )
+         ORDER BY user_id, table_name, record_id, created_at, rowid",
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for group in items.chunk_by(|a, b| a.key() == b.key()) {
+        let Some((operation, payload)) = merge(group) else {
+            continue;
+        };
+        let (first, rest) = group.split_first().expect("groups are never empty");
+        sqlx::query("UPDATE sync_queue SET operation = $1, payload = $2 WHERE id = $3")
+            .bind(operation)
+            .bind(Value::Object(payload).to_string())
+            .bind(&first.id)
+            .execute(&mut *tx)
+            .await?;
+        for item in rest {
+            sqlx::query("DELETE FROM sync_queue WHERE id = $1")
+                .bind(&item.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        report.records += 1;
+        report.removed += rest.len();
+    }
+    tx.commit().await?;
+    Ok(report)
+}
@@ -0,0 +1,642 @@
+//! Conflicts between unsynced local edits and remote changes.
+//!
+//! When a pull brings in a row whose record still has edits waiting in
+//! `sync_queue`, the table's `Strategy` decides. By default the remote
+//! version is parked in `sync_conflicts` and the local push held; both
+//! versions are then compared field by field for a side-by-side resolution
+//! screen, and the user's pick is applied. The other strategies settle the
+//! conflict on the spot and record it as already resolved.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use super::{enqueue, row_object, table_columns, Operation};
+use crate::accounts;
+use crate::category_totals::{self, Cell};
+use crate::db::{new_id, now, quote_ident, timestamp};
+use crate::error::{Error, Result};
+
+/// Tables the pull checks for conflicts and records sync events for.
+pub(crate) const CONFLICT_TABLES: &[&str] = &[
+    "expenses",
+    "budgets",
+    "savings_goals",
+    "savings_contributions",
+    "habit_goals",
+    "habit_tracking",
+    "categories",
+];
+
+/// Bookkeeping columns that are never shown as a difference.
+const HIDDEN_FIELDS: &[&str] = &["id", "user_id", "created_at", "updated_at", "synced_at"];
+
+#[derive(sqlx::FromRow)]
+struct ConflictRow {
+    id: String,
+    table_name: String,
+    record_id: String,
+    remote_payload: String,
+    remote_updated_at: String,
+    detected_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct Conflict {
+    pub id: String,
+    pub table_name: String,
+    pub record_id: String,
+    pub detected_at: String,
+    /// `None` if the record no longer exists locally.
+    pub local_updated_at: Option<String>,
+    pub remote_updated_at: String,
+    /// Every user-visible field both sides have, by name.
+    pub fields: Vec<FieldDiff>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct FieldDiff {
+    pub field: String,
+    pub local: Value,
+    pub remote: Value,
+    pub differs: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum Resolution {
+    KeepLocal,
+    KeepRemote,
+}
+
+impl Resolution {
+    fn as_str(self) -> &'static str {
+        match self {
+            Resolution::KeepLocal => "local",
+            Resolution::KeepRemote => "remote",
+        }
+    }
+}
+
+/// How conflicts in a table are settled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// Park the remote version until the user picks one.
+    #[default]
+    Manual,
+    /// Keep whichever side was updated last.
+    LastWriteWins,
+    /// Keep the fields edited here and take every other field from the
+    /// remote version. Only tables with an audit trail know which fields
+    /// were edited; the others fall back to last write wins.
+    FieldMerge,
+}
+
+impl Strategy {
+    fn as_str(self) -> &'static str {
+        match self {
+            Strategy::Manual => "manual",
+            Strategy::LastWriteWins => "last_write_wins",
+            Strategy::FieldMerge => "field_merge",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "last_write_wins" => Strategy::LastWriteWins,
+            "field_merge" => Strategy::FieldMerge,
+            _ => Strategy::Manual,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct TableStrategy {
+    pub table_name: String,
+    pub strategy: Strategy,
+}
+
+/// What became of a pulled row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum PullOutcome {
+    /// No local edits in the way; merge it as usual.
+    Merge,
+    /// Parked for the user to resolve.
+    Held,
+    /// The local edits win and will overwrite it on the next push.
+    KeptLocal,
+    /// Written over the local row; the local edits were dropped.
+    TookRemote,
+    /// Combined with the local edits, which are queued again.
+    Merged,
+}
+
+/// Tables whose `audit_log` triggers record which fields an edit touched.
+const AUDITED_TABLES: &[&str] = &[
+    "expenses",
+    "budgets",
+    "savings_goals",
+    "savings_contributions",
+];
+
+fn check_table(table: &str) -> Result<()> {
+    if CONFLICT_TABLES.contains(&table) {
+        Ok(())
+    } else {
+        Err(Error::Validation(format!("{table} is not synced")))
+    }
+}
+
+fn parse_payload(payload: &str) -> Result<Map<String, Value>> {
+    match serde_json::from_str(payload) {
+        Ok(Value::Object(map)) => Ok(map),
+        _ => Err(Error::Validation(
+            "Stored conflict is unreadable".to_string(),
+        )),
+    }
+}
+
+/// Bring both sides to a comparable shape: SQLite stores booleans as 0/1
+/// and Postgres formats timestamps differently from `toISOString()`.
+pub(super) fn normalize(value: &Value) -> Value {
+    match value {
+        Value::Bool(b) => Value::from(if *b { 1.0 } else { 0.0 }),
+        Value::Number(n) => n.as_f64().map_or(Value::Null, Value::from),
+        Value::String(s) => match DateTime::parse_from_rfc3339(s) {
+            Ok(at) => Value::String(timestamp(at.with_timezone(&Utc))),
+            Err(_) => value.clone(),
+        },
+        _ => value.clone(),
+    }
+}
+
+async fn describe(conn: &mut SqliteConnection, row: ConflictRow) -> Result<Conflict> {
+    let remote = parse_payload(&row.remote_payload)?;
+    let local = row_object(conn, &row.table_name, &row.record_id).await?;
+
+    let mut fields = Vec::new();
+    if let Some(local) = &local {
+        for (field, local_value) in local {
+            if HIDDEN_FIELDS.contains(&field.as_str()) {
+                continue;
+            }
+            // Local-only columns never reach the server.
+            let Some(remote_value) = remote.get(field) else {
+                continue;
+            };
+            fields.push(FieldDiff {
+                field: field.clone(),
+                local: local_value.clone(),
+                remote: remote_value.clone(),
+                differs: normalize(local_value) != normalize(remote_value),
+            });
+        }
+    }
+
+    Ok(Conflict {
+        id: row.id,
+        table_name: row.table_name,
+        record_id: row.record_id,
+        detected_at: row.detected_at,
+        local_updated_at: local
+            .as_ref()
+            .and_then(|l| l.get("updated_at"))
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        remote_updated_at: row.remote_updated_at,
+        fields,
+    })
+}
+
+/// Unresolved conflicts, oldest first.
+pub async fn pending(pool: &SqlitePool) -> Result<Vec<Conflict>> {
+    let mut conn = pool.acquire().await?;
+    let rows = sqlx::query_as::<_, ConflictRow>(
+        "SELECT id, table_name, record_id, remote_payload, remote_updated_at, detected_at
+         FROM sync_conflicts
+         WHERE resolved_at IS NULL
+         ORDER BY detected_at ASC",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut conflicts = Vec::with_capacity(rows.len());
+    for row in rows {
+        if CONFLICT_TABLES.contains(&row.table_name.as_str()) {
+            conflicts.push(describe(&mut conn, row).await?);
+        }
+    }
+    Ok(conflicts)
+}
+
+async fn open_conflict(conn: &mut SqliteConnection, id: &str) -> Result<ConflictRow> {
+    let row = sqlx::query_as::<_, ConflictRow>(
+        "SELECT id, table_name, record_id, remote_payload, remote_updated_at, detected_at
+         FROM sync_conflicts
+         WHERE id = $1 AND resolved_at IS NULL",
+    )
+    .bind(id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| Error::Validation("Conflict not found or already resolved".to_string()))?;
+    check_table(&row.table_name)?;
+    Ok(row)
+}
+
+/// One unresolved conflict with both versions of every field.
+pub async fn get(pool: &SqlitePool, id: &str) -> Result<Conflict> {
+    let mut conn = pool.acquire().await?;
+    let row = open_conflict(&mut conn, id).await?;
+    describe(&mut conn, row).await
+}
+
+/// Apply the user's pick. Keeping the remote version overwrites the local
+/// row and drops its queued edits; keeping the local one re-stamps the
+/// queued edits so they win on the next push. Returns the conflict's table
+/// and record id.
+pub async fn resolve(
+    pool: &SqlitePool,
+    id: &str,
+    resolution: Resolution,
+) -> Result<(String, String)> {
+    let mut tx = pool.begin().await?;
+    let row = open_conflict(&mut tx, id).await?;
+    let table = quote_ident(&row.table_name);
+    let now = now();
+    let mut before = None;
+
+    match resolution {
+        Resolution::KeepRemote => {
+            if row.table_name == "expenses" {
+                before = sqlx::query_as::<_, (String, Option<String>, Option<String>)>(
+                    "SELECT date, category_id, account_id FROM expenses WHERE id = $1",
+                )
+                .bind(&row.record_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+            }
+            apply_remote(
+                &mut tx,
+                &row.table_name,
+                &row.record_id,
+                &row.remote_payload,
+                &now,
+            )
+            .await?;
+            drop_queued(&mut tx, &row.table_name, &row.record_id).await?;
+        }
+        Resolution::KeepLocal => {
+            sqlx::query(&format!("UPDATE {table} SET updated_at = $1 WHERE id = $2"))
+                .bind(&now)
+                .bind(&row.record_id)
+                .execute(&mut *tx)
+                .await?;
+
+            sqlx::query(
+                "UPDATE sync_queue SET payload = json_set(payload, '$.updated_at', $1), attempts = 0
+                 WHERE table_name = $2 AND record_id = $3",
+            )
+            .bind(&now)
+            .bind(&row.table_name)
+            .bind(&row.record_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+
+    sqlx::query("UPDATE sync_conflicts SET resolved_at = $1, resolution = $2 WHERE id = $3")
+        .bind(&now)
+        .bind(resolution.as_str())
+        .bind(&row.id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    if let Some((date, category_id, account_id)) = before {
+        category_totals::refresh(pool, &[Cell { date, category_id }]).await?;
+        category_totals::refresh_expense(pool, &row.record_id).await?;
+        let (remote_account,): (Option<String>,) =
+            sqlx::query_as("SELECT account_id FROM expenses WHERE id = $1")
+                .bind(&row.record_id)
+                .fetch_one(pool)
+                .await?;
+        accounts::refresh(pool, &[account_id, remote_account]).await?;
+    }
+    Ok((row.table_name, row.record_id))
+}
+
+/// Overwrite the local row with the columns `payload` (a JSON object) has.
+async fn apply_remote(
+    conn: &mut SqliteConnection,
+    table: &str,
+    record_id: &str,
+    payload: &str,
+    now: &str,
+) -> Result<()> {
+    let remote = parse_payload(payload)?;
+    let columns = table_columns(conn, table).await?;
+    let mut assignments: Vec<String> = columns
+        .iter()
+        .filter(|c| !matches!(c.as_str(), "id" | "user_id" | "synced_at"))
+        .filter(|c| remote.contains_key(c.as_str()))
+        .map(|c| {
+            format!(
+                "{} = json_extract($1, '$.\"{}\"')",
+                quote_ident(c),
+                c.replace('\'', "''")
+            )
+        })
+        .collect();
+    if columns.iter().any(|c| c == "synced_at") {
+        assignments.push("synced_at = $2".to_string());
+    }
+    if !assignments.is_empty() {
+        sqlx::query(&format!(
+            "UPDATE {} SET {} WHERE id = $3",
+            quote_ident(table),
+            assignments.join(", ")
+        ))
+        .bind(payload)
+        .bind(now)
+        .bind(record_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+async fn drop_queued(conn: &mut SqliteConnection, table: &str, record_id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM sync_queue WHERE table_name = $1 AND record_id = $2")
+        .bind(table)
+        .bind(record_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// The strategy of every table that can conflict.
+pub async fn strategies(pool: &SqlitePool) -> Result<Vec<TableStrategy>> {
+    let rows: Vec<(String, String)> =
+        sqlx::query_as("SELECT table_name, strategy FROM sync_conflict_strategies")
+            .fetch_all(pool)
+            .await?;
+    Ok(CONFLICT_TABLES
+        .iter()
+        .map(|&table| TableStrategy {
+            table_name: table.to_string(),
+            strategy: rows
+                .iter()
+                .find(|(name, _)| name == table)
+                .map_or_else(Strategy::default, |(_, s)| Strategy::parse(s)),
+        })
+        .collect())
+}
+
+pub async fn set_strategy(pool: &SqlitePool, table: &str, strategy: Strategy) -> Result<()> {
+    check_table(table)?;
+    sqlx::query(
+        "INSERT INTO sync_conflict_strategies (table_name, strategy, updated_at) VALUES ($1, $2, $3)
+         ON CONFLICT(table_name) DO UPDATE SET strategy = excluded.strategy, updated_at = excluded.updated_at",
+    )
+    .bind(table)
+    .bind(strategy.as_str())
+    .bind(now())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn strategy_for(conn: &mut SqliteConnection, table: &str) -> Result<Strategy> {
+    let row: Option<(String,)> =
+        sqlx::query_as("SELECT strategy FROM sync_conflict_strategies WHERE table_name = $1")
+            .bind(table)
+            .fetch_optional(&mut *conn)
+            .await?;
+    Ok(row.map_or_else(Strategy::default, |(s,)| Strategy::parse(&s)))
+}
+
+fn parse_time(value: Option<&Value>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(Value::as_str)
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .map(|at| at.with_timezone(&Utc))
+}
+
+/// Fields edited here since the record last went through sync, from the
+/// audit trail.
+async fn edited_fields(
+    conn: &mut SqliteConnection,
+    table: &str,
+    record_id: &str,
+) -> Result<Vec<String>> {
+    let rows: Vec<(Option<String>, Option<String>)> = sqlx::query_as(
+        "SELECT old_values, new_values FROM audit_log
+         WHERE table_name = $1 AND record_id = $2
+           AND changed_at > COALESCE(
+             (SELECT MAX(occurred_at) FROM sync_events WHERE table_name = $1 AND record_id = $2),
+             ''
+           )",
+    )
+    .bind(table)
+    .bind(record_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut fields: Vec<String> = Vec::new();
+    for (old, new) in rows {
+        let old = old.as_deref().and_then(|o| parse_payload(o).ok());
+        let Some(new) = new.as_deref().and_then(|n| parse_payload(n).ok()) else {
+            continue;
+        };
+        for (field, value) in new {
+            let changed = old.as_ref().is_none_or(|o| o.get(&field) != Some(&value));
+            if changed && !fields.contains(&field) {
+                fields.push(field);
+            }
+        }
+    }
+    Ok(fields)
+}
+
+/// Park the remote version, replacing one already waiting for the record.
+async fn hold(
+    conn: &mut SqliteConnection,
+    table: &str,
+    record_id: &str,
+    payload: &str,
+    remote_updated_at: &str,
+    now: &str,
+) -> Result<()> {
+    let updated = sqlx::query(
+        "UPDATE sync_conflicts SET remote_payload = $1, remote_updated_at = $2, detected_at = $3
+         WHERE table_name = $4 AND record_id = $5 AND resolved_at IS NULL",
+    )
+    .bind(payload)
+    .bind(remote_updated_at)
+    .bind(now)
+    .bind(table)
+    .bind(record_id)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+    if updated == 0 {
+        sqlx::query(
+            "INSERT INTO sync_conflicts (id, table_name, record_id, remote_payload, remote_updated_at, detected_at)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(new_id())
+        .bind(table)
+        .bind(record_id)
+        .bind(payload)
+        .bind(remote_updated_at)
+        .bind(now)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Record a conflict settled by its strategy, closing any parked one.
+async fn settle(
+    conn: &mut SqliteConnection,
+    table: &str,
+    record_id: &str,
+    payload: &str,
+    remote_updated_at: &str,
+    resolution: &str,
+    now: &str,
+) -> Result<()> {
+    hold(conn, table, record_id, payload, remote_updated_at, now).await?;
+    sqlx::query(
+        "UPDATE sync_conflicts SET resolved_at = $1, resolution = $2
+         WHERE table_name = $3 AND record_id = $4 AND resolved_at IS NULL",
+    )
+    .bind(now)
+    .bind(resolution)
+    .bind(table)
+    .bind(record_id)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Check a pulled row against unsynced local edits and settle any conflict
+/// with the table's strategy. A remote change older than the first queued
+/// edit is usually our own earlier push and isn't a conflict.
+pub async fn reconcile_pulled(
+    pool: &SqlitePool,
+    table: &str,
+    remote: Map<String, Value>,
+) -> Result<PullOutcome> {
+    check_table(table)?;
+    let Some(record_id) = remote.get("id").and_then(Value::as_str).map(str::to_string) else {
+        return Err(Error::Validation("Pulled row has no id".to_string()));
+    };
+    let remote_updated_at = remote
+        .get("updated_at")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+
+    let mut tx = pool.begin().await?;
+    let (first_edit,): (Option<String>,) = sqlx::query_as(
+        "SELECT MIN(created_at) FROM sync_queue WHERE table_name = $1 AND record_id = $2",
+    )
+    .bind(table)
+    .bind(&record_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    let Some(first_edit) = first_edit else {
+        return Ok(PullOutcome::Merge);
+    };
+    let remote_time = parse_time(remote.get("updated_at"));
+    let first_edit = DateTime::parse_from_rfc3339(&first_edit)
+        .ok()
+        .map(|at| at.with_timezone(&Utc));
+    if remote_time.is_some_and(|at| Some(at) < first_edit) {
+        return Ok(PullOutcome::Merge);
+    }
+
+    let now = now();
+    let payload = Value::Object(remote.clone()).to_string();
+    let local = row_object(&mut tx, table, &record_id).await?;
+    let mut strategy = strategy_for(&mut tx, table).await?;
+    if strategy == Strategy::FieldMerge && !AUDITED_TABLES.contains(&table) {
+        strategy = Strategy::LastWriteWins;
+    }
+
+    let outcome = match (strategy, local) {
+        (Strategy::Manual, _) | (_, None) => {
+            hold(
+                &mut tx,
+                table,
+                &record_id,
+                &payload,
+                &remote_updated_at,
+                &now,
+            )
+            .await?;
+            PullOutcome::Held
+        }
+        (Strategy::LastWriteWins, Some(local)) => {
+            if remote_time > parse_time(local.get("updated_at")) {
+                apply_remote(&mut tx, table, &record_id, &payload, &now).await?;
+                drop_queued(&mut tx, table, &record_id).await?;
+                PullOutcome::TookRemote
+            } else {
+                PullOutcome::KeptLocal
+            }
+        }
+        (Strategy::FieldMerge, Some(local)) => {
+            let mut merged = remote.clone();
+            for field in edited_fields(&mut tx, table, &record_id).await? {
+                if let Some(value) = local.get(&field) {
+                    merged.insert(field, value.clone());
+                }
+            }
+            merged.insert("updated_at".to_string(), Value::String(now.clone()));
+            let merged = Value::Object(merged).to_string();
+            apply_remote(&mut tx, table, &record_id, &merged, &now).await?;
+            drop_queued(&mut tx, table, &record_id).await?;
+            enqueue(&mut tx, table, &record_id, Operation::Update).await?;
+            PullOutcome::Merged
+        }
+    };
+
+    let resolution = match outcome {
+        PullOutcome::KeptLocal => Some("local"),
+        PullOutcome::TookRemote => Some("remote"),
+        PullOutcome::Merged => Some("merged"),
+        PullOutcome::Merge | PullOutcome::Held => None,
+    };
+    if let Some(resolution) = resolution {
+        settle(
+            &mut tx,
+            table,
+            &record_id,
+            &payload,
+            &remote_updated_at,
+            resolution,
+            &now,
+        )
+        .await?;
+    }
+    if matches!(outcome, PullOutcome::TookRemote | PullOutcome::Merged) {
+        // Stamped after the write, so its own audit entries don't count as
+        // local edits next time.
+        sqlx::query(
+            "INSERT INTO sync_events (table_name, record_id, direction, operation, occurred_at)
+             VALUES ($1, $2, 'pull', 'update', $3)",
+        )
+        .bind(table)
+        .bind(&record_id)
+        .bind(crate::db::now())
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(outcome)
+}
@@ -0,0 +1,612 @@
+//! End-to-end encryption of synced rows.
+//!
+//! Each account has one sync key, an age X25519 identity that only the
+//! user's devices hold, in the keychain. While encryption is on, the worker
+//! seals the value columns of every pushed row into `encrypted_payload` and
+//! sends NULL in their place; ids, owners and timestamps stay readable so
+//! RLS and incremental pulls still work. Pulled rows are opened again
+//! before the frontend merges them. Sealing only needs the public half,
+//! kept in `sync_encryption`, so a device still waiting for the key keeps
+//! pushing sealed rows; it just can't read the ones it pulls.
+//!
+//! The key reaches other devices in two ways. Turning encryption on hands
+//! out a recovery code, and the key is uploaded wrapped with it (age
+//! scrypt), so any device that knows the code can unwrap it. Or a new
+//! device files a request with a public key of its own, and a device that
+//! has the key encrypts it to that public key; both show a fingerprint of
+//! the request so the user can check they match.
+//!
+//! Partner mode is left out: its ledger is read by two accounts. So are
+//! rows shared with a household, for the same reason.
+
+use std::time::Duration;
+
+use age::secrecy::{ExposeSecret, SecretString};
+use age::x25519::{Identity, Recipient};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Manager};
+
+use super::{worker, Operation};
+use crate::auth::{self, Session};
+use crate::backend::{Backend, BackendConfig};
+use crate::db::{new_id, now, quote_ident, Db};
+use crate::error::{Error, Result};
+use crate::secrets;
+
+/// Server column holding a row's sealed values.
+pub const SEALED_COLUMN: &str = "encrypted_payload";
+
+const KEY_SECRET: &str = "sync_key";
+
+/// The key of a request this device filed and is waiting on.
+const DEVICE_KEY_SECRET: &str = "sync_device_key";
+
+const RECOVERY_GROUPS: usize = 8;
+const RECOVERY_GROUP_LEN: usize = 5;
+// No 0/O or 1/I, the code is typed in by hand
+const RECOVERY_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+const MISSING_KEY: &str = "This device doesn't have the sync key yet. Enter the recovery code or approve this device from one that has it";
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct SyncEncryptionStatus {
+    pub enabled: bool,
+    /// Whether this device holds the key and can read pulled rows.
+    pub has_key: bool,
+    /// Short digest of the key, the same on every device.
+    pub fingerprint: Option<String>,
+    /// A key request from this device is waiting for approval.
+    pub awaiting_key: bool,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct RecoveryKit {
+    /// Shown once; unwraps the key on a new device.
+    pub recovery_code: String,
+    pub fingerprint: String,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct KeyRequest {
+    pub id: String,
+    pub device_name: String,
+    /// Digest of the requesting device's key, shown on both devices.
+    pub verification: String,
+    pub created_at: String,
+    pub approved: bool,
+}
+
+fn crypto_error(what: &str) -> impl Fn(String) -> Error + '_ {
+    move |e| Error::Validation(format!("failed to {what}: {e}"))
+}
+
+fn fingerprint(recipient: &str) -> String {
+    let digest = Sha256::digest(recipient.as_bytes());
+    digest[..8]
+        .chunks(2)
+        .map(|pair| format!("{:02x}{:02x}", pair[0], pair[1]))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn recovery_code() -> String {
+    (0..RECOVERY_GROUPS)
+        .map(|_| {
+            rand::random::<[u8; RECOVERY_GROUP_LEN]>()
+                .iter()
+                .map(|b| RECOVERY_ALPHABET[*b as usize % RECOVERY_ALPHABET.len()] as char)
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// The code as typed: any case, with or without separators.
+fn normalize_code(code: &str) -> SecretString {
+    SecretString::from(
+        code.chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_uppercase())
+            .collect::<String>(),
+    )
+}
+
+fn parse_identity(secret: &str) -> Result<Identity> {
+    secret
+        .trim()
+        .parse()
+        .map_err(|_| Error::Validation("Not a sync key".to_string()))
+}
+
+fn parse_recipient(recipient: &str) -> Result<Recipient> {
+    recipient
+        .parse()
+        .map_err(|_| Error::Validation("Unreadable sync key".to_string()))
+}
+
+fn encrypt(recipient: &impl age::Recipient, plain: &[u8]) -> Result<String> {
+    let sealed =
+        age::encrypt(recipient, plain).map_err(|e| crypto_error("encrypt")(e.to_string()))?;
+    Ok(BASE64.encode(sealed))
+}
+
+fn decrypt(identity: &impl age::Identity, sealed: &str) -> Result<Vec<u8>> {
+    let sealed = BASE64
+        .decode(sealed)
+        .map_err(|e| crypto_error("decode")(e.to_string()))?;
+    age::decrypt(identity, &sealed).map_err(|e| crypto_error("decrypt")(e.to_string()))
+}
+
+/// Move `columns` of a row about to be pushed into its sealed copy.
+pub(crate) fn seal(
+    recipient: &Recipient,
+    body: &mut Map<String, Value>,
+    columns: &[&str],
+) -> Result<()> {
+    let mut values = Map::new();
+    for &column in columns {
+        if let Some(value) = body.insert(column.to_string(), Value::Null) {
+            values.insert(column.to_string(), value);
+        }
+    }
+    let sealed = encrypt(recipient, Value::Object(values).to_string().as_bytes())?;
+    body.insert(SEALED_COLUMN.to_string(), sealed.into());
+    Ok(())
+}
+
+/// The account's public key while encryption is on.
+pub(crate) async fn recipient(pool: &SqlitePool) -> Result<Option<Recipient>> {
+    let row: Option<(String,)> =
+        sqlx::query_as("SELECT recipient FROM sync_encryption WHERE id = 1")
+            .fetch_optional(pool)
+            .await?;
+    row.map(|(recipient,)| parse_recipient(&recipient))
+        .transpose()
+}
+
+async fn identity() -> Result<Option<Identity>> {
+    secrets::get(KEY_SECRET)
+        .await?
+        .map(|secret| parse_identity(&secret))
+        .transpose()
+}
+
+/// Keep the key on this device, and note the account's public key.
+async fn store_identity(pool: &SqlitePool, identity: &Identity) -> Result<()> {
+    secrets::set(
+        KEY_SECRET,
+        Some(identity.to_string().expose_secret().to_string()),
+    )
+    .await?;
+    sqlx::query(
+        "INSERT INTO sync_encryption (id, recipient, enabled_at) VALUES (1, $1, $2)
+         ON CONFLICT(id) DO UPDATE SET recipient = excluded.recipient",
+    )
+    .bind(identity.to_public().to_string())
+    .bind(now())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn status(pool: &SqlitePool) -> Result<SyncEncryptionStatus> {
+    let recipient = recipient(pool).await?;
+    // Without a keychain there is simply no key here.
+    let identity = identity().await.ok().flatten();
+    let awaiting_key = secrets::get(DEVICE_KEY_SECRET)
+        .await
+        .ok()
+        .flatten()
+        .is_some();
+    let has_key = match (&identity, &recipient) {
+        (Some(identity), Some(recipient)) => {
+            identity.to_public().to_string() == recipient.to_string()
+        }
+        _ => false,
+    };
+    Ok(SyncEncryptionStatus {
+        enabled: recipient.is_some(),
+        has_key,
+        fingerprint: recipient.map(|r| fingerprint(&r.to_string())),
+        awaiting_key: awaiting_key && !has_key,
+    })
+}
+
+/// Blocking PostgREST client for the key tables.
+#[derive(Clone)]
+struct Remote {
+    base_url: String,
+    anon_key: String,
+    access_token: String,
+    user_id: String,
+    agent: ureq::Agent,
+}
+
+fn remote_error(e: ureq::Error) -> Error {
+    match e {
+        ureq::Error::Status(code, response) => {
+            let body = response.into_string().unwrap_or_default();
+            Error::Remote(format!("server returned {code}: {}", body.trim()))
+        }
+        ureq::Error::Transport(e) => Error::Remote(format!("server unreachable: {e}")),
+    }
+}
+
+impl Remote {
+    fn new(config: &BackendConfig, session: &Session) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(Duration::from_secs(10))
+            .timeout(Duration::from_secs(30))
+            .build();
+        Self {
+            base_url: config.url.clone(),
+            anon_key: config.anon_key.clone(),
+            access_token: session.access_token.clone(),
+            user_id: session.user_id.clone(),
+            agent,
+        }
+    }
+
+    fn request(&self, method: &str, table: &str) -> ureq::Request {
+        self.agent
+            .request(method, &format!("{}/rest/v1/{table}", self.base_url))
+            .set("apikey", &self.anon_key)
+            .set("Authorization", &format!("Bearer {}", self.access_token))
+            .set("Content-Type", "application/json")
+            .query("user_id", &format!("eq.{}", self.user_id))
+    }
+
+    fn select(&self, table: &str, filters: &[(&str, String)]) -> Result<Vec<Map<String, Value>>> {
+        let mut request = self.request("GET", table).query("select", "*");
+        for (column, filter) in filters {
+            request = request.query(column, filter);
+        }
+        let response = request.call().map_err(remote_error)?;
+        serde_json::from_reader(response.into_reader())
+            .map_err(|e| Error::Remote(format!("unexpected response: {e}")))
+    }
+
+    fn insert(&self, table: &str, body: Value) -> Result<()> {
+        self.agent
+            .request("POST", &format!("{}/rest/v1/{table}", self.base_url))
+            .set("apikey", &self.anon_key)
+            .set("Authorization", &format!("Bearer {}", self.access_token))
+            .set("Content-Type", "application/json")
+            .set("Prefer", "return=minimal")
+            .send_string(&body.to_string())
+            .map_err(remote_error)?;
+        Ok(())
+    }
+
+    fn update(&self, table: &str, id: &str, body: Value) -> Result<()> {
+        self.request("PATCH", table)
+            .query("id", &format!("eq.{id}"))
+            .set("Prefer", "return=minimal")
+            .send_string(&body.to_string())
+            .map_err(remote_error)?;
+        Ok(())
+    }
+
+    fn delete(&self, table: &str, id: &str) -> Result<()> {
+        self.request("DELETE", table)
+            .query("id", &format!("eq.{id}"))
+            .call()
+            .map_err(remote_error)?;
+        Ok(())
+    }
+}
+
+async fn blocking<T, F>(f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(f).await?
+}
+
+async fn connect(app: &AppHandle) -> Result<Remote> {
+    let config = app
+        .state::<Backend>()
+        .config()
+        .ok_or_else(|| Error::Unsupported("Sync is not set up in this build".to_string()))?;
+    let session = auth::session(app.state::<Db>().pool())
+        .await?
+        .ok_or_else(|| Error::Validation("Sign in to use sync encryption".to_string()))?;
+    Ok(Remote::new(&config, &session))
+}
+
+/// The account's public key and wrapped key on the server, if encryption
+/// is on.
+async fn server_key(remote: &Remote) -> Result<Option<(String, String)>> {
+    let remote = remote.clone();
+    let rows = blocking(move || remote.select("sync_keys", &[])).await?;
+    Ok(rows.into_iter().next().and_then(|row| {
+        let recipient = row.get("recipient")?.as_str()?.to_string();
+        let wrapped = row.get("wrapped_key")?.as_str()?.to_string();
+        Some((recipient, wrapped))
+    }))
+}
+
+/// Queue every row of the sealed tables, so what the server already has in
+/// plaintext is replaced with sealed copies.
+async fn requeue_sealed(pool: &SqlitePool) -> Result<u64> {
+    let mut tx = pool.begin().await?;
+    let mut queued = 0;
+    for table in worker::sealed_tables() {
+        let ids: Vec<(String,)> = sqlx::query_as(&format!("SELECT id FROM {}", quote_ident(table)))
+            .fetch_all(&mut *tx)
+            .await?;
+        for (id,) in ids {
+            super::enqueue(&mut tx, table, &id, Operation::Update).await?;
+            queued += 1;
+        }
+    }
+    tx.commit().await?;
+    Ok(queued)
+}
+
+/// Turn encryption on for the account with a new key, and hand out the
+/// recovery code for it.
+pub async fn enable(app: &AppHandle) -> Result<RecoveryKit> {
+    let db = app.state::<Db>();
+    let pool = db.pool();
+    if recipient(pool).await?.is_some() {
+        return Err(Error::Validation(
+            "Sync encryption is already on".to_string(),
+        ));
+    }
+    let partnered: Option<(String,)> =
+        sqlx::query_as("SELECT id FROM partnership WHERE partner_id IS NOT NULL LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+    if partnered.is_some() {
+        return Err(Error::Validation(
+            "Sync encryption isn't available in partner mode".to_string(),
+        ));
+    }
+    let remote = connect(app).await?;
+    if server_key(&remote).await?.is_some() {
+        return Err(Error::Validation(
+            "Sync encryption is already on for this account. Enter the recovery code or approve this device from another one".to_string(),
+        ));
+    }
+
+    let identity = Identity::generate();
+    let recipient = identity.to_public().to_string();
+    let code = recovery_code();
+    let wrapped = {
+        let secret = identity.to_string();
+        let passphrase = normalize_code(&code);
+        blocking(move || {
+            let wrapper = age::scrypt::Recipient::new(passphrase);
+            encrypt(&wrapper, secret.expose_secret().as_bytes())
+        })
+        .await?
+    };
+    {
+        let remote = remote.clone();
+        let body = json!({
+            "user_id": remote.user_id,
+            "recipient": recipient,
+            "wrapped_key": wrapped,
+        });
+        blocking(move || remote.insert("sync_keys", body)).await?;
+    }
+    store_identity(pool, &identity).await?;
+    requeue_sealed(pool).await?;
+
+    Ok(RecoveryKit {
+        recovery_code: code,
+        fingerprint: fingerprint(&recipient),
+    })
+}
+
+/// The key itself, for the user to keep somewhere safe.
+pub async fn export_key() -> Result<String> {
+    let identity = identity()
+        .await?
+        .ok_or_else(|| Error::Validation(MISSING_KEY.to_string()))?;
+    Ok(identity.to_string().expose_secret().to_string())
+}
+
+/// Keep `identity` if it is the account's key.
+async fn adopt(pool: &SqlitePool, remote: &Remote, identity: &Identity) -> Result<()> {
+    let Some((recipient, _)) = server_key(remote).await? else {
+        return Err(Error::Validation(
+            "Sync encryption isn't on for this account".to_string(),
+        ));
+    };
+    if identity.to_public().to_string() != recipient {
+        return Err(Error::Validation(
+            "This is not the sync key of this account".to_string(),
+        ));
+    }
+    store_identity(pool, identity).await
+}
+
+/// Put back a key exported earlier.
+pub async fn import_key(app: &AppHandle, secret: &str) -> Result<SyncEncryptionStatus> {
+    let identity = parse_identity(secret)?;
+    let db = app.state::<Db>();
+    adopt(db.pool(), &connect(app).await?, &identity).await?;
+    status(db.pool()).await
+}
+
+/// Unwrap the key from the server with the recovery code.
+pub async fn recover(app: &AppHandle, code: &str) -> Result<SyncEncryptionStatus> {
+    let remote = connect(app).await?;
+    let Some((_, wrapped)) = server_key(&remote).await? else {
+        return Err(Error::Validation(
+            "Sync encryption isn't on for this account".to_string(),
+        ));
+    };
+    let passphrase = normalize_code(code);
+    let secret = blocking(move || {
+        let unwrapper = age::scrypt::Identity::new(passphrase);
+        decrypt(&unwrapper, &wrapped)
+            .map_err(|_| Error::Validation("Wrong recovery code".to_string()))
+    })
+    .await?;
+    let identity = parse_identity(&String::from_utf8_lossy(&secret))?;
+    let db = app.state::<Db>();
+    adopt(db.pool(), &remote, &identity).await?;
+    status(db.pool()).await
+}
+
+fn key_request(row: &Map<String, Value>) -> Option<KeyRequest> {
+    let text = |key: &str| row.get(key).and_then(Value::as_str).map(str::to_string);
+    Some(KeyRequest {
+        id: text("id")?,
+        device_name: text("device_name")?,
+        verification: fingerprint(&text("device_recipient")?),
+        created_at: text("created_at").unwrap_or_default(),
+        approved: row.get("wrapped_key").is_some_and(|v| !v.is_null()),
+    })
+}
+
+/// Ask the user's other devices for the key.
+pub async fn request_key(app: &AppHandle, device_name: &str) -> Result<KeyRequest> {
+    let device_name = device_name.trim();
+    if device_name.is_empty() {
+        return Err(Error::Validation("Name this device".to_string()));
+    }
+    let remote = connect(app).await?;
+    if server_key(&remote).await?.is_none() {
+        return Err(Error::Validation(
+            "Sync encryption isn't on for this account".to_string(),
+        ));
+    }
+
+    let device = Identity::generate();
+    secrets::set(
+        DEVICE_KEY_SECRET,
+        Some(device.to_string().expose_secret().to_string()),
+    )
+    .await?;
+    let device_recipient = device.to_public().to_string();
+    let id = new_id();
+    let created_at = now();
+    let body = json!({
+        "id": id,
+        "user_id": remote.user_id,
+        "device_name": device_name,
+        "device_recipient": device_recipient,
+        "created_at": created_at,
+    });
+    blocking(move || remote.insert("sync_key_requests", body)).await?;
+
+    Ok(KeyRequest {
+        id,
+        device_name: device_name.to_string(),
+        verification: fingerprint(&device_recipient),
+        created_at,
+        approved: false,
+    })
+}
+
+/// Requests from other devices still waiting for the key.
+pub async fn key_requests(app: &AppHandle) -> Result<Vec<KeyRequest>> {
+    let remote = connect(app).await?;
+    let rows = blocking(move || {
+        remote.select(
+            "sync_key_requests",
+            &[
+                ("wrapped_key", "is.null".to_string()),
+                ("order", "created_at.asc".to_string()),
+            ],
+        )
+    })
+    .await?;
+    Ok(rows.iter().filter_map(key_request).collect())
+}
+
+/// Give the key to the device that filed request `id`.
+pub async fn approve(app: &AppHandle, id: &str) -> Result<()> {
+    let identity = identity()
+        .await?
+        .ok_or_else(|| Error::Validation(MISSING_KEY.to_string()))?;
+    let remote = connect(app).await?;
+    let rows = {
+        let remote = remote.clone();
+        let filter = vec![("id", format!("eq.{id}"))];
+        blocking(move || remote.select("sync_key_requests", &filter)).await?
+    };
+    let device_recipient = rows
+        .first()
+        .and_then(|row| row.get("device_recipient"))
+        .and_then(Value::as_str)
+        .ok_or_else(|| Error::Validation("Key request not found".to_string()))?;
+    let wrapped = encrypt(
+        &parse_recipient(device_recipient)?,
+        identity.to_string().expose_secret().as_bytes(),
+    )?;
+    let id = id.to_string();
+    let body = json!({ "wrapped_key": wrapped, "approved_at": now() });
+    blocking(move || remote.update("sync_key_requests", &id, body)).await
+}
+
+/// Pick up the key once another device approved this one's request.
+pub async fn receive_key(app: &AppHandle) -> Result<SyncEncryptionStatus> {
+    let db = app.state::<Db>();
+    let pool = db.pool();
+    let device = secrets::get(DEVICE_KEY_SECRET)
+        .await?
+        .map(|secret| parse_identity(&secret))
+        .transpose()?
+        .ok_or_else(|| Error::Validation("No key request is waiting on this device".to_string()))?;
+    let remote = connect(app).await?;
+    let rows = {
+        let remote = remote.clone();
+        let filter = vec![("device_recipient", format!("eq.{}", device.to_public()))];
+        blocking(move || remote.select("sync_key_requests", &filter)).await?
+    };
+    let Some(row) = rows.first() else {
+        return Err(Error::Validation(
+            "The key request was withdrawn; ask again".to_string(),
+        ));
+    };
+    let Some(wrapped) = row.get("wrapped_key").and_then(Value::as_str) else {
+        return status(pool).await;
+    };
+
+    let secret = decrypt(&device, wrapped)?;
+    let identity = parse_identity(&String::from_utf8_lossy(&secret))?;
+    adopt(pool, &remote, &identity).await?;
+    secrets::set(DEVICE_KEY_SECRET, None).await?;
+    if let Some(id) = row.get("id").and_then(Value::as_str).map(str::to_string) {
+        blocking(move || remote.delete("sync_key_requests", &id)).await?;
+    }
+    status(pool).await
+}
+
+/// Open the sealed rows of a pull, so they merge like plaintext ones.
+pub async fn open_rows(rows: Vec<Map<String, Value>>) -> Result<Vec<Map<String, Value>>> {
+    let sealed = |row: &Map<String, Value>| row.get(SEALED_COLUMN).is_some_and(|v| v.is_string());
+    if !rows.iter().any(sealed) {
+        return Ok(rows);
+    }
+    let identity = identity()
+        .await?
+        .ok_or_else(|| Error::Validation(MISSING_KEY.to_string()))?;
+    rows.into_iter()
+        .map(|mut row| {
+            if let Some(Value::String(payload)) = row.remove(SEALED_COLUMN) {
+                let plain = decrypt(&identity, &payload)?;
+                match serde_json::from_slice(&plain) {
+                    Ok(Value::Object(values)) => row.extend(values),
+                    _ => {
+                        return Err(Error::Validation(
+                            "Sealed row is not a JSON object".to_string(),
+                        ))
+                    }
+                }
+            }
+            Ok(row)
+        })
+        .collect()
+}
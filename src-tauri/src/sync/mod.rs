@@ -0,0 +1,148 @@
+//! Rust side of cloud sync.
+//!
+//! Pulling still happens in the TypeScript sync engine (src/lib/sync.ts),
+//! with `realtime` merging remote changes in between pulls; pushing
+//! `sync_queue` is done by `worker` in the app. This module also
+//! works on the bookkeeping in `sync_queue` and `sync_conflicts`, and queues
+//! the rows Rust writes itself so they are pushed like any other edit.
+//! `peer` syncs with paired devices on the local network, without the
+//! server.
+
+pub mod backoff;
+pub mod compact;
+pub mod conflicts;
+pub mod e2ee;
+pub mod peer;
+pub mod policy;
+pub mod queue;
+pub mod realtime;
+pub mod resync;
+pub mod settings;
+pub mod status;
+pub mod worker;
+
+use serde_json::{Map, Value};
+use sqlx::SqliteConnection;
+
+use crate::db::{new_id, now, quote_ident};
+use crate::error::Result;
+
+/// Column names of a local table, in table order.
+pub(crate) async fn table_columns(conn: &mut SqliteConnection, table: &str) -> Result<Vec<String>> {
+    let columns: Vec<(String,)> = sqlx::query_as("SELECT name FROM pragma_table_info($1)")
+        .bind(table)
+        .fetch_all(&mut *conn)
+        .await?;
+    Ok(columns.into_iter().map(|(name,)| name).collect())
+}
+
+/// A local row as a JSON object keyed by column.
+pub(crate) async fn row_object(
+    conn: &mut SqliteConnection,
+    table: &str,
+    record_id: &str,
+) -> Result<Option<Map<String, Value>>> {
+    let columns = table_columns(conn, table).await?;
+    let pairs = columns
+        .iter()
+        .map(|c| format!("'{}', {}", c.replace('\'', "''"), quote_ident(c)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let row: Option<(String,)> = sqlx::query_as(&format!(
+        "SELECT json_object({pairs}) FROM {} WHERE id = $1",
+        quote_ident(table)
+    ))
+    .bind(record_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(row.and_then(|(json,)| match serde_json::from_str(&json) {
+        Ok(Value::Object(map)) => Some(map),
+        _ => None,
+    }))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Insert,
+    Update,
+    /// A soft delete; the payload carries only the tombstone.
+    Delete,
+}
+
+impl Operation {
+    fn as_str(self) -> &'static str {
+        match self {
+            Operation::Insert => "insert",
+            Operation::Update => "update",
+            Operation::Delete => "delete",
+        }
+    }
+}
+
+/// Queue a row written on the Rust side for the next push, with the whole
+/// row as payload like `queueChange()`. Nothing is queued while signed out
+/// or for tables kept out of sync.
+pub async fn enqueue(
+    conn: &mut SqliteConnection,
+    table: &str,
+    record_id: &str,
+    operation: Operation,
+) -> Result<()> {
+    let Some(payload) = row_object(conn, table, record_id).await? else {
+        return Ok(());
+    };
+    enqueue_payload(conn, table, record_id, operation, Value::Object(payload)).await
+}
+
+/// Queue the tombstone of a soft-deleted row, as `queueChange()` does.
+pub async fn enqueue_delete(
+    conn: &mut SqliteConnection,
+    table: &str,
+    record_id: &str,
+    deleted_at: &str,
+) -> Result<()> {
+    let payload = serde_json::json!({
+        "id": record_id,
+        "deleted_at": deleted_at,
+        "updated_at": deleted_at,
+    });
+    enqueue_payload(conn, table, record_id, Operation::Delete, payload).await
+}
+
+/// Queue a change with a payload built by the caller, for rows that aren't
+/// pushed as stored (the single `notification_preferences` row is keyed by
+/// user on the server and has real booleans there) and for deletes.
+pub async fn enqueue_payload(
+    conn: &mut SqliteConnection,
+    table: &str,
+    record_id: &str,
+    operation: Operation,
+    payload: Value,
+) -> Result<()> {
+    let user: Option<(Option<String>,)> =
+        sqlx::query_as("SELECT user_id FROM auth_state WHERE id = 1")
+            .fetch_optional(&mut *conn)
+            .await?;
+    let Some((Some(user_id),)) = user else {
+        return Ok(());
+    };
+    if !settings::is_enabled(conn, table).await? {
+        return Ok(());
+    }
+
+    sqlx::query(
+        "INSERT INTO sync_queue (id, table_name, record_id, operation, payload, user_id, created_at, attempts)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, 0)",
+    )
+    .bind(new_id())
+    .bind(table)
+    .bind(record_id)
+    .bind(operation.as_str())
+    .bind(payload.to_string())
+    .bind(user_id)
+    .bind(now())
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,803 @@
+//! Syncing two devices directly over the local network, for users who
+//! won't keep their data on a server.
+//!
+//! Devices pair once: one shows a QR code with its address and a new random
+//! key, the other scans it and both remember each other under that key.
+//! While peer sync is on, a device serves its changes over HTTP and
+//! announces itself as `_goaldy-sync._tcp` over mDNS so paired devices can
+//! find it after its address changes. Either side can then start an
+//! exchange: each sends the rows it changed since their last exchange, and
+//! the newer version of a row wins on both, as in a pull.
+//!
+//! Every body is sealed with ChaCha20-Poly1305 under the pair's key, with
+//! the sender, path and time as associated data, so nobody else on the
+//! network can read or forge one. Messages older than a few minutes are
+//! refused; replaying a newer one only merges the same rows again.
+//!
+//! The synced tables take part, minus those kept out of sync. Peer sync
+//! works without an account and alongside cloud sync.
+
+use std::io::Read;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use base64::prelude::{Engine, BASE64_URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use qrcode::render::svg;
+use qrcode::QrCode;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::{resync, settings, table_columns, worker};
+use crate::accounts;
+use crate::app_meta;
+use crate::category_totals;
+use crate::db::{now, quote_ident, timestamp, Db};
+use crate::error::{Error, Result};
+use crate::events::{self, DomainEvent};
+
+/// Scheme of the pairing payload encoded in the QR code:
+/// `goaldy-peer://<ip>:<port>#<key>`.
+pub const SCHEME: &str = "goaldy-peer";
+
+/// Emitted on the device that offered pairing once another device paired,
+/// with the new `Peer`.
+pub const PAIRED_EVENT: &str = "peer://paired";
+
+const SERVICE_TYPE: &str = "_goaldy-sync._tcp.local.";
+const DEVICE_NAME_KEY: &str = "peer_device_name";
+const DEVICE_HEADER: &str = "X-Goaldy-Device";
+const TIME_HEADER: &str = "X-Goaldy-Time";
+/// How long a pairing offer stays open if nobody scans it.
+const PAIRING_LIFETIME: Duration = Duration::from_secs(10 * 60);
+/// How far apart the clocks of two devices may be.
+const MAX_SKEW_SECS: i64 = 5 * 60;
+const DISCOVERY_TIME: Duration = Duration::from_secs(3);
+const TIMEOUT: Duration = Duration::from_secs(60);
+/// Largest body either side reads.
+const MAX_BODY: u64 = 64 * 1024 * 1024;
+
+/// The server and announcement while peer sync is on, and the open pairing
+/// offer.
+#[derive(Default)]
+pub struct PeerSync {
+    running: Mutex<Option<Running>>,
+    pairing: Mutex<Option<Pairing>>,
+}
+
+struct Running {
+    server: Arc<tiny_http::Server>,
+    mdns: ServiceDaemon,
+    address: String,
+}
+
+struct Pairing {
+    key: [u8; 32],
+    expires: Instant,
+}
+
+impl PeerSync {
+    fn is_serving(&self, server: &Arc<tiny_http::Server>) -> bool {
+        self.running
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|running| Arc::ptr_eq(&running.server, server))
+    }
+
+    fn address(&self) -> Option<String> {
+        self.running
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|running| running.address.clone())
+    }
+
+    /// Stop serving and announcing; the server thread notices within a
+    /// second.
+    pub fn stop(&self) {
+        if let Some(running) = self.running.lock().unwrap().take() {
+            if let Err(e) = running.mdns.shutdown() {
+                eprintln!("[Peer] Failed to stop mDNS: {e}");
+            }
+        }
+        self.pairing.lock().unwrap().take();
+    }
+
+    /// The key of the open pairing offer, unless it expired.
+    fn pairing_key(&self) -> Option<[u8; 32]> {
+        self.pairing
+            .lock()
+            .unwrap()
+            .as_ref()
+            .filter(|pairing| Instant::now() < pairing.expires)
+            .map(|pairing| pairing.key)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct PeerStatus {
+    /// Whether this device is reachable by paired devices.
+    pub running: bool,
+    pub device_id: String,
+    pub device_name: Option<String>,
+    /// Where this device is served, while running.
+    pub address: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct PairingOffer {
+    /// What the QR code encodes.
+    pub payload: String,
+    /// The QR code as an SVG document.
+    pub qr_svg: String,
+    pub expires_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct Peer {
+    pub id: String,
+    pub name: String,
+    /// Where the device was last seen.
+    pub address: Option<String>,
+    pub paired_at: String,
+    pub last_synced_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct DiscoveredPeer {
+    pub id: String,
+    pub name: String,
+    pub address: String,
+    pub paired: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, specta::Type)]
+pub struct PeerSyncReport {
+    /// Rows sent to the peer.
+    pub sent: usize,
+    /// Rows received from the peer that were newer than ours.
+    pub applied: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Hello {
+    id: String,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Change {
+    table: String,
+    row: Map<String, Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExchangeRequest {
+    /// The responder's `updated_at` up to which the sender has its rows.
+    since: Option<String>,
+    changes: Vec<Change>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExchangeResponse {
+    /// The new `since` for the next exchange.
+    until: String,
+    changes: Vec<Change>,
+}
+
+fn peer_error(e: impl std::fmt::Display) -> Error {
+    Error::Validation(format!("peer sync error: {e}"))
+}
+
+fn unreachable(name: &str) -> Error {
+    Error::Validation(format!(
+        "Could not reach {name}. Are both devices on the same Wi-Fi with peer sync on?"
+    ))
+}
+
+async fn device_id(pool: &SqlitePool) -> Result<String> {
+    app_meta::install_id(pool)
+        .await?
+        .ok_or_else(|| Error::Validation("This installation has no id yet".to_string()))
+}
+
+async fn device_name(pool: &SqlitePool) -> Result<String> {
+    app_meta::get(pool, DEVICE_NAME_KEY)
+        .await?
+        .ok_or_else(|| Error::Validation("Name this device first".to_string()))
+}
+
+fn cipher(key: &[u8; 32]) -> Result<LessSafeKey> {
+    let key = UnboundKey::new(&CHACHA20_POLY1305, key).map_err(peer_error)?;
+    Ok(LessSafeKey::new(key))
+}
+
+fn aad(device: &str, time: i64, path: &str, response: bool) -> String {
+    let direction = if response { "response" } else { "request" };
+    format!("{device}\n{time}\n{path}\n{direction}")
+}
+
+/// Encrypt `message` as the nonce followed by the ciphertext and tag.
+fn seal(key: &[u8; 32], aad: &str, message: &impl Serialize) -> Result<Vec<u8>> {
+    let nonce = rand::random::<[u8; NONCE_LEN]>();
+    let mut sealed = serde_json::to_vec(message).map_err(peer_error)?;
+    cipher(key)?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(aad.as_bytes()),
+            &mut sealed,
+        )
+        .map_err(peer_error)?;
+    Ok([nonce.as_slice(), &sealed].concat())
+}
+
+fn open<T: DeserializeOwned>(key: &[u8; 32], aad: &str, sealed: &[u8]) -> Option<T> {
+    if sealed.len() < NONCE_LEN {
+        return None;
+    }
+    let (nonce, body) = sealed.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).ok()?;
+    let mut body = body.to_vec();
+    let plain = cipher(key)
+        .ok()?
+        .open_in_place(nonce, Aad::from(aad.as_bytes()), &mut body)
+        .ok()?;
+    serde_json::from_slice(plain).ok()
+}
+
+fn decode_key(secret: &str) -> Option<[u8; 32]> {
+    BASE64_URL_SAFE_NO_PAD.decode(secret).ok()?.try_into().ok()
+}
+
+/// The tables peers exchange: the synced ones the user didn't keep out.
+async fn peer_tables(conn: &mut SqliteConnection) -> Result<Vec<&'static str>> {
+    let mut tables = Vec::new();
+    for table in worker::synced_tables().filter(|t| worker::is_remote(t)) {
+        if settings::is_enabled(conn, table).await? {
+            tables.push(table);
+        }
+    }
+    Ok(tables)
+}
+
+/// Rows changed after `since`, parents first. Ownership and server sync
+/// state stay on each device.
+async fn changes_since(pool: &SqlitePool, since: Option<&str>) -> Result<Vec<Change>> {
+    let mut conn = pool.acquire().await?;
+    let mut changes = Vec::new();
+    for table in peer_tables(&mut conn).await? {
+        let columns = table_columns(&mut conn, table).await?;
+        let pairs = columns
+            .iter()
+            .filter(|c| !matches!(c.as_str(), "user_id" | "synced_at"))
+            .map(|c| format!("'{}', {}", c.replace('\'', "''"), quote_ident(c)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        // Only custom categories are synced.
+        let custom = if table == "categories" {
+            " AND is_custom = 1"
+        } else {
+            ""
+        };
+        let rows: Vec<(String,)> = sqlx::query_as(&format!(
+            "SELECT json_object({pairs}) FROM {}
+             WHERE ($1 IS NULL OR updated_at > $1){custom}
+             ORDER BY updated_at",
+            quote_ident(table)
+        ))
+        .bind(since)
+        .fetch_all(&mut *conn)
+        .await?;
+        changes.extend(
+            rows.into_iter()
+                .filter_map(|(json,)| match serde_json::from_str(&json) {
+                    Ok(Value::Object(row)) => Some(Change {
+                        table: table.to_string(),
+                        row,
+                    }),
+                    _ => None,
+                }),
+        );
+    }
+    Ok(changes)
+}
+
+/// Write the rows that are newer than ours. Returns how many were.
+async fn merge(app: &AppHandle, pool: &SqlitePool, changes: Vec<Change>) -> Result<usize> {
+    let parse = |at: Option<&str>| at.and_then(|at| DateTime::parse_from_rfc3339(at).ok());
+    let mut applied = Vec::new();
+    let mut tx = pool.begin().await?;
+    let tables = peer_tables(&mut tx).await?;
+    for Change { table, row } in changes {
+        let Some(table) = tables.iter().copied().find(|t| *t == table) else {
+            continue;
+        };
+        let Some(id) = row.get("id").and_then(Value::as_str) else {
+            continue;
+        };
+        let local: Option<(String,)> = sqlx::query_as(&format!(
+            "SELECT updated_at FROM {} WHERE id = $1",
+            quote_ident(table)
+        ))
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some((local,)) = &local {
+            let theirs = parse(row.get("updated_at").and_then(Value::as_str));
+            if theirs <= parse(Some(local)) {
+                continue;
+            }
+        }
+        resync::download(&mut tx, table, row, None, None).await?;
+        applied.push(table);
+    }
+    tx.commit().await?;
+
+    if applied.contains(&"expenses") {
+        category_totals::rebuild(pool).await?;
+        accounts::recompute(pool).await?;
+    }
+    let count = applied.len();
+    if count > 0 {
+        applied.sort_unstable();
+        applied.dedup();
+        events::publish(
+            app,
+            &DomainEvent::RemoteChangesApplied {
+                tables: applied.into_iter().map(str::to_string).collect(),
+                count,
+            },
+        )?;
+    }
+    Ok(count)
+}
+
+/// Start serving this device to paired devices and announcing it on the
+/// network, under `device_name`. Restarts if it was running.
+pub async fn start(app: &AppHandle, device_name: &str) -> Result<PeerStatus> {
+    let device_name = device_name.trim();
+    if device_name.is_empty() {
+        return Err(Error::Validation("Name this device first".to_string()));
+    }
+    let db = app.state::<Db>();
+    let pool = db.pool();
+    app_meta::set(pool, DEVICE_NAME_KEY, device_name).await?;
+    let id = device_id(pool).await?;
+
+    let peers = app.state::<PeerSync>();
+    peers.stop();
+    let ip = local_ip_address::local_ip()
+        .map_err(|_| Error::Unsupported("Connect to a Wi-Fi network first".to_string()))?;
+    let server = Arc::new(tiny_http::Server::http((ip, 0)).map_err(peer_error)?);
+    let port = server
+        .server_addr()
+        .to_ip()
+        .map(|addr| addr.port())
+        .ok_or_else(|| peer_error("no port"))?;
+
+    let mdns = ServiceDaemon::new().map_err(peer_error)?;
+    // TXT values are capped at 255 bytes.
+    let announced_name: String = device_name.chars().take(60).collect();
+    let properties = [("id", id.as_str()), ("name", announced_name.as_str())];
+    let service = ServiceInfo::new(
+        SERVICE_TYPE,
+        &id,
+        &format!("goaldy-{}.local.", &id[..8.min(id.len())]),
+        ip,
+        port,
+        &properties[..],
+    )
+    .map_err(peer_error)?;
+    mdns.register(service).map_err(peer_error)?;
+
+    let address = format!("{ip}:{port}");
+    *peers.running.lock().unwrap() = Some(Running {
+        server: server.clone(),
+        mdns,
+        address,
+    });
+    let handle = app.clone();
+    std::thread::spawn(move || serve(&handle, &server));
+    status(pool, &peers).await
+}
+
+pub async fn status(pool: &SqlitePool, peers: &PeerSync) -> Result<PeerStatus> {
+    let address = peers.address();
+    Ok(PeerStatus {
+        running: address.is_some(),
+        device_id: device_id(pool).await?,
+        device_name: app_meta::get(pool, DEVICE_NAME_KEY).await?,
+        address,
+    })
+}
+
+fn header(request: &tiny_http::Request, name: &'static str) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv(name))
+        .map(|h| h.value.as_str().to_string())
+}
+
+/// Answer requests until peer sync is stopped.
+fn serve(app: &AppHandle, server: &Arc<tiny_http::Server>) {
+    while app.state::<PeerSync>().is_serving(server) {
+        let mut request = match server.recv_timeout(Duration::from_secs(1)) {
+            Ok(Some(request)) => request,
+            Ok(None) => continue,
+            Err(e) => {
+                eprintln!("[Peer] Server error: {e}");
+                return;
+            }
+        };
+        let (Some(device), Some(time)) = (
+            header(&request, DEVICE_HEADER),
+            header(&request, TIME_HEADER).and_then(|t| t.parse::<i64>().ok()),
+        ) else {
+            let _ = request.respond(tiny_http::Response::empty(400));
+            continue;
+        };
+        if *request.method() != tiny_http::Method::Post
+            || (Utc::now().timestamp() - time).abs() > MAX_SKEW_SECS
+        {
+            let _ = request.respond(tiny_http::Response::empty(400));
+            continue;
+        }
+        let mut body = Vec::new();
+        if request
+            .as_reader()
+            .take(MAX_BODY)
+            .read_to_end(&mut body)
+            .is_err()
+        {
+            let _ = request.respond(tiny_http::Response::empty(400));
+            continue;
+        }
+
+        let path = request.url().to_string();
+        let answer = tauri::async_runtime::block_on(answer(app, &path, &device, time, &body));
+        let response = match answer {
+            Ok(Some(sealed)) => tiny_http::Response::from_data(sealed),
+            Ok(None) => tiny_http::Response::from_data(Vec::new()).with_status_code(403),
+            Err(e) => {
+                eprintln!("[Peer] Failed to answer {path}: {e}");
+                tiny_http::Response::from_data(Vec::new()).with_status_code(500)
+            }
+        };
+        if let Err(e) = request.respond(response) {
+            eprintln!("[Peer] Failed to respond: {e}");
+        }
+    }
+}
+
+/// The sealed answer to a request, or `None` if it wasn't from a paired
+/// device or for an open pairing offer.
+async fn answer(
+    app: &AppHandle,
+    path: &str,
+    device: &str,
+    time: i64,
+    body: &[u8],
+) -> Result<Option<Vec<u8>>> {
+    let db = app.state::<Db>();
+    let pool = db.pool();
+    let request_aad = aad(device, time, path, false);
+    let response_aad = aad(device, time, path, true);
+    match path {
+        "/pair" => {
+            let peers = app.state::<PeerSync>();
+            let Some(key) = peers.pairing_key() else {
+                return Ok(None);
+            };
+            let Some(hello) = open::<Hello>(&key, &request_aad, body) else {
+                return Ok(None);
+            };
+            if hello.id != device {
+                return Ok(None);
+            }
+            // Each offer pairs one device.
+            peers.pairing.lock().unwrap().take();
+            let peer = remember(pool, &hello, &key, None).await?;
+            let _ = app.emit(PAIRED_EVENT, &peer);
+            let reply = Hello {
+                id: device_id(pool).await?,
+                name: device_name(pool).await?,
+            };
+            Ok(Some(seal(&key, &response_aad, &reply)?))
+        }
+        "/exchange" => {
+            let Some(key) = peer_key(pool, device).await? else {
+                return Ok(None);
+            };
+            let Some(request) = open::<ExchangeRequest>(&key, &request_aad, body) else {
+                return Ok(None);
+            };
+            let until = now();
+            let changes = changes_since(pool, request.since.as_deref()).await?;
+            merge(app, pool, request.changes).await?;
+            sqlx::query("UPDATE sync_peers SET last_synced_at = $1 WHERE id = $2")
+                .bind(now())
+                .bind(device)
+                .execute(pool)
+                .await?;
+            let reply = ExchangeResponse { until, changes };
+            Ok(Some(seal(&key, &response_aad, &reply)?))
+        }
+        _ => Ok(None),
+    }
+}
+
+async fn remember(
+    pool: &SqlitePool,
+    hello: &Hello,
+    key: &[u8; 32],
+    address: Option<&str>,
+) -> Result<Peer> {
+    sqlx::query(
+        "INSERT INTO sync_peers (id, name, secret, address, paired_at)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT(id) DO UPDATE SET
+           name = excluded.name,
+           secret = excluded.secret,
+           address = COALESCE(excluded.address, address),
+           sent_until = NULL,
+           received_until = NULL,
+           paired_at = excluded.paired_at",
+    )
+    .bind(&hello.id)
+    .bind(hello.name.trim())
+    .bind(BASE64_URL_SAFE_NO_PAD.encode(key))
+    .bind(address)
+    .bind(now())
+    .execute(pool)
+    .await?;
+    find(pool, &hello.id).await
+}
+
+async fn find(pool: &SqlitePool, id: &str) -> Result<Peer> {
+    sqlx::query_as::<_, Peer>(
+        "SELECT id, name, address, paired_at, last_synced_at FROM sync_peers WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| Error::Validation("That device is not paired".to_string()))
+}
+
+async fn peer_key(pool: &SqlitePool, id: &str) -> Result<Option<[u8; 32]>> {
+    let row: Option<(String,)> = sqlx::query_as("SELECT secret FROM sync_peers WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.and_then(|(secret,)| decode_key(&secret)))
+}
+
+/// Open pairing for the next device that scans the returned QR code.
+/// Replaces any offer still open.
+pub async fn offer_pairing(app: &AppHandle) -> Result<PairingOffer> {
+    let peers = app.state::<PeerSync>();
+    let address = peers
+        .address()
+        .ok_or_else(|| Error::Validation("Turn on peer sync first".to_string()))?;
+    let key = rand::random::<[u8; 32]>();
+    *peers.pairing.lock().unwrap() = Some(Pairing {
+        key,
+        expires: Instant::now() + PAIRING_LIFETIME,
+    });
+
+    let payload = format!(
+        "{SCHEME}://{address}#{}",
+        BASE64_URL_SAFE_NO_PAD.encode(key)
+    );
+    let qr_svg = QrCode::new(payload.as_bytes())
+        .map_err(peer_error)?
+        .render::<svg::Color>()
+        .min_dimensions(256, 256)
+        .build();
+    Ok(PairingOffer {
+        payload,
+        qr_svg,
+        expires_at: timestamp(Utc::now() + PAIRING_LIFETIME),
+    })
+}
+
+/// Split a scanned payload into the address and the pairing key.
+fn parse_payload(payload: &str) -> Result<(String, [u8; 32])> {
+    let invalid = || Error::Validation("Not a Goaldy pairing code".to_string());
+    let rest = payload
+        .trim()
+        .strip_prefix(SCHEME)
+        .and_then(|rest| rest.strip_prefix("://"))
+        .ok_or_else(invalid)?;
+    let (address, key) = rest.split_once('#').ok_or_else(invalid)?;
+    if address.is_empty() {
+        return Err(invalid());
+    }
+    Ok((address.to_string(), decode_key(key).ok_or_else(invalid)?))
+}
+
+/// POST a sealed `message` to a peer and open its sealed answer. `None`
+/// if the peer couldn't be reached or didn't accept the message.
+async fn call<T: DeserializeOwned>(
+    address: &str,
+    path: &str,
+    sender: &str,
+    key: &[u8; 32],
+    message: &impl Serialize,
+) -> Result<Option<T>> {
+    let time = Utc::now().timestamp();
+    let body = seal(key, &aad(sender, time, path, false), message)?;
+    let url = format!("http://{address}{path}");
+    let device = sender.to_string();
+    let reply = tauri::async_runtime::spawn_blocking(move || -> Option<Vec<u8>> {
+        let response = ureq::post(&url)
+            .set(DEVICE_HEADER, &device)
+            .set(TIME_HEADER, &time.to_string())
+            .timeout(TIMEOUT)
+            .send_bytes(&body)
+            .ok()?;
+        let mut reply = Vec::new();
+        response
+            .into_reader()
+            .take(MAX_BODY)
+            .read_to_end(&mut reply)
+            .ok()?;
+        Some(reply)
+    })
+    .await?;
+    Ok(reply.and_then(|reply| open(key, &aad(sender, time, path, true), &reply)))
+}
+
+/// Pair with the device whose pairing code was scanned.
+pub async fn pair(app: &AppHandle, payload: &str) -> Result<Peer> {
+    let (address, key) = parse_payload(payload)?;
+    let db = app.state::<Db>();
+    let pool = db.pool();
+    let hello = Hello {
+        id: device_id(pool).await?,
+        name: device_name(pool).await?,
+    };
+    let reply: Hello = call(&address, "/pair", &hello.id, &key, &hello)
+        .await?
+        .ok_or_else(|| {
+            Error::Validation(
+                "Pairing failed. Show a new code on the other device and scan it again."
+                    .to_string(),
+            )
+        })?;
+    remember(pool, &reply, &key, Some(&address)).await
+}
+
+/// Every paired device, most recently synced first.
+pub async fn list(pool: &SqlitePool) -> Result<Vec<Peer>> {
+    Ok(sqlx::query_as::<_, Peer>(
+        "SELECT id, name, address, paired_at, last_synced_at FROM sync_peers
+         ORDER BY last_synced_at IS NULL, last_synced_at DESC, name",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Forget a paired device. It can no longer sync with this one until the
+/// two pair again.
+pub async fn remove(pool: &SqlitePool, id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM sync_peers WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Look for devices announcing peer sync on the network for a few seconds,
+/// and note where the paired ones are now.
+pub async fn discover(pool: &SqlitePool) -> Result<Vec<DiscoveredPeer>> {
+    let own_id = device_id(pool).await?;
+    let found = tauri::async_runtime::spawn_blocking(move || -> Result<Vec<DiscoveredPeer>> {
+        let mdns = ServiceDaemon::new().map_err(peer_error)?;
+        let events = mdns.browse(SERVICE_TYPE).map_err(peer_error)?;
+        let deadline = Instant::now() + DISCOVERY_TIME;
+        let mut found: Vec<DiscoveredPeer> = Vec::new();
+        while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+            let Ok(event) = events.recv_timeout(left) else {
+                break;
+            };
+            let ServiceEvent::ServiceResolved(info) = event else {
+                continue;
+            };
+            let (Some(id), Some(ip)) = (
+                info.get_property_val_str("id"),
+                // Prefer IPv4, which every LAN routes.
+                info.get_addresses()
+                    .iter()
+                    .copied()
+                    .min_by_key(|ip| !matches!(ip, IpAddr::V4(_))),
+            ) else {
+                continue;
+            };
+            if id == own_id || found.iter().any(|p| p.id == id) {
+                continue;
+            }
+            let address = match ip {
+                IpAddr::V4(ip) => format!("{ip}:{}", info.get_port()),
+                IpAddr::V6(ip) => format!("[{ip}]:{}", info.get_port()),
+            };
+            found.push(DiscoveredPeer {
+                id: id.to_string(),
+                name: info.get_property_val_str("name").unwrap_or(id).to_string(),
+                address,
+                paired: false,
+            });
+        }
+        let _ = mdns.shutdown();
+        Ok(found)
+    })
+    .await??;
+
+    let mut discovered = Vec::with_capacity(found.len());
+    for mut peer in found {
+        peer.paired = sqlx::query("UPDATE sync_peers SET address = $1 WHERE id = $2")
+            .bind(&peer.address)
+            .bind(&peer.id)
+            .execute(pool)
+            .await?
+            .rows_affected()
+            > 0;
+        discovered.push(peer);
+    }
+    Ok(discovered)
+}
+
+/// Exchange changes with a paired device at its last known address.
+pub async fn sync_with(app: &AppHandle, id: &str) -> Result<PeerSyncReport> {
+    let db = app.state::<Db>();
+    let pool = db.pool();
+    let peer = find(pool, id).await?;
+    let address = peer
+        .address
+        .as_deref()
+        .ok_or_else(|| unreachable(&peer.name))?;
+    let key = peer_key(pool, id)
+        .await?
+        .ok_or_else(|| Error::Validation(format!("Pair with {} again", peer.name)))?;
+    let (sent_until, received_until): (Option<String>, Option<String>) =
+        sqlx::query_as("SELECT sent_until, received_until FROM sync_peers WHERE id = $1")
+            .bind(id)
+            .fetch_one(pool)
+            .await?;
+
+    let started = now();
+    let changes = changes_since(pool, sent_until.as_deref()).await?;
+    let sent = changes.len();
+    let request = ExchangeRequest {
+        since: received_until,
+        changes,
+    };
+    let reply: ExchangeResponse = call(
+        address,
+        "/exchange",
+        &device_id(pool).await?,
+        &key,
+        &request,
+    )
+    .await?
+    .ok_or_else(|| unreachable(&peer.name))?;
+    let applied = merge(app, pool, reply.changes).await?;
+    sqlx::query(
+        "UPDATE sync_peers SET sent_until = $1, received_until = $2, last_synced_at = $3
+         WHERE id = $4",
+    )
+    .bind(&started)
+    .bind(&reply.until)
+    .bind(now())
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(PeerSyncReport { sent, applied })
+}
@@ -0,0 +1,51 @@
+//! Which connections sync may use.
+//!
+//! With "sync only on Wi-Fi" on, nothing is pushed, pulled or streamed
+//! while the connection is cellular or metered; changes stay queued until
+//! an unmetered one comes along. Large attachment uploads wait for one
+//! either way (see `attachments::sync`).
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Manager};
+
+use crate::app_meta;
+use crate::connectivity::{Connectivity, NetworkType};
+use crate::db::Db;
+use crate::error::Result;
+
+const WIFI_ONLY_KEY: &str = "sync_wifi_only";
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct SyncPolicy {
+    pub wifi_only: bool,
+    pub network_type: NetworkType,
+    pub metered: bool,
+    /// Whether records sync on the current connection.
+    pub allowed: bool,
+}
+
+pub async fn wifi_only(pool: &SqlitePool) -> Result<bool> {
+    Ok(app_meta::get(pool, WIFI_ONLY_KEY).await?.as_deref() == Some("1"))
+}
+
+pub async fn set_wifi_only(pool: &SqlitePool, wifi_only: bool) -> Result<()> {
+    app_meta::set(pool, WIFI_ONLY_KEY, if wifi_only { "1" } else { "0" }).await
+}
+
+/// The preference and the connection it is applied to.
+pub async fn current(app: &AppHandle) -> Result<SyncPolicy> {
+    let wifi_only = wifi_only(app.state::<Db>().pool()).await?;
+    let connectivity = app.state::<Connectivity>();
+    Ok(SyncPolicy {
+        wifi_only,
+        network_type: connectivity.network_type(),
+        metered: connectivity.is_metered(),
+        allowed: !wifi_only || connectivity.is_unmetered(),
+    })
+}
+
+/// Whether sync may use the current connection.
+pub(crate) async fn allows_sync(app: &AppHandle) -> Result<bool> {
+    Ok(current(app).await?.allowed)
+}
@@ -0,0 +1,184 @@
+//! Inspecting and unsticking `sync_queue` from a debug screen.
+//!
+//! The sync engine dead-letters an item after `backoff::MAX_ATTEMPTS` failed
+//! pushes and skips items whose record has an open conflict. Neither blocks
+//! the rest of the queue, but a dead item never syncs on its own; these
+//! operations let the user retry or drop it.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::SqlitePool;
+
+use crate::db::now;
+use crate::error::{Error, Result};
+
+const DEFAULT_LIMIT: i64 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueItemStatus {
+    /// Not tried yet.
+    Pending,
+    /// Failed before, will be tried again after `next_attempt_at`.
+    Retrying,
+    /// Dead-lettered: out of attempts, only a retry or discard moves it.
+    Failed,
+    /// Waiting for a sync conflict on the record to be resolved.
+    Held,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, specta::Type)]
+pub struct QueueFilter {
+    pub table_name: Option<String>,
+    pub status: Option<QueueItemStatus>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct QueueItem {
+    pub id: String,
+    pub table_name: String,
+    pub record_id: String,
+    pub operation: String,
+    pub payload: Value,
+    pub created_at: String,
+    pub attempts: i64,
+    pub last_attempt_at: Option<String>,
+    pub next_attempt_at: Option<String>,
+    pub error_message: Option<String>,
+    pub status: QueueItemStatus,
+}
+
+#[derive(sqlx::FromRow)]
+struct QueueRow {
+    id: String,
+    table_name: String,
+    record_id: String,
+    operation: String,
+    payload: String,
+    created_at: String,
+    attempts: i64,
+    last_attempt_at: Option<String>,
+    next_attempt_at: Option<String>,
+    dead_lettered_at: Option<String>,
+    error_message: Option<String>,
+    held: bool,
+}
+
+impl From<QueueRow> for QueueItem {
+    fn from(row: QueueRow) -> Self {
+        let status = if row.held {
+            QueueItemStatus::Held
+        } else if row.dead_lettered_at.is_some() {
+            QueueItemStatus::Failed
+        } else if row.attempts > 0 {
+            QueueItemStatus::Retrying
+        } else {
+            QueueItemStatus::Pending
+        };
+        QueueItem {
+            payload: serde_json::from_str(&row.payload).unwrap_or(Value::String(row.payload)),
+            id: row.id,
+            table_name: row.table_name,
+            record_id: row.record_id,
+            operation: row.operation,
+            created_at: row.created_at,
+            attempts: row.attempts,
+            last_attempt_at: row.last_attempt_at,
+            next_attempt_at: row.next_attempt_at,
+            error_message: row.error_message,
+            status,
+        }
+    }
+}
+
+/// Queue items matching `filter`, oldest first.
+pub async fn inspect(pool: &SqlitePool, filter: &QueueFilter) -> Result<Vec<QueueItem>> {
+    let rows = sqlx::query_as::<_, QueueRow>(
+        "SELECT q.id, q.table_name, q.record_id, q.operation, q.payload, q.created_at,
+                COALESCE(q.attempts, 0) AS attempts, q.last_attempt_at, q.next_attempt_at,
+                q.dead_lettered_at, q.error_message,
+                EXISTS (
+                    SELECT 1 FROM sync_conflicts c
+                    WHERE c.table_name = q.table_name
+                      AND c.record_id = q.record_id
+                      AND c.resolved_at IS NULL
+                ) AS held
+         FROM sync_queue q
+         WHERE ($1 IS NULL OR q.table_name = $1)
+         ORDER BY q.created_at ASC",
+    )
+    .bind(filter.table_name.as_deref())
+    .fetch_all(pool)
+    .await?;
+
+    let limit = filter.limit.unwrap_or(DEFAULT_LIMIT).max(0) as usize;
+    Ok(rows
+        .into_iter()
+        .map(QueueItem::from)
+        .filter(|item| filter.status.is_none_or(|status| item.status == status))
+        .take(limit)
+        .collect())
+}
+
+/// Reset an item's attempts so the next sync pushes it again.
+pub async fn retry_item(pool: &SqlitePool, id: &str) -> Result<()> {
+    let result = sqlx::query(
+        "UPDATE sync_queue
+         SET attempts = 0, error_message = NULL, last_attempt_at = NULL,
+             next_attempt_at = NULL, dead_lettered_at = NULL
+         WHERE id = $1",
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(Error::Validation("Queue item not found".to_string()));
+    }
+    Ok(())
+}
+
+/// Give every dead-lettered item another go. Returns how many.
+pub async fn retry_all_failed(pool: &SqlitePool) -> Result<u64> {
+    let result = sqlx::query(
+        "UPDATE sync_queue
+         SET attempts = 0, error_message = NULL, last_attempt_at = NULL,
+             next_attempt_at = NULL, dead_lettered_at = NULL
+         WHERE dead_lettered_at IS NOT NULL",
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Drop an item for good. The local row keeps its state; the server simply
+/// never hears about this change.
+pub async fn discard_item(pool: &SqlitePool, id: &str) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    let item: Option<(String, String, String)> =
+        sqlx::query_as("SELECT table_name, record_id, operation FROM sync_queue WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    let Some((table_name, record_id, operation)) = item else {
+        return Err(Error::Validation("Queue item not found".to_string()));
+    };
+
+    sqlx::query("DELETE FROM sync_queue WHERE id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    // Keep a trace in the record's change timeline.
+    sqlx::query(
+        "INSERT INTO sync_events (table_name, record_id, direction, operation, occurred_at)
+         VALUES ($1, $2, 'discard', $3, $4)",
+    )
+    .bind(&table_name)
+    .bind(&record_id)
+    .bind(&operation)
+    .bind(now())
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
@@ -0,0 +1,325 @@
+//! Remote changes as they happen, over Supabase Realtime.
+//!
+//! Pulls run on a timer, so an edit on one device used to show on another
+//! only at its next pull. This client joins a Realtime channel for the
+//! Postgres changes of the synced tables belonging to the signed-in user
+//! (and the shared ledger in partner mode), merges each changed row the
+//! way a pull does, and publishes `sync:applied_remote_changes` so open
+//! screens refresh. Conflicting local edits go through `conflicts` first.
+//!
+//! The connection is dropped and made again whenever the session changes,
+//! and after errors with a growing delay. It is closed while the sync
+//! policy rules out the current connection. Periodic pulls keep running and
+//! catch whatever arrived while it was down.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use chrono::DateTime;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Map, Value};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio_tungstenite::tungstenite::Message;
+
+use super::conflicts::{self, PullOutcome, CONFLICT_TABLES};
+use super::worker::{self, TABLES};
+use super::{e2ee, policy, resync, settings};
+use crate::accounts;
+use crate::auth::{self, Session};
+use crate::backend::{Backend, BackendConfig};
+use crate::category_totals::{self, Cell};
+use crate::connectivity::Connectivity;
+use crate::db::{now, quote_ident, Db};
+use crate::error::{Error, Result};
+use crate::events::{self, DomainEvent};
+
+/// Emitted with `true` once the channel is joined and `false` when it drops.
+pub const STATUS_EVENT: &str = "sync://realtime";
+
+const TOPIC: &str = "realtime:goaldy-sync";
+/// Realtime closes connections that stay quiet for longer than a minute.
+const HEARTBEAT: Duration = Duration::from_secs(25);
+const MIN_DELAY: Duration = Duration::from_secs(5);
+const MAX_DELAY: Duration = Duration::from_secs(300);
+
+static CONNECTED: AtomicBool = AtomicBool::new(false);
+
+/// Whether remote changes are currently streaming in.
+pub fn is_connected() -> bool {
+    CONNECTED.load(Ordering::Acquire)
+}
+
+fn set_connected(app: &AppHandle, connected: bool) {
+    if CONNECTED.swap(connected, Ordering::AcqRel) != connected {
+        let _ = app.emit(STATUS_EVENT, connected);
+    }
+}
+
+/// Keep a Realtime connection for the life of the app.
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut delay = MIN_DELAY;
+        loop {
+            match listen(&app).await {
+                Ok(()) => delay = MIN_DELAY,
+                Err(e) => {
+                    eprintln!("[Sync] Realtime disconnected: {e}");
+                    delay = (delay * 2).min(MAX_DELAY);
+                }
+            }
+            set_connected(&app, false);
+            tokio::time::sleep(delay).await;
+        }
+    });
+}
+
+fn socket_url(config: &BackendConfig) -> String {
+    let base = config
+        .url
+        .replacen("https://", "wss://", 1)
+        .replacen("http://", "ws://", 1);
+    format!(
+        "{base}/realtime/v1/websocket?apikey={}&vsn=1.0.0",
+        config.anon_key
+    )
+}
+
+/// The channel join asking for changes to every synced table.
+async fn join(pool: &SqlitePool, session: &Session) -> Result<Value> {
+    let ledger_owner = worker::ledger_owner(pool, &session.user_id).await?;
+    let mut conn = pool.acquire().await?;
+    let mut changes = Vec::new();
+    for table in TABLES {
+        if !settings::is_enabled(&mut conn, table.name).await? {
+            continue;
+        }
+        let filter = if table.ledger && ledger_owner != session.user_id {
+            format!("user_id=in.({},{ledger_owner})", session.user_id)
+        } else {
+            format!("user_id=eq.{}", session.user_id)
+        };
+        changes.push(json!({
+            "event": "*",
+            "schema": "public",
+            "table": table.name,
+            "filter": filter,
+        }));
+    }
+    Ok(json!({
+        "topic": TOPIC,
+        "event": "phx_join",
+        "payload": {
+            "config": { "postgres_changes": changes },
+            "access_token": session.access_token,
+        },
+        "ref": "1",
+    }))
+}
+
+/// Connect and apply changes until the connection drops or the session
+/// changes. Returns right away while there is nothing to connect to.
+async fn listen(app: &AppHandle) -> Result<()> {
+    let Some(config) = app.state::<Backend>().config() else {
+        return Ok(());
+    };
+    if !app.state::<Connectivity>().is_online() || !policy::allows_sync(app).await? {
+        return Ok(());
+    }
+    let db = app.state::<Db>();
+    let pool = db.pool();
+    let Some(session) = auth::session(pool).await? else {
+        return Ok(());
+    };
+
+    let (socket, _) = tokio_tungstenite::connect_async(socket_url(&config))
+        .await
+        .map_err(|e| Error::Remote(format!("realtime unreachable: {e}")))?;
+    let (mut sink, mut stream) = socket.split();
+    let send_error = |e| Error::Remote(format!("realtime send failed: {e}"));
+    sink.send(Message::text(join(pool, &session).await?.to_string()))
+        .await
+        .map_err(send_error)?;
+
+    let mut heartbeat = tokio::time::interval(HEARTBEAT);
+    let mut sent: u64 = 1;
+    loop {
+        tokio::select! {
+            _ = heartbeat.tick() => {
+                let current = auth::session(pool).await?;
+                if current.is_none_or(|s| s.access_token != session.access_token)
+                    || !policy::allows_sync(app).await?
+                {
+                    return Ok(());
+                }
+                sent += 1;
+                let beat = json!({
+                    "topic": "phoenix",
+                    "event": "heartbeat",
+                    "payload": {},
+                    "ref": sent.to_string(),
+                });
+                sink.send(Message::text(beat.to_string())).await.map_err(send_error)?;
+            }
+            message = stream.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => return Ok(()),
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(Error::Remote(format!("realtime failed: {e}"))),
+                };
+                let Ok(message) = serde_json::from_str::<Value>(&text) else {
+                    continue;
+                };
+                handle(app, pool, &session, &message).await?;
+            }
+        }
+    }
+}
+
+async fn handle(
+    app: &AppHandle,
+    pool: &SqlitePool,
+    session: &Session,
+    message: &Value,
+) -> Result<()> {
+    let payload = &message["payload"];
+    match message["event"].as_str() {
+        Some("phx_reply") if message["ref"] == "1" => {
+            if payload["status"] == "ok" {
+                set_connected(app, true);
+            } else {
+                return Err(Error::Remote(format!(
+                    "realtime refused the channel: {}",
+                    payload["response"]
+                )));
+            }
+        }
+        Some("phx_error") | Some("phx_close") => {
+            return Err(Error::Remote("realtime closed the channel".to_string()));
+        }
+        Some("postgres_changes") => {
+            let data = &payload["data"];
+            // Deletes are soft and arrive as updates; hard deletes only
+            // purge old tombstones.
+            if !matches!(data["type"].as_str(), Some("INSERT" | "UPDATE")) {
+                return Ok(());
+            }
+            let (Some(table), Some(Value::Object(record))) =
+                (data["table"].as_str(), data.get("record"))
+            else {
+                return Ok(());
+            };
+            if let Err(e) = apply(app, pool, session, table, record.clone()).await {
+                eprintln!("[Sync] Failed to apply realtime change to {table}: {e}");
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// `updated_at`, then an expense's date, category and account.
+type Local = (String, Option<String>, Option<String>, Option<String>);
+
+/// Merge a changed row as a pull would: newer rows win, older ones are the
+/// echo of our own pushes.
+async fn apply(
+    app: &AppHandle,
+    pool: &SqlitePool,
+    session: &Session,
+    table: &str,
+    record: Map<String, Value>,
+) -> Result<()> {
+    let Some(table) = TABLES.iter().map(|t| t.name).find(|&t| t == table) else {
+        return Ok(());
+    };
+    let mut conn = pool.acquire().await?;
+    if !settings::is_enabled(&mut conn, table).await? {
+        return Ok(());
+    }
+    let Some(row) = e2ee::open_rows(vec![record]).await?.pop() else {
+        return Ok(());
+    };
+    let Some(id) = row.get("id").and_then(Value::as_str).map(str::to_string) else {
+        return Ok(());
+    };
+
+    if CONFLICT_TABLES.contains(&table) {
+        drop(conn);
+        match conflicts::reconcile_pulled(pool, table, row.clone()).await? {
+            PullOutcome::Merge => {}
+            PullOutcome::Held => return Ok(()),
+            PullOutcome::KeptLocal | PullOutcome::TookRemote | PullOutcome::Merged => {
+                return applied(app, table);
+            }
+        }
+        conn = pool.acquire().await?;
+    }
+
+    // The expense's old cell and account, to refresh their totals too.
+    let sql = if table == "expenses" {
+        "SELECT updated_at, date, category_id, account_id FROM expenses WHERE id = $1".to_string()
+    } else {
+        format!(
+            "SELECT updated_at, NULL, NULL, NULL FROM {} WHERE id = $1",
+            quote_ident(table)
+        )
+    };
+    let local: Option<Local> = sqlx::query_as(&sql)
+        .bind(&id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    if let Some((local_updated_at, ..)) = &local {
+        let parse = |at: &str| DateTime::parse_from_rfc3339(at).ok();
+        let remote_updated_at = row
+            .get("updated_at")
+            .and_then(Value::as_str)
+            .and_then(parse);
+        if remote_updated_at <= parse(local_updated_at) {
+            return Ok(());
+        }
+    }
+
+    resync::download(&mut conn, table, row, Some(&session.user_id), Some(&now())).await?;
+    sqlx::query(
+        "INSERT INTO sync_events (table_name, record_id, direction, occurred_at)
+         VALUES ($1, $2, 'pull', $3)",
+    )
+    .bind(table)
+    .bind(&id)
+    .bind(now())
+    .execute(&mut *conn)
+    .await?;
+    drop(conn);
+
+    if table == "expenses" {
+        let mut account_ids = Vec::new();
+        if let Some((_, date, category_id, account_id)) = local {
+            if let Some(date) = date {
+                category_totals::refresh(pool, &[Cell { date, category_id }]).await?;
+            }
+            account_ids.push(account_id);
+        }
+        category_totals::refresh_expense(pool, &id).await?;
+        let remote: Option<(Option<String>,)> =
+            sqlx::query_as("SELECT account_id FROM expenses WHERE id = $1")
+                .bind(&id)
+                .fetch_optional(pool)
+                .await?;
+        account_ids.extend(remote.map(|(account_id,)| account_id));
+        accounts::refresh(pool, &account_ids).await?;
+    }
+    applied(app, table)
+}
+
+fn applied(app: &AppHandle, table: &str) -> Result<()> {
+    events::publish(
+        app,
+        &DomainEvent::RemoteChangesApplied {
+            tables: vec![table.to_string()],
+            count: 1,
+        },
+    )
+}
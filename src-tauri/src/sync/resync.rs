@@ -0,0 +1,373 @@
+//! Comparing every synced row with the server and repairing drift.
+//!
+//! Pulls only fetch what changed since the last sync and pushes only what
+//! was queued, so a lost queue item or a pull that died halfway can leave
+//! the two sides apart for good. A resync downloads each synced table in
+//! full and matches rows by id:
+//!
+//! - only on the server, or newer there: the server's version is written here;
+//! - only here, or newer here: the row is queued for the next push;
+//! - same `updated_at` but different values: the server's version wins;
+//! - records with queued changes or an open conflict are left to sync.
+//!
+//! A dry run only counts, so the user can look at the differences before
+//! anything changes. Applying them ends with an `orphans` repair for rows
+//! whose parent never arrived. The preferences row and tables kept out of
+//! sync are not compared.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::DateTime;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sqlx::SqliteConnection;
+use tauri::{AppHandle, Manager};
+
+use super::worker::{self, RemoteTable, Rest, SyncWorker, TABLES};
+use super::{conflicts, e2ee, enqueue, settings, table_columns, Operation};
+use crate::accounts;
+use crate::auth;
+use crate::backend::Backend;
+use crate::category_totals;
+use crate::db::{now, quote_ident, Db};
+use crate::error::{Error, Result};
+use crate::events::{self, DomainEvent};
+use crate::orphans::{self, OrphanReport};
+
+/// Rows fetched per request.
+const PAGE: usize = 1000;
+
+#[derive(Debug, Clone, Default, Serialize, specta::Type)]
+pub struct TableDiff {
+    pub table_name: String,
+    /// Rows on the server that aren't here.
+    pub missing_locally: usize,
+    /// Rows here the server never got.
+    pub missing_remotely: usize,
+    /// Rows whose server version is newer or differs.
+    pub outdated_locally: usize,
+    /// Rows edited here after their server version.
+    pub outdated_remotely: usize,
+    /// Rows left to sync because of queued changes or a conflict.
+    pub skipped: usize,
+}
+
+impl TableDiff {
+    fn is_empty(&self) -> bool {
+        self.missing_locally == 0
+            && self.missing_remotely == 0
+            && self.outdated_locally == 0
+            && self.outdated_remotely == 0
+    }
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ResyncReport {
+    /// False for a dry run.
+    pub applied: bool,
+    /// Compared tables, in push order.
+    pub tables: Vec<TableDiff>,
+    /// What the repair after applying found.
+    pub orphans: Option<OrphanReport>,
+}
+
+enum Fix {
+    /// Write the server's row here.
+    Download(Map<String, Value>),
+    /// Queue the local row for the next push.
+    Upload(String, Operation),
+}
+
+fn id_of(row: &Map<String, Value>) -> Option<String> {
+    row.get("id").and_then(Value::as_str).map(str::to_string)
+}
+
+fn newer(a: &Map<String, Value>, b: &Map<String, Value>) -> Option<std::cmp::Ordering> {
+    let at = |row: &Map<String, Value>| {
+        row.get("updated_at")
+            .and_then(Value::as_str)
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+    };
+    Some(at(a)?.cmp(&at(b)?))
+}
+
+async fn fetch(
+    client: &Rest,
+    table: &str,
+    user_ids: Vec<String>,
+) -> Result<Vec<Map<String, Value>>> {
+    let mut rows = Vec::new();
+    loop {
+        let client = client.clone();
+        let (table, user_ids, offset) = (table.to_string(), user_ids.clone(), rows.len());
+        let page = tauri::async_runtime::spawn_blocking(move || {
+            client.select(&table, &user_ids, offset, PAGE)
+        })
+        .await??;
+        let done = page.len() < PAGE;
+        rows.extend(page);
+        if done {
+            return e2ee::open_rows(rows).await;
+        }
+    }
+}
+
+/// Every local row of `table` by id.
+async fn local_rows(
+    conn: &mut SqliteConnection,
+    table: &str,
+) -> Result<HashMap<String, Map<String, Value>>> {
+    let columns = table_columns(conn, table).await?;
+    let pairs = columns
+        .iter()
+        .map(|c| format!("'{}', {}", c.replace('\'', "''"), quote_ident(c)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let rows: Vec<(String,)> = sqlx::query_as(&format!(
+        "SELECT json_object({pairs}) FROM {}",
+        quote_ident(table)
+    ))
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|(json,)| match serde_json::from_str(&json) {
+            Ok(Value::Object(row)) => Some((id_of(&row)?, row)),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Records of `table` with queued changes or an open conflict.
+async fn busy(conn: &mut SqliteConnection, table: &str) -> Result<HashSet<String>> {
+    let rows: Vec<(String,)> = sqlx::query_as(
+        "SELECT record_id FROM sync_queue WHERE table_name = $1
+         UNION
+         SELECT record_id FROM sync_conflicts WHERE table_name = $1 AND resolved_at IS NULL",
+    )
+    .bind(table)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(rows.into_iter().map(|(id,)| id).collect())
+}
+
+async fn compare(
+    conn: &mut SqliteConnection,
+    table: &RemoteTable,
+    remote: Vec<Map<String, Value>>,
+) -> Result<(TableDiff, Vec<Fix>)> {
+    let mut diff = TableDiff {
+        table_name: table.name.to_string(),
+        ..TableDiff::default()
+    };
+    let mut fixes = Vec::new();
+    let mut local = local_rows(conn, table.name).await?;
+    let busy = busy(conn, table.name).await?;
+
+    for row in remote {
+        let Some(id) = id_of(&row) else { continue };
+        let here = local.remove(&id);
+        if busy.contains(&id) {
+            diff.skipped += 1;
+            continue;
+        }
+        let Some(here) = here else {
+            diff.missing_locally += 1;
+            fixes.push(Fix::Download(row));
+            continue;
+        };
+        match newer(&row, &here) {
+            Some(std::cmp::Ordering::Less) => {
+                diff.outdated_remotely += 1;
+                fixes.push(Fix::Upload(id, Operation::Update));
+            }
+            Some(std::cmp::Ordering::Greater) => {
+                diff.outdated_locally += 1;
+                fixes.push(Fix::Download(row));
+            }
+            _ => {
+                let differs = table
+                    .columns
+                    .iter()
+                    .any(|&c| match (row.get(c), here.get(c)) {
+                        (Some(a), Some(b)) => conflicts::normalize(a) != conflicts::normalize(b),
+                        _ => false,
+                    });
+                if differs {
+                    diff.outdated_locally += 1;
+                    fixes.push(Fix::Download(row));
+                }
+            }
+        }
+    }
+
+    for (id, row) in local {
+        let deleted = row.get("deleted_at").is_some_and(|v| !v.is_null());
+        // Only custom categories are synced.
+        let default =
+            table.name == "categories" && row.get("is_custom").and_then(Value::as_i64) == Some(0);
+        if deleted || default {
+            continue;
+        }
+        if busy.contains(&id) {
+            diff.skipped += 1;
+            continue;
+        }
+        diff.missing_remotely += 1;
+        fixes.push(Fix::Upload(id, Operation::Insert));
+    }
+    Ok((diff, fixes))
+}
+
+/// Write a server row over the local one, the way a pull merges it: the
+/// row is kept under the signed-in user, whoever wrote it. Rows from a
+/// paired device come without a `user_id` and keep their own, and aren't
+/// marked as synced with the server.
+pub(super) async fn download(
+    conn: &mut SqliteConnection,
+    table: &str,
+    mut row: Map<String, Value>,
+    user_id: Option<&str>,
+    synced_at: Option<&str>,
+) -> Result<()> {
+    let columns = table_columns(conn, table).await?;
+    if let Some(user_id) = user_id {
+        if columns.iter().any(|c| c == "created_by")
+            && row.get("created_by").is_none_or(Value::is_null)
+        {
+            if let Some(writer) = row.get("user_id").cloned() {
+                row.insert("created_by".to_string(), writer);
+            }
+        }
+        row.insert("user_id".to_string(), user_id.into());
+    }
+    match synced_at {
+        Some(at) => row.insert("synced_at".to_string(), at.into()),
+        None => row.remove("synced_at"),
+    };
+    let written: Vec<&String> = columns
+        .iter()
+        .filter(|c| row.contains_key(c.as_str()))
+        .collect();
+    let names = written
+        .iter()
+        .map(|c| quote_ident(c))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let values = written
+        .iter()
+        .map(|c| format!("json_extract($1, '$.\"{}\"')", c.replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let updates = written
+        .iter()
+        .filter(|c| c.as_str() != "id")
+        .map(|c| format!("{0} = excluded.{0}", quote_ident(c)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    sqlx::query(&format!(
+        "INSERT INTO {} ({names}) VALUES ({values}) ON CONFLICT(id) DO UPDATE SET {updates}",
+        quote_ident(table)
+    ))
+    .bind(Value::Object(row).to_string())
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Compare every synced table with the server, and fix the differences
+/// unless `dry_run`.
+pub async fn run(app: &AppHandle, dry_run: bool) -> Result<ResyncReport> {
+    let config = app
+        .state::<Backend>()
+        .config()
+        .ok_or_else(|| Error::Unsupported("Sync is not set up in this build".to_string()))?;
+    let db = app.state::<Db>();
+    let pool = db.pool();
+    let session = auth::session(pool)
+        .await?
+        .ok_or_else(|| Error::Validation("Sign in to resync".to_string()))?;
+    let ledger_owner = worker::ledger_owner(pool, &session.user_id).await?;
+    let client = Rest::new(&config, &session);
+
+    let mut tables = Vec::new();
+    let mut fixes = Vec::new();
+    {
+        let mut conn = pool.acquire().await?;
+        for name in worker::synced_tables() {
+            let Some(table) = TABLES.iter().find(|t| t.name == name) else {
+                continue;
+            };
+            if !settings::is_enabled(&mut conn, name).await? {
+                continue;
+            }
+            let mut user_ids = vec![session.user_id.clone()];
+            if table.ledger && ledger_owner != session.user_id {
+                user_ids.push(ledger_owner.clone());
+            }
+            let remote = fetch(&client, name, user_ids).await?;
+            let (diff, table_fixes) = compare(&mut conn, table, remote).await?;
+            tables.push(diff);
+            fixes.push((name, table_fixes));
+        }
+    }
+    if dry_run || tables.iter().all(TableDiff::is_empty) {
+        return Ok(ResyncReport {
+            applied: false,
+            tables,
+            orphans: None,
+        });
+    }
+
+    let synced_at = now();
+    let mut downloaded = Vec::new();
+    let mut uploads = 0;
+    let mut tx = pool.begin().await?;
+    for (table, table_fixes) in fixes {
+        for fix in table_fixes {
+            match fix {
+                Fix::Download(row) => {
+                    download(
+                        &mut tx,
+                        table,
+                        row,
+                        Some(&session.user_id),
+                        Some(&synced_at),
+                    )
+                    .await?;
+                    downloaded.push(table);
+                }
+                Fix::Upload(id, operation) => {
+                    enqueue(&mut tx, table, &id, operation).await?;
+                    uploads += 1;
+                }
+            }
+        }
+    }
+    tx.commit().await?;
+
+    let orphans = orphans::repair(pool).await?;
+    if downloaded.contains(&"expenses") {
+        category_totals::rebuild(pool).await?;
+        accounts::recompute(pool).await?;
+    }
+    if !downloaded.is_empty() {
+        let count = downloaded.len();
+        downloaded.dedup();
+        events::publish(
+            app,
+            &DomainEvent::RemoteChangesApplied {
+                tables: downloaded.into_iter().map(str::to_string).collect(),
+                count,
+            },
+        )?;
+    }
+    if uploads > 0 {
+        app.state::<SyncWorker>().wake();
+    }
+    Ok(ResyncReport {
+        applied: true,
+        tables,
+        orphans: Some(orphans),
+    })
+}
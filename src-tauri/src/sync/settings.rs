@@ -0,0 +1,144 @@
+//! Which tables take part in cloud sync.
+//!
+//! Every synced table is on unless the user turned it off. While a table is
+//! off, its changes aren't queued, items queued before are held back, and
+//! pulls leave it alone. Turning it back on queues what was edited in the
+//! meantime and has the next pull fetch the whole table, since incremental
+//! pulls would skip what changed remotely while it was off.
+//!
+//! A table can't be off while a table referencing it syncs, or pushed rows
+//! would point at rows the server never got.
+
+use serde::Serialize;
+use sqlx::{SqliteConnection, SqlitePool};
+
+use super::{worker, Operation};
+use crate::db::{now, quote_ident};
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct TableSyncSetting {
+    pub table_name: String,
+    pub enabled: bool,
+}
+
+fn check_table(table: &str) -> Result<()> {
+    if worker::synced_tables().any(|t| t == table) {
+        Ok(())
+    } else {
+        Err(Error::Validation(format!("{table} is not synced")))
+    }
+}
+
+async fn disabled_tables(conn: &mut SqliteConnection) -> Result<Vec<String>> {
+    let rows: Vec<(String,)> =
+        sqlx::query_as("SELECT table_name FROM sync_settings WHERE enabled = 0")
+            .fetch_all(&mut *conn)
+            .await?;
+    Ok(rows.into_iter().map(|(name,)| name).collect())
+}
+
+/// Whether changes to `table` are queued and pushed.
+pub(crate) async fn is_enabled(conn: &mut SqliteConnection, table: &str) -> Result<bool> {
+    let row: Option<(bool,)> =
+        sqlx::query_as("SELECT enabled FROM sync_settings WHERE table_name = $1")
+            .bind(table)
+            .fetch_optional(&mut *conn)
+            .await?;
+    Ok(row.is_none_or(|(enabled,)| enabled))
+}
+
+/// Every synced table and whether it is on, in push order.
+pub async fn list(pool: &SqlitePool) -> Result<Vec<TableSyncSetting>> {
+    let mut conn = pool.acquire().await?;
+    let disabled = disabled_tables(&mut conn).await?;
+    Ok(worker::synced_tables()
+        .map(|table| TableSyncSetting {
+            table_name: table.to_string(),
+            enabled: !disabled.iter().any(|d| d == table),
+        })
+        .collect())
+}
+
+/// Turn sync of `table` on or off.
+pub async fn set(pool: &SqlitePool, table: &str, enabled: bool) -> Result<Vec<TableSyncSetting>> {
+    check_table(table)?;
+    let mut tx = pool.begin().await?;
+    let disabled = disabled_tables(&mut tx).await?;
+    let was_enabled = !disabled.iter().any(|d| d == table);
+    if was_enabled == enabled {
+        drop(tx);
+        return list(pool).await;
+    }
+
+    if enabled {
+        if let Some(parent) = worker::parents(table).find(|p| disabled.iter().any(|d| d == p)) {
+            return Err(Error::Validation(format!(
+                "{table} references {parent}, so turn on sync of {parent} first"
+            )));
+        }
+    } else if let Some(child) = worker::synced_tables()
+        .filter(|t| !disabled.iter().any(|d| d == t))
+        .find(|t| worker::parents(t).any(|p| p == table))
+    {
+        return Err(Error::Validation(format!(
+            "{child} references {table}, so turn off sync of {child} first"
+        )));
+    }
+
+    let disabled_at: Option<(Option<String>,)> =
+        sqlx::query_as("SELECT disabled_at FROM sync_settings WHERE table_name = $1")
+            .bind(table)
+            .fetch_optional(&mut *tx)
+            .await?;
+    let at = now();
+    sqlx::query(
+        "INSERT INTO sync_settings (table_name, enabled, disabled_at, full_pull_pending, updated_at)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT(table_name) DO UPDATE SET
+           enabled = excluded.enabled,
+           disabled_at = excluded.disabled_at,
+           full_pull_pending = excluded.full_pull_pending,
+           updated_at = excluded.updated_at",
+    )
+    .bind(table)
+    .bind(enabled)
+    .bind((!enabled).then_some(&at))
+    .bind(enabled)
+    .bind(&at)
+    .execute(&mut *tx)
+    .await?;
+
+    if enabled {
+        if let Some((Some(since),)) = disabled_at {
+            requeue_since(&mut tx, table, &since).await?;
+        }
+    }
+    tx.commit().await?;
+    list(pool).await
+}
+
+/// Queue the rows of `table` edited since `since`, while their changes
+/// weren't queued. The preferences row is left to the next full pull.
+async fn requeue_since(conn: &mut SqliteConnection, table: &str, since: &str) -> Result<()> {
+    if !worker::is_remote(table) {
+        return Ok(());
+    }
+    // Only custom categories are synced.
+    let custom = if table == "categories" {
+        " AND is_custom = 1"
+    } else {
+        ""
+    };
+    let ids: Vec<(String,)> = sqlx::query_as(&format!(
+        "SELECT id FROM {} WHERE updated_at > $1{custom}",
+        quote_ident(table)
+    ))
+    .bind(since)
+    .fetch_all(&mut *conn)
+    .await?;
+    for (id,) in ids {
+        super::enqueue(conn, table, &id, Operation::Update).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,188 @@
+//! Sync health: what sync is doing, how far behind it is, and what the
+//! recent runs did.
+//!
+//! Pushes from the worker and pulls from the TypeScript engine each note a
+//! run in `sync_log` when they moved or failed anything; passes with
+//! nothing to do are left out so the log isn't a minute-by-minute tick.
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use super::realtime;
+use super::worker::{self, PushReport};
+use crate::db::{new_id, now};
+use crate::error::Result;
+
+const DEFAULT_LIMIT: i64 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncState {
+    Idle,
+    /// A push is running.
+    Syncing,
+    /// The last run failed.
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct TableSyncTimes {
+    pub table_name: String,
+    /// When a row of the table last reached the server.
+    pub last_pushed_at: Option<String>,
+    /// When a row of the table was last merged from the server.
+    pub last_pulled_at: Option<String>,
+    /// Queued changes not pushed yet.
+    pub pending: i64,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct SyncHealth {
+    pub state: SyncState,
+    /// The error of the last run, if it failed.
+    pub last_error: Option<String>,
+    /// When the last pull finished, as recorded by the frontend.
+    pub last_synced_at: Option<String>,
+    /// Queued changes that will still be tried.
+    pub pending: i64,
+    /// Dead-lettered changes, waiting for a retry or discard.
+    pub failed: i64,
+    /// Changes waiting for a sync conflict to be resolved.
+    pub held: i64,
+    /// Whether remote changes stream in as they happen.
+    pub realtime: bool,
+    pub tables: Vec<TableSyncTimes>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct SyncRun {
+    pub id: String,
+    /// `push` or `pull`.
+    pub direction: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub pushed: i64,
+    pub pulled: i64,
+    pub failed: i64,
+    pub deferred: i64,
+    pub error: Option<String>,
+}
+
+/// Note a push pass in the log, unless it had nothing to do.
+pub(crate) async fn record_push(
+    pool: &SqlitePool,
+    started_at: &str,
+    result: &Result<PushReport>,
+) -> Result<()> {
+    let (report, error) = match result {
+        Ok(report) if report.pushed == 0 && report.failed == 0 => return Ok(()),
+        Ok(report) => (report.clone(), report.errors.first().cloned()),
+        Err(e) => (PushReport::default(), Some(e.to_string())),
+    };
+    sqlx::query(
+        "INSERT INTO sync_log (id, direction, started_at, finished_at, pushed, failed, deferred, error)
+         VALUES ($1, 'push', $2, $3, $4, $5, $6, $7)",
+    )
+    .bind(new_id())
+    .bind(started_at)
+    .bind(now())
+    .bind(report.pushed as i64)
+    .bind(report.failed as i64)
+    .bind(report.deferred as i64)
+    .bind(error)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// The current state of sync and where each table stands.
+pub async fn health(pool: &SqlitePool) -> Result<SyncHealth> {
+    let (pending, failed, held): (i64, i64, i64) = sqlx::query_as(
+        "SELECT
+           COALESCE(SUM(dead_lettered_at IS NULL AND NOT held), 0),
+           COALESCE(SUM(dead_lettered_at IS NOT NULL), 0),
+           COALESCE(SUM(held AND dead_lettered_at IS NULL), 0)
+         FROM (
+           SELECT dead_lettered_at, EXISTS (
+             SELECT 1 FROM sync_conflicts c
+             WHERE c.table_name = q.table_name
+               AND c.record_id = q.record_id
+               AND c.resolved_at IS NULL
+           ) AS held
+           FROM sync_queue q
+         )",
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let last: Option<(Option<String>,)> =
+        sqlx::query_as("SELECT error FROM sync_log ORDER BY finished_at DESC LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+    let last_error = last.and_then(|(error,)| error);
+    let state = if worker::is_running() {
+        SyncState::Syncing
+    } else if last_error.is_some() {
+        SyncState::Error
+    } else {
+        SyncState::Idle
+    };
+
+    let last_synced_at: Option<(Option<String>,)> =
+        sqlx::query_as("SELECT last_sync_at FROM auth_state WHERE id = 1")
+            .fetch_optional(pool)
+            .await?;
+
+    let events: Vec<(String, String, String)> = sqlx::query_as(
+        "SELECT table_name, direction, MAX(occurred_at) FROM sync_events
+         GROUP BY table_name, direction",
+    )
+    .fetch_all(pool)
+    .await?;
+    let queued: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT table_name, COUNT(*) FROM sync_queue
+         WHERE dead_lettered_at IS NULL
+         GROUP BY table_name",
+    )
+    .fetch_all(pool)
+    .await?;
+    let last_event = |table: &str, direction: &str| {
+        events
+            .iter()
+            .find(|(t, d, _)| t == table && d == direction)
+            .map(|(_, _, at)| at.clone())
+    };
+    let tables = worker::synced_tables()
+        .map(|table| TableSyncTimes {
+            table_name: table.to_string(),
+            last_pushed_at: last_event(table, "push"),
+            last_pulled_at: last_event(table, "pull"),
+            pending: queued
+                .iter()
+                .find(|(t, _)| t == table)
+                .map_or(0, |(_, n)| *n),
+        })
+        .collect();
+
+    Ok(SyncHealth {
+        state,
+        last_error,
+        last_synced_at: last_synced_at.and_then(|(at,)| at),
+        pending,
+        failed,
+        held,
+        realtime: realtime::is_connected(),
+        tables,
+    })
+}
+
+/// The most recent runs, newest first.
+pub async fn log(pool: &SqlitePool, limit: Option<i64>) -> Result<Vec<SyncRun>> {
+    Ok(sqlx::query_as::<_, SyncRun>(
+        "SELECT id, direction, started_at, finished_at, pushed, pulled, failed, deferred, error
+         FROM sync_log ORDER BY finished_at DESC LIMIT $1",
+    )
+    .bind(limit.unwrap_or(DEFAULT_LIMIT).max(1))
+    .fetch_all(pool)
+    .await?)
+}
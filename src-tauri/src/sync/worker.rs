@@ -0,0 +1,771 @@
+//! Pushing `sync_queue` to Supabase from a background task.
+//!
+//! The worker wakes every `INTERVAL`, or right away when the frontend asks
+//! for a push after queueing a change, and sends due items oldest first with
+//! parents before children, after folding several changes to one record
+//! into one (see `compact`). Each item goes out as the same PostgREST request
+//! the TypeScript engine used to make: an upsert of the table's known
+//! columns, sealed first while end-to-end encryption is on (see `e2ee`),
+//! or a patch of the tombstone for deletes. A pushed item leaves
+//! the queue; a failed one gets its next attempt from `backoff`. Progress is
+//! emitted after every item so a sync indicator can follow along, and
+//! passes that pushed or failed anything are noted in the sync log.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use age::x25519::Recipient;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Notify;
+
+use super::{backoff, compact, e2ee, policy, status};
+use crate::auth::{self, Session};
+use crate::backend::{Backend, BackendConfig};
+use crate::connectivity::Connectivity;
+use crate::db::{now, Db};
+use crate::error::{Error, Result};
+
+/// Emitted with a `SyncProgress` payload after each pushed or failed item.
+pub const PROGRESS_EVENT: &str = "sync://progress";
+
+const INTERVAL: Duration = Duration::from_secs(60);
+
+/// Set while a pass runs, so the loop and a manual push never overlap.
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+struct RunningGuard;
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        RUNNING.store(false, Ordering::Release);
+    }
+}
+
+/// Managed state for waking the worker early.
+#[derive(Default)]
+pub struct SyncWorker {
+    wake: Notify,
+}
+
+impl SyncWorker {
+    /// Start a pass now, or right after the one in progress.
+    pub fn wake(&self) {
+        self.wake.notify_one();
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, specta::Type)]
+pub struct PushReport {
+    pub pushed: usize,
+    pub failed: usize,
+    /// Items held back until a row they reference reaches the server.
+    pub deferred: usize,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncProgress {
+    pub pushed: usize,
+    pub failed: usize,
+    /// Items of this pass not tried yet.
+    pub remaining: usize,
+}
+
+/// A table the server knows, with the columns pushed for it.
+pub(super) struct RemoteTable {
+    pub(super) name: &'static str,
+    pub(super) columns: &'static [&'static str],
+    /// Rows belong to the ledger owner in partner mode, not to whoever wrote
+    /// them.
+    pub(super) ledger: bool,
+    /// Columns that go into `encrypted_payload` instead while end-to-end
+    /// encryption is on; see `e2ee`.
+    sealed: &'static [&'static str],
+    /// Foreign keys on the server, as column and referenced table. Decides
+    /// the order tables are pushed in.
+    references: &'static [(&'static str, &'static str)],
+}
+
+pub(super) const TABLES: &[RemoteTable] = &[
+    RemoteTable {
+        name: "expenses",
+        columns: &[
+            "id",
+            "amount",
+            "category_id",
+            "note",
+            "date",
+            "household_id",
+            "created_by",
+            "created_at",
+            "updated_at",
+            "deleted_at",
+        ],
+        ledger: true,
+        sealed: &["amount", "category_id", "note", "date"],
+        references: &[("category_id", "categories")],
+    },
+    RemoteTable {
+        name: "budgets",
+        columns: &[
+            "id",
+            "month",
+            "total_amount",
+            "spending_limit",
+            "household_id",
+            "created_by",
+            "created_at",
+            "updated_at",
+            "deleted_at",
+        ],
+        ledger: true,
+        sealed: &["total_amount", "spending_limit"],
+        references: &[],
+    },
+    RemoteTable {
+        name: "savings_goals",
+        columns: &[
+            "id",
+            "name",
+            "target_amount",
+            "target_date",
+            "monthly_contribution",
+            "why_statement",
+            "privacy_level",
+            "created_at",
+            "updated_at",
+            "deleted_at",
+        ],
+        ledger: false,
+        sealed: &[
+            "name",
+            "target_amount",
+            "target_date",
+            "monthly_contribution",
+            "why_statement",
+        ],
+        references: &[],
+    },
+    RemoteTable {
+        name: "savings_contributions",
+        columns: &[
+            "id",
+            "goal_id",
+            "month",
+            "amount",
+            "is_full_amount",
+            "created_at",
+            "updated_at",
+            "deleted_at",
+        ],
+        ledger: false,
+        sealed: &["amount", "is_full_amount"],
+        references: &[("goal_id", "savings_goals")],
+    },
+    RemoteTable {
+        name: "habit_goals",
+        columns: &[
+            "id",
+            "name",
+            "category_id",
+            "rule_type",
+            "rule_value",
+            "duration_months",
+            "start_date",
+            "privacy_level",
+            "created_at",
+            "updated_at",
+            "deleted_at",
+        ],
+        ledger: false,
+        sealed: &[
+            "name",
+            "rule_type",
+            "rule_value",
+            "duration_months",
+            "start_date",
+        ],
+        references: &[("category_id", "categories")],
+    },
+    RemoteTable {
+        name: "habit_tracking",
+        columns: &[
+            "id",
+            "habit_goal_id",
+            "month",
+            "spent_amount",
+            "target_amount",
+            "is_compliant",
+            "created_at",
+            "updated_at",
+            "deleted_at",
+        ],
+        ledger: false,
+        sealed: &["spent_amount", "target_amount", "is_compliant"],
+        references: &[("habit_goal_id", "habit_goals")],
+    },
+    RemoteTable {
+        name: "categories",
+        columns: &[
+            "id",
+            "name",
+            "icon",
+            "color",
+            "is_custom",
+            "is_hidden",
+            "sort_order",
+            "created_at",
+            "updated_at",
+            "deleted_at",
+        ],
+        ledger: false,
+        sealed: &[],
+        references: &[],
+    },
+    RemoteTable {
+        name: "feedback_notes",
+        columns: &["id", "content", "created_at", "updated_at", "deleted_at"],
+        ledger: false,
+        sealed: &[],
+        references: &[],
+    },
+    RemoteTable {
+        name: "scheduled_notifications",
+        columns: &[
+            "id",
+            "notification_type",
+            "goal_id",
+            "title",
+            "body",
+            "scheduled_at",
+            "cron_expression",
+            "sent_at",
+            "created_at",
+            "updated_at",
+            "deleted_at",
+        ],
+        ledger: false,
+        sealed: &[],
+        references: &[("goal_id", "savings_goals")],
+    },
+];
+
+/// How deep `table` sits in the foreign keys: 0 for tables referencing
+/// none, else one more than the deepest table it references. Pushing in
+/// order of level puts every parent before its children.
+fn level(table: &str) -> usize {
+    fn depth(table: &str, hops: usize) -> usize {
+        let Some(table) = TABLES.iter().find(|t| t.name == table) else {
+            return 0;
+        };
+        // A cycle would be a mistake in `TABLES`; don't recurse forever.
+        if hops > TABLES.len() {
+            return hops;
+        }
+        table
+            .references
+            .iter()
+            .map(|(_, parent)| depth(parent, hops + 1) + 1)
+            .max()
+            .unwrap_or(0)
+    }
+    depth(table, 0)
+}
+
+/// Whether rows of `table` are pushed to the server.
+pub(crate) fn is_remote(table: &str) -> bool {
+    TABLES.iter().any(|t| t.name == table)
+}
+
+/// Every table that syncs, in push order. The preferences row is pushed
+/// on its own but can be kept out of sync like the rest.
+pub(crate) fn synced_tables() -> impl Iterator<Item = &'static str> {
+    let mut tables: Vec<_> = TABLES.iter().map(|t| t.name).collect();
+    tables.sort_by_key(|t| level(t));
+    tables.into_iter().chain(["notification_preferences"])
+}
+
+/// Tables that rows of `table` reference on the server.
+pub(crate) fn parents(table: &str) -> impl Iterator<Item = &'static str> {
+    TABLES
+        .iter()
+        .find(|t| t.name == table)
+        .into_iter()
+        .flat_map(|t| t.references.iter().map(|(_, parent)| *parent))
+}
+
+/// Tables with columns that are sealed under end-to-end encryption.
+pub(crate) fn sealed_tables() -> impl Iterator<Item = &'static str> {
+    TABLES
+        .iter()
+        .filter(|t| !t.sealed.is_empty())
+        .map(|t| t.name)
+}
+
+/// Keyed by user on the server and never deleted, so pushed separately.
+const PREFERENCE_COLUMNS: &[&str] = &[
+    "notifications_enabled",
+    "monthly_checkin_enabled",
+    "monthly_checkin_cron",
+    "progress_updates_enabled",
+    "progress_updates_cron",
+    "why_reminders_enabled",
+    "why_reminders_cron",
+    "quiet_hours_enabled",
+    "quiet_hours_start",
+    "quiet_hours_end",
+    "updated_at",
+];
+
+#[derive(sqlx::FromRow)]
+struct QueuedChange {
+    id: String,
+    table_name: String,
+    record_id: String,
+    operation: String,
+    payload: String,
+    user_id: String,
+}
+
+/// Who a change is pushed for.
+struct Owner {
+    user_id: String,
+    ledger_owner_id: String,
+    timezone: String,
+    /// Key rows are sealed to, while end-to-end encryption is on.
+    recipient: Option<Recipient>,
+}
+
+/// Run the worker for the life of the app.
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            {
+                let worker = app.state::<SyncWorker>();
+                tokio::select! {
+                    _ = tokio::time::sleep(INTERVAL) => {}
+                    _ = worker.wake.notified() => {}
+                }
+            }
+            if let Err(e) = push(&app).await {
+                eprintln!("[Sync] Push failed: {e}");
+            }
+        }
+    });
+}
+
+/// Push every due queue item once. Does nothing while offline, on a
+/// connection the sync policy rules out, signed out or without a
+/// configured backend; while another pass runs, queues one more after it
+/// instead.
+pub async fn push(app: &AppHandle) -> Result<PushReport> {
+    let Some(config) = app.state::<Backend>().config() else {
+        return Ok(PushReport::default());
+    };
+    if !app.state::<Connectivity>().is_online() || !policy::allows_sync(app).await? {
+        return Ok(PushReport::default());
+    }
+    let db = app.state::<Db>();
+    let pool = db.pool();
+    let Some(session) = auth::session(pool).await? else {
+        return Ok(PushReport::default());
+    };
+    if RUNNING.swap(true, Ordering::Acquire) {
+        app.state::<SyncWorker>().wake();
+        return Ok(PushReport::default());
+    }
+    let _guard = RunningGuard;
+
+    let started_at = now();
+    let progress = |progress: SyncProgress| -> Result<()> {
+        app.emit(PROGRESS_EVENT, progress)?;
+        Ok(())
+    };
+    let result = pass(pool, &config, &session, &progress).await;
+    status::record_push(pool, &started_at, &result).await?;
+    result
+}
+
+/// Push every due queue item once without the app, as `goaldy sync` does.
+pub async fn push_headless(pool: &SqlitePool, config: &BackendConfig) -> Result<PushReport> {
+    let session = auth::session(pool).await?.ok_or_else(|| {
+        Error::Validation("Not signed in, or the session expired; open the app".to_string())
+    })?;
+    let started_at = now();
+    let result = pass(pool, config, &session, &|_| Ok(())).await;
+    status::record_push(pool, &started_at, &result).await?;
+    result
+}
+
+/// Whether a pass is pushing right now.
+pub(crate) fn is_running() -> bool {
+    RUNNING.load(Ordering::Acquire)
+}
+
+async fn pass(
+    pool: &SqlitePool,
+    config: &BackendConfig,
+    session: &Session,
+    progress: &(dyn Fn(SyncProgress) -> Result<()> + Sync),
+) -> Result<PushReport> {
+    let mut report = PushReport::default();
+    compact::run(pool).await?;
+    let items = due(pool, &session.user_id).await?;
+    if items.is_empty() {
+        return Ok(report);
+    }
+    let owner = owner(pool, &session.user_id).await?;
+    let client = Rest::new(config, session);
+
+    let total = items.len();
+    for (done, item) in items.into_iter().enumerate() {
+        if awaits_parent(pool, &item).await? {
+            report.deferred += 1;
+        } else {
+            match send(&client, &owner, &item).await {
+                Ok(()) => {
+                    sqlx::query("DELETE FROM sync_queue WHERE id = $1")
+                        .bind(&item.id)
+                        .execute(pool)
+                        .await?;
+                    sqlx::query(
+                        "INSERT INTO sync_events (table_name, record_id, direction, operation, occurred_at)
+                         VALUES ($1, $2, 'push', $3, $4)",
+                    )
+                    .bind(&item.table_name)
+                    .bind(&item.record_id)
+                    .bind(&item.operation)
+                    .bind(now())
+                    .execute(pool)
+                    .await?;
+                    report.pushed += 1;
+                }
+                Err(e) => {
+                    let message = e.to_string();
+                    backoff::record_failure(pool, &item.id, &message).await?;
+                    report.errors.push(format!(
+                        "Failed to sync {}/{}: {message}",
+                        item.table_name, item.record_id
+                    ));
+                    report.failed += 1;
+                }
+            }
+        }
+        progress(SyncProgress {
+            pushed: report.pushed,
+            failed: report.failed,
+            remaining: total - done - 1,
+        })?;
+    }
+    Ok(report)
+}
+
+/// Due items of `user_id`, by table `level` and oldest first within one, so
+/// parents go before the rows referencing them.
+/// Items of tables kept out of sync wait until they are synced again.
+async fn due(pool: &SqlitePool, user_id: &str) -> Result<Vec<QueuedChange>> {
+    let mut items = sqlx::query_as::<_, QueuedChange>(
+        "SELECT id, table_name, record_id, operation, payload, user_id FROM sync_queue
+         WHERE user_id = $1 AND dead_lettered_at IS NULL
+           AND (next_attempt_at IS NULL OR next_attempt_at <= $2)
+           AND NOT EXISTS (
+             SELECT 1 FROM sync_conflicts c
+             WHERE c.table_name = sync_queue.table_name
+               AND c.record_id = sync_queue.record_id
+               AND c.resolved_at IS NULL
+           )
+           AND table_name NOT IN (SELECT table_name FROM sync_settings WHERE enabled = 0)
+         ORDER BY created_at ASC",
+    )
+    .bind(user_id)
+    .bind(now())
+    .fetch_all(pool)
+    .await?;
+    // Stable, so each level keeps the queue order.
+    items.sort_by_key(|item| level(&item.table_name));
+    Ok(items)
+}
+
+/// Whether an item references a row created here that hasn't reached the
+/// server yet, so pushing it now would break a foreign key there.
+async fn awaits_parent(pool: &SqlitePool, item: &QueuedChange) -> Result<bool> {
+    if item.operation == "delete" {
+        return Ok(false);
+    }
+    let Some(table) = TABLES.iter().find(|t| t.name == item.table_name) else {
+        return Ok(false);
+    };
+    if table.references.is_empty() {
+        return Ok(false);
+    }
+    let Ok(Value::Object(payload)) = serde_json::from_str(&item.payload) else {
+        return Ok(false);
+    };
+    for (column, parent) in table.references {
+        let Some(parent_id) = payload.get(*column).and_then(Value::as_str) else {
+            continue;
+        };
+        let pending: Option<(String,)> = sqlx::query_as(
+            "SELECT id FROM sync_queue
+             WHERE table_name = $1 AND record_id = $2 AND operation = 'insert'
+             LIMIT 1",
+        )
+        .bind(parent)
+        .bind(parent_id)
+        .fetch_optional(pool)
+        .await?;
+        if pending.is_some() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// In partner mode both partners write to the owner's ledger.
+/// Whose ledger `user_id` writes to: the owner's while partnered, else
+/// their own.
+pub(super) async fn ledger_owner(pool: &SqlitePool, user_id: &str) -> Result<String> {
+    let partnership: Option<(String, Option<String>)> =
+        sqlx::query_as("SELECT owner_id, partner_id FROM partnership LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+    Ok(match partnership {
+        Some((owner_id, Some(partner_id))) if partner_id == user_id || owner_id == user_id => {
+            owner_id
+        }
+        _ => user_id.to_string(),
+    })
+}
+
+async fn owner(pool: &SqlitePool, user_id: &str) -> Result<Owner> {
+    let ledger_owner_id = ledger_owner(pool, user_id).await?;
+    let timezone: Option<(Option<String>,)> =
+        sqlx::query_as("SELECT timezone FROM notification_preferences WHERE id = 1")
+            .fetch_optional(pool)
+            .await?;
+    // A partner's rows go to a ledger the owner must be able to read.
+    let recipient = if ledger_owner_id == user_id {
+        e2ee::recipient(pool).await?
+    } else {
+        None
+    };
+    Ok(Owner {
+        user_id: user_id.to_string(),
+        recipient,
+        ledger_owner_id,
+        timezone: timezone
+            .and_then(|(tz,)| tz)
+            .unwrap_or_else(|| "UTC".to_string()),
+    })
+}
+
+async fn send(client: &Rest, owner: &Owner, item: &QueuedChange) -> Result<()> {
+    let payload = match serde_json::from_str(&item.payload) {
+        Ok(Value::Object(payload)) => payload,
+        _ => {
+            return Err(Error::Validation(
+                "Payload is not a JSON object".to_string(),
+            ))
+        }
+    };
+    let Some(request) = request(owner, item, payload)? else {
+        // The server has no such table; the TypeScript engine dropped these
+        // as pushed too.
+        return Ok(());
+    };
+    let client = client.clone();
+    tauri::async_runtime::spawn_blocking(move || client.send(request)).await?
+}
+
+enum Request {
+    Upsert {
+        table: &'static str,
+        on_conflict: &'static str,
+        body: Map<String, Value>,
+    },
+    Tombstone {
+        table: &'static str,
+        id: String,
+        user_id: String,
+        body: Map<String, Value>,
+    },
+}
+
+fn pick(payload: &Map<String, Value>, columns: &[&str]) -> Map<String, Value> {
+    columns
+        .iter()
+        .filter_map(|&c| payload.get(c).map(|v| (c.to_string(), v.clone())))
+        .collect()
+}
+
+fn request(
+    owner: &Owner,
+    item: &QueuedChange,
+    payload: Map<String, Value>,
+) -> Result<Option<Request>> {
+    let updated_at = payload
+        .get("updated_at")
+        .filter(|v| !v.is_null())
+        .cloned()
+        .unwrap_or_else(|| Value::String(now()));
+
+    if item.table_name == "notification_preferences" {
+        let mut body = pick(&payload, PREFERENCE_COLUMNS);
+        for value in body.values_mut() {
+            // Stored as 0/1 locally, real booleans on the server.
+            if let Some(n) = value.as_i64() {
+                *value = Value::Bool(n != 0);
+            }
+        }
+        body.insert("user_id".to_string(), item.user_id.clone().into());
+        body.insert("timezone".to_string(), owner.timezone.clone().into());
+        body.insert("updated_at".to_string(), updated_at);
+        return Ok(Some(Request::Upsert {
+            table: "notification_preferences",
+            on_conflict: "user_id",
+            body,
+        }));
+    }
+
+    let Some(table) = TABLES.iter().find(|t| t.name == item.table_name) else {
+        return Ok(None);
+    };
+    let user_id = if table.ledger {
+        owner.ledger_owner_id.clone()
+    } else {
+        item.user_id.clone()
+    };
+
+    if item.operation == "delete" {
+        let mut body = Map::new();
+        body.insert(
+            "deleted_at".to_string(),
+            payload.get("deleted_at").cloned().unwrap_or(Value::Null),
+        );
+        body.insert("updated_at".to_string(), updated_at);
+        return Ok(Some(Request::Tombstone {
+            table: table.name,
+            id: item.record_id.clone(),
+            user_id,
+            body,
+        }));
+    }
+
+    let mut body = pick(&payload, table.columns);
+    body.insert("user_id".to_string(), user_id.into());
+    if table.columns.contains(&"created_by") {
+        let created_by = body.get("created_by").filter(|v| !v.is_null()).cloned();
+        body.insert(
+            "created_by".to_string(),
+            created_by.unwrap_or_else(|| owner.user_id.clone().into()),
+        );
+    }
+    if let (Some(recipient), false) = (&owner.recipient, table.sealed.is_empty()) {
+        // Rows shared with a household must stay readable to its members.
+        let shared = body.get("household_id").is_some_and(|v| !v.is_null());
+        if shared {
+            body.insert(e2ee::SEALED_COLUMN.to_string(), Value::Null);
+        } else {
+            e2ee::seal(recipient, &mut body, table.sealed)?;
+        }
+    }
+    Ok(Some(Request::Upsert {
+        table: table.name,
+        on_conflict: "id",
+        body,
+    }))
+}
+
+/// Blocking PostgREST client for one pass.
+#[derive(Clone)]
+pub(super) struct Rest {
+    base_url: String,
+    anon_key: String,
+    access_token: String,
+    agent: ureq::Agent,
+}
+
+fn remote_error(e: ureq::Error) -> Error {
+    match e {
+        ureq::Error::Status(code, response) => {
+            let body = response.into_string().unwrap_or_default();
+            Error::Remote(format!("server returned {code}: {}", body.trim()))
+        }
+        ureq::Error::Transport(e) => Error::Remote(format!("server unreachable: {e}")),
+    }
+}
+
+impl Rest {
+    pub(super) fn new(config: &BackendConfig, session: &Session) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(Duration::from_secs(10))
+            .timeout(Duration::from_secs(30))
+            .build();
+        Self {
+            base_url: config.url.clone(),
+            anon_key: config.anon_key.clone(),
+            access_token: session.access_token.clone(),
+            agent,
+        }
+    }
+
+    fn request(&self, method: &str, table: &str) -> ureq::Request {
+        self.agent
+            .request(method, &format!("{}/rest/v1/{table}", self.base_url))
+            .set("apikey", &self.anon_key)
+            .set("Authorization", &format!("Bearer {}", self.access_token))
+            .set("Content-Type", "application/json")
+    }
+
+    /// One page of the rows of `user_ids` in `table`, by id.
+    pub(super) fn select(
+        &self,
+        table: &str,
+        user_ids: &[String],
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Map<String, Value>>> {
+        let response = self
+            .request("GET", table)
+            .query("select", "*")
+            .query("user_id", &format!("in.({})", user_ids.join(",")))
+            .query("order", "id")
+            .query("offset", &offset.to_string())
+            .query("limit", &limit.to_string())
+            .call()
+            .map_err(remote_error)?;
+        serde_json::from_reader(response.into_reader())
+            .map_err(|e| Error::Remote(format!("unexpected response: {e}")))
+    }
+
+    fn send(&self, request: Request) -> Result<()> {
+        match request {
+            Request::Upsert {
+                table,
+                on_conflict,
+                body,
+            } => self
+                .request("POST", table)
+                .query("on_conflict", on_conflict)
+                .set("Prefer", "resolution=merge-duplicates,return=minimal")
+                .send_string(&Value::Object(body).to_string()),
+            Request::Tombstone {
+                table,
+                id,
+                user_id,
+                body,
+            } => self
+                .request("PATCH", table)
+                .query("id", &format!("eq.{id}"))
+                .query("user_id", &format!("eq.{user_id}"))
+                .set("Prefer", "return=minimal")
+                .send_string(&Value::Object(body).to_string()),
+        }
+        .map_err(remote_error)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,356 @@
+//! Tags: labels an expense can carry any number of, for slicing spending
+//! across categories ("vacation", "work-reimbursable").
+//!
+//! Tags are created by name as they are first used and are unique ignoring
+//! case, so renaming one to the name of another merges the two. A tag
+//! filter selects expenses carrying any of its tags, or with `match_all`
+//! every one of them.
+
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::analysis::spending::NET_AMOUNT;
+use crate::db::{new_id, now};
+use crate::error::{Error, Result};
+use crate::expenses::{ExpenseWithCategory, SELECT_WITH_CATEGORY};
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct Tag {
+    pub id: String,
+    pub name: String,
+    pub color: Option<String>,
+    /// Expenses carrying it, not counting deleted ones.
+    pub expense_count: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize, specta::Type)]
+pub struct TagFilter {
+    pub tag_ids: Vec<String>,
+    /// Only expenses with every tag, rather than any of them.
+    #[serde(default)]
+    pub match_all: bool,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct TagTotal {
+    pub tag_id: String,
+    pub name: String,
+    pub color: Option<String>,
+    pub total: f64,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct TaggedCategoryTotal {
+    pub category_id: Option<String>,
+    pub category_name: Option<String>,
+    pub total: f64,
+    pub count: i64,
+}
+
+const SELECT_TAG: &str = "SELECT t.id, t.name, t.color, t.created_at, t.updated_at,
+       (SELECT COUNT(*) FROM expense_tags et
+        JOIN expenses e ON e.id = et.expense_id AND e.deleted_at IS NULL
+        WHERE et.tag_id = t.id) AS expense_count
+     FROM tags t";
+
+/// Expenses matching the filter whose ids are bound as a JSON array to
+/// `$1` and its required tag count to `$2`.
+const MATCHING: &str = "SELECT expense_id FROM expense_tags
+     WHERE tag_id IN (SELECT value FROM json_each($1))
+     GROUP BY expense_id
+     HAVING COUNT(*) >= $2";
+
+impl TagFilter {
+    fn binds(&self) -> Result<(String, i64)> {
+        let mut ids = self.tag_ids.clone();
+        ids.sort();
+        ids.dedup();
+        if ids.is_empty() {
+            return Err(Error::Validation("Pick at least one tag".to_string()));
+        }
+        let needed = if self.match_all { ids.len() as i64 } else { 1 };
+        let ids = serde_json::to_string(&ids)
+            .map_err(|e| Error::Validation(format!("failed to encode tags: {e}")))?;
+        Ok((ids, needed))
+    }
+}
+
+fn clean_name(name: &str) -> Result<String> {
+    let name = name.trim().trim_start_matches('#').trim();
+    if name.is_empty() {
+        return Err(Error::Validation("A tag needs a name".to_string()));
+    }
+    Ok(name.to_string())
+}
+
+async fn get(pool: &SqlitePool, id: &str) -> Result<Tag> {
+    sqlx::query_as::<_, Tag>(&format!("{SELECT_TAG} WHERE t.id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| Error::Validation("Tag not found".to_string()))
+}
+
+/// Every tag, by name.
+pub async fn list(pool: &SqlitePool) -> Result<Vec<Tag>> {
+    Ok(
+        sqlx::query_as::<_, Tag>(&format!("{SELECT_TAG} ORDER BY t.name COLLATE NOCASE"))
+            .fetch_all(pool)
+            .await?,
+    )
+}
+
+/// The id of the tag called `name`, created if new.
+async fn tag_id(conn: &mut SqliteConnection, name: &str) -> Result<String> {
+    let now = now();
+    sqlx::query(
+        "INSERT INTO tags (id, name, created_at, updated_at) VALUES ($1, $2, $3, $3)
+         ON CONFLICT(name) DO NOTHING",
+    )
+    .bind(new_id())
+    .bind(name)
+    .bind(&now)
+    .execute(&mut *conn)
+    .await?;
+    let (id,): (String,) = sqlx::query_as("SELECT id FROM tags WHERE name = $1")
+        .bind(name)
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok(id)
+}
+
+/// Create a tag, or return the one already called `name`.
+pub async fn create(pool: &SqlitePool, name: &str, color: Option<String>) -> Result<Tag> {
+    let name = clean_name(name)?;
+    let mut conn = pool.acquire().await?;
+    let id = tag_id(&mut conn, &name).await?;
+    if color.is_some() {
+        sqlx::query("UPDATE tags SET color = $1, updated_at = $2 WHERE id = $3")
+            .bind(&color)
+            .bind(now())
+            .bind(&id)
+            .execute(&mut *conn)
+            .await?;
+    }
+    drop(conn);
+    get(pool, &id).await
+}
+
+pub async fn set_color(pool: &SqlitePool, id: &str, color: Option<String>) -> Result<Tag> {
+    sqlx::query("UPDATE tags SET color = $1, updated_at = $2 WHERE id = $3")
+        .bind(&color)
+        .bind(now())
+        .bind(id)
+        .execute(pool)
+        .await?;
+    get(pool, id).await
+}
+
+/// Rename a tag. If another tag already has the name, the two are merged
+/// into that one. Returns the tag the expenses now carry.
+pub async fn rename(pool: &SqlitePool, id: &str, name: &str) -> Result<Tag> {
+    let name = clean_name(name)?;
+    get(pool, id).await?;
+    let existing: Option<(String,)> =
+        sqlx::query_as("SELECT id FROM tags WHERE name = $1 AND id != $2")
+            .bind(&name)
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+    if let Some((target,)) = existing {
+        return merge(pool, &[id.to_string()], &target).await;
+    }
+    sqlx::query("UPDATE tags SET name = $1, updated_at = $2 WHERE id = $3")
+        .bind(&name)
+        .bind(now())
+        .bind(id)
+        .execute(pool)
+        .await?;
+    get(pool, id).await
+}
+
+/// Move the expenses of the `sources` tags to `target` and delete them.
+pub async fn merge(pool: &SqlitePool, sources: &[String], target: &str) -> Result<Tag> {
+    get(pool, target).await?;
+    let mut tx = pool.begin().await?;
+    for source in sources.iter().filter(|s| *s != target) {
+        sqlx::query(
+            "INSERT OR IGNORE INTO expense_tags (expense_id, tag_id, created_at)
+             SELECT expense_id, $1, created_at FROM expense_tags WHERE tag_id = $2",
+        )
+        .bind(target)
+        .bind(source)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM tags WHERE id = $1")
+            .bind(source)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+    get(pool, target).await
+}
+
+/// Delete a tag. The expenses carrying it are left alone.
+pub async fn delete(pool: &SqlitePool, id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM tags WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// The tags of an expense, by name.
+pub async fn for_expense(pool: &SqlitePool, expense_id: &str) -> Result<Vec<Tag>> {
+    Ok(sqlx::query_as::<_, Tag>(&format!(
+        "{SELECT_TAG}
+         JOIN expense_tags x ON x.tag_id = t.id AND x.expense_id = $1
+         ORDER BY t.name COLLATE NOCASE"
+    ))
+    .bind(expense_id)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Give an expense exactly the tags named, creating new ones as needed.
+pub async fn set_for_expense(
+    pool: &SqlitePool,
+    expense_id: &str,
+    names: &[String],
+) -> Result<Vec<Tag>> {
+    let found: Option<(String,)> =
+        sqlx::query_as("SELECT id FROM expenses WHERE id = $1 AND deleted_at IS NULL")
+            .bind(expense_id)
+            .fetch_optional(pool)
+            .await?;
+    if found.is_none() {
+        return Err(Error::Validation("Expense not found".to_string()));
+    }
+
+    let now = now();
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM expense_tags WHERE expense_id = $1")
+        .bind(expense_id)
+        .execute(&mut *tx)
+        .await?;
+    for name in names {
+        let id = tag_id(&mut tx, &clean_name(name)?).await?;
+        sqlx::query(
+            "INSERT OR IGNORE INTO expense_tags (expense_id, tag_id, created_at)
+             VALUES ($1, $2, $3)",
+        )
+        .bind(expense_id)
+        .bind(&id)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    for_expense(pool, expense_id).await
+}
+
+/// Add a tag to several expenses at once, or with `remove` take it off
+/// them. Returns how many expenses changed.
+pub async fn tag_expenses(
+    pool: &SqlitePool,
+    tag_id: &str,
+    expense_ids: &[String],
+    remove: bool,
+) -> Result<u64> {
+    get(pool, tag_id).await?;
+    let ids = serde_json::to_string(expense_ids)
+        .map_err(|e| Error::Validation(format!("failed to encode expenses: {e}")))?;
+    let changed = if remove {
+        sqlx::query(
+            "DELETE FROM expense_tags
+             WHERE tag_id = $1 AND expense_id IN (SELECT value FROM json_each($2))",
+        )
+        .bind(tag_id)
+        .bind(&ids)
+        .execute(pool)
+        .await?
+    } else {
+        sqlx::query(
+            "INSERT OR IGNORE INTO expense_tags (expense_id, tag_id, created_at)
+             SELECT e.id, $1, $3 FROM expenses e
+             WHERE e.id IN (SELECT value FROM json_each($2)) AND e.deleted_at IS NULL",
+        )
+        .bind(tag_id)
+        .bind(&ids)
+        .bind(now())
+        .execute(pool)
+        .await?
+    };
+    Ok(changed.rows_affected())
+}
+
+/// Expenses dated `start..=end` matching the filter, newest first.
+pub async fn expenses(
+    pool: &SqlitePool,
+    filter: &TagFilter,
+    start: &str,
+    end: &str,
+) -> Result<Vec<ExpenseWithCategory>> {
+    let (ids, needed) = filter.binds()?;
+    Ok(sqlx::query_as::<_, ExpenseWithCategory>(&format!(
+        "{SELECT_WITH_CATEGORY}
+         WHERE e.id IN ({MATCHING})
+           AND substr(e.date, 1, 10) BETWEEN $3 AND $4 AND e.deleted_at IS NULL
+         ORDER BY e.date DESC, e.created_at DESC"
+    ))
+    .bind(ids)
+    .bind(needed)
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Spending per tag dated `start..=end`, largest first, with received
+/// reimbursements left out. An expense with several tags counts towards
+/// each, so the totals don't add up to the spending.
+pub async fn totals(pool: &SqlitePool, start: &str, end: &str) -> Result<Vec<TagTotal>> {
+    Ok(sqlx::query_as::<_, TagTotal>(&format!(
+        "SELECT t.id AS tag_id, t.name, t.color, SUM({NET_AMOUNT}) AS total, COUNT(*) AS count
+         FROM expense_tags et
+         JOIN tags t ON t.id = et.tag_id
+         JOIN expenses e ON e.id = et.expense_id
+         WHERE e.deleted_at IS NULL AND substr(e.date, 1, 10) BETWEEN $1 AND $2
+         GROUP BY t.id
+         ORDER BY total DESC"
+    ))
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Spending matching the filter dated `start..=end` per category, largest
+/// first.
+pub async fn by_category(
+    pool: &SqlitePool,
+    filter: &TagFilter,
+    start: &str,
+    end: &str,
+) -> Result<Vec<TaggedCategoryTotal>> {
+    let (ids, needed) = filter.binds()?;
+    Ok(sqlx::query_as::<_, TaggedCategoryTotal>(&format!(
+        "SELECT e.category_id, c.name AS category_name, SUM({NET_AMOUNT}) AS total,
+                COUNT(*) AS count
+         FROM expenses e
+         LEFT JOIN categories c ON c.id = e.category_id
+         WHERE e.id IN ({MATCHING})
+           AND substr(e.date, 1, 10) BETWEEN $3 AND $4 AND e.deleted_at IS NULL
+         GROUP BY e.category_id
+         ORDER BY total DESC"
+    ))
+    .bind(ids)
+    .bind(needed)
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await?)
+}
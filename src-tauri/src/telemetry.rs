@@ -0,0 +1,24 @@
+//! Queue of product events (`telemetry_events`).
+//!
+//! Events are only ever written here; nothing leaves the device until an
+//! uploader drains the queue and sets `sent_at`. Properties must not carry
+//! amounts, notes or anything else the user typed.
+
+use serde_json::Value;
+use sqlx::SqlitePool;
+
+use crate::db::{new_id, now};
+use crate::error::Result;
+
+pub async fn record(pool: &SqlitePool, name: &str, properties: Value) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO telemetry_events (id, name, properties, created_at) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(new_id())
+    .bind(name)
+    .bind(properties.to_string())
+    .bind(now())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,298 @@
+//! Device-to-device transfer over the local network, for users who don't
+//! want cloud sync but still get a new phone.
+//!
+//! The old device exports an archive encrypted with a one-time key and serves
+//! it once over plain HTTP. Its QR code carries the address, a path token and
+//! the key; the new device downloads the archive and imports it like any
+//! other `.goaldy` file. The key is only in the QR, never on the wire, so the
+//! HTTP leg needs no TLS.
+//!
+//! Transfer and peer pairing codes are also registered as URL schemes, so
+//! scanning one with the phone's camera opens the app ready to receive.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use qrcode::render::svg;
+use qrcode::QrCode;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_deep_link::DeepLinkExt;
+use url::Url;
+
+use crate::archive::import::{self, StagedArchive};
+use crate::archive::{self, Manifest, EXTENSION};
+use crate::db::{new_id, timestamp};
+use crate::error::{Error, Result};
+use crate::sync::peer;
+
+/// Scheme of the payload encoded in the QR code:
+/// `goaldy-transfer://<ip>:<port>/<token>#<key>`.
+pub const SCHEME: &str = "goaldy-transfer";
+
+/// Emitted on the sending device once a transfer is over, with
+/// `{ "sent": bool }`.
+pub const FINISHED_EVENT: &str = "transfer://finished";
+
+/// Emitted with a `ScannedCode` when the app is opened from a code scanned
+/// outside of it.
+pub const SCANNED_EVENT: &str = "transfer://scanned";
+
+/// How long an offer stays open if nobody scans it.
+const LIFETIME: Duration = Duration::from_secs(10 * 60);
+
+/// The offer this device is currently serving, if any, and the last code
+/// the app was opened with.
+#[derive(Default)]
+pub struct Transfers {
+    active: Mutex<Option<Arc<tiny_http::Server>>>,
+    scanned: Mutex<Option<ScannedCode>>,
+}
+
+impl Transfers {
+    fn is_active(&self, server: &Arc<tiny_http::Server>) -> bool {
+        self.active
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|current| Arc::ptr_eq(current, server))
+    }
+
+    fn clear(&self, server: &Arc<tiny_http::Server>) {
+        let mut active = self.active.lock().unwrap();
+        if active
+            .as_ref()
+            .is_some_and(|current| Arc::ptr_eq(current, server))
+        {
+            *active = None;
+        }
+    }
+
+    /// Stop serving; the server thread notices within a second.
+    pub fn cancel(&self) {
+        self.active.lock().unwrap().take();
+    }
+
+    /// The code the app was last opened with, if the frontend hasn't taken
+    /// it yet.
+    pub fn take_scanned(&self) -> Option<ScannedCode> {
+        self.scanned.lock().unwrap().take()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ScannedKind {
+    /// For `receive`.
+    Transfer,
+    /// For `peer::pair`.
+    Pairing,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct ScannedCode {
+    pub kind: ScannedKind,
+    pub payload: String,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct TransferOffer {
+    /// What the QR code encodes.
+    pub payload: String,
+    /// The QR code as an SVG document.
+    pub qr_svg: String,
+    pub expires_at: String,
+    pub manifest: Manifest,
+}
+
+fn transfer_error(e: impl std::fmt::Display) -> Error {
+    Error::Validation(format!("transfer error: {e}"))
+}
+
+/// Export everything and start serving it to the next device that scans
+/// the returned QR code. Replaces any offer still open.
+pub async fn offer(app: &AppHandle, pool: &SqlitePool) -> Result<TransferOffer> {
+    let transfers = app.state::<Transfers>();
+    transfers.cancel();
+
+    let ip = local_ip_address::local_ip()
+        .map_err(|_| Error::Unsupported("Connect to a Wi-Fi network first".to_string()))?;
+
+    let key = new_id();
+    let token = new_id();
+    let scratch = app.path().app_cache_dir()?;
+    std::fs::create_dir_all(&scratch)?;
+    let path = scratch.join(format!("transfer-{token}.{EXTENSION}"));
+    let manifest = archive::export(app, pool, &path, &key).await?;
+
+    let server = match tiny_http::Server::http((ip, 0)) {
+        Ok(server) => Arc::new(server),
+        Err(e) => {
+            std::fs::remove_file(&path)?;
+            return Err(transfer_error(e));
+        }
+    };
+    let port = server
+        .server_addr()
+        .to_ip()
+        .map(|addr| addr.port())
+        .ok_or_else(|| transfer_error("no port"))?;
+
+    let payload = format!("{SCHEME}://{ip}:{port}/{token}#{key}");
+    let qr_svg = QrCode::new(payload.as_bytes())
+        .map_err(transfer_error)?
+        .render::<svg::Color>()
+        .min_dimensions(256, 256)
+        .build();
+
+    *transfers.active.lock().unwrap() = Some(server.clone());
+    let expires_at = timestamp(Utc::now() + LIFETIME);
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let sent = serve(&app, &server, &path, &token);
+        app.state::<Transfers>().clear(&server);
+        if let Err(e) = std::fs::remove_file(&path) {
+            eprintln!("[Transfer] Failed to remove {}: {e}", path.display());
+        }
+        if let Err(e) = app.emit(FINISHED_EVENT, serde_json::json!({ "sent": sent })) {
+            eprintln!("[Transfer] Failed to emit {FINISHED_EVENT}: {e}");
+        }
+    });
+
+    Ok(TransferOffer {
+        payload,
+        qr_svg,
+        expires_at,
+        manifest,
+    })
+}
+
+/// Answer requests until the archive was downloaded once, the offer expired
+/// or it was cancelled. Returns whether the archive went out.
+fn serve(
+    app: &AppHandle,
+    server: &Arc<tiny_http::Server>,
+    path: &std::path::Path,
+    token: &str,
+) -> bool {
+    let deadline = Instant::now() + LIFETIME;
+    let expected = format!("/{token}");
+    while Instant::now() < deadline && app.state::<Transfers>().is_active(server) {
+        let request = match server.recv_timeout(Duration::from_secs(1)) {
+            Ok(Some(request)) => request,
+            Ok(None) => continue,
+            Err(e) => {
+                eprintln!("[Transfer] Server error: {e}");
+                return false;
+            }
+        };
+
+        if *request.method() != tiny_http::Method::Get || request.url() != expected {
+            let _ = request.respond(tiny_http::Response::empty(404));
+            continue;
+        }
+        let file = match std::fs::File::open(path) {
+            Ok(file) => file,
+            Err(e) => {
+                eprintln!("[Transfer] Failed to open archive: {e}");
+                let _ = request.respond(tiny_http::Response::empty(500));
+                return false;
+            }
+        };
+        match request.respond(tiny_http::Response::from_file(file)) {
+            Ok(()) => return true,
+            // The other device dropped mid-download; let it try again.
+            Err(e) => eprintln!("[Transfer] Download interrupted: {e}"),
+        }
+    }
+    false
+}
+
+/// Split a scanned payload into the download URL and the archive key.
+fn parse_payload(payload: &str) -> Result<(String, String)> {
+    let invalid = || Error::Validation("Not a Goaldy transfer code".to_string());
+    let rest = payload
+        .trim()
+        .strip_prefix(SCHEME)
+        .and_then(|rest| rest.strip_prefix("://"))
+        .ok_or_else(invalid)?;
+    let (location, key) = rest.split_once('#').ok_or_else(invalid)?;
+    if key.is_empty() || !location.contains('/') {
+        return Err(invalid());
+    }
+    Ok((format!("http://{location}"), key.to_string()))
+}
+
+/// Download the archive offered by another device and stage it for import.
+/// Finish with `archive::import::apply` as for a file import.
+pub async fn receive(app: &AppHandle, pool: &SqlitePool, payload: &str) -> Result<StagedArchive> {
+    let (url, key) = parse_payload(payload)?;
+    let scratch = app.path().app_cache_dir()?;
+    std::fs::create_dir_all(&scratch)?;
+    let path = scratch.join(format!("transfer-{}.{EXTENSION}", new_id()));
+
+    let download = tauri::async_runtime::spawn_blocking({
+        let path = path.clone();
+        move || -> Result<()> {
+            let response = ureq::get(&url)
+                .timeout(Duration::from_secs(5 * 60))
+                .call()
+                .map_err(|_| {
+                    Error::Validation(
+                        "Could not reach the other device. Are both on the same Wi-Fi?".to_string(),
+                    )
+                })?;
+            let mut file = std::fs::File::create(&path)?;
+            std::io::copy(&mut response.into_reader(), &mut file)?;
+            Ok(())
+        }
+    })
+    .await?;
+
+    let staged = match download {
+        Ok(()) => import::stage(app, pool, &path, &key).await,
+        Err(e) => Err(e),
+    };
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+    staged
+}
+
+/// Hand codes the app is opened with, now and while running, to the
+/// frontend. Receiving waits for the user to confirm and pick an import mode.
+pub fn watch(app: &AppHandle) {
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            open_link(&handle, &url);
+        }
+    });
+    match app.deep_link().get_current() {
+        Ok(urls) => {
+            for url in urls.unwrap_or_default() {
+                open_link(app, &url);
+            }
+        }
+        Err(e) => eprintln!("[Transfer] Could not read the launch URL: {e}"),
+    }
+}
+
+fn open_link(app: &AppHandle, url: &Url) {
+    let kind = match url.scheme() {
+        SCHEME => ScannedKind::Transfer,
+        peer::SCHEME => ScannedKind::Pairing,
+        _ => return,
+    };
+    let code = ScannedCode {
+        kind,
+        payload: url.to_string(),
+    };
+    // Kept for a frontend that isn't listening yet, on a cold start.
+    *app.state::<Transfers>().scanned.lock().unwrap() = Some(code.clone());
+    if let Err(e) = app.emit(SCANNED_EVENT, &code) {
+        eprintln!("[Transfer] Failed to emit {SCANNED_EVENT}: {e}");
+    }
+}
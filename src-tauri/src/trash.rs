@@ -0,0 +1,311 @@
+//! The trash: soft-deleted rows, restoring them, and purging them for good.
+//!
+//! Deletes only set `deleted_at`. A deleted row stays in the trash for the
+//! retention period the user picks and can be restored until it is purged.
+//! Rows that sync are kept a while longer even when the user's period is
+//! shorter, hidden from the trash. That way a late edit from another device,
+//! pulled after the delete, finds the tombstone instead of recreating the
+//! row. A tombstone still waiting in `sync_queue` is never purged.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::accounts;
+use crate::app_meta;
+use crate::category_totals;
+use crate::db::{now, timestamp};
+use crate::error::{Error, Result};
+use crate::sync::{self, worker, Operation};
+
+const RETENTION_KEY: &str = "trash_retention_days";
+
+const DEFAULT_RETENTION_DAYS: u32 = 30;
+
+const MAX_RETENTION_DAYS: u32 = 365;
+
+/// Shortest time a synced row's tombstone is kept.
+const TOMBSTONE_DAYS: u32 = 90;
+
+/// Tables purged, children before the parents they reference.
+const PURGED: &[&str] = &[
+    "savings_contributions",
+    "habit_tracking",
+    "income_entries",
+    "scheduled_notifications",
+    "trials",
+    "expenses",
+    "budgets",
+    "savings_goals",
+    "habit_goals",
+    "recurring_expenses",
+    "income_sources",
+    "account_balances",
+    "feedback_notes",
+    "categories",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashKind {
+    Expense,
+    Budget,
+    SavingsGoal,
+    HabitGoal,
+    Category,
+    FeedbackNote,
+    RecurringExpense,
+}
+
+impl TrashKind {
+    const ALL: [TrashKind; 7] = [
+        TrashKind::Expense,
+        TrashKind::Budget,
+        TrashKind::SavingsGoal,
+        TrashKind::HabitGoal,
+        TrashKind::Category,
+        TrashKind::FeedbackNote,
+        TrashKind::RecurringExpense,
+    ];
+
+    fn table(self) -> &'static str {
+        match self {
+            TrashKind::Expense => "expenses",
+            TrashKind::Budget => "budgets",
+            TrashKind::SavingsGoal => "savings_goals",
+            TrashKind::HabitGoal => "habit_goals",
+            TrashKind::Category => "categories",
+            TrashKind::FeedbackNote => "feedback_notes",
+            TrashKind::RecurringExpense => "recurring_expenses",
+        }
+    }
+
+    /// SQL for the name shown in the trash, and a line of detail.
+    fn describe(self) -> (&'static str, &'static str) {
+        match self {
+            TrashKind::Expense => (
+                "COALESCE(NULLIF(note, ''), 'Expense')",
+                "printf('%.2f on %s', amount, substr(date, 1, 10))",
+            ),
+            TrashKind::Budget => ("'Budget for ' || month", "printf('%.2f', total_amount)"),
+            TrashKind::SavingsGoal => ("name", "printf('%.2f target', target_amount)"),
+            TrashKind::HabitGoal => ("name", "NULL"),
+            TrashKind::Category => ("name", "NULL"),
+            TrashKind::FeedbackNote => ("substr(content, 1, 80)", "NULL"),
+            TrashKind::RecurringExpense => ("name", "printf('%.2f %s', amount, cadence)"),
+        }
+    }
+
+    /// Rows deleted together with one of this kind, by the column pointing
+    /// to it.
+    fn children(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            TrashKind::Expense => &[("attachments", "expense_id")],
+            TrashKind::SavingsGoal => &[("savings_contributions", "goal_id")],
+            TrashKind::HabitGoal => &[("habit_tracking", "habit_goal_id")],
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct TrashedItem {
+    pub kind: TrashKind,
+    pub id: String,
+    pub label: String,
+    pub detail: Option<String>,
+    pub deleted_at: String,
+    /// When it leaves the trash for good.
+    pub purge_after: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, specta::Type)]
+pub struct PurgeReport {
+    /// Rows deleted for good, parents and children alike.
+    pub purged: u64,
+    /// Past retention, but kept as sync tombstones for now.
+    pub kept: u64,
+}
+
+pub async fn retention_days(pool: &SqlitePool) -> Result<u32> {
+    Ok(app_meta::get(pool, RETENTION_KEY)
+        .await?
+        .and_then(|days| days.parse().ok())
+        .unwrap_or(DEFAULT_RETENTION_DAYS))
+}
+
+pub async fn set_retention_days(pool: &SqlitePool, days: u32) -> Result<()> {
+    if !(1..=MAX_RETENTION_DAYS).contains(&days) {
+        return Err(Error::Validation(format!(
+            "Keep deleted items between 1 and {MAX_RETENTION_DAYS} days"
+        )));
+    }
+    app_meta::set(pool, RETENTION_KEY, &days.to_string()).await
+}
+
+fn days_ago(days: u32) -> String {
+    timestamp(Utc::now() - Duration::days(days.into()))
+}
+
+/// Everything in the trash, most recently deleted first.
+pub async fn list(pool: &SqlitePool) -> Result<Vec<TrashedItem>> {
+    let retention = retention_days(pool).await?;
+    let since = days_ago(retention);
+    let mut items = Vec::new();
+    for kind in TrashKind::ALL {
+        let (label, detail) = kind.describe();
+        let rows: Vec<(String, String, Option<String>, String)> = sqlx::query_as(&format!(
+            "SELECT id, {label} AS label, {detail} AS detail, deleted_at
+             FROM {table}
+             WHERE deleted_at IS NOT NULL AND deleted_at >= $1",
+            table = kind.table(),
+        ))
+        .bind(&since)
+        .fetch_all(pool)
+        .await?;
+        items.extend(rows.into_iter().map(|(id, label, detail, deleted_at)| {
+            TrashedItem {
+                kind,
+                purge_after: DateTime::parse_from_rfc3339(&deleted_at)
+                    .map(|at| timestamp(at.to_utc() + Duration::days(retention.into())))
+                    .unwrap_or_default(),
+                id,
+                label,
+                detail,
+                deleted_at,
+            }
+        }));
+    }
+    items.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+    Ok(items)
+}
+
+/// Take a row out of the trash, along with whatever was deleted with it.
+pub async fn restore(pool: &SqlitePool, kind: TrashKind, id: &str) -> Result<()> {
+    let table = kind.table();
+    let mut tx = pool.begin().await?;
+    let row: Option<(Option<String>,)> =
+        sqlx::query_as(&format!("SELECT deleted_at FROM {table} WHERE id = $1"))
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    let Some((Some(deleted_at),)) = row else {
+        return Err(Error::Validation(format!("{id} is not in the trash")));
+    };
+
+    if kind == TrashKind::Budget {
+        let (taken,): (bool,) = sqlx::query_as(
+            "SELECT EXISTS (
+               SELECT 1 FROM budgets
+               WHERE deleted_at IS NULL AND month = (SELECT month FROM budgets WHERE id = $1)
+             )",
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if taken {
+            return Err(Error::Validation(
+                "That period already has a budget".to_string(),
+            ));
+        }
+    }
+
+    let now = now();
+    sqlx::query(&format!(
+        "UPDATE {table} SET deleted_at = NULL, updated_at = $1 WHERE id = $2"
+    ))
+    .bind(&now)
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+    if worker::is_remote(table) {
+        sync::enqueue(&mut tx, table, id, Operation::Update).await?;
+    }
+
+    for (child, column) in kind.children() {
+        let restored: Vec<(String,)> = sqlx::query_as(&format!(
+            "UPDATE {child} SET deleted_at = NULL, updated_at = $1
+             WHERE {column} = $2 AND deleted_at = $3
+             RETURNING id"
+        ))
+        .bind(&now)
+        .bind(id)
+        .bind(&deleted_at)
+        .fetch_all(&mut *tx)
+        .await?;
+        if worker::is_remote(child) {
+            for (child_id,) in restored {
+                sync::enqueue(&mut tx, child, &child_id, Operation::Update).await?;
+            }
+        }
+    }
+    tx.commit().await?;
+
+    if kind == TrashKind::Expense {
+        category_totals::refresh_expense(pool, id).await?;
+        let (account_id,): (Option<String>,) =
+            sqlx::query_as("SELECT account_id FROM expenses WHERE id = $1")
+                .bind(id)
+                .fetch_one(pool)
+                .await?;
+        accounts::refresh(pool, &[account_id]).await?;
+    }
+    Ok(())
+}
+
+/// Tables referencing `table` whose rows block deleting the row they point
+/// to, with the referencing column.
+async fn blocking_references(pool: &SqlitePool, table: &str) -> Result<Vec<(String, String)>> {
+    Ok(sqlx::query_as(
+        "SELECT m.name, f.\"from\"
+         FROM sqlite_master m
+         JOIN pragma_foreign_key_list(m.name) f
+         WHERE m.type = 'table' AND f.\"table\" = $1
+           AND f.on_delete NOT IN ('CASCADE', 'SET NULL')",
+    )
+    .bind(table)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Permanently delete rows that have been in the trash longer than the
+/// retention period.
+pub async fn purge(pool: &SqlitePool) -> Result<PurgeReport> {
+    let retention = retention_days(pool).await?;
+    let mut report = PurgeReport::default();
+    for table in PURGED {
+        let days = if worker::is_remote(table) {
+            retention.max(TOMBSTONE_DAYS)
+        } else {
+            retention
+        };
+        // Still referenced, by a live row or one purged later.
+        let referenced = blocking_references(pool, table)
+            .await?
+            .into_iter()
+            .map(|(child, column)| {
+                format!(" AND id NOT IN (SELECT {column} FROM {child} WHERE {column} IS NOT NULL)")
+            })
+            .collect::<String>();
+        let expired = format!(
+            "deleted_at IS NOT NULL AND deleted_at < $1
+             AND id NOT IN (SELECT record_id FROM sync_queue WHERE table_name = '{table}')"
+        );
+
+        report.purged += sqlx::query(&format!("DELETE FROM {table} WHERE {expired}{referenced}"))
+            .bind(days_ago(days))
+            .execute(pool)
+            .await?
+            .rows_affected();
+        if days > retention {
+            let (kept,): (i64,) = sqlx::query_as(&format!(
+                "SELECT COUNT(*) FROM {table} WHERE deleted_at IS NOT NULL AND deleted_at < $1"
+            ))
+            .bind(days_ago(retention))
+            .fetch_one(pool)
+            .await?;
+            report.kept += kept as u64;
+        }
+    }
+    Ok(report)
+}
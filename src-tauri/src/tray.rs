@@ -0,0 +1,186 @@
+//! The desktop tray icon.
+//!
+//! Its menu shows what is left of this period's budget and where most of
+//! the month's money went, with actions to add an expense, sync and open
+//! the app. The summary is rebuilt whenever a domain event says spending,
+//! the budget or the data as a whole changed, wherever the write happened.
+
+use chrono::Local;
+use sqlx::SqlitePool;
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Emitter, Listener, Manager};
+
+use crate::budgets;
+use crate::category_totals;
+use crate::currency;
+use crate::db::Db;
+use crate::error::Result;
+use crate::periods;
+use crate::quick_add;
+use crate::sync::worker::SyncWorker;
+
+/// Emitted when "Sync now" is picked, for the frontend to pull; the push
+/// is started here.
+pub const SYNC_NOW_EVENT: &str = "tray://sync-now";
+
+const TRAY_ID: &str = "main";
+/// How many categories the menu lists.
+const TOP_CATEGORIES: usize = 3;
+
+/// Events after which the summary may be out of date.
+const REFRESH_ON: &[&str] = &[
+    "expense:created",
+    "expense:updated",
+    "expense:deleted",
+    "expense:imported",
+    "expense:bulk_changed",
+    "expense:reimbursement_updated",
+    "budget:updated",
+    "budget:settings_updated",
+    "currency:base_changed",
+    "data:restored",
+    "trash:restored",
+    "sync:applied_remote_changes",
+    "sync:remote_kept",
+];
+
+struct Summary {
+    /// E.g. "412.30 EUR left of 1500.00".
+    budget: String,
+    /// E.g. "Groceries: 120.00".
+    categories: Vec<String>,
+}
+
+async fn summary(pool: &SqlitePool) -> Result<Summary> {
+    let today = Local::now().date_naive();
+    let schedule = periods::schedule(pool).await?;
+    let status = budgets::status(pool, &schedule, today).await?;
+    let code = currency::base(pool).await?;
+    let budget = match (&status.budget, status.remaining) {
+        (Some(budget), Some(remaining)) if remaining >= 0.0 => {
+            format!("{remaining:.2} {code} left of {:.2}", budget.limit())
+        }
+        (Some(_), Some(remaining)) => format!("{:.2} {code} over budget", -remaining),
+        _ => format!("{:.2} {code} spent, no budget set", status.spent),
+    };
+    let month = today.format("%Y-%m").to_string();
+    let categories = category_totals::for_month(pool, &month)
+        .await?
+        .into_iter()
+        .filter(|t| t.total > 0.0)
+        .take(TOP_CATEGORIES)
+        .map(|t| {
+            let name = t
+                .category_name
+                .unwrap_or_else(|| "Uncategorized".to_string());
+            format!("{name}: {:.2}", t.total)
+        })
+        .collect();
+    Ok(Summary { budget, categories })
+}
+
+fn menu(app: &AppHandle, summary: Option<&Summary>) -> tauri::Result<Menu<tauri::Wry>> {
+    let menu = Menu::new(app)?;
+    if let Some(summary) = summary {
+        menu.append(&MenuItem::new(app, &summary.budget, false, None::<&str>)?)?;
+        for line in &summary.categories {
+            menu.append(&MenuItem::new(app, line, false, None::<&str>)?)?;
+        }
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
+    menu.append(&MenuItem::with_id(
+        app,
+        "add",
+        "Add expense…",
+        true,
+        None::<&str>,
+    )?)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        "sync",
+        "Sync now",
+        true,
+        None::<&str>,
+    )?)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        "open",
+        "Open Goaldy",
+        true,
+        None::<&str>,
+    )?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?)?;
+    Ok(menu)
+}
+
+fn on_menu(app: &AppHandle, id: &str) {
+    let result = match id {
+        "add" => quick_add::window::show(app),
+        "sync" => {
+            app.state::<SyncWorker>().wake();
+            app.emit(SYNC_NOW_EVENT, ()).map_err(Into::into)
+        }
+        "open" => match app.get_webview_window("main") {
+            Some(window) => window
+                .show()
+                .and_then(|()| window.set_focus())
+                .map_err(Into::into),
+            None => Ok(()),
+        },
+        "quit" => {
+            app.exit(0);
+            Ok(())
+        }
+        _ => Ok(()),
+    };
+    if let Err(e) = result {
+        eprintln!("[Tray] {id} failed: {e}");
+    }
+}
+
+/// Show the tray icon and keep its summary current.
+pub fn setup(app: &AppHandle) -> Result<()> {
+    let mut tray = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("Goaldy")
+        .menu(&menu(app, None)?)
+        .on_menu_event(|app, event| on_menu(app, event.id.as_ref()));
+    if let Some(icon) = app.default_window_icon() {
+        tray = tray.icon(icon.clone());
+    }
+    tray.build(app)?;
+
+    for event in REFRESH_ON {
+        let handle = app.clone();
+        app.listen_any(*event, move |_| refresh(&handle));
+    }
+    refresh(app);
+    Ok(())
+}
+
+/// Rebuild the summary in the background.
+pub fn refresh(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let db = app.state::<Db>();
+        let summary = match summary(db.pool()).await {
+            Ok(summary) => summary,
+            // Likely the first launch before migrations; the next event retries.
+            Err(e) => {
+                eprintln!("[Tray] Failed to build the summary: {e}");
+                return;
+            }
+        };
+        let Some(tray) = app.tray_by_id(TRAY_ID) else {
+            return;
+        };
+        let result = menu(&app, Some(&summary)).and_then(|menu| {
+            tray.set_menu(Some(menu))?;
+            tray.set_tooltip(Some(format!("Goaldy: {}", summary.budget)))
+        });
+        if let Err(e) = result {
+            eprintln!("[Tray] Failed to update the menu: {e}");
+        }
+    });
+}
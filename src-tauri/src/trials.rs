@@ -0,0 +1,179 @@
+//! Free trials: a €0 signup that turns into a paid subscription unless the
+//! user cancels in time.
+//!
+//! Creating a trial queues a reminder in `scheduled_notifications` a few days
+//! before it converts; the frontend notification scheduler delivers it.
+
+use chrono::{Days, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::db::{new_id, now, timestamp};
+use crate::error::{Error, Result};
+use crate::notify;
+
+/// How long before the trial ends the reminder goes out.
+const REMINDER_DAYS_BEFORE: u64 = 3;
+
+/// Local hour the reminder is sent at.
+const REMINDER_HOUR: u32 = 9;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct Trial {
+    pub id: String,
+    pub user_id: Option<String>,
+    pub name: String,
+    /// The €0 signup expense, if the trial was logged as one.
+    pub expense_id: Option<String>,
+    pub amount_after_trial: Option<f64>,
+    pub trial_ends_on: String,
+    pub reminder_id: Option<String>,
+    pub cancelled_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub deleted_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize, specta::Type)]
+pub struct NewTrial {
+    pub name: String,
+    /// "YYYY-MM-DD", the first day the user gets charged.
+    pub trial_ends_on: String,
+    pub amount_after_trial: Option<f64>,
+    pub expense_id: Option<String>,
+}
+
+/// Trials that haven't been cancelled, ending soonest first.
+pub async fn list(pool: &SqlitePool) -> Result<Vec<Trial>> {
+    let rows = sqlx::query_as::<_, Trial>(
+        "SELECT * FROM trials
+         WHERE deleted_at IS NULL AND cancelled_at IS NULL
+         ORDER BY trial_ends_on ASC",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// When to remind: a few days ahead at a friendly hour, or right away when
+/// the trial ends sooner than that.
+fn reminder_time(ends_on: NaiveDate) -> String {
+    let now = Utc::now();
+    let at = ends_on
+        .checked_sub_days(Days::new(REMINDER_DAYS_BEFORE))
+        .and_then(|day| day.and_hms_opt(REMINDER_HOUR, 0, 0))
+        .and_then(|t| t.and_local_timezone(Local).earliest())
+        .map_or(now, |t| t.with_timezone(&Utc));
+    timestamp(at.max(now))
+}
+
+fn reminder_body(name: &str, ends_on: NaiveDate, amount: Option<f64>) -> String {
+    let day = ends_on.format("%b %-d");
+    match amount {
+        Some(amount) => format!(
+            "Your {name} trial ends on {day} and then costs {amount:.2}. Cancel before then if you don't want to keep it."
+        ),
+        None => format!(
+            "Your {name} trial ends on {day}. Cancel before then if you don't want to pay for it."
+        ),
+    }
+}
+
+pub async fn create(pool: &SqlitePool, user_id: Option<&str>, input: NewTrial) -> Result<Trial> {
+    let name = input.name.trim();
+    if name.is_empty() {
+        return Err(Error::Validation("Name is required".to_string()));
+    }
+    let ends_on = NaiveDate::parse_from_str(&input.trial_ends_on, "%Y-%m-%d")
+        .map_err(|_| Error::Validation("Trial end date must be YYYY-MM-DD".to_string()))?;
+    if ends_on < Local::now().date_naive() {
+        return Err(Error::Validation(
+            "Trial end date is in the past".to_string(),
+        ));
+    }
+    if input.amount_after_trial.is_some_and(|a| a < 0.0) {
+        return Err(Error::Validation(
+            "Amount after the trial can't be negative".to_string(),
+        ));
+    }
+    if let Some(expense_id) = &input.expense_id {
+        let amount: Option<(f64,)> =
+            sqlx::query_as("SELECT amount FROM expenses WHERE id = $1 AND deleted_at IS NULL")
+                .bind(expense_id)
+                .fetch_optional(pool)
+                .await?;
+        match amount {
+            None => return Err(Error::Validation("Expense not found".to_string())),
+            Some((amount,)) if amount != 0.0 => {
+                return Err(Error::Validation(
+                    "Only a free signup can start a trial".to_string(),
+                ))
+            }
+            Some(_) => {}
+        }
+    }
+
+    let reminder_id = notify::schedule(
+        pool,
+        "trial_ending",
+        &format!("{name} trial ends soon"),
+        &reminder_body(name, ends_on, input.amount_after_trial),
+        &reminder_time(ends_on),
+    )
+    .await?;
+
+    let id = new_id();
+    let now = now();
+    sqlx::query(
+        "INSERT INTO trials (id, user_id, name, expense_id, amount_after_trial, trial_ends_on, reminder_id, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+    )
+    .bind(&id)
+    .bind(user_id)
+    .bind(name)
+    .bind(&input.expense_id)
+    .bind(input.amount_after_trial)
+    .bind(&input.trial_ends_on)
+    .bind(&reminder_id)
+    .bind(&now)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+
+    Ok(Trial {
+        id,
+        user_id: user_id.map(str::to_string),
+        name: name.to_string(),
+        expense_id: input.expense_id,
+        amount_after_trial: input.amount_after_trial,
+        trial_ends_on: input.trial_ends_on,
+        reminder_id: Some(reminder_id),
+        cancelled_at: None,
+        created_at: now.clone(),
+        updated_at: now,
+        deleted_at: None,
+    })
+}
+
+/// The user cancelled the subscription in time: stop reminding them.
+pub async fn cancel(pool: &SqlitePool, id: &str) -> Result<()> {
+    let reminder: Option<(Option<String>,)> =
+        sqlx::query_as("SELECT reminder_id FROM trials WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+    let Some((reminder_id,)) = reminder else {
+        return Err(Error::Validation("Trial not found".to_string()));
+    };
+    if let Some(reminder_id) = reminder_id {
+        notify::cancel(pool, &reminder_id).await?;
+    }
+
+    let now = now();
+    sqlx::query("UPDATE trials SET cancelled_at = $1, updated_at = $1 WHERE id = $2")
+        .bind(&now)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,146 @@
+//! Sending queued webhook deliveries.
+//!
+//! Every POST carries `X-Goaldy-Event`, `X-Goaldy-Delivery`,
+//! `X-Goaldy-Timestamp` (Unix seconds) and `X-Goaldy-Signature:
+//! sha256=<hex>`, the HMAC-SHA256 of `<timestamp>.<body>` keyed with the
+//! webhook's secret. Any 2xx answer counts as delivered. Anything else is
+//! retried on the sync queue's schedule (`sync::backoff`) until
+//! `MAX_ATTEMPTS`, then given up on.
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use chrono::Utc;
+use ring::hmac;
+use sqlx::SqlitePool;
+
+use crate::db::{now, timestamp};
+use crate::error::Result;
+use crate::sync::backoff;
+
+const MAX_ATTEMPTS: i64 = 8;
+
+/// Set while a pass runs, so triggers and the job never send twice.
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+struct RunningGuard;
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        RUNNING.store(false, Ordering::Release);
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct Due {
+    id: String,
+    event: String,
+    payload: String,
+    attempts: i64,
+    url: String,
+    secret: String,
+}
+
+/// The HTTP status, if the server answered, and what went wrong.
+type Failure = (Option<u16>, String);
+
+fn signature(secret: &str, timestamp: i64, body: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let tag = hmac::sign(&key, format!("{timestamp}.{body}").as_bytes());
+    let mut hex = String::with_capacity(7 + tag.as_ref().len() * 2);
+    hex.push_str("sha256=");
+    for byte in tag.as_ref() {
+        let _ = write!(hex, "{byte:02x}");
+    }
+    hex
+}
+
+/// Blocking. POST one delivery.
+fn post(due: &Due) -> std::result::Result<u16, Failure> {
+    let sent_at = Utc::now().timestamp();
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(10))
+        .timeout(Duration::from_secs(30))
+        .build();
+    let response = agent
+        .post(&due.url)
+        .set("Content-Type", "application/json")
+        .set("User-Agent", "Goaldy-Webhooks")
+        .set("X-Goaldy-Event", &due.event)
+        .set("X-Goaldy-Delivery", &due.id)
+        .set("X-Goaldy-Timestamp", &sent_at.to_string())
+        .set(
+            "X-Goaldy-Signature",
+            &signature(&due.secret, sent_at, &due.payload),
+        )
+        .send_string(&due.payload);
+    match response {
+        Ok(response) => Ok(response.status()),
+        Err(ureq::Error::Status(code, _)) => Err((Some(code), format!("answered {code}"))),
+        Err(ureq::Error::Transport(e)) => Err((None, format!("unreachable: {e}"))),
+    }
+}
+
+/// Send every delivery that is due. Returns how many went through.
+pub async fn deliver_due(pool: &SqlitePool) -> Result<usize> {
+    if RUNNING.swap(true, Ordering::AcqRel) {
+        return Ok(0);
+    }
+    let _guard = RunningGuard;
+
+    let due = sqlx::query_as::<_, Due>(
+        "SELECT d.id, d.event, d.payload, d.attempts, w.url, w.secret
+         FROM webhook_deliveries d JOIN webhooks w ON w.id = d.webhook_id
+         WHERE d.delivered_at IS NULL AND d.failed_at IS NULL AND d.next_attempt_at <= $1
+         ORDER BY d.created_at ASC",
+    )
+    .bind(now())
+    .fetch_all(pool)
+    .await?;
+
+    let mut delivered = 0;
+    for due in due {
+        let attempts = due.attempts + 1;
+        let id = due.id.clone();
+        let result = tauri::async_runtime::spawn_blocking(move || post(&due)).await?;
+        match result {
+            Ok(status) => {
+                delivered += 1;
+                sqlx::query(
+                    "UPDATE webhook_deliveries
+                     SET attempts = $1, delivered_at = $2, last_status = $3, last_error = NULL
+                     WHERE id = $4",
+                )
+                .bind(attempts)
+                .bind(now())
+                .bind(status as i64)
+                .bind(&id)
+                .execute(pool)
+                .await?;
+            }
+            Err((status, error)) => {
+                let given_up = attempts >= MAX_ATTEMPTS;
+                let next_attempt_at = timestamp(
+                    Utc::now()
+                        + chrono::Duration::from_std(backoff::delay(attempts)).unwrap_or_default(),
+                );
+                sqlx::query(
+                    "UPDATE webhook_deliveries
+                     SET attempts = $1, next_attempt_at = $2, failed_at = $3,
+                         last_status = $4, last_error = $5
+                     WHERE id = $6",
+                )
+                .bind(attempts)
+                .bind(next_attempt_at)
+                .bind(given_up.then(now))
+                .bind(status.map(i64::from))
+                .bind(&error)
+                .bind(&id)
+                .execute(pool)
+                .await?;
+            }
+        }
+    }
+    Ok(delivered)
+}
@@ -0,0 +1,351 @@
+//! Webhooks: a signed JSON POST to user-configured URLs when something
+//! worth acting on happens.
+//!
+//! Each webhook picks the events it wants. Triggering one only queues a
+//! delivery per interested webhook; `delivery` sends them right away and
+//! retries failures with a growing delay. An occurrence is delivered at
+//! most once per webhook, by the key it is triggered with, so the budget
+//! crossing its limit again after an edit doesn't fire twice.
+
+mod delivery;
+
+use base64::prelude::{Engine, BASE64_URL_SAFE_NO_PAD};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+use url::{Host, Url};
+
+use crate::db::{new_id, now};
+use crate::error::{Error, Result};
+
+pub use delivery::deliver_due;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+pub enum WebhookEvent {
+    /// Spending went over the period's budget or a category's allocation.
+    #[serde(rename = "budget.exceeded")]
+    BudgetExceeded,
+    /// A savings goal passed 25, 50, 75 or 100 percent of its target.
+    #[serde(rename = "goal.milestone_reached")]
+    GoalMilestone,
+    /// Last month's contributions are waiting to be checked in.
+    #[serde(rename = "checkin.due")]
+    CheckinDue,
+    /// Sent only by "Send test".
+    #[serde(rename = "webhook.test")]
+    Test,
+}
+
+impl WebhookEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::BudgetExceeded => "budget.exceeded",
+            Self::GoalMilestone => "goal.milestone_reached",
+            Self::CheckinDue => "checkin.due",
+            Self::Test => "webhook.test",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    /// Key of the `X-Goaldy-Signature` HMAC, for the receiver to check.
+    pub secret: String,
+    pub events: Vec<WebhookEvent>,
+    pub enabled: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct WebhookRow {
+    id: String,
+    url: String,
+    secret: String,
+    events: String,
+    enabled: bool,
+    created_at: String,
+    updated_at: String,
+}
+
+impl WebhookRow {
+    fn into_webhook(self) -> Webhook {
+        Webhook {
+            // Names this build doesn't know are dropped.
+            events: serde_json::from_str::<Vec<Value>>(&self.events)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|e| serde_json::from_value(e).ok())
+                .collect(),
+            id: self.id,
+            url: self.url,
+            secret: self.secret,
+            enabled: self.enabled,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, specta::Type)]
+pub struct WebhookInput {
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, specta::Type)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub webhook_id: String,
+    pub event: String,
+    pub attempts: i64,
+    pub next_attempt_at: String,
+    pub delivered_at: Option<String>,
+    /// Set once it was given up on.
+    pub failed_at: Option<String>,
+    /// HTTP status of the last attempt, if the server answered.
+    pub last_status: Option<i64>,
+    pub last_error: Option<String>,
+    pub created_at: String,
+}
+
+const SELECT_WEBHOOK: &str =
+    "SELECT id, url, secret, events, enabled, created_at, updated_at FROM webhooks";
+
+/// Deliveries listed per webhook.
+const DELIVERY_LIMIT: i64 = 50;
+
+fn check_input(input: &WebhookInput) -> Result<String> {
+    let url = Url::parse(input.url.trim())
+        .map_err(|_| Error::Validation(format!("\"{}\" is not a URL", input.url)))?;
+    // Plain HTTP only to this machine, for testing a local receiver.
+    let secure = match url.scheme() {
+        "https" => true,
+        "http" => is_loopback(&url),
+        _ => false,
+    };
+    if !secure {
+        return Err(Error::Validation(
+            "Webhook URLs must start with https:// (http:// only for localhost)".to_string(),
+        ));
+    }
+    if input.events.is_empty() {
+        return Err(Error::Validation("Pick at least one event".to_string()));
+    }
+    Ok(url.to_string())
+}
+
+fn is_loopback(url: &Url) -> bool {
+    match url.host() {
+        Some(Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+fn events_json(events: &[WebhookEvent]) -> String {
+    serde_json::to_string(events).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub async fn list(pool: &SqlitePool) -> Result<Vec<Webhook>> {
+    Ok(
+        sqlx::query_as::<_, WebhookRow>(&format!("{SELECT_WEBHOOK} ORDER BY created_at ASC"))
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(WebhookRow::into_webhook)
+            .collect(),
+    )
+}
+
+async fn get(pool: &SqlitePool, id: &str) -> Result<Webhook> {
+    sqlx::query_as::<_, WebhookRow>(&format!("{SELECT_WEBHOOK} WHERE id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .map(WebhookRow::into_webhook)
+        .ok_or_else(|| Error::Validation("Webhook not found".to_string()))
+}
+
+/// Add a webhook with a new signing secret.
+pub async fn create(pool: &SqlitePool, input: WebhookInput) -> Result<Webhook> {
+    let url = check_input(&input)?;
+    let id = new_id();
+    let now = now();
+    sqlx::query(
+        "INSERT INTO webhooks (id, url, secret, events, enabled, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $6)",
+    )
+    .bind(&id)
+    .bind(url)
+    .bind(BASE64_URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>()))
+    .bind(events_json(&input.events))
+    .bind(input.enabled)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+    get(pool, &id).await
+}
+
+pub async fn update(pool: &SqlitePool, id: &str, input: WebhookInput) -> Result<Webhook> {
+    let url = check_input(&input)?;
+    let updated = sqlx::query(
+        "UPDATE webhooks SET url = $1, events = $2, enabled = $3, updated_at = $4 WHERE id = $5",
+    )
+    .bind(url)
+    .bind(events_json(&input.events))
+    .bind(input.enabled)
+    .bind(now())
+    .bind(id)
+    .execute(pool)
+    .await?;
+    if updated.rows_affected() == 0 {
+        return Err(Error::Validation("Webhook not found".to_string()));
+    }
+    get(pool, id).await
+}
+
+/// Remove a webhook and its deliveries, sent or not.
+pub async fn delete(pool: &SqlitePool, id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM webhooks WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// The most recent deliveries of a webhook, newest first.
+pub async fn deliveries(pool: &SqlitePool, webhook_id: &str) -> Result<Vec<WebhookDelivery>> {
+    Ok(sqlx::query_as::<_, WebhookDelivery>(
+        "SELECT id, webhook_id, event, attempts, next_attempt_at, delivered_at, failed_at,
+                last_status, last_error, created_at
+         FROM webhook_deliveries WHERE webhook_id = $1
+         ORDER BY created_at DESC LIMIT $2",
+    )
+    .bind(webhook_id)
+    .bind(DELIVERY_LIMIT)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Queue a test delivery to one webhook, whatever its events.
+pub async fn send_test(pool: &SqlitePool, id: &str) -> Result<()> {
+    let webhook = get(pool, id).await?;
+    queue(
+        pool,
+        &webhook.id,
+        WebhookEvent::Test,
+        None,
+        json!({ "message": "Webhook test from Goaldy" }),
+    )
+    .await?;
+    spawn_delivery(pool);
+    Ok(())
+}
+
+async fn queue(
+    pool: &SqlitePool,
+    webhook_id: &str,
+    event: WebhookEvent,
+    key: Option<&str>,
+    data: Value,
+) -> Result<()> {
+    let id = new_id();
+    let now = now();
+    let payload = json!({
+        "id": id,
+        "event": event.as_str(),
+        "created_at": now,
+        "data": data,
+    });
+    sqlx::query(
+        "INSERT OR IGNORE INTO webhook_deliveries
+            (id, webhook_id, event, event_key, payload, next_attempt_at, created_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $6)",
+    )
+    .bind(&id)
+    .bind(webhook_id)
+    .bind(event.as_str())
+    .bind(key)
+    .bind(payload.to_string())
+    .bind(&now)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+fn spawn_delivery(pool: &SqlitePool) {
+    let pool = pool.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = deliver_due(&pool).await {
+            eprintln!("[Webhooks] Delivery failed: {e}");
+        }
+    });
+}
+
+/// Queue `event` for every enabled webhook that wants it and start sending.
+/// `key` names the occurrence, so triggering it again delivers nothing new.
+/// Webhooks are a side effect of whatever happened, so failures are only
+/// logged.
+pub async fn trigger(pool: &SqlitePool, event: WebhookEvent, key: &str, data: Value) {
+    let result = async {
+        let mut queued = false;
+        for webhook in list(pool).await? {
+            if webhook.enabled && webhook.events.contains(&event) {
+                queue(pool, &webhook.id, event, Some(key), data.clone()).await?;
+                queued = true;
+            }
+        }
+        Ok::<_, Error>(queued)
+    }
+    .await;
+    match result {
+        Ok(true) => spawn_delivery(pool),
+        Ok(false) => {}
+        Err(e) => eprintln!("[Webhooks] Failed to queue {}: {e}", event.as_str()),
+    }
+}
+
+/// Trigger `goal.milestone_reached` if saving took the goal from `before`
+/// across a milestone.
+pub async fn check_goal_milestone(pool: &SqlitePool, goal_id: &str, before: f64) {
+    let goal = match crate::goals::get(pool, goal_id).await {
+        Ok(Some(goal)) if goal.target_amount > 0.0 => goal,
+        Ok(_) => return,
+        Err(e) => {
+            eprintln!("[Webhooks] Failed to load goal {goal_id}: {e}");
+            return;
+        }
+    };
+    let saved = match crate::goals::total_saved(pool, goal_id).await {
+        Ok(saved) => saved,
+        Err(e) => {
+            eprintln!("[Webhooks] Failed to total goal {goal_id}: {e}");
+            return;
+        }
+    };
+    let percent = |amount: f64| amount / goal.target_amount * 100.0;
+    let Some(milestone) = [100, 75, 50, 25]
+        .into_iter()
+        .find(|&m| percent(before) < m as f64 && percent(saved) >= m as f64)
+    else {
+        return;
+    };
+    trigger(
+        pool,
+        WebhookEvent::GoalMilestone,
+        &format!("goal_milestone:{goal_id}:{milestone}"),
+        json!({
+            "goal_id": goal.id,
+            "name": goal.name,
+            "milestone": milestone,
+            "saved": saved,
+            "target_amount": goal.target_amount,
+        }),
+    )
+    .await;
+}
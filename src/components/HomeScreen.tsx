@@ -1,5 +1,6 @@
 import { useSync } from "@/contexts/SyncContext";
 import { addExpense, deleteExpense, getCategories, getExpensesForMonth, getMonthlySpending } from "@/lib/database";
+import { daysLeft, getCurrentPeriod, type BudgetPeriod } from "@/lib/periods";
 import { formatCurrency, type Budget, type Category, type ExpenseWithCategory } from "@/lib/types";
 import { cn } from "@/lib/utils";
 import { ChevronDown, ChevronUp } from "lucide-react";
@@ -10,6 +11,11 @@ import { ExpenseList } from "./ExpenseList";
 import { Numpad } from "./Numpad";
 import { OfflineBanner } from "./OfflineBanner";
 
+function formatDay(date: string): string {
+  const [y, m, d] = date.split('-').map(Number);
+  return new Date(y, m - 1, d).toLocaleDateString('en-US', { month: 'short', day: 'numeric' });
+}
+
 interface HomeScreenProps {
   budget: Budget;
   onEditBudget: () => void;
@@ -22,20 +28,23 @@ export function HomeScreen({ budget, onEditBudget }: HomeScreenProps) {
   const [categories, setCategories] = useState<Category[]>([]);
   const [expenses, setExpenses] = useState<ExpenseWithCategory[]>([]);
   const [totalSpent, setTotalSpent] = useState(0);
+  const [period, setPeriod] = useState<BudgetPeriod | null>(null);
   const [isLoading, setIsLoading] = useState(false);
   const [showCategories, setShowCategories] = useState(false);
   const [showExpenses, setShowExpenses] = useState(false);
 
   const loadData = useCallback(async () => {
     try {
-      const [cats, exps, spent] = await Promise.all([
+      const [cats, exps, spent, current] = await Promise.all([
         getCategories(),
         getExpensesForMonth(),
         getMonthlySpending(),
+        getCurrentPeriod(),
       ]);
       setCategories(cats);
       setExpenses(exps);
       setTotalSpent(spent);
+      setPeriod(current);
     } catch (error) {
       console.error('Failed to load data:', error);
     }
@@ -48,10 +57,7 @@ export function HomeScreen({ budget, onEditBudget }: HomeScreenProps) {
   const remaining = (budget.spending_limit ?? budget.total_amount) - totalSpent;
   const percentUsed = totalSpent / (budget.spending_limit ?? budget.total_amount);
 
-  // Calculate days remaining in month
-  const now = new Date();
-  const lastDay = new Date(now.getFullYear(), now.getMonth() + 1, 0).getDate();
-  const daysRemaining = lastDay - now.getDate() + 1;
+  const daysRemaining = period ? daysLeft(period) : 0;
 
   const handleAddExpense = async () => {
     const value = parseFloat(amount);
@@ -88,7 +94,10 @@ export function HomeScreen({ budget, onEditBudget }: HomeScreenProps) {
   };
 
   // Get month name for display
-  const monthName = new Date().toLocaleDateString('en-US', { month: 'long' });
+  // Calendar month name, or the pay period's dates
+  const monthName = period && period.key.length > 7
+    ? `${formatDay(period.start)} – ${formatDay(period.end)}`
+    : new Date().toLocaleDateString('en-US', { month: 'long' });
 
   return (
     <div className="min-h-screen flex flex-col bg-background">
@@ -0,0 +1,75 @@
+import { CategorySelector } from "@/components/CategorySelector";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { getCategories } from "@/lib/database";
+import { closeQuickAddWindow, quickAddExpense } from "@/lib/quick-add";
+import type { Category } from "@/lib/types";
+import { useEffect, useRef, useState } from "react";
+
+/**
+ * The always-on-top window opened by the global shortcut: amount and
+ * category, Enter to save, Escape to close.
+ */
+export function QuickAddWindow() {
+  const [categories, setCategories] = useState<Category[]>([]);
+  const [amount, setAmount] = useState("");
+  const [categoryId, setCategoryId] = useState<string | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [saving, setSaving] = useState(false);
+  const amountRef = useRef<HTMLInputElement>(null);
+
+  useEffect(() => {
+    getCategories().then(setCategories).catch((e) => setError(String(e)));
+    const reset = () => {
+      setAmount("");
+      setCategoryId(null);
+      setError(null);
+      amountRef.current?.focus();
+    };
+    window.addEventListener("focus", reset);
+    return () => window.removeEventListener("focus", reset);
+  }, []);
+
+  const save = async () => {
+    const value = parseFloat(amount.replace(",", "."));
+    if (!(value > 0)) {
+      setError("Enter an amount");
+      return;
+    }
+    setSaving(true);
+    try {
+      await quickAddExpense(value, categoryId);
+    } catch (e) {
+      setError(String(e));
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  return (
+    <div
+      className="flex h-screen flex-col gap-3 bg-background p-4"
+      onKeyDown={(e) => {
+        if (e.key === "Escape") closeQuickAddWindow();
+        if (e.key === "Enter") save();
+      }}
+    >
+      <Input
+        ref={amountRef}
+        autoFocus
+        inputMode="decimal"
+        placeholder="0.00"
+        value={amount}
+        onChange={(e) => setAmount(e.target.value)}
+        className="text-2xl"
+      />
+      <div className="flex-1 overflow-y-auto">
+        <CategorySelector categories={categories} selected={categoryId} onSelect={setCategoryId} />
+      </div>
+      {error && <p className="text-sm text-destructive">{error}</p>}
+      <Button onClick={save} disabled={saving}>
+        Add expense
+      </Button>
+    </div>
+  );
+}
@@ -2,7 +2,7 @@ import { AppHeader } from "@/components/AppHeader";
 import { Button } from "@/components/ui/button";
 import { Card, CardContent } from "@/components/ui/card";
 import { Input } from "@/components/ui/input";
-import { addContribution, getContributionForMonth, getSavingsGoal } from "@/lib/database";
+import { addContribution, getContributionForMonth, getPendingCheckins, getSavingsGoal } from "@/lib/database";
 import { formatCurrency, type SavingsContribution, type SavingsGoal } from "@/lib/types";
 import { Check, Sparkles, X } from "lucide-react";
 import { useCallback, useEffect, useRef, useState } from "react";
@@ -32,6 +32,7 @@ export function MonthlyCheckIn({ goalId, onComplete, onBack }: MonthlyCheckInPro
   const [existingContribution, setExistingContribution] = useState<SavingsContribution | null>(null);
   const [step, setStep] = useState<CheckInStep | null>(null);
   const [partialAmount, setPartialAmount] = useState('');
+  const [suggestedAmount, setSuggestedAmount] = useState<number | null>(null);
   const [isLoading, setIsLoading] = useState(true);
   const [isSaving, setIsSaving] = useState(false);
   const [showConfetti, setShowConfetti] = useState(false);
@@ -40,12 +41,15 @@ export function MonthlyCheckIn({ goalId, onComplete, onBack }: MonthlyCheckInPro
   const loadGoal = useCallback(async () => {
     try {
       const currentMonth = getCurrentMonth();
-      const [goalData, existingContrib] = await Promise.all([
+      const [goalData, existingContrib, pending] = await Promise.all([
         getSavingsGoal(goalId),
         getContributionForMonth(goalId, currentMonth),
+        getPendingCheckins(currentMonth),
       ]);
       setGoal(goalData);
       setExistingContribution(existingContrib);
+      const checkin = pending.find((c) => c.kind === 'savings_goal' && c.goal_id === goalId);
+      setSuggestedAmount(checkin ? checkin.suggested_amount : null);
 
       // If there's an existing contribution, go straight to the input field
       if (existingContrib) {
@@ -196,7 +200,9 @@ export function MonthlyCheckIn({ goalId, onComplete, onBack }: MonthlyCheckInPro
             <p className="text-muted-foreground mb-8">
               {isUpdating
                 ? `Previously recorded: ${formatCurrency(existingContribution.amount)}`
-                : 'Every bit counts toward your goal!'}
+                : suggestedAmount !== null && suggestedAmount !== goal.monthly_contribution
+                  ? `${formatCurrency(suggestedAmount)} would keep you on plan.`
+                  : 'Every bit counts toward your goal!'}
             </p>
 
             <div className="w-full max-w-sm space-y-4">
@@ -212,7 +218,7 @@ export function MonthlyCheckIn({ goalId, onComplete, onBack }: MonthlyCheckInPro
                   min="0"
                   value={partialAmount}
                   onChange={(e) => setPartialAmount(e.target.value)}
-                  placeholder={isUpdating ? existingContribution.amount.toString() : '0'}
+                  placeholder={isUpdating ? existingContribution.amount.toString() : String(suggestedAmount ?? 0)}
                   className="pl-12 text-2xl h-14 text-center"
                   disabled={isSaving}
                 />
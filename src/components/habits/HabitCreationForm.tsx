@@ -13,7 +13,7 @@ interface HabitCreationFormProps {
   onBack: () => void;
 }
 
-type RuleType = 'max_amount' | 'max_percentage' | 'reduce_by';
+type RuleType = 'max_amount' | 'max_percentage' | 'reduce_by' | 'no_spend' | 'max_count';
 
 const ruleTypeOptions: { value: RuleType; label: string; description: string }[] = [
   {
@@ -31,6 +31,16 @@ const ruleTypeOptions: { value: RuleType; label: string; description: string }[]
     label: 'Reduce By',
     description: 'Reduce spending by a percentage compared to last month',
   },
+  {
+    value: 'no_spend',
+    label: 'No Spend',
+    description: 'Spend nothing at all in this category for the month',
+  },
+  {
+    value: 'max_count',
+    label: 'Maximum Purchases',
+    description: 'Limit how many times per month you buy in this category',
+  },
 ];
 
 export function HabitCreationForm({ onHabitCreated, onBack }: HabitCreationFormProps) {
@@ -57,7 +67,7 @@ export function HabitCreationForm({ onHabitCreated, onBack }: HabitCreationFormP
 
   // Auto-generate name based on category and rule
   useEffect(() => {
-    if (categoryId && ruleValue) {
+    if (categoryId && (ruleValue || ruleType === 'no_spend')) {
       const category = categories.find(c => c.id === categoryId);
       if (category) {
         let generatedName = '';
@@ -71,6 +81,12 @@ export function HabitCreationForm({ onHabitCreated, onBack }: HabitCreationFormP
           case 'reduce_by':
             generatedName = `Reduce ${category.name} by ${ruleValue}%`;
             break;
+          case 'no_spend':
+            generatedName = `No ${category.name} this month`;
+            break;
+          case 'max_count':
+            generatedName = `${category.name} at most ${ruleValue} times`;
+            break;
         }
         setName(generatedName);
       }
@@ -87,8 +103,8 @@ export function HabitCreationForm({ onHabitCreated, onBack }: HabitCreationFormP
       return;
     }
 
-    const value = parseFloat(ruleValue);
-    if (isNaN(value) || value <= 0) {
+    const value = ruleType === 'no_spend' ? 0 : parseFloat(ruleValue);
+    if (ruleType !== 'no_spend' && (isNaN(value) || value <= 0)) {
       setError('Please enter a valid amount or percentage');
       return;
     }
@@ -103,6 +119,11 @@ export function HabitCreationForm({ onHabitCreated, onBack }: HabitCreationFormP
       return;
     }
 
+    if (ruleType === 'max_count' && !Number.isInteger(value)) {
+      setError('Please enter a whole number of purchases');
+      return;
+    }
+
     setIsSubmitting(true);
     try {
       await createHabitGoal(
@@ -128,6 +149,10 @@ export function HabitCreationForm({ onHabitCreated, onBack }: HabitCreationFormP
         return 'Maximum Percentage';
       case 'reduce_by':
         return 'Reduction Percentage';
+      case 'max_count':
+        return 'Maximum Purchases';
+      case 'no_spend':
+        return '';
     }
   };
 
@@ -139,6 +164,10 @@ export function HabitCreationForm({ onHabitCreated, onBack }: HabitCreationFormP
         return '15';
       case 'reduce_by':
         return '20';
+      case 'max_count':
+        return '4';
+      case 'no_spend':
+        return '';
     }
   };
 
@@ -198,6 +227,7 @@ export function HabitCreationForm({ onHabitCreated, onBack }: HabitCreationFormP
               </div>
 
               {/* Rule Value */}
+              {ruleType !== 'no_spend' && (
               <div className="space-y-2">
                 <label htmlFor="ruleValue" className="text-sm font-medium">
                   {getRuleValueLabel()}
@@ -220,7 +250,7 @@ export function HabitCreationForm({ onHabitCreated, onBack }: HabitCreationFormP
                     className={ruleType === 'max_amount' ? 'pl-8' : ''}
                     disabled={isSubmitting}
                   />
-                  {ruleType !== 'max_amount' && (
+                  {(ruleType === 'max_percentage' || ruleType === 'reduce_by') && (
                     <span className="absolute right-3 top-1/2 -translate-y-1/2 text-muted-foreground">
                       %
                     </span>
@@ -230,8 +260,10 @@ export function HabitCreationForm({ onHabitCreated, onBack }: HabitCreationFormP
                   {ruleType === 'max_amount' && 'The maximum amount you want to spend on this category each month.'}
                   {ruleType === 'max_percentage' && 'The maximum percentage of your total monthly spending for this category.'}
                   {ruleType === 'reduce_by' && 'How much less you want to spend compared to the previous month.'}
+                  {ruleType === 'max_count' && 'How many purchases in this category you allow yourself each month.'}
                 </p>
               </div>
+              )}
 
               {/* Habit Name (Auto-generated but editable) */}
               <div className="space-y-2">
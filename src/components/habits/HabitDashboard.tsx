@@ -30,6 +30,10 @@ export function HabitDashboard({ habit, onBack, onDeleted, onUpdated }: HabitDas
   const nameInputRef = useRef<HTMLInputElement>(null);
   const ruleInputRef = useRef<HTMLInputElement>(null);
 
+  // max_count habits track a number of purchases, not money
+  const formatAmount = (value: number) =>
+    habit.rule_type === 'max_count' ? String(value) : formatCurrency(value, '€', false);
+
   const loadTrackingHistory = useCallback(async () => {
     try {
       const history = await getHabitTrackingForGoal(habit.id);
@@ -169,6 +173,10 @@ export function HabitDashboard({ habit, onBack, onDeleted, onUpdated }: HabitDas
         return 'Maximum Percentage';
       case 'reduce_by':
         return 'Reduce By';
+      case 'no_spend':
+        return 'No Spend';
+      case 'max_count':
+        return 'Maximum Purchases';
     }
   };
 
@@ -180,6 +188,10 @@ export function HabitDashboard({ habit, onBack, onDeleted, onUpdated }: HabitDas
         return `${habit.rule_value}%`;
       case 'reduce_by':
         return `${habit.rule_value}%`;
+      case 'no_spend':
+        return formatCurrency(0, '€', false);
+      case 'max_count':
+        return `${habit.rule_value}×`;
     }
   };
 
@@ -217,10 +229,10 @@ export function HabitDashboard({ habit, onBack, onDeleted, onUpdated }: HabitDas
           {/* Main spending display */}
           <div className="text-center mb-4">
             <p className={cn("text-4xl font-bold", getStatusColor())}>
-              {formatCurrency(habit.current_month_spent, '€', false)}
+              {formatAmount(habit.current_month_spent)}
             </p>
             <p className="text-sm text-muted-foreground mt-1">
-              of {formatCurrency(habit.current_month_target, '€', false)} limit
+              of {formatAmount(habit.current_month_target)} limit
             </p>
           </div>
 
@@ -236,8 +248,8 @@ export function HabitDashboard({ habit, onBack, onDeleted, onUpdated }: HabitDas
               <span>{Math.round(habit.percentage_used)}% used</span>
               <span>
                 {habit.current_month_target - habit.current_month_spent > 0
-                  ? `${formatCurrency(habit.current_month_target - habit.current_month_spent, '€', false)} remaining`
-                  : `${formatCurrency(habit.current_month_spent - habit.current_month_target, '€', false)} over`}
+                  ? `${formatAmount(habit.current_month_target - habit.current_month_spent)} remaining`
+                  : `${formatAmount(habit.current_month_spent - habit.current_month_target)} over`}
               </span>
             </div>
           </div>
@@ -130,6 +130,10 @@ interface HabitCardProps {
 }
 
 function HabitCard({ habit, onClick }: HabitCardProps) {
+  // max_count habits track a number of purchases, not money
+  const formatAmount = (value: number) =>
+    habit.rule_type === 'max_count' ? String(value) : formatCurrency(value, '€', false);
+
   const getStatusColor = () => {
     switch (habit.status) {
       case 'safe':
@@ -206,10 +210,10 @@ function HabitCard({ habit, onClick }: HabitCardProps) {
         {/* Spending info */}
         <div className="flex justify-between text-xs">
           <span className={cn("font-medium", getStatusColor())}>
-            {formatCurrency(habit.current_month_spent, '€', false)}
+            {formatAmount(habit.current_month_spent)}
           </span>
           <span className="text-muted-foreground">
-            of {formatCurrency(habit.current_month_target, '€', false)}
+            of {formatAmount(habit.current_month_target)}
           </span>
         </div>
       </CardContent>
@@ -1,4 +1,4 @@
-import { initAuth, logIn, logOut, signUp } from '@/lib/auth';
+import { initAuth, logIn, logOut, saveLocalAuthState, signUp, watchSessionExpiry } from '@/lib/auth';
 import { isSupabaseConfigured } from '@/lib/supabase';
 import type { AuthState, User } from '@/lib/types';
 import { createContext, useCallback, useContext, useEffect, useState, type ReactNode } from 'react';
@@ -46,6 +46,20 @@ export function AuthProvider({ children }: AuthProviderProps) {
     init();
   }, []);
 
+  // The backend refreshes the session in the background; when the server
+  // refuses, sign out so the user can log in again
+  useEffect(() => {
+    const stopWatching = watchSessionExpiry(async () => {
+      await saveLocalAuthState(null);
+      setUser(null);
+      setIsAuthenticated(false);
+      setError('Your session has expired. Please log in again.');
+    });
+    return () => {
+      stopWatching.then((stop) => stop());
+    };
+  }, []);
+
   const login = useCallback(async (email: string, password: string) => {
     setIsLoading(true);
     setError(null);
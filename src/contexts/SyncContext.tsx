@@ -1,4 +1,5 @@
-import { isOnline as checkIsOnline, fullSync, getSyncStatus } from '@/lib/sync';
+import { watchConnectivity } from '@/lib/connectivity';
+import { fullSync, getSyncStatus, onTraySyncNow } from '@/lib/sync';
 import type { SyncResult, SyncStatus } from '@/lib/types';
 import { createContext, useCallback, useContext, useEffect, useRef, useState, type ReactNode } from 'react';
 import { useAuth } from './AuthContext';
@@ -65,18 +66,19 @@ export function SyncProvider({ children }: SyncProviderProps) {
 
   // Update online status
   useEffect(() => {
-    const handleOnline = () => setIsOnline(true);
-    const handleOffline = () => setIsOnline(false);
+    let stopWatching: (() => void) | undefined;
+    let unmounted = false;
 
-    window.addEventListener('online', handleOnline);
-    window.addEventListener('offline', handleOffline);
-
-    // Initial check
-    setIsOnline(checkIsOnline());
+    watchConnectivity(setIsOnline)
+      .then((stop) => {
+        if (unmounted) stop();
+        else stopWatching = stop;
+      })
+      .catch((error) => console.error('[SyncContext] Connectivity watch failed:', error));
 
     return () => {
-      window.removeEventListener('online', handleOnline);
-      window.removeEventListener('offline', handleOffline);
+      unmounted = true;
+      stopWatching?.();
     };
   }, []);
 
@@ -170,6 +172,22 @@ export function SyncProvider({ children }: SyncProviderProps) {
     }
   }, [isAuthenticated, isConfigured, isSyncing, notifySyncComplete]);
 
+  // "Sync now" from the desktop tray
+  useEffect(() => {
+    let stop: (() => void) | undefined;
+    let cancelled = false;
+    onTraySyncNow(() => {
+      sync().catch(console.error);
+    }).then((unlisten) => {
+      if (cancelled) unlisten();
+      else stop = unlisten;
+    });
+    return () => {
+      cancelled = true;
+      stop?.();
+    };
+  }, [sync]);
+
   // Trigger initial sync when authenticated - but only if not already done by RootLayout
   // This prevents the race condition where both RootLayout and SyncContext trigger sync
   useEffect(() => {
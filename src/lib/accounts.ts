@@ -0,0 +1,155 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * Accounts money is kept in, and transfers between them. Transfers change
+ * two balances without counting as spending.
+ */
+
+export type AccountType = 'cash' | 'checking' | 'savings' | 'credit_card';
+
+export interface Account {
+  id: string;
+  name: string;
+  kind: AccountType;
+  opening_balance: number;
+  // YYYY-MM-DD; earlier history is in the opening balance
+  opening_date: string;
+  // Negative for a credit card with money owed on it
+  balance: number;
+  balance_updated_at: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface NewAccount {
+  name: string;
+  kind: AccountType;
+  opening_balance?: number | null;
+  opening_date?: string | null;
+}
+
+export interface Transfer {
+  id: string;
+  from_account_id: string;
+  to_account_id: string;
+  amount: number;
+  date: string;
+  note: string | null;
+  created_at: string;
+}
+
+export interface AccountEntry {
+  kind: 'expense' | 'income' | 'transfer_in' | 'transfer_out';
+  id: string;
+  date: string;
+  // Positive when money came in
+  amount: number;
+  note: string | null;
+}
+
+function assertTauri(): void {
+  if (!isTauri()) {
+    throw new Error('Accounts are only available in the desktop and mobile apps');
+  }
+}
+
+export async function getAccounts(): Promise<Account[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getAccounts()) as Promise<Account[]>;
+}
+
+export async function createAccount(input: NewAccount): Promise<Account> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  const account = { opening_balance: null, opening_date: null, ...input };
+  return unwrap(commands.createAccount(account)) as Promise<Account>;
+}
+
+export async function updateAccount(id: string, input: NewAccount): Promise<Account> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  const account = { opening_balance: null, opening_date: null, ...input };
+  return unwrap(commands.updateAccount(id, account)) as Promise<Account>;
+}
+
+export async function deleteAccount(id: string): Promise<void> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  await unwrap(commands.deleteAccount(id));
+}
+
+/**
+ * Make an account's balance read what the bank says it is today.
+ */
+export async function reconcileAccountBalance(id: string, balance: number): Promise<Account> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.reconcileAccountBalance(id, balance)) as Promise<Account>;
+}
+
+export async function recomputeAccountBalances(): Promise<Account[]> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.recomputeAccountBalances()) as Promise<Account[]>;
+}
+
+export async function getAccountActivity(
+  id: string,
+  start: string,
+  end: string
+): Promise<AccountEntry[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getAccountActivity(id, start, end)) as Promise<AccountEntry[]>;
+}
+
+export async function getTransfers(
+  start: string,
+  end: string,
+  accountId?: string
+): Promise<Transfer[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getTransfers(accountId ?? null, start, end));
+}
+
+export async function createTransfer(
+  fromAccountId: string,
+  toAccountId: string,
+  amount: number,
+  date?: string,
+  note?: string
+): Promise<Transfer> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(
+    commands.createTransfer({
+      from_account_id: fromAccountId,
+      to_account_id: toAccountId,
+      amount,
+      date: date ?? null,
+      note: note ?? null,
+    })
+  );
+}
+
+export async function deleteTransfer(id: string): Promise<void> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  await unwrap(commands.deleteTransfer(id));
+}
+
+/**
+ * Replace an expense that was really money moved between accounts, like a
+ * credit card payment on a bank statement, with a transfer.
+ */
+export async function convertExpenseToTransfer(
+  expenseId: string,
+  toAccountId: string
+): Promise<Transfer> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.convertExpenseToTransfer(expenseId, toAccountId));
+}
@@ -0,0 +1,81 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * Importing history from YNAB register exports and Mint transaction CSVs.
+ * Preview the file first to map each of the app's categories to one of
+ * ours, then import with that mapping; unmapped categories go through the
+ * categorization rules. The mapping is remembered per app.
+ */
+
+export type AppFormat = 'ynab' | 'mint';
+
+export interface AppCategory {
+  name: string;
+  count: number;
+  total: number;
+  // Last import's choice, or the category with a matching name
+  suggested_category_id: string | null;
+}
+
+export interface AppExportPreview {
+  format: AppFormat;
+  categories: AppCategory[];
+  rows: number;
+  // Transfers, income and unreadable rows
+  skipped: number;
+}
+
+export interface AppImportOptions {
+  format?: AppFormat | null;
+  // The app's category name to a category id
+  category_map: Record<string, string>;
+  currency?: string | null;
+  // A chrono format such as "%d.%m.%Y"; month first if not given
+  date_format?: string | null;
+}
+
+export interface AppImportRow {
+  line: number;
+  date: string;
+  amount: number;
+  description: string | null;
+  category_id: string | null;
+  duplicate: boolean;
+}
+
+export interface AppImportReport {
+  dry_run: boolean;
+  rows: AppImportRow[];
+  imported: number;
+  duplicates: number;
+  skipped: number;
+}
+
+function assertTauri(): void {
+  if (!isTauri()) {
+    throw new Error('Importing from other apps is only available in the desktop and mobile apps');
+  }
+}
+
+export async function previewAppExport(
+  path: string,
+  format?: AppFormat,
+  dateFormat?: string
+): Promise<AppExportPreview> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.previewAppExport(path, format ?? null, dateFormat ?? null));
+}
+
+/** With `dryRun` nothing is written and the report is a preview. */
+export async function importAppExport(
+  path: string,
+  options: AppImportOptions,
+  dryRun: boolean
+): Promise<AppImportReport> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  const input = { format: null, currency: null, date_format: null, ...options };
+  return unwrap(commands.importAppExport(path, input, dryRun));
+}
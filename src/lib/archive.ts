@@ -0,0 +1,149 @@
+import { unwrap } from './commands';
+import { runMigrations } from './migrations';
+import { isTauri } from './platform';
+
+/**
+ * Encrypted .goaldy archives: full export and restore of the local database
+ * and attachments. Both sides run in the Rust backend; import needs the
+ * frontend in the middle because the schema migrations live here.
+ */
+
+export type ImportMode = 'merge' | 'replace';
+
+export interface ArchiveManifest {
+  format: string;
+  format_version: number;
+  app_version: string;
+  schema_version: string | null;
+  migrations: string[];
+  created_at: string;
+  database_size: number;
+  attachment_count: number;
+}
+
+interface StagedArchive {
+  id: string;
+  database_path: string;
+  manifest: ArchiveManifest;
+  pending_migrations: string[];
+}
+
+export interface ImportReport {
+  mode: ImportMode;
+  tables: { table: string; rows: number }[];
+  attachments: number;
+}
+
+function assertTauri(): void {
+  if (!isTauri()) {
+    throw new Error('Archives are only available in the desktop and mobile apps');
+  }
+}
+
+/**
+ * Write an encrypted archive of all local data to `path`.
+ */
+export async function exportArchive(path: string, passphrase: string): Promise<ArchiveManifest> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.exportArchive(path, passphrase));
+}
+
+/**
+ * Restore an archive made by exportArchive.
+ *
+ * 'replace' swaps local data for the archive's. 'merge' keeps local data,
+ * adds rows missing locally and takes archived rows updated more recently.
+ * Archives from older app versions are migrated to the current schema first.
+ */
+export async function importArchive(
+  path: string,
+  passphrase: string,
+  mode: ImportMode
+): Promise<ImportReport> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  const staged = await unwrap(commands.stageArchive(path, passphrase));
+  return finishImport(staged, mode);
+}
+
+/**
+ * Bring a staged archive up to the current schema and import it.
+ */
+async function finishImport(staged: StagedArchive, mode: ImportMode): Promise<ImportReport> {
+  const { commands } = await import('./bindings');
+  if (staged.pending_migrations.length > 0) {
+    const Database = (await import('@tauri-apps/plugin-sql')).default;
+    const db = await Database.load(`sqlite:${staged.database_path}`);
+    try {
+      const { errors } = await runMigrations(db);
+      if (errors.length > 0) {
+        throw new Error(`Could not upgrade archive: ${errors[0]}`);
+      }
+    } finally {
+      await db.close();
+    }
+  }
+
+  return unwrap(commands.importArchive(staged.id, mode));
+}
+
+export interface TransferOffer {
+  /** Encoded in the QR code; the receiving device passes it to receiveTransfer. */
+  payload: string;
+  qr_svg: string;
+  expires_at: string;
+  manifest: ArchiveManifest;
+}
+
+/**
+ * Offer all local data to a new device on the same Wi-Fi. Show `qr_svg`
+ * until the 'transfer://finished' event arrives or the offer expires.
+ */
+export async function startTransfer(): Promise<TransferOffer> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.startTransfer());
+}
+
+export async function cancelTransfer(): Promise<void> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  await commands.cancelTransfer();
+}
+
+/**
+ * Pull the data offered by another device from its scanned QR code.
+ */
+export async function receiveTransfer(payload: string, mode: ImportMode): Promise<ImportReport> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  const staged = await unwrap(commands.receiveTransfer(payload));
+  return finishImport(staged, mode);
+}
+
+export interface ScannedCode {
+  kind: 'transfer' | 'pairing';
+  /** For receiveTransfer or pairWithPeer. */
+  payload: string;
+}
+
+/**
+ * The transfer or pairing code the app was opened with from the camera,
+ * once. Call on startup, then follow onCodeScanned.
+ */
+export async function takeScannedCode(): Promise<ScannedCode | null> {
+  if (!isTauri()) return null;
+  const { commands } = await import('./bindings');
+  return commands.takeScannedCode();
+}
+
+/**
+ * Follow codes scanned while the app runs. Returns a function that stops
+ * listening.
+ */
+export async function onCodeScanned(handler: (code: ScannedCode) => void): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  return listen<ScannedCode>('transfer://scanned', (event) => handler(event.payload));
+}
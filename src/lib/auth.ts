@@ -1,4 +1,5 @@
 import { getBrowserDatabase } from './browser-database';
+import { unwrap } from './commands';
 import { isTauri } from './platform';
 import { getSupabase, isSupabaseConfigured } from './supabase';
 import type { AuthSession, AuthState, LocalAuthState } from './types';
@@ -32,6 +33,13 @@ async function getAuthDatabase(): Promise<DatabaseInterface> {
  * Get the current auth session from local SQLite storage.
  */
 export async function getLocalAuthState(): Promise<LocalAuthState | null> {
+  if (isTauri()) {
+    // Tokens are kept in the OS keychain, which only the backend can read
+    const { commands } = await import('./bindings');
+    const state = await unwrap(commands.getOrCreateAuthState());
+    return { id: 1, ...state };
+  }
+
   const db = await getAuthDatabase();
   const result = await db.select<LocalAuthState[]>(
     'SELECT * FROM auth_state WHERE id = 1'
@@ -43,6 +51,21 @@ export async function getLocalAuthState(): Promise<LocalAuthState | null> {
  * Save auth session to local SQLite storage.
  */
 export async function saveLocalAuthState(session: AuthSession | null): Promise<void> {
+  if (isTauri()) {
+    // The backend serializes writes to the single auth_state row
+    const { commands } = await import('./bindings');
+    await unwrap(
+      commands.updateAuthState({
+        user_id: session?.userId ?? null,
+        email: session?.email ?? null,
+        access_token: session?.accessToken ?? null,
+        refresh_token: session?.refreshToken ?? null,
+        expires_at: session?.expiresAt ?? null,
+      })
+    );
+    return;
+  }
+
   const db = await getAuthDatabase();
 
   if (session) {
@@ -76,6 +99,17 @@ export async function updateLastSyncAt(timestamp: string): Promise<void> {
   );
 }
 
+/**
+ * Call `onExpired` when the backend could not refresh the session in the
+ * background. Returns a function that stops watching.
+ */
+export async function watchSessionExpiry(onExpired: () => void): Promise<() => void> {
+  if (!isTauri()) return () => {};
+
+  const { listen } = await import('@tauri-apps/api/event');
+  return listen('auth:session-expired', () => onExpired());
+}
+
 /**
  * Check if the session token is expired.
  */
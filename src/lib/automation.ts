@@ -0,0 +1,43 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * The localhost API for launchers, Shortcuts and scripts. Off by default;
+ * requests need `Authorization: Bearer <token>`.
+ *
+ *   POST /v1/expenses  {"amount": 12.5, "category": "groceries"} or {"text": "12.50 coffee"}
+ *   GET  /v1/budget
+ *   GET  /v1/goals
+ */
+
+export interface AutomationStatus {
+  enabled: boolean;
+  // Where the API answers, while it is up
+  url: string | null;
+  token: string | null;
+}
+
+function assertTauri(): void {
+  if (!isTauri()) {
+    throw new Error('The automation API is only available in the desktop and mobile apps');
+  }
+}
+
+export async function getAutomationStatus(): Promise<AutomationStatus | null> {
+  if (!isTauri()) return null;
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getAutomationStatus());
+}
+
+export async function setAutomationEnabled(enabled: boolean): Promise<AutomationStatus> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.setAutomationEnabled(enabled));
+}
+
+/** Replace the token; tools using the old one stop working. */
+export async function regenerateAutomationToken(): Promise<AutomationStatus> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.regenerateAutomationToken());
+}
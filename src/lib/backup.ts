@@ -0,0 +1,109 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * Automatic encrypted backups to Dropbox, Google Drive or a folder such as
+ * iCloud Drive. The backend runs them on schedule; this only sets them up.
+ */
+
+export type BackupProvider = 'dropbox' | 'google_drive' | 'folder';
+
+export interface BackupTarget {
+  id: string;
+  provider: BackupProvider;
+  folder_path: string | null;
+  interval_hours: number;
+  last_backup_at: string | null;
+  last_error: string | null;
+  created_at: string;
+}
+
+// OAuth clients registered for Goaldy with each provider
+const OAUTH_CLIENTS: Record<'dropbox' | 'google_drive', { clientId?: string; clientSecret?: string }> = {
+  dropbox: {
+    clientId: import.meta.env.VITE_DROPBOX_CLIENT_ID as string | undefined,
+  },
+  google_drive: {
+    clientId: import.meta.env.VITE_GOOGLE_CLIENT_ID as string | undefined,
+    clientSecret: import.meta.env.VITE_GOOGLE_CLIENT_SECRET as string | undefined,
+  },
+};
+
+function assertTauri(): void {
+  if (!isTauri()) {
+    throw new Error('Automatic backups are only available in the desktop and mobile apps');
+  }
+}
+
+export async function getBackupTargets(): Promise<BackupTarget[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getBackupTargets());
+}
+
+/**
+ * Cloud providers this build has OAuth clients for.
+ */
+export function getAvailableProviders(): BackupProvider[] {
+  const providers: BackupProvider[] = ['folder'];
+  if (OAUTH_CLIENTS.dropbox.clientId) providers.unshift('dropbox');
+  if (OAUTH_CLIENTS.google_drive.clientId) providers.unshift('google_drive');
+  return providers;
+}
+
+/**
+ * The iCloud Drive folder on macOS, to preselect in the folder picker.
+ */
+export async function getICloudDriveDir(): Promise<string | null> {
+  if (!isTauri()) return null;
+  const { commands } = await import('./bindings');
+  return commands.getIcloudDriveDir();
+}
+
+export async function addBackupFolder(
+  path: string,
+  passphrase: string,
+  intervalHours = 24
+): Promise<BackupTarget> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.addBackupFolder(path, passphrase, intervalHours));
+}
+
+/**
+ * Sign in to Dropbox or Google Drive in the browser. Resolves once the user
+ * finished there, or rejects after five minutes.
+ */
+export async function connectBackupProvider(
+  provider: 'dropbox' | 'google_drive',
+  passphrase: string,
+  intervalHours = 24
+): Promise<BackupTarget> {
+  assertTauri();
+  const client = OAUTH_CLIENTS[provider];
+  if (!client.clientId) {
+    throw new Error('This build has no sign-in configured for that provider');
+  }
+  const { commands } = await import('./bindings');
+  return unwrap(
+    commands.connectBackupProvider(
+      provider,
+      client.clientId,
+      client.clientSecret ?? null,
+      passphrase,
+      intervalHours
+    )
+  );
+}
+
+export async function removeBackupTarget(id: string): Promise<void> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  await unwrap(commands.removeBackupTarget(id));
+}
+
+export async function runBackup(id: string): Promise<BackupTarget> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.runBackup(id));
+}
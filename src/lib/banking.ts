@@ -0,0 +1,180 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * Bank transactions through GoCardless Bank Account Data. The user brings
+ * their own GoCardless secret id and key; nothing is fetched without them.
+ * Connecting a bank returns a link to approve access at the bank, which
+ * sends the user back to the app to finish. New debits are fetched a few
+ * times a day and wait here until confirmed as expenses or dismissed.
+ *
+ * Banks in the US and Canada link through Plaid instead, for signed-in
+ * users: open Plaid Link with a token from `createPlaidLinkToken` and hand
+ * the public token it returns to `linkPlaid`. Each linked account gets an
+ * account in the app that its confirmed transactions are paid from.
+ */
+
+export interface BankCredentials {
+  secret_id: string;
+  secret_key: string;
+}
+
+export interface BankInstitution {
+  id: string;
+  name: string;
+  bic: string | null;
+  logo: string | null;
+}
+
+export type BankProvider = 'gocardless' | 'plaid';
+
+export type BankConnectionStatus = 'pending' | 'linked' | 'expired' | 'failed';
+
+export interface BankConnection {
+  id: string;
+  institution_id: string;
+  institution_name: string;
+  provider: BankProvider;
+  status: BankConnectionStatus;
+  // The bank's approval page, while pending
+  link: string;
+  account_count: number;
+  access_valid_until: string | null;
+  last_fetched_at: string | null;
+  last_error: string | null;
+  created_at: string;
+}
+
+export interface BankTransaction {
+  id: string;
+  connection_id: string;
+  institution_name: string;
+  // The bank's name for the account it was paid from
+  bank_account_name: string | null;
+  // The account in the app it is paid from once confirmed
+  account_id: string | null;
+  date: string;
+  // Positive, in `currency`
+  amount: number;
+  currency: string;
+  description: string | null;
+  // Suggested by the categorization rules
+  category_id: string | null;
+}
+
+export interface ConfirmBankTransaction {
+  id: string;
+  // The suggested category if null
+  category_id: string | null;
+}
+
+export interface BankConfirmReport {
+  imported: number;
+  // Already logged by hand; marked, not imported
+  duplicates: number;
+}
+
+function assertTauri(): void {
+  if (!isTauri()) {
+    throw new Error('Bank connections are only available in the desktop and mobile apps');
+  }
+}
+
+export async function hasBankCredentials(): Promise<boolean> {
+  if (!isTauri()) return false;
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getBankCredentialsSet());
+}
+
+/** Checked with GoCardless before being stored; `null` removes them. */
+export async function setBankCredentials(credentials: BankCredentials | null): Promise<void> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  await unwrap(commands.setBankCredentials(credentials));
+}
+
+/** Banks in a country, by two-letter code such as "DE". */
+export async function getBankInstitutions(country: string): Promise<BankInstitution[]> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getBankInstitutions(country));
+}
+
+export async function getBankConnections(): Promise<BankConnection[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getBankConnections()) as Promise<BankConnection[]>;
+}
+
+/** Open the returned connection's `link` to approve access at the bank. */
+export async function connectBank(
+  institutionId: string,
+  institutionName: string
+): Promise<BankConnection> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.connectBank(institutionId, institutionName)) as Promise<BankConnection>;
+}
+
+export async function createPlaidLinkToken(): Promise<string> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.createPlaidLinkToken());
+}
+
+/** Finish linking with the public token from Plaid Link's onSuccess. */
+export async function linkPlaid(publicToken: string): Promise<BankConnection> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.linkPlaid(publicToken)) as Promise<BankConnection>;
+}
+
+export async function refreshBankConnection(id: string): Promise<BankConnection> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.refreshBankConnection(id)) as Promise<BankConnection>;
+}
+
+/** Revokes access; expenses already confirmed stay. */
+export async function disconnectBank(id: string): Promise<void> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  await unwrap(commands.disconnectBank(id));
+}
+
+/** Fetch now. Resolves to the number of new pending transactions. */
+export async function fetchBankTransactions(): Promise<number> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.fetchBankTransactions());
+}
+
+export async function getPendingBankTransactions(): Promise<BankTransaction[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getPendingBankTransactions());
+}
+
+export async function confirmBankTransactions(
+  transactions: ConfirmBankTransaction[]
+): Promise<BankConfirmReport> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.confirmBankTransactions(transactions));
+}
+
+export async function dismissBankTransactions(ids: string[]): Promise<void> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  await unwrap(commands.dismissBankTransactions(ids));
+}
+
+/**
+ * Follow fetches that found new transactions, with how many. Returns a
+ * function that stops listening.
+ */
+export async function onBankTransactions(handler: (count: number) => void): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  return listen<number>('banking://transactions', (event) => handler(event.payload));
+}
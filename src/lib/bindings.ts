@@ -0,0 +1,4517 @@
+// Generated by tauri-specta from the Rust commands. Do not edit.
+// @ts-nocheck
+// This file was generated by [tauri-specta](https://github.com/oscartbeaumont/tauri-specta). Do not edit this file manually.
+
+/** user-defined commands **/
+
+
+export const commands = {
+/**
+ * Parse typed or dictated text ("12.50 coffee yesterday") into a draft.
+ */
+async parseExpenseText(text: string) : Promise<Result<ExpenseDraft, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("parse_expense_text", { text }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Whether complete quick-add links are saved without opening the form.
+ */
+async getQuickAddAutoSave() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_quick_add_auto_save") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setQuickAddAutoSave(autoSave: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_quick_add_auto_save", { autoSave }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The draft of the link the app was opened with, once. Check on startup,
+ * as `quick-add://draft` may have fired before anyone listened.
+ */
+async takeQuickAddDraft() : Promise<ExpenseDraft | null> {
+    return await TAURI_INVOKE("take_quick_add_draft");
+},
+/**
+ * Handle text shared from another app like a quick-add link. Returns the
+ * expense if it was saved rather than opened in the form.
+ */
+async quickAddSharedText(text: string) : Promise<Result<Expense | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("quick_add_shared_text", { text }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Save the expense entered in the quick-add window and hide it.
+ */
+async quickAddExpense(input: NewExpense) : Promise<Result<Expense, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("quick_add_expense", { input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async openQuickAddWindow() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("open_quick_add_window") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async closeQuickAddWindow() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("close_quick_add_window") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The global shortcut that opens the quick-add window.
+ */
+async getQuickAddShortcut() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_quick_add_shortcut") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Change the shortcut, e.g. to `Alt+Space`. Fails if the combination is
+ * invalid or another app holds it.
+ */
+async setQuickAddShortcut(shortcut: string) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_quick_add_shortcut", { shortcut }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAutomationStatus() : Promise<Result<AutomationStatus, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_automation_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Turn the localhost API on or off.
+ */
+async setAutomationEnabled(enabled: boolean) : Promise<Result<AutomationStatus, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_automation_enabled", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Replace the API token; tools using the old one stop working.
+ */
+async regenerateAutomationToken() : Promise<Result<AutomationStatus, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("regenerate_automation_token") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getWebhooks() : Promise<Result<Webhook[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_webhooks") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Add a webhook; it gets its own signing secret.
+ */
+async createWebhook(input: WebhookInput) : Promise<Result<Webhook, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_webhook", { input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateWebhook(id: string, input: WebhookInput) : Promise<Result<Webhook, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_webhook", { id, input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteWebhook(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_webhook", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * POST a `webhook.test` event to the webhook now.
+ */
+async testWebhook(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("test_webhook", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getWebhookDeliveries(webhookId: string) : Promise<Result<WebhookDelivery[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_webhook_deliveries", { webhookId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getBankCredentialsSet() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_bank_credentials_set") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Store the GoCardless secret id and key after checking them, or remove
+ * them with `None`.
+ */
+async setBankCredentials(credentials: Credentials | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_bank_credentials", { credentials }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getBankInstitutions(country: string) : Promise<Result<Institution[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_bank_institutions", { country }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getBankConnections() : Promise<Result<BankConnection[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_bank_connections") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Start connecting a bank; the frontend opens the returned link.
+ */
+async connectBank(institutionId: string, institutionName: string) : Promise<Result<BankConnection, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("connect_bank", { institutionId, institutionName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * A token to open Plaid Link with, for banks in the US and Canada.
+ */
+async createPlaidLinkToken() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_plaid_link_token") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Finish linking with the public token Plaid Link handed back.
+ */
+async linkPlaid(publicToken: string) : Promise<Result<BankConnection, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("link_plaid", { publicToken }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async refreshBankConnection(id: string) : Promise<Result<BankConnection, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("refresh_bank_connection", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async disconnectBank(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("disconnect_bank", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Fetch every linked account now. Returns how many new transactions wait
+ * for confirmation.
+ */
+async fetchBankTransactions() : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("fetch_bank_transactions") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getPendingBankTransactions() : Promise<Result<BankTransaction[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_pending_bank_transactions") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Log the given pending transactions as expenses.
+ */
+async confirmBankTransactions(transactions: ConfirmTransaction[]) : Promise<Result<ConfirmReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("confirm_bank_transactions", { transactions }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async dismissBankTransactions(ids: string[]) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("dismiss_bank_transactions", { ids }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Transcribe a WAV voice note on-device and turn it into an expense draft.
+ */
+async voiceNoteToExpense(audio: number[]) : Promise<Result<VoiceExpenseDraft, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("voice_note_to_expense", { audio }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Run OCR on a receipt photo and propose an expense with per-field confidence.
+ */
+async scanReceipt(image: number[]) : Promise<Result<ReceiptDraft, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("scan_receipt", { image }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Rank likely categories for an expense being entered, learned from the
+ * user's own categorized history.
+ */
+async suggestCategories(note: string | null, amount: number | null) : Promise<Result<CategorySuggestion[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("suggest_categories", { note, amount }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getCategorizationRules() : Promise<Result<CategorizationRule[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_categorization_rules") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async saveCategorizationRule(rule: NewRule) : Promise<Result<CategorizationRule, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("save_categorization_rule", { rule }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteCategorizationRule(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_categorization_rule", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * "Always categorize this as ...": a rule from the expense's note or
+ * merchant, recategorizing the expense itself if `category_id` differs.
+ */
+async createRuleFromExpense(expenseId: string, matchField: MatchField, categoryId: string | null) : Promise<Result<CategorizationRule, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_rule_from_expense", { expenseId, matchField, categoryId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Repeating charges found in the expense history that could become
+ * recurring-expense entries.
+ */
+async getRecurringCandidates() : Promise<Result<RecurringCandidate[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_recurring_candidates") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getRecurringExpenses() : Promise<Result<RecurringExpense[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_recurring_expenses") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async createRecurringExpense(input: NewRecurringExpense) : Promise<Result<RecurringExpense, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_recurring_expense", { input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Subscriptions found by the background scan and not yet tracked or
+ * dismissed.
+ */
+async detectedSubscriptions() : Promise<Result<DetectedSubscription[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("detected_subscriptions") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async dismissDetectedSubscription(matchKey: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("dismiss_detected_subscription", { matchKey }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Track a detected subscription as a recurring expense with renewal
+ * reminders.
+ */
+async trackDetectedSubscription(matchKey: string, remindDaysBefore: number | null) : Promise<Result<RecurringExpense, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("track_detected_subscription", { matchKey, remindDaysBefore }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Subscriptions whose latest charge is a price increase.
+ */
+async getPriceIncreases() : Promise<Result<PriceIncrease[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_price_increases") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Create recurring expenses from the repeating events in an ICS calendar.
+ */
+async importIcs(path: string) : Promise<Result<IcsImport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("import_ics", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Clean merchant name for a raw bank description, honoring corrections.
+ */
+async normalizeMerchant(raw: string) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("normalize_merchant", { raw }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Store a user correction; returns the raw key it was saved under.
+ */
+async saveMerchantCorrection(raw: string, merchantName: string) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("save_merchant_correction", { raw, merchantName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getMerchantStats(startDate: string, endDate: string) : Promise<Result<MerchantStats[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_merchant_stats", { startDate, endDate }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getMerchants() : Promise<Result<Merchant[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_merchants") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Rename a merchant, merging it into another of the same name; returns
+ * the id it ends up with.
+ */
+async renameMerchant(id: string, name: string) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("rename_merchant", { id, name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getMerchantMonthlySpending(merchantId: string, startDate: string, endDate: string) : Promise<Result<MerchantMonth[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_merchant_monthly_spending", { merchantId, startDate, endDate }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getTrials() : Promise<Result<Trial[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_trials") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Record a free trial and queue a reminder before it converts to paid.
+ */
+async createTrial(input: NewTrial) : Promise<Result<Trial, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_trial", { input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Mark a trial as cancelled and drop its pending reminder.
+ */
+async cancelTrial(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("cancel_trial", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAccounts() : Promise<Result<Account[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_accounts") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async createAccount(input: NewAccount) : Promise<Result<Account, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_account", { input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateAccount(id: string, input: NewAccount) : Promise<Result<Account, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_account", { id, input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteAccount(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_account", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Set an account's balance to what the bank says it is today.
+ */
+async reconcileAccountBalance(id: string, balance: number) : Promise<Result<Account, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reconcile_account_balance", { id, balance }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Recompute every account balance from its opening balance and history.
+ */
+async recomputeAccountBalances() : Promise<Result<Account[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("recompute_account_balances") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Expenses, income and transfers booked to an account between two days.
+ */
+async getAccountActivity(id: string, start: string, end: string) : Promise<Result<AccountEntry[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_account_activity", { id, start, end }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getTransfers(accountId: string | null, start: string, end: string) : Promise<Result<Transfer[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_transfers", { accountId, start, end }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Move money between two accounts. Not spending, so no expense.
+ */
+async createTransfer(input: NewTransfer) : Promise<Result<Transfer, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_transfer", { input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteTransfer(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_transfer", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Replace an expense that was really a transfer, like a credit card
+ * payment, with a transfer to `to_account_id`.
+ */
+async convertExpenseToTransfer(expenseId: string, toAccountId: string) : Promise<Result<Transfer, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("convert_expense_to_transfer", { expenseId, toAccountId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getTags() : Promise<Result<Tag[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_tags") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Create a tag, or return the existing one with the same name.
+ */
+async createTag(name: string, color: string | null) : Promise<Result<Tag, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_tag", { name, color }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setTagColor(id: string, color: string | null) : Promise<Result<Tag, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_tag_color", { id, color }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Rename a tag, merging it into another tag that already has the name.
+ */
+async renameTag(id: string, name: string) : Promise<Result<Tag, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("rename_tag", { id, name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Fold the `sources` tags into `target`.
+ */
+async mergeTags(sources: string[], target: string) : Promise<Result<Tag, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("merge_tags", { sources, target }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteTag(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_tag", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getExpenseTags(expenseId: string) : Promise<Result<Tag[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_expense_tags", { expenseId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Replace an expense's tags with the ones named.
+ */
+async setExpenseTags(expenseId: string, names: string[]) : Promise<Result<Tag[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_expense_tags", { expenseId, names }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Add a tag to, or with `remove` take it off, several expenses.
+ */
+async tagExpenses(tagId: string, expenseIds: string[], remove: boolean) : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("tag_expenses", { tagId, expenseIds, remove }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getTaggedExpenses(filter: TagFilter, startDate: string, endDate: string) : Promise<Result<ExpenseWithCategory[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_tagged_expenses", { filter, startDate, endDate }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getTagTotals(startDate: string, endDate: string) : Promise<Result<TagTotal[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_tag_totals", { startDate, endDate }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Tagged spending broken down by category.
+ */
+async getTaggedCategoryTotals(filter: TagFilter, startDate: string, endDate: string) : Promise<Result<TaggedCategoryTotal[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_tagged_category_totals", { filter, startDate, endDate }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getExpenseSplit(expenseId: string) : Promise<Result<ExpenseSplit | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_expense_split", { expenseId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Share an expense with other people, replacing any earlier split.
+ */
+async splitExpense(expenseId: string, input: NewSplit) : Promise<Result<ExpenseSplit, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("split_expense", { expenseId, input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async unsplitExpense(expenseId: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("unsplit_expense", { expenseId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Names used in splits and settlements so far, for suggestions.
+ */
+async getSplitPeople() : Promise<Result<string[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_split_people") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Who owes whom, and the payments that would settle it.
+ */
+async getSettleUp() : Promise<Result<SettleUp, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_settle_up") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSettlements() : Promise<Result<Settlement[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_settlements") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async recordSettlement(input: NewSettlement) : Promise<Result<Settlement, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("record_settlement", { input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteSettlement(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_settlement", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getHouseholds() : Promise<Result<Household[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_households") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Share an expense with a household, or with no household stop sharing it.
+ */
+async shareExpenseWithHousehold(expenseId: string, householdId: string | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("share_expense_with_household", { expenseId, householdId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async shareBudgetWithHousehold(budgetId: string, householdId: string | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("share_budget_with_household", { budgetId, householdId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Every member's shared expenses, each with its owner.
+ */
+async getHouseholdExpenses(householdId: string, startDate: string, endDate: string) : Promise<Result<HouseholdExpense[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_household_expenses", { householdId, startDate, endDate }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getHouseholdSpendingByMember(householdId: string, startDate: string, endDate: string) : Promise<Result<MemberSpending[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_household_spending_by_member", { householdId, startDate, endDate }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getHouseholdBudgets(householdId: string, month: string) : Promise<Result<HouseholdBudget[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_household_budgets", { householdId, month }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getBills() : Promise<Result<BillDue[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_bills") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getBillPayments(billId: string) : Promise<Result<BillPayment[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_bill_payments", { billId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Add a bill and queue the reminder for its first due date.
+ */
+async createBill(input: NewBill) : Promise<Result<Bill, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_bill", { input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateBill(id: string, input: NewBill) : Promise<Result<Bill, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_bill", { id, input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteBill(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_bill", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Mark a bill paid, optionally logging the payment as an expense.
+ */
+async markBillPaid(id: string, input: MarkPaid) : Promise<Result<BillPayment, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("mark_bill_paid", { id, input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async unmarkBillPaid(id: string, month: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("unmark_bill_paid", { id, month }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Mark an expense as owed back, repaid or written off; `None` clears it.
+ */
+async setReimbursementStatus(expenseId: string, status: ReimbursementStatus | null, owedBy: string | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_reimbursement_status", { expenseId, status, owedBy }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Money other people still owe the user.
+ */
+async getOutstandingReimbursements() : Promise<Result<Outstanding, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_outstanding_reimbursements") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Spending per category for each month from `from_month` to `to_month`
+ * ("YYYY-MM").
+ */
+async getMonthlyCategoryReport(fromMonth: string, toMonth: string) : Promise<Result<MonthCategoryTotal[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_monthly_category_report", { fromMonth, toMonth }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Each month's total compared with the month before.
+ */
+async getMonthOverMonth(fromMonth: string, toMonth: string) : Promise<Result<MonthDelta[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_month_over_month", { fromMonth, toMonth }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Average spend per day between `start_date` and `end_date`.
+ */
+async getAverageDailySpend(startDate: string, endDate: string) : Promise<Result<DailyAverage, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_average_daily_spend", { startDate, endDate }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Spent against budget for each budget period between the two dates.
+ */
+async getBudgetUtilization(startDate: string, endDate: string) : Promise<Result<BudgetUtilization[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_budget_utilization", { startDate, endDate }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Income minus spending for each month from `from_month` to `to_month`.
+ */
+async getMonthlyCashflow(fromMonth: string, toMonth: string) : Promise<Result<MonthCashflow[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_monthly_cashflow", { fromMonth, toMonth }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Average income and spending over the last full months (three unless
+ * given), for setting the budget to a `share` of what comes in.
+ */
+async getCashflowBaseline(months: number | null, share: number | null) : Promise<Result<CashflowBaseline, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_cashflow_baseline", { months, share }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The year-in-review numbers for `year`, the current one unless given.
+ */
+async getYearReview(year: number | null) : Promise<Result<YearReview, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_year_review", { year }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Spending totals for a date range; `created_by` narrows a shared ledger
+ * to one member.
+ */
+async getSpendingSummary(startDate: string, endDate: string, createdBy: string | null) : Promise<Result<SpendingSummary, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_spending_summary", { startDate, endDate, createdBy }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Notable per-category facts about `month` ("YYYY-MM"), most significant
+ * first.
+ */
+async getCategoryInsights(month: string) : Promise<Result<CategoryInsight[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_category_insights", { month }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Month `a` against month `b` ("YYYY-MM"): category deltas, merchants that
+ * came and went, and budget adherence.
+ */
+async compareMonths(a: string, b: string) : Promise<Result<MonthComparison, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("compare_months", { a, b }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Spending by weekday and time of day for a date range.
+ */
+async getWeekdayPatterns(startDate: string, endDate: string) : Promise<Result<WeekdayPatterns, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_weekday_patterns", { startDate, endDate }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Write a privacy-filtered HTML/JSON snapshot to the downloads folder.
+ */
+async exportSnapshot(range: DateRange, privacy: Privacy) : Promise<Result<SnapshotFiles, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("export_snapshot", { range, privacy }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Every category, income source and account with the account name it is
+ * exported under.
+ */
+async getLedgerAccountMapping() : Promise<Result<AccountMapping, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_ledger_account_mapping") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Write a ledger-cli or beancount file to the downloads folder. A given
+ * mapping is remembered for the next export.
+ */
+async exportLedger(format: LedgerFormat, range: DateRange, mapping: AccountMapping | null) : Promise<Result<LedgerExport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("export_ledger", { format, range, mapping }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Write an Excel workbook of expenses, budgets, goals and spending per
+ * category to the downloads folder.
+ */
+async exportXlsx(range: DateRange) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("export_xlsx", { range }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Write check-ins, bill due dates and goal target dates to an .ics file in
+ * the downloads folder.
+ */
+async exportIcal() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("export_ical") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Write an encrypted `.goaldy` archive of all local data to `path`.
+ */
+async exportArchive(path: string, passphrase: string) : Promise<Result<Manifest, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("export_archive", { path, passphrase }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Decrypt an archive into a staging area and report which migrations its
+ * database still needs (run by the frontend before `import_archive`).
+ */
+async stageArchive(path: string, passphrase: string) : Promise<Result<StagedArchive, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("stage_archive", { path, passphrase }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Replace or merge local data with a staged archive.
+ */
+async importArchive(stagingId: string, mode: ImportMode) : Promise<Result<ArchiveImportReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("import_archive", { stagingId, mode }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Start serving all local data to another device on the same network.
+ */
+async startTransfer() : Promise<Result<TransferOffer, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("start_transfer") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Stop serving the current transfer offer.
+ */
+async cancelTransfer() : Promise<void> {
+    await TAURI_INVOKE("cancel_transfer");
+},
+/**
+ * Download the archive behind a scanned transfer code and stage it for
+ * `import_archive`.
+ */
+async receiveTransfer(payload: string) : Promise<Result<StagedArchive, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("receive_transfer", { payload }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The transfer or pairing code the app was opened with, once. Check on
+ * startup, as `transfer://scanned` may have fired before anyone listened.
+ */
+async takeScannedCode() : Promise<ScannedCode | null> {
+    return await TAURI_INVOKE("take_scanned_code");
+},
+/**
+ * Deleted items that can still be restored, most recent first.
+ */
+async getTrash() : Promise<Result<TrashedItem[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_trash") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async restoreFromTrash(kind: TrashKind, id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("restore_from_trash", { kind, id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * How many days deleted items stay in the trash.
+ */
+async getTrashRetention() : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_trash_retention") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setTrashRetention(days: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_trash_retention", { days }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Delete expired items for good now instead of on the next daily run.
+ */
+async purgeTrash() : Promise<Result<PurgeReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("purge_trash") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Month and week streaks of every active habit, brought up to date.
+ */
+async getHabitStreaks() : Promise<Result<HabitStreak[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_habit_streaks") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Savings goals and habits the check-in for `month` ("YYYY-MM", this month
+ * by default) still asks about, with suggested amounts.
+ */
+async getPendingCheckins(month: string | null) : Promise<Result<PendingCheckin[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_pending_checkins", { month }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Unresolved sync conflicts with local and remote values side by side.
+ */
+async getPendingConflicts() : Promise<Result<Conflict[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_pending_conflicts") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * A single unresolved conflict.
+ */
+async getConflict(id: string) : Promise<Result<Conflict, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_conflict", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Settle a conflict by keeping the local or the remote version.
+ */
+async resolveConflict(id: string, resolution: Resolution) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("resolve_conflict", { id, resolution }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Sync queue items for the debug screen, optionally filtered by table and
+ * status.
+ */
+async inspectSyncQueue(filter: QueueFilter | null) : Promise<Result<QueueItem[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("inspect_sync_queue", { filter }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Reset a queue item so the next sync tries it again.
+ */
+async retryItem(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("retry_item", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Reset every queue item that ran out of attempts.
+ */
+async retryAllFailed() : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("retry_all_failed") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Drop a queue item without pushing it.
+ */
+async discardItem(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("discard_item", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Merge queued changes to the same record now; the worker also does this
+ * before every push.
+ */
+async compactSyncQueue() : Promise<Result<CompactReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("compact_sync_queue") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Record a failed push and schedule the item's next attempt.
+ */
+async recordSyncFailure(id: string, error: string) : Promise<Result<FailureOutcome, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("record_sync_failure", { id, error }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Edits, sync events and conflict resolutions for one record, oldest first.
+ */
+async getChangeTimeline(table: string, id: string) : Promise<Result<TimelineEntry[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_change_timeline", { table, id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Probe the network now and report whether it is reachable.
+ */
+async isOnline() : Promise<boolean> {
+    return await TAURI_INVOKE("is_online");
+},
+/**
+ * Record whether the current connection is metered and, where the
+ * platform says, what type it is. Pushes right away once a connection
+ * turns unmetered, in case sync waited for one.
+ */
+async setMeteredConnection(metered: boolean, networkType: NetworkType | null) : Promise<void> {
+    await TAURI_INVOKE("set_metered_connection", { metered, networkType });
+},
+/**
+ * Tell the backend which Supabase project to talk to.
+ */
+async configureBackend(config: BackendConfig) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("configure_backend", { config }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Upload and download pending attachment files now.
+ */
+async syncAttachments() : Promise<Result<SyncReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("sync_attachments") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Attach the photo or PDF at `path` to an expense, shrinking photos.
+ */
+async saveAttachment(expenseId: string, path: string) : Promise<Result<Attachment, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("save_attachment", { expenseId, path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The files attached to an expense, with their paths on this device.
+ */
+async getExpenseAttachments(expenseId: string) : Promise<Result<Attachment[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_expense_attachments", { expenseId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Remove an attachment here and, on the next sync, remotely.
+ */
+async deleteAttachment(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_attachment", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Remove files left behind by deleted expenses now.
+ */
+async cleanUpAttachments() : Promise<Result<CleanupReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("clean_up_attachments") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Configured backup targets with their last outcome.
+ */
+async getBackupTargets() : Promise<Result<BackupTarget[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_backup_targets") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The iCloud Drive folder to suggest on macOS, if there is one.
+ */
+async getIcloudDriveDir() : Promise<string | null> {
+    return await TAURI_INVOKE("get_icloud_drive_dir");
+},
+/**
+ * Back up into a folder picked by the user.
+ */
+async addBackupFolder(path: string, passphrase: string, intervalHours: number) : Promise<Result<BackupTarget, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_backup_folder", { path, passphrase, intervalHours }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Sign in to Dropbox or Google Drive in the browser and back up there.
+ */
+async connectBackupProvider(provider: BackupProvider, clientId: string, clientSecret: string | null, passphrase: string, intervalHours: number) : Promise<Result<BackupTarget, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("connect_backup_provider", { provider, clientId, clientSecret, passphrase, intervalHours }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Stop backing up to a target. Backups already made stay where they are.
+ */
+async removeBackupTarget(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_backup_target", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Back up to one target right away.
+ */
+async runBackup(id: string) : Promise<Result<BackupTarget, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("run_backup", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * What new periods' budgets start from.
+ */
+async getBudgetTemplate() : Promise<Result<BudgetTemplate, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_budget_template") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Change what new periods' budgets start from.
+ */
+async saveBudgetTemplate(template: BudgetTemplate) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("save_budget_template", { template }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Final numbers of past months and pay periods, newest first.
+ */
+async getMonthCloses(limit: number | null) : Promise<Result<MonthClose[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_month_closes", { limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * How budget periods line up with paydays.
+ */
+async getPaySchedule() : Promise<Result<PaySchedule, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_pay_schedule") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Change how budget periods line up with paydays.
+ */
+async savePaySchedule(schedule: PaySchedule) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("save_pay_schedule", { schedule }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The budget period `date` falls in, today if not given.
+ */
+async getBudgetPeriod(date: string | null) : Promise<Result<Period, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_budget_period", { date }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Spending, safe-to-spend and forecast for the current budget period.
+ */
+async getBudgetStatus() : Promise<Result<BudgetStatus, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_budget_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getIncomeSources() : Promise<Result<IncomeSource[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_income_sources") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async createIncomeSource(input: NewIncomeSource) : Promise<Result<IncomeSource, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_income_source", { input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Stop expecting income from a source; its logged income is kept.
+ */
+async deleteIncomeSource(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_income_source", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getIncomeEntries(startDate: string, endDate: string) : Promise<Result<IncomeEntry[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_income_entries", { startDate, endDate }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Record money received from a source.
+ */
+async logIncome(input: NewIncomeEntry) : Promise<Result<IncomeEntry, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("log_income", { input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteIncomeEntry(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_income_entry", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Expected against received income per source this budget period.
+ */
+async getIncomeVariance() : Promise<Result<IncomeVariance[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_income_variance") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Enter an account's balance on a day.
+ */
+async recordAccountBalance(input: NewAccountBalance) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("record_account_balance", { input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Every account with its latest balance.
+ */
+async getAccountBalances() : Promise<Result<AccountBalance[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_account_balances") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Month-end net worth, oldest first, ending with today.
+ */
+async getNetWorthHistory(months: number | null) : Promise<Result<NetWorthPoint[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_net_worth_history", { months }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * What past months left over for savings, and which goals ask for more
+ * than that usually allows.
+ */
+async getGoalFundingForecast(months: number | null) : Promise<Result<FundingForecast, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_goal_funding_forecast", { months }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Completion date, the contribution needed to finish on time and how
+ * smaller contributions would play out. `interest_rate` is annual, as a
+ * fraction.
+ */
+async getGoalForecast(goalId: string, interestRate: number | null) : Promise<Result<GoalForecast, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_goal_forecast", { goalId, interestRate }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Versions, install id and housekeeping timestamps.
+ */
+async getAppMeta() : Promise<Result<AppMeta, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_app_meta") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The stored session row, created empty if missing.
+ */
+async getOrCreateAuthState() : Promise<Result<AuthState, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_or_create_auth_state") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Replace the stored session; all fields empty signs out.
+ */
+async updateAuthState(update: AuthStateUpdate) : Promise<Result<AuthState, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_auth_state", { update }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Notification preferences, created with the defaults if missing.
+ */
+async getOrCreateNotificationPreferences() : Promise<Result<NotificationPreferences, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_or_create_notification_preferences") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Change some notification preferences, queue them for sync and move the
+ * recurring notifications, here and with the OS, to the new schedule.
+ */
+async updateNotificationPreferences(update: NotificationPreferencesUpdate) : Promise<Result<NotificationPreferences, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_notification_preferences", { update }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getCategoryAlertRules() : Promise<Result<CategoryAlertRule[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_category_alert_rules") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async saveCategoryAlertRule(rule: CategoryAlertRule) : Promise<Result<CategoryAlertRule, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("save_category_alert_rule", { rule }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteCategoryAlertRule(categoryId: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_category_alert_rule", { categoryId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Called after an expense in `category_id` is written.
+ */
+async checkCategoryAlerts(categoryId: string) : Promise<Result<CategoryAlert | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("check_category_alerts", { categoryId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Percentages of the budget to warn at.
+ */
+async getBudgetAlertThresholds() : Promise<Result<number[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_budget_alert_thresholds") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setBudgetAlertThresholds(thresholds: number[]) : Promise<Result<number[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_budget_alert_thresholds", { thresholds }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Import a bank statement CSV export for reconciliation.
+ */
+async importStatement(name: string, content: string) : Promise<Result<StatementImport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("import_statement", { name, content }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Match a statement's lines to expenses and report what is unmatched.
+ */
+async reconcileStatement(importId: string, range: StatementRange | null) : Promise<Result<ReconciliationReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reconcile_statement", { importId, range }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Plan and premium features of the signed-in account.
+ */
+async getEntitlements() : Promise<Result<Entitlements, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_entitlements") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async hasEntitlement(feature: Feature) : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("has_entitlement", { feature }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Validate a purchase with the license server, or restore purchases
+ * when `receipt` is omitted.
+ */
+async verifyPurchase(receipt: Receipt | null) : Promise<Result<Entitlements, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("verify_purchase", { receipt }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Redeem an invite code typed in by hand rather than opened as a link.
+ */
+async redeemInviteCode(code: string) : Promise<Result<Referral, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("redeem_invite_code", { code }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getReferrals() : Promise<Result<Referral[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_referrals") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * This install's variant of `experiment`, or `None` if it isn't running.
+ */
+async getVariant(experiment: string) : Promise<Result<Assignment | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_variant", { experiment }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Record that the user was shown `experiment`.
+ */
+async logExperimentExposure(experiment: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("log_experiment_exposure", { experiment }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Called after expense writes with the cells before and after the change.
+ */
+async updateCategoryTotals(cells: Cell[]) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_category_totals", { cells }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async rebuildCategoryTotals() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("rebuild_category_totals") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Cached spending per category for `month` ("YYYY-MM").
+ */
+async getMonthlyCategoryTotals(month: string) : Promise<Result<CategoryTotal[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_monthly_category_totals", { month }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Broadcast a write made by the frontend to every window.
+ */
+async publishDomainEvent(event: DomainEvent) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("publish_domain_event", { event }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Hand the next pending notifications to the OS again after the queue
+ * changed. A no-op off mobile.
+ */
+async registerPlatformNotifications() : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("register_platform_notifications") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Expenses dated between `start_date` and `end_date`, with their category.
+ */
+async getExpenses(startDate: string, endDate: string) : Promise<Result<ExpenseWithCategory[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_expenses", { startDate, endDate }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getRecentExpenses(limit: number | null) : Promise<Result<ExpenseWithCategory[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_recent_expenses", { limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Expenses (and optionally feedback notes) matching `query`, best first.
+ */
+async searchExpenses(query: string, startDate: string | null, endDate: string | null, limit: number | null, includeNotes: boolean | null) : Promise<Result<SearchResults, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("search_expenses", { query, startDate, endDate, limit, includeNotes }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async createExpense(input: NewExpense) : Promise<Result<Expense, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_expense", { input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateExpense(id: string, update: ExpenseUpdate) : Promise<Result<Expense, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_expense", { id, update }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteExpense(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_expense", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The current period's budget, if one was set.
+ */
+async getCurrentBudget() : Promise<Result<Budget | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_current_budget") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Set the current period's budget.
+ */
+async saveBudget(input: BudgetInput) : Promise<Result<Budget, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("save_budget", { input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSavingsGoals() : Promise<Result<SavingsGoal[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_savings_goals") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSavingsGoal(id: string) : Promise<Result<SavingsGoal | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_savings_goal", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async createSavingsGoal(input: NewGoal) : Promise<Result<SavingsGoal, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_savings_goal", { input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateSavingsGoal(id: string, update: GoalUpdate) : Promise<Result<SavingsGoal, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_savings_goal", { id, update }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Move a goal and its contributions to the trash.
+ */
+async deleteSavingsGoal(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_savings_goal", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getGoalContributions(goalId: string) : Promise<Result<Contribution[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_goal_contributions", { goalId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Record a month's contribution to a goal, replacing any already there.
+ */
+async saveGoalContribution(goalId: string, input: ContributionInput) : Promise<Result<Contribution, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("save_goal_contribution", { goalId, input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Push the sync queue now instead of waiting for the worker's next pass.
+ */
+async pushSyncQueue() : Promise<Result<PushReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("push_sync_queue") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * How conflicts are settled, for every table that can have them.
+ */
+async getConflictStrategies() : Promise<Result<TableStrategy[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_conflict_strategies") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setConflictStrategy(tableName: string, strategy: Strategy) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_conflict_strategy", { tableName, strategy }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Whether each synced table is on, in push order.
+ */
+async getSyncSettings() : Promise<Result<TableSyncSetting[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_sync_settings") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Keep a table out of cloud sync, or bring it back.
+ */
+async setTableSync(tableName: string, enabled: boolean) : Promise<Result<TableSyncSetting[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_table_sync", { tableName, enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Sync state, queue depth and the last push and pull of each table.
+ */
+async getSyncHealth() : Promise<Result<SyncHealth, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_sync_health") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Recent sync runs, newest first.
+ */
+async getSyncLog(limit: number | null) : Promise<Result<SyncRun[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_sync_log", { limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Compare everything with the server and repair what drifted apart. Run
+ * with `dry_run` first to show the user what would change.
+ */
+async forceFullResync(dryRun: boolean) : Promise<Result<ResyncReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("force_full_resync", { dryRun }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The "sync only on Wi-Fi" preference and whether the current connection
+ * passes it.
+ */
+async getSyncPolicy() : Promise<Result<SyncPolicy, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_sync_policy") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setSyncWifiOnly(wifiOnly: boolean) : Promise<Result<SyncPolicy, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_sync_wifi_only", { wifiOnly }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Whether this device is serving peer sync, and under which name.
+ */
+async getPeerSyncStatus() : Promise<Result<PeerStatus, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_peer_sync_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Let paired devices on the same network sync with this one.
+ */
+async startPeerSync(deviceName: string) : Promise<Result<PeerStatus, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("start_peer_sync", { deviceName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async stopPeerSync() : Promise<Result<PeerStatus, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("stop_peer_sync") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Show a pairing code for another device to scan.
+ */
+async offerPeerPairing() : Promise<Result<PairingOffer, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("offer_peer_pairing") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Pair with the device that showed a scanned pairing code.
+ */
+async pairWithPeer(payload: string) : Promise<Result<Peer, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("pair_with_peer", { payload }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSyncPeers() : Promise<Result<Peer[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_sync_peers") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async removeSyncPeer(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_sync_peer", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Devices with peer sync on in the same network, paired or not.
+ */
+async discoverSyncPeers() : Promise<Result<DiscoveredPeer[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("discover_sync_peers") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Exchange changes with a paired device.
+ */
+async syncWithPeer(id: string) : Promise<Result<PeerSyncReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("sync_with_peer", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Settle a pulled row against unsynced local edits before it is merged.
+ */
+async reconcilePulledRow(tableName: string, remote: Partial<{ [key in string]: JsonValue }>) : Promise<Result<PullOutcome, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reconcile_pulled_row", { tableName, remote }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSyncEncryptionStatus() : Promise<Result<SyncEncryptionStatus, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_sync_encryption_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Encrypt synced data with a new key. The recovery code is shown once.
+ */
+async enableSyncEncryption() : Promise<Result<RecoveryKit, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("enable_sync_encryption") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The sync key, for a backup the user keeps themselves.
+ */
+async exportSyncKey() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("export_sync_key") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async importSyncKey(key: string) : Promise<Result<SyncEncryptionStatus, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("import_sync_key", { key }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Restore the sync key on this device with the recovery code.
+ */
+async recoverSyncKey(code: string) : Promise<Result<SyncEncryptionStatus, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("recover_sync_key", { code }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Ask a device that has the sync key to share it with this one.
+ */
+async requestSyncKey(deviceName: string) : Promise<Result<KeyRequest, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("request_sync_key", { deviceName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSyncKeyRequests() : Promise<Result<KeyRequest[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_sync_key_requests") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async approveSyncKeyRequest(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("approve_sync_key_request", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Take the sync key once another device approved this one's request.
+ */
+async receiveSyncKey() : Promise<Result<SyncEncryptionStatus, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("receive_sync_key") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Decrypt the sealed rows of a pull before they are merged.
+ */
+async openSyncRows(rows: (Partial<{ [key in string]: JsonValue }>)[]) : Promise<Result<(Partial<{ [key in string]: JsonValue }>)[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("open_sync_rows", { rows }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Line the recurring notifications up with the preferences.
+ */
+async rescheduleNotifications() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reschedule_notifications") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Show the notifications that are due now.
+ */
+async dispatchDueNotifications() : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("dispatch_due_notifications") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Import spending from a CSV file with the given column mapping. With
+ * `dry_run` nothing is written and the report is a preview.
+ */
+async importCsv(path: string, mapping: ColumnMapping, dryRun: boolean) : Promise<Result<ImportReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("import_csv", { path, mapping, dryRun }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Import spending from an OFX or QIF bank statement, detecting which if
+ * `format` is not given. With `dry_run` nothing is written.
+ */
+async importBankFile(path: string, format: StatementFormat | null, options: StatementOptions, dryRun: boolean) : Promise<Result<ImportReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("import_bank_file", { path, format, options, dryRun }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Read a YNAB or Mint export and list its categories with suggested
+ * matches, for the mapping step before importing.
+ */
+async previewAppExport(path: string, format: AppFormat | null, dateFormat: string | null) : Promise<Result<AppExportPreview, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("preview_app_export", { path, format, dateFormat }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Import spending from a YNAB or Mint export with its categories mapped.
+ * With `dry_run` nothing is written.
+ */
+async importAppExport(path: string, options: AppImportOptions, dryRun: boolean) : Promise<Result<ImportReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("import_app_export", { path, options, dryRun }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Write a JSON backup of expenses, budgets, goals and habits to the export folder.
+ */
+async exportBackup() : Promise<Result<BackupExport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("export_backup") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Restore a JSON backup, replacing existing data only with `overwrite`.
+ */
+async importBackup(path: string, overwrite: boolean | null) : Promise<Result<BackupRestore, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("import_backup", { path, overwrite }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Known currencies, the base currency first.
+ */
+async getCurrencies() : Promise<Result<Currency[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_currencies") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Make `code` the currency amounts are kept and budgeted in.
+ */
+async setBaseCurrency(code: string) : Promise<Result<Currency[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_base_currency", { code }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The rate from `from` to `to` on `date` ("YYYY-MM-DD", today if not given).
+ */
+async getExchangeRate(from: string, to: string, date: string | null) : Promise<Result<ExchangeRate, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_exchange_rate", { from, to, date }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Fetch today's rates now. Returns the day they were published.
+ */
+async refreshExchangeRates() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("refresh_exchange_rates") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Category allocations for the period `date` falls in, today if not given.
+ */
+async getCategoryBudgets(date: string | null) : Promise<Result<CategoryBudget[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_category_budgets", { date }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Set what a category may spend in the period `date` falls in.
+ */
+async allocateCategoryBudget(categoryId: string, amount: number, date: string | null) : Promise<Result<CategoryBudget, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("allocate_category_budget", { categoryId, amount, date }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Drop a category's allocation for the period `date` falls in.
+ */
+async removeCategoryBudget(categoryId: string, date: string | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_category_budget", { categoryId, date }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Spent against allocated per category for the period `date` falls in.
+ */
+async getCategoryEnvelopes(date: string | null) : Promise<Result<EnvelopeSummary, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_category_envelopes", { date }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Recategorize, delete, move or tag the given expenses in one go. The
+ * result carries a token to undo it with.
+ */
+async bulkUpdateExpenses(expenseIds: string[], operation: BulkOperation) : Promise<Result<BulkResult, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("bulk_update_expenses", { expenseIds, operation }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async undoBulkUpdate(undoToken: string) : Promise<Result<UndoResult, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("undo_bulk_update", { undoToken }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Whether the database is encrypted, and whether this build can do it.
+ */
+async getEncryptionStatus() : Promise<Result<EncryptionStatus, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_encryption_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Encrypt the database and restart into it. With a passphrase the key is
+ * derived from it; otherwise a random key is kept in the keychain.
+ */
+async enableDatabaseEncryption(passphrase: string | null) : Promise<Result<EncryptionStatus, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("enable_database_encryption", { passphrase }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+}
+}
+
+/** user-defined events **/
+
+
+
+/** user-defined constants **/
+
+
+
+/** user-defined types **/
+
+export type Account = { id: string; user_id: string | null; name: string; 
+/**
+ * cash, checking, savings or credit_card.
+ */
+kind: string; opening_balance: number; 
+/**
+ * "YYYY-MM-DD"; what happened before is in the opening balance.
+ */
+opening_date: string; 
+/**
+ * Negative for a credit card with money owed on it.
+ */
+balance: number; balance_updated_at: string | null; created_at: string; updated_at: string; deleted_at: string | null }
+export type AccountBalance = { id: string; user_id: string | null; 
+/**
+ * Identifies the account across snapshots.
+ */
+account_key: string; account_name: string; 
+/**
+ * asset or liability; liabilities are stored as positive amounts.
+ */
+kind: string; balance: number; 
+/**
+ * "YYYY-MM-DD".
+ */
+as_of: string; 
+/**
+ * manual or derived.
+ */
+source: string; created_at: string; updated_at: string; deleted_at: string | null }
+/**
+ * One line of an account's statement.
+ */
+export type AccountEntry = { 
+/**
+ * expense, income, transfer_in or transfer_out.
+ */
+kind: string; 
+/**
+ * Id of the expense, income entry or transfer.
+ */
+id: string; 
+/**
+ * "YYYY-MM-DD".
+ */
+date: string; 
+/**
+ * Positive when money came in.
+ */
+amount: number; note: string | null }
+export type AccountKind = "asset" | "liability"
+/**
+ * Account names by id. Anything left out gets its default name.
+ */
+export type AccountMapping = { 
+/**
+ * Category id to account, such as "Expenses:Food:Groceries".
+ */
+categories: Partial<{ [key in string]: string }>; 
+/**
+ * Income source id to account.
+ */
+income_sources: Partial<{ [key in string]: string }>; 
+/**
+ * The app's accounts by id.
+ */
+accounts: Partial<{ [key in string]: string }>; 
+/**
+ * For spending and income without an account; "Assets:Cash" if not
+ * given.
+ */
+default_account: string | null }
+export type AccountType = "cash" | "checking" | "savings" | "credit_card"
+/**
+ * One of the app's categories as found in the file.
+ */
+export type AppCategory = { name: string; count: number; total: number; 
+/**
+ * Last import's choice, or the category with a matching name.
+ */
+suggested_category_id: string | null }
+export type AppExportPreview = { format: AppFormat; 
+/**
+ * Most spending first.
+ */
+categories: AppCategory[]; rows: number; 
+/**
+ * Transfers, income and rows that couldn't be read.
+ */
+skipped: number }
+export type AppFormat = "ynab" | "mint"
+export type AppImportOptions = { 
+/**
+ * Told from the headers if not given.
+ */
+format: AppFormat | null; 
+/**
+ * The app's category name to a category id here.
+ */
+category_map?: Partial<{ [key in string]: string }>; 
+/**
+ * Currency of the amounts; the base currency if not given.
+ */
+currency: string | null; 
+/**
+ * A chrono format such as "%d.%m.%Y" for files written with another
+ * locale.
+ */
+date_format: string | null }
+export type AppMeta = { app_version: string; schema_version: string | null; data_format_version: number | null; install_id: string | null; last_backup_at: string | null; last_maintenance_at: string | null }
+export type ArchiveImportReport = { mode: ImportMode; tables: TableImport[]; attachments: number }
+export type Assignment = { experiment: string; variant: string; assigned_at: string; exposed_at: string | null }
+export type Attachment = { id: string; expense_id: string; file_name: string; mime_type: string; size_bytes: number; sha256: string; sync_status: string; created_at: string; 
+/**
+ * Absolute path of the file on this device.
+ */
+path: string; 
+/**
+ * False while a file added on another device is still downloading.
+ */
+available: boolean }
+export type AuthState = { user_id: string | null; email: string | null; access_token: string | null; refresh_token: string | null; expires_at: string | null; last_sync_at: string | null }
+/**
+ * A new session, or all `None` to sign out. The sync timestamp is kept.
+ */
+export type AuthStateUpdate = { user_id: string | null; email: string | null; access_token: string | null; refresh_token: string | null; expires_at: string | null }
+export type AutomationStatus = { enabled: boolean; 
+/**
+ * Where the API answers, while it is up.
+ */
+url: string | null; 
+/**
+ * For the `Authorization: Bearer` header; set once the API was turned
+ * on.
+ */
+token: string | null }
+export type BackendConfig = { 
+/**
+ * Project URL without a trailing slash.
+ */
+url: string; anon_key: string }
+export type BackupExport = { path: string; tables: TableCount[] }
+export type BackupProvider = "dropbox" | "google_drive" | "folder"
+export type BackupRestore = { schema_version: string | null; created_at: string; tables: TableCount[] }
+/**
+ * A target as shown in settings; credentials stay on the Rust side.
+ */
+export type BackupTarget = { id: string; provider: BackupProvider; folder_path: string | null; interval_hours: number; last_backup_at: string | null; last_error: string | null; created_at: string }
+export type BankConnection = { id: string; institution_id: string; institution_name: string; 
+/**
+ * "gocardless" or "plaid".
+ */
+provider: string; 
+/**
+ * "pending" until approved at the bank, then "linked"; "expired" or
+ * "failed" need a new connection.
+ */
+status: string; 
+/**
+ * The bank's approval page, while pending.
+ */
+link: string; account_count: number; access_valid_until: string | null; last_fetched_at: string | null; last_error: string | null; created_at: string }
+export type BankTransaction = { id: string; connection_id: string; institution_name: string; 
+/**
+ * The bank's name for the account it was paid from.
+ */
+bank_account_name: string | null; 
+/**
+ * The account here it is paid from once confirmed.
+ */
+account_id: string | null; 
+/**
+ * "YYYY-MM-DD".
+ */
+date: string; 
+/**
+ * Positive, in `currency`.
+ */
+amount: number; currency: string; description: string | null; 
+/**
+ * Suggested by the categorization rules.
+ */
+category_id: string | null }
+export type Bill = { id: string; user_id: string | null; name: string; amount: number; 
+/**
+ * Day of the month, 1 to 31.
+ */
+due_day: number; autopay: boolean; category_id: string | null; remind_days_before: number; reminder_id: string | null; created_at: string; updated_at: string; deleted_at: string | null }
+export type BillDue = ({ id: string; user_id: string | null; name: string; amount: number; 
+/**
+ * Day of the month, 1 to 31.
+ */
+due_day: number; autopay: boolean; category_id: string | null; remind_days_before: number; reminder_id: string | null; created_at: string; updated_at: string; deleted_at: string | null }) & { 
+/**
+ * "YYYY-MM-DD" of the open cycle.
+ */
+due_date: string; 
+/**
+ * The due date went by without the bill being paid.
+ */
+overdue: boolean; last_paid_on: string | null }
+export type BillPayment = { id: string; bill_id: string; 
+/**
+ * "YYYY-MM" of the cycle paid.
+ */
+month: string; amount: number; paid_on: string; 
+/**
+ * The expense logged for it, if one was.
+ */
+expense_id: string | null; created_at: string }
+export type Budget = { id: string; user_id: string | null; 
+/**
+ * Key of the budget period, "YYYY-MM" for calendar months.
+ */
+month: string; total_amount: number; spending_limit: number | null; created_by: string | null; created_at: string; updated_at: string; deleted_at: string | null; 
+/**
+ * The household it is shared with; see `households`.
+ */
+household_id: string | null }
+export type BudgetAdherence = { limit_a: number | null; limit_b: number | null; 
+/**
+ * Share of the (prorated) limit spent, 1.0 being exactly on budget.
+ */
+used_a: number | null; used_b: number | null; 
+/**
+ * `used_b - used_a`; negative means `b` stuck to its budget better.
+ */
+difference: number | null }
+/**
+ * Amounts for the current period's budget.
+ */
+export type BudgetInput = { total_amount: number; spending_limit: number | null }
+export type BudgetStatus = { period: Period; budget: Budget | null; spent: number; 
+/**
+ * Left to spend against the budget's limit; negative once overspent.
+ */
+remaining: number | null; 
+/**
+ * Days left in the period, counting today.
+ */
+days_left: number; 
+/**
+ * What can still go out each day without overspending.
+ */
+safe_to_spend_per_day: number | null; 
+/**
+ * Where spending ends up by the last day at the pace so far.
+ */
+projected_spend: number }
+/**
+ * What a new period's budget starts from. `None` amounts copy the previous
+ * period's budget.
+ */
+export type BudgetTemplate = { total_amount: number | null; spending_limit: number | null; 
+/**
+ * Add last period's leftover (or overspend) to the new limit.
+ */
+carry_over: boolean }
+export type BudgetUtilization = { 
+/**
+ * Budget period key.
+ */
+period: string; start: string; end: string; 
+/**
+ * What the period is measured against; `None` without a budget.
+ */
+budget_limit: number | null; spent: number; remaining: number | null; 
+/**
+ * Share of the limit spent, 1.0 being exactly on budget.
+ */
+used: number | null }
+export type BulkOperation = 
+/**
+ * Move to `category_id`, or leave uncategorized with `null`.
+ */
+{ kind: "recategorize"; category_id: string | null } | 
+/**
+ * Into the trash, like deleting one by one.
+ */
+{ kind: "delete" } | 
+/**
+ * Move every date by `days`, earlier when negative. Spending in
+ * another currency is converted again at the new date's rate.
+ */
+{ kind: "shift_date"; days: number } | 
+/**
+ * Add a tag, or with `remove` take it off.
+ */
+{ kind: "tag"; tag_id: string; remove?: boolean }
+export type BulkResult = { 
+/**
+ * The expenses that changed; those already as asked are left out.
+ */
+expense_ids: string[]; 
+/**
+ * `None` when nothing changed.
+ */
+undo_token: string | null }
+export type Cadence = "weekly" | "biweekly" | "monthly" | "quarterly" | "yearly"
+export type CashflowBaseline = { 
+/**
+ * "YYYY-MM", inclusive: the full months averaged, up to last month.
+ */
+from_month: string; to_month: string; average_income: number; average_expenses: number; average_net: number; 
+/**
+ * The requested share of the average income, as a budget limit.
+ */
+suggested_limit: number | null }
+export type CategorizationRule = { id: string; pattern: string; match_field: MatchField; category_id: string; priority: number; created_at: string; updated_at: string }
+export type CategoryAlert = { category_id: string; category_name: string; threshold: number; spent: number; limit_amount: number }
+export type CategoryAlertRule = { category_id: string; 
+/**
+ * Spending limit for the category per budget period, for periods
+ * without a category budget.
+ */
+limit_amount: number; 
+/**
+ * Percentages of the limit to warn at; empty never warns.
+ */
+thresholds: number[] }
+export type CategoryBudget = { id: string; 
+/**
+ * Key of the budget period.
+ */
+month: string; category_id: string; amount: number; created_at: string; updated_at: string }
+export type CategoryDelta = { category_id: string | null; category_name: string; amount_a: number; amount_b: number; delta: number; 
+/**
+ * Change relative to `a`; `None` when nothing was spent in `a`.
+ */
+percent: number | null }
+export type CategoryInsight = ({ kind: "above_average"; percent: number; average: number } | { kind: "below_average"; percent: number; average: number } | 
+/**
+ * Nothing was spent here in the months before.
+ */
+{ kind: "new_spending" } | { kind: "growth_streak"; months: number }) & { category_id: string | null; category_name: string; 
+/**
+ * Net spend this month, scaled up if the month isn't over.
+ */
+amount: number; message: string; 
+/**
+ * Money behind the fact; insights are sorted by it.
+ */
+score: number }
+export type CategorySuggestion = { category_id: string; 
+/**
+ * Posterior probability among the ranked categories, `0.0..=1.0`.
+ */
+score: number }
+export type CategoryTotal = { category_id: string | null; category_name: string | null; 
+/**
+ * Net of reimbursed expenses.
+ */
+total: number; gross: number; expense_count: number }
+/**
+ * A cell to recompute.
+ */
+export type Cell = { 
+/**
+ * The expense date; only its "YYYY-MM" part matters.
+ */
+date: string; category_id: string | null }
+export type CheckinKind = "savings_goal" | "habit_goal"
+export type CleanupReport = { 
+/**
+ * Attachments of deleted expenses marked for removal.
+ */
+marked: number; removed_files: number }
+/**
+ * A column by its header or by zero-based position.
+ */
+export type Column = number | string
+export type ColumnMapping = { date: Column; amount: Column; description: Column | null; 
+/**
+ * A chrono format such as "%d.%m.%Y"; common formats are tried if not
+ * given.
+ */
+date_format: string | null; 
+/**
+ * Defaults to true. Without headers, columns can only be given by
+ * position.
+ */
+has_headers: boolean | null; 
+/**
+ * Category for every imported expense.
+ */
+category_id: string | null; 
+/**
+ * Currency of the amounts, such as a card statement from abroad; the
+ * base currency if not given.
+ */
+currency: string | null }
+export type CompactReport = { 
+/**
+ * Records whose items were merged.
+ */
+records: number; 
+/**
+ * Items removed by merging.
+ */
+removed: number }
+export type CompletedGoal = { goal_id: string; name: string; target_amount: number; 
+/**
+ * "YYYY-MM" of the contribution that reached the target.
+ */
+completed_month: string }
+export type ConfirmReport = { imported: number; 
+/**
+ * Already logged by hand since the fetch; marked, not imported.
+ */
+duplicates: number }
+/**
+ * A pending transaction to turn into an expense.
+ */
+export type ConfirmTransaction = { id: string; 
+/**
+ * The suggested category if not given.
+ */
+category_id: string | null }
+export type Conflict = { id: string; table_name: string; record_id: string; detected_at: string; 
+/**
+ * `None` if the record no longer exists locally.
+ */
+local_updated_at: string | null; remote_updated_at: string; 
+/**
+ * Every user-visible field both sides have, by name.
+ */
+fields: FieldDiff[] }
+export type Contribution = { id: string; user_id: string | null; goal_id: string; 
+/**
+ * "YYYY-MM".
+ */
+month: string; amount: number; 
+/**
+ * 1 when the planned monthly amount was saved in full.
+ */
+is_full_amount: number | null; created_at: string; updated_at: string; deleted_at: string | null }
+/**
+ * A month's contribution to record; replaces one already there.
+ */
+export type ContributionInput = { 
+/**
+ * "YYYY-MM".
+ */
+month: string; amount: number; is_full_amount: boolean }
+/**
+ * User secrets from the GoCardless Bank Account Data portal.
+ */
+export type Credentials = { secret_id: string; secret_key: string }
+export type Currency = { code: string; name: string; symbol: string; decimals: number; is_base: boolean }
+export type DailyAverage = { 
+/**
+ * "YYYY-MM-DD", inclusive. `end` is cut off at today.
+ */
+start: string; end: string; days: number; total: number; 
+/**
+ * `total / days`, counting days without spending.
+ */
+average: number; 
+/**
+ * Days with at least one expense.
+ */
+spending_days: number; 
+/**
+ * The most spent on a single day.
+ */
+highest: number }
+export type DateRange = { 
+/**
+ * "YYYY-MM-DD", inclusive.
+ */
+start_date: string; 
+/**
+ * "YYYY-MM-DD", inclusive.
+ */
+end_date: string }
+export type Debt = { from: string | null; to: string | null; amount: number }
+export type DetectedSubscription = { 
+/**
+ * Match key of the merchant name.
+ */
+match_key: string; name: string; merchant_id: string | null; category_id: string | null; 
+/**
+ * Median charge.
+ */
+amount: number; cadence: string; occurrences: number; last_date: string; 
+/**
+ * When the next charge is expected.
+ */
+next_date: string; confidence: number; 
+/**
+ * "new", "dismissed" or "tracked".
+ */
+status: string; 
+/**
+ * The recurring expense it became, once tracked.
+ */
+recurring_id: string | null; detected_at: string; updated_at: string }
+export type DiscoveredPeer = { id: string; name: string; address: string; paired: boolean }
+export type DomainEvent = { type: "expense:created"; expense_id: string; category_id: string | null; amount: number; date: string } | { type: "expense:updated"; expense_id: string; category_id: string | null; amount: number; date: string } | { type: "expense:deleted"; expense_id: string } | 
+/**
+ * Several expenses written at once.
+ */
+{ type: "expense:imported"; source: string; count: number } | 
+/**
+ * Several expenses changed at once, or such a change undone.
+ */
+{ type: "expense:bulk_changed"; expense_ids: string[] } | { type: "expense:reimbursement_updated"; expense_id: string; status: ReimbursementStatus | null } | { type: "attachment:added"; attachment_id: string; expense_id: string } | { type: "attachment:deleted"; attachment_id: string } | { type: "budget:updated"; budget_id: string; month: string } | 
+/**
+ * The budget template or pay schedule changed.
+ */
+{ type: "budget:settings_updated" } | { type: "budget:category_updated"; month: string; category_id: string; amount: number | null } | { type: "goal:created"; goal_id: string } | { type: "goal:updated"; goal_id: string } | { type: "goal:deleted"; goal_id: string } | { type: "goal:contribution_added"; goal_id: string; contribution_id: string; month: string; amount: number } | { type: "recurring:created"; recurring_ids: string[] } | { type: "income:source_created"; source_id: string } | { type: "income:source_deleted"; source_id: string } | { type: "income:logged"; entry_id: string; source_id: string; amount: number; date: string } | { type: "income:entry_deleted"; entry_id: string } | { type: "net_worth:balance_recorded"; account_name: string } | { type: "trial:created"; trial_id: string } | { type: "trial:cancelled"; trial_id: string } | 
+/**
+ * A bill was added or changed, or a payment taken back.
+ */
+{ type: "bill:saved"; bill_id: string } | { type: "bill:deleted"; bill_id: string } | { type: "bill:paid"; bill_id: string; month: string } | 
+/**
+ * An expense was split, or its split changed or removed.
+ */
+{ type: "split:saved"; expense_id: string } | { type: "split:settlement_recorded"; settlement_id: string } | { type: "split:settlement_deleted"; settlement_id: string } | 
+/**
+ * An expense or budget was shared with a household, or stopped being.
+ */
+{ type: "household:share_changed"; record_id: string; household_id: string | null } | 
+/**
+ * A tag was added, renamed or recolored, or others merged into it.
+ */
+{ type: "tag:saved"; tag_id: string } | { type: "tag:deleted"; tag_id: string } | { type: "tag:expenses_tagged"; expense_ids: string[] } | 
+/**
+ * A merchant was renamed, or merged into the one already so named.
+ */
+{ type: "merchant:renamed"; previous_id: string; merchant_id: string } | 
+/**
+ * An account was added or changed, or its balance recomputed.
+ */
+{ type: "account:saved"; account_id: string } | { type: "account:deleted"; account_id: string } | { type: "account:transfer_saved"; transfer_id: string; from_account_id: string; to_account_id: string } | { type: "account:transfer_deleted"; transfer_id: string } | 
+/**
+ * A categorization rule was added or changed.
+ */
+{ type: "categorization_rule:saved"; rule_id: string } | { type: "categorization_rule:deleted"; rule_id: string } | { type: "category_alert:saved"; category_id: string } | { type: "category_alert:deleted"; category_id: string } | { type: "preferences:notifications_updated" } | { type: "currency:base_changed"; code: string } | 
+/**
+ * A backup or archive replaced or merged local data; reload
+ * everything.
+ */
+{ type: "data:restored"; source: string } | { type: "trash:restored"; kind: TrashKind; record_id: string } | { type: "sync:applied_remote_changes"; tables: string[]; count: number } | 
+/**
+ * A sync conflict was settled by keeping the remote version, which
+ * overwrote the local row.
+ */
+{ type: "sync:remote_kept"; table_name: string; record_id: string }
+export type EncryptionStatus = { 
+/**
+ * Whether this build can encrypt at all.
+ */
+available: boolean; enabled: boolean; key_source: KeySource | null; 
+/**
+ * The encrypted copy is waiting for a restart to take over.
+ */
+restart_required: boolean }
+export type Entitlements = { plan: string; status: Status; features: string[]; source: Source | null; expires_at: string | null; 
+/**
+ * Last day of the offline grace, while in it.
+ */
+grace_until: string | null }
+/**
+ * Spent against allocated for one category. Categories with spending but
+ * no allocation are included with `allocated: None`, and uncategorized
+ * spending has no `category_id`.
+ */
+export type Envelope = { category_id: string | null; category_name: string | null; category_icon: string | null; category_color: string | null; allocated: number | null; 
+/**
+ * Net of reimbursed expenses.
+ */
+spent: number }
+export type EnvelopeSummary = { period: Period; 
+/**
+ * The overall limit of the period's budget, if one was set.
+ */
+budget_limit: number | null; allocated: number; 
+/**
+ * Budget limit minus allocations; negative when over-allocated.
+ */
+unallocated: number | null; spent: number; envelopes: Envelope[] }
+export type ExchangeRate = { from: string; to: string; rate: number; 
+/**
+ * Publishing day of the rates used, which may be before the date asked
+ * for.
+ */
+rate_date: string }
+export type Expense = { id: string; user_id: string | null; amount: number; category_id: string | null; note: string | null; 
+/**
+ * "YYYY-MM-DD".
+ */
+date: string; created_at: string; updated_at: string; synced_at: string | null; deleted_at: string | null; reimbursement_status: string | null; reimbursement_owed_by: string | null; reimbursed_at: string | null; payment_method: string | null; created_by: string | null; 
+/**
+ * Set when the expense was paid in another currency.
+ */
+currency: string | null; 
+/**
+ * What was paid, in `currency`.
+ */
+original_amount: number | null; 
+/**
+ * The merchant the note names; see `merchants`.
+ */
+merchant_id: string | null; 
+/**
+ * The account it was paid from; see `accounts`.
+ */
+account_id: string | null; 
+/**
+ * The household it is shared with; see `households`.
+ */
+household_id: string | null }
+/**
+ * Pre-filled expense fields. Anything we couldn't recognise is left `None`.
+ */
+export type ExpenseDraft = { amount: number | null; category_id: string | null; note: string | null; 
+/**
+ * "YYYY-MM-DD", defaults to today.
+ */
+date: string }
+export type ExpenseHit = (({ id: string; user_id: string | null; amount: number; category_id: string | null; note: string | null; 
+/**
+ * "YYYY-MM-DD".
+ */
+date: string; created_at: string; updated_at: string; synced_at: string | null; deleted_at: string | null; reimbursement_status: string | null; reimbursement_owed_by: string | null; reimbursed_at: string | null; payment_method: string | null; created_by: string | null; 
+/**
+ * Set when the expense was paid in another currency.
+ */
+currency: string | null; 
+/**
+ * What was paid, in `currency`.
+ */
+original_amount: number | null; 
+/**
+ * The merchant the note names; see `merchants`.
+ */
+merchant_id: string | null; 
+/**
+ * The account it was paid from; see `accounts`.
+ */
+account_id: string | null; 
+/**
+ * The household it is shared with; see `households`.
+ */
+household_id: string | null }) & { category_name: string | null; category_icon: string | null; category_color: string | null }) & { 
+/**
+ * The matching part of the note, or the note's start for a category
+ * match.
+ */
+snippet: string | null; 
+/**
+ * Lower is better.
+ */
+rank: number }
+export type ExpenseSplit = { expense_id: string; amount: number; paid_by: string | null; shares: SplitShare[]; 
+/**
+ * What the other shares leave for the user.
+ */
+my_share: number }
+/**
+ * Fields to change; missing fields are left alone and `null` clears the
+ * optional ones.
+ */
+export type ExpenseUpdate = { amount: number | null; category_id?: string | null; note?: string | null; date: string | null; payment_method?: PaymentMethod | null; 
+/**
+ * Currency of the new `amount`, or of the current one if no amount is
+ * given; `null` means the base currency.
+ */
+currency?: string | null; account_id?: string | null }
+export type ExpenseWithCategory = ({ id: string; user_id: string | null; amount: number; category_id: string | null; note: string | null; 
+/**
+ * "YYYY-MM-DD".
+ */
+date: string; created_at: string; updated_at: string; synced_at: string | null; deleted_at: string | null; reimbursement_status: string | null; reimbursement_owed_by: string | null; reimbursed_at: string | null; payment_method: string | null; created_by: string | null; 
+/**
+ * Set when the expense was paid in another currency.
+ */
+currency: string | null; 
+/**
+ * What was paid, in `currency`.
+ */
+original_amount: number | null; 
+/**
+ * The merchant the note names; see `merchants`.
+ */
+merchant_id: string | null; 
+/**
+ * The account it was paid from; see `accounts`.
+ */
+account_id: string | null; 
+/**
+ * The household it is shared with; see `households`.
+ */
+household_id: string | null }) & { category_name: string | null; category_icon: string | null; category_color: string | null }
+export type FailureOutcome = { attempts: number; 
+/**
+ * When the item becomes due again; `None` once dead-lettered.
+ */
+next_attempt_at: string | null; dead_lettered: boolean }
+export type Feature = "bank_sync"
+export type FieldChange = { field: string; old: JsonValue; new: JsonValue }
+export type FieldDiff = { field: string; local: JsonValue; remote: JsonValue; differs: boolean }
+export type FundingForecast = { 
+/**
+ * Past months with enough data to count.
+ */
+months_considered: number; median_surplus: number; p25_surplus: number; p75_surplus: number; 
+/**
+ * Sum of all goals' planned monthly contributions.
+ */
+planned_contributions: number; goals: GoalFunding[] }
+export type GoalForecast = { goal_id: string; status: GoalStatus; total_saved: number; remaining: number; 
+/**
+ * Contributions left before the target date, this month included.
+ */
+months_left: number; 
+/**
+ * What each of those months needs for the goal to be reached on time.
+ */
+required_monthly: number; 
+/**
+ * Annual rate the forecast assumes, as a fraction.
+ */
+interest_rate: number; 
+/**
+ * The planned contribution first, then the smaller ones.
+ */
+scenarios: Scenario[] }
+export type GoalFunding = { goal_id: string; name: string; monthly_contribution: number; 
+/**
+ * The contribution is more than three months in four have left over.
+ */
+at_risk: boolean }
+export type GoalStatus = "reached" | "on_track" | "behind" | 
+/**
+ * The target date has passed without reaching the goal.
+ */
+"overdue" | 
+/**
+ * Nothing is being contributed, so it will never be reached.
+ */
+"stalled"
+/**
+ * Fields to change; missing fields are left alone.
+ */
+export type GoalUpdate = { name: string | null; target_amount: number | null; target_date: string | null; monthly_contribution: number | null; why_statement?: string | null; privacy_level: PrivacyLevel | null }
+export type HabitRun = { habit_goal_id: string; name: string; 
+/**
+ * Compliant months in a row.
+ */
+months: number; 
+/**
+ * "YYYY-MM", inclusive.
+ */
+from_month: string; to_month: string }
+export type HabitStreak = { habit_goal_id: string; current_months: number; longest_months: number; current_weeks: number; longest_weeks: number; freezes_available: number; 
+/**
+ * Months a freeze bridged, "YYYY-MM".
+ */
+frozen_months: string[] }
+export type Hotspot = { weekday: string; time_of_day: TimeOfDay; total: number; count: number; top_category_id: string | null }
+export type Household = { id: string; name: string; owner_id: string; 
+/**
+ * Code other people join with.
+ */
+invite_code: string; created_at: string; members: HouseholdMember[] }
+export type HouseholdBudget = { id: string; owner_id: string; owner_name: string | null; mine: boolean; month: string; total_amount: number; spending_limit: number | null }
+/**
+ * An expense in the combined ledger, the user's own or another member's.
+ */
+export type HouseholdExpense = { id: string; owner_id: string; owner_name: string | null; 
+/**
+ * Whether the user owns, and so may change, the expense.
+ */
+mine: boolean; amount: number; category_id: string | null; category_name: string | null; category_icon: string | null; category_color: string | null; note: string | null; 
+/**
+ * "YYYY-MM-DD".
+ */
+date: string }
+export type HouseholdMember = { household_id: string; user_id: string; display_name: string | null; 
+/**
+ * "owner" or "member".
+ */
+role: string; joined_at: string | null; 
+/**
+ * Whether this is the signed-in user.
+ */
+is_me: boolean }
+export type IcsImport = { created: RecurringExpense[]; skipped: SkippedEvent[] }
+export type ImportMode = 
+/**
+ * Wipe local data and take the archive's.
+ */
+"replace" | 
+/**
+ * Keep local data; add archived rows, and take archived versions that
+ * were updated more recently.
+ */
+"merge"
+export type ImportReport = { dry_run: boolean; rows: ImportRow[]; 
+/**
+ * Expenses created, or that would be on a dry run.
+ */
+imported: number; duplicates: number; 
+/**
+ * Credits and rows that couldn't be read.
+ */
+skipped: number }
+export type ImportRow = { 
+/**
+ * Line in the file, for pointing at it in a preview.
+ */
+line: number; date: string; 
+/**
+ * Positive, like expense amounts.
+ */
+amount: number; description: string | null; 
+/**
+ * The category the expense gets: the one chosen for the import or
+ * mapped from the source's own, or what a categorization rule gives it.
+ */
+category_id: string | null; 
+/**
+ * Already logged; not imported.
+ */
+duplicate: boolean }
+export type IncomeEntry = { id: string; user_id: string | null; source_id: string; amount: number; 
+/**
+ * "YYYY-MM-DD".
+ */
+date: string; note: string | null; created_at: string; updated_at: string; deleted_at: string | null; 
+/**
+ * The account it was paid into; see `accounts`.
+ */
+account_id: string | null }
+export type IncomeSource = { id: string; user_id: string | null; name: string; expected_amount: number; 
+/**
+ * Day of the period the money usually arrives, 1 being its first day.
+ */
+expected_day: number; created_at: string; updated_at: string; deleted_at: string | null }
+export type IncomeVariance = { source_id: string; name: string; expected: number; received: number; 
+/**
+ * `received - expected`; negative when short.
+ */
+difference: number; 
+/**
+ * When the money usually arrives in this period.
+ */
+expected_by: string; 
+/**
+ * Nothing logged yet and the usual day has passed.
+ */
+overdue: boolean }
+export type Institution = { id: string; name: string; bic?: string | null; logo?: string | null }
+export type JsonValue = null | boolean | number | string | JsonValue[] | Partial<{ [key in string]: JsonValue }>
+export type KeyRequest = { id: string; device_name: string; 
+/**
+ * Digest of the requesting device's key, shown on both devices.
+ */
+verification: string; created_at: string; approved: boolean }
+export type KeySource = 
+/**
+ * A random key only the keychain knows.
+ */
+"keychain" | 
+/**
+ * Derived from the user's passphrase, and cached in the keychain.
+ */
+"passphrase"
+export type LedgerExport = { path: string; transactions: number }
+export type LedgerFormat = "ledger" | "beancount"
+export type Manifest = { format: string; format_version: number; app_version: string; 
+/**
+ * Name of the newest migration applied to the archived database.
+ */
+schema_version: string | null; 
+/**
+ * Every migration applied to the archived database, in order.
+ */
+migrations: string[]; created_at: string; database_size: number; attachment_count: number }
+export type MarkPaid = { 
+/**
+ * "YYYY-MM" of the cycle paid; the open one if not given.
+ */
+month: string | null; 
+/**
+ * The bill's amount if not given.
+ */
+amount: number | null; 
+/**
+ * "YYYY-MM-DD"; today if not given.
+ */
+paid_on: string | null; 
+/**
+ * Also log the payment as an expense in the bill's category.
+ */
+record_expense?: boolean }
+export type Match = { line: StatementLine; expense_id: string; 
+/**
+ * Expense amount minus statement amount.
+ */
+amount_difference: number; 
+/**
+ * Days the statement line is dated after the expense.
+ */
+days_apart: number }
+export type MatchField = "note" | "merchant"
+export type MemberSpending = { owner_id: string; owner_name: string | null; mine: boolean; total: number; count: number }
+export type MemberTotal = { 
+/**
+ * User id; `None` for expenses logged before signing in.
+ */
+created_by: string | null; total: number; count: number }
+export type Merchant = { id: string; name: string; 
+/**
+ * Expenses linked to it, not counting deleted ones.
+ */
+expense_count: number }
+export type MerchantMonth = { 
+/**
+ * "YYYY-MM".
+ */
+month: string; total: number; count: number }
+export type MerchantStats = { merchant_id: string; merchant: string; total: number; count: number; average: number; last_date: string; 
+/**
+ * Category used most often for this merchant.
+ */
+category_id: string | null }
+export type MethodTotal = { 
+/**
+ * cash, card, bank or other; `None` for expenses logged without one.
+ */
+payment_method: string | null; total: number; count: number }
+export type MonthCashflow = { 
+/**
+ * "YYYY-MM".
+ */
+month: string; income: number; expenses: number; 
+/**
+ * `income - expenses`; negative when more went out than came in.
+ */
+net: number; 
+/**
+ * Share of the income left over; `None` without income.
+ */
+savings_rate: number | null }
+export type MonthCategoryTotal = { 
+/**
+ * "YYYY-MM".
+ */
+month: string; category_id: string | null; category_name: string | null; category_color: string | null; total: number; count: number }
+export type MonthClose = { 
+/**
+ * Period key: "YYYY-MM" for calendar months, the first day otherwise.
+ */
+month: string; budget_id: string | null; budget_limit: number | null; spent_amount: number; 
+/**
+ * Left over (negative if overspent) against the budget's limit.
+ */
+rollover_amount: number; 
+/**
+ * The following period's budget, created here or already present.
+ */
+next_budget_id: string | null; closed_at: string }
+export type MonthComparison = { month_a: string; month_b: string; 
+/**
+ * Day of the month both were cut off at; `None` when both are over.
+ */
+through_day: number | null; total_a: number; total_b: number; 
+/**
+ * Largest change first.
+ */
+categories: CategoryDelta[]; 
+/**
+ * Merchants in `b` that weren't in `a`.
+ */
+new_merchants: string[]; 
+/**
+ * Merchants in `a` that are gone in `b`.
+ */
+disappeared_merchants: string[]; budget: BudgetAdherence }
+export type MonthDelta = { month: string; total: number; previous_total: number; delta: number; 
+/**
+ * Change relative to the previous month; `None` when nothing was spent
+ * then.
+ */
+percent: number | null }
+export type NetWorthPoint = { date: string; assets: number; liabilities: number; net_worth: number }
+export type NetworkType = "wifi" | "ethernet" | "cellular" | 
+/**
+ * A VPN or something else that hides what is underneath.
+ */
+"other" | "unknown"
+export type NewAccount = { name: string; kind: AccountType; 
+/**
+ * Defaults to 0.
+ */
+opening_balance: number | null; 
+/**
+ * Defaults to today.
+ */
+opening_date: string | null }
+export type NewAccountBalance = { account_name: string; kind: AccountKind; balance: number; 
+/**
+ * Defaults to today.
+ */
+as_of: string | null }
+export type NewBill = { name: string; amount: number; due_day: number; autopay?: boolean; category_id: string | null; 
+/**
+ * Three days if not given; 0 reminds on the due date.
+ */
+remind_days_before: number | null }
+export type NewExpense = { amount: number; category_id: string | null; note: string | null; 
+/**
+ * "YYYY-MM-DD"; today if not given.
+ */
+date: string | null; payment_method: PaymentMethod | null; 
+/**
+ * Currency of `amount`; the base currency if not given.
+ */
+currency: string | null; account_id: string | null }
+export type NewGoal = { name: string; target_amount: number; 
+/**
+ * "YYYY-MM-DD".
+ */
+target_date: string; monthly_contribution: number; why_statement: string | null }
+export type NewIncomeEntry = { source_id: string; amount: number; date: string; note: string | null; account_id: string | null }
+export type NewIncomeSource = { name: string; expected_amount: number; expected_day: number }
+export type NewRecurringExpense = { name: string; amount: number; category_id: string | null; cadence: Cadence; next_due_date: string; 
+/**
+ * Key of the detected pattern this entry was created from, so the
+ * candidate isn't proposed again after a rename.
+ */
+match_key: string | null; remind_days_before?: number | null }
+export type NewRule = { pattern: string; match_field: MatchField; category_id: string; 
+/**
+ * Defaults to 0; higher wins.
+ */
+priority: number | null }
+export type NewSettlement = { from_person: string | null; to_person: string | null; amount: number; 
+/**
+ * Defaults to today.
+ */
+date: string | null; note: string | null }
+export type NewSplit = { 
+/**
+ * Who paid the expense; the user if not given.
+ */
+paid_by: string | null; method: SplitMethod; 
+/**
+ * The people other than the user.
+ */
+participants: Participant[] }
+export type NewTransfer = { from_account_id: string; to_account_id: string; amount: number; 
+/**
+ * Defaults to today.
+ */
+date: string | null; note: string | null }
+export type NewTrial = { name: string; 
+/**
+ * "YYYY-MM-DD", the first day the user gets charged.
+ */
+trial_ends_on: string; amount_after_trial: number | null; expense_id: string | null }
+export type NoteHit = { id: string; content: string; created_at: string; snippet: string | null; rank: number }
+export type NotificationPreferences = { id: number; user_id: string | null; notifications_enabled: boolean; monthly_checkin_enabled: boolean; monthly_checkin_cron: string; progress_updates_enabled: boolean; progress_updates_cron: string; why_reminders_enabled: boolean; why_reminders_cron: string; quiet_hours_enabled: boolean; quiet_hours_start: string; quiet_hours_end: string; timezone: string; created_at: string; updated_at: string }
+/**
+ * Fields to change; `None` leaves a field as it is.
+ */
+export type NotificationPreferencesUpdate = { notifications_enabled: boolean | null; monthly_checkin_enabled: boolean | null; monthly_checkin_cron: string | null; progress_updates_enabled: boolean | null; progress_updates_cron: string | null; why_reminders_enabled: boolean | null; why_reminders_cron: string | null; quiet_hours_enabled: boolean | null; quiet_hours_start: string | null; quiet_hours_end: string | null; timezone: string | null }
+export type OrphanReport = { 
+/**
+ * References set to NULL.
+ */
+cleared: number; 
+/**
+ * Rows moved to `orphaned_rows`.
+ */
+quarantined: number; 
+/**
+ * Rows soft-deleted along with their parent.
+ */
+soft_deleted: number }
+/**
+ * Pending reimbursements, in total and per person.
+ */
+export type Outstanding = { total: number; count: number; by_person: OwedBy[] }
+export type OwedBy = { 
+/**
+ * `None` when the user didn't say who owes the money.
+ */
+owed_by: string | null; total: number; count: number; 
+/**
+ * Date of the oldest unpaid expense, to spot debts going stale.
+ */
+oldest_date: string }
+export type PairingOffer = { 
+/**
+ * What the QR code encodes.
+ */
+payload: string; 
+/**
+ * The QR code as an SVG document.
+ */
+qr_svg: string; expires_at: string }
+export type Participant = { name: string; 
+/**
+ * Amount or percentage, per the method; ignored for equal splits.
+ */
+value: number | null }
+export type PayFrequency = "monthly" | 
+/**
+ * Paid on the 1st and the 16th.
+ */
+"semimonthly" | "biweekly" | "weekly"
+export type PaySchedule = { frequency: PayFrequency; 
+/**
+ * A known payday. Monthly schedules use its day of the month (none
+ * means the 1st), weekly and biweekly ones count from it.
+ */
+anchor_date: string | null }
+export type PaymentMethod = "cash" | "card" | "bank" | "other"
+export type Peer = { id: string; name: string; 
+/**
+ * Where the device was last seen.
+ */
+address: string | null; paired_at: string; last_synced_at: string | null }
+export type PeerStatus = { 
+/**
+ * Whether this device is reachable by paired devices.
+ */
+running: boolean; device_id: string; device_name: string | null; 
+/**
+ * Where this device is served, while running.
+ */
+address: string | null }
+export type PeerSyncReport = { 
+/**
+ * Rows sent to the peer.
+ */
+sent: number; 
+/**
+ * Rows received from the peer that were newer than ours.
+ */
+applied: number }
+export type PendingCheckin = { kind: CheckinKind; 
+/**
+ * The savings goal or habit goal.
+ */
+goal_id: string; name: string; 
+/**
+ * "YYYY-MM".
+ */
+month: string; 
+/**
+ * For a savings goal, the contribution that keeps it on plan; for a
+ * habit, what its category has used so far.
+ */
+suggested_amount: number; 
+/**
+ * The planned monthly contribution, or the habit's limit.
+ */
+target_amount: number; 
+/**
+ * The amounts are numbers of purchases (`max_count` habits).
+ */
+is_count: boolean }
+export type Period = { key: string; start: string; 
+/**
+ * Last day, inclusive.
+ */
+end: string }
+export type PersonBalance = { person: string | null; 
+/**
+ * Positive when owed money, negative when owing.
+ */
+net: number }
+export type PriceIncrease = { match_key: string; name: string; category_id: string | null; cadence: Cadence; previous_amount: number; new_amount: number; delta: number; 
+/**
+ * Increase as a fraction of the previous amount.
+ */
+percent: number; 
+/**
+ * Extra cost over a year at the new price.
+ */
+annual_impact: number; 
+/**
+ * Date of the more expensive charge.
+ */
+charged_on: string; expense_id: string }
+export type Privacy = 
+/**
+ * Amounts, categories and goal names.
+ */
+"full" | 
+/**
+ * Percentages and shares only, no amounts.
+ */
+"relative" | 
+/**
+ * Like `Relative`, with goal names replaced by "Goal 1", "Goal 2", ...
+ */
+"anonymous"
+export type PrivacyLevel = "private" | "progress_only" | "full"
+/**
+ * What became of a pulled row.
+ */
+export type PullOutcome = 
+/**
+ * No local edits in the way; merge it as usual.
+ */
+"merge" | 
+/**
+ * Parked for the user to resolve.
+ */
+"held" | 
+/**
+ * The local edits win and will overwrite it on the next push.
+ */
+"kept_local" | 
+/**
+ * Written over the local row; the local edits were dropped.
+ */
+"took_remote" | 
+/**
+ * Combined with the local edits, which are queued again.
+ */
+"merged"
+export type PurgeReport = { 
+/**
+ * Rows deleted for good, parents and children alike.
+ */
+purged: number; 
+/**
+ * Past retention, but kept as sync tombstones for now.
+ */
+kept: number }
+export type PushReport = { pushed: number; failed: number; 
+/**
+ * Items held back until a row they reference reaches the server.
+ */
+deferred: number; errors: string[] }
+export type QueueFilter = { table_name: string | null; status: QueueItemStatus | null; limit: number | null }
+export type QueueItem = { id: string; table_name: string; record_id: string; operation: string; payload: JsonValue; created_at: string; attempts: number; last_attempt_at: string | null; next_attempt_at: string | null; error_message: string | null; status: QueueItemStatus }
+export type QueueItemStatus = 
+/**
+ * Not tried yet.
+ */
+"pending" | 
+/**
+ * Failed before, will be tried again after `next_attempt_at`.
+ */
+"retrying" | 
+/**
+ * Dead-lettered: out of attempts, only a retry or discard moves it.
+ */
+"failed" | 
+/**
+ * Waiting for a sync conflict on the record to be resolved.
+ */
+"held"
+/**
+ * Proof of a purchase as handed over by the store SDK or checkout.
+ */
+export type Receipt = { source: Source; 
+/**
+ * App Store receipt or transaction JWS, Play purchase token, or
+ * Stripe checkout session id.
+ */
+token: string; product_id: string | null }
+export type ReceiptConfidence = { amount: number; date: number; merchant: number }
+export type ReceiptDraft = { draft: ExpenseDraft; merchant: string | null; confidence: ReceiptConfidence; 
+/**
+ * Raw OCR text, handy when the user wants to correct a field.
+ */
+text: string }
+export type ReconciliationReport = { import_id: string; start_date: string; end_date: string; 
+/**
+ * Pairs found by this run.
+ */
+new_matches: Match[]; 
+/**
+ * Lines matched before, by this or an earlier run.
+ */
+matched_count: number; 
+/**
+ * On the statement but not logged as an expense.
+ */
+unmatched_lines: StatementLine[]; 
+/**
+ * Logged but not on the statement.
+ */
+unmatched_expenses: UnmatchedExpense[]; statement_total: number; expense_total: number }
+export type RecoveryKit = { 
+/**
+ * Shown once; unwraps the key on a new device.
+ */
+recovery_code: string; fingerprint: string }
+export type RecurringCandidate = { 
+/**
+ * Normalized note shared by all occurrences.
+ */
+match_key: string; 
+/**
+ * Most recent note as the user wrote it, used as the suggested name.
+ */
+name: string; category_id: string | null; 
+/**
+ * Median charge.
+ */
+amount: number; cadence: Cadence; occurrences: number; last_date: string; next_date: string; 
+/**
+ * `0.0..=1.0`, how cleanly the history fits the pattern.
+ */
+confidence: number; expense_ids: string[] }
+export type RecurringExpense = { id: string; user_id: string | null; name: string; amount: number; category_id: string | null; cadence: string; next_due_date: string; match_key: string | null; 
+/**
+ * Days before the due date to remind; `None` for no reminder.
+ */
+remind_days_before: number | null; created_at: string; updated_at: string; deleted_at: string | null }
+export type Referral = { code: string; 
+/**
+ * "pending", "redeemed", "rejected" or "ignored".
+ */
+status: string; received_at: string; redeemed_at: string | null; error: string | null }
+export type ReimbursementStatus = "pending" | "received" | "written_off"
+export type Resolution = "keep_local" | "keep_remote"
+export type ResyncReport = { 
+/**
+ * False for a dry run.
+ */
+applied: boolean; 
+/**
+ * Compared tables, in push order.
+ */
+tables: TableDiff[]; 
+/**
+ * What the repair after applying found.
+ */
+orphans: OrphanReport | null }
+export type SavingsGoal = { id: string; user_id: string | null; name: string; target_amount: number; 
+/**
+ * "YYYY-MM-DD".
+ */
+target_date: string; monthly_contribution: number; why_statement: string | null; privacy_level: string | null; created_at: string; updated_at: string; deleted_at: string | null }
+export type ScannedCode = { kind: ScannedKind; payload: string }
+export type ScannedKind = 
+/**
+ * For `receive`.
+ */
+"transfer" | 
+/**
+ * For `peer::pair`.
+ */
+"pairing"
+export type Scenario = { monthly_contribution: number; 
+/**
+ * First of the month the goal is reached; `None` if never.
+ */
+projected_completion: string | null; 
+/**
+ * Balance on the target date.
+ */
+saved_by_target: number; 
+/**
+ * What would still be missing then.
+ */
+shortfall: number; 
+/**
+ * Interest earned by the target date.
+ */
+interest: number }
+export type SearchResults = { 
+/**
+ * The words searched for, for highlighting.
+ */
+terms: string[]; expenses: ExpenseHit[]; feedback_notes: NoteHit[] }
+export type SettleUp = { 
+/**
+ * Everyone with something outstanding, most owed first.
+ */
+balances: PersonBalance[]; 
+/**
+ * The payments that would settle everything.
+ */
+debts: Debt[] }
+export type Settlement = { id: string; from_person: string | null; to_person: string | null; amount: number; 
+/**
+ * "YYYY-MM-DD".
+ */
+date: string; note: string | null; created_at: string }
+export type SkippedEvent = { name: string; reason: string }
+/**
+ * Paths of the files written by `export_snapshot`.
+ */
+export type SnapshotFiles = { html: string; json: string }
+export type Source = "app_store" | "play_store" | "stripe"
+export type SpendingSummary = { 
+/**
+ * Everything spent in the range.
+ */
+gross: number; 
+/**
+ * Part of `gross` that was reimbursed.
+ */
+reimbursed: number; net: number; count: number; 
+/**
+ * Net spend per payment method, largest first.
+ */
+by_payment_method: MethodTotal[]; 
+/**
+ * Net spend per person who entered the expenses, largest first.
+ */
+by_member: MemberTotal[] }
+export type SplitMethod = 
+/**
+ * Everyone, the user included, pays the same.
+ */
+"equal" | 
+/**
+ * Each participant's value is what they owe.
+ */
+"amounts" | 
+/**
+ * Each participant's value is the percentage of the expense they owe.
+ */
+"percentages"
+export type SplitShare = { participant: string; share: number }
+export type StagedArchive = { 
+/**
+ * Pass back to `apply`.
+ */
+id: string; 
+/**
+ * Staged database, for the frontend to migrate.
+ */
+database_path: string; manifest: Manifest; 
+/**
+ * Migrations applied locally but not in the archive, in order.
+ */
+pending_migrations: string[] }
+export type StatementFormat = "ofx" | "qif"
+export type StatementImport = { id: string; name: string; start_date: string | null; end_date: string | null; line_count: number; 
+/**
+ * Rows that weren't debits or couldn't be read.
+ */
+skipped: number }
+export type StatementLine = { id: string; date: string; amount: number; description: string }
+/**
+ * Settings for formats that say which column is which themselves.
+ */
+export type StatementOptions = { 
+/**
+ * Category for every imported expense.
+ */
+category_id: string | null; 
+/**
+ * Currency of the amounts; for OFX the statement's own if not given,
+ * otherwise the base currency.
+ */
+currency: string | null; 
+/**
+ * A chrono format such as "%d/%m/%Y" for QIF dates, which banks write
+ * any which way; common formats are tried if not given.
+ */
+date_format: string | null }
+export type StatementRange = { start_date: string; end_date: string }
+export type Status = 
+/**
+ * No valid token: the free plan.
+ */
+"none" | "active" | 
+/**
+ * Past the paid period but within the offline grace.
+ */
+"grace" | "expired"
+/**
+ * How conflicts in a table are settled.
+ */
+export type Strategy = 
+/**
+ * Park the remote version until the user picks one.
+ */
+"manual" | 
+/**
+ * Keep whichever side was updated last.
+ */
+"last_write_wins" | 
+/**
+ * Keep the fields edited here and take every other field from the
+ * remote version. Only tables with an audit trail know which fields
+ * were edited; the others fall back to last write wins.
+ */
+"field_merge"
+export type SyncEncryptionStatus = { enabled: boolean; 
+/**
+ * Whether this device holds the key and can read pulled rows.
+ */
+has_key: boolean; 
+/**
+ * Short digest of the key, the same on every device.
+ */
+fingerprint: string | null; 
+/**
+ * A key request from this device is waiting for approval.
+ */
+awaiting_key: boolean }
+export type SyncHealth = { state: SyncState; 
+/**
+ * The error of the last run, if it failed.
+ */
+last_error: string | null; 
+/**
+ * When the last pull finished, as recorded by the frontend.
+ */
+last_synced_at: string | null; 
+/**
+ * Queued changes that will still be tried.
+ */
+pending: number; 
+/**
+ * Dead-lettered changes, waiting for a retry or discard.
+ */
+failed: number; 
+/**
+ * Changes waiting for a sync conflict to be resolved.
+ */
+held: number; 
+/**
+ * Whether remote changes stream in as they happen.
+ */
+realtime: boolean; tables: TableSyncTimes[] }
+export type SyncPolicy = { wifi_only: boolean; network_type: NetworkType; metered: boolean; 
+/**
+ * Whether records sync on the current connection.
+ */
+allowed: boolean }
+export type SyncReport = { uploaded: number; downloaded: number; deleted: number; failed: number; 
+/**
+ * Large uploads held back on a metered connection.
+ */
+deferred: number }
+export type SyncRun = { id: string; 
+/**
+ * `push` or `pull`.
+ */
+direction: string; started_at: string; finished_at: string; pushed: number; pulled: number; failed: number; deferred: number; error: string | null }
+export type SyncState = "idle" | 
+/**
+ * A push is running.
+ */
+"syncing" | 
+/**
+ * The last run failed.
+ */
+"error"
+export type TableCount = { table: string; rows: number }
+export type TableDiff = { table_name: string; 
+/**
+ * Rows on the server that aren't here.
+ */
+missing_locally: number; 
+/**
+ * Rows here the server never got.
+ */
+missing_remotely: number; 
+/**
+ * Rows whose server version is newer or differs.
+ */
+outdated_locally: number; 
+/**
+ * Rows edited here after their server version.
+ */
+outdated_remotely: number; 
+/**
+ * Rows left to sync because of queued changes or a conflict.
+ */
+skipped: number }
+export type TableImport = { table: string; 
+/**
+ * Rows inserted or updated.
+ */
+rows: number }
+export type TableStrategy = { table_name: string; strategy: Strategy }
+export type TableSyncSetting = { table_name: string; enabled: boolean }
+export type TableSyncTimes = { table_name: string; 
+/**
+ * When a row of the table last reached the server.
+ */
+last_pushed_at: string | null; 
+/**
+ * When a row of the table was last merged from the server.
+ */
+last_pulled_at: string | null; 
+/**
+ * Queued changes not pushed yet.
+ */
+pending: number }
+export type Tag = { id: string; name: string; color: string | null; 
+/**
+ * Expenses carrying it, not counting deleted ones.
+ */
+expense_count: number; created_at: string; updated_at: string }
+export type TagFilter = { tag_ids: string[]; 
+/**
+ * Only expenses with every tag, rather than any of them.
+ */
+match_all?: boolean }
+export type TagTotal = { tag_id: string; name: string; color: string | null; total: number; count: number }
+export type TaggedCategoryTotal = { category_id: string | null; category_name: string | null; total: number; count: number }
+export type TimeOfDay = 
+/**
+ * 05:00 to 11:59.
+ */
+"morning" | 
+/**
+ * 12:00 to 16:59.
+ */
+"afternoon" | 
+/**
+ * 17:00 to 21:59.
+ */
+"evening" | 
+/**
+ * 22:00 to 04:59.
+ */
+"night"
+export type TimeOfDayTotal = { time_of_day: TimeOfDay; total: number; count: number }
+export type TimelineEntry = ({ kind: "created"; values: Partial<{ [key in string]: JsonValue }> } | { kind: "updated"; changes: FieldChange[] } | 
+/**
+ * Removed from the table outright (soft deletes show up as an update
+ * of `deleted_at`).
+ */
+{ kind: "deleted"; values: Partial<{ [key in string]: JsonValue }> } | { kind: "pushed"; operation: string | null } | { kind: "pulled" } | 
+/**
+ * A queued change that was dropped without reaching the server.
+ */
+{ kind: "discarded"; operation: string | null } | { kind: "conflict_detected"; remote_updated_at: string } | { kind: "conflict_resolved"; kept: string }) & { at: string }
+export type Transfer = { id: string; user_id: string | null; from_account_id: string; to_account_id: string; amount: number; 
+/**
+ * "YYYY-MM-DD".
+ */
+date: string; note: string | null; created_at: string; updated_at: string; deleted_at: string | null }
+export type TransferOffer = { 
+/**
+ * What the QR code encodes.
+ */
+payload: string; 
+/**
+ * The QR code as an SVG document.
+ */
+qr_svg: string; expires_at: string; manifest: Manifest }
+export type TrashKind = "expense" | "budget" | "savings_goal" | "habit_goal" | "category" | "feedback_note" | "recurring_expense"
+export type TrashedItem = { kind: TrashKind; id: string; label: string; detail: string | null; deleted_at: string; 
+/**
+ * When it leaves the trash for good.
+ */
+purge_after: string }
+export type Trial = { id: string; user_id: string | null; name: string; 
+/**
+ * The €0 signup expense, if the trial was logged as one.
+ */
+expense_id: string | null; amount_after_trial: number | null; trial_ends_on: string; reminder_id: string | null; cancelled_at: string | null; created_at: string; updated_at: string; deleted_at: string | null }
+export type UndoResult = { expense_ids: string[]; 
+/**
+ * Changed again since, and left as they are.
+ */
+skipped: number }
+export type UnmatchedExpense = { id: string; date: string; amount: number; note: string | null }
+export type VoiceExpenseDraft = { transcript: string; draft: ExpenseDraft }
+export type Webhook = { id: string; url: string; 
+/**
+ * Key of the `X-Goaldy-Signature` HMAC, for the receiver to check.
+ */
+secret: string; events: WebhookEvent[]; enabled: boolean; created_at: string; updated_at: string }
+export type WebhookDelivery = { id: string; webhook_id: string; event: string; attempts: number; next_attempt_at: string; delivered_at: string | null; 
+/**
+ * Set once it was given up on.
+ */
+failed_at: string | null; 
+/**
+ * HTTP status of the last attempt, if the server answered.
+ */
+last_status: number | null; last_error: string | null; created_at: string }
+export type WebhookEvent = 
+/**
+ * Spending went over the period's budget or a category's allocation.
+ */
+"budget.exceeded" | 
+/**
+ * A savings goal passed 25, 50, 75 or 100 percent of its target.
+ */
+"goal.milestone_reached" | 
+/**
+ * Last month's contributions are waiting to be checked in.
+ */
+"checkin.due" | 
+/**
+ * Sent only by "Send test".
+ */
+"webhook.test"
+export type WebhookInput = { url: string; events: WebhookEvent[]; enabled: boolean }
+export type WeekdayPatterns = { 
+/**
+ * Monday first.
+ */
+by_weekday: WeekdayTotal[]; 
+/**
+ * Share of spending on Saturdays and Sundays, 0 to 1.
+ */
+weekend_share: number; 
+/**
+ * Only expenses entered on the day they're dated.
+ */
+by_time_of_day: TimeOfDayTotal[]; 
+/**
+ * How many expenses had a usable time.
+ */
+timed_count: number; 
+/**
+ * Biggest weekday/time-of-day combinations, largest first.
+ */
+hotspots: Hotspot[] }
+export type WeekdayTotal = { 
+/**
+ * "monday" through "sunday".
+ */
+weekday: string; total: number; count: number; 
+/**
+ * Spend per occurrence of this weekday in the range.
+ */
+average: number; top_category_id: string | null }
+export type YearCategory = { category_id: string | null; category_name: string | null; category_color: string | null; total: number; count: number; 
+/**
+ * Share of the year's spending, 0 to 1.
+ */
+share: number }
+export type YearReview = { year: number; total_spent: number; total_income: number; 
+/**
+ * Contributed to savings goals.
+ */
+total_saved: number; expense_count: number; 
+/**
+ * Largest first.
+ */
+top_categories: YearCategory[]; 
+/**
+ * The finished month with the least spending.
+ */
+best_month: MonthCashflow | null; 
+/**
+ * The finished month with the most spending.
+ */
+worst_month: MonthCashflow | null; 
+/**
+ * Every month of the year so far.
+ */
+months: MonthCashflow[]; goals_completed: CompletedGoal[]; longest_habit_streak: HabitRun | null }
+
+/** tauri-specta globals **/
+
+import {
+	invoke as TAURI_INVOKE,
+	Channel as TAURI_CHANNEL,
+} from "@tauri-apps/api/core";
+import * as TAURI_API_EVENT from "@tauri-apps/api/event";
+import { type WebviewWindow as __WebviewWindow__ } from "@tauri-apps/api/webviewWindow";
+
+type __EventObj__<T> = {
+	listen: (
+		cb: TAURI_API_EVENT.EventCallback<T>,
+	) => ReturnType<typeof TAURI_API_EVENT.listen<T>>;
+	once: (
+		cb: TAURI_API_EVENT.EventCallback<T>,
+	) => ReturnType<typeof TAURI_API_EVENT.once<T>>;
+	emit: null extends T
+		? (payload?: T) => ReturnType<typeof TAURI_API_EVENT.emit>
+		: (payload: T) => ReturnType<typeof TAURI_API_EVENT.emit>;
+};
+
+export type Result<T, E> =
+	| { status: "ok"; data: T }
+	| { status: "error"; error: E };
+
+function __makeEvents__<T extends Record<string, any>>(
+	mappings: Record<keyof T, string>,
+) {
+	return new Proxy(
+		{} as unknown as {
+			[K in keyof T]: __EventObj__<T[K]> & {
+				(handle: __WebviewWindow__): __EventObj__<T[K]>;
+			};
+		},
+		{
+			get: (_, event) => {
+				const name = mappings[event as keyof T];
+
+				return new Proxy((() => {}) as any, {
+					apply: (_, __, [window]: [__WebviewWindow__]) => ({
+						listen: (arg: any) => window.listen(name, arg),
+						once: (arg: any) => window.once(name, arg),
+						emit: (arg: any) => window.emit(name, arg),
+					}),
+					get: (_, command: keyof __EventObj__<any>) => {
+						switch (command) {
+							case "listen":
+								return (arg: any) => TAURI_API_EVENT.listen(name, arg);
+							case "once":
+								return (arg: any) => TAURI_API_EVENT.once(name, arg);
+							case "emit":
+								return (arg: any) => TAURI_API_EVENT.emit(name, arg);
+						}
+					},
+				});
+			},
+		},
+	);
+}
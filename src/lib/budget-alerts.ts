@@ -0,0 +1,22 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * Percentages of the period's budget, and of each category allocation,
+ * at which the backend warns after an expense is written.
+ */
+
+export async function getBudgetAlertThresholds(): Promise<number[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getBudgetAlertThresholds());
+}
+
+// An empty list turns the alerts off
+export async function setBudgetAlertThresholds(thresholds: number[]): Promise<number[]> {
+  if (!isTauri()) {
+    throw new Error('Budget alerts are only available in the desktop and mobile apps');
+  }
+  const { commands } = await import('./bindings');
+  return unwrap(commands.setBudgetAlertThresholds(thresholds));
+}
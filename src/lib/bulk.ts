@@ -0,0 +1,50 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * Changes to a selection of expenses at once. Each is all or nothing and
+ * returns a token that undoes it for a day; expenses edited again in the
+ * meantime are left alone by the undo.
+ */
+
+export type BulkOperation =
+  // null leaves the expenses uncategorized
+  | { kind: 'recategorize'; category_id: string | null }
+  | { kind: 'delete' }
+  // Negative moves them earlier
+  | { kind: 'shift_date'; days: number }
+  | { kind: 'tag'; tag_id: string; remove?: boolean };
+
+export interface BulkResult {
+  // Only the expenses that actually changed
+  expense_ids: string[];
+  // null when nothing changed
+  undo_token: string | null;
+}
+
+export interface UndoResult {
+  expense_ids: string[];
+  // Changed again since, and left as they are
+  skipped: number;
+}
+
+function assertTauri(): void {
+  if (!isTauri()) {
+    throw new Error('Bulk changes are only available in the desktop and mobile apps');
+  }
+}
+
+export async function bulkUpdateExpenses(
+  expenseIds: string[],
+  operation: BulkOperation
+): Promise<BulkResult> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.bulkUpdateExpenses(expenseIds, operation));
+}
+
+export async function undoBulkUpdate(undoToken: string): Promise<UndoResult> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.undoBulkUpdate(undoToken));
+}
@@ -0,0 +1,16 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * Write the upcoming check-ins, bill due dates and goal target dates to
+ * goaldy-calendar.ics in the downloads folder, for importing into a
+ * calendar app. Importing a newer file updates the events in place.
+ * Returns the file's path.
+ */
+export async function exportCalendar(): Promise<string> {
+  if (!isTauri()) {
+    throw new Error('Calendar export is only available in the desktop and mobile apps');
+  }
+  const { commands } = await import('./bindings');
+  return unwrap(commands.exportIcal());
+}
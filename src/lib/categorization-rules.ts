@@ -0,0 +1,58 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * Rules that categorize new and imported expenses which arrive without a
+ * category, matched on the note or on the merchant it normalizes to.
+ */
+
+export type RuleMatchField = 'note' | 'merchant';
+
+export interface CategorizationRule {
+  id: string;
+  pattern: string;
+  match_field: RuleMatchField;
+  category_id: string;
+  priority: number;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface NewCategorizationRule {
+  pattern: string;
+  match_field: RuleMatchField;
+  category_id: string;
+  priority?: number | null;
+}
+
+const DESKTOP_ONLY = 'Categorization rules are only available in the desktop and mobile apps';
+
+export async function getCategorizationRules(): Promise<CategorizationRule[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getCategorizationRules());
+}
+
+// Saving a pattern that already has a rule retargets that rule
+export async function saveCategorizationRule(rule: NewCategorizationRule): Promise<CategorizationRule> {
+  if (!isTauri()) throw new Error(DESKTOP_ONLY);
+  const { commands } = await import('./bindings');
+  return unwrap(commands.saveCategorizationRule({ priority: null, ...rule }));
+}
+
+export async function deleteCategorizationRule(id: string): Promise<void> {
+  if (!isTauri()) throw new Error(DESKTOP_ONLY);
+  const { commands } = await import('./bindings');
+  await unwrap(commands.deleteCategorizationRule(id));
+}
+
+// "Always categorize this as ...", moving the expense too if the category differs
+export async function createRuleFromExpense(
+  expenseId: string,
+  matchField: RuleMatchField,
+  categoryId?: string,
+): Promise<CategorizationRule> {
+  if (!isTauri()) throw new Error(DESKTOP_ONLY);
+  const { commands } = await import('./bindings');
+  return unwrap(commands.createRuleFromExpense(expenseId, matchField, categoryId ?? null));
+}
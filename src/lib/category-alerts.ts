@@ -0,0 +1,46 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * Per-category spending limits with their own warning thresholds,
+ * e.g. warn at 60% for dining but never for utilities.
+ */
+
+export interface CategoryAlertRule {
+  category_id: string;
+  // Limit per budget period
+  limit_amount: number;
+  // Percentages of the limit to warn at; empty never warns
+  thresholds: number[];
+}
+
+export async function getCategoryAlertRules(): Promise<CategoryAlertRule[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getCategoryAlertRules());
+}
+
+export async function saveCategoryAlertRule(rule: CategoryAlertRule): Promise<CategoryAlertRule> {
+  if (!isTauri()) {
+    throw new Error('Category alerts are only available in the desktop and mobile apps');
+  }
+  const { commands } = await import('./bindings');
+  return unwrap(commands.saveCategoryAlertRule(rule));
+}
+
+export async function deleteCategoryAlertRule(categoryId: string): Promise<void> {
+  if (!isTauri()) return;
+  const { commands } = await import('./bindings');
+  await unwrap(commands.deleteCategoryAlertRule(categoryId));
+}
+
+/**
+ * Ask the backend to check a category after one of its expenses changed.
+ * Failures are only logged so they never block saving the expense.
+ */
+export function checkCategoryAlerts(categoryId: string | null | undefined): void {
+  if (!categoryId || !isTauri()) return;
+  import('./bindings')
+    .then(({ commands }) => unwrap(commands.checkCategoryAlerts(categoryId)))
+    .catch((error) => console.error('[CategoryAlerts] Check failed:', error));
+}
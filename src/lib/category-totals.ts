@@ -0,0 +1,43 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * Cached spending per calendar month and category, maintained by the
+ * backend. Only available in the desktop and mobile apps.
+ */
+
+export interface CategoryTotal {
+  category_id: string | null;
+  category_name: string | null;
+  // Net of expenses that were paid back
+  total: number;
+  gross: number;
+  expense_count: number;
+}
+
+interface ExpenseCell {
+  date: string;
+  category_id: string | null;
+}
+
+/**
+ * Tell the backend which expenses changed, as they were before and after
+ * the write, so it can recompute the affected totals. Failures are only
+ * logged; the nightly rebuild catches anything missed.
+ */
+export function refreshCategoryTotals(...rows: (ExpenseCell | null | undefined)[]): void {
+  if (!isTauri()) return;
+  const cells = rows
+    .filter((row): row is ExpenseCell => !!row)
+    .map(({ date, category_id }) => ({ date, category_id }));
+  if (cells.length === 0) return;
+  import('./bindings')
+    .then(({ commands }) => unwrap(commands.updateCategoryTotals(cells)))
+    .catch((error) => console.error('[CategoryTotals] Refresh failed:', error));
+}
+
+export async function getMonthlyCategoryTotals(month: string): Promise<CategoryTotal[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getMonthlyCategoryTotals(month));
+}
@@ -0,0 +1,11 @@
+import type { Result } from './bindings';
+
+/**
+ * The generated commands resolve to a Result instead of rejecting. Unwrap
+ * one so that a failed command throws, like a plain invoke would.
+ */
+export async function unwrap<T>(result: Promise<Result<T, string>>): Promise<T> {
+  const settled = await result;
+  if (settled.status === 'error') throw new Error(settled.error);
+  return settled.data;
+}
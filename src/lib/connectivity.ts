@@ -0,0 +1,93 @@
+import type { commands as Commands } from './bindings';
+import { isTauri } from './platform';
+
+/**
+ * Online/offline state shared by the sync engine and the UI.
+ *
+ * In Tauri the backend probes the network and emits 'connectivity://changed';
+ * in the browser we fall back to navigator.onLine and its window events.
+ */
+
+// Network Information API, missing from the DOM typings
+interface NetworkInformation extends EventTarget {
+  type?: string;
+  saveData?: boolean;
+}
+
+export type NetworkType = 'wifi' | 'ethernet' | 'cellular' | 'other' | 'unknown';
+
+function networkType(type: string | undefined): NetworkType {
+  switch (type) {
+    case 'wifi':
+    case 'ethernet':
+    case 'cellular':
+      return type;
+    case 'bluetooth':
+    case 'wimax':
+    case 'other':
+      return 'other';
+    default:
+      return 'unknown';
+  }
+}
+
+let lastKnown: boolean | null = null;
+
+/**
+ * Whether the network is reachable, as last reported.
+ */
+export function isConnected(): boolean {
+  return lastKnown ?? navigator.onLine;
+}
+
+/**
+ * Call `onChange` with the current state and on every change.
+ * Returns a function that stops watching.
+ */
+export async function watchConnectivity(onChange: (online: boolean) => void): Promise<() => void> {
+  if (!isTauri()) {
+    const handleOnline = () => onChange(true);
+    const handleOffline = () => onChange(false);
+    window.addEventListener('online', handleOnline);
+    window.addEventListener('offline', handleOffline);
+    onChange(navigator.onLine);
+    return () => {
+      window.removeEventListener('online', handleOnline);
+      window.removeEventListener('offline', handleOffline);
+    };
+  }
+
+  const { commands } = await import('./bindings');
+  const { listen } = await import('@tauri-apps/api/event');
+  const unlisten = await listen<{ online: boolean }>('connectivity://changed', (event) => {
+    lastKnown = event.payload.online;
+    onChange(lastKnown);
+  });
+  const stopReportingMetered = reportMetered(commands);
+  lastKnown = await commands.isOnline();
+  onChange(lastKnown);
+  return () => {
+    unlisten();
+    stopReportingMetered();
+  };
+}
+
+/**
+ * Tell the backend whether the connection is metered and what type it is,
+ * so attachment transfers are throttled on cellular data or with data
+ * saver on, and sync can keep to Wi-Fi when the user asks for that.
+ */
+function reportMetered(commands: typeof Commands): () => void {
+  const connection = (navigator as Navigator & { connection?: NetworkInformation }).connection;
+  if (!connection) return () => {};
+
+  const report = () => {
+    const metered = connection.saveData === true || connection.type === 'cellular';
+    commands.setMeteredConnection(metered, networkType(connection.type)).catch((error) =>
+      console.error('[Connectivity] Failed to report metered connection:', error)
+    );
+  };
+  report();
+  connection.addEventListener('change', report);
+  return () => connection.removeEventListener('change', report);
+}
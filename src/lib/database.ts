@@ -1,6 +1,11 @@
 import { getBrowserDatabase } from "./browser-database";
+import { checkCategoryAlerts } from "./category-alerts";
+import { getMonthlyCategoryTotals, refreshCategoryTotals } from "./category-totals";
+import { unwrap } from "./commands";
+import { publishDomainEvent } from "./events";
+import { getCurrentPeriod } from "./periods";
 import { isTauri } from "./platform";
-import type { Budget, Category, Expense, ExpenseWithCategory, FeedbackNote, HabitGoal, HabitGoalWithStats, HabitTracking, SavingsContribution, SavingsGoal, SavingsGoalWithStats } from "./types";
+import type { Budget, Category, Expense, ExpenseWithCategory, FeedbackNote, HabitGoal, HabitGoalWithStats, HabitStreak, HabitTracking, PendingCheckin, SavingsContribution, SavingsGoal, SavingsGoalWithStats } from "./types";
 import { generateId, getCurrentMonth } from "./types";
 
 // Database interface that both Tauri SQLite and BrowserDatabase implement
@@ -29,6 +34,12 @@ async function queueChange(tableName: string, recordId: string, operation: 'inse
   return _queueChange(tableName, recordId, operation, payload);
 }
 
+// In the app, budgets, expenses and goals go through typed backend
+// commands; the SQL next to them is what the browser build runs.
+async function backend() {
+  return (await import('./bindings')).commands;
+}
+
 let db: DatabaseInterface | null = null;
 let dbInitPromise: Promise<DatabaseInterface> | null = null;
 
@@ -66,8 +77,9 @@ export async function getDatabase(): Promise<DatabaseInterface> {
 
 // Budget operations
 export async function getCurrentBudget(): Promise<Budget | null> {
+  if (isTauri()) return unwrap((await backend()).getCurrentBudget());
   const database = await getDatabase();
-  const month = getCurrentMonth();
+  const month = (await getCurrentPeriod()).key;
   const result = await database.select<Budget[]>(
     "SELECT * FROM budgets WHERE month = $1 AND deleted_at IS NULL",
     [month]
@@ -76,8 +88,17 @@ export async function getCurrentBudget(): Promise<Budget | null> {
 }
 
 export async function createOrUpdateBudget(totalAmount: number, spendingLimit?: number): Promise<Budget> {
+  if (isTauri()) {
+    return unwrap(
+      (await backend()).saveBudget({
+        total_amount: totalAmount,
+        spending_limit: spendingLimit ?? null,
+      })
+    );
+  }
+
   const database = await getDatabase();
-  const month = getCurrentMonth();
+  const month = (await getCurrentPeriod()).key;
   const now = new Date().toISOString();
   const userId = await getCurrentUserId();
 
@@ -101,12 +122,13 @@ export async function createOrUpdateBudget(totalAmount: number, spendingLimit?:
       await queueChange('budgets', existing.id, 'update', updated);
     }
 
+    publishDomainEvent({ type: 'budget:updated', budget_id: existing.id, month });
     return updated;
   } else {
     const id = generateId();
     await database.execute(
-      "INSERT INTO budgets (id, user_id, month, total_amount, spending_limit, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7)",
-      [id, userId, month, totalAmount, spendingLimit ?? null, now, now]
+      "INSERT INTO budgets (id, user_id, month, total_amount, spending_limit, created_by, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+      [id, userId, month, totalAmount, spendingLimit ?? null, userId, now, now]
     );
     const budget: Budget = {
       id,
@@ -114,6 +136,7 @@ export async function createOrUpdateBudget(totalAmount: number, spendingLimit?:
       month,
       total_amount: totalAmount,
       spending_limit: spendingLimit ?? null,
+      created_by: userId,
       created_at: now,
       updated_at: now,
       deleted_at: null,
@@ -124,6 +147,7 @@ export async function createOrUpdateBudget(totalAmount: number, spendingLimit?:
       await queueChange('budgets', id, 'insert', budget);
     }
 
+    publishDomainEvent({ type: 'budget:updated', budget_id: id, month });
     return budget;
   }
 }
@@ -247,21 +271,33 @@ export async function deleteCategory(id: string): Promise<void> {
     throw new Error('Cannot delete default categories');
   }
 
+  // Soft delete so it can be restored from the trash; tombstones are
+  // purged later.
+  await database.execute(
+    "UPDATE categories SET deleted_at = $1, updated_at = $1 WHERE id = $2",
+    [now, id]
+  );
   if (userId) {
-    // Soft delete for authenticated users (for sync)
-    await database.execute(
-      "UPDATE categories SET deleted_at = $1, updated_at = $1 WHERE id = $2",
-      [now, id]
-    );
     await queueChange('categories', id, 'delete', { id, deleted_at: now, updated_at: now });
-  } else {
-    // Hard delete for offline-only users
-    await database.execute("DELETE FROM categories WHERE id = $1", [id]);
   }
 }
 
 // Expense operations
-export async function addExpense(amount: number, categoryId?: string, note?: string, date?: string): Promise<Expense> {
+export async function addExpense(amount: number, categoryId?: string, note?: string, date?: string, currency?: string): Promise<Expense> {
+  if (isTauri()) {
+    return unwrap(
+      (await backend()).createExpense({
+        amount,
+        category_id: categoryId ?? null,
+        note: note ?? null,
+        date: date ?? null,
+        payment_method: null,
+        currency: currency ?? null,
+        account_id: null,
+      })
+    );
+  }
+
   const database = await getDatabase();
   const id = generateId();
   const now = new Date().toISOString();
@@ -269,8 +305,8 @@ export async function addExpense(amount: number, categoryId?: string, note?: str
   const userId = await getCurrentUserId();
 
   await database.execute(
-    "INSERT INTO expenses (id, user_id, amount, category_id, note, date, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
-    [id, userId, amount, categoryId ?? null, note ?? null, expenseDate, now, now]
+    "INSERT INTO expenses (id, user_id, amount, category_id, note, date, created_by, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+    [id, userId, amount, categoryId ?? null, note ?? null, expenseDate, userId, now, now]
   );
 
   const expense: Expense = {
@@ -280,6 +316,7 @@ export async function addExpense(amount: number, categoryId?: string, note?: str
     category_id: categoryId ?? null,
     note: note ?? null,
     date: expenseDate,
+    created_by: userId,
     created_at: now,
     updated_at: now,
     synced_at: null,
@@ -291,13 +328,32 @@ export async function addExpense(amount: number, categoryId?: string, note?: str
     await queueChange('expenses', id, 'insert', expense);
   }
 
+  refreshCategoryTotals(expense);
+  checkCategoryAlerts(expense.category_id);
+  publishDomainEvent({
+    type: 'expense:created',
+    expense_id: id,
+    category_id: expense.category_id,
+    amount,
+    date: expenseDate,
+  });
+
   return expense;
 }
 
-export async function updateExpense(id: string, updates: Partial<Pick<Expense, 'amount' | 'category_id' | 'note' | 'date'>>): Promise<void> {
+export async function updateExpense(id: string, updates: Partial<Pick<Expense, 'amount' | 'category_id' | 'note' | 'date' | 'payment_method'>>): Promise<void> {
+  if (isTauri()) {
+    await unwrap((await backend()).updateExpense(id, { amount: null, date: null, ...updates }));
+    return;
+  }
+
   const database = await getDatabase();
   const now = new Date().toISOString();
   const userId = await getCurrentUserId();
+  const before = await database.select<Expense[]>(
+    "SELECT * FROM expenses WHERE id = $1",
+    [id]
+  );
 
   const setClauses: string[] = ['updated_at = $1'];
   const params: (string | number | null)[] = [now];
@@ -319,6 +375,10 @@ export async function updateExpense(id: string, updates: Partial<Pick<Expense, '
     setClauses.push(`date = $${paramIndex++}`);
     params.push(updates.date);
   }
+  if (updates.payment_method !== undefined) {
+    setClauses.push(`payment_method = $${paramIndex++}`);
+    params.push(updates.payment_method);
+  }
 
   params.push(id);
 
@@ -327,63 +387,92 @@ export async function updateExpense(id: string, updates: Partial<Pick<Expense, '
     params
   );
 
-  // Queue for sync - fetch the updated expense
-  if (userId) {
-    const result = await database.select<Expense[]>(
-      "SELECT * FROM expenses WHERE id = $1",
-      [id]
-    );
-    if (result[0]) {
-      await queueChange('expenses', id, 'update', result[0]);
-    }
+  const result = await database.select<Expense[]>(
+    "SELECT * FROM expenses WHERE id = $1",
+    [id]
+  );
+  // Queue for sync
+  if (userId && result[0]) {
+    await queueChange('expenses', id, 'update', result[0]);
   }
+
+  refreshCategoryTotals(before[0], result[0]);
+  checkCategoryAlerts(result[0]?.category_id);
 }
 
 export async function deleteExpense(id: string): Promise<void> {
+  if (isTauri()) {
+    await unwrap((await backend()).deleteExpense(id));
+    return;
+  }
+
   const database = await getDatabase();
   const now = new Date().toISOString();
   const userId = await getCurrentUserId();
+  const existing = await database.select<Expense[]>(
+    "SELECT * FROM expenses WHERE id = $1",
+    [id]
+  );
 
+  // Soft delete so it can be restored from the trash; tombstones are
+  // purged later.
+  await database.execute(
+    "UPDATE expenses SET deleted_at = $1, updated_at = $1 WHERE id = $2",
+    [now, id]
+  );
   if (userId) {
-    // Soft delete for authenticated users (for sync)
-    await database.execute(
-      "UPDATE expenses SET deleted_at = $1, updated_at = $1 WHERE id = $2",
-      [now, id]
-    );
     await queueChange('expenses', id, 'delete', { id, deleted_at: now, updated_at: now });
-  } else {
-    // Hard delete for offline-only users
-    await database.execute("DELETE FROM expenses WHERE id = $1", [id]);
   }
+
+  refreshCategoryTotals(existing[0]);
+}
+
+// First and last day of `month`, or of the current budget period
+async function dateRange(month?: string): Promise<[string, string]> {
+  if (month) return [`${month}-01`, `${month}-31`];
+  const period = await getCurrentPeriod();
+  return [period.start, period.end];
 }
 
 export async function getExpensesForMonth(month?: string): Promise<ExpenseWithCategory[]> {
+  const [start, end] = await dateRange(month);
+  if (isTauri()) {
+    return unwrap((await backend()).getExpenses(start, end));
+  }
   const database = await getDatabase();
-  const targetMonth = month || getCurrentMonth();
 
   return database.select<ExpenseWithCategory[]>(
     `SELECT e.*, c.name as category_name, c.icon as category_icon, c.color as category_color
      FROM expenses e
      LEFT JOIN categories c ON e.category_id = c.id
-     WHERE strftime('%Y-%m', e.date) = $1 AND e.deleted_at IS NULL
+     WHERE substr(e.date, 1, 10) BETWEEN $1 AND $2 AND e.deleted_at IS NULL
      ORDER BY e.date DESC, e.created_at DESC`,
-    [targetMonth]
+    [start, end]
   );
 }
 
+// Net spending: expenses that were paid back don't count
 export async function getMonthlySpending(month?: string): Promise<number> {
+  const [start, end] = await dateRange(month);
+  if (isTauri()) {
+    const summary = await unwrap((await backend()).getSpendingSummary(start, end, null));
+    return summary.net;
+  }
   const database = await getDatabase();
-  const targetMonth = month || getCurrentMonth();
 
   const result = await database.select<{ total: number }[]>(
-    `SELECT COALESCE(SUM(amount), 0) as total FROM expenses WHERE strftime('%Y-%m', date) = $1 AND deleted_at IS NULL`,
-    [targetMonth]
+    `SELECT COALESCE(SUM(amount), 0) as total FROM expenses
+     WHERE substr(date, 1, 10) BETWEEN $1 AND $2 AND deleted_at IS NULL
+       AND COALESCE(reimbursement_status, '') != 'received'`,
+    [start, end]
   );
 
   return result[0]?.total || 0;
 }
 
 export async function getRecentExpenses(limit: number = 10): Promise<ExpenseWithCategory[]> {
+  if (isTauri()) return unwrap((await backend()).getRecentExpenses(limit));
+
   const database = await getDatabase();
 
   return database.select<ExpenseWithCategory[]>(
@@ -438,22 +527,21 @@ export async function deleteFeedbackNote(id: string): Promise<void> {
   const now = new Date().toISOString();
   const userId = await getCurrentUserId();
 
+  // Soft delete so it can be restored from the trash; tombstones are
+  // purged later.
+  await database.execute(
+    "UPDATE feedback_notes SET deleted_at = $1, updated_at = $1 WHERE id = $2",
+    [now, id]
+  );
   if (userId) {
-    // Soft delete for authenticated users (for sync)
-    await database.execute(
-      "UPDATE feedback_notes SET deleted_at = $1, updated_at = $1 WHERE id = $2",
-      [now, id]
-    );
     await queueChange('feedback_notes', id, 'delete', { id, deleted_at: now, updated_at: now });
-  } else {
-    // Hard delete for offline-only users
-    await database.execute("DELETE FROM feedback_notes WHERE id = $1", [id]);
   }
 }
 
 // Savings Goals operations
 
 export async function getSavingsGoals(): Promise<SavingsGoal[]> {
+  if (isTauri()) return unwrap((await backend()).getSavingsGoals()) as Promise<SavingsGoal[]>;
   const database = await getDatabase();
   return database.select<SavingsGoal[]>(
     "SELECT * FROM savings_goals WHERE deleted_at IS NULL ORDER BY created_at DESC"
@@ -461,6 +549,7 @@ export async function getSavingsGoals(): Promise<SavingsGoal[]> {
 }
 
 export async function getSavingsGoal(id: string): Promise<SavingsGoal | null> {
+  if (isTauri()) return unwrap((await backend()).getSavingsGoal(id)) as Promise<SavingsGoal | null>;
   const database = await getDatabase();
   const result = await database.select<SavingsGoal[]>(
     "SELECT * FROM savings_goals WHERE id = $1 AND deleted_at IS NULL",
@@ -476,6 +565,18 @@ export async function createSavingsGoal(
   monthlyContribution: number,
   whyStatement?: string
 ): Promise<SavingsGoal> {
+  if (isTauri()) {
+    return unwrap(
+      (await backend()).createSavingsGoal({
+        name,
+        target_amount: targetAmount,
+        target_date: targetDate,
+        monthly_contribution: monthlyContribution,
+        why_statement: whyStatement ?? null,
+      })
+    ) as Promise<SavingsGoal>;
+  }
+
   const database = await getDatabase();
   const id = generateId();
   const now = new Date().toISOString();
@@ -513,6 +614,19 @@ export async function updateSavingsGoal(
   id: string,
   updates: Partial<Pick<SavingsGoal, 'name' | 'target_amount' | 'target_date' | 'monthly_contribution' | 'why_statement' | 'privacy_level'>>
 ): Promise<void> {
+  if (isTauri()) {
+    const update = {
+      name: null,
+      target_amount: null,
+      target_date: null,
+      monthly_contribution: null,
+      privacy_level: null,
+      ...updates,
+    };
+    await unwrap((await backend()).updateSavingsGoal(id, update));
+    return;
+  }
+
   const database = await getDatabase();
   const now = new Date().toISOString();
   const userId = await getCurrentUserId();
@@ -566,36 +680,36 @@ export async function updateSavingsGoal(
 }
 
 export async function deleteSavingsGoal(id: string): Promise<void> {
+  if (isTauri()) {
+    await unwrap((await backend()).deleteSavingsGoal(id));
+    return;
+  }
+
   const database = await getDatabase();
   const now = new Date().toISOString();
   const userId = await getCurrentUserId();
 
+  // Soft delete so it can be restored from the trash; tombstones are
+  // purged later.
+  await database.execute(
+    "UPDATE savings_goals SET deleted_at = $1, updated_at = $1 WHERE id = $2",
+    [now, id]
+  );
   if (userId) {
-    // Soft delete for authenticated users (for sync)
-    await database.execute(
-      "UPDATE savings_goals SET deleted_at = $1, updated_at = $1 WHERE id = $2",
-      [now, id]
-    );
     await queueChange('savings_goals', id, 'delete', { id, deleted_at: now, updated_at: now });
-  } else {
-    // Hard delete for offline-only users
-    await database.execute("DELETE FROM savings_goals WHERE id = $1", [id]);
   }
 
   // Also delete associated contributions
-  if (userId) {
-    await database.execute(
-      "UPDATE savings_contributions SET deleted_at = $1, updated_at = $1 WHERE goal_id = $2",
-      [now, id]
-    );
-  } else {
-    await database.execute("DELETE FROM savings_contributions WHERE goal_id = $1", [id]);
-  }
+  await database.execute(
+    "UPDATE savings_contributions SET deleted_at = $1, updated_at = $1 WHERE goal_id = $2 AND deleted_at IS NULL",
+    [now, id]
+  );
 }
 
 // Savings Contributions operations
 
 export async function getContributionsForGoal(goalId: string): Promise<SavingsContribution[]> {
+  if (isTauri()) return unwrap((await backend()).getGoalContributions(goalId));
   const database = await getDatabase();
   return database.select<SavingsContribution[]>(
     "SELECT * FROM savings_contributions WHERE goal_id = $1 AND deleted_at IS NULL ORDER BY month DESC",
@@ -618,6 +732,16 @@ export async function addContribution(
   amount: number,
   isFullAmount: boolean
 ): Promise<SavingsContribution> {
+  if (isTauri()) {
+    return unwrap(
+      (await backend()).saveGoalContribution(goalId, {
+        month,
+        amount,
+        is_full_amount: isFullAmount,
+      })
+    );
+  }
+
   const database = await getDatabase();
   const id = generateId();
   const now = new Date().toISOString();
@@ -643,6 +767,13 @@ export async function addContribution(
       await queueChange('savings_contributions', existing.id, 'update', updated);
     }
 
+    publishDomainEvent({
+      type: 'goal:contribution_added',
+      goal_id: goalId,
+      contribution_id: existing.id,
+      month,
+      amount,
+    });
     return updated;
   }
 
@@ -669,6 +800,13 @@ export async function addContribution(
     await queueChange('savings_contributions', id, 'insert', contribution);
   }
 
+  publishDomainEvent({
+    type: 'goal:contribution_added',
+    goal_id: goalId,
+    contribution_id: id,
+    month,
+    amount,
+  });
   return contribution;
 }
 
@@ -810,6 +948,29 @@ export async function getAllSavingsGoalsWithStats(): Promise<SavingsGoalWithStat
   return goalsWithStats;
 }
 
+// Goals and habits without a contribution or tracking row for `month`
+// (default: this month), with suggested amounts
+export async function getPendingCheckins(month?: string): Promise<PendingCheckin[]> {
+  if (isTauri()) return unwrap((await backend()).getPendingCheckins(month ?? null));
+
+  const targetMonth = month || getCurrentMonth();
+  const pending: PendingCheckin[] = [];
+  for (const goal of await getSavingsGoals()) {
+    if (goal.created_at.slice(0, 7) > targetMonth) continue;
+    if (await getContributionForMonth(goal.id, targetMonth)) continue;
+    pending.push({
+      kind: 'savings_goal',
+      goal_id: goal.id,
+      name: goal.name,
+      month: targetMonth,
+      suggested_amount: goal.monthly_contribution,
+      target_amount: goal.monthly_contribution,
+      is_count: false,
+    });
+  }
+  return pending;
+}
+
 // Check if any goals need monthly check-in (for the previous month)
 export async function getGoalsNeedingCheckIn(): Promise<SavingsGoal[]> {
   const now = new Date();
@@ -855,7 +1016,7 @@ export async function getHabitGoal(id: string): Promise<HabitGoal | null> {
 export async function createHabitGoal(
   name: string,
   categoryId: string,
-  ruleType: 'max_amount' | 'max_percentage' | 'reduce_by',
+  ruleType: 'max_amount' | 'max_percentage' | 'reduce_by' | 'no_spend' | 'max_count',
   ruleValue: number,
   durationMonths?: number
 ): Promise<HabitGoal> {
@@ -958,27 +1119,21 @@ export async function deleteHabitGoal(id: string): Promise<void> {
   const now = new Date().toISOString();
   const userId = await getCurrentUserId();
 
+  // Soft delete so it can be restored from the trash; tombstones are
+  // purged later.
+  await database.execute(
+    "UPDATE habit_goals SET deleted_at = $1, updated_at = $1 WHERE id = $2",
+    [now, id]
+  );
   if (userId) {
-    // Soft delete for authenticated users (for sync)
-    await database.execute(
-      "UPDATE habit_goals SET deleted_at = $1, updated_at = $1 WHERE id = $2",
-      [now, id]
-    );
     await queueChange('habit_goals', id, 'delete', { id, deleted_at: now, updated_at: now });
-  } else {
-    // Hard delete for offline-only users
-    await database.execute("DELETE FROM habit_goals WHERE id = $1", [id]);
   }
 
   // Also delete associated tracking records
-  if (userId) {
-    await database.execute(
-      "UPDATE habit_tracking SET deleted_at = $1, updated_at = $1 WHERE habit_goal_id = $2",
-      [now, id]
-    );
-  } else {
-    await database.execute("DELETE FROM habit_tracking WHERE habit_goal_id = $1", [id]);
-  }
+  await database.execute(
+    "UPDATE habit_tracking SET deleted_at = $1, updated_at = $1 WHERE habit_goal_id = $2 AND deleted_at IS NULL",
+    [now, id]
+  );
 }
 
 // ============================================
@@ -1065,7 +1220,17 @@ export async function recordHabitTracking(
   return tracking;
 }
 
+// Month and week streaks, with freezes, for every active habit
+export async function getHabitStreaks(): Promise<HabitStreak[]> {
+  return unwrap((await backend()).getHabitStreaks());
+}
+
 export async function getHabitStreakForGoal(habitGoalId: string): Promise<number> {
+  if (isTauri()) {
+    const streaks = await getHabitStreaks();
+    return streaks.find((s) => s.habit_goal_id === habitGoalId)?.current_months ?? 0;
+  }
+
   const database = await getDatabase();
   const trackingRecords = await database.select<HabitTracking[]>(
     "SELECT * FROM habit_tracking WHERE habit_goal_id = $1 AND deleted_at IS NULL ORDER BY month DESC",
@@ -1097,8 +1262,13 @@ export async function getHabitStreakForGoal(habitGoalId: string): Promise<number
 
 // Get spending for a specific category in a month
 export async function getCategorySpendingForMonth(categoryId: string, month?: string): Promise<number> {
-  const database = await getDatabase();
   const targetMonth = month || getCurrentMonth();
+  if (isTauri()) {
+    const totals = await getMonthlyCategoryTotals(targetMonth);
+    return totals.find((t) => t.category_id === categoryId)?.gross ?? 0;
+  }
+
+  const database = await getDatabase();
 
   const result = await database.select<{ total: number }[]>(
     `SELECT COALESCE(SUM(amount), 0) as total FROM expenses
@@ -1109,6 +1279,17 @@ export async function getCategorySpendingForMonth(categoryId: string, month?: st
   return result[0]?.total || 0;
 }
 
+// Number of expenses in a category for a month (for max_count habits)
+export async function getCategoryCountForMonth(categoryId: string, month: string): Promise<number> {
+  const database = await getDatabase();
+  const result = await database.select<{ count: number }[]>(
+    `SELECT COUNT(*) as count FROM expenses
+     WHERE category_id = $1 AND strftime('%Y-%m', date) = $2 AND deleted_at IS NULL`,
+    [categoryId, month]
+  );
+  return result[0]?.count || 0;
+}
+
 // Get spending for previous month for a category (for reduce_by calculations)
 export async function getCategorySpendingForPreviousMonth(categoryId: string): Promise<number> {
   const now = new Date();
@@ -1153,7 +1334,11 @@ export async function calculateHabitTargetAmount(
       return prevMonthSpending * (1 - habitGoal.rule_value / 100);
     }
 
+    case 'no_spend':
+      return 0;
+
     default:
+      // max_amount, and max_count where the value is a number of purchases
       return habitGoal.rule_value;
   }
 }
@@ -1173,16 +1358,19 @@ export async function getHabitGoalWithStats(habitGoalId: string): Promise<HabitG
   );
   const category = categories[0];
 
-  // Get current month spending for this category
-  const currentMonthSpent = await getCategorySpendingForMonth(goal.category_id, currentMonth);
+  // Get current month spending for this category; for max_count the
+  // number of purchases instead
+  const currentMonthSpent = goal.rule_type === 'max_count'
+    ? await getCategoryCountForMonth(goal.category_id, currentMonth)
+    : await getCategorySpendingForMonth(goal.category_id, currentMonth);
 
   // Calculate target amount based on rule type
   const currentMonthTarget = await calculateHabitTargetAmount(goal, currentMonth);
 
-  // Calculate percentage used
+  // Calculate percentage used; a no-spend habit is used up by any spending
   const percentageUsed = currentMonthTarget > 0
     ? (currentMonthSpent / currentMonthTarget) * 100
-    : 0;
+    : currentMonthSpent > 0 ? 100 : 0;
 
   // Determine compliance and status
   const isCompliant = currentMonthSpent <= currentMonthTarget;
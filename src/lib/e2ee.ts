@@ -0,0 +1,154 @@
+import type { JsonValue } from './bindings';
+import { unwrap } from './commands';
+import { getDatabase } from './database';
+import { isTauri } from './platform';
+import type { getSupabase } from './supabase';
+
+/**
+ * End-to-end encryption of synced data. The Rust side seals rows before
+ * they are pushed and opens pulled ones; the key never leaves the user's
+ * devices except wrapped with the recovery code.
+ */
+
+export interface SyncEncryptionStatus {
+  enabled: boolean;
+  // Whether this device can read encrypted rows
+  has_key: boolean;
+  fingerprint: string | null;
+  // A key request from this device is waiting for approval
+  awaiting_key: boolean;
+}
+
+export interface RecoveryKit {
+  recovery_code: string;
+  fingerprint: string;
+}
+
+export interface SyncKeyRequest {
+  id: string;
+  device_name: string;
+  // Compare with the code shown on the requesting device
+  verification: string;
+  created_at: string;
+  approved: boolean;
+}
+
+const SEALED_COLUMN = 'encrypted_payload';
+
+function assertTauri(): void {
+  if (!isTauri()) {
+    throw new Error('Sync encryption is only available in the desktop and mobile apps');
+  }
+}
+
+export async function getSyncEncryptionStatus(): Promise<SyncEncryptionStatus> {
+  if (!isTauri()) return { enabled: false, has_key: false, fingerprint: null, awaiting_key: false };
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getSyncEncryptionStatus());
+}
+
+/**
+ * Turn encryption on with a new key. The recovery code is only ever shown
+ * here, so the caller must make sure the user writes it down.
+ */
+export async function enableSyncEncryption(): Promise<RecoveryKit> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.enableSyncEncryption());
+}
+
+export async function exportSyncKey(): Promise<string> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.exportSyncKey());
+}
+
+export async function importSyncKey(key: string): Promise<SyncEncryptionStatus> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.importSyncKey(key));
+}
+
+export async function recoverSyncKey(code: string): Promise<SyncEncryptionStatus> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.recoverSyncKey(code));
+}
+
+/**
+ * Ask the user's other devices for the key. Poll receiveSyncKey until one
+ * of them approved.
+ */
+export async function requestSyncKey(deviceName: string): Promise<SyncKeyRequest> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.requestSyncKey(deviceName));
+}
+
+export async function getSyncKeyRequests(): Promise<SyncKeyRequest[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getSyncKeyRequests());
+}
+
+export async function approveSyncKeyRequest(id: string): Promise<void> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  await unwrap(commands.approveSyncKeyRequest(id));
+}
+
+export async function receiveSyncKey(): Promise<SyncEncryptionStatus> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.receiveSyncKey());
+}
+
+/**
+ * Note the account's public key once encryption was turned on from another
+ * device, so this one seals what it pushes too. Called at the start of
+ * each sync pull.
+ */
+export async function refreshSyncEncryption(
+  supabase: NonNullable<ReturnType<typeof getSupabase>>,
+  userId: string
+): Promise<void> {
+  if (!isTauri()) return;
+  const { data, error } = await supabase
+    .from('sync_keys')
+    .select('recipient, created_at')
+    .eq('user_id', userId)
+    .maybeSingle();
+  if (error) {
+    console.warn('Failed to check sync encryption:', error.message);
+    return;
+  }
+  if (!data) return;
+
+  const db = await getDatabase();
+  await db.execute(
+    `INSERT INTO sync_encryption (id, recipient, enabled_at) VALUES (1, $1, $2)
+     ON CONFLICT(id) DO UPDATE SET recipient = excluded.recipient`,
+    [data.recipient, data.created_at ?? new Date().toISOString()]
+  );
+}
+
+/**
+ * Decrypt the sealed rows of a pulled table. The web app has no key, so it
+ * leaves them out rather than merging empty values.
+ */
+export async function openRemoteRows<T extends Record<string, unknown>>(
+  tableName: string,
+  rows: T[] | null
+): Promise<T[]> {
+  const all = rows ?? [];
+  if (!all.some(row => typeof row[SEALED_COLUMN] === 'string')) return all;
+
+  if (!isTauri()) {
+    console.warn(`Skipping encrypted ${tableName} rows, which only the apps can read`);
+    return all.filter(row => typeof row[SEALED_COLUMN] !== 'string');
+  }
+  const { commands } = await import('./bindings');
+  const input = all as unknown as Partial<Record<string, JsonValue>>[];
+  const opened = await unwrap(commands.openSyncRows(input));
+  return opened as unknown as T[];
+}
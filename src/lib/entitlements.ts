@@ -0,0 +1,49 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * Premium entitlements. The backend only trusts tokens signed by the
+ * license server, so these are read from it rather than from the database.
+ */
+
+export type PurchaseSource = 'app_store' | 'play_store' | 'stripe';
+
+export interface Entitlements {
+  plan: string;
+  status: 'none' | 'active' | 'grace' | 'expired';
+  features: string[];
+  source: PurchaseSource | null;
+  expires_at: string | null;
+  grace_until: string | null;
+}
+
+export interface Receipt {
+  source: PurchaseSource;
+  token: string;
+  product_id?: string | null;
+}
+
+const FREE: Entitlements = {
+  plan: 'free',
+  status: 'none',
+  features: [],
+  source: null,
+  expires_at: null,
+  grace_until: null,
+};
+
+export async function getEntitlements(): Promise<Entitlements> {
+  if (!isTauri()) return FREE;
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getEntitlements());
+}
+
+/**
+ * Verify a purchase with the license server, or restore earlier purchases
+ * when no receipt is given.
+ */
+export async function verifyPurchase(receipt?: Receipt): Promise<Entitlements> {
+  if (!isTauri()) return FREE;
+  const { commands } = await import('./bindings');
+  return unwrap(commands.verifyPurchase(receipt ? { product_id: null, ...receipt } : null));
+}
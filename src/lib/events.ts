@@ -0,0 +1,155 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * Domain events shared by all windows. The backend broadcasts each one
+ * under its `type`; writes made here are published through it too, so
+ * every window hears about every change the same way.
+ */
+
+export type DomainEvent =
+  | {
+      type: 'expense:created';
+      expense_id: string;
+      category_id: string | null;
+      amount: number;
+      date: string;
+    }
+  | {
+      type: 'expense:updated';
+      expense_id: string;
+      category_id: string | null;
+      amount: number;
+      date: string;
+    }
+  | { type: 'expense:deleted'; expense_id: string }
+  | {
+      type: 'expense:imported';
+      source: string;
+      count: number;
+    }
+  | { type: 'expense:bulk_changed'; expense_ids: string[] }
+  | {
+      type: 'expense:reimbursement_updated';
+      expense_id: string;
+      status: 'pending' | 'received' | 'written_off' | null;
+    }
+  | {
+      type: 'attachment:added';
+      attachment_id: string;
+      expense_id: string;
+    }
+  | { type: 'attachment:deleted'; attachment_id: string }
+  | {
+      type: 'budget:updated';
+      budget_id: string;
+      // Budget period key
+      month: string;
+    }
+  | { type: 'budget:settings_updated' }
+  | {
+      type: 'budget:category_updated';
+      month: string;
+      category_id: string;
+      // null once the allocation is removed
+      amount: number | null;
+    }
+  | { type: 'goal:created'; goal_id: string }
+  | { type: 'goal:updated'; goal_id: string }
+  | { type: 'goal:deleted'; goal_id: string }
+  | {
+      type: 'goal:contribution_added';
+      goal_id: string;
+      contribution_id: string;
+      month: string;
+      amount: number;
+    }
+  | { type: 'recurring:created'; recurring_ids: string[] }
+  | { type: 'income:source_created'; source_id: string }
+  | { type: 'income:source_deleted'; source_id: string }
+  | {
+      type: 'income:logged';
+      entry_id: string;
+      source_id: string;
+      amount: number;
+      date: string;
+    }
+  | { type: 'income:entry_deleted'; entry_id: string }
+  | { type: 'net_worth:balance_recorded'; account_name: string }
+  | { type: 'trial:created'; trial_id: string }
+  | { type: 'trial:cancelled'; trial_id: string }
+  | { type: 'bill:saved'; bill_id: string }
+  | { type: 'bill:deleted'; bill_id: string }
+  | {
+      type: 'bill:paid';
+      bill_id: string;
+      // Cycle paid, YYYY-MM
+      month: string;
+    }
+  | { type: 'split:saved'; expense_id: string }
+  | { type: 'split:settlement_recorded'; settlement_id: string }
+  | { type: 'split:settlement_deleted'; settlement_id: string }
+  | { type: 'household:share_changed'; record_id: string; household_id: string | null }
+  | { type: 'tag:saved'; tag_id: string }
+  | { type: 'tag:deleted'; tag_id: string }
+  | { type: 'tag:expenses_tagged'; expense_ids: string[] }
+  | { type: 'merchant:renamed'; previous_id: string; merchant_id: string }
+  | { type: 'account:saved'; account_id: string }
+  | { type: 'account:deleted'; account_id: string }
+  | {
+      type: 'account:transfer_saved';
+      transfer_id: string;
+      from_account_id: string;
+      to_account_id: string;
+    }
+  | { type: 'account:transfer_deleted'; transfer_id: string }
+  | { type: 'categorization_rule:saved'; rule_id: string }
+  | { type: 'categorization_rule:deleted'; rule_id: string }
+  | { type: 'category_alert:saved'; category_id: string }
+  | { type: 'category_alert:deleted'; category_id: string }
+  | { type: 'preferences:notifications_updated' }
+  | { type: 'currency:base_changed'; code: string }
+  | { type: 'data:restored'; source: 'backup' | 'archive' }
+  | {
+      type: 'trash:restored';
+      kind:
+        | 'expense'
+        | 'budget'
+        | 'savings_goal'
+        | 'habit_goal'
+        | 'category'
+        | 'feedback_note'
+        | 'recurring_expense';
+      record_id: string;
+    }
+  | {
+      type: 'sync:applied_remote_changes';
+      tables: string[];
+      count: number;
+    }
+  | { type: 'sync:remote_kept'; table_name: string; record_id: string };
+
+export type DomainEventType = DomainEvent['type'];
+
+/**
+ * Broadcast a change. Failures are only logged; other windows catch up on
+ * their next load.
+ */
+export function publishDomainEvent(event: DomainEvent): void {
+  if (!isTauri()) return;
+  import('./bindings')
+    .then(({ commands }) => unwrap(commands.publishDomainEvent(event)))
+    .catch((error) => console.error('[Events] Publish failed:', error));
+}
+
+/**
+ * Listen for one kind of event. Returns a function that stops listening.
+ */
+export async function onDomainEvent<T extends DomainEventType>(
+  type: T,
+  handler: (event: Extract<DomainEvent, { type: T }>) => void
+): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  return listen<Extract<DomainEvent, { type: T }>>(type, (event) => handler(event.payload));
+}
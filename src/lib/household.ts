@@ -0,0 +1,401 @@
+import { getCurrentUserId } from './auth';
+import { unwrap } from './commands';
+import { getDatabase } from './database';
+import { isTauri } from './platform';
+import { generateInviteCode, getAuthedSupabase } from './sharing';
+import { getSupabase } from './supabase';
+import { generateId } from './types';
+
+/**
+ * Households: a group of accounts sharing some of their records.
+ *
+ * Unlike partner mode every member keeps their own ledger and shares single
+ * expenses and budgets by setting household_id. The other members' shared
+ * rows are pulled into household_expenses and household_budgets, so they
+ * show up in the household view without counting towards the user's own
+ * budgets.
+ */
+
+export interface HouseholdMember {
+  household_id: string;
+  user_id: string;
+  display_name: string | null;
+  role: 'owner' | 'member';
+  joined_at: string | null;
+  is_me: boolean;
+}
+
+export interface Household {
+  id: string;
+  name: string;
+  owner_id: string;
+  invite_code: string;
+  created_at: string;
+  members: HouseholdMember[];
+}
+
+export interface HouseholdExpense {
+  id: string;
+  owner_id: string;
+  owner_name: string | null;
+  // Only the user's own shared expenses can be changed
+  mine: boolean;
+  amount: number;
+  category_id: string | null;
+  category_name: string | null;
+  category_icon: string | null;
+  category_color: string | null;
+  note: string | null;
+  date: string;
+}
+
+export interface MemberSpending {
+  owner_id: string;
+  owner_name: string | null;
+  mine: boolean;
+  total: number;
+  count: number;
+}
+
+export interface HouseholdBudget {
+  id: string;
+  owner_id: string;
+  owner_name: string | null;
+  mine: boolean;
+  month: string;
+  total_amount: number;
+  spending_limit: number | null;
+}
+
+function assertTauri(): void {
+  if (!isTauri()) {
+    throw new Error('Households are only available in the desktop and mobile apps');
+  }
+}
+
+export async function getHouseholds(): Promise<Household[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getHouseholds()) as Promise<Household[]>;
+}
+
+/**
+ * Start a household with the user as its owner.
+ */
+export async function createHousehold(name: string): Promise<Household> {
+  assertTauri();
+  const trimmed = name.trim();
+  if (!trimmed) throw new Error('Give the household a name');
+
+  const supabase = await getAuthedSupabase();
+  const userId = await getCurrentUserId();
+  const now = new Date().toISOString();
+  const id = generateId();
+
+  const { error } = await supabase.from('households').insert({
+    id,
+    name: trimmed,
+    owner_id: userId,
+    invite_code: generateInviteCode(),
+    created_at: now,
+    updated_at: now,
+  });
+  if (error) throw new Error(error.message);
+
+  const { error: memberError } = await supabase
+    .from('household_members')
+    .insert({ household_id: id, user_id: userId, role: 'owner', joined_at: now });
+  if (memberError) throw new Error(memberError.message);
+
+  await refreshHouseholds();
+  return requireHousehold(id);
+}
+
+/**
+ * Join someone else's household with the code they shared.
+ */
+export async function joinHousehold(code: string): Promise<Household> {
+  assertTauri();
+  const supabase = await getAuthedSupabase();
+  const { data, error } = await supabase.rpc('join_household', {
+    code: code.trim().toUpperCase(),
+  });
+  if (error) throw new Error(error.message);
+
+  await refreshHouseholds();
+  return requireHousehold(data.id);
+}
+
+async function requireHousehold(id: string): Promise<Household> {
+  const household = (await getHouseholds()).find(h => h.id === id);
+  if (!household) throw new Error('Household not found');
+  return household;
+}
+
+/**
+ * Leave a household. Rows the user shared with it stay visible to the
+ * remaining members until unshared.
+ */
+export async function leaveHousehold(id: string): Promise<void> {
+  assertTauri();
+  const supabase = await getAuthedSupabase();
+  const { error } = await supabase.rpc('leave_household', { household: id });
+  if (error) throw new Error(error.message);
+
+  await removeLocalHousehold(id);
+}
+
+async function removeLocalHousehold(id: string): Promise<void> {
+  const db = await getDatabase();
+  await db.execute('DELETE FROM household_expenses WHERE household_id = $1', [id]);
+  await db.execute('DELETE FROM household_budgets WHERE household_id = $1', [id]);
+  await db.execute('DELETE FROM household_members WHERE household_id = $1', [id]);
+  await db.execute('DELETE FROM households WHERE id = $1', [id]);
+}
+
+export async function shareExpenseWithHousehold(expenseId: string, householdId: string | null): Promise<void> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  await unwrap(commands.shareExpenseWithHousehold(expenseId, householdId));
+}
+
+export async function shareBudgetWithHousehold(budgetId: string, householdId: string | null): Promise<void> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  await unwrap(commands.shareBudgetWithHousehold(budgetId, householdId));
+}
+
+/**
+ * Every member's shared expenses between two dates, newest first.
+ */
+export async function getHouseholdExpenses(
+  householdId: string,
+  startDate: string,
+  endDate: string
+): Promise<HouseholdExpense[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getHouseholdExpenses(householdId, startDate, endDate));
+}
+
+export async function getHouseholdSpendingByMember(
+  householdId: string,
+  startDate: string,
+  endDate: string
+): Promise<MemberSpending[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getHouseholdSpendingByMember(householdId, startDate, endDate));
+}
+
+export async function getHouseholdBudgets(householdId: string, month: string): Promise<HouseholdBudget[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getHouseholdBudgets(householdId, month));
+}
+
+/**
+ * Refresh the local households and members from Supabase, dropping the
+ * ones the user has left or that were deleted. Called at the start of each
+ * sync pull.
+ */
+export async function refreshHouseholds(): Promise<void> {
+  if (!isTauri()) return;
+  const supabase = await getAuthedSupabase();
+  const userId = await getCurrentUserId();
+  const db = await getDatabase();
+
+  const { data: memberships, error } = await supabase
+    .from('household_members')
+    .select('household_id')
+    .eq('user_id', userId)
+    .is('left_at', null);
+  if (error) throw new Error(error.message);
+
+  const ids = (memberships ?? []).map(m => m.household_id as string);
+  const { data: households, error: householdsError } = ids.length
+    ? await supabase.from('households').select('*').in('id', ids).is('deleted_at', null)
+    : { data: [], error: null };
+  if (householdsError) throw new Error(householdsError.message);
+
+  const { data: members, error: membersError } = ids.length
+    ? await supabase.from('household_members').select('*').in('household_id', ids).is('left_at', null)
+    : { data: [], error: null };
+  if (membersError) throw new Error(membersError.message);
+
+  const memberIds = [...new Set((members ?? []).map(m => m.user_id as string))];
+  const { data: profiles } = memberIds.length
+    ? await supabase.from('profiles').select('id, display_name, email').in('id', memberIds)
+    : { data: [] };
+  const names = new Map((profiles ?? []).map(p => [p.id as string, (p.display_name || p.email || null) as string | null]));
+
+  const active = new Set((households ?? []).map(h => h.id as string));
+  const local = await db.select<{ id: string }[]>('SELECT id FROM households');
+  for (const { id } of local) {
+    if (!active.has(id)) await removeLocalHousehold(id);
+  }
+
+  const now = new Date().toISOString();
+  for (const household of households ?? []) {
+    await db.execute(
+      `INSERT INTO households (id, name, owner_id, invite_code, created_at, updated_at)
+       VALUES ($1, $2, $3, $4, $5, $6)
+       ON CONFLICT(id) DO UPDATE SET name = excluded.name, owner_id = excluded.owner_id,
+         invite_code = excluded.invite_code, updated_at = excluded.updated_at`,
+      [household.id, household.name, household.owner_id, household.invite_code, household.created_at ?? now, now]
+    );
+    await db.execute('DELETE FROM household_members WHERE household_id = $1', [household.id]);
+  }
+  for (const member of members ?? []) {
+    if (!active.has(member.household_id)) continue;
+    await db.execute(
+      `INSERT INTO household_members (household_id, user_id, display_name, role, joined_at)
+       VALUES ($1, $2, $3, $4, $5)`,
+      [member.household_id, member.user_id, names.get(member.user_id) ?? null, member.role, member.joined_at]
+    );
+  }
+}
+
+/**
+ * Drop pulled rows their owner has stopped sharing. Unshared rows are no
+ * longer visible at all, so they never come in as changes.
+ */
+async function pruneUnshared(
+  supabase: NonNullable<ReturnType<typeof getSupabase>>,
+  householdId: string,
+  own: string
+): Promise<void> {
+  const db = await getDatabase();
+  for (const table of ['expenses', 'budgets']) {
+    const { data, error } = await supabase
+      .from(table)
+      .select('id')
+      .eq('household_id', householdId)
+      .not('user_id', 'in', own);
+    if (error) throw new Error(error.message);
+
+    const shared = new Set((data ?? []).map(row => row.id as string));
+    const local = await db.select<{ id: string }[]>(
+      `SELECT id FROM household_${table} WHERE household_id = $1`,
+      [householdId]
+    );
+    for (const row of local) {
+      if (!shared.has(row.id)) {
+        await db.execute(`DELETE FROM household_${table} WHERE id = $1`, [row.id]);
+      }
+    }
+  }
+}
+
+/**
+ * Pull the other members' shared expenses and budgets of each household.
+ * Rows of ledgerUserIds are the user's own and come in with the normal
+ * pull. Each household has its own cursor, the newest row pulled so far,
+ * so a household joined later still gets its older records.
+ */
+export async function pullHouseholdRecords(
+  supabase: NonNullable<ReturnType<typeof getSupabase>>,
+  ledgerUserIds: string[]
+): Promise<{ pulled: number; tables: string[] }> {
+  const result = { pulled: 0, tables: [] as string[] };
+  if (!isTauri()) return result;
+
+  const db = await getDatabase();
+  const households = await db.select<{ id: string }[]>('SELECT id FROM households');
+  const own = `(${ledgerUserIds.join(',')})`;
+  const now = new Date().toISOString();
+
+  for (const { id } of households) {
+    await pruneUnshared(supabase, id, own);
+
+    const [expenseCursor] = await db.select<{ since: string | null }[]>(
+      'SELECT MAX(updated_at) as since FROM household_expenses WHERE household_id = $1',
+      [id]
+    );
+    let expensesQuery = supabase
+      .from('expenses')
+      .select('*, categories(name, icon, color)')
+      .eq('household_id', id)
+      .not('user_id', 'in', own);
+    if (expenseCursor?.since) {
+      expensesQuery = expensesQuery.gt('updated_at', expenseCursor.since);
+    }
+    const { data: expenses, error } = await expensesQuery;
+    if (error) throw new Error(error.message);
+
+    for (const remote of expenses ?? []) {
+      await db.execute(
+        `INSERT INTO household_expenses (id, household_id, owner_id, amount, category_id, category_name,
+           category_icon, category_color, note, date, created_by, created_at, updated_at, deleted_at, pulled_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+         ON CONFLICT(id) DO UPDATE SET household_id = excluded.household_id, owner_id = excluded.owner_id,
+           amount = excluded.amount, category_id = excluded.category_id, category_name = excluded.category_name,
+           category_icon = excluded.category_icon, category_color = excluded.category_color, note = excluded.note,
+           date = excluded.date, created_by = excluded.created_by, updated_at = excluded.updated_at,
+           deleted_at = excluded.deleted_at, pulled_at = excluded.pulled_at`,
+        [
+          remote.id,
+          id,
+          remote.created_by ?? remote.user_id,
+          remote.amount,
+          remote.category_id,
+          remote.categories?.name ?? null,
+          remote.categories?.icon ?? null,
+          remote.categories?.color ?? null,
+          remote.note,
+          remote.date,
+          remote.created_by,
+          remote.created_at,
+          remote.updated_at,
+          remote.deleted_at,
+          now,
+        ]
+      );
+      result.pulled++;
+      if (!result.tables.includes('household_expenses')) result.tables.push('household_expenses');
+    }
+
+    const [budgetCursor] = await db.select<{ since: string | null }[]>(
+      'SELECT MAX(updated_at) as since FROM household_budgets WHERE household_id = $1',
+      [id]
+    );
+    let budgetsQuery = supabase
+      .from('budgets')
+      .select('*')
+      .eq('household_id', id)
+      .not('user_id', 'in', own);
+    if (budgetCursor?.since) {
+      budgetsQuery = budgetsQuery.gt('updated_at', budgetCursor.since);
+    }
+    const { data: budgets, error: budgetsError } = await budgetsQuery;
+    if (budgetsError) throw new Error(budgetsError.message);
+
+    for (const remote of budgets ?? []) {
+      await db.execute(
+        `INSERT INTO household_budgets (id, household_id, owner_id, month, total_amount, spending_limit,
+           created_at, updated_at, deleted_at, pulled_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+         ON CONFLICT(id) DO UPDATE SET household_id = excluded.household_id, owner_id = excluded.owner_id,
+           month = excluded.month, total_amount = excluded.total_amount,
+           spending_limit = excluded.spending_limit, updated_at = excluded.updated_at,
+           deleted_at = excluded.deleted_at, pulled_at = excluded.pulled_at`,
+        [
+          remote.id,
+          id,
+          remote.created_by ?? remote.user_id,
+          remote.month,
+          remote.total_amount,
+          remote.spending_limit,
+          remote.created_at,
+          remote.updated_at,
+          remote.deleted_at,
+          now,
+        ]
+      );
+      result.pulled++;
+      if (!result.tables.includes('household_budgets')) result.tables.push('household_budgets');
+    }
+  }
+
+  return result;
+}
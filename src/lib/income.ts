@@ -0,0 +1,153 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * Income sources with what they are expected to pay each budget period,
+ * and the money actually received from them.
+ */
+
+export interface IncomeSource {
+  id: string;
+  name: string;
+  expected_amount: number;
+  // Day of the budget period the money usually arrives, 1 being the first
+  expected_day: number;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface IncomeEntry {
+  id: string;
+  source_id: string;
+  amount: number;
+  date: string;
+  note: string | null;
+  created_at: string;
+  account_id: string | null;
+}
+
+export interface IncomeVariance {
+  source_id: string;
+  name: string;
+  expected: number;
+  received: number;
+  // Negative when short
+  difference: number;
+  expected_by: string;
+  overdue: boolean;
+}
+
+export interface MonthCashflow {
+  // YYYY-MM
+  month: string;
+  income: number;
+  expenses: number;
+  // Negative when more went out than came in
+  net: number;
+  // Share of income left over; null without income
+  savings_rate: number | null;
+}
+
+export interface CashflowBaseline {
+  from_month: string;
+  to_month: string;
+  average_income: number;
+  average_expenses: number;
+  average_net: number;
+  suggested_limit: number | null;
+}
+
+function assertTauri(): void {
+  if (!isTauri()) {
+    throw new Error('Income tracking is only available in the desktop and mobile apps');
+  }
+}
+
+export async function getIncomeSources(): Promise<IncomeSource[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getIncomeSources());
+}
+
+export async function createIncomeSource(
+  name: string,
+  expectedAmount: number,
+  expectedDay = 1
+): Promise<IncomeSource> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(
+    commands.createIncomeSource({
+      name,
+      expected_amount: expectedAmount,
+      expected_day: expectedDay,
+    })
+  );
+}
+
+export async function deleteIncomeSource(id: string): Promise<void> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  await unwrap(commands.deleteIncomeSource(id));
+}
+
+export async function getIncomeEntries(startDate: string, endDate: string): Promise<IncomeEntry[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getIncomeEntries(startDate, endDate));
+}
+
+export async function deleteIncomeEntry(id: string): Promise<void> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  await unwrap(commands.deleteIncomeEntry(id));
+}
+
+export async function logIncome(
+  sourceId: string,
+  amount: number,
+  date: string,
+  note?: string,
+  accountId?: string
+): Promise<IncomeEntry> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(
+    commands.logIncome({
+      source_id: sourceId,
+      amount,
+      date,
+      note: note ?? null,
+      account_id: accountId ?? null,
+    })
+  );
+}
+
+/**
+ * Expected against received income per source for the current period.
+ */
+export async function getIncomeVariance(): Promise<IncomeVariance[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getIncomeVariance());
+}
+
+/**
+ * Income minus spending per month, both months inclusive (YYYY-MM).
+ */
+export async function getMonthlyCashflow(fromMonth: string, toMonth: string): Promise<MonthCashflow[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getMonthlyCashflow(fromMonth, toMonth));
+}
+
+/**
+ * Average income and spending over the last full months. With a share
+ * (0 to 1), suggested_limit is that much of the average income, for
+ * setting the budget relative to what comes in.
+ */
+export async function getCashflowBaseline(months?: number, share?: number): Promise<CashflowBaseline> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getCashflowBaseline(months ?? null, share ?? null));
+}
@@ -0,0 +1,54 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * Export to ledger-cli or beancount. Categories, income sources and
+ * accounts are booked to double-entry account names like
+ * "Expenses:Groceries" or "Liabilities:Visa", which the mapping can change.
+ */
+
+export type LedgerFormat = 'ledger' | 'beancount';
+
+export interface LedgerDateRange {
+  // YYYY-MM-DD, inclusive
+  start_date: string;
+  end_date: string;
+}
+
+export interface AccountMapping {
+  // Keyed by category, income source and account id
+  categories: Record<string, string>;
+  income_sources: Record<string, string>;
+  accounts: Record<string, string>;
+  // Pays for spending without an account, "Assets:Cash" by default
+  default_account: string | null;
+}
+
+export interface LedgerExport {
+  path: string;
+  transactions: number;
+}
+
+function assertTauri(): void {
+  if (!isTauri()) {
+    throw new Error('Ledger export is only available in the desktop and mobile apps');
+  }
+}
+
+/** The account name everything is exported under, defaults filled in. */
+export async function getLedgerAccountMapping(): Promise<AccountMapping | null> {
+  if (!isTauri()) return null;
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getLedgerAccountMapping());
+}
+
+/** Write the file to the downloads folder; a given mapping is remembered. */
+export async function exportLedger(
+  format: LedgerFormat,
+  range: LedgerDateRange,
+  mapping?: AccountMapping
+): Promise<LedgerExport> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.exportLedger(format, range, mapping ?? null));
+}
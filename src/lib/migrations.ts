@@ -12,7 +12,7 @@ import { getBrowserDatabase } from './browser-database';
 import { isTauri } from './platform';
 
 // Database interface
-interface DatabaseInterface {
+export interface DatabaseInterface {
   execute(query: string, params?: unknown[]): Promise<{ rowsAffected: number }>;
   select<T>(query: string, params?: unknown[]): Promise<T>;
 }
@@ -260,6 +260,1424 @@ CREATE TABLE IF NOT EXISTS _migrations (
   id INTEGER PRIMARY KEY AUTOINCREMENT,
   name TEXT NOT NULL UNIQUE,
   applied_at TEXT NOT NULL
+);
+    `,
+  },
+  {
+    name: '00002_recurring_expenses',
+    sql: `
+-- ============================================
+-- Recurring Expenses (local-only)
+-- ============================================
+CREATE TABLE IF NOT EXISTS recurring_expenses (
+  id TEXT PRIMARY KEY,
+  user_id TEXT,
+  name TEXT NOT NULL,
+  amount REAL NOT NULL,
+  category_id TEXT,
+  cadence TEXT NOT NULL, -- 'weekly', 'biweekly', 'monthly', 'quarterly', 'yearly'
+  next_due_date TEXT NOT NULL,
+  match_key TEXT, -- normalized note linking the entry to past expenses
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  deleted_at TEXT,
+  FOREIGN KEY (category_id) REFERENCES categories(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_recurring_expenses_due ON recurring_expenses(next_due_date);
+CREATE INDEX IF NOT EXISTS idx_recurring_expenses_match_key ON recurring_expenses(match_key);
+    `,
+  },
+  {
+    name: '00003_merchant_aliases',
+    sql: `
+-- ============================================
+-- Merchant Aliases (local-only)
+-- User corrections for merchant name normalization
+-- ============================================
+CREATE TABLE IF NOT EXISTS merchant_aliases (
+  raw_key TEXT PRIMARY KEY,
+  merchant_name TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+    `,
+  },
+  {
+    name: '00004_trials',
+    sql: `
+-- ============================================
+-- Trials (local-only)
+-- Free trials that turn into paid subscriptions
+-- ============================================
+CREATE TABLE IF NOT EXISTS trials (
+  id TEXT PRIMARY KEY,
+  user_id TEXT,
+  name TEXT NOT NULL,
+  expense_id TEXT,
+  amount_after_trial REAL,
+  trial_ends_on TEXT NOT NULL,
+  reminder_id TEXT,
+  cancelled_at TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  deleted_at TEXT,
+  FOREIGN KEY (expense_id) REFERENCES expenses(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_trials_ends_on ON trials(trial_ends_on);
+    `,
+  },
+  {
+    name: '00005_expense_reimbursements',
+    sql: `
+-- ============================================
+-- Expense Reimbursements (local-only)
+-- NULL status means the expense is not expected to be paid back
+-- ============================================
+ALTER TABLE expenses ADD COLUMN reimbursement_status TEXT;
+ALTER TABLE expenses ADD COLUMN reimbursement_owed_by TEXT;
+ALTER TABLE expenses ADD COLUMN reimbursed_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_expenses_reimbursement ON expenses(reimbursement_status);
+    `,
+  },
+  {
+    name: '00006_expense_payment_method',
+    sql: `
+-- ============================================
+-- Expense Payment Method (local-only)
+-- cash, card, bank or other
+-- ============================================
+ALTER TABLE expenses ADD COLUMN payment_method TEXT;
+    `,
+  },
+  {
+    name: '00007_partner_mode',
+    sql: `
+-- ============================================
+-- Partner Mode
+-- created_by records who entered a budget or expense in a shared ledger
+-- ============================================
+ALTER TABLE budgets ADD COLUMN created_by TEXT;
+ALTER TABLE expenses ADD COLUMN created_by TEXT;
+
+UPDATE budgets SET created_by = user_id WHERE created_by IS NULL;
+UPDATE expenses SET created_by = user_id WHERE created_by IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_expenses_created_by ON expenses(created_by);
+
+-- Local copy of the partnership (local-only, at most one row)
+CREATE TABLE IF NOT EXISTS partnership (
+  id TEXT PRIMARY KEY,
+  owner_id TEXT NOT NULL,
+  partner_id TEXT,
+  invite_code TEXT NOT NULL,
+  partner_name TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+    `,
+  },
+  {
+    name: '00008_sync_conflicts',
+    sql: `
+-- ============================================
+-- Sync conflicts (local-only)
+-- A remote change pulled while the same record still had unsynced local
+-- edits. Both versions are kept until the user picks one, and pushes for
+-- the record are held until then.
+-- ============================================
+CREATE TABLE IF NOT EXISTS sync_conflicts (
+  id TEXT PRIMARY KEY,
+  table_name TEXT NOT NULL,
+  record_id TEXT NOT NULL,
+  remote_payload TEXT NOT NULL,
+  remote_updated_at TEXT NOT NULL,
+  detected_at TEXT NOT NULL,
+  resolved_at TEXT,
+  resolution TEXT
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_sync_conflicts_open ON sync_conflicts(table_name, record_id) WHERE resolved_at IS NULL;
+    `,
+  },
+  {
+    name: '00009_change_history',
+    sql: `
+-- ============================================
+-- Change history (local-only)
+-- audit_log is written by triggers, so edits from the UI, from sync and
+-- from Rust commands all land there. Only user-facing columns are tracked.
+-- sync_events is written by the sync engine for every pushed or pulled row.
+-- ============================================
+CREATE TABLE IF NOT EXISTS audit_log (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  table_name TEXT NOT NULL,
+  record_id TEXT NOT NULL,
+  action TEXT NOT NULL,
+  old_values TEXT,
+  new_values TEXT,
+  changed_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_record ON audit_log(table_name, record_id);
+
+CREATE TABLE IF NOT EXISTS sync_events (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  table_name TEXT NOT NULL,
+  record_id TEXT NOT NULL,
+  direction TEXT NOT NULL,
+  operation TEXT,
+  occurred_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_sync_events_record ON sync_events(table_name, record_id);
+
+CREATE TRIGGER IF NOT EXISTS audit_budgets_insert AFTER INSERT ON budgets
+BEGIN
+  INSERT INTO audit_log (table_name, record_id, action, new_values, changed_at)
+  VALUES ('budgets', NEW.id, 'insert', json_object('month', NEW.month, 'total_amount', NEW.total_amount, 'spending_limit', NEW.spending_limit, 'deleted_at', NEW.deleted_at), strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS audit_budgets_update AFTER UPDATE ON budgets
+WHEN OLD.month IS NOT NEW.month
+  OR OLD.total_amount IS NOT NEW.total_amount
+  OR OLD.spending_limit IS NOT NEW.spending_limit
+  OR OLD.deleted_at IS NOT NEW.deleted_at
+BEGIN
+  INSERT INTO audit_log (table_name, record_id, action, old_values, new_values, changed_at)
+  VALUES ('budgets', NEW.id, 'update', json_object('month', OLD.month, 'total_amount', OLD.total_amount, 'spending_limit', OLD.spending_limit, 'deleted_at', OLD.deleted_at), json_object('month', NEW.month, 'total_amount', NEW.total_amount, 'spending_limit', NEW.spending_limit, 'deleted_at', NEW.deleted_at), strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS audit_budgets_delete AFTER DELETE ON budgets
+BEGIN
+  INSERT INTO audit_log (table_name, record_id, action, old_values, changed_at)
+  VALUES ('budgets', OLD.id, 'delete', json_object('month', OLD.month, 'total_amount', OLD.total_amount, 'spending_limit', OLD.spending_limit, 'deleted_at', OLD.deleted_at), strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS audit_expenses_insert AFTER INSERT ON expenses
+BEGIN
+  INSERT INTO audit_log (table_name, record_id, action, new_values, changed_at)
+  VALUES ('expenses', NEW.id, 'insert', json_object('amount', NEW.amount, 'category_id', NEW.category_id, 'note', NEW.note, 'date', NEW.date, 'payment_method', NEW.payment_method, 'reimbursement_status', NEW.reimbursement_status, 'deleted_at', NEW.deleted_at), strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS audit_expenses_update AFTER UPDATE ON expenses
+WHEN OLD.amount IS NOT NEW.amount
+  OR OLD.category_id IS NOT NEW.category_id
+  OR OLD.note IS NOT NEW.note
+  OR OLD.date IS NOT NEW.date
+  OR OLD.payment_method IS NOT NEW.payment_method
+  OR OLD.reimbursement_status IS NOT NEW.reimbursement_status
+  OR OLD.deleted_at IS NOT NEW.deleted_at
+BEGIN
+  INSERT INTO audit_log (table_name, record_id, action, old_values, new_values, changed_at)
+  VALUES ('expenses', NEW.id, 'update', json_object('amount', OLD.amount, 'category_id', OLD.category_id, 'note', OLD.note, 'date', OLD.date, 'payment_method', OLD.payment_method, 'reimbursement_status', OLD.reimbursement_status, 'deleted_at', OLD.deleted_at), json_object('amount', NEW.amount, 'category_id', NEW.category_id, 'note', NEW.note, 'date', NEW.date, 'payment_method', NEW.payment_method, 'reimbursement_status', NEW.reimbursement_status, 'deleted_at', NEW.deleted_at), strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS audit_expenses_delete AFTER DELETE ON expenses
+BEGIN
+  INSERT INTO audit_log (table_name, record_id, action, old_values, changed_at)
+  VALUES ('expenses', OLD.id, 'delete', json_object('amount', OLD.amount, 'category_id', OLD.category_id, 'note', OLD.note, 'date', OLD.date, 'payment_method', OLD.payment_method, 'reimbursement_status', OLD.reimbursement_status, 'deleted_at', OLD.deleted_at), strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS audit_savings_goals_insert AFTER INSERT ON savings_goals
+BEGIN
+  INSERT INTO audit_log (table_name, record_id, action, new_values, changed_at)
+  VALUES ('savings_goals', NEW.id, 'insert', json_object('name', NEW.name, 'target_amount', NEW.target_amount, 'target_date', NEW.target_date, 'monthly_contribution', NEW.monthly_contribution, 'deleted_at', NEW.deleted_at), strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS audit_savings_goals_update AFTER UPDATE ON savings_goals
+WHEN OLD.name IS NOT NEW.name
+  OR OLD.target_amount IS NOT NEW.target_amount
+  OR OLD.target_date IS NOT NEW.target_date
+  OR OLD.monthly_contribution IS NOT NEW.monthly_contribution
+  OR OLD.deleted_at IS NOT NEW.deleted_at
+BEGIN
+  INSERT INTO audit_log (table_name, record_id, action, old_values, new_values, changed_at)
+  VALUES ('savings_goals', NEW.id, 'update', json_object('name', OLD.name, 'target_amount', OLD.target_amount, 'target_date', OLD.target_date, 'monthly_contribution', OLD.monthly_contribution, 'deleted_at', OLD.deleted_at), json_object('name', NEW.name, 'target_amount', NEW.target_amount, 'target_date', NEW.target_date, 'monthly_contribution', NEW.monthly_contribution, 'deleted_at', NEW.deleted_at), strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS audit_savings_goals_delete AFTER DELETE ON savings_goals
+BEGIN
+  INSERT INTO audit_log (table_name, record_id, action, old_values, changed_at)
+  VALUES ('savings_goals', OLD.id, 'delete', json_object('name', OLD.name, 'target_amount', OLD.target_amount, 'target_date', OLD.target_date, 'monthly_contribution', OLD.monthly_contribution, 'deleted_at', OLD.deleted_at), strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS audit_savings_contributions_insert AFTER INSERT ON savings_contributions
+BEGIN
+  INSERT INTO audit_log (table_name, record_id, action, new_values, changed_at)
+  VALUES ('savings_contributions', NEW.id, 'insert', json_object('goal_id', NEW.goal_id, 'month', NEW.month, 'amount', NEW.amount, 'is_full_amount', NEW.is_full_amount, 'deleted_at', NEW.deleted_at), strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS audit_savings_contributions_update AFTER UPDATE ON savings_contributions
+WHEN OLD.goal_id IS NOT NEW.goal_id
+  OR OLD.month IS NOT NEW.month
+  OR OLD.amount IS NOT NEW.amount
+  OR OLD.is_full_amount IS NOT NEW.is_full_amount
+  OR OLD.deleted_at IS NOT NEW.deleted_at
+BEGIN
+  INSERT INTO audit_log (table_name, record_id, action, old_values, new_values, changed_at)
+  VALUES ('savings_contributions', NEW.id, 'update', json_object('goal_id', OLD.goal_id, 'month', OLD.month, 'amount', OLD.amount, 'is_full_amount', OLD.is_full_amount, 'deleted_at', OLD.deleted_at), json_object('goal_id', NEW.goal_id, 'month', NEW.month, 'amount', NEW.amount, 'is_full_amount', NEW.is_full_amount, 'deleted_at', NEW.deleted_at), strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS audit_savings_contributions_delete AFTER DELETE ON savings_contributions
+BEGIN
+  INSERT INTO audit_log (table_name, record_id, action, old_values, changed_at)
+  VALUES ('savings_contributions', OLD.id, 'delete', json_object('goal_id', OLD.goal_id, 'month', OLD.month, 'amount', OLD.amount, 'is_full_amount', OLD.is_full_amount, 'deleted_at', OLD.deleted_at), strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+    `,
+  },
+  {
+    name: '00010_sync_backoff',
+    sql: `
+-- ============================================
+-- Sync retry backoff (local-only)
+-- A failed push is retried after next_attempt_at. Items that run out of
+-- attempts are dead-lettered and skipped until retried by hand.
+-- ============================================
+ALTER TABLE sync_queue ADD COLUMN next_attempt_at TEXT;
+ALTER TABLE sync_queue ADD COLUMN dead_lettered_at TEXT;
+
+UPDATE sync_queue SET dead_lettered_at = COALESCE(last_attempt_at, created_at) WHERE attempts >= 5;
+
+CREATE INDEX IF NOT EXISTS idx_sync_queue_due ON sync_queue(next_attempt_at) WHERE dead_lettered_at IS NULL;
+    `,
+  },
+  {
+    name: '00011_attachments',
+    sql: `
+-- ============================================
+-- Attachments (files synced through object storage)
+-- file_name is relative to the attachments folder in the app data dir.
+-- sync_status is local, uploading, synced, remote, downloading or failed.
+-- upload_url and transferred_bytes let an interrupted transfer resume.
+-- ============================================
+CREATE TABLE IF NOT EXISTS attachments (
+  id TEXT PRIMARY KEY,
+  expense_id TEXT NOT NULL,
+  file_name TEXT NOT NULL,
+  mime_type TEXT NOT NULL,
+  size_bytes INTEGER NOT NULL,
+  sha256 TEXT NOT NULL,
+  sync_status TEXT NOT NULL DEFAULT 'local',
+  remote_path TEXT,
+  upload_url TEXT,
+  transferred_bytes INTEGER NOT NULL DEFAULT 0,
+  sync_error TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  deleted_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_attachments_expense ON attachments(expense_id);
+CREATE INDEX IF NOT EXISTS idx_attachments_sync_status ON attachments(sync_status);
+    `,
+  },
+  {
+    name: '00012_backup_targets',
+    sql: `
+-- ============================================
+-- Backup targets (local-only)
+-- Where automatic encrypted backups go: a Dropbox or Google Drive account
+-- connected through OAuth, or a folder such as iCloud Drive.
+-- ============================================
+CREATE TABLE IF NOT EXISTS backup_targets (
+  id TEXT PRIMARY KEY,
+  provider TEXT NOT NULL CHECK (provider IN ('dropbox', 'google_drive', 'folder')),
+  folder_path TEXT,
+  client_id TEXT,
+  client_secret TEXT,
+  access_token TEXT,
+  refresh_token TEXT,
+  token_expires_at TEXT,
+  remote_folder_id TEXT,
+  passphrase TEXT NOT NULL,
+  interval_hours INTEGER NOT NULL DEFAULT 24,
+  last_backup_at TEXT,
+  last_error TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+    `,
+  },
+  {
+    name: '00013_month_close',
+    sql: `
+-- ============================================
+-- Budget template (local-only)
+-- What a new month budget starts from. NULL amounts copy the previous
+-- month. With carry_over, what was left of last month (or overspent) is
+-- added to the new limit.
+-- ============================================
+CREATE TABLE IF NOT EXISTS budget_template (
+  id INTEGER PRIMARY KEY CHECK (id = 1),
+  total_amount REAL,
+  spending_limit REAL,
+  carry_over INTEGER NOT NULL DEFAULT 0,
+  updated_at TEXT NOT NULL
+);
+
+-- ============================================
+-- Month closes (local-only)
+-- Final numbers of a finished month, written once by the month-end job.
+-- ============================================
+CREATE TABLE IF NOT EXISTS month_closes (
+  month TEXT PRIMARY KEY,
+  budget_id TEXT,
+  budget_limit REAL,
+  spent_amount REAL NOT NULL,
+  rollover_amount REAL NOT NULL DEFAULT 0,
+  next_budget_id TEXT,
+  closed_at TEXT NOT NULL
+);
+    `,
+  },
+  {
+    name: '00014_pay_schedule',
+    sql: `
+-- ============================================
+-- Pay schedule (local-only)
+-- How budget periods line up with paydays. Without a row budgets run
+-- per calendar month. anchor_date is a known payday (YYYY-MM-DD).
+-- ============================================
+CREATE TABLE IF NOT EXISTS pay_schedule (
+  id INTEGER PRIMARY KEY CHECK (id = 1),
+  frequency TEXT NOT NULL DEFAULT 'monthly'
+    CHECK (frequency IN ('monthly', 'semimonthly', 'biweekly', 'weekly')),
+  anchor_date TEXT,
+  updated_at TEXT NOT NULL
+);
+    `,
+  },
+  {
+    name: '00015_income',
+    sql: `
+-- ============================================
+-- Income sources (local-only)
+-- What each source is expected to pay per budget period and the day of
+-- the period it usually arrives on, 1 being the first day.
+-- ============================================
+CREATE TABLE IF NOT EXISTS income_sources (
+  id TEXT PRIMARY KEY,
+  user_id TEXT,
+  name TEXT NOT NULL,
+  expected_amount REAL NOT NULL DEFAULT 0,
+  expected_day INTEGER NOT NULL DEFAULT 1,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  deleted_at TEXT
+);
+
+-- ============================================
+-- Income entries (local-only)
+-- Money actually received from a source.
+-- ============================================
+CREATE TABLE IF NOT EXISTS income_entries (
+  id TEXT PRIMARY KEY,
+  user_id TEXT,
+  source_id TEXT NOT NULL REFERENCES income_sources(id),
+  amount REAL NOT NULL,
+  date TEXT NOT NULL,
+  note TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  deleted_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_income_entries_source_date ON income_entries(source_id, date);
+    `,
+  },
+  {
+    name: '00016_account_balances',
+    sql: `
+-- ============================================
+-- Account balances (local-only)
+-- Dated balance snapshots for net worth. source is manual for balances
+-- the user entered or derived for ones computed from app data, such as
+-- money saved towards a goal. Liabilities hold positive amounts.
+-- ============================================
+CREATE TABLE IF NOT EXISTS account_balances (
+  id TEXT PRIMARY KEY,
+  user_id TEXT,
+  account_key TEXT NOT NULL,
+  account_name TEXT NOT NULL,
+  kind TEXT NOT NULL DEFAULT 'asset' CHECK (kind IN ('asset', 'liability')),
+  balance REAL NOT NULL,
+  as_of TEXT NOT NULL,
+  source TEXT NOT NULL DEFAULT 'manual' CHECK (source IN ('manual', 'derived')),
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  deleted_at TEXT
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_account_balances_account_date ON account_balances(account_key, as_of);
+    `,
+  },
+  {
+    name: '00017_app_meta',
+    sql: `
+-- ============================================
+-- App metadata (local-only)
+-- Key-value facts about this installation, written by the backend.
+-- Keys include schema_version, data_format_version, install_id,
+-- last_backup_at and last_maintenance_at.
+-- ============================================
+CREATE TABLE IF NOT EXISTS app_meta (
+  key TEXT PRIMARY KEY,
+  value TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+    `,
+  },
+  {
+    name: '00018_category_alert_thresholds',
+    sql: `
+-- ============================================
+-- Category alert thresholds (local-only)
+-- A spending limit per budget period for a category and the
+-- percentages of it to warn at, as a JSON array of integers.
+-- An empty array never warns.
+-- ============================================
+CREATE TABLE IF NOT EXISTS category_alert_thresholds (
+  category_id TEXT PRIMARY KEY REFERENCES categories(id) ON DELETE CASCADE,
+  limit_amount REAL NOT NULL,
+  thresholds TEXT NOT NULL DEFAULT '[80,100]',
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+    `,
+  },
+  {
+    name: '00019_statement_imports',
+    sql: `
+-- ============================================
+-- Bank statement imports (local-only)
+-- Statements imported for reconciliation. Each line is a debit from the
+-- statement, stored as a positive amount like expenses, and is linked to
+-- the expense it was matched with, if any.
+-- ============================================
+CREATE TABLE IF NOT EXISTS statement_imports (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  start_date TEXT,
+  end_date TEXT,
+  line_count INTEGER NOT NULL DEFAULT 0,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS statement_lines (
+  id TEXT PRIMARY KEY,
+  import_id TEXT NOT NULL REFERENCES statement_imports(id) ON DELETE CASCADE,
+  date TEXT NOT NULL,
+  amount REAL NOT NULL,
+  description TEXT NOT NULL,
+  matched_expense_id TEXT REFERENCES expenses(id) ON DELETE SET NULL,
+  matched_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_statement_lines_import ON statement_lines(import_id, date);
+    `,
+  },
+  {
+    name: '00020_recurring_reminders',
+    sql: `
+-- ============================================
+-- Recurring expense reminders (local-only)
+-- Days before the due date to send a reminder, NULL for none, and the
+-- UID of the calendar event an entry was imported from.
+-- ============================================
+ALTER TABLE recurring_expenses ADD COLUMN remind_days_before INTEGER;
+ALTER TABLE recurring_expenses ADD COLUMN calendar_uid TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_recurring_expenses_calendar_uid ON recurring_expenses(calendar_uid);
+    `,
+  },
+  {
+    name: '00021_entitlements',
+    sql: `
+-- ============================================
+-- Entitlements (local-only)
+-- The latest signed entitlement token issued by the server for each
+-- account. The backend verifies the signature every time it reads one,
+-- so editing this table does not unlock anything.
+-- ============================================
+CREATE TABLE IF NOT EXISTS entitlement_tokens (
+  user_id TEXT PRIMARY KEY,
+  token TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+    `,
+  },
+  {
+    name: '00022_referrals',
+    sql: `
+-- ============================================
+-- Referral attributions (local-only)
+-- Invite codes this install was opened with. A code stays pending until
+-- the backend has validated it, which may be long after the link was
+-- opened if the device was offline or signed out.
+-- Status is one of pending, redeemed, rejected or ignored.
+-- ============================================
+CREATE TABLE IF NOT EXISTS referral_attributions (
+  code TEXT PRIMARY KEY,
+  status TEXT NOT NULL DEFAULT 'pending',
+  received_at TEXT NOT NULL,
+  redeemed_at TEXT,
+  error TEXT,
+  updated_at TEXT NOT NULL
+);
+    `,
+  },
+  {
+    name: '00023_experiments',
+    sql: `
+-- ============================================
+-- Telemetry queue (local-only)
+-- Product events waiting to be sent, with their properties as JSON.
+-- ============================================
+CREATE TABLE IF NOT EXISTS telemetry_events (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  properties TEXT NOT NULL DEFAULT '{}',
+  created_at TEXT NOT NULL,
+  sent_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_telemetry_events_unsent ON telemetry_events(sent_at, created_at);
+
+-- ============================================
+-- Experiment assignments (local-only)
+-- The variant this install was put in for each experiment, and when it
+-- was first shown.
+-- ============================================
+CREATE TABLE IF NOT EXISTS experiment_assignments (
+  experiment TEXT PRIMARY KEY,
+  variant TEXT NOT NULL,
+  assigned_at TEXT NOT NULL,
+  exposed_at TEXT
+);
+    `,
+  },
+  {
+    name: '00024_monthly_category_totals',
+    sql: `
+-- ============================================
+-- Monthly category totals (local-only)
+-- Spending per calendar month and category, kept up to date by the
+-- backend after every expense write so dashboards do not scan expenses.
+-- category_key is the category id, or an empty string for uncategorized.
+-- total leaves out expenses that were paid back, gross includes them.
+-- ============================================
+CREATE TABLE IF NOT EXISTS monthly_category_totals (
+  month TEXT NOT NULL,
+  category_key TEXT NOT NULL,
+  total REAL NOT NULL,
+  gross REAL NOT NULL,
+  expense_count INTEGER NOT NULL,
+  updated_at TEXT NOT NULL,
+  PRIMARY KEY (month, category_key)
+);
+    `,
+  },
+  {
+    name: '00025_platform_notifications',
+    sql: `
+-- ============================================
+-- Platform notifications (local-only)
+-- Pending scheduled_notifications handed to the OS scheduler on mobile
+-- so they fire while the app is closed. platform_id is the numeric id
+-- the OS knows them by. Rows here were delivered by the OS, so the
+-- in-app scheduler marks them sent without showing them again.
+-- ============================================
+CREATE TABLE IF NOT EXISTS platform_notifications (
+  notification_id TEXT PRIMARY KEY,
+  platform_id INTEGER NOT NULL,
+  scheduled_at TEXT NOT NULL,
+  registered_at TEXT NOT NULL
+);
+    `,
+  },
+  {
+    name: '00026_foreign_key_actions',
+    sql: `
+-- ============================================
+-- Orphaned rows (local-only)
+-- Rows whose required parent no longer exists, moved aside by the
+-- backend cleanup instead of being deleted outright. row_data holds
+-- the whole row as JSON.
+-- ============================================
+CREATE TABLE IF NOT EXISTS orphaned_rows (
+  id TEXT PRIMARY KEY,
+  table_name TEXT NOT NULL,
+  record_id TEXT,
+  parent_table TEXT NOT NULL,
+  row_data TEXT NOT NULL,
+  quarantined_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_orphaned_rows_table ON orphaned_rows(table_name);
+
+-- ============================================
+-- Delete actions for foreign keys declared without one
+-- SQLite cannot alter a constraint in place, so these triggers do what
+-- ON DELETE SET NULL or CASCADE would. They run before the end of the
+-- deleting statement, which is when NO ACTION keys are checked.
+-- ============================================
+CREATE TRIGGER IF NOT EXISTS fk_categories_delete AFTER DELETE ON categories
+BEGIN
+  UPDATE expenses SET category_id = NULL WHERE category_id = OLD.id;
+  UPDATE recurring_expenses SET category_id = NULL WHERE category_id = OLD.id;
+  DELETE FROM habit_goals WHERE category_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS fk_income_sources_delete AFTER DELETE ON income_sources
+BEGIN
+  DELETE FROM income_entries WHERE source_id = OLD.id;
+END;
+    `,
+  },
+  {
+    name: '00027_sync_conflict_strategies',
+    sql: `
+-- ============================================
+-- Sync conflict strategies (local-only)
+-- How a pulled change to a record with unsynced local edits is settled, per
+-- table. Tables without a row keep asking the user.
+-- ============================================
+CREATE TABLE IF NOT EXISTS sync_conflict_strategies (
+  table_name TEXT PRIMARY KEY,
+  strategy TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+    `,
+  },
+  {
+    name: '00028_multi_currency',
+    sql: `
+-- ============================================
+-- Multi-currency (local-only)
+-- expenses.amount stays in the base currency so budgets and totals
+-- keep adding it up as before. For spending in another currency,
+-- currency and original_amount keep what was actually paid.
+-- ============================================
+ALTER TABLE expenses ADD COLUMN currency TEXT;
+ALTER TABLE expenses ADD COLUMN original_amount REAL;
+
+CREATE TABLE IF NOT EXISTS currencies (
+  code TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  symbol TEXT NOT NULL,
+  decimals INTEGER NOT NULL DEFAULT 2,
+  is_base INTEGER NOT NULL DEFAULT 0,
+  created_at TEXT NOT NULL
+);
+
+INSERT OR IGNORE INTO currencies (code, name, symbol, decimals, is_base, created_at) VALUES
+  ('EUR', 'Euro', '€', 2, 1, strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  ('USD', 'US Dollar', '$', 2, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  ('GBP', 'British Pound', '£', 2, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  ('CHF', 'Swiss Franc', 'CHF', 2, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  ('JPY', 'Japanese Yen', '¥', 0, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  ('SEK', 'Swedish Krona', 'kr', 2, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  ('NOK', 'Norwegian Krone', 'kr', 2, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  ('DKK', 'Danish Krone', 'kr', 2, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  ('PLN', 'Polish Zloty', 'zł', 2, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  ('CZK', 'Czech Koruna', 'Kč', 2, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  ('CAD', 'Canadian Dollar', 'C$', 2, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  ('AUD', 'Australian Dollar', 'A$', 2, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+
+-- Reference rates against the euro, one row per currency and publishing day.
+-- rate is units of quote per euro.
+CREATE TABLE IF NOT EXISTS exchange_rates (
+  quote TEXT NOT NULL,
+  rate_date TEXT NOT NULL,
+  rate REAL NOT NULL,
+  fetched_at TEXT NOT NULL,
+  PRIMARY KEY (quote, rate_date)
+);
+    `,
+  },
+  {
+    name: '00029_category_budgets',
+    sql: `
+-- ============================================
+-- Category budgets (local-only)
+-- What each category may spend in a budget period, keyed like
+-- budgets.month. Where a category has an allocation it also takes the
+-- place of the standing limit in category_alert_thresholds.
+-- ============================================
+CREATE TABLE IF NOT EXISTS category_budgets (
+  id TEXT PRIMARY KEY,
+  month TEXT NOT NULL,
+  category_id TEXT NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+  amount REAL NOT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  UNIQUE (month, category_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_category_budgets_category ON category_budgets(category_id);
+    `,
+  },
+  {
+    name: '00030_attachment_cleanup',
+    sql: `
+-- ============================================
+-- Attachment cleanup (local-only)
+-- Deleting an expense marks its attachments deleted, whichever side
+-- deleted it. The files go once any remote copy is removed.
+-- ============================================
+CREATE INDEX IF NOT EXISTS idx_attachments_deleted ON attachments(deleted_at);
+
+CREATE TRIGGER IF NOT EXISTS attachments_expense_soft_deleted AFTER UPDATE OF deleted_at ON expenses
+WHEN OLD.deleted_at IS NULL AND NEW.deleted_at IS NOT NULL
+BEGIN
+  UPDATE attachments SET deleted_at = NEW.deleted_at, updated_at = NEW.deleted_at
+  WHERE expense_id = NEW.id AND deleted_at IS NULL;
+END;
+
+CREATE TRIGGER IF NOT EXISTS attachments_expense_deleted AFTER DELETE ON expenses
+BEGIN
+  UPDATE attachments
+  SET deleted_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+  WHERE expense_id = OLD.id AND deleted_at IS NULL;
+END;
+    `,
+  },
+  {
+    name: '00031_search_index',
+    sql: `
+-- ============================================
+-- Full-text search index (local-only)
+-- One row per live expense (note and category name) and per feedback
+-- note, kept current by triggers. kind is expense or feedback_note and
+-- date is the expense date or the day the note was written.
+-- ============================================
+CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+  note,
+  category,
+  kind UNINDEXED,
+  record_id UNINDEXED,
+  date UNINDEXED,
+  tokenize = 'unicode61 remove_diacritics 2',
+  prefix = '2 3'
+);
+
+INSERT INTO search_index (note, category, kind, record_id, date)
+SELECT e.note, c.name, 'expense', e.id, substr(e.date, 1, 10)
+FROM expenses e LEFT JOIN categories c ON c.id = e.category_id
+WHERE e.deleted_at IS NULL;
+
+INSERT INTO search_index (note, category, kind, record_id, date)
+SELECT content, NULL, 'feedback_note', id, substr(created_at, 1, 10)
+FROM feedback_notes
+WHERE deleted_at IS NULL;
+
+CREATE TRIGGER IF NOT EXISTS search_expenses_insert AFTER INSERT ON expenses
+WHEN NEW.deleted_at IS NULL
+BEGIN
+  INSERT INTO search_index (note, category, kind, record_id, date)
+  VALUES (NEW.note, (SELECT name FROM categories WHERE id = NEW.category_id), 'expense', NEW.id, substr(NEW.date, 1, 10));
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_expenses_update AFTER UPDATE ON expenses
+BEGIN
+  DELETE FROM search_index WHERE kind = 'expense' AND record_id = OLD.id;
+  INSERT INTO search_index (note, category, kind, record_id, date)
+  SELECT NEW.note, (SELECT name FROM categories WHERE id = NEW.category_id), 'expense', NEW.id, substr(NEW.date, 1, 10)
+  WHERE NEW.deleted_at IS NULL;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_expenses_delete AFTER DELETE ON expenses
+BEGIN
+  DELETE FROM search_index WHERE kind = 'expense' AND record_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_categories_rename AFTER UPDATE OF name ON categories
+BEGIN
+  UPDATE search_index SET category = NEW.name
+  WHERE kind = 'expense' AND record_id IN (SELECT id FROM expenses WHERE category_id = NEW.id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_feedback_insert AFTER INSERT ON feedback_notes
+WHEN NEW.deleted_at IS NULL
+BEGIN
+  INSERT INTO search_index (note, category, kind, record_id, date)
+  VALUES (NEW.content, NULL, 'feedback_note', NEW.id, substr(NEW.created_at, 1, 10));
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_feedback_update AFTER UPDATE ON feedback_notes
+BEGIN
+  DELETE FROM search_index WHERE kind = 'feedback_note' AND record_id = OLD.id;
+  INSERT INTO search_index (note, category, kind, record_id, date)
+  SELECT NEW.content, NULL, 'feedback_note', NEW.id, substr(NEW.created_at, 1, 10)
+  WHERE NEW.deleted_at IS NULL;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_feedback_delete AFTER DELETE ON feedback_notes
+BEGIN
+  DELETE FROM search_index WHERE kind = 'feedback_note' AND record_id = OLD.id;
+END;
+    `,
+  },
+  {
+    name: '00032_habit_streaks',
+    sql: `
+-- ============================================
+-- Habit streaks (local-only)
+-- Streaks per habit goal, recomputed by the backend from habit_tracking
+-- and expenses so the UI does not walk the history itself. Months count
+-- compliant calendar months, weeks compliant ISO weeks. frozen_months is
+-- a JSON array of the months a streak freeze bridged.
+-- ============================================
+CREATE TABLE IF NOT EXISTS habit_streaks (
+  habit_goal_id TEXT PRIMARY KEY REFERENCES habit_goals(id) ON DELETE CASCADE,
+  current_months INTEGER NOT NULL DEFAULT 0,
+  longest_months INTEGER NOT NULL DEFAULT 0,
+  current_weeks INTEGER NOT NULL DEFAULT 0,
+  longest_weeks INTEGER NOT NULL DEFAULT 0,
+  freezes_available INTEGER NOT NULL DEFAULT 0,
+  frozen_months TEXT NOT NULL DEFAULT '[]',
+  updated_at TEXT NOT NULL
+);
+    `,
+  },
+  {
+    name: '00033_categorization_rules',
+    sql: `
+-- ============================================
+-- Categorization Rules (local-only)
+-- Patterns that put expenses in a category when they are entered
+-- or imported without one. A rule matches the note, or the
+-- normalized merchant name, case-insensitively. Higher priority
+-- rules win.
+-- ============================================
+CREATE TABLE IF NOT EXISTS categorization_rules (
+  id TEXT PRIMARY KEY,
+  pattern TEXT NOT NULL COLLATE NOCASE,
+  match_field TEXT NOT NULL DEFAULT 'note' CHECK (match_field IN ('note', 'merchant')),
+  category_id TEXT NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+  priority INTEGER NOT NULL DEFAULT 0,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  UNIQUE (match_field, pattern)
+);
+
+CREATE INDEX IF NOT EXISTS idx_categorization_rules_category ON categorization_rules(category_id);
+    `,
+  },
+  {
+    name: '00034_merchants',
+    sql: `
+-- ============================================
+-- Merchants (local-only)
+-- One row per clean merchant name the normalizer produces from
+-- expense notes. expenses.merchant_id links an expense to its
+-- merchant and is not synced, since every device derives it from
+-- the note and its own merchant corrections.
+-- ============================================
+CREATE TABLE IF NOT EXISTS merchants (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+ALTER TABLE expenses ADD COLUMN merchant_id TEXT REFERENCES merchants(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_expenses_merchant ON expenses(merchant_id);
+    `,
+  },
+  {
+    name: '00035_detected_subscriptions',
+    sql: `
+-- ============================================
+-- Detected Subscriptions (local-only)
+-- Charges that repeat at a steady cadence and amount at one
+-- merchant, found by the background scan. Keyed by the match key
+-- of the merchant name, so a dismissed or tracked subscription
+-- keeps its status across scans. Those still new are dropped once
+-- their charges stop.
+-- ============================================
+CREATE TABLE IF NOT EXISTS detected_subscriptions (
+  match_key TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  merchant_id TEXT REFERENCES merchants(id) ON DELETE SET NULL,
+  category_id TEXT REFERENCES categories(id) ON DELETE SET NULL,
+  amount REAL NOT NULL,
+  cadence TEXT NOT NULL,
+  occurrences INTEGER NOT NULL,
+  last_date TEXT NOT NULL,
+  next_date TEXT NOT NULL,
+  confidence REAL NOT NULL,
+  status TEXT NOT NULL DEFAULT 'new' CHECK (status IN ('new', 'dismissed', 'tracked')),
+  recurring_id TEXT REFERENCES recurring_expenses(id) ON DELETE SET NULL,
+  detected_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+    `,
+  },
+  {
+    name: '00036_bills',
+    sql: `
+-- ============================================
+-- Bills (local-only)
+-- Fixed charges due on a day of every month. A due day past the
+-- end of a short month falls on its last day. reminder_id is the
+-- queued notification for the next unpaid due date. Paying a bill
+-- records the month it was for and, optionally, the expense logged
+-- for it.
+-- ============================================
+CREATE TABLE IF NOT EXISTS bills (
+  id TEXT PRIMARY KEY,
+  user_id TEXT,
+  name TEXT NOT NULL,
+  amount REAL NOT NULL,
+  due_day INTEGER NOT NULL CHECK (due_day BETWEEN 1 AND 31),
+  autopay INTEGER NOT NULL DEFAULT 0,
+  category_id TEXT,
+  remind_days_before INTEGER NOT NULL DEFAULT 3 CHECK (remind_days_before >= 0),
+  reminder_id TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  deleted_at TEXT,
+  FOREIGN KEY (category_id) REFERENCES categories(id)
+);
+
+CREATE TABLE IF NOT EXISTS bill_payments (
+  id TEXT PRIMARY KEY,
+  bill_id TEXT NOT NULL,
+  month TEXT NOT NULL,
+  amount REAL NOT NULL,
+  paid_on TEXT NOT NULL,
+  expense_id TEXT,
+  created_at TEXT NOT NULL,
+  UNIQUE (bill_id, month),
+  FOREIGN KEY (bill_id) REFERENCES bills(id) ON DELETE CASCADE,
+  FOREIGN KEY (expense_id) REFERENCES expenses(id) ON DELETE SET NULL
+);
+    `,
+  },
+  {
+    name: '00037_accounts',
+    sql: `
+-- ============================================
+-- Accounts and transfers (local-only)
+-- Where money is kept: cash, checking and savings accounts and credit
+-- cards. balance is cached from opening_balance and what was spent from,
+-- paid into and moved between accounts since opening_date, and is
+-- negative for a credit card that is owed on. Transfers move money
+-- between two accounts and are kept apart from expenses so they never
+-- count as spending.
+-- ============================================
+CREATE TABLE IF NOT EXISTS accounts (
+  id TEXT PRIMARY KEY,
+  user_id TEXT,
+  name TEXT NOT NULL,
+  kind TEXT NOT NULL CHECK (kind IN ('cash', 'checking', 'savings', 'credit_card')),
+  opening_balance REAL NOT NULL DEFAULT 0,
+  opening_date TEXT NOT NULL,
+  balance REAL NOT NULL DEFAULT 0,
+  balance_updated_at TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  deleted_at TEXT
+);
+
+CREATE TABLE IF NOT EXISTS account_transfers (
+  id TEXT PRIMARY KEY,
+  user_id TEXT,
+  from_account_id TEXT NOT NULL,
+  to_account_id TEXT NOT NULL,
+  amount REAL NOT NULL CHECK (amount > 0),
+  date TEXT NOT NULL,
+  note TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  deleted_at TEXT,
+  CHECK (from_account_id != to_account_id),
+  FOREIGN KEY (from_account_id) REFERENCES accounts(id),
+  FOREIGN KEY (to_account_id) REFERENCES accounts(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_account_transfers_from ON account_transfers(from_account_id, date);
+CREATE INDEX IF NOT EXISTS idx_account_transfers_to ON account_transfers(to_account_id, date);
+
+ALTER TABLE expenses ADD COLUMN account_id TEXT REFERENCES accounts(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_expenses_account ON expenses(account_id);
+
+ALTER TABLE income_entries ADD COLUMN account_id TEXT REFERENCES accounts(id) ON DELETE SET NULL;
+    `,
+  },
+  {
+    name: '00038_tags',
+    sql: `
+-- ============================================
+-- Tags (local-only)
+-- Free-form labels an expense can carry any number of, cutting across
+-- categories, like vacation or work-reimbursable. Names are unique
+-- ignoring case. Merging tags moves their expenses over to the one
+-- kept and drops the rest.
+-- ============================================
+CREATE TABLE IF NOT EXISTS tags (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+  color TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS expense_tags (
+  expense_id TEXT NOT NULL,
+  tag_id TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  PRIMARY KEY (expense_id, tag_id),
+  FOREIGN KEY (expense_id) REFERENCES expenses(id) ON DELETE CASCADE,
+  FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_expense_tags_tag ON expense_tags(tag_id);
+    `,
+  },
+  {
+    name: '00039_expense_splits',
+    sql: `
+-- ============================================
+-- Expense splits and settlements (local-only)
+-- An expense shared with other people has one row per person other
+-- than the user, with the share they owe. paid_by is who paid the
+-- whole expense, NULL being the user, and is the same on every row of
+-- an expense. The user share is what the other shares leave. A
+-- settlement records money paid back between two people, NULL again
+-- standing for the user.
+-- ============================================
+CREATE TABLE IF NOT EXISTS expense_splits (
+  id TEXT PRIMARY KEY,
+  expense_id TEXT NOT NULL,
+  participant TEXT NOT NULL COLLATE NOCASE,
+  share REAL NOT NULL CHECK (share >= 0),
+  paid_by TEXT COLLATE NOCASE,
+  created_at TEXT NOT NULL,
+  UNIQUE (expense_id, participant),
+  FOREIGN KEY (expense_id) REFERENCES expenses(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS split_settlements (
+  id TEXT PRIMARY KEY,
+  from_person TEXT COLLATE NOCASE,
+  to_person TEXT COLLATE NOCASE,
+  amount REAL NOT NULL CHECK (amount > 0),
+  date TEXT NOT NULL,
+  note TEXT,
+  created_at TEXT NOT NULL,
+  CHECK (from_person IS NOT NULL OR to_person IS NOT NULL)
+);
+    `,
+  },
+  {
+    name: '00040_households',
+    sql: `
+-- ============================================
+-- Households
+-- Local copies of the households the user belongs to and their
+-- members, refreshed at the start of each pull. expenses and budgets
+-- get household_id to mark a row shared with a household, which is
+-- synced. Shared rows of the other members are pulled into
+-- household_expenses and household_budgets, apart from the users own
+-- data so they never count towards the users budgets. owner_id says
+-- whose each row is.
+-- ============================================
+CREATE TABLE IF NOT EXISTS households (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  owner_id TEXT NOT NULL,
+  invite_code TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS household_members (
+  household_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  display_name TEXT,
+  role TEXT NOT NULL DEFAULT 'member',
+  joined_at TEXT,
+  PRIMARY KEY (household_id, user_id),
+  FOREIGN KEY (household_id) REFERENCES households(id) ON DELETE CASCADE
+);
+
+ALTER TABLE expenses ADD COLUMN household_id TEXT;
+
+ALTER TABLE budgets ADD COLUMN household_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_expenses_household ON expenses(household_id);
+
+CREATE TABLE IF NOT EXISTS household_expenses (
+  id TEXT PRIMARY KEY,
+  household_id TEXT NOT NULL,
+  owner_id TEXT NOT NULL,
+  amount REAL NOT NULL,
+  category_id TEXT,
+  category_name TEXT,
+  category_icon TEXT,
+  category_color TEXT,
+  note TEXT,
+  date TEXT NOT NULL,
+  created_by TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  deleted_at TEXT,
+  pulled_at TEXT NOT NULL,
+  FOREIGN KEY (household_id) REFERENCES households(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_household_expenses_date ON household_expenses(household_id, date);
+
+CREATE TABLE IF NOT EXISTS household_budgets (
+  id TEXT PRIMARY KEY,
+  household_id TEXT NOT NULL,
+  owner_id TEXT NOT NULL,
+  month TEXT NOT NULL,
+  total_amount REAL NOT NULL,
+  spending_limit REAL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  deleted_at TEXT,
+  pulled_at TEXT NOT NULL,
+  FOREIGN KEY (household_id) REFERENCES households(id) ON DELETE CASCADE
+);
+    `,
+  },
+  {
+    name: '00041_sync_encryption',
+    sql: `
+-- ============================================
+-- Sync encryption (local-only)
+-- Public half of the account sync key, present once end-to-end
+-- encryption is on for the account, whether or not this device holds
+-- the secret half yet. The push worker seals rows to it. The secret
+-- half lives in the keychain.
+-- ============================================
+CREATE TABLE IF NOT EXISTS sync_encryption (
+  id INTEGER PRIMARY KEY CHECK (id = 1),
+  recipient TEXT NOT NULL,
+  enabled_at TEXT NOT NULL
+);
+    `,
+  },
+  {
+    name: '00042_sync_settings',
+    sql: `
+-- ============================================
+-- Sync settings (local-only)
+-- Tables the user keeps out of cloud sync. Tables without a row sync.
+-- disabled_at marks when a table was turned off, so edits made since are
+-- queued once it is back on. full_pull_pending has the next pull fetch
+-- the whole table instead of what changed since the last sync.
+-- ============================================
+CREATE TABLE IF NOT EXISTS sync_settings (
+  table_name TEXT PRIMARY KEY,
+  enabled INTEGER NOT NULL DEFAULT 1,
+  disabled_at TEXT,
+  full_pull_pending INTEGER NOT NULL DEFAULT 0,
+  updated_at TEXT NOT NULL
+);
+    `,
+  },
+  {
+    name: '00043_sync_log',
+    sql: `
+-- ============================================
+-- Sync log (local-only)
+-- One row per push or pull that moved or failed anything, for the sync
+-- health screen. error holds the first error of a failed run. Only the
+-- newest 500 runs are kept.
+-- ============================================
+CREATE TABLE IF NOT EXISTS sync_log (
+  id TEXT PRIMARY KEY,
+  direction TEXT NOT NULL CHECK (direction IN ('push', 'pull')),
+  started_at TEXT NOT NULL,
+  finished_at TEXT NOT NULL,
+  pushed INTEGER NOT NULL DEFAULT 0,
+  pulled INTEGER NOT NULL DEFAULT 0,
+  failed INTEGER NOT NULL DEFAULT 0,
+  deferred INTEGER NOT NULL DEFAULT 0,
+  error TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_sync_log_finished ON sync_log(finished_at);
+
+CREATE TRIGGER IF NOT EXISTS sync_log_prune
+AFTER INSERT ON sync_log
+BEGIN
+  DELETE FROM sync_log WHERE id NOT IN (
+    SELECT id FROM sync_log ORDER BY finished_at DESC LIMIT 500
+  );
+END;
+    `,
+  },
+  {
+    name: '00044_sync_peers',
+    sql: `
+-- ============================================
+-- Sync peers (local-only)
+-- Devices paired for syncing over the local network without the cloud.
+-- id is the peer install id and secret the key shared at pairing, base64.
+-- sent_until is the local updated_at up to which rows went to the peer,
+-- received_until the peer updated_at up to which rows came from it.
+-- ============================================
+CREATE TABLE IF NOT EXISTS sync_peers (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  secret TEXT NOT NULL,
+  address TEXT,
+  sent_until TEXT,
+  received_until TEXT,
+  paired_at TEXT NOT NULL,
+  last_synced_at TEXT
+);
+    `,
+  },
+  {
+    name: '00045_webhooks',
+    sql: `
+-- ============================================
+-- Webhooks (local-only)
+-- URLs that get a signed JSON POST when one of their events happens.
+-- events is a JSON array of event names, secret the HMAC key.
+-- Deliveries are the retry queue and history of those POSTs, and event_key
+-- keeps the same occurrence from being delivered twice.
+-- ============================================
+CREATE TABLE IF NOT EXISTS webhooks (
+  id TEXT PRIMARY KEY,
+  url TEXT NOT NULL,
+  secret TEXT NOT NULL,
+  events TEXT NOT NULL,
+  enabled INTEGER NOT NULL DEFAULT 1,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+  id TEXT PRIMARY KEY,
+  webhook_id TEXT NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+  event TEXT NOT NULL,
+  event_key TEXT,
+  payload TEXT NOT NULL,
+  attempts INTEGER NOT NULL DEFAULT 0,
+  next_attempt_at TEXT NOT NULL,
+  delivered_at TEXT,
+  failed_at TEXT,
+  last_status INTEGER,
+  last_error TEXT,
+  created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due
+  ON webhook_deliveries(next_attempt_at)
+  WHERE delivered_at IS NULL AND failed_at IS NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_webhook_deliveries_event_key
+  ON webhook_deliveries(webhook_id, event_key)
+  WHERE event_key IS NOT NULL;
+    `,
+  },
+  {
+    name: '00046_bank_connections',
+    sql: `
+-- ============================================
+-- Bank connections (local-only)
+-- Open banking consents through GoCardless Bank Account Data. A
+-- connection is one requisition: pending until the user approves it at
+-- the bank, then linked with the accounts it grants until access_valid_until.
+-- Bank transactions are fetched debits waiting for the user to confirm
+-- them as expenses or dismiss them. Each is kept once per bank account by
+-- the id the bank gives it.
+-- ============================================
+CREATE TABLE IF NOT EXISTS bank_connections (
+  id TEXT PRIMARY KEY,
+  institution_id TEXT NOT NULL,
+  institution_name TEXT NOT NULL,
+  requisition_id TEXT NOT NULL UNIQUE,
+  link TEXT NOT NULL,
+  status TEXT NOT NULL DEFAULT 'pending'
+    CHECK (status IN ('pending', 'linked', 'expired', 'failed')),
+  account_ids TEXT NOT NULL DEFAULT '[]',
+  access_valid_until TEXT,
+  last_fetched_at TEXT,
+  last_error TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS bank_transactions (
+  id TEXT PRIMARY KEY,
+  connection_id TEXT NOT NULL REFERENCES bank_connections(id) ON DELETE CASCADE,
+  bank_account_id TEXT NOT NULL,
+  transaction_id TEXT NOT NULL,
+  date TEXT NOT NULL,
+  amount REAL NOT NULL,
+  currency TEXT NOT NULL,
+  description TEXT,
+  category_id TEXT,
+  status TEXT NOT NULL DEFAULT 'pending'
+    CHECK (status IN ('pending', 'confirmed', 'dismissed', 'duplicate')),
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_bank_transactions_bank_id
+  ON bank_transactions(bank_account_id, transaction_id);
+CREATE INDEX IF NOT EXISTS idx_bank_transactions_status ON bank_transactions(status, date);
+    `,
+  },
+  {
+    name: '00047_plaid',
+    sql: `
+-- ============================================
+-- Plaid (local-only)
+-- Bank connections now come from GoCardless or from Plaid, for banks in
+-- the US and Canada. A Plaid connection is one item, kept in requisition_id,
+-- and sync_cursor is where its transaction sync left off.
+-- Bank accounts are the accounts of a connection by the id the provider
+-- gives them, with the account here that confirmed transactions are
+-- paid from.
+-- ============================================
+ALTER TABLE bank_connections ADD COLUMN provider TEXT NOT NULL DEFAULT 'gocardless'
+  CHECK (provider IN ('gocardless', 'plaid'));
+ALTER TABLE bank_connections ADD COLUMN sync_cursor TEXT;
+
+CREATE TABLE IF NOT EXISTS bank_accounts (
+  id TEXT PRIMARY KEY,
+  connection_id TEXT NOT NULL REFERENCES bank_connections(id) ON DELETE CASCADE,
+  bank_account_id TEXT NOT NULL UNIQUE,
+  name TEXT NOT NULL,
+  mask TEXT,
+  account_id TEXT REFERENCES accounts(id) ON DELETE SET NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_bank_accounts_connection ON bank_accounts(connection_id);
+    `,
+  },
+  {
+    name: '00048_bulk_undo',
+    sql: `
+-- ============================================
+-- Bulk undo (local-only)
+-- What a bulk change to expenses replaced, kept under its undo token
+-- for a day. snapshot is JSON with the expenses as they were and, for
+-- tagging, the expenses the tag was added to or taken off.
+-- ============================================
+CREATE TABLE IF NOT EXISTS bulk_undo (
+  token TEXT PRIMARY KEY,
+  operation TEXT NOT NULL,
+  snapshot TEXT NOT NULL,
+  created_at TEXT NOT NULL
 );
     `,
   },
@@ -309,6 +1727,15 @@ async function markMigrationApplied(
   );
 }
 
+/**
+ * True while `current` is a CREATE TRIGGER whose BEGIN ... END block is not
+ * closed yet, so the semicolons between its statements don't end it.
+ */
+function inTriggerBody(current: string): boolean {
+  const head = current.replace(/^(\s*--[^\n]*\n)*\s*/, '').toUpperCase();
+  return head.startsWith('CREATE TRIGGER') && !/\bEND\s*$/.test(head);
+}
+
 /**
  * Split SQL into individual statements, handling semicolons inside strings.
  * Also strips leading comment lines from each statement.
@@ -333,8 +1760,8 @@ function splitStatements(sql: string): string[] {
       }
     }
 
-    // Split on semicolons outside strings
-    if (char === ';' && !inString) {
+    // Split on semicolons outside strings, except inside a trigger body
+    if (char === ';' && !inString && !inTriggerBody(current)) {
       const stmt = current.trim();
       if (stmt) {
         statements.push(stmt);
@@ -373,15 +1800,17 @@ function splitStatements(sql: string): string[] {
  *
  * For Tauri (SQLite): Executes SQL statements to create tables and insert data.
  * For Browser (sql.js): Same as Tauri - executes all SQL statements since sql.js is real SQLite.
+ *
+ * Pass `target` to migrate another database, e.g. one staged from an archive import.
  */
-export async function runMigrations(): Promise<{
+export async function runMigrations(target?: DatabaseInterface): Promise<{
   applied: string[];
   errors: string[];
 }> {
   const result = { applied: [] as string[], errors: [] as string[] };
 
   try {
-    const db = await getMigrationDatabase();
+    const db = target ?? await getMigrationDatabase();
 
     // Ensure _migrations table exists (both Tauri SQLite and browser sql.js)
     await db.execute(`
@@ -0,0 +1,64 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * Net worth from dated account balances. Manual balances are entered by the
+ * user; derived ones (money saved towards goals) are kept up to date by the
+ * backend.
+ */
+
+export type AccountKind = 'asset' | 'liability';
+
+export interface AccountBalance {
+  id: string;
+  account_key: string;
+  account_name: string;
+  kind: AccountKind;
+  // Liabilities are positive amounts
+  balance: number;
+  as_of: string;
+  source: 'manual' | 'derived';
+  updated_at: string;
+}
+
+export interface NetWorthPoint {
+  date: string;
+  assets: number;
+  liabilities: number;
+  net_worth: number;
+}
+
+export async function recordAccountBalance(
+  accountName: string,
+  kind: AccountKind,
+  balance: number,
+  asOf?: string
+): Promise<void> {
+  if (!isTauri()) {
+    throw new Error('Net worth tracking is only available in the desktop and mobile apps');
+  }
+  const { commands } = await import('./bindings');
+  await unwrap(
+    commands.recordAccountBalance({
+      account_name: accountName,
+      kind,
+      balance,
+      as_of: asOf ?? null,
+    })
+  );
+}
+
+export async function getAccountBalances(): Promise<AccountBalance[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getAccountBalances()) as Promise<AccountBalance[]>;
+}
+
+/**
+ * Month-end net worth for the last `months` months, oldest first.
+ */
+export async function getNetWorthHistory(months = 12): Promise<NetWorthPoint[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getNetWorthHistory(months));
+}
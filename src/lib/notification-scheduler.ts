@@ -1,3 +1,4 @@
+import { unwrap } from './commands';
 import { getAllHabitGoalsWithStats, getAllSavingsGoalsWithStats, getHabitGoalsNeedingAlert } from './database';
 import {
     cancelNotificationsByType,
@@ -9,6 +10,7 @@ import {
     showNotification,
     type NotificationPreferences
 } from './notifications';
+import { isTauri } from './platform';
 import type { HabitGoalWithStats, SavingsGoalWithStats } from './types';
 
 // Background notification checker state
@@ -202,6 +204,13 @@ function generateProgressMessage(goal: SavingsGoalWithStats, totalGoals: number)
  * Always cancels existing and reschedules to pick up any cron changes.
  */
 export async function rescheduleAllNotifications(): Promise<void> {
+  // The backend schedules from the same preferences
+  if (isTauri()) {
+    const { commands } = await import('./bindings');
+    await unwrap(commands.rescheduleNotifications());
+    return;
+  }
+
   const prefs = await getNotificationPreferences();
 
   if (!prefs.notifications_enabled) {
@@ -1,4 +1,6 @@
+import type { NotificationPreferencesUpdate } from './bindings';
 import { getBrowserDatabase } from './browser-database';
+import { unwrap } from './commands';
 import { getNextExecutionTime } from './cron';
 import { isTauri } from './platform';
 import { generateId } from './types';
@@ -10,7 +12,7 @@ interface DatabaseInterface {
 }
 
 // Notification types
-export type NotificationType = 'monthly_checkin' | 'progress_update' | 'why_reminder' | 'habit_alert' | 'habit_milestone';
+export type NotificationType = 'monthly_checkin' | 'progress_update' | 'why_reminder' | 'habit_alert' | 'habit_milestone' | 'price_increase' | 'trial_ending' | 'checkin_reminder' | 'checkin_slipped';
 
 // Default cron expressions
 export const DEFAULT_MONTHLY_CRON = '0 9 2 * *';     // 2nd of month at 09:00
@@ -123,6 +125,19 @@ async function queueChange(tableName: string, recordId: string, operation: 'inse
   return _queueChange(tableName, recordId, operation, payload);
 }
 
+/**
+ * Ask the backend to hand the next pending notifications to the OS again,
+ * so they fire on mobile even when the app is closed. Call after anything
+ * that changes the queue. Failures are only logged; an hourly job
+ * re-registers anyway.
+ */
+export function registerPlatformNotifications(): void {
+  if (!isTauri()) return;
+  import('./bindings')
+    .then(({ commands }) => unwrap(commands.registerPlatformNotifications()))
+    .catch((error) => console.error('[Notifications] Platform registration failed:', error));
+}
+
 export type PermissionStatus = 'granted' | 'denied' | 'unavailable';
 
 /**
@@ -244,6 +259,7 @@ export async function scheduleNotification(
      VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)`,
     [id, userId, type, goalId || null, title, body, nextExecution.toISOString(), cronExpression, nowStr, nowStr]
   );
+  registerPlatformNotifications();
 
   return id;
 }
@@ -257,6 +273,7 @@ export async function cancelNotificationsByType(type: NotificationType): Promise
     `DELETE FROM scheduled_notifications WHERE notification_type = $1 AND sent_at IS NULL`,
     [type]
   );
+  registerPlatformNotifications();
   console.log(`[Notifications] Cancelled all ${type} notifications`);
 }
 
@@ -315,12 +332,33 @@ export async function getNotificationPreferences(): Promise<NotificationPreferen
   };
 }
 
+// Fields left null keep their saved value
+const NO_CHANGES: NotificationPreferencesUpdate = {
+  notifications_enabled: null,
+  monthly_checkin_enabled: null,
+  monthly_checkin_cron: null,
+  progress_updates_enabled: null,
+  progress_updates_cron: null,
+  why_reminders_enabled: null,
+  why_reminders_cron: null,
+  quiet_hours_enabled: null,
+  quiet_hours_start: null,
+  quiet_hours_end: null,
+  timezone: null,
+};
+
 /**
  * Save notification preferences.
  */
 export async function saveNotificationPreferences(
   prefs: Partial<Omit<NotificationPreferences, 'id' | 'created_at' | 'updated_at'>>
 ): Promise<NotificationPreferences> {
+  if (isTauri()) {
+    // The backend upserts the single row under a write lock and queues it for sync
+    const { commands } = await import('./bindings');
+    return unwrap(commands.updateNotificationPreferences({ ...NO_CHANGES, ...prefs }));
+  }
+
   const db = await getNotificationDatabase();
   const userId = await getCurrentUserId();
   const now = new Date().toISOString();
@@ -441,9 +479,28 @@ export function isWithinQuietHours(prefs: NotificationPreferences): boolean {
 }
 
 /**
- * Check for due notifications and send them.
+ * Whether a monthly check-in went out at or after `since`.
+ */
+async function monthlyCheckInSentSince(db: DatabaseInterface, since: Date): Promise<boolean> {
+  const rows = await db.select<{ count: number }[]>(
+    `SELECT COUNT(*) as count FROM scheduled_notifications
+     WHERE notification_type = 'monthly_checkin' AND sent_at >= $1`,
+    [since.toISOString()]
+  );
+  return (rows[0]?.count ?? 0) > 0;
+}
+
+/**
+ * Check for due notifications and send them. In Tauri the backend's
+ * scheduler delivers them, and is only nudged from here.
  */
 export async function checkAndSendDueNotifications(): Promise<void> {
+  if (isTauri()) {
+    const { commands } = await import('./bindings');
+    await unwrap(commands.dispatchDueNotifications());
+    return;
+  }
+
   const db = await getNotificationDatabase();
   const now = new Date();
   const nowStr = now.toISOString();
@@ -460,10 +517,15 @@ export async function checkAndSendDueNotifications(): Promise<void> {
     return;
   }
 
-  const dueNotifications = await db.select<ScheduledNotification[]>(
-    `SELECT * FROM scheduled_notifications
-     WHERE scheduled_at <= $1 AND sent_at IS NULL
-     ORDER BY scheduled_at ASC`,
+  // Rows the OS was holding have already been shown by it
+  const dueNotifications = await db.select<(ScheduledNotification & { delivered_by_os: number })[]>(
+    `SELECT n.*, EXISTS (
+       SELECT 1 FROM platform_notifications p
+       WHERE p.notification_id = n.id AND p.scheduled_at = n.scheduled_at
+     ) AS delivered_by_os
+     FROM scheduled_notifications n
+     WHERE n.scheduled_at <= $1 AND n.sent_at IS NULL
+     ORDER BY n.scheduled_at ASC`,
     [nowStr]
   );
 
@@ -473,7 +535,13 @@ export async function checkAndSendDueNotifications(): Promise<void> {
     console.log(`[Notifications] Processing: "${notification.title}" (scheduled: ${notification.scheduled_at})`);
 
     try {
-      await showNotification(notification.title, notification.body);
+      // The month-end close in the backend may have asked already
+      const duplicate = Boolean(notification.delivered_by_os)
+        || (notification.notification_type === 'monthly_checkin'
+          && await monthlyCheckInSentSince(db, new Date(now.getFullYear(), now.getMonth(), 1)));
+      if (!duplicate) {
+        await showNotification(notification.title, notification.body);
+      }
 
       await db.execute(
         `UPDATE scheduled_notifications SET sent_at = $1 WHERE id = $2`,
@@ -507,6 +575,11 @@ export async function checkAndSendDueNotifications(): Promise<void> {
       console.error(`Failed to send notification ${notification.id}:`, error);
     }
   }
+
+  // Recurring ones were rescheduled, and the OS window moves on
+  if (dueNotifications.length > 0) {
+    registerPlatformNotifications();
+  }
 }
 
 /**
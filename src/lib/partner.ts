@@ -0,0 +1,160 @@
+import { getCurrentUserId } from './auth';
+import { getDatabase } from './database';
+import { generateInviteCode, getAuthedSupabase } from './sharing';
+import type { Partnership } from './types';
+import { generateId } from './types';
+
+/**
+ * Partner mode: two accounts sharing one budget.
+ *
+ * The owner's budgets and expenses are the shared ledger. Once the partner
+ * accepts the invite, both apps sync budgets and expenses into that ledger
+ * (user_id = owner) and record who entered each row in created_by.
+ */
+
+/**
+ * Get the local copy of the partnership, if any.
+ */
+export async function getPartnership(): Promise<Partnership | null> {
+  const db = await getDatabase();
+  const result = await db.select<Partnership[]>('SELECT * FROM partnership LIMIT 1');
+  return result[0] || null;
+}
+
+async function saveLocalPartnership(remote: Record<string, unknown>, partnerName: string | null): Promise<Partnership> {
+  const db = await getDatabase();
+  const now = new Date().toISOString();
+  const partnership: Partnership = {
+    id: remote.id as string,
+    owner_id: remote.owner_id as string,
+    partner_id: (remote.partner_id as string | null) ?? null,
+    invite_code: remote.invite_code as string,
+    partner_name: partnerName,
+    created_at: (remote.created_at as string) ?? now,
+    updated_at: now,
+  };
+
+  await db.execute('DELETE FROM partnership');
+  await db.execute(
+    `INSERT INTO partnership (id, owner_id, partner_id, invite_code, partner_name, created_at, updated_at)
+     VALUES ($1, $2, $3, $4, $5, $6, $7)`,
+    [
+      partnership.id,
+      partnership.owner_id,
+      partnership.partner_id,
+      partnership.invite_code,
+      partnership.partner_name,
+      partnership.created_at,
+      partnership.updated_at,
+    ]
+  );
+  return partnership;
+}
+
+/**
+ * The user whose budgets and expenses the current user syncs with:
+ * the owner while partner mode is active, otherwise the user themselves.
+ */
+export async function getLedgerOwnerId(): Promise<string | null> {
+  const userId = await getCurrentUserId();
+  const partnership = await getPartnership();
+  if (partnership?.partner_id && (partnership.partner_id === userId || partnership.owner_id === userId)) {
+    return partnership.owner_id;
+  }
+  return userId;
+}
+
+/**
+ * Start partner mode: share this account's budget under a new invite code.
+ */
+export async function createPartnerInvite(): Promise<Partnership> {
+  const supabase = await getAuthedSupabase();
+  const userId = await getCurrentUserId();
+  const existing = await getPartnership();
+  if (existing) {
+    return existing;
+  }
+
+  const now = new Date().toISOString();
+  const { data, error } = await supabase
+    .from('partnerships')
+    .insert({
+      id: generateId(),
+      owner_id: userId,
+      invite_code: generateInviteCode(),
+      created_at: now,
+      updated_at: now,
+    })
+    .select()
+    .single();
+
+  if (error) throw new Error(error.message);
+  return saveLocalPartnership(data, null);
+}
+
+/**
+ * Join someone else's budget with the code they shared.
+ */
+export async function acceptPartnerInvite(code: string): Promise<Partnership> {
+  const supabase = await getAuthedSupabase();
+  const { data, error } = await supabase.rpc('accept_partner_invite', {
+    code: code.trim().toUpperCase(),
+  });
+
+  if (error) throw new Error(error.message);
+  return saveLocalPartnership(data, await fetchDisplayName(data.owner_id));
+}
+
+async function fetchDisplayName(userId: string | null): Promise<string | null> {
+  if (!userId) return null;
+  const supabase = await getAuthedSupabase();
+  const { data } = await supabase
+    .from('profiles')
+    .select('display_name, email')
+    .eq('id', userId)
+    .maybeSingle();
+  return data?.display_name || data?.email || null;
+}
+
+/**
+ * Refresh the local partnership from Supabase, e.g. to notice that the
+ * partner joined or left. Called at the start of each sync pull.
+ */
+export async function refreshPartnership(): Promise<Partnership | null> {
+  const local = await getPartnership();
+  if (!local) return null;
+
+  const supabase = await getAuthedSupabase();
+  const { data, error } = await supabase
+    .from('partnerships')
+    .select('*')
+    .eq('id', local.id)
+    .maybeSingle();
+  if (error) throw new Error(error.message);
+
+  if (!data || data.deleted_at) {
+    const db = await getDatabase();
+    await db.execute('DELETE FROM partnership');
+    return null;
+  }
+
+  const userId = await getCurrentUserId();
+  const otherId = data.owner_id === userId ? data.partner_id : data.owner_id;
+  const partnerName = local.partner_name ?? await fetchDisplayName(otherId);
+  return saveLocalPartnership(data, partnerName);
+}
+
+/**
+ * End partner mode for both members. Rows already synced stay where they are.
+ */
+export async function leavePartnership(): Promise<void> {
+  const local = await getPartnership();
+  if (!local) return;
+
+  const supabase = await getAuthedSupabase();
+  const { error } = await supabase.rpc('leave_partnership', { partnership_id: local.id });
+  if (error) throw new Error(error.message);
+
+  const db = await getDatabase();
+  await db.execute('DELETE FROM partnership');
+}
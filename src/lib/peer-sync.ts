@@ -0,0 +1,121 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * Syncing with another device on the same network, without a cloud
+ * account. Devices pair once by scanning a code; after that either one can
+ * exchange changes with the other while both have peer sync on.
+ */
+
+export interface PeerStatus {
+  running: boolean;
+  device_id: string;
+  device_name: string | null;
+  // Where this device is served, while running
+  address: string | null;
+}
+
+export interface PairingOffer {
+  payload: string;
+  qr_svg: string;
+  expires_at: string;
+}
+
+export interface Peer {
+  id: string;
+  name: string;
+  address: string | null;
+  paired_at: string;
+  last_synced_at: string | null;
+}
+
+export interface DiscoveredPeer {
+  id: string;
+  name: string;
+  address: string;
+  paired: boolean;
+}
+
+export interface PeerSyncReport {
+  sent: number;
+  // Rows from the peer that were newer than ours
+  applied: number;
+}
+
+function assertTauri(): void {
+  if (!isTauri()) {
+    throw new Error('Peer sync is only available in the desktop and mobile apps');
+  }
+}
+
+export async function getPeerSyncStatus(): Promise<PeerStatus | null> {
+  if (!isTauri()) return null;
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getPeerSyncStatus());
+}
+
+export async function startPeerSync(deviceName: string): Promise<PeerStatus> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.startPeerSync(deviceName));
+}
+
+export async function stopPeerSync(): Promise<PeerStatus> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.stopPeerSync());
+}
+
+/**
+ * Show a code for another device to scan. Needs peer sync on; the code
+ * pairs one device and expires after ten minutes.
+ */
+export async function offerPeerPairing(): Promise<PairingOffer> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.offerPeerPairing());
+}
+
+export async function pairWithPeer(payload: string): Promise<Peer> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.pairWithPeer(payload));
+}
+
+export async function getSyncPeers(): Promise<Peer[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getSyncPeers());
+}
+
+export async function removeSyncPeer(id: string): Promise<void> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  await unwrap(commands.removeSyncPeer(id));
+}
+
+/**
+ * Look for devices with peer sync on for a few seconds. Paired ones found
+ * get their address updated for `syncWithPeer`.
+ */
+export async function discoverSyncPeers(): Promise<DiscoveredPeer[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.discoverSyncPeers());
+}
+
+export async function syncWithPeer(id: string): Promise<PeerSyncReport> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.syncWithPeer(id));
+}
+
+/**
+ * Follow devices pairing with this one through a shown code. Returns a
+ * function that stops listening.
+ */
+export async function onPeerPaired(handler: (peer: Peer) => void): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  return listen<Peer>('peer://paired', (event) => handler(event.payload));
+}
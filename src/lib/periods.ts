@@ -0,0 +1,65 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+import { getCurrentMonth } from './types';
+
+/**
+ * Budget periods. The backend resolves them from the pay schedule; the
+ * browser build has no schedule and always uses the calendar month.
+ */
+
+export type PayFrequency = 'monthly' | 'semimonthly' | 'biweekly' | 'weekly';
+
+export interface PaySchedule {
+  frequency: PayFrequency;
+  // A known payday (YYYY-MM-DD)
+  anchor_date: string | null;
+}
+
+export interface BudgetPeriod {
+  // "YYYY-MM" for calendar months, otherwise the first day
+  key: string;
+  start: string;
+  // Last day, inclusive
+  end: string;
+}
+
+function calendarMonth(): BudgetPeriod {
+  const now = new Date();
+  const key = getCurrentMonth();
+  const lastDay = new Date(now.getFullYear(), now.getMonth() + 1, 0).getDate();
+  return { key, start: `${key}-01`, end: `${key}-${String(lastDay).padStart(2, '0')}` };
+}
+
+/**
+ * The budget period today falls in.
+ */
+export async function getCurrentPeriod(): Promise<BudgetPeriod> {
+  if (!isTauri()) return calendarMonth();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getBudgetPeriod(null));
+}
+
+export async function getPaySchedule(): Promise<PaySchedule> {
+  if (!isTauri()) return { frequency: 'monthly', anchor_date: null };
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getPaySchedule());
+}
+
+export async function savePaySchedule(schedule: PaySchedule): Promise<void> {
+  if (!isTauri()) {
+    throw new Error('Pay schedules are only available in the desktop and mobile apps');
+  }
+  const { commands } = await import('./bindings');
+  await unwrap(commands.savePaySchedule(schedule));
+}
+
+/**
+ * Days left in `period`, counting today.
+ */
+export function daysLeft(period: BudgetPeriod): number {
+  const today = new Date();
+  today.setHours(0, 0, 0, 0);
+  const [y, m, d] = period.end.split('-').map(Number);
+  const end = new Date(y, m - 1, d);
+  return Math.max(0, Math.round((end.getTime() - today.getTime()) / 86_400_000) + 1);
+}
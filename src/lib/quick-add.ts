@@ -0,0 +1,118 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+import type { Expense } from './types';
+
+/**
+ * Quick expense entry from outside the app: `goaldy://add?amount=12.50&category=groceries`
+ * links and text shared from other apps ("12.50 coffee"). Both end up in the
+ * expense form pre-filled, unless auto-save is on and the amount and category
+ * are known.
+ */
+
+export interface ExpenseDraft {
+  amount: number | null;
+  category_id: string | null;
+  note: string | null;
+  date: string;
+}
+
+function assertTauri(): void {
+  if (!isTauri()) {
+    throw new Error('Quick add is only available in the desktop and mobile apps');
+  }
+}
+
+export async function getQuickAddAutoSave(): Promise<boolean> {
+  if (!isTauri()) return false;
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getQuickAddAutoSave());
+}
+
+export async function setQuickAddAutoSave(autoSave: boolean): Promise<void> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  await unwrap(commands.setQuickAddAutoSave(autoSave));
+}
+
+/**
+ * The draft of the link the app was opened with, once. Call on startup,
+ * then follow onQuickAddDraft.
+ */
+export async function takeQuickAddDraft(): Promise<ExpenseDraft | null> {
+  if (!isTauri()) return null;
+  const { commands } = await import('./bindings');
+  return commands.takeQuickAddDraft();
+}
+
+/**
+ * Handle text shared from another app. Resolves to the expense if it was
+ * saved; otherwise its draft arrives through onQuickAddDraft.
+ */
+export async function quickAddSharedText(text: string): Promise<Expense | null> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.quickAddSharedText(text));
+}
+
+/**
+ * Follow drafts to open in the expense form. Returns a function that stops
+ * listening.
+ */
+export async function onQuickAddDraft(handler: (draft: ExpenseDraft) => void): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  return listen<ExpenseDraft>('quick-add://draft', (event) => handler(event.payload));
+}
+
+/**
+ * Save an expense from the quick-add window, which then hides.
+ */
+export async function quickAddExpense(amount: number, categoryId: string | null, note?: string): Promise<Expense> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(
+    commands.quickAddExpense({
+      amount,
+      category_id: categoryId,
+      note: note ?? null,
+      date: null,
+      payment_method: null,
+      currency: null,
+      account_id: null,
+    })
+  );
+}
+
+export async function openQuickAddWindow(): Promise<void> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  await unwrap(commands.openQuickAddWindow());
+}
+
+export async function closeQuickAddWindow(): Promise<void> {
+  if (!isTauri()) return;
+  const { commands } = await import('./bindings');
+  await unwrap(commands.closeQuickAddWindow());
+}
+
+/** The desktop shortcut that opens the quick-add window, e.g. "CommandOrControl+Shift+E". */
+export async function getQuickAddShortcut(): Promise<string | null> {
+  if (!isTauri()) return null;
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getQuickAddShortcut());
+}
+
+/**
+ * Change the shortcut. Fails if it isn't a valid combination or another app
+ * already uses it; the old one then stays.
+ */
+export async function setQuickAddShortcut(shortcut: string): Promise<string> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.setQuickAddShortcut(shortcut));
+}
+
+/** Whether this page is the quick-add window rather than the main app. */
+export function isQuickAddWindow(): boolean {
+  return new URLSearchParams(window.location.search).get('window') === 'quick-add';
+}
@@ -0,0 +1,26 @@
+import type { ReceiptDraft } from './bindings';
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * Receipt scanning: on-device OCR of a receipt photo, read into an expense
+ * draft with the total, date and merchant. Each field comes with a
+ * confidence so the form can point out what to check. Builds without the
+ * `ocr` feature reject with an unsupported error.
+ */
+
+export type { ReceiptConfidence, ReceiptDraft } from './bindings';
+
+function assertTauri(): void {
+  if (!isTauri()) {
+    throw new Error('Receipt scanning is only available in the desktop and mobile apps');
+  }
+}
+
+/** Read a receipt photo (PNG, JPEG, ...) into a pre-filled expense draft. */
+export async function scanReceipt(image: Uint8Array | ArrayBuffer): Promise<ReceiptDraft> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  const bytes = image instanceof Uint8Array ? image : new Uint8Array(image);
+  return unwrap(commands.scanReceipt(Array.from(bytes)));
+}
@@ -0,0 +1,29 @@
+import { getFullSession } from './auth';
+import { getSupabase } from './supabase';
+
+/**
+ * Pieces shared by partner mode and households: invite codes and a
+ * Supabase client signed in as the current user.
+ */
+
+const INVITE_CODE_LENGTH = 8;
+// No 0/O or 1/I, the code is typed in by hand
+const INVITE_CODE_ALPHABET = 'ABCDEFGHJKLMNPQRSTUVWXYZ23456789';
+
+export function generateInviteCode(): string {
+  const bytes = crypto.getRandomValues(new Uint8Array(INVITE_CODE_LENGTH));
+  return Array.from(bytes, b => INVITE_CODE_ALPHABET[b % INVITE_CODE_ALPHABET.length]).join('');
+}
+
+export async function getAuthedSupabase() {
+  const supabase = getSupabase();
+  const session = await getFullSession();
+  if (!supabase || !session) {
+    throw new Error('Sharing needs a signed-in, synced account');
+  }
+  await supabase.auth.setSession({
+    access_token: session.accessToken,
+    refresh_token: session.refreshToken,
+  });
+  return supabase;
+}
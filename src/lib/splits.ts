@@ -0,0 +1,123 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * Expenses shared with other people, and settling up. A person of null
+ * stands for the user throughout.
+ */
+
+export type SplitMethod = 'equal' | 'amounts' | 'percentages';
+
+export interface SplitParticipant {
+  name: string;
+  // Amount or percentage, per the method; ignored for equal splits
+  value?: number | null;
+}
+
+export interface NewSplit {
+  // Who paid the whole expense; null for the user
+  paid_by?: string | null;
+  method: SplitMethod;
+  // Everyone other than the user
+  participants: SplitParticipant[];
+}
+
+export interface ExpenseSplit {
+  expense_id: string;
+  amount: number;
+  paid_by: string | null;
+  shares: { participant: string; share: number }[];
+  my_share: number;
+}
+
+export interface SettleUp {
+  // Positive net when owed money, negative when owing
+  balances: { person: string | null; net: number }[];
+  debts: { from: string | null; to: string | null; amount: number }[];
+}
+
+export interface Settlement {
+  id: string;
+  from_person: string | null;
+  to_person: string | null;
+  amount: number;
+  date: string;
+  note: string | null;
+  created_at: string;
+}
+
+function assertTauri(): void {
+  if (!isTauri()) {
+    throw new Error('Splitting expenses is only available in the desktop and mobile apps');
+  }
+}
+
+export async function getExpenseSplit(expenseId: string): Promise<ExpenseSplit | null> {
+  if (!isTauri()) return null;
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getExpenseSplit(expenseId));
+}
+
+export async function splitExpense(expenseId: string, input: NewSplit): Promise<ExpenseSplit> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  const split = {
+    paid_by: null,
+    ...input,
+    participants: input.participants.map((participant) => ({ value: null, ...participant })),
+  };
+  return unwrap(commands.splitExpense(expenseId, split));
+}
+
+export async function unsplitExpense(expenseId: string): Promise<void> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  await unwrap(commands.unsplitExpense(expenseId));
+}
+
+export async function getSplitPeople(): Promise<string[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getSplitPeople());
+}
+
+export async function getSettleUp(): Promise<SettleUp> {
+  if (!isTauri()) return { balances: [], debts: [] };
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getSettleUp());
+}
+
+export async function getSettlements(): Promise<Settlement[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getSettlements());
+}
+
+/**
+ * Record that one person paid another back.
+ */
+export async function recordSettlement(
+  fromPerson: string | null,
+  toPerson: string | null,
+  amount: number,
+  date?: string,
+  note?: string
+): Promise<Settlement> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(
+    commands.recordSettlement({
+      from_person: fromPerson,
+      to_person: toPerson,
+      amount,
+      date: date ?? null,
+      note: note ?? null,
+    })
+  );
+}
+
+export async function deleteSettlement(id: string): Promise<void> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  await unwrap(commands.deleteSettlement(id));
+}
@@ -1,5 +1,7 @@
 import { createClient, SupabaseClient } from '@supabase/supabase-js';
+import { unwrap } from './commands';
 import { SUPABASE_URL, SUPABASE_ANON_KEY, validateSupabaseConfig } from './supabase-config';
+import { isTauri } from './platform';
 
 let supabaseClient: SupabaseClient | null = null;
 
@@ -31,3 +33,13 @@ export function getSupabase(): SupabaseClient | null {
 export function isSupabaseConfigured(): boolean {
   return validateSupabaseConfig();
 }
+
+/**
+ * Hand the project URL and anon key to the Rust backend, which syncs
+ * attachment files with Supabase Storage on its own.
+ */
+export async function configureBackend(): Promise<void> {
+  if (!isTauri() || !validateSupabaseConfig()) return;
+  const { commands } = await import('./bindings');
+  await unwrap(commands.configureBackend({ url: SUPABASE_URL, anon_key: SUPABASE_ANON_KEY }));
+}
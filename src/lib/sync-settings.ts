@@ -0,0 +1,107 @@
+import { unwrap } from './commands';
+import type { NetworkType } from './connectivity';
+import { getDatabase } from './database';
+import { isTauri } from './platform';
+
+/**
+ * Which tables take part in cloud sync, and on which connections. Turning
+ * tables on and off goes through the app, which checks foreign keys and
+ * catches up on what was missed; the sync engine only reads the settings.
+ */
+
+export interface SyncPolicy {
+  wifi_only: boolean;
+  network_type: NetworkType;
+  metered: boolean;
+  // Whether records sync on the current connection
+  allowed: boolean;
+}
+
+export interface TableSyncSetting {
+  table_name: string;
+  enabled: boolean;
+}
+
+function assertTauri(): void {
+  if (!isTauri()) {
+    throw new Error('Sync settings are only available in the desktop and mobile apps');
+  }
+}
+
+export async function getSyncSettings(): Promise<TableSyncSetting[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getSyncSettings());
+}
+
+/**
+ * Keep a table out of sync or bring it back. Fails while a synced table
+ * references it, or when turning on a table whose parent is off.
+ */
+export async function setTableSync(tableName: string, enabled: boolean): Promise<TableSyncSetting[]> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.setTableSync(tableName, enabled));
+}
+
+export async function getSyncPolicy(): Promise<SyncPolicy | null> {
+  if (!isTauri()) return null;
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getSyncPolicy());
+}
+
+/**
+ * Keep sync to Wi-Fi and other unmetered connections. Changes made on
+ * cellular data stay queued until then.
+ */
+export async function setSyncWifiOnly(wifiOnly: boolean): Promise<SyncPolicy> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.setSyncWifiOnly(wifiOnly));
+}
+
+/**
+ * Whether the sync policy lets sync use the current connection. The
+ * browser has no policy.
+ */
+export async function isSyncAllowed(): Promise<boolean> {
+  return (await getSyncPolicy())?.allowed ?? true;
+}
+
+export async function isTableSynced(tableName: string): Promise<boolean> {
+  const db = await getDatabase();
+  const rows = await db.select<{ enabled: number }[]>(
+    'SELECT enabled FROM sync_settings WHERE table_name = $1',
+    [tableName]
+  );
+  return rows.length === 0 || rows[0].enabled === 1;
+}
+
+export async function getExcludedSyncTables(): Promise<Set<string>> {
+  const db = await getDatabase();
+  const rows = await db.select<{ table_name: string }[]>(
+    'SELECT table_name FROM sync_settings WHERE enabled = 0'
+  );
+  return new Set(rows.map(row => row.table_name));
+}
+
+/**
+ * Tables turned back on since the last pull, which are pulled in full.
+ */
+export async function getFullPullTables(): Promise<Set<string>> {
+  const db = await getDatabase();
+  const rows = await db.select<{ table_name: string }[]>(
+    'SELECT table_name FROM sync_settings WHERE enabled = 1 AND full_pull_pending = 1'
+  );
+  return new Set(rows.map(row => row.table_name));
+}
+
+export async function clearFullPulls(tables: Set<string>): Promise<void> {
+  const db = await getDatabase();
+  for (const table of tables) {
+    await db.execute(
+      'UPDATE sync_settings SET full_pull_pending = 0 WHERE table_name = $1',
+      [table]
+    );
+  }
+}
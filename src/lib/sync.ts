@@ -1,6 +1,17 @@
 import { getCurrentUserId, getFullSession, getLocalAuthState, updateLastSyncAt } from './auth';
+import type { JsonValue } from './bindings';
+import { refreshCategoryTotals } from './category-totals';
+import { unwrap } from './commands';
+import { isConnected } from './connectivity';
 import { getDatabase } from './database';
+import { openRemoteRows, refreshSyncEncryption } from './e2ee';
+import { publishDomainEvent } from './events';
+import { pullHouseholdRecords, refreshHouseholds } from './household';
+import { registerPlatformNotifications } from './notifications';
+import { getLedgerOwnerId, refreshPartnership } from './partner';
+import { isTauri } from './platform';
 import { getSupabase, isSupabaseConfigured } from './supabase';
+import { clearFullPulls, getExcludedSyncTables, getFullPullTables, isSyncAllowed, isTableSynced } from './sync-settings';
 import type { Budget, Category, Expense, FeedbackNote, HabitGoal, HabitTracking, SavingsContribution, SavingsGoal, SyncOperation, SyncQueueItem, SyncResult, SyncStatus } from './types';
 import { generateId } from './types';
 
@@ -44,7 +55,7 @@ const MAX_RETRY_ATTEMPTS = 5;
  * Check if the app is online.
  */
 export function isOnline(): boolean {
-  return navigator.onLine;
+  return isConnected();
 }
 
 /**
@@ -56,21 +67,42 @@ export async function getSyncStatus(): Promise<SyncStatus> {
 
   // Count pending sync items
   const pendingResult = await db.select<{ count: number }[]>(
-    `SELECT COUNT(*) as count FROM sync_queue WHERE user_id = $1 AND attempts < $2`,
-    [userId, MAX_RETRY_ATTEMPTS]
+    `SELECT COUNT(*) as count FROM sync_queue WHERE user_id = $1 AND dead_lettered_at IS NULL`,
+    [userId]
   );
 
   const authState = await getLocalAuthState();
+  const lastRun = await db.select<{ error: string | null }[]>(
+    'SELECT error FROM sync_log ORDER BY finished_at DESC LIMIT 1'
+  );
 
   return {
     isOnline: isOnline(),
-    isSyncing: false, // This would be managed by a global state
+    isSyncing: activeRuns > 0,
     lastSyncAt: authState?.last_sync_at || null,
     pendingChanges: pendingResult[0]?.count || 0,
-    error: null,
+    error: lastRun[0]?.error ?? null,
   };
 }
 
+// Pulls and browser pushes in progress
+let activeRuns = 0;
+
+/**
+ * Note a run in the sync log, unless it had nothing to do. The app's
+ * background pushes note their own.
+ */
+async function logSyncRun(direction: 'push' | 'pull', startedAt: string, result: SyncResult): Promise<void> {
+  const failed = result.errors.length;
+  if (result.pushed === 0 && result.pulled === 0 && failed === 0) return;
+  const db = await getDatabase();
+  await db.execute(
+    `INSERT INTO sync_log (id, direction, started_at, finished_at, pushed, pulled, failed, error)
+     VALUES ($1, $2, $3, $4, $5, $6, $7, $8)`,
+    [generateId(), direction, startedAt, new Date().toISOString(), result.pushed, result.pulled, failed, result.errors[0] ?? null]
+  );
+}
+
 /**
  * Queue a change for sync.
  */
@@ -85,6 +117,7 @@ export async function queueChange(
     // Not authenticated, don't queue
     return;
   }
+  if (!(await isTableSynced(tableName))) return;
 
   const db = await getDatabase();
   const id = generateId();
@@ -103,6 +136,23 @@ export async function queueChange(
   }
 }
 
+/**
+ * Note a pushed or pulled row in the record's change history.
+ */
+async function recordSyncEvent(
+  tableName: string,
+  recordId: string,
+  direction: 'push' | 'pull',
+  operation?: SyncOperation
+): Promise<void> {
+  const db = await getDatabase();
+  await db.execute(
+    `INSERT INTO sync_events (table_name, record_id, direction, operation, occurred_at)
+     VALUES ($1, $2, $3, $4, $5)`,
+    [tableName, recordId, direction, operation ?? null, new Date().toISOString()]
+  );
+}
+
 /**
  * Get pending sync queue items.
  */
@@ -119,7 +169,15 @@ async function getPendingSyncItems(): Promise<SyncQueueItem[]> {
   // 4. Then by created_at within each table
   return db.select<SyncQueueItem[]>(
     `SELECT * FROM sync_queue
-     WHERE user_id = $1 AND attempts < $2
+     WHERE user_id = $1 AND dead_lettered_at IS NULL
+       AND (next_attempt_at IS NULL OR next_attempt_at <= $2)
+       AND NOT EXISTS (
+         SELECT 1 FROM sync_conflicts c
+         WHERE c.table_name = sync_queue.table_name
+           AND c.record_id = sync_queue.record_id
+           AND c.resolved_at IS NULL
+       )
+       AND table_name NOT IN (SELECT table_name FROM sync_settings WHERE enabled = 0)
      ORDER BY
        CASE table_name
          WHEN 'categories' THEN 1
@@ -135,7 +193,7 @@ async function getPendingSyncItems(): Promise<SyncQueueItem[]> {
          ELSE 11
        END,
        created_at ASC`,
-    [userId, MAX_RETRY_ATTEMPTS]
+    [userId, new Date().toISOString()]
   );
 }
 
@@ -148,14 +206,18 @@ async function removeSyncItem(id: string): Promise<void> {
 }
 
 /**
- * Mark a sync item as failed.
+ * Mark a sync item as failed. Only the browser build pushes from here, and
+ * it retries on the next sync until the item dead-letters.
  */
 async function markSyncItemFailed(id: string, error: string): Promise<void> {
   const db = await getDatabase();
   const now = new Date().toISOString();
   await db.execute(
-    `UPDATE sync_queue SET attempts = attempts + 1, last_attempt_at = $1, error_message = $2 WHERE id = $3`,
-    [now, error, id]
+    `UPDATE sync_queue SET
+       attempts = attempts + 1, last_attempt_at = $1, error_message = $2,
+       dead_lettered_at = CASE WHEN attempts + 1 >= $3 THEN $1 ELSE NULL END
+     WHERE id = $4`,
+    [now, error, MAX_RETRY_ATTEMPTS, id]
   );
 }
 
@@ -172,9 +234,10 @@ export async function clearSyncQueue(resetFailedOnly = false): Promise<number> {
   if (resetFailedOnly) {
     // Reset failed items so they can be retried
     const result = await db.execute(
-      `UPDATE sync_queue SET attempts = 0, error_message = NULL, last_attempt_at = NULL
-       WHERE user_id = $1 AND attempts >= $2`,
-      [userId, MAX_RETRY_ATTEMPTS]
+      `UPDATE sync_queue SET attempts = 0, error_message = NULL, last_attempt_at = NULL,
+         next_attempt_at = NULL, dead_lettered_at = NULL
+       WHERE user_id = $1 AND dead_lettered_at IS NOT NULL`,
+      [userId]
     );
     return result.rowsAffected;
   } else {
@@ -205,12 +268,139 @@ export async function getSyncQueueStatus(): Promise<{
     [userId]
   );
 
-  const pending = items.filter(i => i.attempts < MAX_RETRY_ATTEMPTS).length;
-  const failed = items.filter(i => i.attempts >= MAX_RETRY_ATTEMPTS).length;
+  const pending = items.filter(i => !i.dead_lettered_at).length;
+  const failed = items.filter(i => i.dead_lettered_at).length;
 
   return { pending, failed, items };
 }
 
+export type SyncState = 'idle' | 'syncing' | 'error';
+
+export interface TableSyncTimes {
+  table_name: string;
+  last_pushed_at: string | null;
+  last_pulled_at: string | null;
+  // Queued changes not pushed yet
+  pending: number;
+}
+
+export interface SyncHealth {
+  state: SyncState;
+  last_error: string | null;
+  last_synced_at: string | null;
+  pending: number;
+  // Dead-lettered changes
+  failed: number;
+  // Changes waiting for a conflict to be resolved
+  held: number;
+  // Remote changes stream in as they happen
+  realtime: boolean;
+  tables: TableSyncTimes[];
+}
+
+export interface SyncRun {
+  id: string;
+  direction: 'push' | 'pull';
+  started_at: string;
+  finished_at: string;
+  pushed: number;
+  pulled: number;
+  failed: number;
+  deferred: number;
+  error: string | null;
+}
+
+/**
+ * Everything a sync health screen shows. The state only sees the app's
+ * background push, so combine it with getSyncStatus().isSyncing for pulls.
+ */
+export async function getSyncHealth(): Promise<SyncHealth | null> {
+  if (!isTauri()) return null;
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getSyncHealth());
+}
+
+/**
+ * Recent sync runs, newest first.
+ */
+export async function getSyncLog(limit?: number): Promise<SyncRun[]> {
+  const db = await getDatabase();
+  return db.select<SyncRun[]>(
+    `SELECT id, direction, started_at, finished_at, pushed, pulled, failed, deferred, error
+     FROM sync_log ORDER BY finished_at DESC LIMIT $1`,
+    [limit ?? 50]
+  );
+}
+
+export interface TableDiff {
+  table_name: string;
+  // On the server, not here
+  missing_locally: number;
+  // Here, never pushed
+  missing_remotely: number;
+  // Newer or different on the server
+  outdated_locally: number;
+  // Edited here after the server's version
+  outdated_remotely: number;
+  // Left to sync because of queued changes or a conflict
+  skipped: number;
+}
+
+export interface ResyncReport {
+  applied: boolean;
+  tables: TableDiff[];
+  orphans: { cleared: number; quarantined: number; soft_deleted: number } | null;
+}
+
+/**
+ * Compare every synced row with the server and repair what drifted. Call
+ * with dryRun first and show the differences before applying them.
+ */
+export async function forceFullResync(dryRun: boolean): Promise<ResyncReport> {
+  if (!isTauri()) {
+    throw new Error('A full resync is only available in the desktop and mobile apps');
+  }
+  const { commands } = await import('./bindings');
+  return unwrap(commands.forceFullResync(dryRun));
+}
+
+export interface SyncProgress {
+  pushed: number;
+  failed: number;
+  // Items of the current push not tried yet
+  remaining: number;
+}
+
+/**
+ * Follow the app's background push item by item. Returns a function that
+ * stops listening.
+ */
+export async function onSyncProgress(handler: (progress: SyncProgress) => void): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  return listen<SyncProgress>('sync://progress', (event) => handler(event.payload));
+}
+
+/**
+ * Follow whether the app is receiving remote changes as they happen.
+ * Returns a function that stops listening.
+ */
+export async function onRealtimeStatus(handler: (connected: boolean) => void): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  return listen<boolean>('sync://realtime', (event) => handler(event.payload));
+}
+
+/**
+ * Follow "Sync now" in the desktop tray menu, which pushes on its own and
+ * leaves the pull to the handler. Returns a function that stops listening.
+ */
+export async function onTraySyncNow(handler: () => void): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  return listen('tray://sync-now', () => handler());
+}
+
 /**
  * Push local changes to Supabase.
  */
@@ -236,6 +426,17 @@ export async function pushChanges(): Promise<SyncResult> {
     return result;
   }
 
+  // The app pushes from its background worker; getFullSession() above has
+  // refreshed the token it reads
+  if (isTauri()) {
+    const { commands } = await import('./bindings');
+    const report = await unwrap(commands.pushSyncQueue());
+    result.pushed = report.pushed;
+    result.errors = report.errors;
+    result.success = report.failed === 0;
+    return result;
+  }
+
   // Set the auth session for this request
   await supabase.auth.setSession({
     access_token: session.accessToken,
@@ -243,6 +444,8 @@ export async function pushChanges(): Promise<SyncResult> {
   });
 
   const items = await getPendingSyncItems();
+  const startedAt = new Date().toISOString();
+  activeRuns++;
 
   for (const item of items) {
     try {
@@ -271,6 +474,7 @@ export async function pushChanges(): Promise<SyncResult> {
       }
 
       await removeSyncItem(item.id);
+      await recordSyncEvent(item.table_name, item.record_id, 'push', item.operation);
       result.pushed++;
     } catch (error) {
       const errorMessage = error instanceof Error ? error.message : 'Unknown error';
@@ -278,10 +482,12 @@ export async function pushChanges(): Promise<SyncResult> {
       result.errors.push(`Failed to sync ${item.table_name}/${item.record_id}: ${errorMessage}`);
     }
   }
+  activeRuns--;
 
   if (result.errors.length > 0) {
     result.success = false;
   }
+  await logSyncRun('push', startedAt, result);
 
   return result;
 }
@@ -296,6 +502,9 @@ async function pushExpense(
 ): Promise<void> {
   if (!supabase) return;
 
+  // In partner mode expenses live in the owner's ledger
+  const ledgerOwnerId = (await getLedgerOwnerId()) ?? item.user_id;
+
   if (item.operation === 'delete') {
     // Soft delete - update deleted_at
     const { error } = await supabase
@@ -305,7 +514,7 @@ async function pushExpense(
         updated_at: payload.updated_at || new Date().toISOString(),
       })
       .eq('id', item.record_id)
-      .eq('user_id', item.user_id);
+      .eq('user_id', ledgerOwnerId);
 
     if (error) throw new Error(error.message);
   } else {
@@ -314,14 +523,18 @@ async function pushExpense(
       .from('expenses')
       .upsert({
         id: payload.id,
-        user_id: item.user_id,
+        user_id: ledgerOwnerId,
         amount: payload.amount,
         category_id: payload.category_id,
         note: payload.note,
         date: payload.date,
+        household_id: payload.household_id ?? null,
+        created_by: payload.created_by ?? item.user_id,
         created_at: payload.created_at,
         updated_at: payload.updated_at,
         deleted_at: payload.deleted_at,
+        // Pushed in plaintext, so drop any sealed copy that would win on pull
+        encrypted_payload: null,
       }, {
         onConflict: 'id',
       });
@@ -340,6 +553,9 @@ async function pushBudget(
 ): Promise<void> {
   if (!supabase) return;
 
+  // In partner mode there is one budget, the owner's
+  const ledgerOwnerId = (await getLedgerOwnerId()) ?? item.user_id;
+
   if (item.operation === 'delete') {
     // Soft delete
     const { error } = await supabase
@@ -349,7 +565,7 @@ async function pushBudget(
         updated_at: payload.updated_at || new Date().toISOString(),
       })
       .eq('id', item.record_id)
-      .eq('user_id', item.user_id);
+      .eq('user_id', ledgerOwnerId);
 
     if (error) throw new Error(error.message);
   } else {
@@ -358,13 +574,16 @@ async function pushBudget(
       .from('budgets')
       .upsert({
         id: payload.id,
-        user_id: item.user_id,
+        user_id: ledgerOwnerId,
         month: payload.month,
         total_amount: payload.total_amount,
         spending_limit: payload.spending_limit,
+        household_id: payload.household_id ?? null,
+        created_by: payload.created_by ?? item.user_id,
         created_at: payload.created_at,
         updated_at: payload.updated_at,
         deleted_at: payload.deleted_at,
+        encrypted_payload: null,
       }, {
         onConflict: 'id',
       });
@@ -411,6 +630,7 @@ async function pushSavingsGoal(
         created_at: payload.created_at,
         updated_at: payload.updated_at,
         deleted_at: payload.deleted_at,
+        encrypted_payload: null,
       }, {
         onConflict: 'id',
       });
@@ -455,6 +675,7 @@ async function pushSavingsContribution(
         created_at: payload.created_at,
         updated_at: payload.updated_at,
         deleted_at: payload.deleted_at,
+        encrypted_payload: null,
       }, {
         onConflict: 'id',
       });
@@ -537,6 +758,7 @@ async function pushHabitGoal(
         created_at: payload.created_at,
         updated_at: payload.updated_at,
         deleted_at: payload.deleted_at,
+        encrypted_payload: null,
       }, {
         onConflict: 'id',
       });
@@ -582,6 +804,7 @@ async function pushHabitTracking(
         created_at: payload.created_at,
         updated_at: payload.updated_at,
         deleted_at: payload.deleted_at,
+        encrypted_payload: null,
       }, {
         onConflict: 'id',
       });
@@ -735,6 +958,7 @@ export async function pullChanges(): Promise<SyncResult> {
     pulled: 0,
     errors: [],
   };
+  const pulledTables = new Set<string>();
 
   if (!isSupabaseConfigured() || !isOnline()) {
     return result;
@@ -742,6 +966,7 @@ export async function pullChanges(): Promise<SyncResult> {
 
   const supabase = getSupabase();
   if (!supabase) return result;
+  if (!(await isSyncAllowed())) return result;
 
   const session = await getFullSession();
   const userId = await getCurrentUserId();
@@ -759,32 +984,50 @@ export async function pullChanges(): Promise<SyncResult> {
 
   const authState = await getLocalAuthState();
   const lastSyncAt = authState?.last_sync_at;
+  const startedAt = new Date().toISOString();
+  activeRuns++;
 
   try {
+    // Budgets and expenses come from the shared ledger in partner mode
+    await refreshPartnership();
+    await refreshHouseholds();
+    await refreshSyncEncryption(supabase, userId);
+    const ledgerUserIds = [...new Set([userId, (await getLedgerOwnerId()) ?? userId])];
+
+    // Tables kept out of sync aren't pulled; tables just turned back on are
+    // pulled in full, as changes made while they were off are older than
+    // the last sync.
+    const excluded = await getExcludedSyncTables();
+    const fullPull = await getFullPullTables();
+    const sinceFor = (table: string) => (fullPull.has(table) ? null : lastSyncAt);
+    const notPulled = { data: null, error: null };
+
     // Pull expenses
     let expensesQuery = supabase
       .from('expenses')
       .select('*')
-      .eq('user_id', userId);
+      .in('user_id', ledgerUserIds);
 
-    if (lastSyncAt) {
-      expensesQuery = expensesQuery.gt('updated_at', lastSyncAt);
+    const expensesSince = sinceFor('expenses');
+    if (expensesSince) {
+      expensesQuery = expensesQuery.gt('updated_at', expensesSince);
     }
 
-    const { data: remoteExpenses, error: expensesError } = await expensesQuery;
+    const { data: remoteExpenses, error: expensesError } = excluded.has('expenses') ? notPulled : await expensesQuery;
     if (expensesError) throw new Error(expensesError.message);
 
     // Pull budgets
     let budgetsQuery = supabase
       .from('budgets')
       .select('*')
-      .eq('user_id', userId);
+      .in('user_id', ledgerUserIds);
 
-    if (lastSyncAt) {
-      budgetsQuery = budgetsQuery.gt('updated_at', lastSyncAt);
+    const budgetsSince = sinceFor('budgets');
+    if (budgetsSince) {
+      budgetsQuery = budgetsQuery.gt('updated_at', budgetsSince);
     }
 
-    const { data: remoteBudgets, error: budgetsError } = await budgetsQuery;
+    const { data: remoteBudgets, error: budgetsError } = excluded.has('budgets') ? notPulled : await budgetsQuery;
     if (budgetsError) throw new Error(budgetsError.message);
 
     // Pull savings goals
@@ -793,11 +1036,12 @@ export async function pullChanges(): Promise<SyncResult> {
       .select('*')
       .eq('user_id', userId);
 
-    if (lastSyncAt) {
-      savingsGoalsQuery = savingsGoalsQuery.gt('updated_at', lastSyncAt);
+    const savingsGoalsSince = sinceFor('savings_goals');
+    if (savingsGoalsSince) {
+      savingsGoalsQuery = savingsGoalsQuery.gt('updated_at', savingsGoalsSince);
     }
 
-    const { data: remoteSavingsGoals, error: savingsGoalsError } = await savingsGoalsQuery;
+    const { data: remoteSavingsGoals, error: savingsGoalsError } = excluded.has('savings_goals') ? notPulled : await savingsGoalsQuery;
     if (savingsGoalsError) throw new Error(savingsGoalsError.message);
 
     // Pull savings contributions
@@ -806,37 +1050,58 @@ export async function pullChanges(): Promise<SyncResult> {
       .select('*')
       .eq('user_id', userId);
 
-    if (lastSyncAt) {
-      savingsContributionsQuery = savingsContributionsQuery.gt('updated_at', lastSyncAt);
+    const savingsContributionsSince = sinceFor('savings_contributions');
+    if (savingsContributionsSince) {
+      savingsContributionsQuery = savingsContributionsQuery.gt('updated_at', savingsContributionsSince);
     }
 
-    const { data: remoteSavingsContributions, error: savingsContributionsError } = await savingsContributionsQuery;
+    const { data: remoteSavingsContributions, error: savingsContributionsError } = excluded.has('savings_contributions') ? notPulled : await savingsContributionsQuery;
     if (savingsContributionsError) throw new Error(savingsContributionsError.message);
 
     const db = await getDatabase();
 
     // Merge expenses
-    for (const remoteExpense of remoteExpenses || []) {
+    for (const remoteExpense of await openRemoteRows('expenses', remoteExpenses)) {
+      if (await holdConflict(db, 'expenses', remoteExpense)) continue;
       const merged = await mergeExpense(db, remoteExpense, userId);
-      if (merged) result.pulled++;
+      if (merged) {
+        result.pulled++;
+        pulledTables.add('expenses');
+        await recordSyncEvent('expenses', remoteExpense.id, 'pull');
+      }
     }
 
     // Merge budgets
-    for (const remoteBudget of remoteBudgets || []) {
+    for (const remoteBudget of await openRemoteRows('budgets', remoteBudgets)) {
+      if (await holdConflict(db, 'budgets', remoteBudget)) continue;
       const merged = await mergeBudget(db, remoteBudget, userId);
-      if (merged) result.pulled++;
+      if (merged) {
+        result.pulled++;
+        pulledTables.add('budgets');
+        await recordSyncEvent('budgets', remoteBudget.id, 'pull');
+      }
     }
 
     // Merge savings goals
-    for (const remoteSavingsGoal of remoteSavingsGoals || []) {
+    for (const remoteSavingsGoal of await openRemoteRows('savings_goals', remoteSavingsGoals)) {
+      if (await holdConflict(db, 'savings_goals', remoteSavingsGoal)) continue;
       const merged = await mergeSavingsGoal(db, remoteSavingsGoal, userId);
-      if (merged) result.pulled++;
+      if (merged) {
+        result.pulled++;
+        pulledTables.add('savings_goals');
+        await recordSyncEvent('savings_goals', remoteSavingsGoal.id, 'pull');
+      }
     }
 
     // Merge savings contributions
-    for (const remoteSavingsContribution of remoteSavingsContributions || []) {
+    for (const remoteSavingsContribution of await openRemoteRows('savings_contributions', remoteSavingsContributions)) {
+      if (await holdConflict(db, 'savings_contributions', remoteSavingsContribution)) continue;
       const merged = await mergeSavingsContribution(db, remoteSavingsContribution, userId);
-      if (merged) result.pulled++;
+      if (merged) {
+        result.pulled++;
+        pulledTables.add('savings_contributions');
+        await recordSyncEvent('savings_contributions', remoteSavingsContribution.id, 'pull');
+      }
     }
 
     // Pull notification preferences
@@ -845,11 +1110,12 @@ export async function pullChanges(): Promise<SyncResult> {
       .select('*')
       .eq('user_id', userId);
 
-    if (lastSyncAt) {
-      notificationPrefsQuery = notificationPrefsQuery.gt('updated_at', lastSyncAt);
+    const notificationPrefsSince = sinceFor('notification_preferences');
+    if (notificationPrefsSince) {
+      notificationPrefsQuery = notificationPrefsQuery.gt('updated_at', notificationPrefsSince);
     }
 
-    const { data: remoteNotificationPrefs, error: notificationPrefsError } = await notificationPrefsQuery;
+    const { data: remoteNotificationPrefs, error: notificationPrefsError } = excluded.has('notification_preferences') ? notPulled : await notificationPrefsQuery;
     if (notificationPrefsError) {
       // Table might not exist yet - log but don't fail
       console.warn('Failed to pull notification preferences:', notificationPrefsError.message);
@@ -857,7 +1123,10 @@ export async function pullChanges(): Promise<SyncResult> {
       // Merge notification preferences
       for (const remotePrefs of remoteNotificationPrefs || []) {
         const merged = await mergeNotificationPreferences(db, remotePrefs, userId);
-        if (merged) result.pulled++;
+        if (merged) {
+          result.pulled++;
+          pulledTables.add('notification_preferences');
+        }
       }
     }
 
@@ -867,17 +1136,23 @@ export async function pullChanges(): Promise<SyncResult> {
       .select('*')
       .eq('user_id', userId);
 
-    if (lastSyncAt) {
-      habitGoalsQuery = habitGoalsQuery.gt('updated_at', lastSyncAt);
+    const habitGoalsSince = sinceFor('habit_goals');
+    if (habitGoalsSince) {
+      habitGoalsQuery = habitGoalsQuery.gt('updated_at', habitGoalsSince);
     }
 
-    const { data: remoteHabitGoals, error: habitGoalsError } = await habitGoalsQuery;
+    const { data: remoteHabitGoals, error: habitGoalsError } = excluded.has('habit_goals') ? notPulled : await habitGoalsQuery;
     if (habitGoalsError) {
       console.warn('Failed to pull habit goals:', habitGoalsError.message);
     } else {
-      for (const remoteHabitGoal of remoteHabitGoals || []) {
+      for (const remoteHabitGoal of await openRemoteRows('habit_goals', remoteHabitGoals)) {
+        if (await holdConflict(db, 'habit_goals', remoteHabitGoal)) continue;
         const merged = await mergeHabitGoal(db, remoteHabitGoal, userId);
-        if (merged) result.pulled++;
+        if (merged) {
+          result.pulled++;
+          pulledTables.add('habit_goals');
+          await recordSyncEvent('habit_goals', remoteHabitGoal.id, 'pull');
+        }
       }
     }
 
@@ -887,17 +1162,23 @@ export async function pullChanges(): Promise<SyncResult> {
       .select('*')
       .eq('user_id', userId);
 
-    if (lastSyncAt) {
-      habitTrackingQuery = habitTrackingQuery.gt('updated_at', lastSyncAt);
+    const habitTrackingSince = sinceFor('habit_tracking');
+    if (habitTrackingSince) {
+      habitTrackingQuery = habitTrackingQuery.gt('updated_at', habitTrackingSince);
     }
 
-    const { data: remoteHabitTracking, error: habitTrackingError } = await habitTrackingQuery;
+    const { data: remoteHabitTracking, error: habitTrackingError } = excluded.has('habit_tracking') ? notPulled : await habitTrackingQuery;
     if (habitTrackingError) {
       console.warn('Failed to pull habit tracking:', habitTrackingError.message);
     } else {
-      for (const remoteTracking of remoteHabitTracking || []) {
+      for (const remoteTracking of await openRemoteRows('habit_tracking', remoteHabitTracking)) {
+        if (await holdConflict(db, 'habit_tracking', remoteTracking)) continue;
         const merged = await mergeHabitTracking(db, remoteTracking, userId);
-        if (merged) result.pulled++;
+        if (merged) {
+          result.pulled++;
+          pulledTables.add('habit_tracking');
+          await recordSyncEvent('habit_tracking', remoteTracking.id, 'pull');
+        }
       }
     }
 
@@ -907,17 +1188,23 @@ export async function pullChanges(): Promise<SyncResult> {
       .select('*')
       .eq('user_id', userId);
 
-    if (lastSyncAt) {
-      categoriesQuery = categoriesQuery.gt('updated_at', lastSyncAt);
+    const categoriesSince = sinceFor('categories');
+    if (categoriesSince) {
+      categoriesQuery = categoriesQuery.gt('updated_at', categoriesSince);
     }
 
-    const { data: remoteCategories, error: categoriesError } = await categoriesQuery;
+    const { data: remoteCategories, error: categoriesError } = excluded.has('categories') ? notPulled : await categoriesQuery;
     if (categoriesError) {
       console.warn('Failed to pull categories:', categoriesError.message);
     } else {
       for (const remoteCategory of remoteCategories || []) {
+        if (await holdConflict(db, 'categories', remoteCategory)) continue;
         const merged = await mergeCategory(db, remoteCategory, userId);
-        if (merged) result.pulled++;
+        if (merged) {
+          result.pulled++;
+          pulledTables.add('categories');
+          await recordSyncEvent('categories', remoteCategory.id, 'pull');
+        }
       }
     }
 
@@ -927,17 +1214,21 @@ export async function pullChanges(): Promise<SyncResult> {
       .select('*')
       .eq('user_id', userId);
 
-    if (lastSyncAt) {
-      feedbackNotesQuery = feedbackNotesQuery.gt('updated_at', lastSyncAt);
+    const feedbackNotesSince = sinceFor('feedback_notes');
+    if (feedbackNotesSince) {
+      feedbackNotesQuery = feedbackNotesQuery.gt('updated_at', feedbackNotesSince);
     }
 
-    const { data: remoteFeedbackNotes, error: feedbackNotesError } = await feedbackNotesQuery;
+    const { data: remoteFeedbackNotes, error: feedbackNotesError } = excluded.has('feedback_notes') ? notPulled : await feedbackNotesQuery;
     if (feedbackNotesError) {
       console.warn('Failed to pull feedback notes:', feedbackNotesError.message);
     } else {
       for (const remoteNote of remoteFeedbackNotes || []) {
         const merged = await mergeFeedbackNote(db, remoteNote, userId);
-        if (merged) result.pulled++;
+        if (merged) {
+          result.pulled++;
+          pulledTables.add('feedback_notes');
+        }
       }
     }
 
@@ -947,34 +1238,104 @@ export async function pullChanges(): Promise<SyncResult> {
       .select('*')
       .eq('user_id', userId);
 
-    if (lastSyncAt) {
-      scheduledNotificationsQuery = scheduledNotificationsQuery.gt('updated_at', lastSyncAt);
+    const scheduledNotificationsSince = sinceFor('scheduled_notifications');
+    if (scheduledNotificationsSince) {
+      scheduledNotificationsQuery = scheduledNotificationsQuery.gt('updated_at', scheduledNotificationsSince);
     }
 
-    const { data: remoteScheduledNotifications, error: scheduledNotificationsError } = await scheduledNotificationsQuery;
+    const { data: remoteScheduledNotifications, error: scheduledNotificationsError } = excluded.has('scheduled_notifications') ? notPulled : await scheduledNotificationsQuery;
     if (scheduledNotificationsError) {
       console.warn('Failed to pull scheduled notifications:', scheduledNotificationsError.message);
     } else {
       for (const remoteNotification of remoteScheduledNotifications || []) {
         const merged = await mergeScheduledNotification(db, remoteNotification, userId);
-        if (merged) result.pulled++;
+        if (merged) {
+          result.pulled++;
+          pulledTables.add('scheduled_notifications');
+        }
       }
     }
 
+    // Other members' shared rows, kept apart from the user's own
+    const household = await pullHouseholdRecords(supabase, ledgerUserIds);
+    result.pulled += household.pulled;
+    household.tables.forEach(table => pulledTables.add(table));
+
     // Update last sync timestamp
     const now = new Date().toISOString();
     await updateLastSyncAt(now);
+    await clearFullPulls(fullPull);
+
+    if (result.pulled > 0) {
+      publishDomainEvent({
+        type: 'sync:applied_remote_changes',
+        tables: [...pulledTables],
+        count: result.pulled,
+      });
+    }
+    if (pulledTables.has('scheduled_notifications') || pulledTables.has('notification_preferences')) {
+      registerPlatformNotifications();
+    }
 
   } catch (error) {
     console.error("[Sync error] ", error);
     const errorMessage = error instanceof Error ? error.message : 'Unknown error';
     result.success = false;
     result.errors.push(`Pull failed: ${errorMessage}`);
+  } finally {
+    activeRuns--;
   }
+  await logSyncRun('pull', startedAt, result);
 
   return result;
 }
 
+/**
+ * Park a pulled row as a conflict instead of merging it when the record has
+ * local edits still waiting to be pushed and the remote change came after
+ * the first of them. In Tauri the backend settles it with the table's
+ * conflict strategy instead. Returns true if the row must not be merged.
+ */
+async function holdConflict(
+  db: Awaited<ReturnType<typeof getDatabase>>,
+  tableName: string,
+  remote: Record<string, unknown>
+): Promise<boolean> {
+  if (isTauri()) {
+    const { commands } = await import('./bindings');
+    const row = remote as Partial<Record<string, JsonValue>>;
+    const outcome = await unwrap(commands.reconcilePulledRow(tableName, row));
+    return outcome !== 'merge';
+  }
+
+  const pending = await db.select<{ first_edit: string | null }[]>(
+    `SELECT MIN(created_at) as first_edit FROM sync_queue WHERE table_name = $1 AND record_id = $2`,
+    [tableName, remote.id]
+  );
+  const firstEdit = pending[0]?.first_edit;
+  if (!firstEdit) return false;
+
+  // Older remote versions are usually our own earlier pushes
+  const remoteUpdatedAt = remote.updated_at as string;
+  if (new Date(remoteUpdatedAt).getTime() < new Date(firstEdit).getTime()) return false;
+
+  const now = new Date().toISOString();
+  const payload = JSON.stringify(remote);
+  const updated = await db.execute(
+    `UPDATE sync_conflicts SET remote_payload = $1, remote_updated_at = $2, detected_at = $3
+     WHERE table_name = $4 AND record_id = $5 AND resolved_at IS NULL`,
+    [payload, remoteUpdatedAt, now, tableName, remote.id]
+  );
+  if (updated.rowsAffected === 0) {
+    await db.execute(
+      `INSERT INTO sync_conflicts (id, table_name, record_id, remote_payload, remote_updated_at, detected_at)
+       VALUES ($1, $2, $3, $4, $5, $6)`,
+      [generateId(), tableName, remote.id, payload, remoteUpdatedAt, now]
+    );
+  }
+  return true;
+}
+
 /**
  * Merge a remote expense with local data (last write wins).
  */
@@ -999,8 +1360,9 @@ async function mergeExpense(
       await db.execute(
         `UPDATE expenses SET
           amount = $1, category_id = $2, note = $3, date = $4,
-          updated_at = $5, synced_at = $6, deleted_at = $7, user_id = $8
-         WHERE id = $9`,
+          updated_at = $5, synced_at = $6, deleted_at = $7, user_id = $8, created_by = $9,
+          household_id = $10
+         WHERE id = $11`,
         [
           remote.amount,
           remote.category_id,
@@ -1010,14 +1372,16 @@ async function mergeExpense(
           new Date().toISOString(),
           remote.deleted_at,
           userId,
+          remote.created_by ?? local.created_by ?? null,
+          remote.household_id ?? null,
           remote.id,
         ]
       );
     } else {
       // Insert new
       await db.execute(
-        `INSERT INTO expenses (id, user_id, amount, category_id, note, date, created_at, updated_at, synced_at, deleted_at)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)`,
+        `INSERT INTO expenses (id, user_id, amount, category_id, note, date, created_by, created_at, updated_at, synced_at, deleted_at, household_id)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)`,
         [
           remote.id,
           userId,
@@ -1025,13 +1389,16 @@ async function mergeExpense(
           remote.category_id,
           remote.note,
           remote.date,
+          remote.created_by ?? remote.user_id,
           remote.created_at,
           remote.updated_at,
           new Date().toISOString(),
           remote.deleted_at,
+          remote.household_id ?? null,
         ]
       );
     }
+    refreshCategoryTotals(local, remote as { date: string; category_id: string | null });
     return true;
   }
 
@@ -1071,8 +1438,9 @@ async function mergeBudget(
       // Update existing (use local.id in case we found it by month with a different ID)
       await db.execute(
         `UPDATE budgets SET
-          id = $1, total_amount = $2, spending_limit = $3, updated_at = $4, deleted_at = $5, user_id = $6
-         WHERE id = $7`,
+          id = $1, total_amount = $2, spending_limit = $3, updated_at = $4, deleted_at = $5, user_id = $6, created_by = $7,
+          household_id = $8
+         WHERE id = $9`,
         [
           remote.id,
           remote.total_amount,
@@ -1080,23 +1448,27 @@ async function mergeBudget(
           remote.updated_at,
           remote.deleted_at,
           userId,
+          remote.created_by ?? local.created_by ?? null,
+          remote.household_id ?? null,
           local.id,
         ]
       );
     } else {
       // Insert new
       await db.execute(
-        `INSERT INTO budgets (id, user_id, month, total_amount, spending_limit, created_at, updated_at, deleted_at)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)`,
+        `INSERT INTO budgets (id, user_id, month, total_amount, spending_limit, created_by, created_at, updated_at, deleted_at, household_id)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)`,
         [
           remote.id,
           userId,
           remote.month,
           remote.total_amount,
           remote.spending_limit,
+          remote.created_by ?? remote.user_id,
           remote.created_at,
           remote.updated_at,
           remote.deleted_at,
+          remote.household_id ?? null,
         ]
       );
     }
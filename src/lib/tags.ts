@@ -0,0 +1,135 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+import type { ExpenseWithCategory } from './types';
+
+/**
+ * Tags label expenses across categories ("vacation", "work-reimbursable").
+ * An expense can carry any number of them; names are unique ignoring case.
+ */
+
+export interface Tag {
+  id: string;
+  name: string;
+  color: string | null;
+  expense_count: number;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface TagFilter {
+  tag_ids: string[];
+  // Only expenses with every tag rather than any of them
+  match_all?: boolean;
+}
+
+export interface TagTotal {
+  tag_id: string;
+  name: string;
+  color: string | null;
+  total: number;
+  count: number;
+}
+
+export interface TaggedCategoryTotal {
+  category_id: string | null;
+  category_name: string | null;
+  total: number;
+  count: number;
+}
+
+function assertTauri(): void {
+  if (!isTauri()) {
+    throw new Error('Tags are only available in the desktop and mobile apps');
+  }
+}
+
+export async function getTags(): Promise<Tag[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getTags());
+}
+
+export async function createTag(name: string, color?: string): Promise<Tag> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.createTag(name, color ?? null));
+}
+
+export async function setTagColor(id: string, color: string | null): Promise<Tag> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.setTagColor(id, color));
+}
+
+/**
+ * Rename a tag. Renaming to the name of another tag merges the two, and the
+ * tag returned is the one kept.
+ */
+export async function renameTag(id: string, name: string): Promise<Tag> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.renameTag(id, name));
+}
+
+export async function mergeTags(sources: string[], target: string): Promise<Tag> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.mergeTags(sources, target));
+}
+
+export async function deleteTag(id: string): Promise<void> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  await unwrap(commands.deleteTag(id));
+}
+
+export async function getExpenseTags(expenseId: string): Promise<Tag[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getExpenseTags(expenseId));
+}
+
+export async function setExpenseTags(expenseId: string, names: string[]): Promise<Tag[]> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.setExpenseTags(expenseId, names));
+}
+
+export async function tagExpenses(
+  tagId: string,
+  expenseIds: string[],
+  remove = false
+): Promise<number> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.tagExpenses(tagId, expenseIds, remove));
+}
+
+export async function getTaggedExpenses(
+  filter: TagFilter,
+  startDate: string,
+  endDate: string
+): Promise<ExpenseWithCategory[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getTaggedExpenses(filter, startDate, endDate));
+}
+
+/**
+ * Spending per tag. An expense with several tags counts towards each.
+ */
+export async function getTagTotals(startDate: string, endDate: string): Promise<TagTotal[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getTagTotals(startDate, endDate));
+}
+
+export async function getTaggedCategoryTotals(
+  filter: TagFilter,
+  startDate: string,
+  endDate: string
+): Promise<TaggedCategoryTotal[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getTaggedCategoryTotals(filter, startDate, endDate));
+}
@@ -20,9 +20,11 @@ export interface Budget {
   month: string; // "2026-01"
   total_amount: number;
   spending_limit: number | null;
+  created_by?: string | null;
   created_at: string;
   updated_at: string;
   deleted_at: string | null;
+  household_id?: string | null;
 }
 
 export interface Expense {
@@ -36,8 +38,23 @@ export interface Expense {
   updated_at: string;
   synced_at: string | null;
   deleted_at: string | null;
+  reimbursement_status?: ReimbursementStatus | null;
+  reimbursement_owed_by?: string | null;
+  reimbursed_at?: string | null;
+  payment_method?: PaymentMethod | null;
+  created_by?: string | null;
+  /** Set when paid in another currency; amount is then the converted value. */
+  currency?: string | null;
+  original_amount?: number | null;
+  merchant_id?: string | null;
+  account_id?: string | null;
+  household_id?: string | null;
 }
 
+export type PaymentMethod = 'cash' | 'card' | 'bank' | 'other';
+
+export type ReimbursementStatus = 'pending' | 'received' | 'written_off';
+
 export interface ExpenseWithCategory extends Expense {
   category_name: string | null;
   category_icon: string | null;
@@ -70,6 +87,19 @@ export interface SavingsContribution {
   deleted_at: string | null;
 }
 
+// A savings goal or habit the month's check-in still asks about
+export interface PendingCheckin {
+  kind: 'savings_goal' | 'habit_goal';
+  goal_id: string;
+  name: string;
+  month: string; // "2026-01"
+  // Savings: the contribution that keeps the goal on plan. Habits: usage so far
+  suggested_amount: number;
+  // Planned monthly contribution, or the habit's limit
+  target_amount: number;
+  is_count: boolean; // max_count habits count purchases
+}
+
 // Extended type with calculated stats for the UI
 export interface SavingsGoalWithStats extends SavingsGoal {
   total_saved: number;
@@ -85,7 +115,7 @@ export interface HabitGoal {
   user_id: string | null;
   name: string;
   category_id: string;
-  rule_type: 'max_amount' | 'max_percentage' | 'reduce_by';
+  rule_type: 'max_amount' | 'max_percentage' | 'reduce_by' | 'no_spend' | 'max_count';
   rule_value: number;
   duration_months: number | null;
   start_date: string;
@@ -121,6 +151,17 @@ export interface HabitGoalWithStats extends HabitGoal {
   status: 'safe' | 'warning' | 'exceeded';
 }
 
+// Computed by the backend from habit_tracking and expenses
+export interface HabitStreak {
+  habit_goal_id: string;
+  current_months: number;
+  longest_months: number;
+  current_weeks: number;
+  longest_weeks: number;
+  freezes_available: number;
+  frozen_months: string[]; // "2026-01", months a freeze bridged
+}
+
 export interface FeedbackNote {
   id: string;
   user_id: string | null;
@@ -130,6 +171,17 @@ export interface FeedbackNote {
   deleted_at: string | null;
 }
 
+// Partner mode: the owner's budget is shared with one partner
+export interface Partnership {
+  id: string;
+  owner_id: string;
+  partner_id: string | null; // null until the invite is accepted
+  invite_code: string;
+  partner_name: string | null; // the other member's display name
+  created_at: string;
+  updated_at: string;
+}
+
 // Utility functions
 export function generateId(): string {
   return crypto.randomUUID();
@@ -194,6 +246,8 @@ export interface SyncQueueItem {
   created_at: string;
   attempts: number;
   last_attempt_at: string | null;
+  next_attempt_at: string | null;
+  dead_lettered_at: string | null;
   error_message: string | null;
 }
 
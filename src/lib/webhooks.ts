@@ -0,0 +1,89 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * Webhooks: a JSON POST to a URL of the user's when the budget is exceeded,
+ * a goal reaches a milestone or the monthly check-in is due. Each request
+ * is signed with `X-Goaldy-Signature: sha256=<hex>`, the HMAC-SHA256 of
+ * `<X-Goaldy-Timestamp>.<body>` keyed with the webhook's secret. Failed
+ * deliveries are retried for a while.
+ */
+
+export type WebhookEvent =
+  | 'budget.exceeded'
+  | 'goal.milestone_reached'
+  | 'checkin.due'
+  | 'webhook.test';
+
+export interface Webhook {
+  id: string;
+  url: string;
+  secret: string;
+  events: WebhookEvent[];
+  enabled: boolean;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface WebhookInput {
+  url: string;
+  events: WebhookEvent[];
+  enabled: boolean;
+}
+
+export interface WebhookDelivery {
+  id: string;
+  webhook_id: string;
+  event: string;
+  attempts: number;
+  next_attempt_at: string;
+  delivered_at: string | null;
+  // Set once it was given up on
+  failed_at: string | null;
+  last_status: number | null;
+  last_error: string | null;
+  created_at: string;
+}
+
+function assertTauri(): void {
+  if (!isTauri()) {
+    throw new Error('Webhooks are only available in the desktop and mobile apps');
+  }
+}
+
+export async function getWebhooks(): Promise<Webhook[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getWebhooks());
+}
+
+export async function createWebhook(input: WebhookInput): Promise<Webhook> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.createWebhook(input));
+}
+
+export async function updateWebhook(id: string, input: WebhookInput): Promise<Webhook> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  return unwrap(commands.updateWebhook(id, input));
+}
+
+export async function deleteWebhook(id: string): Promise<void> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  await unwrap(commands.deleteWebhook(id));
+}
+
+/** Send a `webhook.test` event to the webhook now. */
+export async function testWebhook(id: string): Promise<void> {
+  assertTauri();
+  const { commands } = await import('./bindings');
+  await unwrap(commands.testWebhook(id));
+}
+
+export async function getWebhookDeliveries(webhookId: string): Promise<WebhookDelivery[]> {
+  if (!isTauri()) return [];
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getWebhookDeliveries(webhookId));
+}
@@ -0,0 +1,19 @@
+import { unwrap } from './commands';
+import { isTauri } from './platform';
+
+/**
+ * Excel export: expenses, budgets, goals and spending per category and
+ * month, one sheet each.
+ */
+export async function exportXlsx(range: {
+  // YYYY-MM-DD, inclusive
+  start_date: string;
+  end_date: string;
+}): Promise<string> {
+  if (!isTauri()) {
+    throw new Error('Excel export is only available in the desktop and mobile apps');
+  }
+  const { commands } = await import('./bindings');
+  // Path of the written workbook
+  return unwrap(commands.exportXlsx(range));
+}
@@ -0,0 +1,59 @@
+import { unwrap } from './commands';
+import type { MonthCashflow } from './income';
+import { isTauri } from './platform';
+
+/**
+ * The numbers behind the annual recap: totals, the biggest categories, the
+ * best and worst finished months, goals completed and the longest habit
+ * streak within the year.
+ */
+
+export interface YearCategory {
+  category_id: string | null;
+  category_name: string | null;
+  category_color: string | null;
+  total: number;
+  count: number;
+  // Share of the year's spending, 0 to 1
+  share: number;
+}
+
+export interface CompletedGoal {
+  goal_id: string;
+  name: string;
+  target_amount: number;
+  // YYYY-MM of the contribution that reached the target
+  completed_month: string;
+}
+
+export interface HabitRun {
+  habit_goal_id: string;
+  name: string;
+  months: number;
+  from_month: string;
+  to_month: string;
+}
+
+export interface YearReview {
+  year: number;
+  total_spent: number;
+  total_income: number;
+  // Contributed to savings goals
+  total_saved: number;
+  expense_count: number;
+  top_categories: YearCategory[];
+  best_month: MonthCashflow | null;
+  worst_month: MonthCashflow | null;
+  months: MonthCashflow[];
+  goals_completed: CompletedGoal[];
+  longest_habit_streak: HabitRun | null;
+}
+
+/**
+ * The review of a year, the current one (so far) unless given.
+ */
+export async function getYearReview(year?: number): Promise<YearReview | null> {
+  if (!isTauri()) return null;
+  const { commands } = await import('./bindings');
+  return unwrap(commands.getYearReview(year ?? null));
+}
@@ -1,8 +1,11 @@
 import { RouterProvider } from "@tanstack/react-router";
 import React from "react";
 import ReactDOM from "react-dom/client";
+import { QuickAddWindow } from "./components/QuickAddWindow";
 import "./index.css";
 import { runMigrations } from "./lib/migrations";
+import { isQuickAddWindow } from "./lib/quick-add";
+import { configureBackend } from "./lib/supabase";
 import { router } from "./router";
 
 // Run migrations before rendering the app
@@ -19,9 +22,12 @@ runMigrations()
     console.error('[App] Failed to run migrations:', error);
   })
   .finally(() => {
+    configureBackend().catch((error) => {
+      console.error('[App] Failed to configure backend:', error);
+    });
     ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
       <React.StrictMode>
-        <RouterProvider router={router} />
+        {isQuickAddWindow() ? <QuickAddWindow /> : <RouterProvider router={router} />}
       </React.StrictMode>,
     );
   });
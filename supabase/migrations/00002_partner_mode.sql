@@ -0,0 +1,121 @@
+-- Goaldy Partner Mode - Supabase Migration
+-- Two accounts share one budget. The owner's budgets and expenses are the
+-- shared ledger; the partner reads and writes them once they accepted the
+-- owner's invite. created_by records who entered each row.
+
+-- ============================================
+-- Partnerships
+-- ============================================
+CREATE TABLE IF NOT EXISTS public.partnerships (
+  id TEXT PRIMARY KEY,
+  owner_id UUID NOT NULL REFERENCES auth.users(id) ON DELETE CASCADE,
+  partner_id UUID REFERENCES auth.users(id) ON DELETE CASCADE,
+  invite_code TEXT NOT NULL UNIQUE,
+  created_at TIMESTAMPTZ DEFAULT NOW(),
+  updated_at TIMESTAMPTZ DEFAULT NOW(),
+  deleted_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_partnerships_owner ON public.partnerships(owner_id);
+CREATE INDEX IF NOT EXISTS idx_partnerships_partner ON public.partnerships(partner_id);
+
+ALTER TABLE public.partnerships ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "Members can view their partnership"
+  ON public.partnerships FOR SELECT
+  USING (auth.uid() = owner_id OR auth.uid() = partner_id);
+
+CREATE POLICY "Owners can create partnerships"
+  ON public.partnerships FOR INSERT
+  WITH CHECK (auth.uid() = owner_id);
+
+-- True when the current user may use the ledger owned by ledger_owner.
+CREATE OR REPLACE FUNCTION public.is_ledger_member(ledger_owner UUID)
+RETURNS BOOLEAN AS $$
+  SELECT auth.uid() = ledger_owner OR EXISTS (
+    SELECT 1 FROM public.partnerships
+    WHERE owner_id = ledger_owner
+      AND partner_id = auth.uid()
+      AND deleted_at IS NULL
+  );
+$$ LANGUAGE sql STABLE SECURITY DEFINER;
+
+-- Invite codes are looked up across users, so accepting runs as definer.
+CREATE OR REPLACE FUNCTION public.accept_partner_invite(code TEXT)
+RETURNS public.partnerships AS $$
+DECLARE
+  accepted public.partnerships;
+BEGIN
+  UPDATE public.partnerships
+  SET partner_id = auth.uid(), updated_at = NOW()
+  WHERE invite_code = code
+    AND partner_id IS NULL
+    AND owner_id <> auth.uid()
+    AND deleted_at IS NULL
+  RETURNING * INTO accepted;
+
+  IF accepted.id IS NULL THEN
+    RAISE EXCEPTION 'Invite code is invalid or already used';
+  END IF;
+  RETURN accepted;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- Members can't update partnerships directly, since is_ledger_member trusts
+-- owner_id and partner_id. Leaving only ever ends the partnership.
+CREATE OR REPLACE FUNCTION public.leave_partnership(partnership_id TEXT)
+RETURNS VOID AS $$
+BEGIN
+  UPDATE public.partnerships
+  SET deleted_at = NOW(), updated_at = NOW()
+  WHERE id = partnership_id
+    AND (owner_id = auth.uid() OR partner_id = auth.uid())
+    AND deleted_at IS NULL;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- Partners see each other's name in the attribution UI.
+CREATE POLICY "Partners can view each other's profile"
+  ON public.profiles FOR SELECT
+  USING (EXISTS (
+    SELECT 1 FROM public.partnerships
+    WHERE deleted_at IS NULL
+      AND ((owner_id = auth.uid() AND partner_id = profiles.id)
+        OR (partner_id = auth.uid() AND owner_id = profiles.id))
+  ));
+
+-- ============================================
+-- Attribution
+-- ============================================
+ALTER TABLE public.budgets ADD COLUMN IF NOT EXISTS created_by UUID REFERENCES auth.users(id) ON DELETE SET NULL;
+ALTER TABLE public.expenses ADD COLUMN IF NOT EXISTS created_by UUID REFERENCES auth.users(id) ON DELETE SET NULL;
+
+UPDATE public.budgets SET created_by = user_id WHERE created_by IS NULL;
+UPDATE public.expenses SET created_by = user_id WHERE created_by IS NULL;
+
+-- ============================================
+-- Shared ledger access (adds to the owner-only policies)
+-- ============================================
+CREATE POLICY "Partners can view shared budgets"
+  ON public.budgets FOR SELECT
+  USING (public.is_ledger_member(user_id));
+
+CREATE POLICY "Partners can insert shared budgets"
+  ON public.budgets FOR INSERT
+  WITH CHECK (public.is_ledger_member(user_id) AND created_by = auth.uid());
+
+CREATE POLICY "Partners can update shared budgets"
+  ON public.budgets FOR UPDATE
+  USING (public.is_ledger_member(user_id));
+
+CREATE POLICY "Partners can view shared expenses"
+  ON public.expenses FOR SELECT
+  USING (public.is_ledger_member(user_id));
+
+CREATE POLICY "Partners can insert shared expenses"
+  ON public.expenses FOR INSERT
+  WITH CHECK (public.is_ledger_member(user_id) AND created_by = auth.uid());
+
+CREATE POLICY "Partners can update shared expenses"
+  ON public.expenses FOR UPDATE
+  USING (public.is_ledger_member(user_id));
@@ -0,0 +1,30 @@
+-- Goaldy Attachments - Supabase Migration
+-- Receipt photos and other files attached to expenses live in a private
+-- Storage bucket. Each user's objects sit under a folder named after their
+-- user id: <user_id>/<expense_id>.<attachment_id>.<sha256>.<ext>
+
+-- ============================================
+-- Bucket
+-- ============================================
+INSERT INTO storage.buckets (id, name, public)
+VALUES ('attachments', 'attachments', false)
+ON CONFLICT (id) DO NOTHING;
+
+-- ============================================
+-- Access: own folder only
+-- ============================================
+CREATE POLICY "Users can view own attachments"
+  ON storage.objects FOR SELECT
+  USING (bucket_id = 'attachments' AND (storage.foldername(name))[1] = auth.uid()::text);
+
+CREATE POLICY "Users can upload own attachments"
+  ON storage.objects FOR INSERT
+  WITH CHECK (bucket_id = 'attachments' AND (storage.foldername(name))[1] = auth.uid()::text);
+
+CREATE POLICY "Users can update own attachments"
+  ON storage.objects FOR UPDATE
+  USING (bucket_id = 'attachments' AND (storage.foldername(name))[1] = auth.uid()::text);
+
+CREATE POLICY "Users can delete own attachments"
+  ON storage.objects FOR DELETE
+  USING (bucket_id = 'attachments' AND (storage.foldername(name))[1] = auth.uid()::text);
@@ -0,0 +1,144 @@
+-- Goaldy Households - Supabase Migration
+-- A household is a group of accounts that share some of their records.
+-- Unlike partner mode there is no common ledger: every member keeps their
+-- own budgets and expenses, and marks the ones to share with household_id.
+-- Members can read each other's shared rows but only change their own.
+
+-- ============================================
+-- Households and members
+-- ============================================
+CREATE TABLE IF NOT EXISTS public.households (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  owner_id UUID NOT NULL REFERENCES auth.users(id) ON DELETE CASCADE,
+  invite_code TEXT NOT NULL UNIQUE,
+  created_at TIMESTAMPTZ DEFAULT NOW(),
+  updated_at TIMESTAMPTZ DEFAULT NOW(),
+  deleted_at TIMESTAMPTZ
+);
+
+CREATE TABLE IF NOT EXISTS public.household_members (
+  household_id TEXT NOT NULL REFERENCES public.households(id) ON DELETE CASCADE,
+  user_id UUID NOT NULL REFERENCES auth.users(id) ON DELETE CASCADE,
+  role TEXT NOT NULL DEFAULT 'member' CHECK (role IN ('owner', 'member')),
+  joined_at TIMESTAMPTZ DEFAULT NOW(),
+  left_at TIMESTAMPTZ,
+  PRIMARY KEY (household_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_household_members_user ON public.household_members(user_id);
+
+ALTER TABLE public.households ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.household_members ENABLE ROW LEVEL SECURITY;
+
+-- True when the current user is a member of the household.
+CREATE OR REPLACE FUNCTION public.is_household_member(household TEXT)
+RETURNS BOOLEAN AS $$
+  SELECT EXISTS (
+    SELECT 1 FROM public.household_members m
+    JOIN public.households h ON h.id = m.household_id
+    WHERE m.household_id = household
+      AND m.user_id = auth.uid()
+      AND m.left_at IS NULL
+      AND h.deleted_at IS NULL
+  );
+$$ LANGUAGE sql STABLE SECURITY DEFINER;
+
+-- True when the current user shares a household with other_user.
+CREATE OR REPLACE FUNCTION public.shares_household_with(other_user UUID)
+RETURNS BOOLEAN AS $$
+  SELECT EXISTS (
+    SELECT 1 FROM public.household_members mine
+    JOIN public.household_members theirs ON theirs.household_id = mine.household_id
+    JOIN public.households h ON h.id = mine.household_id
+    WHERE mine.user_id = auth.uid()
+      AND theirs.user_id = other_user
+      AND mine.left_at IS NULL
+      AND theirs.left_at IS NULL
+      AND h.deleted_at IS NULL
+  );
+$$ LANGUAGE sql STABLE SECURITY DEFINER;
+
+CREATE POLICY "Members can view their households"
+  ON public.households FOR SELECT
+  USING (owner_id = auth.uid() OR public.is_household_member(id));
+
+CREATE POLICY "Users can create households"
+  ON public.households FOR INSERT
+  WITH CHECK (owner_id = auth.uid());
+
+CREATE POLICY "Owners can update their households"
+  ON public.households FOR UPDATE
+  USING (owner_id = auth.uid());
+
+CREATE POLICY "Members can view fellow members"
+  ON public.household_members FOR SELECT
+  USING (public.is_household_member(household_id) OR user_id = auth.uid());
+
+CREATE POLICY "Owners can add themselves"
+  ON public.household_members FOR INSERT
+  WITH CHECK (
+    user_id = auth.uid() AND EXISTS (
+      SELECT 1 FROM public.households
+      WHERE id = household_id AND owner_id = auth.uid()
+    )
+  );
+
+-- Invite codes are looked up across users, so joining runs as definer.
+CREATE OR REPLACE FUNCTION public.join_household(code TEXT)
+RETURNS public.households AS $$
+DECLARE
+  joined public.households;
+BEGIN
+  SELECT * INTO joined FROM public.households
+  WHERE invite_code = code AND deleted_at IS NULL;
+
+  IF joined.id IS NULL THEN
+    RAISE EXCEPTION 'Invite code is invalid';
+  END IF;
+
+  INSERT INTO public.household_members (household_id, user_id, role)
+  VALUES (joined.id, auth.uid(), 'member')
+  ON CONFLICT (household_id, user_id)
+  DO UPDATE SET left_at = NULL, joined_at = NOW();
+  RETURN joined;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- Memberships are never updated directly, so a member can't change their
+-- household or role. Leaving only sets left_at.
+CREATE OR REPLACE FUNCTION public.leave_household(household TEXT)
+RETURNS VOID AS $$
+BEGIN
+  UPDATE public.household_members
+  SET left_at = NOW()
+  WHERE household_id = household AND user_id = auth.uid() AND left_at IS NULL;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER;
+
+-- Members see each other's name next to shared records.
+CREATE POLICY "Household members can view each other's profile"
+  ON public.profiles FOR SELECT
+  USING (public.shares_household_with(id));
+
+-- Shared expenses show their category, which may be a custom one.
+CREATE POLICY "Household members can view each other's categories"
+  ON public.categories FOR SELECT
+  USING (user_id IS NOT NULL AND public.shares_household_with(user_id));
+
+-- ============================================
+-- Shared records
+-- ============================================
+ALTER TABLE public.budgets ADD COLUMN IF NOT EXISTS household_id TEXT REFERENCES public.households(id) ON DELETE SET NULL;
+ALTER TABLE public.expenses ADD COLUMN IF NOT EXISTS household_id TEXT REFERENCES public.households(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_budgets_household ON public.budgets(household_id);
+CREATE INDEX IF NOT EXISTS idx_expenses_household ON public.expenses(household_id);
+
+CREATE POLICY "Household members can view shared budgets"
+  ON public.budgets FOR SELECT
+  USING (household_id IS NOT NULL AND public.is_household_member(household_id));
+
+CREATE POLICY "Household members can view shared expenses"
+  ON public.expenses FOR SELECT
+  USING (household_id IS NOT NULL AND public.is_household_member(household_id));
@@ -0,0 +1,94 @@
+-- Goaldy End-to-End Sync Encryption - Supabase Migration
+-- With encryption on, the app seals the value columns of a synced row into
+-- encrypted_payload before pushing it and sends NULL in their place. Keys,
+-- ownership and timestamps stay readable so RLS and incremental pulls keep
+-- working. Rows shared with a household are not sealed.
+
+-- ============================================
+-- Sealed rows
+-- ============================================
+ALTER TABLE public.expenses ADD COLUMN IF NOT EXISTS encrypted_payload TEXT;
+ALTER TABLE public.expenses ALTER COLUMN amount DROP NOT NULL;
+ALTER TABLE public.expenses ALTER COLUMN date DROP NOT NULL;
+
+ALTER TABLE public.budgets ADD COLUMN IF NOT EXISTS encrypted_payload TEXT;
+ALTER TABLE public.budgets ALTER COLUMN total_amount DROP NOT NULL;
+
+ALTER TABLE public.savings_goals ADD COLUMN IF NOT EXISTS encrypted_payload TEXT;
+ALTER TABLE public.savings_goals ALTER COLUMN name DROP NOT NULL;
+ALTER TABLE public.savings_goals ALTER COLUMN target_amount DROP NOT NULL;
+ALTER TABLE public.savings_goals ALTER COLUMN target_date DROP NOT NULL;
+ALTER TABLE public.savings_goals ALTER COLUMN monthly_contribution DROP NOT NULL;
+
+ALTER TABLE public.savings_contributions ADD COLUMN IF NOT EXISTS encrypted_payload TEXT;
+ALTER TABLE public.savings_contributions ALTER COLUMN amount DROP NOT NULL;
+
+ALTER TABLE public.habit_goals ADD COLUMN IF NOT EXISTS encrypted_payload TEXT;
+ALTER TABLE public.habit_goals ALTER COLUMN name DROP NOT NULL;
+ALTER TABLE public.habit_goals ALTER COLUMN rule_type DROP NOT NULL;
+ALTER TABLE public.habit_goals ALTER COLUMN rule_value DROP NOT NULL;
+ALTER TABLE public.habit_goals ALTER COLUMN start_date DROP NOT NULL;
+
+ALTER TABLE public.habit_tracking ADD COLUMN IF NOT EXISTS encrypted_payload TEXT;
+ALTER TABLE public.habit_tracking ALTER COLUMN spent_amount DROP NOT NULL;
+ALTER TABLE public.habit_tracking ALTER COLUMN target_amount DROP NOT NULL;
+
+-- ============================================
+-- Sync keys
+-- ============================================
+-- The public half of each account's sync key, and the secret half wrapped
+-- with the user's recovery code. The server can read neither the code nor
+-- the key.
+CREATE TABLE IF NOT EXISTS public.sync_keys (
+  user_id UUID PRIMARY KEY REFERENCES auth.users(id) ON DELETE CASCADE,
+  recipient TEXT NOT NULL,
+  wrapped_key TEXT NOT NULL,
+  created_at TIMESTAMPTZ DEFAULT NOW(),
+  updated_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+ALTER TABLE public.sync_keys ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "Users can view own sync key"
+  ON public.sync_keys FOR SELECT
+  USING (auth.uid() = user_id);
+
+CREATE POLICY "Users can insert own sync key"
+  ON public.sync_keys FOR INSERT
+  WITH CHECK (auth.uid() = user_id);
+
+CREATE POLICY "Users can update own sync key"
+  ON public.sync_keys FOR UPDATE
+  USING (auth.uid() = user_id);
+
+-- A new device asks for the sync key with a public key of its own; a device
+-- that has the key answers by encrypting it to that public key.
+CREATE TABLE IF NOT EXISTS public.sync_key_requests (
+  id TEXT PRIMARY KEY,
+  user_id UUID NOT NULL REFERENCES auth.users(id) ON DELETE CASCADE,
+  device_name TEXT NOT NULL,
+  device_recipient TEXT NOT NULL,
+  wrapped_key TEXT,
+  created_at TIMESTAMPTZ DEFAULT NOW(),
+  approved_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_sync_key_requests_user ON public.sync_key_requests(user_id);
+
+ALTER TABLE public.sync_key_requests ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "Users can view own sync key requests"
+  ON public.sync_key_requests FOR SELECT
+  USING (auth.uid() = user_id);
+
+CREATE POLICY "Users can insert own sync key requests"
+  ON public.sync_key_requests FOR INSERT
+  WITH CHECK (auth.uid() = user_id);
+
+CREATE POLICY "Users can update own sync key requests"
+  ON public.sync_key_requests FOR UPDATE
+  USING (auth.uid() = user_id);
+
+CREATE POLICY "Users can delete own sync key requests"
+  ON public.sync_key_requests FOR DELETE
+  USING (auth.uid() = user_id);
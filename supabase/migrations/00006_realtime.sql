@@ -0,0 +1,15 @@
+-- Goaldy Realtime Sync - Supabase Migration
+-- The apps subscribe to changes of their synced tables over Realtime and
+-- merge them as they arrive, instead of waiting for the next pull. RLS
+-- still decides which changes a subscriber is sent.
+
+ALTER PUBLICATION supabase_realtime ADD TABLE
+  public.expenses,
+  public.budgets,
+  public.savings_goals,
+  public.savings_contributions,
+  public.habit_goals,
+  public.habit_tracking,
+  public.categories,
+  public.feedback_notes,
+  public.scheduled_notifications;